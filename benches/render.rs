@@ -0,0 +1,230 @@
+//! Benchmarks of rendering a page and drawing it, run with `cargo bench`.
+//!
+//! The pages are generated from a fixed list of words, so the benchmarks don't need the network
+//! and every run renders the same documents. A page with `n` sections has three paragraphs with
+//! links, bold and italic text in each section, and a list in every fourth one.
+//!
+//! Baseline on a 1 core x86_64 Linux machine (release profile, rustc 1.76), as the median of
+//! criterion's estimate, to compare optimizations against:
+//!
+//! | benchmark                        | width 40 | width 80 | width 160 |
+//! |----------------------------------|----------|----------|-----------|
+//! | `render_document/small` (5)      | 498µs    | 513µs    | 482µs     |
+//! | `render_document/medium` (40)    | 4.04ms   | 4.08ms   | 3.93ms    |
+//! | `render_document/huge` (400)     | 45.2ms   | 45.7ms   | 46.8ms    |
+//! | `wrap_prepared/huge` (400)       | 29.6ms   | 30.2ms   | 28.8ms    |
+//!
+//! | benchmark                                    | time    |
+//! |----------------------------------------------|---------|
+//! | `page_draw/cached` (medium, 160x100)         | 1.01ms  |
+//! | `select_header/last` (huge, 400 sections)    | 1.95ms  |
+//!
+//! Drawing a frame of a page that's already rendered for the width is what happens on every
+//! key press and tick, rendering the document happens once per width. Resizing only wraps the
+//! words prepared by the first render again, which skips walking the document and hyphenating.
+
+use std::{hint::black_box, sync::Arc};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ratatui::{backend::TestBackend, Terminal};
+use wiki_api::{
+    document::{Document, HeaderKind},
+    languages::Language,
+    page::{Page, PageKind, Section},
+    parser::{Parser, WikipediaParser},
+    Endpoint,
+};
+use wiki_tui::{
+    action::{Action, PageAction},
+    components::{page::PageComponent, Component},
+    config::{Config, Theme},
+    renderer::{
+        default_renderer::{render_document, DefaultRenderer},
+        PageRenderer, RenderOptions,
+    },
+};
+
+const WORDS: [&str; 24] = [
+    "rust",
+    "is",
+    "an",
+    "iron",
+    "oxide",
+    "usually",
+    "reddish-brown",
+    "formed",
+    "by",
+    "the",
+    "reaction",
+    "of",
+    "and",
+    "oxygen",
+    "in",
+    "catalytic",
+    "presence",
+    "water",
+    "or",
+    "air",
+    "moisture",
+    "several",
+    "forms",
+    "distinguishable",
+];
+
+const SIZES: [(&str, usize); 3] = [("small", 5), ("medium", 40), ("huge", 400)];
+const WIDTHS: [u16; 3] = [40, 80, 160];
+
+/// Returns a sentence of `len` words starting at the `seed`th word of the list, with a link every
+/// seventh word and bold and italic text in between
+fn sentence(seed: usize, len: usize) -> String {
+    let mut sentence = String::new();
+    for idx in 0..len {
+        let word = WORDS[(seed + idx * 5) % WORDS.len()];
+        let word = match (seed + idx) % 7 {
+            0 => format!(r#"<a rel="mw:WikiLink" href="./{word}" title="{word}">{word}</a>"#),
+            3 => format!("<b>{word}</b>"),
+            5 => format!("<i>{word}</i>"),
+            _ => word.to_string(),
+        };
+        if idx > 0 {
+            sentence.push(' ');
+        }
+        sentence.push_str(&word);
+    }
+    sentence.push('.');
+    sentence
+}
+
+fn page_html(sections: usize) -> String {
+    let mut html = String::new();
+    for section in 0..sections {
+        html.push_str(&format!(r#"<section data-mw-section-id="{section}">"#));
+        if section > 0 {
+            html.push_str(&format!(
+                r#"<h2 id="Section_{section}">Section {section}</h2>"#
+            ));
+        }
+        for paragraph in 0..3 {
+            let seed = section * 3 + paragraph;
+            let sentences: Vec<String> = (0..4)
+                .map(|idx| sentence(seed + idx, 12 + (seed + idx) % 9))
+                .collect();
+            html.push_str(&format!("<p>{}</p>", sentences.join(" ")));
+        }
+        if section % 4 == 3 {
+            html.push_str("<ul>");
+            for item in 0..5 {
+                html.push_str(&format!("<li>{}</li>", sentence(section + item, 8)));
+            }
+            html.push_str("</ul>");
+        }
+        html.push_str("</section>");
+    }
+    html
+}
+
+fn document(sections: usize) -> Document {
+    let nodes = WikipediaParser::parse_document(
+        &page_html(sections),
+        Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+        Language::English,
+    )
+    .nodes();
+    Document { nodes }
+}
+
+fn page(sections: usize) -> PageComponent {
+    let page = Page {
+        title: "Rust".to_string(),
+        pageid: 0,
+        kind: PageKind::Article,
+        content: document(sections),
+        language: Language::English,
+        endpoint: Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+        language_links: None,
+        sections: Some(
+            (1..sections)
+                .map(|section| Section {
+                    index: section,
+                    header_kind: HeaderKind::Sub,
+                    text: format!("Section {section}"),
+                    number: section.to_string(),
+                    anchor: format!("Section_{section}"),
+                })
+                .collect(),
+        ),
+        revision_id: None,
+        redirected_from: None,
+        description: None,
+    };
+    PageComponent::new(
+        page,
+        Arc::new(Config::default()),
+        Arc::new(Theme::default()),
+    )
+}
+
+fn bench_render_document(c: &mut Criterion) {
+    let theme = Theme::default();
+    for (name, sections) in SIZES {
+        let document = document(sections);
+        let mut group = c.benchmark_group(format!("render_document/{name}"));
+        for width in WIDTHS {
+            group.bench_with_input(BenchmarkId::from_parameter(width), &width, |b, width| {
+                b.iter(|| render_document(black_box(&document), &theme, *width, false))
+            });
+        }
+        group.finish();
+    }
+}
+
+/// Wraps the words of a document that's already prepared, like resizing the terminal does
+fn bench_wrap_prepared(c: &mut Criterion) {
+    let renderer = DefaultRenderer::new(Arc::new(Theme::default()), RenderOptions::default());
+    let document = document(400);
+    let prepared = renderer.prepare(&document, 0).unwrap();
+    let mut group = c.benchmark_group("wrap_prepared/huge");
+    for width in WIDTHS {
+        group.bench_with_input(BenchmarkId::from_parameter(width), &width, |b, width| {
+            b.iter(|| prepared.wrap(black_box(&document), *width))
+        });
+    }
+    group.finish();
+}
+
+/// Draws a tall viewport of a page that's already rendered for the width, like every frame after
+/// the first one
+fn bench_page_draw(c: &mut Criterion) {
+    let mut page = page(40);
+    let mut terminal = Terminal::new(TestBackend::new(160, 100)).unwrap();
+    terminal.draw(|f| page.render(f, f.size())).unwrap();
+
+    c.bench_function("page_draw/cached", |b| {
+        b.iter(|| {
+            terminal.draw(|f| page.render(f, f.size())).unwrap();
+        });
+    });
+}
+
+fn bench_select_header(c: &mut Criterion) {
+    let mut page = page(400);
+    let mut terminal = Terminal::new(TestBackend::new(160, 50)).unwrap();
+    terminal.draw(|f| page.render(f, f.size())).unwrap();
+
+    c.bench_function("select_header/last", |b| {
+        b.iter(|| {
+            page.update(Action::Page(PageAction::GoToHeader(
+                "Section_399".to_string(),
+            )))
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_render_document,
+    bench_wrap_prepared,
+    bench_page_draw,
+    bench_select_header
+);
+criterion_main!(benches);
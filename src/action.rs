@@ -1,13 +1,38 @@
 use std::fmt::Debug;
 
+use reqwest::Url;
 use tokio::sync::mpsc;
 use wiki_api::{
     languages::Language,
-    page::{LanguageLink, Link, Page},
-    search::{Search, SearchResult},
+    page::{
+        ArticleUrl, Backlinks, LanguageLink, Link, Page, PageInfo, Revision, Revisions, SectionKind,
+    },
+    search::{Search, SearchResult, Suggestion},
+    Endpoint,
 };
 
-use crate::components::page::Renderer;
+use crate::{
+    bookmarks::Bookmark, config::Homepage, history::VisitedPage, images::PageImage,
+    session::SessionPage, watches::WatchedPage,
+};
+
+/// How important a message is. Errors are shown until they're dismissed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+impl MessageLevel {
+    pub fn title(&self) -> &'static str {
+        match self {
+            MessageLevel::Info => "Information",
+            MessageLevel::Warning => "Warning",
+            MessageLevel::Error => "Error",
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Action {
@@ -19,7 +44,70 @@ pub enum Action {
 
     // View Focus
     ToggleShowLogger,
+    CycleColorMode,
+    CycleTheme,
+    ShowHelp,
+    ShowCommandPalette,
+    ShowMessages,
+    ShowRequestStats,
+    ShowSearchLanguageSelection,
+    ShowSiteSelection,
+    ToggleOffline,
     ShowPageLanguageSelection,
+    ShowHighlights,
+    /// Shows the meanings listed on the current disambiguation page
+    ShowDisambiguation,
+    ShowSearchFilters,
+    ShowVisitedPages,
+    ShowBookmarks,
+    ShowReadingList,
+    ShowWatchedPages,
+    /// Checks the watched pages for new revisions, sent every interval
+    PollWatchedPages,
+    /// WatchedPageChecked(Page, RevisionId), the latest revision of a watched page, `None` when
+    /// the check failed
+    WatchedPageChecked(WatchedPage, Option<usize>),
+    /// ShowExportPrompt(FileName, Width), asks for the file the page is exported to. The width of
+    /// the page is suggested for formats rendering the lines
+    ShowExportPrompt(String, u16),
+    /// ShowPageInfo(Endpoint, Language, Title), shows the information about the page, fetching
+    /// it unless it was fetched before in this session
+    ShowPageInfo(Endpoint, Language, String),
+    /// FetchPageInfo(Endpoint, Title), fetches the information about the page
+    FetchPageInfo(Endpoint, String),
+    /// PageInfoLoaded(Endpoint, Title, Result), the fetched information or the error
+    PageInfoLoaded(Endpoint, String, Result<PageInfo, String>),
+    /// ShowRevisions(Endpoint, Language, Title), lists the revisions of the page
+    ShowRevisions(Endpoint, Language, String),
+    /// FetchRevisions(Endpoint, Title, Continue), fetches the next batch of revisions of the page
+    FetchRevisions(Endpoint, String, Option<String>),
+    /// RevisionsLoaded(Endpoint, Title, Result), the fetched revisions or the error
+    RevisionsLoaded(Endpoint, String, Result<Revisions, String>),
+    /// LoadAssociatedPage(Endpoint, Language, Title), loads the talk page of the page or the page
+    /// a talk page is about
+    LoadAssociatedPage(Endpoint, Language, String),
+    /// ShowBacklinks(Endpoint, Language, Title), lists the articles linking to the page
+    ShowBacklinks(Endpoint, Language, String),
+    /// LoadBacklinks(Endpoint, Title, AllNamespaces), loads the first pages linking to the page,
+    /// the cached ones when they were loaded before
+    LoadBacklinks(Endpoint, String, bool),
+    /// ContinueBacklinks(Endpoint, Title, AllNamespaces), fetches the pages after the cached ones
+    ContinueBacklinks(Endpoint, String, bool),
+    /// BacklinksLoaded(Endpoint, Title, AllNamespaces, Result), the fetched pages or the error.
+    /// The popup receives all pages loaded so far
+    BacklinksLoaded(Endpoint, String, bool, Result<Backlinks, String>),
+    /// Shows the language selection for the search filters
+    ShowFilterLanguageSelection,
+    /// Sets the language of the search filters, sent by the language selection
+    SelectFilterLanguage(Language),
+    /// Changes the language of the searches and of the pages opened by their title for the rest
+    /// of the session
+    SwitchLanguage(Language),
+    /// Searches the site with the index in the configured sites for the rest of the session
+    SwitchSite(usize),
+    /// ShowSiteSearch(Query), asks for the site the query is searched on once, without switching
+    /// the site of the session
+    ShowSiteSearch(String),
 
     /// PopupMessage(Title, Content)
     PopupMessage(String, String),
@@ -27,7 +115,15 @@ pub enum Action {
     PopupError(String),
     /// PopupError(Title, Content, Callback)
     PopupDialog(String, String, Box<ActionPacket>),
+    /// PopupChoice(Title, Content, Choices), each choice is a key, its description and the
+    /// actions sent when pressing the key
+    PopupChoice(String, String, Vec<(char, String, ActionPacket)>),
     PopPopup,
+    /// ShowMessage(Level, Text), shows the message as a toast and adds it to the message log
+    ShowMessage(MessageLevel, String),
+    /// ShowRetryMessage(Error, Retry), shows an error message, the retry is sent when pressing the
+    /// `retry_load` key
+    ShowRetryMessage(String, Box<ActionPacket>),
 
     SwitchContextSearch,
     SwitchContextPage,
@@ -48,39 +144,224 @@ pub enum Action {
     // Mode
     EnterInsert,
     EnterNormal,
-    EnterProcessing,
 
     // Search Bar
     EnterSearchBar,
     ClearSearchBar,
     SubmitSearchBar,
+    /// Submits the search bar and opens the first result right away
+    SubmitLuckySearchBar,
     ExitSearchBar,
+    /// Shows the suggested pages for the query in the search bar
+    ShowSearchSuggestions(String, Vec<Suggestion>),
 
     // Page loading
     LoadSearchResult(SearchResult),
+    /// LoadLuckySearchResult(Result, Query), loads the first result of a search and tells which
+    /// page was opened for the query
+    LoadLuckySearchResult(SearchResult, String),
     LoadLink(Link),
     LoadLangaugeLink(LanguageLink),
+    /// Loads a random article in the language of the search
+    LoadRandomPage,
+    /// LoadPage(Title), loads the article in the language of the search
+    LoadPage(String),
+    /// LoadPageOrSearch(Title), loads the article in the language of the search and searches for
+    /// the title when there is no such article
+    LoadPageOrSearch(String),
+    /// Loads the article of a Wikipedia url and jumps to its section
+    LoadArticleUrl(ArticleUrl),
+    /// Opens the url in the web browser, suspending the tui for browsers running in the terminal
+    OpenInBrowser(String),
+    /// OpenMedia(Endpoint, Title), downloads the file and opens it in the viewer. Files that
+    /// aren't images can only be copied or opened in the browser
+    OpenMedia(Endpoint, String),
+    /// LoadImage(Url), downloads and decodes the image of a figure to draw it into the page
+    LoadImage(Url),
+    /// LoadPageSource(Endpoint, Title), fetches the wikitext of the page
+    LoadPageSource(Endpoint, String),
+    /// LoadRevision(Endpoint, Language, Title, Revision), loads an old revision of the page
+    LoadRevision(Endpoint, Language, String, Revision),
+    /// ReloadPage(Endpoint, Language, Title, OldId), fetches a page of the history again, at the
+    /// old revision if it was one
+    ReloadPage(Endpoint, Language, String, Option<usize>),
+    /// Cancels loading a page and stays on the current page
+    CancelLoading,
+    /// RefreshPage(Endpoint, Language, Title, OldId), fetches the current page again, ignoring the
+    /// cached page
+    RefreshPage(Endpoint, Language, String, Option<usize>),
+    /// LoadFreshPage(Endpoint, Language, Title), fetches the page ignoring the cached page and
+    /// displays it
+    LoadFreshPage(Endpoint, Language, String),
+    /// PrefetchPage(Endpoint, Language, Title), fetches the page in the background so it opens
+    /// right away
+    PrefetchPage(Endpoint, Language, String),
+    /// SetOffline(IsOffline), enters or leaves the offline mode, where only cached pages can be
+    /// read
+    SetOffline(bool),
+    /// Opens a visited page, without fetching it again when it's still loaded
+    OpenVisitedPage(VisitedPage),
+    /// Opens a bookmarked page at the bookmarked section
+    OpenBookmark(Bookmark),
+    /// Opens the page of the last session at its position
+    RestoreSession(SessionPage),
+    /// Opens the homepage on startup, falling back to the search when it cannot be loaded
+    LoadHomepage(Homepage),
 
     Search(SearchAction),
     Page(PageAction),
     PageViewer(PageViewerAction),
 }
 
+impl Action {
+    /// Returns the action of the keybinding with the name in the config, so the command palette
+    /// runs the same action as the key. Keys that only work while something is focused, like
+    /// submitting a prompt, have no action
+    pub fn from_binding(name: &str) -> Option<Action> {
+        let action = match name {
+            "scroll_down" => Action::ScrollDown(1),
+            "scroll_up" => Action::ScrollUp(1),
+            "scroll_to_top" => Action::ScrollToTop,
+            "scroll_to_bottom" => Action::ScrollToBottom,
+            "pop_popup" => Action::PopPopup,
+            "half_down" => Action::ScrollHalfDown,
+            "half_up" => Action::ScrollHalfUp,
+            "unselect_scroll" => Action::UnselectScroll,
+            "quit" => Action::Quit,
+            "enter_search_bar" => Action::EnterSearchBar,
+            "switch_context_search" => Action::SwitchContextSearch,
+            "switch_context_page" => Action::SwitchContextPage,
+            "toggle_search_language_selection" => Action::ShowSearchLanguageSelection,
+            "toggle_site_selection" => Action::ShowSiteSelection,
+            "toggle_offline" => Action::ToggleOffline,
+            "suspend" => Action::Suspend,
+            "toggle_logger" => Action::ToggleShowLogger,
+            "toggle_messages" => Action::ShowMessages,
+            "toggle_color_mode" => Action::CycleColorMode,
+            "cycle_theme" => Action::CycleTheme,
+            "toggle_help" => Action::ShowHelp,
+            "command_palette" => Action::ShowCommandPalette,
+            "load_random_page" => Action::LoadRandomPage,
+            "show_request_stats" => Action::ShowRequestStats,
+
+            "continue_search" => Action::Search(SearchAction::ContinueSearch),
+            "toggle_filters" => Action::ShowSearchFilters,
+
+            "pop_page" => Action::PageViewer(PageViewerAction::PopPage),
+            "history_back" => Action::PageViewer(PageViewerAction::Back),
+            "history_forward" => Action::PageViewer(PageViewerAction::Forward),
+            "toggle_page_language_selection" => Action::ShowPageLanguageSelection,
+            "toggle_highlights" => Action::ShowHighlights,
+            "show_disambiguation" => Action::ShowDisambiguation,
+            "toggle_visited_pages" => Action::ShowVisitedPages,
+            "toggle_bookmarks" => Action::ShowBookmarks,
+            "toggle_reading_list" => Action::ShowReadingList,
+            "toggle_watched_pages" => Action::ShowWatchedPages,
+
+            name => Action::Page(match name {
+                "select_first_link" => PageAction::SelectFirstLink,
+                "select_last_link" => PageAction::SelectLastLink,
+                "select_prev_link" => PageAction::SelectPrevLink,
+                "select_next_link" => PageAction::SelectNextLink,
+                "open_link" => PageAction::OpenLink,
+                "jump_back" => PageAction::JumpBack,
+                "select_reference_link" => PageAction::SelectReferenceLink,
+                "toggle_zen_mode" => PageAction::ToggleZenMode,
+                "toggle_toc" => PageAction::ToggleContents,
+                "add_highlight" => PageAction::AddHighlight,
+                "add_bookmark" => PageAction::AddBookmark,
+                "read_link_later" => PageAction::ReadLater(true),
+                "read_page_later" => PageAction::ReadLater(false),
+                "watch_page" => PageAction::ToggleWatch,
+                "export_page" => PageAction::ExportPage,
+                "dump_render" => PageAction::DumpRender,
+                "copy_url" => PageAction::CopyUrl(false),
+                "copy_section_url" => PageAction::CopyUrl(true),
+                "open_in_browser" => PageAction::OpenInBrowser,
+                "show_page_info" => PageAction::ShowPageInfo,
+                "show_revisions" => PageAction::ShowRevisions,
+                "show_backlinks" => PageAction::ShowBacklinks,
+                "switch_namespace" => PageAction::SwitchNamespace,
+                "refresh_page" => PageAction::Refresh,
+                "view_source" => PageAction::ViewSource,
+                "toggle_source_wrap" => PageAction::ToggleSourceWrap,
+                "visual_mode" => PageAction::StartVisualMode,
+                "toggle_scrollbar" => PageAction::ToggleScrollbar,
+                "move_divider_left" => PageAction::MoveDivider(false),
+                "move_divider_right" => PageAction::MoveDivider(true),
+                "scroll_left" => PageAction::ScrollSideways(false),
+                "scroll_right" => PageAction::ScrollSideways(true),
+                "lookup_word" => PageAction::LookupWord,
+                "search_other_site" => PageAction::SearchOtherSite,
+                "toggle_fold" => PageAction::ToggleFold,
+                "fold_all" => PageAction::FoldAll,
+                "unfold_all" => PageAction::UnfoldAll,
+                "copy_section" => PageAction::CopySection,
+                "go_to_references" => PageAction::GoToSectionKind(SectionKind::References),
+                "go_to_external_links" => PageAction::GoToSectionKind(SectionKind::ExternalLinks),
+                "switch_renderer" => PageAction::NextRenderer,
+                "search" => PageAction::OpenSearch,
+                "next_match" => PageAction::SearchNext,
+                "prev_match" => PageAction::SearchPrev,
+                _ => return None,
+            }),
+        };
+        Some(action)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SearchAction {
     StartSearch(String),
+    /// Starts a search and opens the first result, when the search is unambiguous
+    StartLuckySearch(String),
     FinshSearch(Search),
     ContinueSearch,
+    /// Loading the next search results failed with the given error
+    ContinueSearchFailed(String),
     ClearSearchResults,
     OpenSearchResult,
     ChangeMode(crate::components::search::Mode),
+    /// SetStatus(Status), shows what happens while searching, like a retry of a failed request
+    SetStatus(String),
     ChangeLanguage(Language),
+    /// ChangeSite(Site), switches to the site with the index in the configured sites
+    ChangeSite(usize),
+    /// SearchSite(Site, Query), searches the site with the index in the configured sites once,
+    /// the following searches stay on the current site
+    SearchSite(usize, String),
+    /// Replaces the search filters and searches again when results are shown
+    SetFilters(crate::components::search::SearchFilters),
+    /// Fetches the suggested pages for an incomplete query
+    FetchSuggestions(String),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PageAction {
-    SwitchRenderer(Renderer),
+    /// Switches to the renderer with the given index
+    SwitchRenderer(usize),
+    /// Switches to the next renderer, the first one after the last one
+    NextRenderer,
     ToggleContents,
+    /// Enters or leaves the zen mode, keeping the first visible word at the top
+    ToggleZenMode,
+    /// Shows or hides the scrollbar, keeping the first visible word at the top
+    ToggleScrollbar,
+    /// Limits the width of the content to the columns, `0` removes the limit
+    SetMaxWidth(u16),
+    /// MoveDivider(IsRight), moves the divider between the page and the table of contents by a
+    /// column
+    MoveDivider(bool),
+    /// ScrollSideways(IsRight), scrolls the lines wider than the page, like preformatted text and
+    /// tables, by a few columns
+    ScrollSideways(bool),
+    /// Folds or unfolds the section at the top of the viewport
+    ToggleFold,
+    FoldAll,
+    UnfoldAll,
+
+    /// Starts selecting lines at the selected link or the top of the viewport
+    StartVisualMode,
 
     SelectFirstLink,
     SelectLastLink,
@@ -90,15 +371,128 @@ pub enum PageAction {
 
     SelectPrevLink,
     SelectNextLink,
+    /// Opens the selected link
+    OpenLink,
 
     GoToHeader(String),
+    /// Scrolls to the first line of the introduction after the title, also while the table of
+    /// contents is focused
+    GoToIntroduction,
+    /// Jumps to the section of the kind, like the references, looked up by its localized title
+    GoToSectionKind(SectionKind),
+    /// Jumps to the reference with the anchor, remembering the position to jump back to
+    JumpToReference(String),
+    /// Returns to the position before jumping to a reference
+    JumpBack,
+    /// Selects the next link of the shown reference preview
+    SelectReferenceLink,
+
+    AddHighlight,
+    GoToHighlight(usize),
+    RemoveHighlight(usize),
+
+    /// RestorePosition(Y, Selected), scrolls to the line and selects the link once the page is
+    /// rendered
+    RestorePosition(u16, (usize, usize)),
+
+    /// Bookmarks the page at the section shown in the viewport
+    AddBookmark,
+    /// ReadLater(IsLink), puts the selected link or the page on the reading list
+    ReadLater(bool),
+    /// Watches the page for new revisions, or stops watching it
+    ToggleWatch,
+
+    /// Marks the page as a random article in the status bar
+    MarkRandom,
+    /// Marks the page as an old revision, shown in a banner above the page
+    MarkRevision(Revision),
+    /// MarkCached(Fetched), marks the page as read from the cache, fetched at the time in seconds
+    /// since the unix epoch
+    MarkCached(u64),
+    /// Fetches the page again, ignoring the cached page
+    Refresh,
+
+    /// Asks for the file the page is exported to
+    ExportPage,
+    /// ExportPageTo(Path, Width), exports the page to the file, as markdown, as plain text for
+    /// `.txt` files or as the lines of the given width with ansi escape codes for `.ans` files
+    ExportPageTo(std::path::PathBuf, u16),
+    /// Writes the rendered lines of the page as json to the data directory, for bug reports
+    DumpRender,
+
+    /// Searches the site for the words of the selected link
+    LookupWord,
+    /// Searches another site for the words of the selected link, or for the selected section in
+    /// the table of contents
+    SearchOtherSite,
+    /// Copies the text of the section at the top of the viewport to the clipboard
+    CopySection,
+    /// Copies the url of the page to the clipboard. With `true` the url points to the section
+    /// shown in the viewport
+    CopyUrl(bool),
+    /// Copies the url of a media file to the clipboard
+    CopyMediaUrl(String),
+    /// Opens the page in the web browser at the section shown in the viewport
+    OpenInBrowser,
+    /// Shows the information about the page, like its last edit and its categories
+    ShowPageInfo,
+
+    /// Lists the revisions of the page
+    ShowRevisions,
+    /// Lists the articles linking to the page
+    ShowBacklinks,
+    /// Switches between the page and its talk page
+    SwitchNamespace,
+
+    /// Switches between the rendered page and its wikitext source
+    ViewSource,
+    /// SourceLoaded(Title, Result), the fetched wikitext of the page or the error
+    SourceLoaded(String, Result<String, String>),
+    /// Toggles wrapping the long lines of the wikitext source
+    ToggleSourceWrap,
+
+    /// Opens the prompt of the in-page search
+    OpenSearch,
+    /// Jumps to the next match of the in-page search
+    SearchNext,
+    /// Jumps to the previous match of the in-page search
+    SearchPrev,
+
+    /// Displays a notice in the status bar for a few seconds
+    ShowNotice(String),
+
+    /// Renders the next chunk of a page that's too large to be rendered at once
+    RenderChunk,
+
+    /// Prefetches the pages of the visible links and of the link that stayed selected
+    Prefetch,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PageViewerAction {
     DisplayPage(Page),
+    /// Displays a page that was fetched again after being dropped from the history
+    RestorePage(Page),
     PopPage,
+    Back,
+    Forward,
+    RemoveVisitedPage(VisitedPage),
+    /// ReadingListChanged(Len), the number of pages on the reading list after it was changed
+    ReadingListChanged(usize),
+    /// Replaces the current page with the page fetched again, keeping the position
+    RefreshPage(Page),
+    /// OfflineChanged(IsOffline), whether the offline mode is shown in the status bar
+    OfflineChanged(bool),
+    /// TocWidthChanged(Width), the width of the table of contents in thousandths of the page,
+    /// kept for the other pages of the session
+    TocWidthChanged(u16),
+    /// StartLoading(What), shows that a page is loading, like "'Title'"
+    StartLoading(String),
+    /// SetLoadingStatus(Status), shows what happens while loading, like a retry of a failed request
+    SetLoadingStatus(String),
     ExitLoading,
+    /// ImageLoaded(Url, Image), the decoded image of a figure or why it couldn't be loaded
+    ImageLoaded(Url, Result<PageImage, String>),
 }
 
 pub enum ActionResult {
@@ -149,6 +543,15 @@ impl ActionPacket {
         self.actions.push(action);
     }
 
+    /// Adds the actions of the other packet after the actions of this one
+    pub fn append(&mut self, other: ActionPacket) {
+        self.actions.extend(other.actions);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
     pub fn send(self, action_tx: &mpsc::UnboundedSender<Action>) {
         for action in self.actions {
             action_tx.send(action).unwrap();
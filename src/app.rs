@@ -1,33 +1,74 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use anyhow::Result;
-use crossterm::event::{KeyCode, KeyEvent};
+use anyhow::{Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
 use ratatui::{
     prelude::{Constraint, Direction, Layout, Rect},
     style::Style,
     widgets::Block,
 };
-use tracing::warn;
+use tracing::{debug, info, warn};
 
-use tokio::sync::mpsc;
+use reqwest::Url;
+use tokio::{sync::mpsc, time::MissedTickBehavior};
+use wiki_api::{
+    languages::Language,
+    page::{
+        backlinks, media_file, page_info, request_file, revisions, Backlinks, PageInfo,
+        PageNotFound,
+    },
+    rate_limit::{with_priority, Priority},
+    search::Namespace,
+    Endpoint,
+};
 
 use crate::{
-    action::{Action, ActionPacket, ActionResult},
+    action::{
+        Action, ActionPacket, ActionResult, MessageLevel, PageAction, PageViewerAction,
+        SearchAction,
+    },
+    bookmarks::Bookmarks,
+    browser::Opener,
     components::{
+        backlinks_popup::BacklinksComponent,
+        bookmarks_popup::BookmarksComponent,
+        command_palette::CommandPaletteComponent,
+        export_popup::ExportComponent,
+        help_popup::HelpComponent,
         logger::LoggerComponent,
         message_popup::MessagePopupComponent,
+        messages_popup::MessagesComponent,
+        page_info_popup::PageInfoComponent,
         page_viewer::PageViewer,
+        reading_list_popup::ReadingListComponent,
+        request_stats_popup::RequestStatsComponent,
+        revisions_popup::RevisionsComponent,
         search::SearchComponent,
         search_bar::{SearchBarComponent, SEARCH_BAR_HEIGTH},
         search_language_popup::SearchLanguageSelectionComponent,
-        Component,
+        site_popup::SiteSelectionComponent,
+        toasts::ToastsComponent,
+        watched_pages_popup::WatchedPagesComponent,
+        Component, Keymap,
     },
-    config::{Config, Theme, ZenModeComponents},
+    config::{Config, Theme, ThemeState, ZenModeComponents},
     has_modifier,
+    images::PageImage,
+    logging::log_buffer,
+    media::{download, format_progress},
     page_loader::PageLoader,
+    reading_list::ReadingList,
+    session::Session,
     terminal::Frame,
+    ui::ColorMode,
+    watches::{WatchPoller, WatchedPage, Watches},
 };
 
+/// Number of revisions fetched at once for the revisions popup
+const REVISIONS_BATCH_SIZE: usize = 50;
+/// Number of linking pages fetched at once for the backlinks popup
+const BACKLINKS_BATCH_SIZE: usize = 100;
+
 const CONTEXT_SEARCH: u8 = 0;
 const CONTEXT_PAGE: u8 = 1;
 
@@ -37,6 +78,7 @@ pub struct AppComponent {
     page: PageViewer,
     logger: LoggerComponent,
     search_bar: SearchBarComponent,
+    toasts: ToastsComponent,
     page_loader: Option<PageLoader>,
 
     is_logger: bool,
@@ -44,14 +86,94 @@ pub struct AppComponent {
     popups: Vec<Box<dyn Component + Send>>,
     config: Arc<Config>,
     theme: Arc<Theme>,
+    themes: Vec<Arc<Theme>>,
+    color_mode: ColorMode,
 
     context: u8,
     prev_context: u8,
 
     action_tx: Option<mpsc::UnboundedSender<Action>>,
+    /// The information about the pages fetched in this session, by their endpoint and title
+    page_info: HashMap<(Endpoint, String), PageInfo>,
+    /// The pages linking to the pages loaded in this session, by the endpoint and title of the
+    /// linked page and whether all namespaces are included
+    backlinks: HashMap<(Endpoint, String, bool), Backlinks>,
+    /// Decides which watched pages are checked for new revisions
+    watch_poller: WatchPoller,
 }
 
 impl AppComponent {
+    /// Sets the available themes and returns the theme that was active in the last session
+    pub fn set_themes(&mut self, themes: Vec<Theme>) -> Arc<Theme> {
+        self.themes = themes.into_iter().map(Arc::new).collect();
+        if self.themes.is_empty() {
+            self.themes.push(Arc::new(Theme::default()));
+        }
+
+        let active = match ThemeState::open() {
+            Ok(store) => store.get().active.clone(),
+            Err(error) => {
+                warn!("{:?}", error.context("failed loading the active theme"));
+                None
+            }
+        };
+
+        active
+            .and_then(|name| self.themes.iter().find(|theme| theme.name == name))
+            .unwrap_or(&self.themes[0])
+            .clone()
+    }
+
+    fn cycle_theme(&mut self) {
+        if self.themes.len() <= 1 {
+            info!("no other themes to switch to");
+            return;
+        }
+
+        let idx = self
+            .themes
+            .iter()
+            .position(|theme| Arc::ptr_eq(theme, &self.theme))
+            .map(|idx| (idx + 1) % self.themes.len())
+            .unwrap_or_default();
+        let theme = self.themes[idx].clone();
+        info!("switching to the theme '{}'", theme.name);
+
+        self.search.set_theme(theme.clone());
+        self.page.set_theme(theme.clone());
+        self.logger.set_theme(theme.clone());
+        self.search_bar.set_theme(theme.clone());
+        self.toasts.set_theme(theme.clone());
+        for popup in self.popups.iter_mut() {
+            popup.set_theme(theme.clone());
+        }
+
+        self.theme = theme;
+
+        if let Err(error) = self.save_active_theme() {
+            warn!("{:?}", error.context("failed saving the active theme"));
+        }
+    }
+
+    /// Saves the current page and its position, so it's opened again on the next startup
+    pub fn save_session(&self) {
+        if !self.config.ui.restore_session {
+            return;
+        }
+        let session = Session {
+            page: self.page.current_page().map(|page| page.session_page()),
+        };
+        if let Err(error) = session.save() {
+            warn!("{:?}", error.context("failed saving the session"));
+        }
+    }
+
+    fn save_active_theme(&self) -> Result<()> {
+        let mut store = ThemeState::open()?;
+        store.get_mut().active = Some(self.theme.name.clone());
+        store.save()
+    }
+
     fn switch_context(&mut self, context: u8) {
         self.prev_context = context;
         std::mem::swap(&mut self.prev_context, &mut self.context);
@@ -62,6 +184,519 @@ impl AppComponent {
         self.popups.push(Box::new(selection_widget));
     }
 
+    /// The keymaps of the current context and the global actions
+    fn keymaps(&self) -> Vec<Keymap> {
+        let context_keymaps = match self.context {
+            // the focussed log viewer receives the keys instead of the context
+            _ if self.is_logger && self.logger.is_focussed => self.logger.keymaps(),
+            CONTEXT_SEARCH => self.search.keymaps(),
+            CONTEXT_PAGE => self.page.keymaps(),
+            _ => Vec::new(),
+        };
+        context_keymaps
+            .into_iter()
+            .chain(Component::keymaps(self))
+            .collect()
+    }
+
+    /// Shows the keybindings of the global actions and the current context
+    fn show_help(&mut self) {
+        self.popups.push(Box::new(HelpComponent::new(
+            self.keymaps(),
+            self.config.clone(),
+            self.theme.clone(),
+        )));
+    }
+
+    /// Shows the palette for running the actions of the help by their name
+    fn show_command_palette(&mut self) {
+        self.popups.push(Box::new(CommandPaletteComponent::new(
+            self.keymaps(),
+            self.config.clone(),
+            self.theme.clone(),
+        )));
+    }
+
+    fn show_highlights(&mut self) {
+        let highlights_widget = self.page.get_highlights_popup();
+        self.popups.push(Box::new(highlights_widget));
+    }
+
+    fn show_bookmarks(&mut self) -> ActionResult {
+        let store = match Bookmarks::open() {
+            Ok(store) => store,
+            Err(error) => {
+                let error = error.context("Unable to load the bookmarks");
+                warn!("{:?}", error);
+                return Action::PopupError(error.to_string()).into();
+            }
+        };
+        self.popups.push(Box::new(BookmarksComponent::new(
+            store.get().bookmarks(),
+            self.config.clone(),
+            self.theme.clone(),
+        )));
+        ActionResult::consumed()
+    }
+
+    fn cancel_loading(&mut self) -> ActionResult {
+        if !self.page_loader.as_ref().unwrap().cancel_loading() {
+            return ActionResult::consumed();
+        }
+        info!("cancelled loading the page");
+
+        let mut packet = ActionPacket::single(Action::PageViewer(PageViewerAction::ExitLoading))
+            .action(Action::Page(PageAction::ShowNotice(
+                "Cancelled loading the page".to_string(),
+            )));
+        if self.page.current_page().is_none() {
+            packet = packet.action(Action::SwitchContextSearch);
+        }
+        packet.into()
+    }
+
+    fn set_offline(&mut self, is_offline: bool) -> ActionResult {
+        let page_loader = self.page_loader.as_ref().unwrap();
+        if page_loader.is_offline() == is_offline {
+            return ActionResult::consumed();
+        }
+        page_loader.set_offline(is_offline);
+        info!("offline mode: {is_offline}");
+
+        let notice = match is_offline {
+            true => "Offline, only cached pages can be opened",
+            false => "Online again",
+        };
+        ActionPacket::single(Action::PageViewer(PageViewerAction::OfflineChanged(
+            is_offline,
+        )))
+        .action(Action::Page(PageAction::ShowNotice(notice.to_string())))
+        .into()
+    }
+
+    fn show_reading_list(&mut self) -> ActionResult {
+        let store = match ReadingList::open() {
+            Ok(store) => store,
+            Err(error) => {
+                let error = error.context("Unable to load the reading list");
+                warn!("{:?}", error);
+                return Action::PopupError(error.to_string()).into();
+            }
+        };
+        self.popups.push(Box::new(ReadingListComponent::new(
+            store.get().entries(),
+            self.config.clone(),
+            self.theme.clone(),
+        )));
+        ActionResult::consumed()
+    }
+
+    fn show_watched_pages(&mut self) -> ActionResult {
+        let store = match Watches::open() {
+            Ok(store) => store,
+            Err(error) => {
+                let error = error.context("Unable to load the watched pages");
+                warn!("{:?}", error);
+                return Action::PopupError(error.to_string()).into();
+            }
+        };
+        self.popups.push(Box::new(WatchedPagesComponent::new(
+            store.get().pages(),
+            self.config.clone(),
+            self.theme.clone(),
+        )));
+        ActionResult::consumed()
+    }
+
+    /// Sends the action polling the watched pages every interval, the first one right away
+    fn start_watch_timer(&self) {
+        if self.config.api.watch_interval == 0 {
+            return;
+        }
+        let interval = Duration::from_secs(self.config.api.watch_interval * 60);
+        let action_tx = self.action_tx.clone().unwrap();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if action_tx.send(Action::PollWatchedPages).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Fetches the latest revision of the watched pages that are due. Nothing is fetched while
+    /// offline
+    fn poll_watched_pages(&mut self) {
+        if self.page_loader.as_ref().unwrap().is_offline() {
+            debug!("offline, not checking the watched pages");
+            return;
+        }
+        let pages = match Watches::open() {
+            Ok(store) => self.watch_poller.start_round(store.get().pages()),
+            Err(error) => {
+                warn!("{:?}", error.context("failed loading the watched pages"));
+                return;
+            }
+        };
+
+        for page in pages {
+            let action_tx = self.action_tx.clone().unwrap();
+            tokio::spawn(async move {
+                let latest = revisions(page.endpoint.clone(), &page.title, 1, None);
+                let revision_id = match with_priority(Priority::Background, latest).await {
+                    Ok(revisions) => revisions.revisions.first().map(|revision| revision.id),
+                    Err(error) => {
+                        let error = error.context(format!("failed checking '{}'", page.title));
+                        warn!("{:?}", error);
+                        None
+                    }
+                };
+                let _ = action_tx.send(Action::WatchedPageChecked(page, revision_id));
+            });
+        }
+    }
+
+    /// Records the latest revision of a watched page and tells when it was edited
+    fn watched_page_checked(
+        &mut self,
+        page: WatchedPage,
+        revision_id: Option<usize>,
+    ) -> ActionResult {
+        self.watch_poller.finish(&page, revision_id.is_some());
+        let Some(revision_id) = revision_id else {
+            return ActionResult::Ignored;
+        };
+
+        let result = Watches::open().and_then(|mut store| {
+            let is_update = store.get_mut().update_revision(&page, revision_id);
+            store.save()?;
+            Ok(is_update)
+        });
+        match result {
+            Ok(true) => {
+                info!("the watched page '{}' was edited", page.title);
+                self.toasts.push(
+                    MessageLevel::Info,
+                    format!(
+                        "'{}' was edited, the watched pages show the new version",
+                        page.title
+                    ),
+                );
+            }
+            Ok(false) => return ActionResult::Ignored,
+            Err(error) => {
+                warn!("{:?}", error.context("failed saving the watched pages"));
+                return ActionResult::Ignored;
+            }
+        }
+
+        if let Some(popup) = self.popups.last_mut() {
+            popup.update(Action::WatchedPageChecked(page, Some(revision_id)));
+        }
+        ActionResult::consumed()
+    }
+
+    fn show_page_info(&mut self, endpoint: Endpoint, language: Language, title: String) {
+        let info = self
+            .page_info
+            .get(&(endpoint.clone(), title.clone()))
+            .cloned();
+        if info.is_none() {
+            self.fetch_page_info(endpoint.clone(), title.clone());
+        }
+        self.popups.push(Box::new(PageInfoComponent::new(
+            endpoint,
+            language,
+            title,
+            info,
+            self.config.clone(),
+            self.theme.clone(),
+        )));
+    }
+
+    fn fetch_page_info(&self, endpoint: Endpoint, title: String) {
+        let action_tx = self.action_tx.clone().unwrap();
+        tokio::spawn(async move {
+            let result = page_info(endpoint.clone(), &title).await.map_err(|error| {
+                let error = error.context("failed fetching the page information");
+                warn!("{:?}", error);
+                format!("{error:#}")
+            });
+            action_tx
+                .send(Action::PageInfoLoaded(endpoint, title, result))
+                .unwrap();
+        });
+    }
+
+    /// Looks up the file, downloads it and opens it in the viewer. The progress is shown in the
+    /// status bar and every step fails with its own message
+    fn open_media(&self, endpoint: Endpoint, title: String) {
+        let action_tx = self.action_tx.clone().unwrap();
+        let config = self.config.clone();
+        tokio::spawn(async move {
+            let fail = |error: anyhow::Error| {
+                warn!("{:?}", error);
+                action_tx
+                    .send(Action::ShowMessage(
+                        MessageLevel::Error,
+                        format!("{error:#}"),
+                    ))
+                    .unwrap();
+            };
+
+            let file = match media_file(endpoint, &title, config.page.media.max_size).await {
+                Ok(file) => file,
+                Err(error) if error.is::<PageNotFound>() => {
+                    return fail(anyhow::anyhow!("The file '{title}' doesn't exist"))
+                }
+                Err(error) => return fail(error.context(format!("Unable to look up '{title}'"))),
+            };
+
+            if !file.is_image() {
+                let url = file.url.to_string();
+                let mut choices = vec![(
+                    'c',
+                    "Copy the url".to_string(),
+                    Action::Page(PageAction::CopyMediaUrl(url.clone())).into(),
+                )];
+                if config.ui.open_in_browser {
+                    choices.push((
+                        'o',
+                        "Open in the browser".to_string(),
+                        Action::OpenInBrowser(url).into(),
+                    ));
+                }
+                action_tx
+                    .send(Action::PopupChoice(
+                        "Information".to_string(),
+                        format!("'{title}' is a {} file and can't be shown", file.mime),
+                        choices,
+                    ))
+                    .unwrap();
+                return;
+            }
+
+            let mut last_progress = String::new();
+            let path = download(&file, |downloaded, total| {
+                let progress = format_progress(downloaded, total);
+                if progress != last_progress {
+                    let _ = action_tx.send(Action::Page(PageAction::ShowNotice(format!(
+                        "Downloading '{title}' ({progress})"
+                    ))));
+                    last_progress = progress;
+                }
+            })
+            .await;
+            let path = match path {
+                Ok(path) => path,
+                Err(error) => return fail(error.context(format!("Unable to download '{title}'"))),
+            };
+
+            let opener =
+                Opener::viewer(config.page.media.viewer.as_deref(), &path.to_string_lossy());
+            action_tx
+                .send(Action::Page(PageAction::ShowNotice(format!(
+                    "Opening '{title}'"
+                ))))
+                .unwrap();
+            if let Err(error) = opener.run().await {
+                let is_missing = error
+                    .root_cause()
+                    .downcast_ref::<std::io::Error>()
+                    .is_some_and(|error| error.kind() == std::io::ErrorKind::NotFound);
+                match is_missing {
+                    true => fail(anyhow::anyhow!(
+                        "The viewer '{}' was not found",
+                        opener.program
+                    )),
+                    false => fail(error.context(format!("Unable to open '{title}'"))),
+                }
+            }
+        });
+    }
+
+    fn load_image(&self, url: Url) {
+        let action_tx = self.action_tx.clone().unwrap();
+        tokio::spawn(async move {
+            let image = async {
+                let bytes = request_file(url.clone())
+                    .await?
+                    .bytes()
+                    .await
+                    .context("failed receiving the image")?;
+                tokio::task::spawn_blocking(move || PageImage::decode(&bytes)).await?
+            }
+            .await
+            .map_err(|error| {
+                warn!("failed loading the image '{url}': {error:?}");
+                format!("{error:#}")
+            });
+            action_tx
+                .send(Action::PageViewer(PageViewerAction::ImageLoaded(
+                    url, image,
+                )))
+                .unwrap();
+        });
+    }
+
+    fn show_revisions(&mut self, endpoint: Endpoint, language: Language, title: String) {
+        self.fetch_revisions(endpoint.clone(), title.clone(), None);
+        self.popups.push(Box::new(RevisionsComponent::new(
+            endpoint,
+            language,
+            title,
+            self.config.clone(),
+            self.theme.clone(),
+        )));
+    }
+
+    fn fetch_revisions(&self, endpoint: Endpoint, title: String, continue_from: Option<String>) {
+        let action_tx = self.action_tx.clone().unwrap();
+        tokio::spawn(async move {
+            let result = revisions(
+                endpoint.clone(),
+                &title,
+                REVISIONS_BATCH_SIZE,
+                continue_from.as_deref(),
+            )
+            .await
+            .map_err(|error| {
+                let error = error.context("failed fetching the revisions");
+                warn!("{:?}", error);
+                format!("{error:#}")
+            });
+            action_tx
+                .send(Action::RevisionsLoaded(endpoint, title, result))
+                .unwrap();
+        });
+    }
+
+    fn show_backlinks(&mut self, endpoint: Endpoint, language: Language, title: String) {
+        self.popups.push(Box::new(BacklinksComponent::new(
+            endpoint.clone(),
+            language,
+            title.clone(),
+            self.config.clone(),
+            self.theme.clone(),
+        )));
+        self.load_backlinks(endpoint, title, false);
+    }
+
+    /// Passes the cached pages linking to the page to the backlinks popup, or fetches the first
+    /// pages when there are none
+    fn load_backlinks(&mut self, endpoint: Endpoint, title: String, all_namespaces: bool) {
+        let key = (endpoint, title, all_namespaces);
+        match self.backlinks.get(&key).cloned() {
+            Some(backlinks) => {
+                let (endpoint, title, all_namespaces) = key;
+                if let Some(popup) = self.popups.last_mut() {
+                    popup.update(Action::BacklinksLoaded(
+                        endpoint,
+                        title,
+                        all_namespaces,
+                        Ok(backlinks),
+                    ));
+                }
+            }
+            None => self.fetch_backlinks(key, None),
+        }
+    }
+
+    fn continue_backlinks(&mut self, endpoint: Endpoint, title: String, all_namespaces: bool) {
+        let key = (endpoint, title, all_namespaces);
+        let continue_from = self
+            .backlinks
+            .get(&key)
+            .and_then(|backlinks| backlinks.continue_from.clone());
+        match continue_from {
+            Some(continue_from) => self.fetch_backlinks(key, Some(continue_from)),
+            None => self.load_backlinks(key.0, key.1, key.2),
+        }
+    }
+
+    fn fetch_backlinks(&self, key: (Endpoint, String, bool), continue_from: Option<String>) {
+        let action_tx = self.action_tx.clone().unwrap();
+        tokio::spawn(async move {
+            let (endpoint, title, all_namespaces) = key;
+            let namespaces: &[Namespace] = match all_namespaces {
+                true => &[],
+                false => &[Namespace::Main],
+            };
+            let result = backlinks(
+                endpoint.clone(),
+                &title,
+                namespaces,
+                BACKLINKS_BATCH_SIZE,
+                continue_from.as_deref(),
+            )
+            .await
+            .map_err(|error| {
+                let error = error.context("failed fetching the linking pages");
+                warn!("{:?}", error);
+                format!("{error:#}")
+            });
+            action_tx
+                .send(Action::BacklinksLoaded(
+                    endpoint,
+                    title,
+                    all_namespaces,
+                    result,
+                ))
+                .unwrap();
+        });
+    }
+
+    /// Adds the fetched pages to the cached ones and passes all of them to the backlinks popup
+    fn backlinks_loaded(
+        &mut self,
+        endpoint: Endpoint,
+        title: String,
+        all_namespaces: bool,
+        result: Result<Backlinks, String>,
+    ) -> ActionResult {
+        let result = result.map(|batch| {
+            let key = (endpoint.clone(), title.clone(), all_namespaces);
+            match self.backlinks.get_mut(&key) {
+                Some(backlinks) => {
+                    backlinks.links.extend(batch.links);
+                    backlinks.continue_from = batch.continue_from;
+                    backlinks.clone()
+                }
+                None => {
+                    self.backlinks.insert(key, batch.clone());
+                    batch
+                }
+            }
+        });
+
+        match self.popups.last_mut() {
+            Some(popup) => popup.update(Action::BacklinksLoaded(
+                endpoint,
+                title,
+                all_namespaces,
+                result,
+            )),
+            None => ActionResult::consumed(),
+        }
+    }
+
+    /// Shows and focusses the log viewer, the focussed log viewer is hidden
+    fn toggle_logger(&mut self) -> ActionResult {
+        if log_buffer().is_none() {
+            return Action::ShowMessage(
+                MessageLevel::Info,
+                "The log viewer is disabled, enable it with 'logging.log_viewer'".to_string(),
+            )
+            .into();
+        }
+        self.is_logger = !self.is_logger || !self.logger.is_focussed;
+        self.logger.is_focussed = self.is_logger;
+        ActionResult::consumed()
+    }
+
     fn render_search_bar(&mut self, f: &mut Frame<'_>, area: Rect) -> Rect {
         let (search_bar_area, area) = {
             let chunks = Layout::default()
@@ -92,23 +727,54 @@ impl Component for AppComponent {
             .init(action_tx.clone(), config.clone(), theme.clone())?;
         self.search_bar
             .init(action_tx.clone(), config.clone(), theme.clone())?;
+        self.logger
+            .init(action_tx.clone(), config.clone(), theme.clone())?;
+        self.toasts.set_config(config.clone());
+        self.toasts.set_theme(theme.clone());
 
         self.page_loader = Some(PageLoader::new(config.clone(), action_tx.clone()));
 
         action_tx.send(Action::EnterSearchBar).unwrap();
         self.action_tx = Some(action_tx);
 
+        self.color_mode = config.ui.color_mode.resolve();
+        info!("using the color mode '{:?}'", self.color_mode);
+
         self.config = config;
         self.theme = theme;
 
+        self.start_watch_timer();
+
         Ok(())
     }
+
+    fn needs_redraw(&self) -> bool {
+        let is_context_changing = match self.context {
+            CONTEXT_SEARCH => self.search.needs_redraw(),
+            CONTEXT_PAGE => self.page.needs_redraw(),
+            _ => false,
+        };
+        is_context_changing
+            || self.toasts.needs_redraw()
+            || (self.is_logger && self.logger.needs_redraw())
+            || self.popups.last().is_some_and(|popup| popup.needs_redraw())
+    }
+
+    fn keymaps(&self) -> Vec<Keymap> {
+        vec![Keymap::new("Global", self.config.bindings.keymap("global"))]
+    }
+
     fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
         // we need to always handle CTRL-C
         if matches!(key.code, KeyCode::Char('c') if has_modifier!(key, Modifier::CONTROL)) {
             return Action::Quit.into();
         }
 
+        // suspending works everywhere, like in a terminal without raw mode
+        if self.config.bindings.global.suspend.matches_event(key) {
+            return Action::Suspend.into();
+        }
+
         if let Some(ref mut popup) = self.popups.last_mut() {
             let result = popup.handle_key_events(key);
             if result.is_consumed() {
@@ -116,11 +782,19 @@ impl Component for AppComponent {
             }
         }
 
+        // errors are shown until they're dismissed
+        if self.toasts.has_errors() && self.config.bindings.global.pop_popup.matches_event(key) {
+            self.toasts.dismiss();
+            return ActionResult::consumed();
+        }
+
         if self.search_bar.is_focussed {
             return self.search_bar.handle_key_events(key);
         }
 
         let result = match self.context {
+            // the focussed log viewer receives the keys instead of the context
+            _ if self.is_logger && self.logger.is_focussed => self.logger.handle_key_events(key),
             CONTEXT_SEARCH => self.search.handle_key_events(key),
             CONTEXT_PAGE => self.page.handle_key_events(key),
             _ => {
@@ -133,47 +807,33 @@ impl Component for AppComponent {
             return result;
         }
 
-        let global_bindings = &self.config.bindings.global;
-        macro_rules! match_bindings {
-            ($($bind:ident => $action:expr),+) => {
-                $(if global_bindings.$bind.matches_event(key) {
-                    return $action.into();
-                })+
-            };
+        // a failed load is retried from its error
+        if self.config.bindings.global.retry_load.matches_event(key) {
+            if let Some(retry) = self.toasts.take_retry() {
+                return retry.into();
+            }
         }
 
-        match_bindings!(
-            quit => Action::Quit,
-            pop_popup => Action::PopPopup,
-
-            toggle_logger => Action::ToggleShowLogger,
-
-            switch_context_search => Action::SwitchContextSearch,
-            switch_context_page => Action::SwitchContextPage,
-
-            scroll_down => Action::ScrollDown(1),
-            scroll_up => Action::ScrollUp(1),
-
-            scroll_to_top => Action::ScrollToTop,
-            scroll_to_bottom => Action::ScrollToBottom,
-
-            half_up => Action::ScrollHalfUp,
-            half_down => Action::ScrollHalfDown,
-
-            unselect_scroll => Action::UnselectScroll,
-            enter_search_bar => Action::EnterSearchBar,
+        // the global keys run the same actions as the command palette
+        self.config
+            .bindings
+            .keymap("global")
+            .into_iter()
+            .filter(|(.., binding)| binding.matches_event(key))
+            .find_map(|(name, ..)| Action::from_binding(name))
+            .map_or(ActionResult::Ignored, ActionResult::from)
+    }
 
-            toggle_search_language_selection => {
-                self.popups
-                    .push(Box::new(SearchLanguageSelectionComponent::new(
-                        self.config.clone(),
-                        self.theme.clone(),
-                    )));
-                ActionResult::consumed()
-            }
-        );
+    fn handle_mouse_events(&mut self, mouse: MouseEvent) -> ActionResult {
+        // popups and the search bar cover the page, so it doesn't react to the pointer
+        if !self.popups.is_empty() || self.search_bar.is_focussed {
+            return ActionResult::Ignored;
+        }
 
-        ActionResult::Ignored
+        match self.context {
+            CONTEXT_PAGE => self.page.handle_mouse_events(mouse),
+            _ => ActionResult::Ignored,
+        }
     }
 
     fn update(&mut self, action: Action) -> ActionResult {
@@ -183,8 +843,149 @@ impl Component for AppComponent {
                 self.popups.pop();
             }
 
-            Action::ToggleShowLogger => self.is_logger = !self.is_logger,
+            Action::ToggleShowLogger => return self.toggle_logger(),
+            Action::ShowMessage(level, text) => self.toasts.push(level, text),
+            Action::ShowRetryMessage(error, retry) => self.toasts.push_retry(error, *retry),
+            Action::CycleTheme => self.cycle_theme(),
+            Action::CycleColorMode => {
+                self.color_mode = self.color_mode.next();
+                info!("switched to the color mode '{:?}'", self.color_mode);
+            }
             Action::ShowPageLanguageSelection => self.show_page_language(),
+            Action::ShowHighlights => self.show_highlights(),
+            Action::ShowDisambiguation => {
+                if let Some(popup) = self.page.get_disambiguation_popup() {
+                    self.popups.push(Box::new(popup));
+                }
+            }
+            Action::ShowVisitedPages => self
+                .popups
+                .push(Box::new(self.page.get_visited_pages_popup())),
+            Action::ShowBookmarks => return self.show_bookmarks(),
+            Action::ShowReadingList => return self.show_reading_list(),
+            Action::ShowWatchedPages => return self.show_watched_pages(),
+            Action::PollWatchedPages => {
+                self.poll_watched_pages();
+                return ActionResult::Ignored;
+            }
+            Action::WatchedPageChecked(page, revision_id) => {
+                return self.watched_page_checked(page, revision_id)
+            }
+            Action::ShowExportPrompt(file_name, width) => self.popups.push(Box::new(
+                ExportComponent::new(file_name, width, self.config.clone(), self.theme.clone()),
+            )),
+            Action::ShowPageInfo(endpoint, language, title) => {
+                self.show_page_info(endpoint, language, title)
+            }
+            Action::FetchPageInfo(endpoint, title) => self.fetch_page_info(endpoint, title),
+            Action::OpenMedia(endpoint, title) => self.open_media(endpoint, title),
+            Action::LoadImage(url) => self.load_image(url),
+            Action::PageInfoLoaded(ref endpoint, ref title, ref result) => {
+                if let Ok(info) = result {
+                    self.page_info
+                        .insert((endpoint.clone(), title.clone()), info.clone());
+                }
+                if let Some(popup) = self.popups.last_mut() {
+                    return popup.update(action);
+                }
+            }
+            Action::ShowRevisions(endpoint, language, title) => {
+                self.show_revisions(endpoint, language, title)
+            }
+            Action::FetchRevisions(endpoint, title, continue_from) => {
+                self.fetch_revisions(endpoint, title, continue_from)
+            }
+            Action::RevisionsLoaded(..) => {
+                if let Some(popup) = self.popups.last_mut() {
+                    return popup.update(action);
+                }
+            }
+            Action::LoadAssociatedPage(endpoint, language, title) => self
+                .page_loader
+                .as_ref()
+                .unwrap()
+                .load_associated_page(endpoint, language, title),
+            Action::ShowBacklinks(endpoint, language, title) => {
+                self.show_backlinks(endpoint, language, title)
+            }
+            Action::LoadBacklinks(endpoint, title, all_namespaces) => {
+                self.load_backlinks(endpoint, title, all_namespaces)
+            }
+            Action::ContinueBacklinks(endpoint, title, all_namespaces) => {
+                self.continue_backlinks(endpoint, title, all_namespaces)
+            }
+            Action::BacklinksLoaded(endpoint, title, all_namespaces, result) => {
+                return self.backlinks_loaded(endpoint, title, all_namespaces, result)
+            }
+            Action::ShowHelp => self.show_help(),
+            Action::ShowCommandPalette => self.show_command_palette(),
+            Action::ShowSearchLanguageSelection => {
+                self.popups
+                    .push(Box::new(SearchLanguageSelectionComponent::new(
+                        self.config.clone(),
+                        self.theme.clone(),
+                    )))
+            }
+            Action::ShowMessages => self.popups.push(Box::new(MessagesComponent::new(
+                self.toasts.log().iter().cloned().collect(),
+                self.config.clone(),
+                self.theme.clone(),
+            ))),
+            Action::ShowRequestStats => self.popups.push(Box::new(RequestStatsComponent::new(
+                self.config.clone(),
+                self.theme.clone(),
+            ))),
+            Action::ShowSiteSelection => self.popups.push(Box::new(SiteSelectionComponent::new(
+                self.search.site_index(),
+                self.config.clone(),
+                self.theme.clone(),
+            ))),
+            Action::ToggleOffline => {
+                let is_offline = self.page_loader.as_ref().unwrap().is_offline();
+                return Action::SetOffline(!is_offline).into();
+            }
+            Action::ShowSearchFilters => self.popups.push(Box::new(
+                self.search
+                    .get_filters_popup(self.config.clone(), self.theme.clone()),
+            )),
+            Action::ShowFilterLanguageSelection => {
+                self.popups
+                    .push(Box::new(SearchLanguageSelectionComponent::for_filters(
+                        self.config.clone(),
+                        self.theme.clone(),
+                    )))
+            }
+
+            Action::SwitchLanguage(language) => {
+                self.search
+                    .update(Action::Search(SearchAction::ChangeLanguage(language)));
+                info!("switched the language to '{}'", language.code());
+                return Action::Page(PageAction::ShowNotice(format!(
+                    "Switched the language to '{}'",
+                    language.name()
+                )))
+                .into();
+            }
+            Action::SwitchSite(site) => {
+                self.search
+                    .update(Action::Search(SearchAction::ChangeSite(site)));
+                let name = &self.config.api.sites[site].name;
+                info!("switched the site to '{}'", name);
+                return Action::Page(PageAction::ShowNotice(format!(
+                    "Switched the site to '{}'",
+                    name
+                )))
+                .into();
+            }
+
+            Action::ShowSiteSearch(query) => {
+                self.popups.push(Box::new(SiteSelectionComponent::for_query(
+                    query,
+                    self.search.site_index(),
+                    self.config.clone(),
+                    self.theme.clone(),
+                )))
+            }
 
             Action::SwitchContextSearch => self.switch_context(CONTEXT_SEARCH),
             Action::SwitchContextPage => self.switch_context(CONTEXT_PAGE),
@@ -193,21 +994,127 @@ impl Component for AppComponent {
             Action::EnterSearchBar => self.search_bar.is_focussed = true,
             Action::ExitSearchBar => self.search_bar.is_focussed = false,
             Action::ClearSearchBar => self.search_bar.clear(),
+            Action::ShowSearchSuggestions(query, suggestions) => {
+                self.search_bar.show_suggestions(query, suggestions)
+            }
             Action::SubmitSearchBar => {
+                if let Some(action) = self.search_bar.submit_suggestion() {
+                    return ActionPacket::single(Action::ExitSearchBar)
+                        .action(action)
+                        .into();
+                }
+
                 return ActionPacket::default()
                     .action(Action::ExitSearchBar)
                     .action(Action::SwitchContextSearch)
-                    .action(self.search_bar.submit())
-                    .into()
+                    .action(self.search_bar.submit(false))
+                    .into();
+            }
+            Action::SubmitLuckySearchBar => {
+                return ActionPacket::default()
+                    .action(Action::ExitSearchBar)
+                    .action(Action::SwitchContextSearch)
+                    .action(self.search_bar.submit(true))
+                    .into();
             }
 
             Action::LoadSearchResult(title) => {
                 self.page_loader.as_ref().unwrap().load_search_result(title)
             }
+            Action::LoadLuckySearchResult(result, query) => self
+                .page_loader
+                .as_ref()
+                .unwrap()
+                .load_lucky_search_result(result, query),
             Action::LoadLink(link) => self.page_loader.as_ref().unwrap().load_link(link),
             Action::LoadLangaugeLink(link) => {
                 self.page_loader.as_ref().unwrap().load_language_link(link)
             }
+            Action::OpenVisitedPage(visited) => {
+                if self.page.show_visited_page(&visited) {
+                    return Action::SwitchContextPage.into();
+                }
+                self.page_loader.as_ref().unwrap().load_page(
+                    visited.endpoint,
+                    visited.language,
+                    visited.title,
+                )
+            }
+            Action::LoadRandomPage => {
+                let (endpoint, language) = self.search.site();
+                self.page_loader
+                    .as_ref()
+                    .unwrap()
+                    .load_random_page(endpoint, language)
+            }
+            Action::LoadPage(title) => {
+                let (endpoint, language) = self.search.site();
+                self.page_loader
+                    .as_ref()
+                    .unwrap()
+                    .load_page(endpoint, language, title)
+            }
+            Action::LoadPageOrSearch(title) => {
+                let (endpoint, language) = self.search.site();
+                self.page_loader
+                    .as_ref()
+                    .unwrap()
+                    .load_page_or_search(endpoint, language, title)
+            }
+            Action::LoadArticleUrl(url) => self.page_loader.as_ref().unwrap().load_page_at(
+                url.endpoint,
+                url.language,
+                url.title,
+                url.anchor,
+            ),
+            Action::OpenBookmark(bookmark) => self.page_loader.as_ref().unwrap().load_page_at(
+                bookmark.endpoint,
+                bookmark.language,
+                bookmark.title,
+                bookmark.anchor,
+            ),
+            Action::LoadHomepage(homepage) => {
+                let (endpoint, language) = self.search.site();
+                self.page_loader
+                    .as_ref()
+                    .unwrap()
+                    .load_homepage(endpoint, language, homepage)
+            }
+            Action::RestoreSession(page) => {
+                self.page_loader.as_ref().unwrap().restore_session(page)
+            }
+            Action::LoadPageSource(endpoint, title) => self
+                .page_loader
+                .as_ref()
+                .unwrap()
+                .load_page_source(endpoint, title),
+            Action::LoadRevision(endpoint, language, title, revision) => self
+                .page_loader
+                .as_ref()
+                .unwrap()
+                .load_revision(endpoint, language, title, revision),
+            Action::ReloadPage(endpoint, language, title, oldid) => self
+                .page_loader
+                .as_ref()
+                .unwrap()
+                .reload_page(endpoint, language, title, oldid),
+            Action::LoadFreshPage(endpoint, language, title) => self
+                .page_loader
+                .as_ref()
+                .unwrap()
+                .load_fresh_page(endpoint, language, title),
+            Action::RefreshPage(endpoint, language, title, oldid) => self
+                .page_loader
+                .as_ref()
+                .unwrap()
+                .refresh_page(endpoint, language, title, oldid),
+            Action::SetOffline(is_offline) => return self.set_offline(is_offline),
+            Action::CancelLoading => return self.cancel_loading(),
+            Action::PrefetchPage(endpoint, language, title) => self
+                .page_loader
+                .as_ref()
+                .unwrap()
+                .prefetch_page(endpoint, language, title),
 
             Action::PopupMessage(title, content) => self.popups.push(Box::new(
                 MessagePopupComponent::new_raw(title, content, self.theme.clone()),
@@ -215,6 +1122,9 @@ impl Component for AppComponent {
             Action::PopupError(error) => self.popups.push(Box::new(
                 MessagePopupComponent::new_error(error, self.theme.clone()),
             )),
+            Action::PopupChoice(title, content, choices) => self.popups.push(Box::new(
+                MessagePopupComponent::new_choice(title, content, choices, self.theme.clone()),
+            )),
             Action::PopupDialog(title, content, cb) => {
                 self.popups
                     .push(Box::new(MessagePopupComponent::new_confirmation(
@@ -258,6 +1168,7 @@ impl Component for AppComponent {
     }
 
     fn render(&mut self, f: &mut Frame<'_>, mut area: Rect) {
+        let full_area = area;
         f.render_widget(
             Block::default().style(Style::default().bg(self.theme.bg)),
             area,
@@ -269,13 +1180,7 @@ impl Component for AppComponent {
             // always render the searchbar if its focussed
             Some(_) if self.search_bar.is_focussed => area = self.render_search_bar(f, area),
             Some(page) if self.context == CONTEXT_PAGE => {
-                if !page.is_zen_mode()
-                    || self
-                        .config
-                        .page
-                        .zen_mode
-                        .contains(ZenModeComponents::SEARCH_BAR)
-                {
+                if page.is_shown(ZenModeComponents::SEARCH_BAR) {
                     area = self.render_search_bar(f, area);
                 }
             }
@@ -297,8 +1202,50 @@ impl Component for AppComponent {
             _ => warn!("unknown context"),
         }
 
+        self.search_bar.render_suggestions(f);
+
         if let Some(ref mut popup) = self.popups.last_mut() {
             popup.render(f, area);
         }
+
+        self.toasts.render(f, full_area);
+
+        self.color_mode.apply(f.buffer_mut(), self.theme.bg);
+        crate::images::finish_frame(f.buffer_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use crate::{
+        action::{Action, ActionPacket, ActionResult},
+        components::Component,
+    };
+
+    use super::AppComponent;
+
+    #[test]
+    fn test_retry_key() {
+        let mut app = AppComponent::default();
+        let retry = ActionPacket::single(Action::LoadRandomPage);
+        app.update(Action::ShowRetryMessage(
+            "Unable to fetch".to_string(),
+            Box::new(retry.clone()),
+        ));
+
+        // the keys are typed into the focussed search bar, the error can still be retried
+        app.search_bar.is_focussed = true;
+        for key in [
+            KeyEvent::from(KeyCode::Char('r')),
+            KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT),
+        ] {
+            app.handle_key_events(key);
+        }
+        app.search_bar.is_focussed = false;
+
+        let result = app.handle_key_events(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT));
+        assert!(matches!(result, ActionResult::Consumed(packet) if packet == retry));
     }
 }
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use wiki_api::{languages::Language, Endpoint};
+
+use crate::store::Store;
+
+pub const BOOKMARKS_STORE: &str = "bookmarks";
+
+/// A bookmarked page, optionally pointing to one of its sections
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    /// The name shown in the bookmark manager, the title of the page unless it was renamed
+    pub name: String,
+    pub title: String,
+    #[serde(with = "crate::store::language_code")]
+    pub language: Language,
+    pub endpoint: Endpoint,
+    /// Id of the header the page is opened at, `None` for the top of the page
+    pub anchor: Option<String>,
+}
+
+impl Bookmark {
+    pub fn new(
+        title: String,
+        language: Language,
+        endpoint: Endpoint,
+        anchor: Option<String>,
+    ) -> Self {
+        Self {
+            name: title.clone(),
+            title,
+            language,
+            endpoint,
+            anchor,
+        }
+    }
+
+    fn is_same_page(&self, language: Language, title: &str) -> bool {
+        self.language == language && self.title == title
+    }
+}
+
+/// The bookmarks, ordered by the time they were added. There is at most one bookmark per page
+#[derive(Serialize, Deserialize, Default)]
+pub struct Bookmarks {
+    bookmarks: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    pub fn open() -> anyhow::Result<Store<Bookmarks>> {
+        Store::open(BOOKMARKS_STORE)
+    }
+
+    pub fn bookmarks(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    /// Adds the bookmark. A bookmark of the same page is updated instead, keeping its name.
+    /// Returns whether a bookmark was updated
+    pub fn add(&mut self, bookmark: Bookmark) -> bool {
+        match self
+            .bookmarks
+            .iter_mut()
+            .find(|existing| existing.is_same_page(bookmark.language, &bookmark.title))
+        {
+            Some(existing) => {
+                existing.endpoint = bookmark.endpoint;
+                existing.anchor = bookmark.anchor;
+                true
+            }
+            None => {
+                self.bookmarks.push(bookmark);
+                false
+            }
+        }
+    }
+
+    pub fn remove(&mut self, language: Language, title: &str) {
+        self.bookmarks
+            .retain(|bookmark| !bookmark.is_same_page(language, title));
+    }
+
+    pub fn rename(&mut self, language: Language, title: &str, name: String) {
+        if let Some(bookmark) = self
+            .bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.is_same_page(language, title))
+        {
+            bookmark.name = name;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::{languages::Language, Endpoint};
+
+    use super::{Bookmark, Bookmarks};
+
+    #[test]
+    fn test_bookmarks() {
+        let endpoint = Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap();
+        let bookmark = |title: &str, anchor: Option<&str>| {
+            Bookmark::new(
+                title.to_string(),
+                Language::English,
+                endpoint.clone(),
+                anchor.map(|anchor| anchor.to_string()),
+            )
+        };
+
+        let mut bookmarks = Bookmarks::default();
+        assert!(!bookmarks.add(bookmark("Rust", None)));
+        assert!(!bookmarks.add(bookmark("Iron", None)));
+        bookmarks.rename(Language::English, "Rust", "Oxide".to_string());
+
+        // bookmarking a page again updates its bookmark
+        assert!(bookmarks.add(bookmark("Rust", Some("History"))));
+        assert_eq!(bookmarks.bookmarks().len(), 2);
+        assert_eq!(bookmarks.bookmarks()[0].name, "Oxide");
+        assert_eq!(bookmarks.bookmarks()[0].anchor.as_deref(), Some("History"));
+
+        bookmarks.remove(Language::English, "Iron");
+        assert_eq!(bookmarks.bookmarks().len(), 1);
+    }
+}
@@ -0,0 +1,138 @@
+use anyhow::{anyhow, Context, Result};
+use tokio::process::Command;
+
+/// Browsers running in the terminal. The tui has to be suspended while they are open
+const TERMINAL_BROWSERS: [&str; 7] = [
+    "lynx", "w3m", "links", "links2", "elinks", "browsh", "carbonyl",
+];
+
+/// The program opening a url
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opener {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Whether the program runs in the terminal, taking over the screen and the input
+    pub is_terminal: bool,
+}
+
+impl Opener {
+    /// Returns the browser set in the `BROWSER` environment variable, or the opener of the
+    /// platform
+    pub fn new(url: &str) -> Opener {
+        std::env::var("BROWSER")
+            .ok()
+            .and_then(|browser| Opener::from_browser_var(&browser, url))
+            .unwrap_or_else(|| Opener::platform(url))
+    }
+
+    /// Returns the configured viewer of media files, or the opener of the platform
+    pub fn viewer(command: Option<&str>, path: &str) -> Opener {
+        command
+            .and_then(|command| Opener::from_command(command, path))
+            .unwrap_or_else(|| Opener::platform(path))
+    }
+
+    /// Parses the `BROWSER` environment variable, a list of commands separated by `:`, of which
+    /// the first one is used
+    fn from_browser_var(browser: &str, url: &str) -> Option<Opener> {
+        Opener::from_command(browser.split(':').next()?, url)
+    }
+
+    /// Parses a command. A `%s` in the command is replaced by the url, otherwise the url is
+    /// appended
+    fn from_command(command: &str, url: &str) -> Option<Opener> {
+        let mut words = command.split_whitespace();
+        let program = words.next()?.to_string();
+
+        let mut args: Vec<String> = words.map(|word| word.replace("%s", url)).collect();
+        if !command.contains("%s") {
+            args.push(url.to_string());
+        }
+
+        let name = std::path::Path::new(&program)
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        Some(Opener {
+            is_terminal: TERMINAL_BROWSERS.contains(&name),
+            program,
+            args,
+        })
+    }
+
+    fn platform(url: &str) -> Opener {
+        let (program, mut args) = if cfg!(target_os = "macos") {
+            ("open", Vec::new())
+        } else if cfg!(windows) {
+            // the empty string is the title of the window `start` would open
+            (
+                "cmd",
+                vec!["/C".to_string(), "start".to_string(), String::new()],
+            )
+        } else {
+            ("xdg-open", Vec::new())
+        };
+        args.push(url.to_string());
+
+        Opener {
+            program: program.to_string(),
+            args,
+            is_terminal: false,
+        }
+    }
+
+    /// Runs the program and waits until it exits. Terminal browsers inherit the terminal, all
+    /// others run without any input or output
+    pub async fn run(&self) -> Result<()> {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args);
+        if !self.is_terminal {
+            command
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null());
+        }
+
+        let status = command
+            .status()
+            .await
+            .with_context(|| format!("failed starting '{}'", self.program))?;
+        if !status.success() {
+            return Err(anyhow!("'{}' exited with {status}", self.program));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Opener;
+
+    #[test]
+    fn test_browser_var() {
+        let url = "https://en.wikipedia.org/wiki/Rust#History";
+
+        let opener = Opener::from_browser_var("firefox --new-tab:chromium", url).unwrap();
+        assert_eq!(opener.program, "firefox");
+        assert_eq!(opener.args, ["--new-tab", url]);
+        assert!(!opener.is_terminal);
+
+        let opener = Opener::from_browser_var("/usr/bin/w3m -o %s", url).unwrap();
+        assert_eq!(opener.args, ["-o", url]);
+        assert!(opener.is_terminal);
+
+        assert_eq!(Opener::from_browser_var("", url), None);
+    }
+
+    #[test]
+    fn test_viewer() {
+        let path = "/tmp/wiki-tui/Rust.jpg";
+
+        let opener = Opener::viewer(Some("feh --scale-down"), path);
+        assert_eq!(opener.program, "feh");
+        assert_eq!(opener.args, ["--scale-down", path]);
+
+        let opener = Opener::viewer(Some("  "), path);
+        assert_eq!(opener.args.last().map(String::as_str), Some(path));
+    }
+}
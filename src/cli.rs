@@ -2,23 +2,78 @@ use clap::Parser;
 
 use crate::{
     action::{Action, ActionPacket, SearchAction},
-    config::{cache_dir, config_dir, CONFIG_FILE_NAME, THEME_FILE_NAME},
+    config::{cache_dir, config_dir, Homepage, CONFIG_FILE_NAME, THEME_FILE_NAME},
+    print::PrintOptions,
 };
-use wiki_api::languages::Language;
+use wiki_api::{languages::Language, page::ArticleUrl};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
+#[command(group(clap::ArgGroup::new("output").args(["print", "dump_render"])))]
 struct Cli {
-    /// Search for an article
+    /// Open the article with the title or the url of a Wikipedia article. When there is no
+    /// article with the title, it's searched for instead
     #[arg(value_name = "QUERY")]
     search_query: Option<String>,
 
+    /// Search for the query instead of opening the article with the title
+    #[arg(short = 's', long = "search", requires = "search_query")]
+    search: bool,
+
+    /// Open the first result of the search query right away, unless the query is ambiguous
+    #[arg(long = "lucky", requires = "search_query", conflicts_with = "search")]
+    lucky: bool,
+
+    /// Open a random article
+    #[arg(long = "random", conflicts_with = "search_query")]
+    random: bool,
+
     /// Override the configured search language of wikipedia. The value can be either the language
     /// code, the name of the language in english or the native language name
     #[arg(value_name = "LANGUAGE", short = 'l', long = "language")]
     language: Option<Language>,
 
+    /// Print the article with the title (or url) to stdout and exit, without starting the tui
+    #[arg(value_name = "ARTICLE", long = "print", conflicts_with_all = ["search_query", "random"])]
+    print: Option<String>,
+
+    /// Write the rendered lines of the article with the title (or url) as json to the data
+    /// directory and exit, for attaching them to bug reports. The path of the file is printed
+    #[arg(
+        value_name = "ARTICLE",
+        long = "dump-render",
+        conflicts_with_all = ["search_query", "random", "print"]
+    )]
+    dump_render: Option<String>,
+
+    /// Width of the printed or dumped article, defaults to the width of the terminal or 80 when
+    /// printing to a pipe or file
+    #[arg(value_name = "WIDTH", long = "width", requires = "output")]
+    width: Option<u16>,
+
+    /// Print the article with colors and styles
+    #[arg(long = "color", requires = "print")]
+    color: bool,
+
+    /// Open the homepage for this run instead of the configured one: the title of an article,
+    /// "random", "featured" for today's featured article or "search"
+    #[arg(
+        value_name = "HOME",
+        long = "home",
+        conflicts_with_all = ["search_query", "random", "print", "dump_render"]
+    )]
+    home: Option<String>,
+
+    /// Don't reopen the page of the last session, even when `ui.restore_session` is enabled
+    #[arg(long = "no-restore")]
+    no_restore: bool,
+
+    /// Send the requests through this HTTP or SOCKS5 proxy instead of the configured one, like
+    /// "http://proxy.example.com:8080" or "socks5://localhost:1080"
+    #[arg(value_name = "URL", long = "proxy")]
+    proxy: Option<String>,
+
     /// Override the configured logging level
     #[arg(value_name = "LEVEL", long = "level")]
     level: Option<tracing::level_filters::LevelFilter>,
@@ -40,9 +95,24 @@ struct Cli {
     load_debug_page: Option<std::path::PathBuf>,
 }
 
+/// Returns whether the query is meant as a url rather than the title of an article
+pub fn is_url(query: &str) -> bool {
+    query.starts_with("http://") || query.starts_with("https://")
+}
+
 pub struct CliResults {
     pub actions: Option<ActionPacket>,
     pub log_level: Option<tracing::level_filters::LevelFilter>,
+    /// Print an article instead of starting the tui
+    pub print: Option<PrintOptions>,
+    /// Whether the arguments don't open anything, so the session or the homepage is opened
+    pub starts_empty: bool,
+    /// Skip restoring the page of the last session
+    pub no_restore: bool,
+    /// The homepage overriding the configured one
+    pub homepage: Option<Homepage>,
+    /// The proxy overriding the configured one
+    pub proxy: Option<String>,
 }
 
 pub fn match_cli() -> CliResults {
@@ -52,22 +122,67 @@ pub fn match_cli() -> CliResults {
     let mut results = CliResults {
         actions: None,
         log_level: None,
+        print: None,
+        starts_empty: cli.search_query.is_none() && !cli.random,
+        no_restore: cli.no_restore,
+        homepage: cli.home.map(Homepage::from),
+        proxy: cli.proxy.clone(),
     };
 
     let mut packet = ActionPacket::default();
 
     if let Some(language) = cli.language {
-        packet.add_action(Action::Search(SearchAction::ChangeLanguage(language)));
+        packet.add_action(Action::SwitchLanguage(language));
     }
 
     if let Some(level) = cli.level {
         results.log_level = Some(level);
     }
 
+    if let Some(article) = cli.print {
+        results.print = Some(PrintOptions {
+            article,
+            language: cli.language,
+            width: cli.width,
+            color: cli.color,
+            dump: false,
+            proxy: cli.proxy,
+        });
+    } else if let Some(article) = cli.dump_render {
+        results.print = Some(PrintOptions {
+            article,
+            language: cli.language,
+            width: cli.width,
+            color: false,
+            dump: true,
+            proxy: cli.proxy,
+        });
+    }
+
     if let Some(search_query) = cli.search_query {
         packet.add_action(Action::ExitSearchBar);
-        packet.add_action(Action::SwitchContextSearch);
-        packet.add_action(Action::Search(SearchAction::StartSearch(search_query)));
+        if is_url(&search_query) {
+            match ArticleUrl::parse(&search_query) {
+                Ok(url) => packet.add_action(Action::LoadArticleUrl(url)),
+                Err(error) => {
+                    eprintln!("error: unable to open the url '{search_query}': {error:#}");
+                    std::process::exit(libc::EXIT_FAILURE)
+                }
+            }
+        } else if cli.search || cli.lucky {
+            packet.add_action(Action::SwitchContextSearch);
+            packet.add_action(Action::Search(match cli.lucky {
+                true => SearchAction::StartLuckySearch(search_query),
+                false => SearchAction::StartSearch(search_query),
+            }));
+        } else {
+            packet.add_action(Action::LoadPageOrSearch(search_query));
+        }
+    }
+
+    if cli.random {
+        packet.add_action(Action::ExitSearchBar);
+        packet.add_action(Action::LoadRandomPage);
     }
 
     if cli.print_config_path {
@@ -110,6 +225,7 @@ pub fn match_cli() -> CliResults {
             packet.add_action(Action::PageViewer(
                 crate::action::PageViewerAction::DisplayPage(page),
             ));
+            results.starts_empty = false;
         }
     }
     results.actions = Some(packet);
@@ -0,0 +1,207 @@
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT},
+    Certificate, Client, NoProxy, Proxy,
+};
+use wiki_api::Endpoint;
+
+use crate::config::{ApiConfig, VERSION_PLACEHOLDER};
+
+/// Environment variables naming the proxy of https requests, in the order reqwest reads them
+const PROXY_VARIABLES: [&str; 4] = ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"];
+
+/// Environment variables naming the hosts that are requested without the proxy
+const NO_PROXY_VARIABLES: [&str; 2] = ["NO_PROXY", "no_proxy"];
+
+/// Returns the proxy the requests to the site are sent through, the configured proxy or the one of
+/// the environment variables. Hosts excluded by the `NO_PROXY` environment variable are requested
+/// directly
+pub fn proxy_url(config: &ApiConfig, endpoint: &Endpoint) -> Option<String> {
+    let no_proxy = NO_PROXY_VARIABLES
+        .iter()
+        .find_map(|variable| std::env::var(variable).ok())
+        .unwrap_or_default();
+    if endpoint
+        .host_str()
+        .is_some_and(|host| is_no_proxy(&no_proxy, host))
+    {
+        return None;
+    }
+    config.proxy.clone().or_else(|| {
+        PROXY_VARIABLES
+            .iter()
+            .find_map(|variable| std::env::var(variable).ok())
+            .filter(|proxy| !proxy.is_empty())
+    })
+}
+
+/// Returns whether the host is excluded from the proxy by the comma separated list of `NO_PROXY`.
+/// Like reqwest, `*` excludes every host and a domain excludes itself and its subdomains
+fn is_no_proxy(no_proxy: &str, host: &str) -> bool {
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim().to_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            let domain = entry.trim_start_matches('.');
+            entry == "*" || host == domain || host.ends_with(&format!(".{domain}"))
+        })
+}
+
+/// Returns the configured user agent with the version of wiki-tui
+pub fn user_agent(config: &ApiConfig) -> String {
+    config
+        .user_agent
+        .replace(VERSION_PLACEHOLDER, env!("CARGO_PKG_VERSION"))
+}
+
+/// Returns the configured headers. Names and values with invalid characters are rejected, as well
+/// as a user agent, which has its own option
+fn header_map(config: &ApiConfig) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in config.headers.iter() {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| anyhow!("invalid name of the header '{name}' in 'api.headers'"))?;
+        if name == USER_AGENT {
+            bail!("the user agent is set with 'api.user_agent' instead of 'api.headers'");
+        }
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| anyhow!("invalid value of the header '{name}' in 'api.headers'"))?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+/// Builds the client sending the requests to the sites, rejecting an invalid rate limit. Without a
+/// configured proxy, the proxy environment variables are used
+pub fn build_client(config: &ApiConfig) -> Result<Client> {
+    if !config.rate_limit.is_finite() || config.rate_limit < 0.0 {
+        bail!(
+            "invalid rate limit '{}' in 'api.rate_limit', it has to be a positive number or 0",
+            config.rate_limit
+        );
+    }
+    let user_agent = user_agent(config);
+    if user_agent.trim().is_empty() {
+        bail!("the user agent in 'api.user_agent' is empty");
+    }
+    let user_agent = HeaderValue::from_str(&user_agent)
+        .map_err(|_| anyhow!("invalid characters in the user agent '{user_agent}'"))?;
+    let mut builder = Client::builder()
+        .user_agent(user_agent)
+        .default_headers(header_map(config)?);
+
+    if let Some(ref proxy) = config.proxy {
+        let proxy = Proxy::all(proxy)
+            .with_context(|| format!("invalid proxy url '{proxy}'"))?
+            .no_proxy(NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(ref path) = config.ca_certificate {
+        let pem = std::fs::read(path)
+            .with_context(|| format!("failed reading the CA certificate '{}'", path.display()))?;
+        let certificate = Certificate::from_pem(&pem)
+            .with_context(|| format!("'{}' is not a PEM encoded certificate", path.display()))?;
+        builder = builder.add_root_certificate(certificate);
+    }
+
+    builder.build().context("failed building the http client")
+}
+
+/// Builds the client with the proxy overriding the configured one and sends all requests with it,
+/// adding the configured query parameters to the requests to the APIs
+pub fn init_client(config: &mut ApiConfig, proxy: Option<String>) -> Result<()> {
+    if proxy.is_some() {
+        config.proxy = proxy;
+    }
+    if config.query_params.iter().any(|(name, _)| name.is_empty()) {
+        bail!("empty name of a query parameter in 'api.query_params'");
+    }
+    wiki_api::set_client(build_client(config)?);
+    wiki_api::set_query_params(config.query_params.clone());
+    if config.rate_limit > 0.0 {
+        wiki_api::set_rate_limit(config.rate_limit, config.rate_burst);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::config::Config;
+
+    use super::{build_client, is_no_proxy, user_agent};
+
+    #[test]
+    fn test_build_client() {
+        let mut config = Config::default().api;
+        assert!(build_client(&config).is_ok());
+
+        config.proxy = Some("socks5://localhost:1080".to_string());
+        assert!(build_client(&config).is_ok());
+
+        config.proxy = Some("not a proxy".to_string());
+        let error = build_client(&config).unwrap_err();
+        assert!(error.to_string().contains("'not a proxy'"));
+
+        config.proxy = None;
+        config.ca_certificate = Some(PathBuf::from("/nonexistent/ca.pem"));
+        let error = build_client(&config).unwrap_err();
+        assert!(error.to_string().contains("/nonexistent/ca.pem"));
+    }
+
+    #[test]
+    fn test_no_proxy() {
+        assert!(!is_no_proxy("", "en.wikipedia.org"));
+        assert!(is_no_proxy("*", "en.wikipedia.org"));
+        assert!(is_no_proxy("localhost, wikipedia.org", "en.wikipedia.org"));
+        assert!(is_no_proxy(".Wikipedia.org", "wikipedia.org"));
+        assert!(is_no_proxy("10.0.0.1", "10.0.0.1"));
+        assert!(!is_no_proxy("pedia.org", "en.wikipedia.org"));
+        assert!(!is_no_proxy("wiktionary.org", "en.wikipedia.org"));
+    }
+
+    #[test]
+    fn test_request_options() {
+        let mut config = Config::default().api;
+        assert_eq!(
+            user_agent(&config),
+            format!(
+                "wiki-tui/{} (https://github.com/builditluc/wiki-tui)",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+
+        config.headers = vec![("X-Api-Token".to_string(), "secret".to_string())];
+        assert!(build_client(&config).is_ok());
+
+        config.headers = vec![("X Api Token".to_string(), "secret".to_string())];
+        let error = build_client(&config).unwrap_err();
+        assert!(error.to_string().contains("'X Api Token'"));
+
+        config.headers = vec![("X-Api-Token".to_string(), "line\nbreak".to_string())];
+        let error = build_client(&config).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("value of the header 'x-api-token'"));
+
+        config.headers = vec![("user-agent".to_string(), "curl".to_string())];
+        let error = build_client(&config).unwrap_err();
+        assert!(error.to_string().contains("'api.user_agent'"));
+
+        config.headers = Vec::new();
+        config.user_agent = "wiki-tui/{version}\r\n".to_string();
+        assert!(build_client(&config).is_err());
+        config.user_agent = " ".to_string();
+        assert!(build_client(&config).is_err());
+
+        config = Config::default().api;
+        config.rate_limit = 0.0;
+        assert!(build_client(&config).is_ok());
+        config.rate_limit = -1.0;
+        let error = build_client(&config).unwrap_err();
+        assert!(error.to_string().contains("'api.rate_limit'"));
+    }
+}
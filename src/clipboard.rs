@@ -0,0 +1,82 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use tracing::debug;
+
+/// The programs setting the system clipboard, tried in order. Each reads the text from stdin
+const CLIPBOARD_COMMANDS: [(&str, &[&str]); 5] = [
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("clip.exe", &[]),
+];
+
+/// Puts the text on the clipboard. The system clipboard is used when one of the clipboard programs
+/// is available, otherwise the text is sent to the terminal with an OSC 52 escape sequence, which
+/// also reaches the local clipboard in remote sessions
+pub fn copy(text: &str) -> Result<()> {
+    // in remote sessions the clipboard programs would set the clipboard of the remote machine
+    if std::env::var_os("SSH_TTY").is_none() {
+        for (program, args) in CLIPBOARD_COMMANDS {
+            match run_clipboard_command(program, args, text) {
+                Ok(_) => return Ok(()),
+                Err(error) => debug!("unable to copy with '{program}': {error:#}"),
+            }
+        }
+    }
+    copy_osc52(text)
+}
+
+fn run_clipboard_command(program: &str, args: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("failed starting the program")?;
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("missing stdin"))?
+        .write_all(text.as_bytes())
+        .context("failed writing the text")?;
+
+    let status = child.wait().context("failed waiting for the program")?;
+    if !status.success() {
+        return Err(anyhow!("the program exited with {status}"));
+    }
+    Ok(())
+}
+
+/// Writes the escape sequence to stderr, the output the tui is drawn to
+fn copy_osc52(text: &str) -> Result<()> {
+    let mut stderr = std::io::stderr().lock();
+    stderr
+        .write_all(osc52_sequence(text).as_bytes())
+        .and_then(|_| stderr.flush())
+        .context("failed writing to the terminal")
+}
+
+fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::osc52_sequence;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(
+            osc52_sequence("https://en.wikipedia.org/wiki/Rust"),
+            "\x1b]52;c;aHR0cHM6Ly9lbi53aWtpcGVkaWEub3JnL3dpa2kvUnVzdA==\x07"
+        );
+    }
+}
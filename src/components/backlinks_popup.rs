@@ -0,0 +1,273 @@
+use std::{sync::Arc, time::Instant};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, HighlightSpacing, List, ListItem},
+};
+use wiki_api::{
+    languages::Language,
+    page::{ArticleUrl, Backlink, Backlinks},
+    Endpoint,
+};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult},
+    config::{Config, Theme},
+    terminal::Frame,
+    ui::{centered_rect, spinner, ScrollBehaviour, StatefulList},
+};
+
+use super::Component;
+
+/// More pages are fetched when the selection gets this close to the end of the list
+const LOAD_MORE_MARGIN: usize = 5;
+
+/// A popup listing the pages linking to a page. More pages are fetched while scrolling down and
+/// the selected page can be opened
+pub struct BacklinksComponent {
+    endpoint: Endpoint,
+    language: Language,
+    title: String,
+
+    /// Whether the pages of all namespaces are listed, otherwise only articles
+    all_namespaces: bool,
+    list: StatefulList<Backlink>,
+    /// Whether there are more pages than the listed ones
+    has_more: bool,
+    /// When the pages being fetched started loading, `None` while nothing is loading
+    loading: Option<Instant>,
+    /// The error of the last fetch, fetching is only retried when pressing 'r'
+    error: Option<String>,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl BacklinksComponent {
+    /// Creates the popup listing the articles linking to the page, which are loading until
+    /// they're set
+    pub fn new(
+        endpoint: Endpoint,
+        language: Language,
+        title: String,
+        config: Arc<Config>,
+        theme: Arc<Theme>,
+    ) -> Self {
+        Self {
+            endpoint,
+            language,
+            title,
+
+            all_namespaces: false,
+            list: StatefulList::with_items(Vec::new())
+                .scroll_behavior(ScrollBehaviour::StickToEnds),
+            has_more: false,
+            loading: Some(Instant::now()),
+            error: None,
+
+            config,
+            theme,
+        }
+    }
+
+    fn set_backlinks(&mut self, backlinks: Backlinks) {
+        let selected = self.list.get_state_mut().selected();
+        *self.list.get_items_mut() = backlinks.links;
+        self.list.get_state_mut().select(selected);
+        self.has_more = backlinks.continue_from.is_some();
+    }
+
+    /// Loads the first pages again, the cached ones when they were loaded before
+    fn load_action(&mut self) -> Action {
+        self.list =
+            StatefulList::with_items(Vec::new()).scroll_behavior(ScrollBehaviour::StickToEnds);
+        self.has_more = false;
+        self.loading = Some(Instant::now());
+        self.error = None;
+        Action::LoadBacklinks(
+            self.endpoint.clone(),
+            self.title.clone(),
+            self.all_namespaces,
+        )
+    }
+
+    fn continue_action(&mut self) -> Action {
+        self.loading = Some(Instant::now());
+        self.error = None;
+        Action::ContinueBacklinks(
+            self.endpoint.clone(),
+            self.title.clone(),
+            self.all_namespaces,
+        )
+    }
+
+    /// Fetches more pages when the selection is near the end of the list
+    fn fetch_more(&mut self) -> ActionResult {
+        let selected = self.list.get_state_mut().selected().unwrap_or_default();
+        let is_near_end = selected + LOAD_MORE_MARGIN >= self.list.get_items().len();
+        if !is_near_end || !self.has_more || self.loading.is_some() || self.error.is_some() {
+            return ActionResult::consumed();
+        }
+        self.continue_action().into()
+    }
+
+    fn open_selected_page(&self) -> ActionResult {
+        let backlink = match self.list.selected() {
+            Some(backlink) => backlink,
+            None => return ActionResult::Ignored,
+        };
+        ActionPacket::single(Action::PopPopup)
+            .action(Action::LoadArticleUrl(ArticleUrl {
+                title: backlink.title.clone(),
+                language: self.language,
+                endpoint: self.endpoint.clone(),
+                anchor: None,
+            }))
+            .into()
+    }
+}
+
+impl Component for BacklinksComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.pop_popup.matches_event(key)
+            || self.config.bindings.page.show_backlinks.matches_event(key)
+        {
+            return Action::PopPopup.into();
+        }
+
+        if self.config.bindings.global.submit.matches_event(key) {
+            return self.open_selected_page();
+        }
+        match key.code {
+            KeyCode::Char('f') if self.loading.is_none() => {
+                self.all_namespaces = !self.all_namespaces;
+                self.load_action().into()
+            }
+            KeyCode::Char('r') if self.error.is_some() => match self.list.get_items().is_empty() {
+                true => self.load_action().into(),
+                false => self.continue_action().into(),
+            },
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::BacklinksLoaded(endpoint, title, all_namespaces, result)
+                if endpoint == self.endpoint
+                    && title == self.title
+                    && all_namespaces == self.all_namespaces =>
+            {
+                self.loading = None;
+                match result {
+                    Ok(backlinks) => self.set_backlinks(backlinks),
+                    Err(error) => self.error = Some(error),
+                }
+            }
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.list.previous()
+                }
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.list.next()
+                }
+                return self.fetch_more();
+            }
+            Action::UnselectScroll => self.list.unselect(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let namespaces = match self.all_namespaces {
+            true => "All namespaces",
+            false => "Articles",
+        };
+        let mut hint = match self.all_namespaces {
+            true => "<ENTER> Open | <f> Only articles".to_string(),
+            false => "<ENTER> Open | <f> All namespaces".to_string(),
+        };
+        if self.error.is_some() {
+            hint.push_str(" | <r> Retry");
+        }
+        let more = if self.has_more { "+" } else { "" };
+        let block = self
+            .theme
+            .default_block()
+            .title(format!(
+                "What links to '{}' ({}{more}) | {namespaces}",
+                self.title,
+                self.list.get_items().len()
+            ))
+            .title_bottom(Line::from(hint).right_aligned())
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 60, 70);
+        f.render_widget(Clear, area);
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+
+        let status = match (self.loading, self.error.as_ref()) {
+            (Some(started), _) => Some(
+                Line::from(format!("{} Loading the linking pages", spinner(started)))
+                    .fg(self.theme.inactive_fg),
+            ),
+            (None, Some(error)) => Some(
+                Line::from(format!("Unable to load the linking pages: {error}"))
+                    .fg(self.theme.red_link_fg),
+            ),
+            (None, None) if self.list.get_items().is_empty() => {
+                Some(Line::from("No pages link here").fg(self.theme.inactive_fg))
+            }
+            (None, None) => None,
+        };
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(status.is_some() as u16),
+            ])
+            .split(inner_area);
+        if let Some(status) = status {
+            f.render_widget(self.theme.default_paragraph(status), chunks[1]);
+        }
+
+        let items: Vec<ListItem> = self
+            .list
+            .get_items()
+            .iter()
+            .map(|backlink| {
+                let mut spans =
+                    vec![Span::raw(backlink.title.clone()).fg(self.theme.search_title_fg)];
+                if backlink.is_redirect {
+                    spans.push(Span::raw(" (redirect)").fg(self.theme.inactive_fg));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_symbol("| ")
+            .highlight_spacing(HighlightSpacing::Always)
+            .highlight_style(
+                Style::default()
+                    .fg(self.theme.selected_fg)
+                    .bg(self.theme.selected_bg)
+                    .add_modifier(Modifier::ITALIC),
+            );
+        f.render_stateful_widget(list, chunks[0], self.list.get_state_mut());
+    }
+}
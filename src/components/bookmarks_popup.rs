@@ -0,0 +1,277 @@
+use std::sync::Arc;
+
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem},
+};
+use tracing::warn;
+use tui_input::{backend::crossterm::EventHandler, Input};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult},
+    bookmarks::{Bookmark, Bookmarks},
+    config::{Config, Theme},
+    terminal::Frame,
+    ui::{centered_rect, StatefulList},
+};
+
+use super::Component;
+
+/// The prompt at the top of the popup
+enum Prompt {
+    Filter,
+    /// Renaming the selected bookmark
+    Rename(Input),
+}
+
+/// A popup managing the bookmarks. Changes are saved right away
+pub struct BookmarksComponent {
+    bookmarks: Vec<Bookmark>,
+    /// The bookmarks matching the filter
+    list: StatefulList<Bookmark>,
+    filter: Input,
+    prompt: Option<Prompt>,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl BookmarksComponent {
+    pub fn new(bookmarks: &[Bookmark], config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        Self {
+            list: StatefulList::with_items(bookmarks.to_vec()),
+            bookmarks: bookmarks.to_vec(),
+            filter: Input::default(),
+            prompt: None,
+
+            config,
+            theme,
+        }
+    }
+
+    fn update_list(&mut self) {
+        let selected = self.list.get_state_mut().selected();
+        let filter = self.filter.value().to_lowercase();
+        let bookmarks = self
+            .bookmarks
+            .iter()
+            .filter(|bookmark| {
+                bookmark.name.to_lowercase().contains(&filter)
+                    || bookmark.title.to_lowercase().contains(&filter)
+            })
+            .cloned()
+            .collect();
+        self.list = StatefulList::with_items(bookmarks);
+        if let Some(idx) = selected {
+            let idx = idx.min(self.list.get_items().len().saturating_sub(1));
+            self.list.get_state_mut().select(Some(idx));
+        }
+    }
+
+    /// Applies the change to the stored bookmarks and the ones shown in the popup
+    fn change(&mut self, change: impl Fn(&mut Bookmarks)) -> ActionResult {
+        let result = Bookmarks::open().and_then(|mut store| {
+            change(store.get_mut());
+            store.save()?;
+            Ok(store.get().bookmarks().to_vec())
+        });
+        match result {
+            Ok(bookmarks) => {
+                self.bookmarks = bookmarks;
+                self.update_list();
+                ActionResult::consumed()
+            }
+            Err(error) => {
+                let error = error.context("Unable to save the bookmarks");
+                warn!("{:?}", error);
+                Action::PopupError(error.to_string()).into()
+            }
+        }
+    }
+
+    fn handle_prompt_input(&mut self, prompt: Prompt, key: KeyEvent) -> ActionResult {
+        let is_pop = self.config.bindings.global.pop_popup.matches_event(key);
+        let is_submit = self.config.bindings.global.submit.matches_event(key);
+
+        match prompt {
+            Prompt::Filter => {
+                if is_pop {
+                    self.filter.reset();
+                    self.update_list();
+                } else if !is_submit {
+                    if self.filter.handle_event(&Event::Key(key)).is_some() {
+                        self.update_list();
+                    }
+                    self.prompt = Some(Prompt::Filter);
+                }
+            }
+            Prompt::Rename(mut input) => {
+                if is_submit {
+                    return self.rename_selected(input.value().trim().to_string());
+                } else if !is_pop {
+                    input.handle_event(&Event::Key(key));
+                    self.prompt = Some(Prompt::Rename(input));
+                }
+            }
+        }
+        ActionResult::consumed()
+    }
+
+    fn rename_selected(&mut self, name: String) -> ActionResult {
+        let bookmark = match self.list.selected() {
+            Some(bookmark) if !name.is_empty() => bookmark.clone(),
+            _ => return ActionResult::consumed(),
+        };
+        self.change(|bookmarks| bookmarks.rename(bookmark.language, &bookmark.title, name.clone()))
+    }
+
+    fn remove_selected(&mut self) -> ActionResult {
+        let bookmark = match self.list.selected() {
+            Some(bookmark) => bookmark.clone(),
+            None => return ActionResult::Ignored,
+        };
+        self.change(|bookmarks| bookmarks.remove(bookmark.language, &bookmark.title))
+    }
+
+    fn render_prompt(&self, f: &mut Frame<'_>, area: Rect) {
+        let (prefix, input) = match self.prompt.as_ref() {
+            Some(Prompt::Rename(input)) => ("Rename: ", input),
+            _ => ("/", &self.filter),
+        };
+        f.render_widget(
+            self.theme
+                .default_paragraph(format!("{prefix}{}", input.value()))
+                .fg(self.theme.highlight_fg),
+            area,
+        );
+        if self.prompt.is_some() {
+            f.set_cursor(
+                area.x + prefix.len() as u16 + input.visual_cursor() as u16,
+                area.y,
+            );
+        }
+    }
+}
+
+impl Component for BookmarksComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if let Some(prompt) = self.prompt.take() {
+            return self.handle_prompt_input(prompt, key);
+        }
+
+        if self.config.bindings.global.pop_popup.matches_event(key)
+            || self
+                .config
+                .bindings
+                .page
+                .toggle_bookmarks
+                .matches_event(key)
+        {
+            return Action::PopPopup.into();
+        }
+
+        if self.config.bindings.global.submit.matches_event(key) {
+            return match self.list.selected() {
+                Some(bookmark) => ActionPacket::single(Action::PopPopup)
+                    .action(Action::OpenBookmark(bookmark.clone()))
+                    .into(),
+                None => ActionResult::Ignored,
+            };
+        }
+
+        match key.code {
+            KeyCode::Char('/') => {
+                self.prompt = Some(Prompt::Filter);
+                ActionResult::consumed()
+            }
+            KeyCode::Char('r') => {
+                let name = match self.list.selected() {
+                    Some(bookmark) => bookmark.name.clone(),
+                    None => return ActionResult::Ignored,
+                };
+                self.prompt = Some(Prompt::Rename(Input::new(name)));
+                ActionResult::consumed()
+            }
+            KeyCode::Char('d') => self.remove_selected(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.list.previous()
+                }
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.list.next()
+                }
+            }
+            Action::UnselectScroll => self.list.unselect(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let block = self
+            .theme
+            .default_block()
+            .title("Bookmarks")
+            .title_bottom(
+                Line::from("<ENTER> Open | </> Filter | <r> Rename | <d> Delete").right_aligned(),
+            )
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 50, 60);
+        f.render_widget(Clear, area);
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+
+        let list_area = if self.prompt.is_some() || !self.filter.value().is_empty() {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(inner_area);
+            self.render_prompt(f, chunks[0]);
+            chunks[1]
+        } else {
+            inner_area
+        };
+
+        if self.list.get_items().is_empty() {
+            f.render_widget(self.theme.default_paragraph("No bookmarks"), list_area);
+            return;
+        }
+
+        let items = self.list.get_items().iter().map(|bookmark| {
+            let mut location = bookmark.language.name().to_string();
+            if bookmark.name != bookmark.title {
+                location = format!("{} | {location}", bookmark.title);
+            }
+            if let Some(anchor) = bookmark.anchor.as_ref() {
+                location.push_str(&format!(" | #{anchor}"));
+            }
+            ListItem::new(Line::from(vec![
+                Span::raw(bookmark.name.clone()).fg(self.theme.fg),
+                Span::raw(format!("  {location}")).fg(self.theme.inactive_fg),
+            ]))
+        });
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_stateful_widget(list, list_area, self.list.get_state_mut());
+    }
+}
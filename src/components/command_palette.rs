@@ -0,0 +1,592 @@
+use std::{str::FromStr, sync::Arc};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem},
+};
+use tui_input::{backend::crossterm::EventHandler, Input};
+use wiki_api::languages::{suggest_language, Language};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, PageAction},
+    config::{Config, Theme},
+    history::{HistoryKind, PromptHistory},
+    terminal::Frame,
+    ui::{centered_rect, StatefulList},
+};
+
+use super::{Component, Keymap};
+
+/// Commands that aren't bound to a key because they need an argument: (name, argument, description)
+const ARGUMENT_COMMANDS: [(&str, &str, &str); 3] = [
+    ("open", "<title>", "Open the article with the title"),
+    (
+        "width",
+        "<columns>",
+        "Limit the width of the page, 0 removes the limit",
+    ),
+    ("lang", "<code>", "Switch the language of the searches"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CommandKind {
+    /// An action of a keybinding, run without pressing its keys
+    Binding(Action),
+    Argument,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Command {
+    name: &'static str,
+    description: &'static str,
+    /// The keys of an action or the placeholder of the argument
+    hint: String,
+    kind: CommandKind,
+}
+
+/// An entry in the list of the palette
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Candidate {
+    /// The line run when the entry is picked, recent commands keep their argument
+    line: String,
+    command: usize,
+}
+
+/// All commands of the palette. The actions are taken from the keymaps, so the palette lists the
+/// actions of the help that don't need something to be focused
+struct Commands {
+    commands: Vec<Command>,
+}
+
+impl Commands {
+    fn new(keymaps: &[Keymap]) -> Self {
+        let mut commands: Vec<Command> = Vec::new();
+        for action in keymaps.iter().flat_map(|keymap| keymap.bindings.iter()) {
+            // the actions of the current context shadow global ones with the same name
+            if commands.iter().any(|command| command.name == action.name) {
+                continue;
+            }
+            if let Some(ref binding_action) = action.action {
+                commands.push(Command {
+                    name: action.name,
+                    description: action.description,
+                    hint: action.keys.clone(),
+                    kind: CommandKind::Binding(binding_action.clone()),
+                });
+            }
+        }
+        commands.extend(
+            ARGUMENT_COMMANDS
+                .iter()
+                .map(|(name, argument, description)| Command {
+                    name,
+                    description,
+                    hint: argument.to_string(),
+                    kind: CommandKind::Argument,
+                }),
+        );
+        Self { commands }
+    }
+
+    fn find(&self, name: &str) -> Option<usize> {
+        self.commands
+            .iter()
+            .position(|command| command.name == name)
+    }
+
+    /// Lists the commands matching the input. The recent lines come first, most recent first.
+    /// `recent` is ordered from the oldest to the most recent line
+    fn candidates(&self, input: &str, recent: &[String]) -> Vec<Candidate> {
+        let input = input.trim_start();
+        let (name, argument) = split_line(input);
+
+        // once an argument is typed, only the command itself is left
+        if input.contains(char::is_whitespace) {
+            return self
+                .find(name)
+                .map(|command| Candidate {
+                    line: match argument.is_empty() {
+                        true => name.to_string(),
+                        false => format!("{name} {argument}"),
+                    },
+                    command,
+                })
+                .into_iter()
+                .collect();
+        }
+
+        let recency = |name: &str| {
+            recent
+                .iter()
+                .rev()
+                .position(|line| split_line(line).0 == name)
+                .unwrap_or(usize::MAX)
+        };
+
+        if input.is_empty() {
+            let mut candidates: Vec<Candidate> = recent
+                .iter()
+                .rev()
+                .filter_map(|line| {
+                    self.find(split_line(line).0).map(|command| Candidate {
+                        line: line.to_string(),
+                        command,
+                    })
+                })
+                .collect();
+            for (idx, command) in self.commands.iter().enumerate() {
+                if !candidates
+                    .iter()
+                    .any(|candidate| candidate.line == command.name)
+                {
+                    candidates.push(Candidate {
+                        line: command.name.to_string(),
+                        command: idx,
+                    });
+                }
+            }
+            return candidates;
+        }
+
+        let mut matches: Vec<(usize, usize, usize)> = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, command)| {
+                fuzzy_score(name, command.name).map(|score| (score, recency(command.name), idx))
+            })
+            .collect();
+        matches.sort();
+        matches
+            .into_iter()
+            .map(|(_, _, idx)| Candidate {
+                line: self.commands[idx].name.to_string(),
+                command: idx,
+            })
+            .collect()
+    }
+
+    /// Parses the line into the action it runs
+    fn parse(&self, line: &str) -> Result<Action, String> {
+        let (name, argument) = split_line(line.trim());
+        let command = match self.find(name) {
+            Some(command) => &self.commands[command],
+            None => return Err(format!("Unknown command '{name}'")),
+        };
+
+        match command.kind {
+            CommandKind::Binding(_) if !argument.is_empty() => {
+                Err(format!("'{name}' takes no argument"))
+            }
+            CommandKind::Binding(ref action) => Ok(action.clone()),
+            CommandKind::Argument if argument.is_empty() => {
+                Err(format!("Usage: {} {}", command.name, command.hint))
+            }
+            CommandKind::Argument => parse_argument(name, argument),
+        }
+    }
+}
+
+fn parse_argument(name: &str, argument: &str) -> Result<Action, String> {
+    match name {
+        "open" => Ok(Action::LoadPage(argument.to_string())),
+        "width" => argument
+            .parse::<u16>()
+            .map(|width| Action::Page(PageAction::SetMaxWidth(width)))
+            .map_err(|_| format!("'{argument}' is not a number of columns")),
+        "lang" => Language::from_str(argument)
+            .map(Action::SwitchLanguage)
+            .map_err(|_| match suggest_language(argument) {
+                Some(language) => format!(
+                    "Unknown language '{argument}', did you mean '{}' ({})?",
+                    language.code(),
+                    language.name()
+                ),
+                None => format!("Unknown language '{argument}'"),
+            }),
+        _ => unreachable!("'{}' is not a command with an argument", name),
+    }
+}
+
+/// Splits a line into the name of the command and its argument
+fn split_line(line: &str) -> (&str, &str) {
+    match line.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (line, ""),
+    }
+}
+
+/// Matches the query against the name when all of its characters appear in order. Lower scores
+/// are better, every character skipped before or between the matched ones adds to the score
+fn fuzzy_score(query: &str, name: &str) -> Option<usize> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let mut position = 0;
+    let mut score = 0;
+    for c in query.to_lowercase().chars() {
+        let skipped = name[position..].iter().position(|n| *n == c)?;
+        score += skipped;
+        position += skipped + 1;
+    }
+    Some(score)
+}
+
+/// A popup for running any action by its name, including the ones that need an argument
+pub struct CommandPaletteComponent {
+    input: Input,
+    list: StatefulList<Candidate>,
+    commands: Commands,
+    history: PromptHistory,
+    /// The reason the last line couldn't be run
+    error: Option<String>,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl CommandPaletteComponent {
+    pub fn new(keymaps: Vec<Keymap>, config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        let mut palette = Self {
+            input: Input::default(),
+            list: StatefulList::with_items(Vec::new()),
+            commands: Commands::new(&keymaps),
+            history: PromptHistory::load(HistoryKind::Command, config.ui.history_size),
+            error: None,
+
+            config,
+            theme,
+        };
+        palette.update_list();
+        palette
+    }
+
+    fn update_list(&mut self) {
+        let candidates = self
+            .commands
+            .candidates(self.input.value(), self.history.entries());
+        let is_empty = candidates.is_empty();
+        self.list = StatefulList::with_items(candidates);
+        if !is_empty {
+            self.list.get_state_mut().select(Some(0));
+        }
+    }
+
+    fn set_input(&mut self, value: String) {
+        self.input = Input::new(value);
+        self.update_list();
+    }
+
+    /// Runs the typed line when it names a command, the selected entry otherwise
+    fn submit(&mut self) -> ActionResult {
+        let value = self.input.value().trim().to_string();
+        let line = match self.commands.find(split_line(&value).0) {
+            Some(_) => value,
+            None => match self.list.selected() {
+                Some(candidate) => candidate.line.clone(),
+                None if value.is_empty() => return ActionResult::consumed(),
+                None => value,
+            },
+        };
+
+        // commands picked without their argument are completed instead
+        let (name, argument) = split_line(&line);
+        if let Some(command) = self.commands.find(name) {
+            if self.commands.commands[command].kind == CommandKind::Argument && argument.is_empty()
+            {
+                self.set_input(format!("{name} "));
+                return ActionResult::consumed();
+            }
+        }
+
+        match self.commands.parse(&line) {
+            Ok(action) => {
+                self.history.add(&line);
+                ActionPacket::single(Action::PopPopup).action(action).into()
+            }
+            Err(error) => {
+                self.error = Some(error);
+                ActionResult::consumed()
+            }
+        }
+    }
+
+    /// Completes the input with the selected entry
+    fn complete(&mut self) {
+        if let Some(candidate) = self.list.selected() {
+            let command = &self.commands.commands[candidate.command];
+            let line = match command.kind {
+                CommandKind::Argument if candidate.line == command.name => {
+                    format!("{} ", command.name)
+                }
+                _ => candidate.line.clone(),
+            };
+            self.set_input(line);
+        }
+    }
+}
+
+impl Component for CommandPaletteComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.pop_popup.matches_event(key) {
+            return Action::PopPopup.into();
+        }
+
+        if self.config.bindings.global.submit.matches_event(key) {
+            return self.submit();
+        }
+
+        match key.code {
+            KeyCode::Tab => self.complete(),
+            KeyCode::Up => self.list.previous(),
+            KeyCode::Down => self.list.next(),
+            _ => {
+                self.input.handle_event(&crossterm::event::Event::Key(key));
+                self.error = None;
+                self.update_list();
+            }
+        }
+        ActionResult::consumed()
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.list.previous()
+                }
+                ActionResult::consumed()
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.list.next()
+                }
+                ActionResult::consumed()
+            }
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let popup_block = self
+            .theme
+            .default_block()
+            .title("Command Palette")
+            .title_bottom("<ENTER> Run | <TAB> Complete | <ESC> Close")
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 50, 50);
+        f.render_widget(Clear, area);
+        f.render_widget(popup_block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(self.error.is_some() as u16),
+                Constraint::Percentage(100),
+            ])
+            .split(area);
+        let (input_area, error_area, list_area) = (chunks[0], chunks[1], chunks[2]);
+
+        let width = (input_area.width as usize).saturating_sub(1);
+        let scroll = self.input.visual_scroll(width);
+        let cursor = self.input.visual_cursor();
+        f.render_widget(
+            self.theme
+                .default_paragraph(format!(":{}", self.input.value()))
+                .scroll((0, scroll as u16)),
+            input_area,
+        );
+        f.set_cursor(
+            input_area.x + 1 + (cursor.max(scroll) - scroll) as u16,
+            input_area.y,
+        );
+
+        if let Some(ref error) = self.error {
+            f.render_widget(
+                self.theme
+                    .default_paragraph(error.as_str())
+                    .fg(self.theme.red_link_fg),
+                error_area,
+            );
+        }
+
+        let name_width = self
+            .list
+            .get_items()
+            .iter()
+            .map(|candidate| candidate.line.chars().count())
+            .max()
+            .unwrap_or_default();
+        let list_items: Vec<ListItem> = self
+            .list
+            .get_items()
+            .iter()
+            .map(|candidate| {
+                let command = &self.commands.commands[candidate.command];
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{:name_width$}  ", candidate.line)).fg(self.theme.fg),
+                    Span::raw(format!("{}  ", command.description)).fg(self.theme.fg),
+                    Span::raw(command.hint.clone()).fg(self.theme.inactive_fg),
+                ]))
+            })
+            .collect();
+        let list_widget = List::new(list_items).highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_stateful_widget(list_widget, list_area, self.list.get_state_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::languages::Language;
+
+    use super::{fuzzy_score, Commands};
+    use crate::{
+        action::{Action, PageAction},
+        components::{Keymap, KeymapAction},
+    };
+
+    fn commands() -> Commands {
+        let action = |name| KeymapAction {
+            name,
+            description: "",
+            keys: String::new(),
+            action: Action::from_binding(name),
+        };
+        Commands::new(&[
+            Keymap {
+                title: "Page",
+                bindings: vec![
+                    action("toggle_zen_mode"),
+                    action("toggle_toc"),
+                    action("jump_to_header"),
+                ],
+            },
+            Keymap {
+                title: "Global",
+                bindings: vec![
+                    KeymapAction {
+                        action: Some(Action::Quit),
+                        ..action("toggle_zen_mode")
+                    },
+                    action("quit"),
+                ],
+            },
+        ])
+    }
+
+    fn names(commands: &Commands, input: &str, recent: &[String]) -> Vec<String> {
+        commands
+            .candidates(input, recent)
+            .into_iter()
+            .map(|candidate| candidate.line)
+            .collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("toggle", "toggle_toc"), Some(0));
+        assert_eq!(fuzzy_score("tt", "toggle_toc"), Some(6));
+        assert_eq!(fuzzy_score("ZEN", "toggle_zen_mode"), Some(7));
+        assert_eq!(fuzzy_score("zt", "toggle_zen_mode"), None);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let commands = commands();
+        assert_eq!(
+            names(&commands, "", &[]),
+            [
+                "toggle_zen_mode",
+                "toggle_toc",
+                "quit",
+                "open",
+                "width",
+                "lang"
+            ]
+        );
+        assert_eq!(names(&commands, "tgt", &[]), ["toggle_toc"]);
+        assert_eq!(names(&commands, "open  Rust", &[]), ["open Rust"]);
+
+        // recent commands come first, the most recent one at the top
+        let recent = ["open Rust".to_string(), "toggle_toc".to_string()];
+        assert_eq!(
+            names(&commands, "", &recent),
+            [
+                "toggle_toc",
+                "open Rust",
+                "toggle_zen_mode",
+                "quit",
+                "open",
+                "width",
+                "lang"
+            ]
+        );
+        assert_eq!(
+            names(&commands, "tog", &recent),
+            ["toggle_toc", "toggle_zen_mode"]
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let commands = commands();
+        // the actions of the context shadow the global ones
+        assert_eq!(
+            commands.parse("toggle_zen_mode"),
+            Ok(Action::Page(PageAction::ToggleZenMode))
+        );
+        assert_eq!(
+            commands.parse("toggle_toc"),
+            Ok(Action::Page(PageAction::ToggleContents))
+        );
+        // keys that only work while something is focused aren't listed
+        assert_eq!(
+            commands.parse("jump_to_header"),
+            Err("Unknown command 'jump_to_header'".to_string())
+        );
+        assert_eq!(
+            commands.parse("quit now"),
+            Err("'quit' takes no argument".to_string())
+        );
+        assert_eq!(
+            commands.parse("unknown"),
+            Err("Unknown command 'unknown'".to_string())
+        );
+
+        assert_eq!(
+            commands.parse("open Rust (programming language)"),
+            Ok(Action::LoadPage("Rust (programming language)".to_string()))
+        );
+        assert_eq!(
+            commands.parse("open"),
+            Err("Usage: open <title>".to_string())
+        );
+        assert_eq!(
+            commands.parse("width 80"),
+            Ok(Action::Page(PageAction::SetMaxWidth(80)))
+        );
+        assert_eq!(
+            commands.parse("width wide"),
+            Err("'wide' is not a number of columns".to_string())
+        );
+        assert_eq!(
+            commands.parse("lang de"),
+            Ok(Action::SwitchLanguage(Language::German))
+        );
+        assert!(commands
+            .parse("lang germna")
+            .is_err_and(|error| error.contains("did you mean 'de'")));
+    }
+}
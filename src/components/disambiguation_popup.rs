@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem},
+};
+use tui_input::{backend::crossterm::EventHandler, Input};
+use wiki_api::document::DisambiguationEntry;
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult},
+    config::{Config, Theme},
+    terminal::Frame,
+    ui::{centered_rect, StatefulList},
+};
+
+use super::Component;
+
+/// A popup on top of a disambiguation page listing its meanings. Typing filters the meanings,
+/// closing the popup leaves the page as it is
+pub struct DisambiguationComponent {
+    title: String,
+    entries: Vec<DisambiguationEntry>,
+    input: Input,
+    list: StatefulList<DisambiguationEntry>,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl DisambiguationComponent {
+    pub fn new(
+        title: String,
+        entries: Vec<DisambiguationEntry>,
+        config: Arc<Config>,
+        theme: Arc<Theme>,
+    ) -> Self {
+        let mut popup = Self {
+            title,
+            entries,
+            input: Input::default(),
+            list: StatefulList::with_items(Vec::new()),
+
+            config,
+            theme,
+        };
+        popup.update_list();
+        popup
+    }
+
+    fn update_list(&mut self) {
+        let query = self.input.value().to_lowercase();
+        let entries = self
+            .entries
+            .iter()
+            .filter(|entry| {
+                entry.text.to_lowercase().contains(&query)
+                    || entry.description.to_lowercase().contains(&query)
+            })
+            .cloned()
+            .collect::<Vec<DisambiguationEntry>>();
+        let is_empty = entries.is_empty();
+        self.list = StatefulList::with_items(entries);
+        if !is_empty {
+            self.list.get_state_mut().select(Some(0));
+        }
+    }
+}
+
+impl Component for DisambiguationComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.submit.matches_event(key) {
+            return match self.list.selected() {
+                Some(entry) => ActionPacket::single(Action::PopPopup)
+                    .action(Action::LoadLink(entry.link.clone()))
+                    .into(),
+                None => ActionResult::consumed(),
+            };
+        }
+
+        if self.config.bindings.global.pop_popup.matches_event(key) {
+            return Action::PopPopup.into();
+        }
+
+        match key.code {
+            KeyCode::Up => self.list.previous(),
+            KeyCode::Down => self.list.next(),
+            _ => {
+                self.input.handle_event(&crossterm::event::Event::Key(key));
+                self.update_list();
+            }
+        }
+        ActionResult::consumed()
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.list.previous()
+                }
+                ActionResult::consumed()
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.list.next()
+                }
+                ActionResult::consumed()
+            }
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let popup_block = self
+            .theme
+            .default_block()
+            .title(format!("{} may refer to", self.title))
+            .title_bottom(Line::from("<ENTER> Open | <ESC> Show the page").right_aligned())
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 60, 60);
+        f.render_widget(Clear, area);
+        f.render_widget(popup_block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Length(1), Constraint::Percentage(100)])
+            .split(area);
+        let (input_area, list_area) = (chunks[0], chunks[1]);
+
+        let scroll = self.input.visual_scroll(input_area.width as usize);
+        let cursor = self.input.visual_cursor();
+        let value = self.input.value();
+        f.render_widget(
+            self.theme
+                .default_paragraph(format!(
+                    "{}{}",
+                    value,
+                    "_".repeat((input_area.width as usize).saturating_sub(value.len()))
+                ))
+                .scroll((0, scroll as u16)),
+            input_area,
+        );
+        f.set_cursor(
+            input_area.x + (cursor.max(scroll) - scroll) as u16,
+            input_area.y,
+        );
+
+        if self.list.get_items().is_empty() {
+            f.render_widget(
+                self.theme
+                    .default_paragraph("No meaning matches the filter")
+                    .fg(self.theme.inactive_fg),
+                list_area,
+            );
+            return;
+        }
+
+        let list_items = self.list.get_items().iter().map(|entry| {
+            let mut spans = vec![Span::raw(entry.text.clone()).fg(self.theme.fg)];
+            if !entry.description.is_empty() {
+                spans.push(
+                    Span::raw(format!(" - {}", entry.description)).fg(self.theme.inactive_fg),
+                );
+            }
+            ListItem::new(Line::from(spans))
+        });
+        let list_widget = List::new(list_items).highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_stateful_widget(list_widget, list_area, self.list.get_state_mut());
+    }
+}
@@ -0,0 +1,176 @@
+use std::{path::PathBuf, sync::Arc};
+
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::Clear,
+};
+use tui_input::{backend::crossterm::EventHandler, Input};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, PageAction},
+    config::{Config, Theme},
+    export::ExportFormat,
+    terminal::Frame,
+    ui::centered_rect,
+};
+
+use super::Component;
+
+/// A prompt asking for the file the page is exported to. Formats rendering the lines of the page
+/// also ask for their width, starting at the width of the page. Overwriting an existing file has
+/// to be confirmed
+pub struct ExportComponent {
+    input: Input,
+    /// The width of the page, suggested for the lines
+    width: u16,
+    /// The file and the input of the width of its lines, `Some` while asking for the width
+    width_input: Option<(PathBuf, Input)>,
+    /// The existing file that would be overwritten and the width of the lines, `Some` while
+    /// asking for the confirmation
+    overwrite: Option<(PathBuf, u16)>,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl ExportComponent {
+    pub fn new(file_name: String, width: u16, config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        Self {
+            input: Input::new(file_name),
+            width,
+            width_input: None,
+            overwrite: None,
+
+            config,
+            theme,
+        }
+    }
+
+    fn export(path: PathBuf, width: u16) -> ActionResult {
+        ActionPacket::single(Action::PopPopup)
+            .action(Action::Page(PageAction::ExportPageTo(path, width)))
+            .into()
+    }
+
+    fn submit(&mut self) -> ActionResult {
+        // the width has to be a number of columns, otherwise it's asked for again
+        if let Some((path, input)) = self.width_input.take() {
+            return match input.value().trim().parse::<u16>() {
+                Ok(width) if width > 0 => self.confirm(path, width),
+                _ => {
+                    self.width_input = Some((path, input));
+                    ActionResult::consumed()
+                }
+            };
+        }
+
+        let path = PathBuf::from(self.input.value().trim());
+        if path.as_os_str().is_empty() {
+            return ActionResult::consumed();
+        }
+
+        if ExportFormat::from_path(&path).has_width() {
+            self.width_input = Some((path, Input::new(self.width.to_string())));
+            return ActionResult::consumed();
+        }
+        self.confirm(path, self.width)
+    }
+
+    /// Exports the page, unless the file exists and overwriting it has to be confirmed first
+    fn confirm(&mut self, path: PathBuf, width: u16) -> ActionResult {
+        if path.exists() {
+            self.overwrite = Some((path, width));
+            return ActionResult::consumed();
+        }
+        Self::export(path, width)
+    }
+}
+
+impl Component for ExportComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if let Some((path, width)) = self.overwrite.take() {
+            return match key.code {
+                KeyCode::Char('y') => Self::export(path, width),
+                _ => ActionResult::consumed(),
+            };
+        }
+
+        if self.config.bindings.global.pop_popup.matches_event(key) {
+            return Action::PopPopup.into();
+        }
+        if self.config.bindings.global.submit.matches_event(key) {
+            return self.submit();
+        }
+
+        match self.width_input {
+            Some((_, ref mut input)) => input.handle_event(&Event::Key(key)),
+            None => self.input.handle_event(&Event::Key(key)),
+        };
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let mut block = self
+            .theme
+            .default_block()
+            .title(match self.width_input {
+                Some(_) => "Export Page | Width of the Lines",
+                None => "Export Page",
+            })
+            .style(Style::default().bg(self.theme.bg));
+        block = match (&self.overwrite, &self.width_input) {
+            (Some(_), _) => {
+                block.title_bottom(Line::from("<y> Overwrite | <n> Cancel").right_aligned())
+            }
+            (None, Some(_)) => block.title_bottom(Line::from("<ENTER> Export").right_aligned()),
+            (None, None) => block.title_bottom(
+                Line::from("<ENTER> Export | .md Markdown | .txt Plain text | .html Web page | .ans Colored text")
+                    .right_aligned(),
+            ),
+        };
+
+        let area = centered_rect(area, 50, 20);
+        let area = Rect {
+            y: area.y + area.height.saturating_sub(3) / 2,
+            height: area.height.min(3),
+            ..area
+        };
+        f.render_widget(Clear, area);
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+
+        let input = match self.width_input {
+            Some((_, ref input)) => input,
+            None => &self.input,
+        };
+        match self.overwrite {
+            Some((ref path, _)) => f.render_widget(
+                self.theme
+                    .default_paragraph(format!("'{}' already exists", path.display()))
+                    .fg(self.theme.highlight_fg),
+                inner_area,
+            ),
+            None => {
+                let scroll = input.visual_scroll(inner_area.width.saturating_sub(1) as usize);
+                f.render_widget(
+                    self.theme
+                        .default_paragraph(input.value())
+                        .scroll((0, scroll as u16)),
+                    inner_area,
+                );
+                f.set_cursor(
+                    inner_area.x + (input.visual_cursor().max(scroll) - scroll) as u16,
+                    inner_area.y,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, Paragraph},
+};
+
+use crate::{
+    action::{Action, ActionResult},
+    config::{Config, Theme},
+    terminal::Frame,
+    ui::centered_rect,
+};
+
+use super::{Component, Keymap};
+
+/// A popup listing the keybindings of all active components
+///
+/// The popup is modal, it consumes every key until it is closed
+pub struct HelpComponent {
+    keymaps: Vec<Keymap>,
+    scroll: u16,
+    /// Number of lines that don't fit into the popup, updated when rendering
+    max_scroll: u16,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl HelpComponent {
+    pub fn new(keymaps: Vec<Keymap>, config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        Self {
+            keymaps,
+            scroll: 0,
+            max_scroll: 0,
+
+            config,
+            theme,
+        }
+    }
+
+    fn scroll_down(&mut self, amount: u16) {
+        self.scroll = self.scroll.saturating_add(amount).min(self.max_scroll);
+    }
+
+    fn scroll_up(&mut self, amount: u16) {
+        self.scroll = self.scroll.saturating_sub(amount);
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let key_width = self
+            .keymaps
+            .iter()
+            .flat_map(|keymap| keymap.bindings.iter())
+            .map(|action| action.keys.chars().count())
+            .max()
+            .unwrap_or_default();
+
+        let mut lines = Vec::new();
+        for keymap in self.keymaps.iter() {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::from(keymap.title).fg(self.theme.title).bold());
+
+            for action in keymap.bindings.iter() {
+                lines.push(Line::from(vec![
+                    Span::raw(format!("  {:key_width$}  ", action.keys))
+                        .fg(self.theme.highlight_fg),
+                    Span::raw(action.description).fg(self.theme.fg),
+                ]));
+            }
+        }
+        lines
+    }
+}
+
+impl Component for HelpComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        let global = &self.config.bindings.global;
+        if global.toggle_help.matches_event(key)
+            || global.pop_popup.matches_event(key)
+            || key.code == KeyCode::Char('q')
+        {
+            return Action::PopPopup.into();
+        }
+
+        if global.scroll_down.matches_event(key) {
+            self.scroll_down(1);
+        } else if global.scroll_up.matches_event(key) {
+            self.scroll_up(1);
+        } else if global.half_down.matches_event(key) {
+            self.scroll_down(self.max_scroll / 2);
+        } else if global.half_up.matches_event(key) {
+            self.scroll_up(self.max_scroll / 2);
+        } else if global.scroll_to_top.matches_event(key) {
+            self.scroll = 0;
+        } else if global.scroll_to_bottom.matches_event(key) {
+            self.scroll = self.max_scroll;
+        }
+
+        // swallow every other key while the help is open
+        ActionResult::consumed()
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => self.scroll_up(n),
+            Action::ScrollDown(n) => self.scroll_down(n),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let block = self
+            .theme
+            .default_block()
+            .title("Help")
+            .title_bottom(Line::from("<?> Close").right_aligned())
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 60, 80);
+        f.render_widget(Clear, area);
+
+        let lines = self.lines();
+        self.max_scroll = (lines.len() as u16).saturating_sub(block.inner(area).height);
+        self.scroll = self.scroll.min(self.max_scroll);
+
+        f.render_widget(
+            Paragraph::new(lines).block(block).scroll((self.scroll, 0)),
+            area,
+        );
+    }
+}
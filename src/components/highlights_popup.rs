@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem},
+};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, PageAction},
+    config::{Config, Theme},
+    highlights::Highlight,
+    terminal::Frame,
+    ui::{centered_rect, StatefulList},
+};
+
+use super::Component;
+
+const ORPHANED_MARKER: &str = "(orphaned) ";
+
+pub struct HighlightsComponent {
+    /// The highlights and whether they are orphaned
+    list: StatefulList<(Highlight, bool)>,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl HighlightsComponent {
+    pub fn new(highlights: Vec<(Highlight, bool)>, config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        Self {
+            list: StatefulList::with_items(highlights),
+
+            config,
+            theme,
+        }
+    }
+
+    fn remove_selected(&mut self) -> ActionResult {
+        let idx = match self.list.get_state_mut().selected() {
+            Some(idx) if idx < self.list.get_items().len() => idx,
+            _ => return ActionResult::Ignored,
+        };
+
+        self.list.get_items_mut().remove(idx);
+        if idx >= self.list.get_items().len() {
+            self.list.previous();
+        }
+
+        Action::Page(PageAction::RemoveHighlight(idx)).into()
+    }
+}
+
+impl Component for HighlightsComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: crossterm::event::KeyEvent) -> ActionResult {
+        if self.config.bindings.global.submit.matches_event(key) {
+            let idx = self.list.get_state_mut().selected();
+            return match (idx, self.list.selected()) {
+                (Some(_), Some((_, true))) => Action::PopupMessage(
+                    "Information".to_string(),
+                    "This highlight could not be found in the current version of the article"
+                        .to_string(),
+                )
+                .into(),
+                (Some(idx), Some((_, false))) => ActionPacket::single(Action::PopPopup)
+                    .action(Action::Page(PageAction::GoToHighlight(idx)))
+                    .into(),
+                _ => ActionResult::Ignored,
+            };
+        }
+
+        if self.config.bindings.global.pop_popup.matches_event(key)
+            || self
+                .config
+                .bindings
+                .page
+                .toggle_highlights
+                .matches_event(key)
+        {
+            return Action::PopPopup.into();
+        }
+
+        match key.code {
+            KeyCode::Char('d') => self.remove_selected(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.list.previous()
+                }
+                ActionResult::consumed()
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.list.next()
+                }
+                ActionResult::consumed()
+            }
+            Action::UnselectScroll => {
+                self.list.unselect();
+                ActionResult::consumed()
+            }
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let popup_block = self
+            .theme
+            .default_block()
+            .title("Highlights")
+            .title_bottom(Line::from("<ENTER> Jump | <d> Delete").right_aligned())
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 50, 60);
+        f.render_widget(Clear, area);
+
+        if self.list.get_items().is_empty() {
+            f.render_widget(
+                self.theme
+                    .default_paragraph("No highlights for this article")
+                    .block(popup_block),
+                area,
+            );
+            return;
+        }
+
+        let width = popup_block.inner(area).width as usize;
+        let list_items = self.list.get_items().iter().map(|(highlight, orphaned)| {
+            let mut spans = Vec::new();
+            if *orphaned {
+                spans.push(Span::raw(ORPHANED_MARKER).fg(self.theme.inactive_fg));
+            }
+
+            let max_width = width.saturating_sub(spans.len() * ORPHANED_MARKER.len());
+            let text = if highlight.text.chars().count() > max_width {
+                highlight
+                    .text
+                    .chars()
+                    .take(max_width.saturating_sub(3))
+                    .collect::<String>()
+                    + "..."
+            } else {
+                highlight.text.to_string()
+            };
+            spans.push(Span::raw(text).fg(self.theme.fg));
+
+            ListItem::new(Line::from(spans))
+        });
+        let list_widget = List::new(list_items).block(popup_block).highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_stateful_widget(list_widget, area, self.list.get_state_mut());
+    }
+}
@@ -1,28 +1,193 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use crossterm::event::{Event, KeyEvent};
 use ratatui::{
+    layout::{Constraint, Layout},
     prelude::Rect,
-    style::{Color, Style},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::Clear,
 };
 use tokio::sync::mpsc;
-use tui_logger::{TuiLoggerWidget, TuiWidgetState};
+use tracing::{level_filters::LevelFilter, Level};
+use tui_input::{backend::crossterm::EventHandler, Input};
 
 use crate::{
-    action::Action,
+    action::{Action, ActionResult, MessageLevel},
+    clipboard,
     config::{Config, Theme},
+    logging::{log_buffer, LogRecord},
     terminal::Frame,
 };
 
-use super::Component;
+use super::{Component, Keymap};
 
-#[derive(Default)]
+/// The levels shown in the log viewer, cycled through from the least to the most records
+const LEVEL_FILTERS: [LevelFilter; 5] = [
+    LevelFilter::ERROR,
+    LevelFilter::WARN,
+    LevelFilter::INFO,
+    LevelFilter::DEBUG,
+    LevelFilter::TRACE,
+];
+
+/// Shows the most recent log records. New records are followed until scrolling up, scrolling
+/// back to the newest record follows them again
 pub struct LoggerComponent {
-    state: TuiWidgetState,
+    /// Whether the log viewer receives the keys
+    pub is_focussed: bool,
+    /// The most verbose level that's shown
+    level: LevelFilter,
+    /// The text the shown records contain, ignoring the case
+    filter: String,
+    /// The filter prompt, `Some` while the filter is being typed
+    filter_input: Option<Input>,
+    /// The newest shown record while scrolled up, `None` while following new records
+    anchor: Option<u64>,
+    /// Number of records fitting into the log viewer when it was last rendered
+    height: usize,
+    /// The records shown when the log viewer was last rendered
+    shown: Vec<String>,
+    /// The newest record when the log viewer was last rendered
+    rendered_id: Option<u64>,
+
     config: Arc<Config>,
     theme: Arc<Theme>,
 }
 
+impl Default for LoggerComponent {
+    fn default() -> Self {
+        Self {
+            is_focussed: false,
+            level: LevelFilter::TRACE,
+            filter: String::new(),
+            filter_input: None,
+            anchor: None,
+            height: 0,
+            shown: Vec::new(),
+            rendered_id: None,
+
+            config: Arc::new(Config::default()),
+            theme: Arc::new(Theme::default()),
+        }
+    }
+}
+
+impl LoggerComponent {
+    fn is_shown(&self, record: &LogRecord) -> bool {
+        is_shown(record, self.level, &self.filter.to_lowercase())
+    }
+
+    /// Returns the ids of the records that are shown with the current filters
+    fn shown_ids(&self) -> Vec<u64> {
+        let filter = self.filter.to_lowercase();
+        log_buffer()
+            .map(|buffer| {
+                buffer.with_records(|records| {
+                    records
+                        .iter()
+                        .filter(|record| is_shown(record, self.level, &filter))
+                        .map(|record| record.id)
+                        .collect()
+                })
+            })
+            .unwrap_or_default()
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        self.anchor = scroll(&self.shown_ids(), self.anchor, self.height, delta);
+    }
+
+    fn cycle_level(&mut self) {
+        let idx = LEVEL_FILTERS
+            .iter()
+            .position(|level| level == &self.level)
+            .unwrap_or_default();
+        self.level = LEVEL_FILTERS[(idx + 1) % LEVEL_FILTERS.len()];
+        // the anchor might not be shown with the new level
+        self.anchor = None;
+    }
+
+    fn copy_shown(&self) -> ActionResult {
+        if self.shown.is_empty() {
+            return ActionResult::consumed();
+        }
+        match clipboard::copy(&self.shown.join("\n")) {
+            Ok(_) => Action::ShowMessage(
+                MessageLevel::Info,
+                format!("Copied {} log records", self.shown.len()),
+            )
+            .into(),
+            Err(error) => Action::ShowMessage(
+                MessageLevel::Error,
+                format!("Unable to copy the log records: {error:#}"),
+            )
+            .into(),
+        }
+    }
+
+    fn handle_filter_input(&mut self, key: KeyEvent) -> ActionResult {
+        let global_bindings = &self.config.bindings.global;
+        if global_bindings.submit.matches_event(key) {
+            self.filter_input = None;
+            return ActionResult::consumed();
+        }
+
+        if global_bindings.pop_popup.matches_event(key) {
+            self.filter_input = None;
+            self.filter.clear();
+            self.anchor = None;
+            return ActionResult::consumed();
+        }
+
+        if let Some(ref mut input) = self.filter_input {
+            input.handle_event(&Event::Key(key));
+            if input.value() != self.filter {
+                self.filter = input.value().to_string();
+                self.anchor = None;
+            }
+        }
+        ActionResult::consumed()
+    }
+
+    fn level_color(level: &Level) -> Color {
+        match *level {
+            Level::ERROR => Color::Red,
+            Level::WARN => Color::Yellow,
+            Level::INFO => Color::Cyan,
+            Level::DEBUG => Color::Green,
+            Level::TRACE => Color::Magenta,
+        }
+    }
+}
+
+/// Whether the record has at most the level and contains the lowercase filter
+fn is_shown(record: &LogRecord, level: LevelFilter, filter: &str) -> bool {
+    record.level <= level
+        && (filter.is_empty()
+            || record.message.to_lowercase().contains(filter)
+            || record.target.to_lowercase().contains(filter))
+}
+
+/// Moves the newest shown record by the delta, positive deltas scroll towards the newer records.
+/// Returns `None` when the newest record is reached, so that new records are followed again
+fn scroll(ids: &[u64], anchor: Option<u64>, height: usize, delta: isize) -> Option<u64> {
+    if ids.is_empty() {
+        return None;
+    }
+    let newest = ids.len() - 1;
+    let position = anchor
+        .map(|anchor| ids.partition_point(|id| *id <= anchor).saturating_sub(1))
+        .unwrap_or(newest);
+    // the oldest records stay at the top of the viewer
+    let oldest = height.clamp(1, ids.len()) - 1;
+    let position = position
+        .saturating_add_signed(delta)
+        .clamp(oldest.min(position), newest);
+    (position < newest).then(|| ids[position])
+}
+
 impl Component for LoggerComponent {
     fn init(
         &mut self,
@@ -30,21 +195,161 @@ impl Component for LoggerComponent {
         config: Arc<Config>,
         theme: Arc<Theme>,
     ) -> Result<()> {
-        self.state = TuiWidgetState::new();
         self.config = config;
         self.theme = theme;
         Ok(())
     }
 
-    fn render(&mut self, frame: &mut Frame<'_>, size: Rect) {
-        let widget = TuiLoggerWidget::default()
-            .block(self.theme.default_block().title("Log"))
-            .style_error(Style::default().fg(Color::Red))
-            .style_warn(Style::default().fg(Color::Yellow))
-            .style_info(Style::default().fg(Color::Cyan))
-            .style_debug(Style::default().fg(Color::Green))
-            .style_trace(Style::default().fg(Color::Magenta))
-            .state(&self.state);
-        frame.render_widget(widget, size)
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn keymaps(&self) -> Vec<Keymap> {
+        vec![Keymap::new(
+            "Log viewer",
+            self.config.bindings.keymap("logger"),
+        )]
+    }
+
+    fn needs_redraw(&self) -> bool {
+        log_buffer().is_some_and(|buffer| buffer.newest_id() != self.rendered_id)
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.filter_input.is_some() {
+            return self.handle_filter_input(key);
+        }
+
+        let half = (self.height / 2).max(1) as isize;
+        let global_bindings = &self.config.bindings.global;
+        let logger_bindings = &self.config.bindings.logger;
+        if global_bindings.scroll_up.matches_event(key) {
+            self.scroll(-1);
+        } else if global_bindings.scroll_down.matches_event(key) {
+            self.scroll(1);
+        } else if global_bindings.half_up.matches_event(key) {
+            self.scroll(-half);
+        } else if global_bindings.half_down.matches_event(key) {
+            self.scroll(half);
+        } else if global_bindings.scroll_to_top.matches_event(key) {
+            self.scroll(isize::MIN);
+        } else if global_bindings.scroll_to_bottom.matches_event(key) {
+            self.anchor = None;
+        } else if logger_bindings.filter_log.matches_event(key) {
+            self.filter_input = Some(Input::new(self.filter.clone()));
+        } else if logger_bindings.cycle_log_level.matches_event(key) {
+            self.cycle_level();
+        } else if logger_bindings.copy_log.matches_event(key) {
+            return self.copy_shown();
+        } else if global_bindings.pop_popup.matches_event(key) {
+            self.is_focussed = false;
+        } else {
+            return ActionResult::Ignored;
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let mut title = vec![
+            Span::raw("Log"),
+            Span::raw(format!(" [{}]", self.level)).fg(self.theme.inactive_fg),
+        ];
+        if !self.filter.is_empty() {
+            title.push(Span::raw(format!(" [{}]", self.filter)).fg(self.theme.inactive_fg));
+        }
+        if self.anchor.is_some() {
+            title.push(Span::raw(" [paused]").fg(Color::Yellow));
+        }
+
+        let mut block = self.theme.default_block().title(Line::from(title));
+        if self.is_focussed {
+            block = block.border_style(
+                Style::default()
+                    .fg(self.theme.border_highlight_fg)
+                    .bg(self.theme.border_highlight_bg),
+            );
+        }
+        let inner_area = block.inner(area);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let [records_area, prompt_area] = match self.filter_input {
+            Some(_) => Layout::vertical([Constraint::Min(0), Constraint::Length(1)]),
+            None => Layout::vertical([Constraint::Min(0), Constraint::Length(0)]),
+        }
+        .areas(inner_area);
+        self.height = records_area.height as usize;
+
+        let buffer = match log_buffer() {
+            Some(buffer) => buffer,
+            None => {
+                f.render_widget(
+                    self.theme.default_paragraph("The log viewer is disabled"),
+                    records_area,
+                );
+                return;
+            }
+        };
+
+        self.rendered_id = buffer.newest_id();
+        let (lines, shown) = buffer.with_records(|records| {
+            let records: Vec<&LogRecord> = records
+                .iter()
+                .filter(|record| self.is_shown(record))
+                .filter(|record| self.anchor.map_or(true, |anchor| record.id <= anchor))
+                .collect();
+            let records = &records[records.len().saturating_sub(self.height)..];
+
+            let lines: Vec<Line> = records
+                .iter()
+                .map(|record| {
+                    Line::from(vec![
+                        Span::raw(format!("{:>9.3} ", record.elapsed.as_secs_f64()))
+                            .fg(self.theme.inactive_fg),
+                        Span::raw(format!("{:<5} ", record.level))
+                            .fg(Self::level_color(&record.level)),
+                        Span::raw(format!("{}: ", record.target)).fg(self.theme.inactive_fg),
+                        Span::raw(record.message.clone()).fg(self.theme.fg),
+                    ])
+                })
+                .collect();
+            let shown = records.iter().map(|record| record.to_string()).collect();
+            (lines, shown)
+        });
+        self.shown = shown;
+        f.render_widget(self.theme.default_paragraph(lines), records_area);
+
+        if let Some(ref input) = self.filter_input {
+            let scroll = input.visual_scroll((prompt_area.width as usize).saturating_sub(8));
+            f.render_widget(
+                self.theme
+                    .default_paragraph(format!("Filter: {}", input.value()))
+                    .scroll((0, scroll as u16)),
+                prompt_area,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scroll;
+
+    #[test]
+    fn test_scroll() {
+        let ids = [2, 4, 5, 7, 8, 9];
+
+        // scrolling up pauses on an older record
+        assert_eq!(scroll(&ids, None, 2, -1), Some(8));
+        assert_eq!(scroll(&ids, Some(8), 2, -2), Some(5));
+        // the oldest records fill the viewer
+        assert_eq!(scroll(&ids, Some(5), 2, -10), Some(4));
+        assert_eq!(scroll(&ids, None, 10, -3), None);
+        // reaching the newest record follows again
+        assert_eq!(scroll(&ids, Some(5), 2, 3), None);
+        assert_eq!(scroll(&ids, Some(5), 2, 1), Some(7));
+        // the anchor doesn't need to be shown
+        assert_eq!(scroll(&ids, Some(6), 2, 0), Some(5));
+        assert_eq!(scroll(&[], Some(6), 2, -1), None);
     }
 }
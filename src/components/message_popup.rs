@@ -24,6 +24,7 @@ pub struct MessagePopupComponent<'a> {
     theme: Arc<Theme>,
 
     confirmation: Option<ActionPacket>,
+    choices: Vec<(char, String, ActionPacket)>,
 }
 
 impl<'a> MessagePopupComponent<'a> {
@@ -36,6 +37,7 @@ impl<'a> MessagePopupComponent<'a> {
             theme,
 
             confirmation: None,
+            choices: Vec::new(),
         }
     }
 
@@ -51,6 +53,7 @@ impl<'a> MessagePopupComponent<'a> {
             theme,
 
             confirmation: None,
+            choices: Vec::new(),
         }
     }
 
@@ -68,11 +71,29 @@ impl<'a> MessagePopupComponent<'a> {
             theme,
 
             confirmation: Some(cb),
+            choices: Vec::new(),
+        }
+    }
+
+    /// Creates a popup that sends the actions of a choice when pressing its key
+    pub fn new_choice(
+        title: String,
+        content: String,
+        choices: Vec<(char, String, ActionPacket)>,
+        theme: Arc<Theme>,
+    ) -> Self {
+        Self {
+            choices,
+            ..Self::new_raw(title, content, theme)
         }
     }
 }
 
 impl<'a> Component for MessagePopupComponent<'a> {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
     fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
         match key.code {
             KeyCode::Char('y') if self.confirmation.is_some() => self
@@ -83,6 +104,15 @@ impl<'a> Component for MessagePopupComponent<'a> {
                 .into(),
             KeyCode::Char('n') if self.confirmation.is_some() => Action::PopPopup.into(),
 
+            KeyCode::Char(c) if self.choices.iter().any(|(key, _, _)| *key == c) => {
+                let idx = self
+                    .choices
+                    .iter()
+                    .position(|(key, _, _)| *key == c)
+                    .unwrap();
+                self.choices.remove(idx).2.action(Action::PopPopup).into()
+            }
+
             KeyCode::Esc => Action::PopPopup.into(),
             _ => ActionResult::Ignored,
         }
@@ -91,7 +121,18 @@ impl<'a> Component for MessagePopupComponent<'a> {
     fn render(&mut self, f: &mut crate::terminal::Frame<'_>, area: ratatui::prelude::Rect) {
         let max_area = centered_rect(area, 50, 80);
 
-        let width = (max_area.width as usize).min(self.content.chars().count() + 2) as usize;
+        let hints = self
+            .choices
+            .iter()
+            .map(|(key, description, _)| format!("<{key}> {description}"))
+            .chain(std::iter::once("<ESC> Dismiss".to_string()))
+            .collect::<Vec<String>>()
+            .join(" | ");
+        let content_width = match self.choices.is_empty() {
+            true => self.content.chars().count(),
+            false => self.content.chars().count().max(hints.chars().count()),
+        };
+        let width = (max_area.width as usize).min(content_width + 2);
         let wrapped_message = textwrap::wrap(&self.content, width);
 
         let height = (max_area.height as usize).min(wrapped_message.len() + 2);
@@ -115,6 +156,8 @@ impl<'a> Component for MessagePopupComponent<'a> {
             block
                 .title_bottom(Line::from(vec!["Y".bold(), "es".into()]).right_aligned())
                 .title_bottom(Line::from(vec!["N".bold(), "o".into()]).right_aligned())
+        } else if !self.choices.is_empty() {
+            block.title_bottom(Line::from(hints).right_aligned())
         } else {
             block.title_bottom(Line::from("<ESC> Dismiss").right_aligned())
         };
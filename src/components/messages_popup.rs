@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem},
+};
+
+use crate::{
+    action::{Action, ActionResult},
+    config::{Config, Theme},
+    page_cache::now,
+    terminal::Frame,
+    ui::{centered_rect, StatefulList},
+};
+
+use super::{toasts::Message, visited_popup::format_elapsed, Component};
+
+/// A popup listing the recent messages, the newest message first
+pub struct MessagesComponent {
+    list: StatefulList<Message>,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl MessagesComponent {
+    pub fn new(messages: Vec<Message>, config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        Self {
+            list: StatefulList::with_items(messages),
+
+            config,
+            theme,
+        }
+    }
+}
+
+impl Component for MessagesComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.pop_popup.matches_event(key)
+            || self
+                .config
+                .bindings
+                .global
+                .toggle_messages
+                .matches_event(key)
+        {
+            return Action::PopPopup.into();
+        }
+        ActionResult::Ignored
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.list.previous()
+                }
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.list.next()
+                }
+            }
+            Action::UnselectScroll => self.list.unselect(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let block = self
+            .theme
+            .default_block()
+            .title("Messages")
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 60, 60);
+        f.render_widget(Clear, area);
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+
+        if self.list.get_items().is_empty() {
+            f.render_widget(self.theme.default_paragraph("No messages"), inner_area);
+            return;
+        }
+
+        let now = now();
+        let items = self.list.get_items().iter().map(|message| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<12}", message.level.title()))
+                    .fg(message.level.color(&self.theme)),
+                Span::raw(message.text.clone()).fg(self.theme.fg),
+                Span::raw(format!("  {}", format_elapsed(now, message.time)))
+                    .fg(self.theme.inactive_fg),
+            ]))
+        });
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_stateful_widget(list, inner_area, self.list.get_state_mut());
+    }
+}
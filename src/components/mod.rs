@@ -1,25 +1,43 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::prelude::Rect;
 use tokio::sync::mpsc;
 
 use crate::{
     action::{Action, ActionResult},
-    config::{Config, Theme},
+    config::{Config, Keybinding, Theme},
     event::Event,
     terminal::Frame,
 };
 
+pub mod backlinks_popup;
+pub mod bookmarks_popup;
+pub mod command_palette;
+pub mod disambiguation_popup;
+pub mod export_popup;
+pub mod help_popup;
+pub mod highlights_popup;
 pub mod logger;
 pub mod message_popup;
+pub mod messages_popup;
 pub mod page;
+pub mod page_info_popup;
 pub mod page_language_popup;
 pub mod page_viewer;
+pub mod reading_list_popup;
+pub mod request_stats_popup;
+pub mod revisions_popup;
 pub mod search;
 pub mod search_bar;
+pub mod search_filter_popup;
 pub mod search_language_popup;
+pub mod site_popup;
+pub mod status_bar;
+pub mod toasts;
+pub mod visited_popup;
+pub mod watched_pages_popup;
 
 #[macro_export]
 macro_rules! key_event {
@@ -43,6 +61,45 @@ macro_rules! key_event {
     };
 }
 
+/// An action of a keymap, listed in the help and run by its name from the command palette
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeymapAction {
+    /// The name of the action in the config
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The formatted keys
+    pub keys: String,
+    /// The action run from the command palette, `None` for keys that only work while something is
+    /// focused
+    pub action: Option<Action>,
+}
+
+/// The keybindings of a component, with a human-readable description of every action
+pub struct Keymap {
+    pub title: &'static str,
+    pub bindings: Vec<KeymapAction>,
+}
+
+impl Keymap {
+    pub fn new(
+        title: &'static str,
+        keymap: Vec<(&'static str, &'static str, &Keybinding)>,
+    ) -> Self {
+        Self {
+            title,
+            bindings: keymap
+                .into_iter()
+                .map(|(name, description, binding)| KeymapAction {
+                    name,
+                    description,
+                    keys: binding.to_string(),
+                    action: Action::from_binding(name),
+                })
+                .collect(),
+        }
+    }
+}
+
 pub trait Component {
     // TODO: use custom error type
     #[allow(unused_variables)]
@@ -55,12 +112,29 @@ pub trait Component {
         Ok(())
     }
 
+    /// Replaces the theme of the component, used when switching the theme at runtime
+    #[allow(unused_variables)]
+    fn set_theme(&mut self, theme: Arc<Theme>) {}
+
+    /// Whether the component changes without receiving an action and has to be rendered again,
+    /// like a spinner or a notice that disappears after a while
+    fn needs_redraw(&self) -> bool {
+        false
+    }
+
+    /// Returns the keybindings the component responds to, grouped by its modes. The keymap of
+    /// the focused mode comes first
+    fn keymaps(&self) -> Vec<Keymap> {
+        Vec::new()
+    }
+
     #[allow(unused_variables)]
     fn handle_events(&mut self, event: Option<Event>) -> ActionResult {
         match event {
             Some(Event::Quit) => Action::Quit.into(),
             Some(Event::RenderTick) => Action::RenderTick.into(),
             Some(Event::Key(key_event)) => self.handle_key_events(key_event),
+            Some(Event::Mouse(mouse_event)) => self.handle_mouse_events(mouse_event),
             Some(Event::Resize(x, y)) => Action::Resize(x, y).into(),
             None => ActionResult::Ignored,
         }
@@ -71,6 +145,11 @@ pub trait Component {
         ActionResult::Ignored
     }
 
+    #[allow(unused_variables)]
+    fn handle_mouse_events(&mut self, mouse: MouseEvent) -> ActionResult {
+        ActionResult::Ignored
+    }
+
     #[allow(unused_variables)]
     fn update(&mut self, action: Action) -> ActionResult {
         ActionResult::Ignored
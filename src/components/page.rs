@@ -1,68 +1,240 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::{hash_map::Entry, HashMap, HashSet},
+    ops::{Range, RangeInclusive},
+    path::PathBuf,
+    sync::{Arc, Once},
+    time::{Duration, Instant},
+};
 
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{Event, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     layout::{Constraint, Direction, Flex, Layout},
     prelude::{Margin, Rect},
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, List, ListState, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+    widgets::{
+        Block, Clear, List, ListState, Padding, Paragraph, Scrollbar, ScrollbarOrientation,
+        ScrollbarState,
+    },
 };
+use reqwest::Url;
 use tracing::{debug, info, warn};
+use tui_input::{backend::crossterm::EventHandler, Input};
+use unicode_width::UnicodeWidthStr;
 use wiki_api::{
     document::{Data, Node},
-    page::{Link, Page, Section},
+    languages::Language,
+    page::{
+        link_data::{InterwikiData, InterwikiKind, RedLinkData},
+        ArticleUrl, Link, Page, Revision, Section, SectionKind,
+    },
 };
 
 use crate::{
-    action::{Action, ActionPacket, ActionResult, PageAction},
-    components::Component,
-    config::{Config, Theme, TocConfigPosition, TocConfigTitle, ZenModeComponents},
-    has_modifier,
-    renderer::{default_renderer::render_document, RenderedDocument},
+    action::{
+        Action, ActionPacket, ActionResult, MessageLevel, PageAction, PageViewerAction,
+        SearchAction,
+    },
+    bookmarks::{Bookmark, Bookmarks},
+    clipboard,
+    components::{
+        status_bar::{format_reading_time, StatusContext, StatusTemplate},
+        visited_popup::format_elapsed,
+        Component, Keymap,
+    },
+    config::{
+        Config, DisplayOptions, ImageProtocol, LookupMode, Theme, TocConfigPosition,
+        TocConfigTitle, ZenModeComponents,
+    },
+    export::{export_file_name, export_page, nodes_to_text, write_render_dump},
+    highlights::{Highlight, Highlights},
+    history::{HistoryKind, PromptHistory},
+    images::{cell_size, queue_image, resolve_protocol, PageImage},
+    page_cache::now,
+    reading_list::{ReadingList, ReadingListEntry},
+    renderer::{
+        bidi, default_renderer::PreparedDocument, dump::RenderDump, fold_hides, renderers,
+        search_pattern, section_header, top_level_sections, PageRenderer, RenderedDocument,
+        SearchMatch,
+    },
+    session::SessionPage,
+    store::page_key,
     terminal::Frame,
-    ui::padded_rect,
+    watches::{WatchedPage, Watches, MAX_WATCHED_PAGES},
 };
 
-#[cfg(debug_assertions)]
-use crate::renderer::test_renderer::{render_nodes_raw, render_tree_data, render_tree_raw};
-
-#[derive(Default, Debug, Clone, PartialEq, Eq)]
-#[repr(u8)]
-pub enum Renderer {
-    #[default]
-    Default,
-
-    #[cfg(debug_assertions)]
-    TestRendererTreeData,
-    #[cfg(debug_assertions)]
-    TestRendererTreeRaw,
-    #[cfg(debug_assertions)]
-    TestRendererNodeRaw,
+/// Minimum number of words a paragraph needs to be considered the lead of the article
+const LEAD_MIN_WORDS: usize = 8;
+
+/// Number of columns the lines wider than the page, like the unwrapped lines of the source, are
+/// scrolled horizontally at once
+const SCROLL_COLUMNS: u16 = 4;
+
+/// How long a notice, like the name of the theme after switching it, is displayed in the status
+/// bar
+const NOTICE_DURATION: Duration = Duration::from_secs(3);
+
+/// The page bindings that are only handled while the table of contents is focused
+const CONTENTS_BINDINGS: [&str; 3] = ["jump_to_header", "search_other_site", "toggle_toc"];
+
+/// What the global scroll bindings do in the table of contents
+const CONTENTS_SCROLLING: [(&str, &str); 4] = [
+    ("scroll_down", "Select the next section"),
+    ("scroll_up", "Select the previous section"),
+    ("scroll_to_top", "Select the first entry"),
+    ("scroll_to_bottom", "Select the last entry"),
+];
+
+/// How long a key sequence can be completed after pressing its prefix. A prefix that's also bound
+/// on its own, like `g` scrolling to the top, runs its binding after this time
+const SEQUENCE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The narrowest the page gets when moving the divider of the table of contents
+const MIN_PAGE_WIDTH: u16 = 20;
+
+/// The fewest lines of the page and its status bar the padding leaves on small terminals
+const MIN_PAGE_HEIGHT: u16 = 4;
+
+/// The narrowest the table of contents gets when moving its divider
+const MIN_TOC_WIDTH: u16 = 10;
+
+/// How long a link needs to stay selected until its page is prefetched
+pub const PREFETCH_DELAY: Duration = Duration::from_millis(300);
+
+/// Unknown placeholders in the status bar are only reported for the first page
+static UNKNOWN_PLACEHOLDERS: Once = Once::new();
+
+/// Loaded images are forgotten when their figure is this many viewport heights away
+const IMAGE_EVICT_DISTANCE: usize = 3;
+
+/// How long rendering a page may take before a frame is drawn. Larger pages are rendered in chunks
+/// of sections, with frames drawn in between
+const RENDER_CHUNK_DURATION: Duration = Duration::from_millis(10);
+
+/// The image of a figure, loaded when the figure comes close to the viewport
+enum ImageState {
+    Loading,
+    Loaded {
+        image: PageImage,
+        encoded: Option<EncodedImage>,
+    },
+    /// The image couldn't be loaded, the figure only shows its placeholder
+    Failed,
+}
+
+/// The escape sequence drawing an image, kept until the image is drawn with another size
+struct EncodedImage {
+    /// The number of columns and rows of the image
+    cells: (u16, u16),
+    /// The size of a cell in pixels
+    cell: (u16, u16),
+    sequence: Arc<str>,
 }
 
-impl Renderer {
-    pub fn next(&self) -> Self {
-        match self {
-            #[cfg(not(debug_assertions))]
-            &Renderer::Default => Renderer::Default,
+/// A page rendered in chunks of its top-level sections. The part rendered so far is in the render
+/// cache and displayed while the rest is rendered
+struct RenderJob {
+    width: u16,
+    /// The sections that still have to be rendered, the next one last
+    sections: Vec<usize>,
+}
 
-            #[cfg(debug_assertions)]
-            &Renderer::Default => Renderer::TestRendererTreeData,
-            #[cfg(debug_assertions)]
-            &Renderer::TestRendererTreeData => Renderer::TestRendererTreeRaw,
-            #[cfg(debug_assertions)]
-            &Renderer::TestRendererTreeRaw => Renderer::TestRendererNodeRaw,
-            #[cfg(debug_assertions)]
-            &Renderer::TestRendererNodeRaw => Renderer::Default,
+impl RenderJob {
+    /// Returns the percentage of the nodes of the document that have been rendered
+    fn progress(&self, nodes: usize) -> usize {
+        match self.sections.last() {
+            Some(next) => next * 100 / nodes.max(1),
+            None => 100,
         }
     }
 }
 
+/// The lines selected in the visual mode, as lines of the page rendered with the width
+#[derive(Debug, Clone, Copy)]
+struct VisualSelection {
+    /// The line the selection started at
+    anchor: usize,
+    /// The line moved with the scroll bindings
+    cursor: usize,
+    width: u16,
+}
+
+/// The reference of a selected reflink, shown until the selection or the viewport moves
+#[derive(Debug, Clone, Copy)]
+struct ReferencePreview {
+    /// The first node of the reflink
+    reflink: usize,
+    /// The line of the viewport when the reflink was selected
+    y: u16,
+    /// The first and last node of the selected link of the reference, `None` while the reflink
+    /// itself is selected
+    selected: Option<(usize, usize)>,
+}
+
+/// A piece of the text of a reference and the first and last node of the link it belongs to
+type ReferencePiece = (String, Option<(usize, usize)>);
+
+/// The state of an in-page search
+#[derive(Default)]
+struct PageSearchState {
+    query: String,
+    /// Whether the query is a regular expression
+    regex: bool,
+    /// Whether matches have to start and end at a word boundary
+    whole_word: bool,
+    /// The error of an invalid regular expression
+    error: Option<String>,
+
+    /// The matches in the page rendered with the width `width`
+    matches: Vec<SearchMatch>,
+    width: u16,
+    /// Index of the match the page was scrolled to
+    current: Option<usize>,
+}
+
+impl PageSearchState {
+    /// Clears the query and its matches, keeping the search options
+    fn clear(&mut self) {
+        *self = PageSearchState {
+            regex: self.regex,
+            whole_word: self.whole_word,
+            ..Default::default()
+        };
+    }
+}
+
+/// The state of the view showing the wikitext source instead of the page
+struct SourceView {
+    /// Whether long lines are wrapped, otherwise they are scrolled horizontally
+    wrap: bool,
+    /// The horizontal scroll position of the unwrapped lines
+    x: u16,
+    /// The scroll position in the page, restored when leaving the source view
+    page_y: u16,
+}
+
 #[derive(Default)]
 struct PageContentsState {
     list_state: ListState,
-    max_idx_section: u8,
+    max_idx_section: usize,
+}
+
+/// An entry of the table of contents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContentsEntry<'a> {
+    /// The entry before the sections, which leads to the introduction of the article
+    Introduction,
+    Section(&'a Section),
+}
+
+/// The position in a page, kept to restore it when the page is fetched again
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PagePosition {
+    y: u16,
+    selected: (usize, usize),
+    is_contents: bool,
+    contents_selected: Option<usize>,
 }
 
 macro_rules! rendered_page {
@@ -79,713 +251,4685 @@ macro_rules! rendered_page {
 
 pub struct PageComponent {
     pub page: Page,
-    renderer: Renderer,
+    renderers: Vec<Box<dyn PageRenderer + Send>>,
+    /// Index of the active renderer
+    renderer: usize,
     render_cache: HashMap<u16, RenderedDocument>,
+    /// The page that's rendered in chunks, `None` when the cached renders are complete
+    render_job: Option<RenderJob>,
+    /// The prepared words of the whole document or its top-level sections by their node. They
+    /// are wrapped again for every width, but only prepared again when the words change, like
+    /// with another renderer or theme
+    prepared: HashMap<usize, PreparedDocument>,
     viewport: Rect,
     selected: (usize, usize),
+    /// Whether the viewport still needs to be moved to the lead of the article
+    pending_start_at_lead: bool,
+    /// The header the viewport still needs to be moved to
+    pending_header: Option<String>,
+    /// The line the viewport still needs to be moved to, clamped to the rendered page
+    pending_y: Option<u16>,
+    /// The position of the word that still needs to be the first visible one, kept when the width
+    /// of the page changes
+    pending_word: Option<(usize, usize)>,
+    /// The position before jumping to a reference
+    jump_back: Option<PagePosition>,
+    /// The reference of the selected reflink, `None` when no reflink is selected
+    reference_preview: Option<ReferencePreview>,
+    /// The columns of the links in the reference preview that were drawn last, as their row and
+    /// columns on the screen and their first and last node
+    reference_links: Vec<(u16, Range<u16>, (usize, usize))>,
+    /// The section nodes that are folded. Every loaded page gets a new component, so the folds
+    /// are reset on page load
+    folded: HashSet<usize>,
+    /// The prefix of a key sequence and when it was pressed, `Some` until the next key or the
+    /// timeout
+    sequence_prefix: Option<(char, Instant)>,
+    /// The action of a prefix that timed out, sent by the page viewer after rendering
+    released_action: Option<Action>,
+    /// The width of the table of contents in thousandths of the page after moving its divider,
+    /// `None` uses the configured percentage
+    toc_width: Option<u16>,
+    /// The width the table of contents and the page were split from when rendered last
+    toc_split_width: u16,
+    /// The estimated reading time of the page, counted once when the page is loaded
+    reading_time: String,
+    /// The selected lines, `Some` while in the visual mode
+    visual: Option<VisualSelection>,
+    /// The area the lines of the page were drawn into last
+    page_area: Rect,
+    /// The columns of the words with a node that were drawn last, as their row in the page area,
+    /// their columns and their node. Sorted by row, so the word under the pointer is found
+    /// without measuring the words again
+    word_columns: Vec<(u16, Range<u16>, usize)>,
+    /// The last position of the mouse pointer, `None` when it isn't above the page
+    pointer: Option<(u16, u16)>,
+    /// The first and last node of the link under the pointer, styled apart from the selection
+    hovered: Option<(usize, usize)>,
+    /// Whether another link moved below the pointer since the last frame was drawn
+    is_hover_stale: bool,
+    /// The columns reserved for the scrollbar when it was drawn last, `None` without a scrollbar
+    scrollbar_area: Option<Rect>,
+    /// Whether the scrollbar is dragged with the mouse
+    is_dragging_scrollbar: bool,
 
     config: Arc<Config>,
     theme: Arc<Theme>,
+    /// The notice displayed in the status bar and when it was shown
+    notice: Option<(String, Instant)>,
+    /// Number of pages before and after this one in the history of the page viewer
+    history: (usize, usize),
+    /// Number of pages on the reading list
+    reading_list_len: usize,
+    /// Whether the page was opened as a random article
+    is_random: bool,
+    /// When the page was fetched if it was read from the cache, in seconds since the unix epoch
+    cached: Option<u64>,
+    /// Whether only cached pages can be opened
+    is_offline: bool,
+    /// Since when the selected node is selected, its page is prefetched once it stayed selected
+    selected_since: Instant,
+    /// The old revision of the page that's shown, `None` for the current version
+    revision: Option<Revision>,
+    /// Messages for the user that are sent by the page viewer after rendering
+    messages: Vec<(MessageLevel, String)>,
+    status_template: StatusTemplate,
 
     is_contents: bool,
     is_zen_mode: bool,
+    /// Components shown in zen mode
+    zen_mode: ZenModeComponents,
+    display: DisplayOptions,
     contents_state: PageContentsState,
+
+    highlights: Vec<Highlight>,
+    /// Node ranges of the highlights, `None` if the highlight is orphaned
+    highlight_ranges: Vec<Option<(usize, usize)>>,
+
+    /// The search prompt, `Some` while the query is being typed
+    search_input: Option<Input>,
+    search: PageSearchState,
+    search_history: PromptHistory,
+
+    /// The wikitext of the page, fetched when viewing the source for the first time
+    source: Option<String>,
+    /// The source view, `Some` while the wikitext is shown instead of the page
+    source_view: Option<SourceView>,
+
+    /// The protocol the images of figures are drawn with, `None` when they aren't drawn
+    image_protocol: ImageProtocol,
+    /// The images of the figures by their url
+    images: HashMap<Url, ImageState>,
+    /// Urls of the images that should be loaded, sent by the page viewer after rendering
+    images_to_load: Vec<Url>,
+    /// Whether the page is displayed from right to left, detected from its language or content
+    is_right_to_left: bool,
 }
 
 impl PageComponent {
     pub fn new(page: Page, config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        // the sections are listed after the entry scrolling to the top
+        let max_idx_section = page
+            .sections()
+            .filter(|sections| !sections.is_empty())
+            .map(|sections| sections.len() + 1)
+            .unwrap_or_default();
         let contents_state = PageContentsState {
-            list_state: ListState::default().with_selected(Some(0)),
-            max_idx_section: page.sections().map(|x| x.len() as u8).unwrap_or_default(),
+            // pages without sections have nothing to select
+            list_state: ListState::default().with_selected((max_idx_section > 0).then_some(0)),
+            max_idx_section,
+        };
+
+        let mut messages = Vec::new();
+        let highlights = match Highlights::open() {
+            Ok(store) => store.get().get(&page_key(&page)).to_vec(),
+            Err(error) => {
+                let error = error.context("Unable to load the highlights");
+                warn!("{:?}", error);
+                messages.push((MessageLevel::Error, error.to_string()));
+                Vec::new()
+            }
+        };
+        let highlight_ranges = highlights
+            .iter()
+            .map(|highlight| highlight.resolve(&page.content))
+            .collect();
+
+        let display = config.page.display_options(page.kind);
+
+        let renderers = renderers(
+            theme.clone(),
+            config.page.render_options(page.kind, page.language),
+        );
+        let renderer = renderers
+            .iter()
+            .position(|renderer| renderer.name() == config.page.renderer)
+            .unwrap_or_else(|| {
+                warn!(
+                    "unknown renderer '{}', using the default",
+                    config.page.renderer
+                );
+                messages.push((
+                    MessageLevel::Warning,
+                    format!(
+                        "Unknown renderer '{}', using the default",
+                        config.page.renderer
+                    ),
+                ));
+                0
+            });
+
+        let reading_time = format_reading_time(
+            page.content.word_count(),
+            config.page.words_per_minute,
+            config.page.cjk_characters_per_minute,
+        );
+
+        let image_protocol = match config.page.images.enabled {
+            true => resolve_protocol(config.page.images.protocol),
+            false => ImageProtocol::None,
         };
 
+        // the language of pages from a custom endpoint can be unknown, their text decides then
+        let is_right_to_left = config.page.right_to_left
+            && match page.language {
+                Language::Unknown => bidi::is_right_to_left(&page.content),
+                language => language.is_right_to_left(),
+            };
+
+        let status_template = StatusTemplate::parse(&config.page.status_bar);
+        if !status_template.unknown_placeholders().is_empty() {
+            UNKNOWN_PLACEHOLDERS.call_once(|| {
+                let unknown = status_template.unknown_placeholders().join("', '");
+                warn!("unknown placeholders '{}' in the status bar", unknown);
+                messages.push((
+                    MessageLevel::Warning,
+                    format!("Unknown placeholders '{unknown}' in the status bar"),
+                ));
+            });
+        }
+
         Self {
             page,
-            renderer: Renderer::default(),
+            renderers,
+            renderer,
             render_cache: HashMap::new(),
+            render_job: None,
+            prepared: HashMap::new(),
             viewport: Rect::default(),
             selected: (0, 0),
+            pending_start_at_lead: config.page.start_at_lead,
+            pending_header: None,
+            pending_y: None,
+            pending_word: None,
+            jump_back: None,
+            reference_preview: None,
+            reference_links: Vec::new(),
+            folded: HashSet::new(),
+            sequence_prefix: None,
+            released_action: None,
+            visual: None,
+            page_area: Rect::default(),
+            word_columns: Vec::new(),
+            pointer: None,
+            hovered: None,
+            is_hover_stale: false,
+            scrollbar_area: None,
+            is_dragging_scrollbar: false,
+            reading_time,
+            toc_width: None,
+            toc_split_width: 0,
 
             is_contents: false,
             is_zen_mode: config.page.default_zen,
+            zen_mode: config.page.zen_mode.clone(),
+            display,
             contents_state,
 
+            highlights,
+            highlight_ranges,
+
+            search_input: None,
+            search: PageSearchState::default(),
+            search_history: PromptHistory::load(HistoryKind::PageSearch, config.ui.history_size),
+
+            source: None,
+            source_view: None,
+
+            image_protocol,
+            images: HashMap::new(),
+            images_to_load: Vec::new(),
+            is_right_to_left,
+
             config,
             theme,
+            notice: None,
+            history: (0, 0),
+            reading_list_len: 0,
+            is_random: false,
+            cached: None,
+            is_offline: false,
+            selected_since: Instant::now(),
+            revision: None,
+            messages,
+            status_template,
         }
     }
 
-    pub fn is_zen_mode(&self) -> bool {
-        self.is_zen_mode
+    pub fn position(&self) -> PagePosition {
+        PagePosition {
+            y: self.viewport.y,
+            selected: self.selected,
+            is_contents: self.is_contents,
+            contents_selected: self.contents_state.list_state.selected(),
+        }
     }
 
-    fn render_page(&mut self, width: u16) {
-        let page = match self.renderer {
-            Renderer::Default => render_document(&self.page.content, width),
-            #[cfg(debug_assertions)]
-            Renderer::TestRendererTreeData => render_tree_data(&self.page.content),
-            #[cfg(debug_assertions)]
-            Renderer::TestRendererTreeRaw => render_tree_raw(&self.page.content),
-            #[cfg(debug_assertions)]
-            Renderer::TestRendererNodeRaw => render_nodes_raw(&self.page.content),
-        };
+    /// Restores the position, the viewport is moved there when rendering the page
+    pub fn restore_position(&mut self, position: PagePosition) {
+        self.viewport.y = position.y;
+        self.selected = position.selected;
+        self.selected_since = Instant::now();
+        self.is_contents = position.is_contents;
+        self.contents_state
+            .list_state
+            .select(position.contents_selected);
+        self.pending_start_at_lead = false;
+    }
 
-        self.render_cache.insert(width, page);
+    /// Returns the page and its position, which is restored in the next session
+    pub fn session_page(&self) -> SessionPage {
+        SessionPage {
+            title: self.page.title.clone(),
+            language: self.page.language,
+            endpoint: self.page.endpoint.clone(),
+            y: self.viewport.y,
+            selected: self.selected,
+        }
     }
 
-    fn rendered_page(&self, width: u16) -> Option<&RenderedDocument> {
-        self.render_cache.get(&width)
+    /// Restores the position of the last session. The selection is only restored when it's still
+    /// a link, the page could have changed since then
+    fn restore_session_position(&mut self, y: u16, selected: (usize, usize)) {
+        if let Some(Data::Link(_)) =
+            Node::new(&self.page.content, selected.0).map(|node| node.data())
+        {
+            self.select_node(selected.0);
+        }
+        self.pending_y = Some(y);
+        self.pending_start_at_lead = false;
     }
 
-    fn render_contents(&mut self, f: &mut Frame<'_>, area: Rect) {
-        let title = match self.config.page.toc.title {
-            TocConfigTitle::Default => "Contents".to_string(),
-            TocConfigTitle::Article => self.page.title.to_string(),
-            TocConfigTitle::Custom(ref title) => title.to_string(),
-        };
+    /// Returns the old revision of the page that's shown, `None` for the current version
+    pub fn revision(&self) -> Option<&Revision> {
+        self.revision.as_ref()
+    }
 
-        let sections = self.page.sections.as_ref();
-        let mut block = self.theme.default_block().title(title);
-        if self.is_contents {
-            block = block.border_style(
-                Style::default()
-                    .fg(self.theme.border_highlight_fg)
-                    .bg(self.theme.border_highlight_bg),
-            );
-        }
+    /// Marks the page as an old revision, which is announced by a banner above the page
+    pub fn mark_revision(&mut self, revision: Revision) {
+        self.revision = Some(revision);
+    }
 
-        if sections.is_none() {
-            f.render_widget(
-                self.theme
-                    .default_paragraph("No Contents available")
-                    .block(block),
-                area,
-            );
-            return;
-        }
+    pub fn set_history(&mut self, back: usize, forward: usize) {
+        self.history = (back, forward);
+    }
 
-        let sections = sections.unwrap();
-        let list = List::new(sections.iter().map(|x| {
-            let mut item = self.config.page.toc.formatted_item(&x.number, &x.text);
+    pub fn set_reading_list_len(&mut self, len: usize) {
+        self.reading_list_len = len;
+    }
 
-            if item
-                .char_indices()
-                .nth(block.inner(area).width as usize)
-                .is_some()
-            {
-                let idx = item
-                    .char_indices()
-                    .nth(block.inner(area).width.saturating_sub(3) as usize)
-                    .map_or(0, |(idx, _)| idx);
-                let trunc = item.drain(0..idx).collect::<String>() + "...";
-                trunc.fg(self.theme.fg)
-            } else {
-                item.fg(self.theme.fg)
-            }
-        }))
-        .block(block)
-        .highlight_style(
-            Style::default()
-                .fg(self.theme.selected_fg)
-                .bg(self.theme.selected_bg)
-                .add_modifier(Modifier::ITALIC),
-        );
-        f.render_stateful_widget(list, area, &mut self.contents_state.list_state);
+    /// Sets the width of the table of contents chosen on another page of the session
+    pub fn set_toc_width(&mut self, toc_width: Option<u16>) {
+        self.toc_width = toc_width;
     }
 
-    fn switch_renderer(&mut self, renderer: Renderer) {
-        self.renderer = renderer;
+    pub fn set_offline(&mut self, is_offline: bool) {
+        self.is_offline = is_offline;
+    }
 
-        debug!("flushing '{}' cached renders", self.render_cache.len());
-        self.render_cache.clear();
-        self.selected = (0, 0);
+    /// Returns the messages for the user since the last call
+    pub fn take_messages(&mut self) -> Vec<(MessageLevel, String)> {
+        std::mem::take(&mut self.messages)
     }
 
-    fn select_header(&mut self, anchor: String) {
-        // HACK: do not hardcode this
-        if &anchor == "Content_Top" {
-            info!("special case: jumping to top");
-            self.viewport.y = 0;
+    /// Returns the urls of the images that came close to the viewport since the last call. Only
+    /// works after rendering
+    pub fn images_to_load(&mut self) -> Vec<Url> {
+        std::mem::take(&mut self.images_to_load)
+    }
+
+    /// Stores the loaded image, unless this page isn't waiting for it
+    pub fn image_loaded(&mut self, url: &Url, image: &Result<PageImage, String>) {
+        if !matches!(self.images.get(url), Some(ImageState::Loading)) {
             return;
         }
+        let state = match image {
+            Ok(image) => ImageState::Loaded {
+                image: image.clone(),
+                encoded: None,
+            },
+            Err(_) => ImageState::Failed,
+        };
+        // the lines reserved for the image are left out
+        let is_failed = matches!(state, ImageState::Failed);
+        self.images.insert(url.clone(), state);
+        if is_failed {
+            self.flush_render_cache();
+        }
+    }
 
-        let header_node = self
-            .page
-            .content
-            .nth(0)
-            .unwrap()
-            .descendants()
-            .filter(|node| {
-                if let Data::Header { id, .. } = node.data() {
-                    id == &anchor
-                } else {
-                    false
-                }
-            })
-            .last();
+    /// Prefetches the pages of the first links in the viewport and of the link that stayed
+    /// selected for a while. Only works after rendering
+    fn prefetch(&self) -> ActionResult {
+        if !self.config.api.prefetch {
+            return ActionResult::consumed();
+        }
 
-        if header_node.is_none() {
-            warn!("no header with the anchor '{}' could be found", anchor);
-            return;
+        let mut links = Vec::new();
+        if self.selected_since.elapsed() >= PREFETCH_DELAY {
+            if let Some(Link::Internal(data)) = self.selected_link() {
+                links.push(data);
+            }
+        }
+        let visible_lines = self.visible_lines();
+        if let Some(page) = self.render_cache.get(&self.viewport.width) {
+            let visible = page
+                .links
+                .iter()
+                .filter(|(y, _)| visible_lines.contains(&(*y as u16)))
+                .filter_map(
+                    |(_, idx)| match Node::new(&self.page.content, *idx)?.data() {
+                        Data::Link(Link::Internal(data)) => Some(data.to_owned()),
+                        _ => None,
+                    },
+                )
+                .take(self.config.api.prefetch_visible);
+            links.extend(visible);
         }
 
-        let header_node = header_node.unwrap();
-        self.scroll_to_node(header_node.index());
+        let mut packet = ActionPacket::default();
+        for link in links {
+            packet.add_action(Action::PrefetchPage(
+                link.endpoint,
+                link.language,
+                link.page,
+            ));
+        }
+        packet.into()
     }
 
-    fn selected_header(&self) -> Option<&Section> {
-        let sections = self.page.sections()?;
-        let section_idx = self.contents_state.list_state.selected()?;
-        assert!(section_idx < self.contents_state.max_idx_section as usize);
+    /// Returns the highlights of this page and whether they are orphaned
+    pub fn highlights(&self) -> Vec<(Highlight, bool)> {
+        self.highlights
+            .iter()
+            .zip(self.highlight_ranges.iter())
+            .map(|(highlight, range)| (highlight.clone(), range.is_none()))
+            .collect()
+    }
 
-        Some(&sections[section_idx])
+    /// Highlights the selected link
+    fn add_highlight(&mut self) -> ActionResult {
+        self.highlight_nodes(self.selected.0, self.selected.1)
     }
 
-    /// Returns the y-Position of the selected element
-    fn selected_y(&self) -> usize {
-        let page = match self.render_cache.get(&self.viewport.width) {
-            Some(page) => page,
-            None => return 0,
+    /// Highlights the text of the lines selected in the visual mode and leaves the visual mode
+    fn highlight_visual_selection(&mut self) -> ActionResult {
+        let lines = match self.visual_lines() {
+            Some(lines) => lines,
+            None => return ActionResult::consumed(),
         };
+        self.visual = None;
 
-        for (y, line) in page.lines.iter().enumerate() {
-            if line
-                .iter()
-                .any(|word| self.selected.0 <= word.index && self.selected.1 >= word.index)
-            {
-                return y;
+        let page = rendered_page!(self, self.viewport.width);
+        let mut indices = page
+            .lines
+            .get(lines)
+            .unwrap_or_default()
+            .iter()
+            .flatten()
+            .filter(|word| word.node(&self.page.content).is_some())
+            .map(|word| word.index);
+        let first = match indices.next() {
+            Some(first) => first,
+            None => {
+                info!("nothing selected to highlight");
+                return ActionResult::consumed();
+            }
+        };
+        let (first, last) = indices.fold((first, first), |(first, last), index| {
+            (first.min(index), last.max(index))
+        });
+        self.highlight_nodes(first, last)
+    }
+
+    /// Highlights the text of the nodes between `first` and `last` (both inclusive)
+    fn highlight_nodes(&mut self, first: usize, last: usize) -> ActionResult {
+        let highlight = match Highlight::from_selection(&self.page.content, first, last) {
+            Some(highlight) => highlight,
+            None => {
+                info!("nothing selected to highlight");
+                return ActionResult::consumed();
             }
+        };
+
+        if self.highlights.contains(&highlight) {
+            info!("the selection is already highlighted");
+            return ActionResult::consumed();
         }
 
-        0
+        let result = Highlights::open().and_then(|mut store| {
+            store
+                .get_mut()
+                .add(&page_key(&self.page), highlight.clone());
+            store.save()
+        });
+        if let Err(error) = result {
+            let error = error.context("Unable to save the highlight");
+            warn!("{:?}", error);
+            return Action::ShowMessage(MessageLevel::Error, error.to_string()).into();
+        }
+
+        self.highlight_ranges.push(Some((first, last)));
+        self.highlights.push(highlight);
+        ActionResult::consumed()
     }
 
-    fn select_node(&mut self, idx: usize) {
-        let node = match Node::new(&self.page.content, idx) {
-            Some(node) => node,
-            None => return,
-        };
+    fn remove_highlight(&mut self, idx: usize) -> ActionResult {
+        if idx >= self.highlights.len() {
+            warn!("no highlight with the index '{}'", idx);
+            return Action::ShowMessage(
+                MessageLevel::Warning,
+                format!("There's no highlight with the number {}", idx + 1),
+            )
+            .into();
+        }
 
-        let first_index = node.index();
-        let last_index = node.last_child().map(|x| x.index()).unwrap_or(first_index);
+        let result = Highlights::open().and_then(|mut store| {
+            store.get_mut().remove(&page_key(&self.page), idx);
+            store.save()
+        });
+        if let Err(error) = result {
+            let error = error.context("Unable to remove the highlight");
+            warn!("{:?}", error);
+            return Action::ShowMessage(MessageLevel::Error, error.to_string()).into();
+        }
 
-        self.selected = (first_index, last_index);
+        self.highlights.remove(idx);
+        self.highlight_ranges.remove(idx);
+        ActionResult::consumed()
     }
 
-    fn selected_node(&self) -> Option<Node> {
-        self.page.content.nth(self.selected.0)
+    fn select_highlight(&mut self, idx: usize) {
+        let message = match self.highlight_ranges.get(idx) {
+            Some(Some((first, _))) => return self.scroll_to_node(*first),
+            Some(None) => {
+                warn!("the highlight '{}' is orphaned", idx);
+                "The highlighted text is no longer part of the page".to_string()
+            }
+            None => {
+                warn!("no highlight with the index '{}'", idx);
+                format!("There's no highlight with the number {}", idx + 1)
+            }
+        };
+        self.messages.push((MessageLevel::Warning, message));
     }
 
-    fn select_first(&mut self) {
-        if self.page.content.nth(0).is_none() {
-            return;
-        }
+    /// Returns the anchor of the nearest header above the top of the viewport, `None` in the lead
+    /// section
+    fn viewport_anchor(&self) -> Option<String> {
+        let page = self.render_cache.get(&self.viewport.width)?;
+        let index = page
+            .lines
+            .iter()
+            .skip(self.viewport.y as usize)
+            .flatten()
+            .find_map(|word| word.node(&self.page.content))?
+            .index();
 
-        let selectable_node = self
-            .page
+        self.page
             .content
-            .nth(0)
-            .unwrap()
+            .nth(0)?
             .descendants()
-            .find(|node| matches!(node.data(), &Data::Link(_)));
+            .take_while(|node| node.index() <= index)
+            .filter_map(|node| match node.data() {
+                Data::Header { id, .. } => Some(id.to_string()),
+                _ => None,
+            })
+            .last()
+    }
 
-        if let Some(node) = selectable_node {
-            self.select_node(node.index());
-            self.check_and_update_scrolling();
-        }
+    fn add_bookmark(&mut self) -> ActionResult {
+        let bookmark = Bookmark::new(
+            self.page.title.clone(),
+            self.page.language,
+            self.page.endpoint.clone(),
+            self.viewport_anchor(),
+        );
+
+        let result = Bookmarks::open().and_then(|mut store| {
+            let is_update = store.get_mut().add(bookmark);
+            store.save()?;
+            Ok(is_update)
+        });
+        let notice = match result {
+            Ok(true) => format!("Updated the bookmark of '{}'", self.page.title),
+            Ok(false) => format!("Bookmarked '{}'", self.page.title),
+            Err(error) => {
+                let error = error.context("Unable to save the bookmark");
+                warn!("{:?}", error);
+                return Action::ShowMessage(MessageLevel::Error, error.to_string()).into();
+            }
+        };
+        self.notice = Some((notice, Instant::now()));
+        ActionResult::consumed()
     }
 
-    fn select_last(&mut self) {
-        if self.page.content.nth(0).is_none() {
-            return;
-        }
+    /// Puts the page or the selected link on the reading list
+    fn read_later(&mut self, is_link: bool) -> ActionResult {
+        let entry = match is_link {
+            true => match self.selected_link() {
+                Some(Link::Internal(link)) => ReadingListEntry::new(
+                    link.page,
+                    link.language,
+                    link.endpoint,
+                    link.anchor.map(|anchor| anchor.anchor),
+                ),
+                Some(Link::RedLink(link)) => {
+                    return Action::PopupMessage(
+                        "Information".to_string(),
+                        format!("The page '{}' doesn't exist yet", link.title),
+                    )
+                    .into()
+                }
+                Some(_) => {
+                    return Action::PopupMessage(
+                        "Information".to_string(),
+                        "Only links to other pages can be read later".to_string(),
+                    )
+                    .into()
+                }
+                None => return ActionResult::Ignored,
+            },
+            false => ReadingListEntry::new(
+                self.page.title.clone(),
+                self.page.language,
+                self.page.endpoint.clone(),
+                None,
+            ),
+        };
+        let title = entry.title.clone();
 
-        let selectable_node = self
-            .page
-            .content
-            .nth(0)
-            .unwrap()
-            .descendants()
-            .filter(|node| matches!(node.data(), &Data::Link(_)) && node.index() > self.selected.1)
-            .last();
+        let result = ReadingList::open().and_then(|mut store| {
+            let is_moved = store.get_mut().add(entry);
+            store.save()?;
+            Ok((is_moved, store.get().entries().len()))
+        });
+        let (notice, len) = match result {
+            Ok((true, len)) => (
+                format!("Moved '{title}' to the top of the reading list"),
+                len,
+            ),
+            Ok((false, len)) => (format!("Added '{title}' to the reading list"), len),
+            Err(error) => {
+                let error = error.context("Unable to save the reading list");
+                warn!("{:?}", error);
+                return Action::ShowMessage(MessageLevel::Error, error.to_string()).into();
+            }
+        };
+        self.notice = Some((notice, Instant::now()));
+        Action::PageViewer(PageViewerAction::ReadingListChanged(len)).into()
+    }
 
-        if let Some(node) = selectable_node {
-            self.select_node(node.index());
-            self.check_and_update_scrolling();
-        }
+    /// Watches the page for new revisions, or stops watching a watched page. An old revision
+    /// isn't the latest one, so the latest revision is looked up by the first check
+    fn toggle_watch(&mut self) -> ActionResult {
+        let title = self.page.title.clone();
+        let language = self.page.language;
+        let revision_id = match self.revision {
+            Some(_) => None,
+            None => self.page.revision_id,
+        };
+
+        // whether the page is watched afterwards, `None` when too many pages are watched
+        let result = Watches::open().and_then(|mut store| {
+            let watches = store.get_mut();
+            let is_watched = match watches.is_watched(&self.page.endpoint, language, &title) {
+                true => {
+                    watches.remove(&self.page.endpoint, language, &title);
+                    Some(false)
+                }
+                false => watches
+                    .add(WatchedPage::new(
+                        title.clone(),
+                        language,
+                        self.page.endpoint.clone(),
+                        revision_id,
+                    ))
+                    .then_some(true),
+            };
+            store.save()?;
+            Ok(is_watched)
+        });
+        let notice = match result {
+            Ok(Some(true)) => format!("Watching '{title}' for changes"),
+            Ok(Some(false)) => format!("Stopped watching '{title}'"),
+            Ok(None) => {
+                return Action::ShowMessage(
+                    MessageLevel::Warning,
+                    format!(
+                        "Only {MAX_WATCHED_PAGES} pages can be watched, stop watching another \
+                        page first"
+                    ),
+                )
+                .into()
+            }
+            Err(error) => {
+                let error = error.context("Unable to save the watched pages");
+                warn!("{:?}", error);
+                return Action::ShowMessage(MessageLevel::Error, error.to_string()).into();
+            }
+        };
+        self.notice = Some((notice, Instant::now()));
+        ActionResult::consumed()
     }
 
-    fn select_next(&mut self) {
-        if self.page.content.nth(0).is_none() {
-            return;
+    /// Exports the page to the file. Lines with ansi escape codes are rendered by the default
+    /// renderer, like the page is displayed
+    fn export_page(&mut self, path: PathBuf, width: u16) -> ActionResult {
+        if let Err(error) = export_page(
+            &self.page,
+            &self.highlights,
+            &path,
+            self.renderers[0].as_ref(),
+            &self.theme,
+            width,
+        ) {
+            let error = error.context("Unable to export the page");
+            warn!("{:?}", error);
+            return Action::ShowMessage(MessageLevel::Error, error.to_string()).into();
         }
 
-        let selectable_node = self
-            .page
-            .content
-            .nth(0)
-            .unwrap()
-            .descendants()
-            .find(|node| matches!(node.data(), &Data::Link(_)) && self.selected.1 < node.index());
+        self.notice = Some((format!("Exported to '{}'", path.display()), Instant::now()));
+        ActionResult::consumed()
+    }
 
-        if let Some(node) = selectable_node {
-            self.select_node(node.index());
-            self.check_and_update_scrolling();
+    /// Writes the page as it's displayed at the current width to a json file, which can be
+    /// attached to bug reports
+    fn dump_render(&mut self) -> ActionResult {
+        let title = self.page.title.clone();
+        let renderer = self.renderers[self.renderer].name().to_string();
+        let width = self.viewport.width;
+        let dump = RenderDump::new(&title, &renderer, width, rendered_page!(self, width));
+        match write_render_dump(&dump) {
+            Ok(path) => Action::ShowMessage(
+                MessageLevel::Info,
+                format!("Wrote the rendered page to '{}'", path.display()),
+            )
+            .into(),
+            Err(error) => {
+                let error = error.context("Unable to dump the rendered page");
+                warn!("{:?}", error);
+                Action::ShowMessage(MessageLevel::Error, error.to_string()).into()
+            }
         }
     }
 
-    fn select_prev(&mut self) {
-        if self.page.content.nth(0).is_none() {
-            return;
+    fn copy_url(&mut self, with_section: bool) -> ActionResult {
+        let anchor = match with_section {
+            true => self.viewport_anchor(),
+            false => None,
+        };
+        let url = self.page.url(anchor.as_deref());
+        if let Err(error) = clipboard::copy(url.as_str()) {
+            let error = error.context("Unable to copy the url");
+            warn!("{:?}", error);
+            return Action::ShowMessage(MessageLevel::Error, error.to_string()).into();
         }
 
-        let selectable_node = self
-            .page
-            .content
-            .nth(0)
-            .unwrap()
-            .descendants()
-            .filter(|node| matches!(node.data(), &Data::Link(_)) && node.index() < self.selected.0)
-            .last();
+        self.notice = Some((format!("Copied '{url}'"), Instant::now()));
+        ActionResult::consumed()
+    }
 
-        if let Some(node) = selectable_node {
-            self.select_node(node.index());
-            self.check_and_update_scrolling();
+    fn copy_media_url(&mut self, url: String) -> ActionResult {
+        if let Err(error) = clipboard::copy(&url) {
+            let error = error.context("Unable to copy the url");
+            warn!("{:?}", error);
+            return Action::ShowMessage(MessageLevel::Error, error.to_string()).into();
         }
+
+        self.notice = Some((format!("Copied '{url}'"), Instant::now()));
+        ActionResult::consumed()
     }
 
-    /// Checks if the current link is out of the viewport and moves the selection accordingly. If
-    /// no links could be found in the current viewport, the selection stays as it was
-    fn check_and_update_selection(&mut self) {
-        let page = rendered_page!(self, self.viewport.width);
+    fn open_in_browser(&mut self) -> ActionResult {
+        if !self.config.ui.open_in_browser {
+            self.notice = Some((
+                "Opening pages in the browser is disabled".to_string(),
+                Instant::now(),
+            ));
+            return ActionResult::consumed();
+        }
 
-        let selected_y = self.selected_y() as u16;
-        let selected_node = match self.selected_node() {
-            Some(node) => node,
-            None => return,
-        };
+        let url = self.page.url(self.viewport_anchor().as_deref());
+        self.notice = Some((format!("Opening '{url}'"), Instant::now()));
+        Action::OpenInBrowser(url.to_string()).into()
+    }
 
-        if self.viewport.contains((0_u16, selected_y).into()) {
-            return;
-        }
+    /// Returns whether the wikitext source is shown instead of the page
+    pub fn is_viewing_source(&self) -> bool {
+        self.source_view.is_some()
+    }
 
-        if selected_y < self.viewport.top() {
-            let (_, idx) = page
-                .links
-                .iter()
-                .find(|(y, _)| self.viewport.contains((0, *y as u16).into()))
-                .map(|x| x.to_owned())
-                .unwrap_or((selected_y as usize, selected_node.index()));
+    /// Switches between the page and its source. The source is fetched the first time it's viewed
+    fn toggle_source(&mut self) -> ActionResult {
+        if let Some(view) = self.source_view.take() {
+            self.flush_render_cache();
+            self.scroll_to_y(view.page_y);
+            return ActionResult::consumed();
+        }
 
-            self.select_node(idx);
-            return;
+        if self.source.is_none() {
+            self.notice = Some(("Loading the source".to_string(), Instant::now()));
+            return Action::LoadPageSource(self.page.endpoint.clone(), self.page.title.clone())
+                .into();
         }
 
-        if selected_y > self.viewport.bottom() {
-            let (_, idx) = page
-                .links
-                .iter()
-                .rev()
-                .find(|(y, _)| self.viewport.contains((0, *y as u16).into()))
-                .map(|x| x.to_owned())
-                .unwrap_or((selected_y as usize, selected_node.index()));
+        self.source_view = Some(SourceView {
+            wrap: true,
+            x: 0,
+            page_y: self.viewport.y,
+        });
+        self.flush_render_cache();
+        self.viewport.y = 0;
+        ActionResult::consumed()
+    }
 
-            self.select_node(idx)
+    fn source_loaded(&mut self, title: String, result: Result<String, String>) -> ActionResult {
+        if title != self.page.title {
+            info!("the source of '{}' is not for the current page", title);
+            return ActionResult::consumed();
         }
-    }
 
-    fn scroll_up(&mut self, amount: u16) {
-        if self.is_contents && self.config.page.toc.enable_scrolling {
-            let i = match self.contents_state.list_state.selected() {
-                Some(i) => {
-                    if i == 0 {
-                        self.contents_state.max_idx_section as usize - 1
-                    } else {
-                        i - 1
-                    }
+        match result {
+            Ok(source) => {
+                self.source = Some(source);
+                self.notice = None;
+                if !self.is_viewing_source() {
+                    return self.toggle_source();
                 }
-                None => 0,
-            };
+                ActionResult::consumed()
+            }
+            Err(error) => Action::ShowMessage(MessageLevel::Error, error).into(),
+        }
+    }
 
-            self.contents_state.list_state.select(Some(i));
+    fn toggle_source_wrap(&mut self) {
+        if let Some(ref mut view) = self.source_view {
+            view.wrap = !view.wrap;
+            view.x = 0;
+            self.flush_render_cache();
+            self.scroll_to_y(self.viewport.y);
+        }
+    }
+
+    /// Scrolls the lines wider than the page to the column, stopping at the end of the widest
+    /// line. Pages that fit their width, like wrapped prose, don't scroll
+    fn scroll_to_x(&mut self, x: u16) {
+        if self.source_view.as_ref().is_some_and(|view| view.wrap) {
             return;
         }
 
-        self.scroll_to_y(self.viewport.y.saturating_sub(amount));
+        let width = rendered_page!(self, self.viewport.width).max_width() as u16;
+        let x = x.min(width.saturating_sub(self.viewport.width));
+        match self.source_view {
+            Some(ref mut view) => view.x = x,
+            None => self.viewport.x = x,
+        }
     }
 
-    fn scroll_down(&mut self, amount: u16) {
-        if self.is_contents && self.config.page.toc.enable_scrolling {
-            let i = match self.contents_state.list_state.selected() {
-                Some(i) => {
-                    if i >= self.contents_state.max_idx_section as usize - 1 {
-                        0
-                    } else {
-                        i + 1
-                    }
-                }
-                None => 0,
-            };
-
-            self.contents_state.list_state.select(Some(i));
-            return;
+    /// Returns the first visible column of the lines, the source keeps its own
+    fn scroll_x(&self) -> u16 {
+        match self.source_view {
+            Some(ref view) => view.x,
+            None => self.viewport.x,
         }
+    }
 
-        self.scroll_to_y(self.viewport.y + amount);
+    fn scroll_sideways(&mut self, is_right: bool) {
+        let x = match is_right {
+            true => self.scroll_x() + SCROLL_COLUMNS,
+            false => self.scroll_x().saturating_sub(SCROLL_COLUMNS),
+        };
+        self.scroll_to_x(x);
     }
 
-    fn scroll_to_bottom(&mut self) {
-        let page = rendered_page!(self, self.viewport.width);
-        self.scroll_to_y(page.lines.len() as u16);
+    /// Starts the visual mode at the selected link when it's visible, otherwise at the top of the
+    /// viewport
+    fn start_visual_mode(&mut self) {
+        let selected_y = self.selected_y() as u16;
+        let y = match self.selected_link() {
+            Some(_) if self.visible_lines().contains(&selected_y) => selected_y,
+            _ => self.viewport.y,
+        } as usize;
+        self.visual = Some(VisualSelection {
+            anchor: y,
+            cursor: y,
+            width: self.viewport.width,
+        });
+        self.notice = Some((
+            format!(
+                "Press {} to copy the selected lines",
+                self.config.bindings.page.copy_selection
+            ),
+            Instant::now(),
+        ));
     }
 
-    fn scroll_to_y(&mut self, y: u16) {
-        let page = rendered_page!(self, self.viewport.width);
-        let n_lines = page.lines.len() as u16;
-        self.viewport.y = y;
+    /// Returns the selected lines of the visual mode
+    fn visual_lines(&self) -> Option<RangeInclusive<usize>> {
+        self.visual
+            .map(|visual| visual.anchor.min(visual.cursor)..=visual.anchor.max(visual.cursor))
+    }
 
-        if self.viewport.bottom() >= n_lines {
-            self.viewport.y = n_lines.saturating_sub(self.viewport.height);
+    /// Moves the end of the visual selection to the line and scrolls it into view
+    fn move_visual_cursor(&mut self, y: usize) {
+        let n_lines = rendered_page!(self, self.viewport.width).lines.len();
+        let y = y.min(n_lines.saturating_sub(1));
+        if let Some(visual) = self.visual.as_mut() {
+            visual.cursor = y;
         }
 
-        self.check_and_update_selection();
+        let y = y as u16;
+        if y < self.viewport.top() {
+            self.scroll_to_y(y);
+        } else if y >= self.visible_lines().end {
+            self.scroll_to_bottom_line(y);
+        }
     }
 
-    fn scroll_to_node(&mut self, idx: usize) {
-        let page = rendered_page!(self, self.viewport.width);
-        let node = match Node::new(&self.page.content, idx) {
-            Some(node) => node,
-            None => return,
+    /// Copies the text of the selected lines to the clipboard and leaves the visual mode
+    fn copy_selection(&mut self) -> ActionResult {
+        let lines = match self.visual_lines() {
+            Some(lines) => lines,
+            None => return ActionResult::consumed(),
         };
-        let first_index = idx;
-        let last_index = node.last_child().map(|x| x.index()).unwrap_or(first_index);
-        let y = page.lines.iter().enumerate().find_map(|(y, line)| {
-            line.iter()
-                .find(|word| {
-                    if let Some(node) = word.node(&self.page.content) {
-                        first_index <= node.index() && node.index() <= last_index
-                    } else {
-                        false
-                    }
-                })
-                .map(|_| y)
-        });
+        self.visual = None;
 
-        if let Some(y) = y {
-            self.scroll_to_y(y as u16);
+        let count = lines.clone().count();
+        let text = rendered_page!(self, self.viewport.width).text(lines);
+        if let Err(error) = clipboard::copy(text.trim_end()) {
+            let error = error.context("Unable to copy the selection");
+            warn!("{:?}", error);
+            return Action::ShowMessage(MessageLevel::Error, error.to_string()).into();
         }
-    }
 
-    /// Checks if the current viewport shows the selected link and if not, moves the viewport so
-    /// the link is visible
-    fn check_and_update_scrolling(&mut self) {
-        let selection_y = self.selected_y() as u16;
+        let notice = match count {
+            1 => "Copied 1 line".to_string(),
+            count => format!("Copied {count} lines"),
+        };
+        self.notice = Some((notice, Instant::now()));
+        ActionResult::consumed()
+    }
 
-        if selection_y < self.viewport.top() {
-            self.scroll_to_y(selection_y);
-            return;
+    /// Handles the keys of the visual mode. The scroll bindings extend the selection, every other
+    /// key is ignored until the selection is copied, highlighted or the visual mode is left
+    fn handle_visual_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        let page_bindings = &self.config.bindings.page;
+        if page_bindings.copy_selection.matches_event(key) {
+            return self.copy_selection();
         }
-
-        if selection_y >= self.viewport.bottom() {
-            self.scroll_to_y(selection_y.saturating_sub(self.viewport.height) + 1);
+        if page_bindings.add_highlight.matches_event(key) {
+            return self.highlight_visual_selection();
+        }
+        if page_bindings.visual_mode.matches_event(key)
+            || self.config.bindings.global.pop_popup.matches_event(key)
+        {
+            self.visual = None;
+            return ActionResult::consumed();
         }
+
+        let cursor = match self.visual {
+            Some(visual) => visual.cursor,
+            None => return ActionResult::Ignored,
+        };
+        let half = (self.viewport.height / 2).max(1) as usize;
+        let global_bindings = &self.config.bindings.global;
+        let y = if global_bindings.scroll_down.matches_event(key) {
+            cursor + 1
+        } else if global_bindings.scroll_up.matches_event(key) {
+            cursor.saturating_sub(1)
+        } else if global_bindings.half_down.matches_event(key) {
+            cursor + half
+        } else if global_bindings.half_up.matches_event(key) {
+            cursor.saturating_sub(half)
+        } else if global_bindings.scroll_to_top.matches_event(key) {
+            0
+        } else if global_bindings.scroll_to_bottom.matches_event(key) {
+            usize::MAX
+        } else {
+            return ActionResult::consumed();
+        };
+        self.move_visual_cursor(y);
+        ActionResult::consumed()
     }
 
-    fn open_link(&self) -> ActionResult {
-        let index = self.selected.0;
-        let node = Node::new(&self.page.content, index).unwrap();
-        let data = node.data().to_owned();
+    /// Handles the keys of the source view. All other keys are ignored, so the source can still be
+    /// scrolled
+    fn handle_source_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        let page_bindings = &self.config.bindings.page;
+        if page_bindings.view_source.matches_event(key) || page_bindings.pop_page.matches_event(key)
+        {
+            return Action::Page(PageAction::ViewSource).into();
+        }
+        if page_bindings.toggle_source_wrap.matches_event(key) {
+            return Action::Page(PageAction::ToggleSourceWrap).into();
+        }
 
-        let link = match data {
-            Data::Link(link) => link,
-            _ => {
-                warn!("tried to open an element that is not a link");
-                return ActionResult::Ignored;
-            }
-        };
+        if page_bindings.select_prev_link.matches_event(key)
+            || page_bindings.scroll_left.matches_event(key)
+        {
+            return Action::Page(PageAction::ScrollSideways(false)).into();
+        }
+        if page_bindings.select_next_link.matches_event(key)
+            || page_bindings.scroll_right.matches_event(key)
+        {
+            return Action::Page(PageAction::ScrollSideways(true)).into();
+        }
 
-        match link {
-            Link::Internal(_) | Link::Anchor(_) => (),
-            Link::External(link_data) => return Action::PopupMessage(
-                "Warning".to_string(), 
-                format!("This link doesn't point to another page. \nInstead, it leads to the following external webpage: \n\n{}", link_data.url.as_str())
-            ).into(),
-            Link::RedLink(link_data) => return Action::PopupMessage(
-                "Information".to_string(), 
-                format!("The page '{}' doesn't exist yet", link_data.title)
-            ).into(),
-            Link::MediaLink(_) | Link::ExternalToInternal(_) => {
-                info!("tried to open an unsupported link '{:?}'", link);
-                return Action::PopupMessage(
-                    "Information".to_string(), 
-                    "This type of link is not supported yet".to_string()
-                ).into()
-            }
+        if page_bindings.search.matches_event(key) {
+            return self.open_search_prompt();
+        }
+        if page_bindings.next_match.matches_event(key) {
+            return Action::Page(PageAction::SearchNext).into();
+        }
+        if page_bindings.prev_match.matches_event(key) {
+            return Action::Page(PageAction::SearchPrev).into();
         }
 
-        Action::PopupDialog(
-            "Information".to_string(),
-            format!(
-                "Do you want to open the page '{}'",
-                link.title().unwrap_or("UNKNOWN")
-            ),
-            Box::<ActionPacket>::new(Action::LoadLink(link).into()),
-        )
-        .into()
+        ActionResult::Ignored
     }
 
-    fn resize(&mut self, width: u16, height: u16) {
-        self.viewport.width = width;
-        self.viewport.height = height;
+    /// Flushes the cached renders, which also have to be searched again
+    fn flush_render_cache(&mut self) {
+        debug!("flushing '{}' cached renders", self.render_cache.len());
+        self.render_cache.clear();
+        self.render_job = None;
+        self.search.width = 0;
+        // the wide lines the page was scrolled along may be wrapped or gone in the next render
+        self.viewport.x = 0;
     }
 
-    fn render_status_bar(&self, f: &mut Frame<'_>, area: Rect) -> Rect {
-        let (area, status_area) = {
-            let splits = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Percentage(100), Constraint::Min(1)])
-                .split(padded_rect(area, 1, 1));
-            (splits[0], splits[1])
-        };
+    fn is_highlighted(&self, index: usize) -> bool {
+        self.highlight_ranges
+            .iter()
+            .flatten()
+            .any(|(first, last)| *first <= index && index <= *last)
+    }
 
-        let status_msg = format!(
-            " wiki-tui | Page '{}' | Language '{}' | '{}' other languages available",
-            self.page.title,
-            self.page.language.name(),
-            self.page.available_languages().unwrap_or_default()
-        );
-        f.render_widget(
-            self.theme.default_paragraph(status_msg).style(
-                Style::default()
-                    .fg(self.theme.status_bar_fg)
-                    .bg(self.theme.status_bar_bg),
-            ),
-            status_area,
-        );
+    /// Returns whether the search prompt is open and receives all keys
+    pub fn is_searching(&self) -> bool {
+        self.search_input.is_some()
+    }
 
-        area
+    fn open_search_prompt(&mut self) -> ActionResult {
+        self.search_input = Some(Input::default());
+        self.search.clear();
+        self.search_history.reset();
+        ActionResult::consumed()
     }
 
-    fn render_toc(&mut self, f: &mut Frame<'_>, area: Rect) -> Rect {
-        if self.config.page.toc.enabled {
-            let mut constraints = [
-                Constraint::Percentage(
-                    100_u16.saturating_sub(self.config.page.toc.width_percentage),
-                ),
-                Constraint::Percentage(self.config.page.toc.width_percentage),
-            ];
+    fn handle_search_input(&mut self, key: KeyEvent) -> ActionResult {
+        let page_bindings = &self.config.bindings.page;
+        if page_bindings.toggle_search_regex.matches_event(key) {
+            self.search.regex = !self.search.regex;
+            self.update_search_matches();
+            return ActionResult::consumed();
+        }
 
-            if self.config.page.toc.position == TocConfigPosition::Left {
-                constraints.reverse();
+        if page_bindings.toggle_search_whole_word.matches_event(key) {
+            self.search.whole_word = !self.search.whole_word;
+            self.update_search_matches();
+            return ActionResult::consumed();
+        }
+
+        let global_bindings = &self.config.bindings.global;
+        if global_bindings.submit.matches_event(key) {
+            self.search_input = None;
+            if self.search.query.is_empty() {
+                return ActionResult::consumed();
             }
+            self.search_history.add(&self.search.query);
+            return Action::Page(PageAction::SearchNext).into();
+        }
 
-            let splits = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints(constraints)
-                .split(area);
+        if global_bindings.pop_popup.matches_event(key) {
+            self.search_input = None;
+            self.search.clear();
+            return ActionResult::consumed();
+        }
 
-            match self.config.page.toc.position {
-                TocConfigPosition::Left => {
-                    self.render_contents(f, splits[0]);
-                    splits[1]
+        if let Some(ref mut input) = self.search_input {
+            if global_bindings.history_prev.matches_event(key) {
+                if let Some(query) = self.search_history.recall_prev(input.value()) {
+                    *input = Input::new(query);
                 }
-                TocConfigPosition::Right => {
-                    self.render_contents(f, splits[1]);
-                    splits[0]
+            } else if global_bindings.history_next.matches_event(key) {
+                if let Some(query) = self.search_history.recall_next() {
+                    *input = Input::new(query);
                 }
+            } else {
+                self.search_history.reset();
+                input.handle_event(&Event::Key(key));
             }
-        } else {
-            area
-        }
-    }
-
-    fn render_scrollbar(&mut self, f: &mut Frame<'_>, area: Rect, content_length: usize) {
-        let scrollbar = Scrollbar::default()
-            .begin_symbol(None)
-            .end_symbol(None)
-            .track_symbol(Some(" "))
-            .track_style(
-                Style::new()
-                    .fg(self.theme.scrollbar_track_fg)
-                    .bg(self.theme.scrollbar_track_fg),
-            )
-            .thumb_style(Style::new().fg(self.theme.scrollbar_thumb_fg))
-            .orientation(ScrollbarOrientation::VerticalRight);
-        let mut scrollbar_state =
-            ScrollbarState::new(content_length).position(self.viewport.top() as usize);
-        f.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
-    }
-}
 
-impl Component for PageComponent {
-    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
-        let page_bindings = &self.config.bindings.page;
-        macro_rules! matches_binding {
-            ($binding:ident, $action:expr) => {
-                if page_bindings.$binding.matches_event(key) {
-                    return $action.into();
-                }
-            };
+            if input.value() != self.search.query {
+                self.search.query = input.value().to_string();
+                self.update_search_matches();
+            }
         }
-        matches_binding!(toggle_toc, Action::Page(PageAction::ToggleContents));
+        ActionResult::consumed()
+    }
 
-        if self.is_contents {
-            matches_binding!(jump_to_header, {
-                let header = self.selected_header();
-                if header.is_none() {
-                    info!("no header selected");
-                    return ActionResult::Ignored;
-                }
-                ActionPacket::single(Action::Page(PageAction::GoToHeader(
-                    header.unwrap().anchor.to_string(),
-                )))
-                .action(Action::Page(PageAction::ToggleContents))
-            });
-            return ActionResult::Ignored;
+    /// Searches the page rendered with the current width for the query
+    fn update_search_matches(&mut self) {
+        self.search.matches.clear();
+        self.search.width = self.viewport.width;
+        self.search.current = None;
+        self.search.error = None;
+        if self.search.query.is_empty() {
+            return;
         }
 
-        matches_binding!(select_first_link, Action::Page(PageAction::SelectFirstLink));
-        matches_binding!(select_last_link, Action::Page(PageAction::SelectLastLink));
-
-        matches_binding!(select_prev_link, Action::Page(PageAction::SelectPrevLink));
-        matches_binding!(select_next_link, Action::Page(PageAction::SelectNextLink));
+        let pattern = match search_pattern(
+            &self.search.query,
+            self.search.regex,
+            self.search.whole_word,
+        ) {
+            Ok(pattern) => pattern,
+            Err(error) => {
+                // the last line of the error describes the problem, the others repeat the pattern
+                let error = error.to_string();
+                self.search.error = error.lines().last().map(|line| line.trim().to_string());
+                return;
+            }
+        };
 
-        matches_binding!(open_link, self.open_link());
-        matches_binding!(toggle_zen_mode, {
-            self.is_zen_mode = !self.is_zen_mode;
-            ActionResult::Ignored
-        });
+        self.finish_rendering();
+        let page = rendered_page!(self, self.viewport.width);
+        self.search.matches = page.search(&pattern);
+    }
 
-        match key.code {
-            KeyCode::Char('r') if has_modifier!(key, Modifier::CONTROL) => {
-                Action::Page(PageAction::SwitchRenderer(self.renderer.next())).into()
+    /// Jumps to the next match after the current one, or the first match at or after the scroll
+    /// position when no match has been jumped to yet. Wraps around at the end of the page
+    fn search_next(&mut self) {
+        let matches = &self.search.matches;
+        if matches.is_empty() {
+            if !self.search.query.is_empty() {
+                info!("no matches for '{}'", self.search.query);
+            }
+            return;
+        }
+
+        let next = match self.search.current {
+            Some(current) => current + 1,
+            None => matches.partition_point(|m| m.line < self.viewport.y as usize),
+        };
+        self.jump_to_match(next % matches.len());
+    }
+
+    /// Jumps to the match before the current one, or the last match before the scroll position
+    /// when no match has been jumped to yet. Wraps around at the start of the page
+    fn search_prev(&mut self) {
+        let matches = &self.search.matches;
+        if matches.is_empty() {
+            if !self.search.query.is_empty() {
+                info!("no matches for '{}'", self.search.query);
+            }
+            return;
+        }
+
+        let current = match self.search.current {
+            Some(current) => current,
+            None => matches.partition_point(|m| m.line < self.viewport.y as usize),
+        };
+        self.jump_to_match(current.checked_sub(1).unwrap_or(matches.len() - 1));
+    }
+
+    fn jump_to_match(&mut self, idx: usize) {
+        let y = self.search.matches[idx].line as u16;
+        let column = self.search.matches[idx].columns.start as u16;
+        self.search.current = Some(idx);
+
+        let x = self.scroll_x();
+        if column < x || column >= x + self.viewport.width {
+            self.scroll_to_x(column.saturating_sub(self.viewport.width / 2));
+        }
+
+        if !self.visible_lines().contains(&y) {
+            self.scroll_to_y(y);
+        }
+    }
+
+    pub fn is_zen_mode(&self) -> bool {
+        self.is_zen_mode
+    }
+
+    /// Keeps the first visible word at the top when the page is rendered with another width
+    fn keep_first_visible_word(&mut self) {
+        self.pending_word = match self.viewport.y {
+            0 => None,
+            y => self
+                .rendered_page(self.viewport.width)
+                .and_then(|page| page.word_position(&self.page.content, y as usize)),
+        };
+    }
+
+    /// Enters or leaves the zen mode. The page is wrapped at another width, so the first visible
+    /// word stays at the top instead of the line
+    fn toggle_zen_mode(&mut self) {
+        self.keep_first_visible_word();
+        self.is_zen_mode = !self.is_zen_mode;
+    }
+
+    /// Limits the width of the content, keeping the first visible word at the top
+    fn set_max_width(&mut self, max_width: u16) {
+        self.keep_first_visible_word();
+        self.display.max_width = max_width;
+
+        let notice = match max_width {
+            0 => "Removed the width limit".to_string(),
+            _ => format!("Limited the width to {max_width} columns"),
+        };
+        self.notice = Some((notice, Instant::now()));
+    }
+
+    /// Returns the width of the table of contents in columns when the area is split
+    fn toc_columns(&self, width: u16) -> u16 {
+        let columns = match self.toc_width {
+            Some(toc_width) => (width as u32 * toc_width as u32 + 500) / 1000,
+            None => width as u32 * self.config.page.toc.width_percentage as u32 / 100,
+        } as u16;
+        clamp_toc_columns(columns, width)
+    }
+
+    /// Moves the divider between the page and the table of contents by a column. The width is
+    /// kept for the other pages, the first visible word stays at the top
+    fn move_divider(&mut self, is_right: bool) -> ActionResult {
+        let width = self.toc_split_width;
+        if width == 0 {
+            return ActionResult::consumed();
+        }
+
+        // moving the divider to the right grows a table of contents on the left
+        let is_growing = is_right == (self.config.page.toc.position == TocConfigPosition::Left);
+        let columns = self.toc_columns(width);
+        let moved = match is_growing {
+            true => clamp_toc_columns(columns + 1, width),
+            false => clamp_toc_columns(columns.saturating_sub(1), width),
+        };
+        if moved == columns {
+            self.notice = Some((
+                "The table of contents can't be moved further".to_string(),
+                Instant::now(),
+            ));
+            return ActionResult::consumed();
+        }
+
+        let toc_width = ((moved as u32 * 1000 + width as u32 / 2) / width as u32) as u16;
+        self.keep_first_visible_word();
+        self.toc_width = Some(toc_width);
+        self.notice = Some((
+            format!("Table of contents at {}%", (toc_width as u32 + 5) / 10),
+            Instant::now(),
+        ));
+        Action::PageViewer(PageViewerAction::TocWidthChanged(toc_width)).into()
+    }
+
+    /// Shows or hides the scrollbar, in zen mode only for the zen mode. The page gets wider or
+    /// narrower by the margin of the scrollbar, so the cached renders are flushed
+    fn toggle_scrollbar(&mut self) {
+        let is_shown = self.is_shown(ZenModeComponents::SCROLLBAR);
+        if self.is_zen_mode {
+            self.zen_mode.set(ZenModeComponents::SCROLLBAR, !is_shown);
+            if !is_shown {
+                self.display.hidden.remove(ZenModeComponents::SCROLLBAR);
+            }
+        } else {
+            self.display
+                .hidden
+                .set(ZenModeComponents::SCROLLBAR, is_shown);
+        }
+
+        self.keep_first_visible_word();
+        self.flush_render_cache();
+    }
+
+    /// Returns whether the component should be shown, taking the zen-mode and the display
+    /// options of the page into account
+    pub fn is_shown(&self, component: ZenModeComponents) -> bool {
+        if self.display.hidden.contains(component.clone()) {
+            return false;
+        }
+
+        !self.is_zen_mode || self.zen_mode.contains(component)
+    }
+
+    /// Returns whether the node is hidden in a folded section
+    fn is_folded_away(&self, index: usize) -> bool {
+        self.folded
+            .iter()
+            .any(|section| fold_hides(&self.page.content, *section, index))
+    }
+
+    /// Unfolds the sections hiding the node
+    fn unfold_around(&mut self, index: usize) {
+        let len = self.folded.len();
+        let document = &self.page.content;
+        self.folded
+            .retain(|section| !fold_hides(document, *section, index));
+        if self.folded.len() != len {
+            self.flush_render_cache();
+        }
+    }
+
+    /// Folds or unfolds the section at the top of the viewport and scrolls to its header
+    fn toggle_fold(&mut self) {
+        let page = rendered_page!(self, self.viewport.width);
+        let header = page
+            .section_at(&self.page.content, self.viewport.y as usize)
+            .and_then(|section| {
+                section_header(self.page.content.nth(section)?).map(|header| (section, header))
+            });
+        let (section, header) = match header {
+            Some((section, header)) => (section, header.index()),
+            None => {
+                self.notice = Some(("There is no section to fold".to_string(), Instant::now()));
+                return;
+            }
+        };
+
+        if !self.folded.remove(&section) {
+            self.folded.insert(section);
+        }
+        self.flush_render_cache();
+        self.scroll_to_node(header);
+    }
+
+    /// Folds every section and scrolls to the header of the outermost section at the top of the
+    /// viewport
+    fn fold_all(&mut self) {
+        if self.page.content.nth(0).is_none() {
+            return self.notice_no_content();
+        }
+        let page = rendered_page!(self, self.viewport.width);
+        let top_header = page
+            .section_at(&self.page.content, self.viewport.y as usize)
+            .and_then(|section| self.page.content.nth(section))
+            .and_then(|section| {
+                std::iter::once(section)
+                    .chain(section.ancestors())
+                    .filter_map(section_header)
+                    .last()
+            })
+            .map(|header| header.index());
+
+        self.folded = self
+            .page
+            .content
+            .nth(0)
+            .into_iter()
+            .flat_map(|root| root.descendants())
+            .filter(|node| section_header(*node).is_some())
+            .map(|node| node.index())
+            .collect();
+        self.flush_render_cache();
+        match top_header {
+            Some(header) => self.scroll_to_node(header),
+            None => self.scroll_to_y(self.viewport.y),
+        }
+    }
+
+    /// Unfolds every section, keeping the first visible word at the top
+    fn unfold_all(&mut self) {
+        self.keep_first_visible_word();
+        self.folded.clear();
+        self.flush_render_cache();
+    }
+
+    /// Returns the action of the first binding of the key in the sections of the keymap
+    fn bound_action(&self, sections: &[&str], key: KeyEvent) -> Option<Action> {
+        sections
+            .iter()
+            .flat_map(|section| self.config.bindings.keymap(section))
+            .filter(|(.., binding)| binding.matches_event(key))
+            .find_map(|(name, ..)| Action::from_binding(name))
+    }
+
+    /// Handles the key following the prefix of a key sequence. When the key doesn't complete a
+    /// sequence, the binding of the prefix runs before the key is handled as usual
+    fn complete_sequence(&mut self, prefix: char, key: KeyEvent) -> ActionResult {
+        let bound = self
+            .config
+            .bindings
+            .keymap("page")
+            .into_iter()
+            .filter(|(.., binding)| binding.matches_sequence(prefix, key))
+            .find_map(|(name, ..)| Action::from_binding(name));
+        if let Some(action) = bound {
+            return action.into();
+        }
+
+        let mut packet = ActionPacket::default();
+        if let Some(action) = self.released_prefix_action(prefix) {
+            packet.add_action(action);
+        }
+        // the key isn't passed on to the global bindings once the prefix consumed it
+        match self.handle_key_events(key) {
+            ActionResult::Consumed(result) => packet.append(result),
+            ActionResult::Ignored => match self.bound_action(&["global"], key) {
+                Some(action) => packet.add_action(action),
+                None if packet.is_empty() => return ActionResult::Ignored,
+                None => {}
+            },
+        }
+        packet.into()
+    }
+
+    /// Returns the action of the binding of the prefix on its own, which runs when no key
+    /// completed the sequence, like the zen mode for `z` and scrolling to the top for `g`
+    fn released_prefix_action(&self, prefix: char) -> Option<Action> {
+        self.bound_action(&["page", "global"], KeyEvent::from(KeyCode::Char(prefix)))
+    }
+
+    /// Returns the action of the prefix of a key sequence that timed out since the last call
+    pub fn take_released_action(&mut self) -> Option<Action> {
+        self.released_action.take()
+    }
+
+    /// Jumps to the section of the kind, or tells that the page doesn't have one
+    fn go_to_section_kind(&mut self, kind: SectionKind) {
+        match self.page.section_of_kind(kind) {
+            Some(section) => self.select_header(section.anchor.clone()),
+            None => {
+                self.notice = Some((format!("The page has no {} section", kind), Instant::now()))
+            }
+        }
+    }
+
+    /// Searches the site for the words of the selected link, like `K` in vim. The page stays in
+    /// the history, so it's still there after opening a result
+    fn lookup_word(&mut self) -> ActionResult {
+        let query =
+            rendered_page!(self, self.viewport.width).nodes_text(self.selected.0..=self.selected.1);
+        if self.selected_link().is_none() || query.is_empty() {
+            self.notice = Some((
+                "Select a link to search for its words".to_string(),
+                Instant::now(),
+            ));
+            return ActionResult::consumed();
+        }
+
+        let search = match self.config.page.lookup {
+            LookupMode::Search => SearchAction::StartSearch(query),
+            LookupMode::Lucky => SearchAction::StartLuckySearch(query),
+        };
+        ActionPacket::single(Action::SwitchContextSearch)
+            .action(Action::Search(search))
+            .into()
+    }
+
+    /// Asks for another site to search for the selected section of the table of contents, or for
+    /// the words of the selected link. Looks up a word on Wiktionary, for example
+    fn search_other_site(&mut self) -> ActionResult {
+        let query = match self.is_contents {
+            true => match self.selected_contents_entry() {
+                Some(ContentsEntry::Introduction) => self.page.title.clone(),
+                Some(ContentsEntry::Section(section)) => section.text.trim().to_string(),
+                None => String::new(),
+            },
+            false if self.selected_link().is_some() => rendered_page!(self, self.viewport.width)
+                .nodes_text(self.selected.0..=self.selected.1),
+            false => String::new(),
+        };
+        if query.is_empty() {
+            let message = match self.is_contents {
+                true => "Select a section to search for it",
+                false => "Select a link to search for its words",
+            };
+            self.notice = Some((message.to_string(), Instant::now()));
+            return ActionResult::consumed();
+        }
+        Action::ShowSiteSearch(query).into()
+    }
+
+    /// Copies the text of the section at the top of the viewport to the clipboard, up to the
+    /// header of its first subsection
+    fn copy_section(&mut self) -> ActionResult {
+        self.finish_rendering();
+        let page = rendered_page!(self, self.viewport.width);
+        let section = page
+            .word_position(&self.page.content, self.viewport.y as usize)
+            .and_then(|(index, _)| self.page.content.nth(index))
+            .and_then(|node| {
+                std::iter::once(node)
+                    .chain(node.ancestors())
+                    .find(|node| matches!(node.data(), Data::Section { .. }))
+            });
+        let section = match section {
+            Some(section) => section,
+            None => {
+                self.notice = Some(("There is no section to copy".to_string(), Instant::now()));
+                return ActionResult::consumed();
+            }
+        };
+
+        let text = nodes_to_text(
+            section
+                .children()
+                .filter(|child| !matches!(child.data(), Data::Section { .. })),
+        );
+        if let Err(error) = clipboard::copy(&text) {
+            let error = error.context("Unable to copy the section");
+            warn!("{:?}", error);
+            return Action::ShowMessage(MessageLevel::Error, error.to_string()).into();
+        }
+
+        self.notice = Some((
+            format!("Copied {} characters", text.chars().count()),
+            Instant::now(),
+        ));
+        ActionResult::consumed()
+    }
+
+    fn render_page(&mut self, width: u16) {
+        // only one width is rendered in chunks, the incomplete render of another one is dropped
+        if let Some(job) = self.render_job.take() {
+            debug!("cancelling the render of the width '{}'", job.width);
+            self.render_cache.remove(&job.width);
+        }
+
+        if let (Some(source), Some(view)) = (self.source.as_ref(), self.source_view.as_ref()) {
+            let page = RenderedDocument::from_text(
+                source,
+                Style::default().fg(self.theme.fg),
+                view.wrap.then_some(width),
+            );
+            self.render_cache.insert(width, page);
+            return;
+        }
+
+        match top_level_sections(&self.page.content) {
+            Some(mut sections) => {
+                sections.reverse();
+                self.render_cache.insert(width, RenderedDocument::default());
+                self.render_job = Some(RenderJob { width, sections });
+                self.render_chunk(RENDER_CHUNK_DURATION);
+            }
+            None => {
+                let page = match self.wrap_node(0, width) {
+                    Some(page) => page,
+                    None => self.renderers[self.renderer].render(&self.page.content, width),
+                };
+                let page = self.arrange_rendered(page, width);
+                self.render_cache.insert(width, page);
+            }
+        }
+    }
+
+    /// Wraps the words of the node, the whole document or one of its top-level sections, at the
+    /// width. The words are prepared when the node is wrapped for the first time. `None` when the
+    /// renderer can only render whole documents
+    fn wrap_node(&mut self, node: usize, width: u16) -> Option<RenderedDocument> {
+        let prepared = match self.prepared.entry(node) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(self.renderers[self.renderer].prepare(&self.page.content, node)?)
+            }
+        };
+        Some(prepared.wrap(&self.page.content, width))
+    }
+
+    /// Renders the next sections of the page that's rendered in chunks until the duration is used
+    /// up, at least one section is rendered
+    fn render_chunk(&mut self, duration: Duration) {
+        let mut job = match self.render_job.take() {
+            Some(job) => job,
+            None => return,
+        };
+
+        let started = Instant::now();
+        while let Some(section) = job.sections.pop() {
+            let chunk = match self.wrap_node(section, job.width) {
+                Some(chunk) => chunk,
+                // the renderer can only render the whole document
+                None => {
+                    let page = self.renderers[self.renderer].render(&self.page.content, job.width);
+                    let page = self.arrange_rendered(page, job.width);
+                    self.render_cache.insert(job.width, page);
+                    return;
+                }
+            };
+            let chunk = self.arrange_rendered(chunk, job.width);
+            if let Some(page) = self.render_cache.get_mut(&job.width) {
+                page.append(chunk);
+            }
+
+            if started.elapsed() >= duration {
+                break;
+            }
+        }
+
+        if !job.sections.is_empty() {
+            self.render_job = Some(job);
+        }
+    }
+
+    /// Renders the whole page for the width of the viewport at once, including the rest of a page
+    /// that's rendered in chunks. Needed for jumping to positions that might not be rendered yet
+    fn finish_rendering(&mut self) {
+        if self.rendered_page(self.viewport.width).is_none() {
+            self.render_page(self.viewport.width);
+        }
+        self.render_chunk(Duration::MAX);
+    }
+
+    /// Returns whether the page is rendered in chunks and not finished yet
+    pub fn is_rendering(&self) -> bool {
+        self.render_job.is_some()
+    }
+
+    /// Folds the sections, reserves the lines of the images and reorders the lines of
+    /// right-to-left pages in a rendered page or a chunk of it
+    fn arrange_rendered(&self, page: RenderedDocument, width: u16) -> RenderedDocument {
+        let page = page.fold(
+            &self.page.content,
+            &self.folded,
+            Style::default()
+                .fg(self.theme.inactive_fg)
+                .add_modifier(Modifier::DIM),
+        );
+        let page = match self.image_protocol {
+            ImageProtocol::None => page,
+            _ if self.renderers[self.renderer].is_plain() => page,
+            _ => page.reserve_images(&self.page.content, self.config.page.images.rows, |image| {
+                !matches!(self.images.get(image), Some(ImageState::Failed))
+            }),
+        };
+        // screen readers reorder the text themselves
+        match self.is_right_to_left && !self.renderers[self.renderer].is_plain() {
+            true => page.right_to_left(width),
+            false => page,
+        }
+    }
+
+    /// Returns the lines above the top of the page: the title, the short description wrapped to
+    /// the width and the notice of the redirect that led to the page
+    fn header_lines(&self, width: u16, is_plain: bool) -> Vec<Line<'static>> {
+        let styled = |line: Line<'static>, style: Style| match is_plain {
+            true => line,
+            false => line.patch_style(style),
+        };
+        if self.is_viewing_source() {
+            let title = format!("Source of '{}'", self.page.title);
+            return vec![styled(
+                Line::raw(title),
+                Style::default().fg(self.theme.page_title_fg).bold(),
+            )];
+        }
+
+        let mut lines = vec![styled(
+            Line::raw(self.page.title.clone()),
+            Style::default().fg(self.theme.page_title_fg).bold(),
+        )];
+        if let Some(ref description) = self.page.description {
+            let mut style = Style::default().add_modifier(Modifier::DIM | Modifier::ITALIC);
+            if self.theme.page_description_fg != Color::Reset {
+                style = style.fg(self.theme.page_description_fg);
+            }
+            lines.extend(
+                textwrap::wrap(description, width.max(1) as usize)
+                    .into_iter()
+                    .map(|line| styled(Line::raw(line.into_owned()), style)),
+            );
+        }
+        if let Some(ref from) = self.page.redirected_from {
+            let notice = format!("(redirected from {from})");
+            lines.push(styled(
+                Line::raw(notice),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
+        lines
+    }
+
+    /// Draws the images of the figures that are completely in the viewport into the lines reserved
+    /// for them. Images close to the viewport are loaded and the ones far away are forgotten
+    fn render_images(&mut self, f: &mut Frame<'_>, area: Rect) {
+        // the images don't move sideways with the lines, so they're left out while the lines are
+        // scrolled
+        if self.image_protocol == ImageProtocol::None
+            || self.is_viewing_source()
+            || self.viewport.x > 0
+        {
+            return;
+        }
+        let image_areas = match self.rendered_page(area.width) {
+            Some(page) if !page.images.is_empty() => page.images.clone(),
+            _ => return,
+        };
+
+        // the title, the description and the redirect notice move the first lines of the page down
+        let offset = match self.viewport.y == 0 && !self.is_zen_mode {
+            true => self.header_lines(area.width, true).len(),
+            false => 0,
+        };
+        let (top, height) = (self.viewport.y as usize, area.height as usize);
+        let cell = cell_size();
+
+        for image_area in image_areas {
+            let url = match Node::new(&self.page.content, image_area.index).map(|node| node.data())
+            {
+                Some(Data::Figure { image, .. }) => image.clone(),
+                _ => continue,
+            };
+
+            let (start, end) = (image_area.y, image_area.y + image_area.rows as usize);
+            let distance = top
+                .saturating_sub(end)
+                .max(start.saturating_sub(top + height));
+            if distance <= height && !self.images.contains_key(&url) {
+                self.images.insert(url.clone(), ImageState::Loading);
+                self.images_to_load.push(url.clone());
+            } else if distance > height * IMAGE_EVICT_DISTANCE
+                && matches!(self.images.get(&url), Some(ImageState::Loaded { .. }))
+            {
+                self.images.remove(&url);
+            }
+
+            if start + offset < top || end + offset > top + height {
+                continue;
+            }
+            let (image, encoded) = match self.images.get_mut(&url) {
+                Some(ImageState::Loaded { image, encoded }) => (image, encoded),
+                _ => continue,
+            };
+            let cells = image.cells(image_area.rows, area.width, cell);
+            if cells.0 == 0 {
+                continue;
+            }
+            let id = image_area.index as u32 + 1;
+            let sequence = match encoded {
+                Some(encoded) if encoded.cells == cells && encoded.cell == cell => {
+                    encoded.sequence.clone()
+                }
+                _ => {
+                    let sequence: Arc<str> =
+                        image.encode(self.image_protocol, cells, cell, id).into();
+                    *encoded = Some(EncodedImage {
+                        cells,
+                        cell,
+                        sequence: sequence.clone(),
+                    });
+                    sequence
+                }
+            };
+
+            // the image is drawn after the frame, its cells are skipped so the text doesn't
+            // overwrite it
+            let image_area = Rect::new(
+                area.x + (area.width - cells.0) / 2,
+                area.y + (start + offset - top) as u16,
+                cells.0,
+                cells.1,
+            );
+            let buffer = f.buffer_mut();
+            for y in image_area.top()..image_area.bottom() {
+                for x in image_area.left()..image_area.right() {
+                    buffer.get_mut(x, y).set_skip(true);
+                }
+            }
+            let kitty_id = (self.image_protocol == ImageProtocol::Kitty).then_some(id);
+            queue_image(image_area, sequence, kitty_id);
+        }
+    }
+
+    fn rendered_page(&self, width: u16) -> Option<&RenderedDocument> {
+        self.render_cache.get(&width)
+    }
+
+    fn render_contents(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let title = match self.config.page.toc.title {
+            TocConfigTitle::Default => "Contents".to_string(),
+            TocConfigTitle::Article => self.page.title.to_string(),
+            TocConfigTitle::Custom(ref title) => title.to_string(),
+        };
+
+        // `Reset` keeps the colors of the other borders and the text
+        let or = |color: Color, default: Color| match color {
+            Color::Reset => default,
+            color => color,
+        };
+        let sections = self
+            .page
+            .sections
+            .as_ref()
+            .filter(|sections| !sections.is_empty());
+        let border_style = match self.is_contents {
+            true => Style::default()
+                .fg(or(
+                    self.theme.contents_border_focused_fg,
+                    self.theme.border_highlight_fg,
+                ))
+                .bg(self.theme.border_highlight_bg),
+            false => Style::default()
+                .fg(or(self.theme.contents_border_fg, self.theme.border_fg))
+                .bg(self.theme.border_bg),
+        };
+        let block = self
+            .theme
+            .default_block()
+            .title(title)
+            .border_style(border_style);
+
+        let sections = match sections {
+            Some(sections) => sections,
+            None => {
+                f.render_widget(
+                    self.theme
+                        .default_paragraph("No Contents available")
+                        .block(block),
+                    area,
+                );
+                return;
+            }
+        };
+        let inner = block.inner(area);
+        // the symbol is drawn in front of every item, so the items are cut before
+        let symbol = self.theme.contents_highlight_symbol.as_str();
+        let width = inner.width.saturating_sub(symbol.chars().count() as u16);
+        let fg = or(self.theme.contents_fg, self.theme.fg);
+        let toc = &self.config.page.toc;
+        let items = std::iter::once(toc.formatted_item("", &toc.introduction)).chain(
+            sections
+                .iter()
+                .map(|section| toc.formatted_item(&section.number, &section.text)),
+        );
+        let current = self.current_contents_entry();
+        let list = List::new(items.enumerate().map(|(idx, mut item)| {
+            if item.char_indices().nth(width as usize).is_some() {
+                let idx = item
+                    .char_indices()
+                    .nth(width.saturating_sub(3) as usize)
+                    .map_or(0, |(idx, _)| idx);
+                item = item.drain(0..idx).collect::<String>() + "...";
+            }
+            // the entry of the section being read is bold
+            match Some(idx) == current {
+                true => item.fg(fg).bold(),
+                false => item.fg(fg),
+            }
+        }))
+        .block(block)
+        .highlight_symbol(symbol)
+        .highlight_style(
+            Style::default()
+                .fg(self.theme.contents_highlight_fg)
+                .bg(self.theme.contents_highlight_bg)
+                .add_modifier(self.theme.contents_highlight_modifier),
+        );
+        f.render_stateful_widget(list, area, &mut self.contents_state.list_state);
+
+        // the list only shows the sections that fit, a scrollbar on the border shows where the
+        // visible sections are
+        let hidden = (sections.len() + 1).saturating_sub(inner.height as usize);
+        if hidden > 0 {
+            let thumb_symbol = self.theme.scrollbar_thumb_char.to_string();
+            let scrollbar = Scrollbar::default()
+                .begin_symbol(None)
+                .end_symbol(None)
+                .track_symbol(None)
+                .thumb_symbol(&thumb_symbol)
+                .thumb_style(Style::new().fg(self.theme.scrollbar_thumb_fg))
+                .orientation(ScrollbarOrientation::VerticalRight);
+            let mut scrollbar_state =
+                ScrollbarState::new(hidden).position(self.contents_state.list_state.offset());
+            f.render_stateful_widget(
+                scrollbar,
+                area.inner(&Margin {
+                    vertical: 1,
+                    horizontal: 0,
+                }),
+                &mut scrollbar_state,
+            );
+        }
+    }
+
+    fn switch_renderer(&mut self, renderer: usize) {
+        if renderer >= self.renderers.len() {
+            warn!("unknown renderer '{renderer}'");
+            self.messages
+                .push((MessageLevel::Warning, "Unknown renderer".to_string()));
+            return;
+        }
+        // the renderers wrap the nodes into different lines, the first visible word stays at the top
+        self.keep_first_visible_word();
+        self.renderer = renderer;
+
+        self.prepared.clear();
+        self.flush_render_cache();
+        self.selected = (0, 0);
+        self.selected_since = Instant::now();
+    }
+
+    /// Scrolls to the first line of the introduction, skipping the empty lines between it and the
+    /// title
+    fn go_to_introduction(&mut self) {
+        let y = rendered_page!(self, self.viewport.width)
+            .lines
+            .iter()
+            .position(|line| !line.is_empty())
+            .unwrap_or_default();
+        self.scroll_to_y(y as u16);
+    }
+
+    fn select_header(&mut self, anchor: String) {
+        // the page cannot be scrolled before its size is known
+        if self.viewport.width == 0 {
+            self.pending_start_at_lead = false;
+            self.pending_header = Some(anchor);
+            return;
+        }
+
+        let root = match self.page.content.nth(0) {
+            Some(root) => root,
+            None => return self.notice_no_content(),
+        };
+        let header_node = root
+            .descendants()
+            .filter(|node| {
+                if let Data::Header { id, .. } = node.data() {
+                    id == &anchor
+                } else {
+                    false
+                }
+            })
+            .last()
+            .map(|node| node.index());
+
+        match header_node {
+            Some(header_node) => self.scroll_to_node(header_node),
+            None => {
+                warn!("no header with the anchor '{}' could be found", anchor);
+                self.messages.push((
+                    MessageLevel::Warning,
+                    format!("The section '{anchor}' could not be found"),
+                ));
+            }
+        }
+    }
+
+    /// Returns the selected entry of the table of contents. The first entry is the introduction,
+    /// so the sections are offset by one
+    fn selected_contents_entry(&self) -> Option<ContentsEntry<'_>> {
+        let sections = self
+            .page
+            .sections()
+            .filter(|sections| !sections.is_empty())?;
+        match self.contents_state.list_state.selected()? {
+            0 => Some(ContentsEntry::Introduction),
+            idx => sections.get(idx - 1).map(ContentsEntry::Section),
+        }
+    }
+
+    /// Returns the index of the entry of the table of contents of the section at the top of the
+    /// viewport. Above the first header that's the first entry, the introduction
+    fn current_contents_entry(&self) -> Option<usize> {
+        let sections = self.page.sections()?;
+        let page = self.rendered_page(self.viewport.width)?;
+        let anchor = page
+            .section_at(&self.page.content, self.viewport.y as usize)
+            .and_then(|section| section_header(self.page.content.nth(section)?))
+            .and_then(|header| match header.data() {
+                Data::Header { id, .. } => Some(id.clone()),
+                _ => None,
+            });
+        match anchor {
+            Some(anchor) => sections
+                .iter()
+                .position(|section| section.anchor == anchor)
+                .map(|idx| idx + 1),
+            None => Some(0),
+        }
+    }
+
+    /// Shows that the page has no content, for the actions that need some
+    fn notice_no_content(&mut self) {
+        self.notice = Some(("This page has no content".to_string(), Instant::now()));
+    }
+
+    /// Returns the y-Position of the selected element
+    fn selected_y(&self) -> usize {
+        let page = match self.render_cache.get(&self.viewport.width) {
+            Some(page) => page,
+            None => return 0,
+        };
+
+        for (y, line) in page.lines.iter().enumerate() {
+            if line
+                .iter()
+                .any(|word| self.selected.0 <= word.index && self.selected.1 >= word.index)
+            {
+                return y;
+            }
+        }
+
+        0
+    }
+
+    fn select_node(&mut self, idx: usize) {
+        let node = match Node::new(&self.page.content, idx) {
+            Some(node) => node,
+            None => return,
+        };
+
+        let first_index = node.index();
+        let last_index = node.last_child().map(|x| x.index()).unwrap_or(first_index);
+
+        self.selected = (first_index, last_index);
+        self.selected_since = Instant::now();
+    }
+
+    fn selected_node(&self) -> Option<Node> {
+        self.page.content.nth(self.selected.0)
+    }
+
+    fn select_first(&mut self) {
+        let root = match self.page.content.nth(0) {
+            Some(root) => root,
+            None => return self.notice_no_content(),
+        };
+
+        let selectable_node = root.descendants().find(|node| {
+            matches!(node.data(), &Data::Link(_)) && !self.is_folded_away(node.index())
+        });
+
+        if let Some(node) = selectable_node {
+            self.select_node(node.index());
+            self.check_and_update_scrolling();
+            self.preview_reference();
+        }
+    }
+
+    fn select_last(&mut self) {
+        // the link could be in a part of the page that isn't rendered yet
+        self.finish_rendering();
+        let root = match self.page.content.nth(0) {
+            Some(root) => root,
+            None => return self.notice_no_content(),
+        };
+
+        let selectable_node = root
+            .descendants()
+            .filter(|node| {
+                matches!(node.data(), &Data::Link(_))
+                    && node.index() > self.selected.1
+                    && !self.is_folded_away(node.index())
+            })
+            .last();
+
+        if let Some(node) = selectable_node {
+            self.select_node(node.index());
+            self.check_and_update_scrolling();
+            self.preview_reference();
+        }
+    }
+
+    fn select_next(&mut self) {
+        self.finish_rendering();
+        let root = match self.page.content.nth(0) {
+            Some(root) => root,
+            None => return self.notice_no_content(),
+        };
+
+        let selectable_node = root.descendants().find(|node| {
+            matches!(node.data(), &Data::Link(_))
+                && self.selected.1 < node.index()
+                && !self.is_folded_away(node.index())
+        });
+
+        if let Some(node) = selectable_node {
+            self.select_node(node.index());
+            self.check_and_update_scrolling();
+            self.preview_reference();
+        }
+    }
+
+    fn select_prev(&mut self) {
+        let root = match self.page.content.nth(0) {
+            Some(root) => root,
+            None => return self.notice_no_content(),
+        };
+
+        let selectable_node = root
+            .descendants()
+            .filter(|node| {
+                matches!(node.data(), &Data::Link(_))
+                    && node.index() < self.selected.0
+                    && !self.is_folded_away(node.index())
+            })
+            .last();
+
+        if let Some(node) = selectable_node {
+            self.select_node(node.index());
+            self.check_and_update_scrolling();
+            self.preview_reference();
+        }
+    }
+
+    /// Shows the reference of the selected link when it's a reflink
+    fn preview_reference(&mut self) {
+        self.reference_preview = match self.selected_link() {
+            Some(link) if link.is_reference() => Some(ReferencePreview {
+                reflink: self.selected.0,
+                y: self.viewport.y,
+                selected: None,
+            }),
+            _ => None,
+        };
+    }
+
+    /// Returns the reference preview when it's still shown
+    fn shown_reference_preview(&self) -> Option<&ReferencePreview> {
+        self.reference_preview
+            .as_ref()
+            .filter(|preview| preview.reflink == self.selected.0 && preview.y == self.viewport.y)
+    }
+
+    /// Returns the first and last node of the links in the reference of the reflink
+    fn reference_links(&self, reflink: usize) -> Vec<(usize, usize)> {
+        let reference = match self
+            .page
+            .content
+            .nth(reflink)
+            .map(|node| node.data().clone())
+        {
+            Some(Data::Link(Link::Anchor(data))) => self.page.content.reference(&data.anchor),
+            _ => None,
+        };
+        reference
+            .map(|reference| {
+                reference
+                    .descendants()
+                    .filter(|node| matches!(node.data(), Data::Link(_)))
+                    .map(|link| {
+                        let last = link
+                            .last_child()
+                            .map_or(link.index(), |child| child.index());
+                        (link.index(), last)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Selects the next link in the shown reference preview. After its last link, the reflink is
+    /// selected again
+    fn select_reference_link(&mut self) {
+        let preview = match self.shown_reference_preview() {
+            Some(preview) => *preview,
+            None => return,
+        };
+        let links = self.reference_links(preview.reflink);
+        let next = match preview.selected {
+            Some(selected) => links
+                .iter()
+                .skip_while(|link| **link != selected)
+                .nth(1)
+                .copied(),
+            None => links.first().copied(),
+        };
+        if let Some(preview) = self.reference_preview.as_mut() {
+            preview.selected = next;
+        }
+    }
+
+    /// Returns the first and last node of the link of the reference preview drawn at the position
+    /// on the screen
+    fn reference_link_at(&self, column: u16, row: u16) -> Option<(usize, usize)> {
+        self.shown_reference_preview()?;
+        self.reference_links
+            .iter()
+            .find(|(link_row, columns, _)| *link_row == row && columns.contains(&column))
+            .map(|(_, _, link)| *link)
+    }
+
+    /// Jumps to the reference, the position before the jump is restored with the jump back
+    /// binding
+    fn jump_to_reference(&mut self, anchor: String) {
+        let reference = match self.page.content.reference(&anchor) {
+            Some(reference) => reference.index(),
+            None => {
+                warn!("no reference with the anchor '{}' could be found", anchor);
+                self.notice = Some((
+                    "The reference could not be found".to_string(),
+                    Instant::now(),
+                ));
+                return;
+            }
+        };
+
+        self.jump_back = Some(self.position());
+        self.reference_preview = None;
+        self.scroll_to_node(reference);
+        self.notice = Some((
+            format!("Press {} to jump back", self.config.bindings.page.jump_back),
+            Instant::now(),
+        ));
+    }
+
+    fn jump_back(&mut self) {
+        match self.jump_back.take() {
+            Some(position) => self.restore_position(position),
+            None => {
+                self.notice = Some((
+                    "There is no position to jump back to".to_string(),
+                    Instant::now(),
+                ))
+            }
+        }
+    }
+
+    /// Checks if the current link is out of the viewport and moves the selection accordingly. If
+    /// no links could be found in the current viewport, the selection stays as it was
+    fn check_and_update_selection(&mut self) {
+        if !self.config.page.link_selection {
+            return;
+        }
+
+        let page = rendered_page!(self, self.viewport.width);
+
+        let selected_y = self.selected_y() as u16;
+        let selected_node = match self.selected_node() {
+            Some(node) => node,
+            None => return,
+        };
+
+        // a link in a folded section is replaced like a link above the viewport
+        let is_folded_away = self.is_folded_away(selected_node.index());
+        let visible_lines = self.visible_lines();
+        if !is_folded_away && visible_lines.contains(&selected_y) {
+            return;
+        }
+
+        if is_folded_away || selected_y < visible_lines.start {
+            let (_, idx) = page
+                .links
+                .iter()
+                .find(|(y, _)| visible_lines.contains(&(*y as u16)))
+                .map(|x| x.to_owned())
+                .unwrap_or((selected_y as usize, selected_node.index()));
+
+            self.select_node(idx);
+            return;
+        }
+
+        if selected_y >= visible_lines.end {
+            let (_, idx) = page
+                .links
+                .iter()
+                .rev()
+                .find(|(y, _)| visible_lines.contains(&(*y as u16)))
+                .map(|x| x.to_owned())
+                .unwrap_or((selected_y as usize, selected_node.index()));
+
+            self.select_node(idx)
+        }
+    }
+
+    fn scroll_up(&mut self, amount: u16) {
+        if self.is_contents && self.config.page.toc.enable_scrolling {
+            if self.contents_state.max_idx_section == 0 {
+                return;
+            }
+            let i = match self.contents_state.list_state.selected() {
+                Some(i) => {
+                    if i == 0 {
+                        self.contents_state.max_idx_section - 1
+                    } else {
+                        i - 1
+                    }
+                }
+                None => 0,
+            };
+
+            self.contents_state.list_state.select(Some(i));
+            return;
+        }
+
+        self.scroll_to_y(self.viewport.y.saturating_sub(amount));
+    }
+
+    fn scroll_down(&mut self, amount: u16) {
+        if self.is_contents && self.config.page.toc.enable_scrolling {
+            if self.contents_state.max_idx_section == 0 {
+                return;
+            }
+            let i = match self.contents_state.list_state.selected() {
+                Some(i) => {
+                    if i >= self.contents_state.max_idx_section - 1 {
+                        0
+                    } else {
+                        i + 1
+                    }
+                }
+                None => 0,
+            };
+
+            self.contents_state.list_state.select(Some(i));
+            return;
+        }
+
+        self.scroll_to_y(self.viewport.y + amount);
+    }
+
+    /// Scrolls the page to the top, or selects the first entry of the focused table of contents
+    fn scroll_to_top(&mut self) {
+        if self.is_contents && self.config.page.toc.enable_scrolling {
+            if self.contents_state.max_idx_section > 0 {
+                self.contents_state.list_state.select(Some(0));
+            }
+            return;
+        }
+
+        self.scroll_to_y(0);
+    }
+
+    /// Scrolls the page to the bottom, or selects the last entry of the focused table of contents
+    fn scroll_to_bottom(&mut self) {
+        if self.is_contents && self.config.page.toc.enable_scrolling {
+            if let Some(last) = self.contents_state.max_idx_section.checked_sub(1) {
+                self.contents_state.list_state.select(Some(last));
+            }
+            return;
+        }
+
+        self.finish_rendering();
+        let page = rendered_page!(self, self.viewport.width);
+        self.scroll_to_y(page.lines.len() as u16);
+    }
+
+    fn scroll_to_y(&mut self, y: u16) {
+        let page = rendered_page!(self, self.viewport.width);
+        let n_lines = page.lines.len() as u16;
+        self.viewport.y = y;
+
+        if self.viewport.bottom() >= n_lines {
+            self.viewport.y = n_lines.saturating_sub(self.viewport.height);
+        }
+
+        // the source has no links that could be selected
+        if !self.is_viewing_source() {
+            self.check_and_update_selection();
+        }
+    }
+
+    /// Scrolls to the line at the same proportion of the page as the row on the scrollbar. Rows
+    /// above or below the scrollbar, while dragging it, scroll to the top or the bottom
+    fn scroll_to_scrollbar_row(&mut self, row: u16) {
+        let area = match self.scrollbar_area {
+            Some(area) => area,
+            None => return,
+        };
+        let n_lines = rendered_page!(self, self.viewport.width).lines.len();
+        let y = scrollbar_line(
+            row.saturating_sub(area.y),
+            area.height,
+            n_lines,
+            self.viewport.height,
+        );
+        self.scroll_to_y(y);
+    }
+
+    /// Moves the viewport to the lead of the article, unless it's already visible on the first
+    /// screen
+    fn scroll_to_lead(&mut self) {
+        let page = rendered_page!(self, self.viewport.width);
+        if let Some(y) = page.lead_line(&self.page.content, LEAD_MIN_WORDS, self.viewport.height) {
+            debug!("starting at the lead of the article at line '{y}'");
+            self.scroll_to_y(y as u16);
+        }
+    }
+
+    fn scroll_to_node(&mut self, idx: usize) {
+        // the node could be hidden in a folded section
+        self.unfold_around(idx);
+        self.finish_rendering();
+        let page = rendered_page!(self, self.viewport.width);
+        let node = match Node::new(&self.page.content, idx) {
+            Some(node) => node,
+            None => return,
+        };
+        let first_index = idx;
+        let last_index = node.last_child().map(|x| x.index()).unwrap_or(first_index);
+        let y = page.lines.iter().enumerate().find_map(|(y, line)| {
+            line.iter()
+                .find(|word| {
+                    if let Some(node) = word.node(&self.page.content) {
+                        first_index <= node.index() && node.index() <= last_index
+                    } else {
+                        false
+                    }
+                })
+                .map(|_| y)
+        });
+
+        if let Some(y) = y {
+            self.scroll_to_y(y as u16);
+        }
+    }
+
+    /// Checks if the current viewport shows the selected link and if not, moves the viewport so
+    /// the link is visible
+    fn check_and_update_scrolling(&mut self) {
+        let selection_y = self.selected_y() as u16;
+
+        if selection_y < self.viewport.top() {
+            self.scroll_to_y(selection_y);
+            return;
+        }
+
+        if selection_y >= self.visible_lines().end {
+            self.scroll_to_bottom_line(selection_y);
+        }
+    }
+
+    /// Returns the lines of the page shown in the viewport. At the top of the page, the title and
+    /// the other header lines take the place of the last lines of the viewport
+    fn visible_lines(&self) -> Range<u16> {
+        let header = match self.viewport.y == 0 && !self.is_zen_mode {
+            true => self.header_lines(self.viewport.width, true).len() as u16,
+            false => 0,
+        };
+        self.viewport.top()..self.viewport.bottom().saturating_sub(header)
+    }
+
+    /// Scrolls the line below the visible lines to the bottom of the viewport. The header isn't
+    /// shown after scrolling, so the line is never hidden by it
+    fn scroll_to_bottom_line(&mut self, y: u16) {
+        self.scroll_to_y((y + 1).saturating_sub(self.viewport.height).max(1));
+    }
+
+    /// Returns the first and last node of the link drawn at the position on the screen
+    fn link_at(&self, column: u16, row: u16) -> Option<(usize, usize)> {
+        if self.is_viewing_source() {
+            return None;
+        }
+        let x = column.checked_sub(self.page_area.x)?;
+        let y = row.checked_sub(self.page_area.y)?;
+        if x >= self.page_area.width || y >= self.page_area.height {
+            return None;
+        }
+
+        let first = self.word_columns.partition_point(|(row, ..)| *row < y);
+        let (_, _, index) = self.word_columns[first..]
+            .iter()
+            .take_while(|(row, ..)| *row == y)
+            .find(|(_, columns, _)| columns.contains(&x))?;
+        let node = self.page.content.nth(*index)?;
+        let link = std::iter::once(node)
+            .chain(node.ancestors())
+            .find(|node| matches!(node.data(), Data::Link(_)))?;
+        let last = link
+            .last_child()
+            .map_or(link.index(), |child| child.index());
+        Some((link.index(), last))
+    }
+
+    /// Hovers the link at the position of the pointer. The page is only drawn again when the
+    /// pointer moved onto another link or off the link
+    fn hover(&mut self, column: u16, row: u16) -> ActionResult {
+        self.pointer = Some((column, row));
+        let hovered = self.link_at(column, row);
+        if hovered == self.hovered {
+            return ActionResult::Ignored;
+        }
+        self.hovered = hovered;
+        ActionResult::consumed()
+    }
+
+    /// Returns the link under the mouse pointer
+    fn hovered_link(&self) -> Option<Link> {
+        match self.page.content.nth(self.hovered?.0)?.data() {
+            Data::Link(link) => Some(link.to_owned()),
+            _ => None,
+        }
+    }
+
+    /// Returns the selected link, `None` when the selection isn't a link
+    fn selected_link(&self) -> Option<Link> {
+        match Node::new(&self.page.content, self.selected.0)?.data() {
+            Data::Link(link) => Some(link.to_owned()),
+            _ => None,
+        }
+    }
+
+    /// Explains that the page of the red link doesn't exist and offers to search for its title
+    /// instead or, when an interlanguage link follows it, to open the article in that language
+    fn explain_red_link(&self, link: RedLinkData) -> ActionResult {
+        let site = self
+            .config
+            .api
+            .site_of(&self.page.endpoint)
+            .map(|site| site.name.as_str())
+            .unwrap_or("the wiki");
+
+        let mut choices = vec![(
+            's',
+            "Search".to_string(),
+            ActionPacket::single(Action::SwitchContextSearch).action(Action::Search(
+                SearchAction::StartSearch(link.title.clone()),
+            )),
+        )];
+        if let Some(article) = self.interlanguage_article(self.selected.0) {
+            choices.push((
+                'l',
+                format!("Open in {}", article.language.name()),
+                Action::LoadArticleUrl(article).into(),
+            ));
+        }
+
+        Action::PopupChoice(
+            "Information".to_string(),
+            format!(
+                "'{}' does not exist on {} {}",
+                link.title,
+                self.page.language.name(),
+                site
+            ),
+            choices,
+        )
+        .into()
+    }
+
+    /// Returns the article of the interlanguage link following the red link at the index, like
+    /// the `de` in `Iron rust [de]`
+    fn interlanguage_article(&self, index: usize) -> Option<ArticleUrl> {
+        let parent = self.page.content.nth(index)?.parent()?;
+        parent
+            .descendants()
+            .filter(|node| node.index() > index)
+            .filter_map(|node| match node.data() {
+                Data::Link(link) => Some(link.to_owned()),
+                _ => None,
+            })
+            .take_while(|link| matches!(link, Link::External(_) | Link::Interwiki(_)))
+            .find_map(|link| {
+                let url = match link {
+                    Link::External(data) => data.url,
+                    Link::Interwiki(data) => data.url,
+                    _ => return None,
+                };
+                self.config
+                    .api
+                    .resolve_article_url(&url)
+                    .map(|(_, article)| article)
+                    .filter(|article| article.language != self.page.language)
+            })
+    }
+
+    /// Asks to open the page of the url when it belongs to one of the sites, like an internal link
+    fn open_on_site(&self, url: &Url) -> Option<ActionResult> {
+        let (site, article) = self.config.api.resolve_article_url(url)?;
+        Some(
+            Action::PopupDialog(
+                "Information".to_string(),
+                format!(
+                    "Do you want to open the page '{}' on {}",
+                    article.title, site.name
+                ),
+                Box::<ActionPacket>::new(Action::LoadArticleUrl(article).into()),
+            )
+            .into(),
+        )
+    }
+
+    /// Opens a link to another wiki. Pages of the sites and of other languages of the site of the
+    /// page are loaded, the other links are shown with their url
+    fn open_interwiki_link(&self, link: InterwikiData) -> ActionResult {
+        if let Some(result) = self.open_on_site(&link.url) {
+            return result;
+        }
+        if let Some(article) = link.language_article(&self.page.endpoint) {
+            return Action::PopupDialog(
+                "Information".to_string(),
+                format!(
+                    "Do you want to open the page '{}' in {}",
+                    article.title,
+                    article.language.name()
+                ),
+                Box::<ActionPacket>::new(Action::LoadArticleUrl(article).into()),
+            )
+            .into();
+        }
+
+        let wiki = match link.kind {
+            InterwikiKind::SisterProject(name) => name.to_string(),
+            _ => "another wiki".to_string(),
+        };
+        Action::PopupMessage(
+            "Information".to_string(),
+            format!(
+                "The page '{}' is on {}, which isn't one of the sites. It can be opened in the \
+                browser at: \n\n{}",
+                link.title, wiki, link.url
+            ),
+        )
+        .into()
+    }
+
+    /// Opens the selected link, or the selected link of the reference preview
+    fn open_link(&self) -> ActionResult {
+        let preview_link = self
+            .shown_reference_preview()
+            .and_then(|preview| self.page.content.nth(preview.selected?.0))
+            .and_then(|node| match node.data() {
+                Data::Link(link) => Some(link.to_owned()),
+                _ => None,
+            });
+        let link = match preview_link.or_else(|| self.selected_link()) {
+            Some(link) => link,
+            None => {
+                warn!("tried to open an element that is not a link");
+                return ActionResult::Ignored;
+            }
+        };
+
+        if let Link::Anchor(ref data) = link {
+            if link.is_reference() {
+                return Action::Page(PageAction::JumpToReference(data.anchor.clone())).into();
+            }
+        }
+
+        match link {
+            Link::Internal(_) | Link::Anchor(_) => (),
+            Link::External(link_data) => {
+                if let Some(result) = self.open_on_site(&link_data.url) {
+                    return result;
+                }
+                return Action::PopupMessage(
+                "Warning".to_string(), 
+                format!("This link doesn't point to another page. \nInstead, it leads to the following external webpage: \n\n{}", link_data.url.as_str())
+                ).into();
+            }
+            Link::RedLink(link_data) => return self.explain_red_link(link_data),
+            Link::Interwiki(link_data) => return self.open_interwiki_link(link_data),
+            Link::MediaLink(link_data) => {
+                if !self.config.page.media.enabled {
+                    return Action::Page(PageAction::ShowNotice(
+                        "Opening media is disabled".to_string(),
+                    ))
+                    .into();
+                }
+                let title = link_data.file_title();
+                return ActionPacket::single(Action::Page(PageAction::ShowNotice(format!(
+                    "Looking up '{title}'"
+                ))))
+                .action(Action::OpenMedia(self.page.endpoint.clone(), title))
+                .into();
+            }
+            Link::ExternalToInternal(_) => {
+                info!("tried to open an unsupported link '{:?}'", link);
+                return Action::PopupMessage(
+                    "Information".to_string(),
+                    "This type of link is not supported yet".to_string(),
+                )
+                .into();
+            }
+        }
+
+        Action::PopupDialog(
+            "Information".to_string(),
+            format!(
+                "Do you want to open the page '{}'",
+                link.title().unwrap_or("UNKNOWN")
+            ),
+            Box::<ActionPacket>::new(Action::LoadLink(link).into()),
+        )
+        .into()
+    }
+
+    fn resize(&mut self, width: u16, height: u16) {
+        // the renders for the old size are no longer needed. Resizes are coalesced, so this only
+        // happens once the size settled. The first visible word stays at the top
+        self.keep_first_visible_word();
+        self.flush_render_cache();
+        self.viewport.width = width;
+        self.viewport.height = height;
+    }
+
+    /// Returns the area of the page and the line of the status bar below it
+    fn split_status_bar(area: Rect) -> (Rect, Rect) {
+        let splits = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(100), Constraint::Min(1)])
+            .split(area);
+        (splits[0], splits[1])
+    }
+
+    /// Returns the values of the placeholders in the status bar
+    fn status_context(&self) -> StatusContext {
+        let site = match self.config.api.site_of(&self.page.endpoint) {
+            Some(site) => site.name.as_str(),
+            None => self.page.endpoint.host_str().unwrap_or("wiki-tui"),
+        };
+        let scroll_percent = match self.render_cache.get(&self.viewport.width) {
+            Some(page) => match page
+                .lines
+                .len()
+                .saturating_sub(self.viewport.height as usize)
+            {
+                0 => 100,
+                max_y => (self.viewport.y as usize * 100 / max_y).min(100) as u16,
+            },
+            None => 0,
+        };
+        let section = self
+            .viewport_anchor()
+            .and_then(|anchor| {
+                self.page
+                    .sections()?
+                    .iter()
+                    .find(|section| section.anchor == anchor)
+                    .map(|section| section.text.clone())
+            })
+            .unwrap_or_default();
+        // the link under the pointer is shown instead of the selected one
+        let hovered_target = self.hovered_link().map(|link| link_target(&link));
+        let link_target = match hovered_target {
+            Some(ref target) => target.clone(),
+            None => self
+                .selected_link()
+                .map(|link| link_target(&link))
+                .unwrap_or_default(),
+        };
+
+        let renderer = &self.renderers[self.renderer];
+        let renderer = match renderer.node_label(&self.page.content, self.selected) {
+            Some(label) => format!("{} {label}", renderer.name()),
+            None => renderer.name().to_string(),
+        };
+
+        let mut context = StatusContext {
+            site: site.to_string(),
+            kind: match self.is_random {
+                true => "Random page".to_string(),
+                false => "Page".to_string(),
+            },
+            title: self.page.title.clone(),
+            language: self.page.language.name().to_string(),
+            languages_available: self.page.available_languages().unwrap_or_default(),
+            renderer,
+            scroll_percent,
+            reading_time: self.reading_time.clone(),
+            section,
+            link_target,
+            ..Default::default()
+        };
+        let x = self.scroll_x();
+        if x > 0 {
+            context.columns = format!("cols {x}–{}", x + self.viewport.width);
+        }
+        if let Some(fetched) = self.cached {
+            context.cached = format!("cached {}", format_elapsed(now(), fetched));
+        }
+        if self.is_offline {
+            context.offline = "OFFLINE".to_string();
+        }
+        if self.reading_list_len > 0 {
+            context.reading_list = format!("{} to read", self.reading_list_len);
+        }
+        if self.history != (0, 0) {
+            context.history = format!("({} back / {} forward)", self.history.0, self.history.1);
+        }
+        if let Some((notice, _)) = self
+            .notice
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed() < NOTICE_DURATION)
+        {
+            context.notice = notice.clone();
+        } else if let Some(target) =
+            hovered_target.filter(|_| !self.status_template.shows_link_target())
+        {
+            context.notice = format!("→ {target}");
+        } else if let Some(ref job) = self.render_job {
+            let progress = job.progress(self.page.content.nodes.len());
+            context.notice = format!("rendering… {progress}%");
+        }
+        if !self.search.query.is_empty() {
+            context.search = match self.search.current {
+                _ if self.search.error.is_some() => {
+                    format!("Invalid pattern '{}'", self.search.query)
+                }
+                _ if self.search.matches.is_empty() => {
+                    format!("No matches for '{}'", self.search.query)
+                }
+                Some(current) => format!("match {}/{}", current + 1, self.search.matches.len()),
+                None => format!("{} matches", self.search.matches.len()),
+            };
+        }
+        context
+    }
+
+    fn render_status_bar(&self, f: &mut Frame<'_>, status_area: Rect) {
+        if let Some(ref input) = self.search_input {
+            let mut options = String::new();
+            if self.search.regex {
+                options.push_str("[regex] ");
+            }
+            if self.search.whole_word {
+                options.push_str("[word] ");
+            }
+            let prompt_width = options.chars().count() + 1;
+
+            let mut prompt = vec![
+                Span::raw(options).fg(self.theme.inactive_fg),
+                Span::raw(format!("/{}", input.value())),
+            ];
+            if let Some(ref error) = self.search.error {
+                prompt.push(Span::raw(format!("  {error}")).fg(self.theme.red_link_fg));
+            }
+
+            let scroll =
+                input.visual_scroll((status_area.width as usize).saturating_sub(prompt_width));
+            f.render_widget(
+                self.theme
+                    .default_paragraph(Line::from(prompt))
+                    .scroll((0, scroll as u16))
+                    .style(
+                        Style::default()
+                            .fg(self.theme.status_bar_fg)
+                            .bg(self.theme.status_bar_bg),
+                    ),
+                status_area,
+            );
+            f.set_cursor(
+                // Put cursor past the end of the input text, after the prompt
+                status_area.x + (input.visual_cursor().max(scroll) - scroll + prompt_width) as u16,
+                status_area.y,
+            );
+            return;
+        }
+
+        let status_msg = self
+            .status_template
+            .render(&self.status_context(), status_area.width as usize);
+        f.render_widget(
+            self.theme.default_paragraph(status_msg).style(
+                Style::default()
+                    .fg(self.theme.status_bar_fg)
+                    .bg(self.theme.status_bar_bg),
+            ),
+            status_area,
+        );
+    }
+
+    fn render_toc(&mut self, f: &mut Frame<'_>, area: Rect) -> Rect {
+        if self.config.page.toc.enabled {
+            self.toc_split_width = area.width;
+            let toc_columns = self.toc_columns(area.width);
+            let mut constraints = [Constraint::Min(0), Constraint::Length(toc_columns)];
+
+            if self.config.page.toc.position == TocConfigPosition::Left {
+                constraints.reverse();
+            }
+
+            let splits = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(constraints)
+                .split(area);
+
+            match self.config.page.toc.position {
+                TocConfigPosition::Left => {
+                    self.render_contents(f, splits[0]);
+                    splits[1]
+                }
+                TocConfigPosition::Right => {
+                    self.render_contents(f, splits[1]);
+                    splits[0]
+                }
+            }
+        } else {
+            area
+        }
+    }
+
+    /// Renders the reference of the selected reflink at the bottom of the page, its links are
+    /// styled like the links of the page and can be selected and clicked
+    fn render_reference_preview(
+        &mut self,
+        f: &mut Frame<'_>,
+        area: Rect,
+        preview: ReferencePreview,
+        is_plain: bool,
+    ) {
+        let reference = match self.selected_link() {
+            Some(Link::Anchor(data)) => self.page.content.reference(&data.anchor),
+            _ => return,
+        };
+        // the text of the reference as its words, made of pieces with the link they belong to.
+        // Pieces of several nodes without whitespace between them form a single word
+        let mut words: Vec<Vec<ReferencePiece>> = Vec::new();
+        match reference {
+            Some(reference) => {
+                let mut is_word_open = false;
+                for node in reference.descendants() {
+                    let contents = match node.data() {
+                        Data::Text { contents } => contents,
+                        _ => continue,
+                    };
+                    let link = node
+                        .ancestors()
+                        .take_while(|ancestor| ancestor.index() != reference.index())
+                        .find(|ancestor| matches!(ancestor.data(), Data::Link(_)))
+                        .map(|link| {
+                            let last = link
+                                .last_child()
+                                .map_or(link.index(), |child| child.index());
+                            (link.index(), last)
+                        });
+                    for (i, piece) in contents.split(char::is_whitespace).enumerate() {
+                        if i > 0 {
+                            is_word_open = false;
+                        }
+                        if piece.is_empty() {
+                            continue;
+                        }
+                        match words.last_mut().filter(|_| is_word_open) {
+                            Some(word) => word.push((piece.to_string(), link)),
+                            None => words.push(vec![(piece.to_string(), link)]),
+                        }
+                        is_word_open = true;
+                    }
+                }
+            }
+            None => words.extend(
+                "The reference could not be found"
+                    .split(' ')
+                    .map(|word| vec![(word.to_string(), None)]),
+            ),
+        }
+
+        // the words are wrapped here instead of by the paragraph, so the columns of the links are
+        // known
+        let text_width = area.width.saturating_sub(2).max(1) as usize;
+        let mut lines: Vec<Vec<ReferencePiece>> = vec![Vec::new()];
+        let mut line_width = 0;
+        for word in words {
+            let word_width: usize = word.iter().map(|(piece, _)| piece.width()).sum();
+            let line = lines.last_mut().expect("there is always a line");
+            if !line.is_empty() && line_width + 1 + word_width > text_width {
+                lines.push(Vec::new());
+                line_width = 0;
+            }
+            let line = lines.last_mut().expect("there is always a line");
+            if !line.is_empty() {
+                line.push((" ".to_string(), None));
+                line_width += 1;
+            }
+            line.extend(word);
+            line_width += word_width;
+        }
+
+        let max_height = (area.height / 3).max(3).min(area.height);
+        let height = (lines.len() as u16 + 2).min(max_height);
+        let preview_area = Rect {
+            y: area.bottom().saturating_sub(height),
+            height,
+            ..area
+        };
+        let inner = preview_area.inner(&Margin::new(1, 1));
+
+        let mut reference_links = std::mem::take(&mut self.reference_links);
+        reference_links.clear();
+        let lines: Vec<Line> = lines
+            .into_iter()
+            .take(inner.height as usize)
+            .enumerate()
+            .map(|(row, line)| {
+                let mut column = inner.x;
+                let spans: Vec<Span> = line
+                    .into_iter()
+                    .map(|(piece, link)| {
+                        let width = piece.width() as u16;
+                        let mut style = Style::default();
+                        if let Some(link) = link {
+                            reference_links.push((
+                                inner.y + row as u16,
+                                column..column + width,
+                                link,
+                            ));
+                            if !is_plain {
+                                style = style.fg(self.theme.wiki_link_fg);
+                            }
+                            if preview.selected == Some(link) {
+                                style = style.add_modifier(Modifier::UNDERLINED);
+                                if self.theme.selected_link_fg != Color::Reset {
+                                    style = style.fg(self.theme.selected_link_fg);
+                                }
+                            }
+                        }
+                        column += width;
+                        Span::styled(piece, style)
+                    })
+                    .collect();
+                Line::from(spans)
+            })
+            .collect();
+        self.reference_links = reference_links;
+
+        let number = self
+            .selected_node()
+            .map(|node| node.text())
+            .unwrap_or_default();
+        let block = self
+            .theme
+            .default_block()
+            .title(format!("Reference {}", number.trim()))
+            .style(Style::default().bg(self.theme.bg));
+        f.render_widget(Clear, preview_area);
+        f.render_widget(Paragraph::new(lines).block(block), preview_area);
+    }
+
+    fn render_scrollbar(&mut self, f: &mut Frame<'_>, area: Rect, content_length: usize) {
+        let track_symbol = self.theme.scrollbar_track_char.to_string();
+        let thumb_symbol = self.theme.scrollbar_thumb_char.to_string();
+        let scrollbar = Scrollbar::default()
+            .begin_symbol(None)
+            .end_symbol(None)
+            .track_symbol(Some(&track_symbol))
+            .thumb_symbol(&thumb_symbol)
+            .track_style(
+                Style::new()
+                    .fg(self.theme.scrollbar_track_fg)
+                    .bg(self.theme.scrollbar_track_fg),
+            )
+            .thumb_style(Style::new().fg(self.theme.scrollbar_thumb_fg))
+            .orientation(ScrollbarOrientation::VerticalRight);
+        let mut scrollbar_state =
+            ScrollbarState::new(content_length).position(self.viewport.top() as usize);
+        f.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
+    }
+}
+
+impl Component for PageComponent {
+    fn keymaps(&self) -> Vec<Keymap> {
+        page_keymaps(&self.config, self.is_contents)
+    }
+
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        // the styles are baked into the rendered words, so the renderers need the new theme and
+        // the prepared words and cached renders have to be flushed
+        self.renderers = renderers(
+            theme.clone(),
+            self.config
+                .page
+                .render_options(self.page.kind, self.page.language),
+        );
+        self.prepared.clear();
+        self.flush_render_cache();
+
+        self.notice = Some((format!("Theme '{}'", theme.name), Instant::now()));
+        self.theme = theme;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        let is_notice_shown = self
+            .notice
+            .as_ref()
+            .is_some_and(|(_, shown)| shown.elapsed() < NOTICE_DURATION);
+        is_notice_shown || self.sequence_prefix.is_some() || self.is_hover_stale
+    }
+
+    fn handle_mouse_events(&mut self, mouse: MouseEvent) -> ActionResult {
+        match mouse.kind {
+            MouseEventKind::Moved => self.hover(mouse.column, mouse.row),
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(link) = self.reference_link_at(mouse.column, mouse.row) {
+                    if let Some(preview) = self.reference_preview.as_mut() {
+                        preview.selected = Some(link);
+                    }
+                    return self.open_link();
+                }
+                let is_scrollbar = self.scrollbar_area.is_some_and(|area| {
+                    (area.left()..area.right()).contains(&mouse.column)
+                        && (area.top()..area.bottom()).contains(&mouse.row)
+                });
+                if !is_scrollbar {
+                    return ActionResult::Ignored;
+                }
+                self.is_dragging_scrollbar = true;
+                self.scroll_to_scrollbar_row(mouse.row);
+                ActionResult::consumed()
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.is_dragging_scrollbar => {
+                self.scroll_to_scrollbar_row(mouse.row);
+                ActionResult::consumed()
+            }
+            // the page stays at the line the thumb was dragged to
+            MouseEventKind::Up(MouseButton::Left) if self.is_dragging_scrollbar => {
+                self.is_dragging_scrollbar = false;
+                ActionResult::consumed()
             }
             _ => ActionResult::Ignored,
         }
     }
 
-    fn update(&mut self, action: Action) -> ActionResult {
-        match action {
-            Action::Page(page_action) => match page_action {
-                PageAction::SwitchRenderer(renderer) => self.switch_renderer(renderer),
-                PageAction::ToggleContents => self.is_contents = !self.is_contents,
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.is_searching() {
+            return self.handle_search_input(key);
+        }
+        if self.is_viewing_source() {
+            return self.handle_source_key_events(key);
+        }
+        if self.visual.is_some() {
+            return self.handle_visual_key_events(key);
+        }
+
+        // sections are folded and copied with bindings like `za` and `ys`, like in vim, and `gr`
+        // and `ge` jump to the references and external links. The `g` prefix is also the default
+        // binding of scrolling to the top, which runs when no key follows
+        if let Some((prefix, _)) = self.sequence_prefix.take() {
+            return self.complete_sequence(prefix, key);
+        } else if let KeyCode::Char(c) = key.code {
+            let is_prefix = self.config.bindings.page.sequence_prefixes().contains(&c);
+            if !self.is_contents && is_prefix && key.modifiers.is_empty() {
+                self.sequence_prefix = Some((c, Instant::now()));
+                return ActionResult::consumed();
+            }
+        }
+
+        let page_bindings = &self.config.bindings.page;
+        macro_rules! matches_binding {
+            ($binding:ident, $action:expr) => {
+                if page_bindings.$binding.matches_event(key) {
+                    return $action.into();
+                }
+            };
+        }
+        matches_binding!(toggle_toc, Action::Page(PageAction::ToggleContents));
+
+        if self.is_contents {
+            matches_binding!(jump_to_header, {
+                let action = match self.selected_contents_entry() {
+                    Some(ContentsEntry::Introduction) => Action::Page(PageAction::GoToIntroduction),
+                    Some(ContentsEntry::Section(section)) => {
+                        Action::Page(PageAction::GoToHeader(section.anchor.to_string()))
+                    }
+                    None => {
+                        info!("no header selected");
+                        self.notice =
+                            Some(("There is no section to jump to".to_string(), Instant::now()));
+                        return ActionResult::consumed();
+                    }
+                };
+                ActionPacket::single(action).action(Action::Page(PageAction::ToggleContents))
+            });
+
+            matches_binding!(search_other_site, Action::Page(PageAction::SearchOtherSite));
+
+            // select the first and the last entry
+            let global_bindings = &self.config.bindings.global;
+            if global_bindings.scroll_to_top.matches_event(key) {
+                return Action::ScrollToTop.into();
+            }
+            if global_bindings.scroll_to_bottom.matches_event(key) {
+                return Action::ScrollToBottom.into();
+            }
+            return ActionResult::Ignored;
+        }
+
+        // without link selection, the keys are left to other components
+        if self.config.page.link_selection {
+            matches_binding!(select_first_link, Action::Page(PageAction::SelectFirstLink));
+            matches_binding!(select_last_link, Action::Page(PageAction::SelectLastLink));
+
+            matches_binding!(select_prev_link, Action::Page(PageAction::SelectPrevLink));
+            matches_binding!(select_next_link, Action::Page(PageAction::SelectNextLink));
+
+            matches_binding!(open_link, self.open_link());
+            matches_binding!(jump_back, Action::Page(PageAction::JumpBack));
+            if self.shown_reference_preview().is_some() {
+                matches_binding!(
+                    select_reference_link,
+                    Action::Page(PageAction::SelectReferenceLink)
+                );
+            }
+            matches_binding!(add_highlight, Action::Page(PageAction::AddHighlight));
+            matches_binding!(read_link_later, Action::Page(PageAction::ReadLater(true)));
+            matches_binding!(lookup_word, Action::Page(PageAction::LookupWord));
+            matches_binding!(search_other_site, Action::Page(PageAction::SearchOtherSite));
+        }
+
+        matches_binding!(add_bookmark, Action::Page(PageAction::AddBookmark));
+        matches_binding!(read_page_later, Action::Page(PageAction::ReadLater(false)));
+        matches_binding!(watch_page, Action::Page(PageAction::ToggleWatch));
+        matches_binding!(export_page, Action::Page(PageAction::ExportPage));
+        matches_binding!(dump_render, Action::Page(PageAction::DumpRender));
+        matches_binding!(copy_url, Action::Page(PageAction::CopyUrl(false)));
+        matches_binding!(copy_section_url, Action::Page(PageAction::CopyUrl(true)));
+        matches_binding!(open_in_browser, Action::Page(PageAction::OpenInBrowser));
+        matches_binding!(show_page_info, Action::Page(PageAction::ShowPageInfo));
+        matches_binding!(show_revisions, Action::Page(PageAction::ShowRevisions));
+        matches_binding!(show_backlinks, Action::Page(PageAction::ShowBacklinks));
+        matches_binding!(switch_namespace, Action::Page(PageAction::SwitchNamespace));
+        matches_binding!(refresh_page, Action::Page(PageAction::Refresh));
+        matches_binding!(view_source, Action::Page(PageAction::ViewSource));
+        matches_binding!(visual_mode, Action::Page(PageAction::StartVisualMode));
+
+        matches_binding!(search, self.open_search_prompt());
+        matches_binding!(next_match, Action::Page(PageAction::SearchNext));
+        matches_binding!(prev_match, Action::Page(PageAction::SearchPrev));
+
+        matches_binding!(scroll_left, Action::Page(PageAction::ScrollSideways(false)));
+        matches_binding!(scroll_right, Action::Page(PageAction::ScrollSideways(true)));
+
+        matches_binding!(toggle_zen_mode, Action::Page(PageAction::ToggleZenMode));
+        matches_binding!(toggle_scrollbar, Action::Page(PageAction::ToggleScrollbar));
+        if self.is_shown(ZenModeComponents::TOC) && self.config.page.toc.enabled {
+            matches_binding!(
+                move_divider_left,
+                Action::Page(PageAction::MoveDivider(false))
+            );
+            matches_binding!(
+                move_divider_right,
+                Action::Page(PageAction::MoveDivider(true))
+            );
+        }
+
+        matches_binding!(switch_renderer, Action::Page(PageAction::NextRenderer));
+
+        ActionResult::Ignored
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::Page(page_action) => match page_action {
+                PageAction::SwitchRenderer(renderer) => self.switch_renderer(renderer),
+                PageAction::NextRenderer => {
+                    self.switch_renderer((self.renderer + 1) % self.renderers.len())
+                }
+                PageAction::ToggleContents => self.is_contents = !self.is_contents,
+                PageAction::ToggleZenMode => self.toggle_zen_mode(),
+                PageAction::ToggleScrollbar => self.toggle_scrollbar(),
+                PageAction::SetMaxWidth(max_width) => self.set_max_width(max_width),
+                PageAction::MoveDivider(is_right) => return self.move_divider(is_right),
+                PageAction::ScrollSideways(is_right) => self.scroll_sideways(is_right),
+                PageAction::StartVisualMode => self.start_visual_mode(),
+                PageAction::CopySection => return self.copy_section(),
+                PageAction::LookupWord => return self.lookup_word(),
+                PageAction::SearchOtherSite => return self.search_other_site(),
+                PageAction::ToggleFold => self.toggle_fold(),
+                PageAction::FoldAll => self.fold_all(),
+                PageAction::UnfoldAll => self.unfold_all(),
+
+                PageAction::SelectFirstLink => self.select_first(),
+                PageAction::SelectLastLink => self.select_last(),
+
+                PageAction::SelectTopLink | PageAction::SelectBottomLink => todo!(),
+
+                PageAction::SelectPrevLink => self.select_prev(),
+                PageAction::SelectNextLink => self.select_next(),
+                PageAction::OpenLink => return self.open_link(),
+
+                PageAction::GoToHeader(anchor) => self.select_header(anchor),
+                PageAction::GoToIntroduction => self.go_to_introduction(),
+                PageAction::GoToSectionKind(kind) => self.go_to_section_kind(kind),
+                PageAction::JumpToReference(anchor) => self.jump_to_reference(anchor),
+                PageAction::JumpBack => self.jump_back(),
+                PageAction::SelectReferenceLink => self.select_reference_link(),
+                PageAction::RestorePosition(y, selected) => {
+                    self.restore_session_position(y, selected)
+                }
+
+                PageAction::AddHighlight => return self.add_highlight(),
+                PageAction::GoToHighlight(idx) => self.select_highlight(idx),
+                PageAction::RemoveHighlight(idx) => return self.remove_highlight(idx),
+
+                PageAction::AddBookmark => return self.add_bookmark(),
+                PageAction::ReadLater(is_link) => return self.read_later(is_link),
+                PageAction::ToggleWatch => return self.toggle_watch(),
+
+                PageAction::MarkRandom => self.is_random = true,
+                PageAction::RenderChunk => self.render_chunk(RENDER_CHUNK_DURATION),
+                PageAction::Prefetch => return self.prefetch(),
+                PageAction::MarkRevision(revision) => self.mark_revision(revision),
+                PageAction::MarkCached(fetched) => self.cached = Some(fetched),
+                PageAction::Refresh => {
+                    return Action::RefreshPage(
+                        self.page.endpoint.clone(),
+                        self.page.language,
+                        self.page.title.clone(),
+                        self.revision.as_ref().map(|revision| revision.id),
+                    )
+                    .into()
+                }
+
+                PageAction::ExportPage => {
+                    return Action::ShowExportPrompt(
+                        export_file_name(&self.page.title),
+                        self.viewport.width,
+                    )
+                    .into()
+                }
+                PageAction::ExportPageTo(path, width) => return self.export_page(path, width),
+                PageAction::DumpRender => return self.dump_render(),
+                PageAction::CopyUrl(with_section) => return self.copy_url(with_section),
+                PageAction::CopyMediaUrl(url) => return self.copy_media_url(url),
+                PageAction::OpenInBrowser => return self.open_in_browser(),
+                PageAction::ShowPageInfo => {
+                    return Action::ShowPageInfo(
+                        self.page.endpoint.clone(),
+                        self.page.language,
+                        self.page.title.clone(),
+                    )
+                    .into()
+                }
+
+                PageAction::ShowRevisions => {
+                    return Action::ShowRevisions(
+                        self.page.endpoint.clone(),
+                        self.page.language,
+                        self.page.title.clone(),
+                    )
+                    .into()
+                }
+
+                PageAction::ShowBacklinks => {
+                    return Action::ShowBacklinks(
+                        self.page.endpoint.clone(),
+                        self.page.language,
+                        self.page.title.clone(),
+                    )
+                    .into()
+                }
+
+                PageAction::SwitchNamespace => {
+                    return Action::LoadAssociatedPage(
+                        self.page.endpoint.clone(),
+                        self.page.language,
+                        self.page.title.clone(),
+                    )
+                    .into()
+                }
+
+                PageAction::ViewSource => return self.toggle_source(),
+                PageAction::SourceLoaded(title, result) => {
+                    return self.source_loaded(title, result)
+                }
+                PageAction::ToggleSourceWrap => self.toggle_source_wrap(),
+
+                PageAction::OpenSearch => return self.open_search_prompt(),
+                PageAction::SearchNext => self.search_next(),
+                PageAction::SearchPrev => self.search_prev(),
+
+                PageAction::ShowNotice(notice) => self.notice = Some((notice, Instant::now())),
+            },
+            Action::ScrollUp(amount) => self.scroll_up(amount),
+            Action::ScrollDown(amount) => self.scroll_down(amount),
+
+            Action::ScrollHalfUp => self.scroll_up(self.viewport.height / 2),
+            Action::ScrollHalfDown => self.scroll_down(self.viewport.height / 2),
+
+            Action::ScrollToTop => self.scroll_to_top(),
+            Action::ScrollToBottom => self.scroll_to_bottom(),
+
+            Action::Resize(width, heigth) => self.resize(width, heigth),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame, mut area: Rect) {
+        if let Some((prefix, pressed)) = self.sequence_prefix {
+            if pressed.elapsed() >= SEQUENCE_TIMEOUT {
+                self.sequence_prefix = None;
+                self.released_action = self.released_prefix_action(prefix);
+            }
+        }
+
+        // when in zen mode, use the constraints for the zen mode. The padding is kept in both
+        let frame_area = area;
+        if self.is_zen_mode() {
+            let [zen_area] = Layout::horizontal([self.config.page.zen_horizontal])
+                .flex(Flex::Center)
+                .areas(area);
+            area = Layout::vertical([self.config.page.zen_vertical])
+                .flex(Flex::Center)
+                .split(zen_area)[0];
+        }
+        area = Block::new()
+            .padding(fit_padding(self.config.page.padding, area))
+            .inner(area);
+
+        // the search prompt replaces the status bar, even when it's hidden. The status bar is
+        // rendered after the page, because it shows the scroll position in the rendered page
+        let mut status_area = None;
+        if self.is_shown(ZenModeComponents::STATUS_BAR) || self.is_searching() {
+            let (page_area, bar_area) = Self::split_status_bar(area);
+            area = page_area;
+            status_area = Some(bar_area);
+        }
+
+        if self.is_shown(ZenModeComponents::TOC) {
+            area = self.render_toc(f, area);
+        }
+
+        // the plain renderer draws no scrollbar, so its page gets the columns
+        let is_plain = self.renderers[self.renderer].is_plain();
+        let is_scrollbar = self.is_shown(ZenModeComponents::SCROLLBAR) && !is_plain;
+        let mut page_area = match is_scrollbar {
+            true => area.inner(&Margin {
+                vertical: 0,
+                horizontal: 2, // for the scrollbar
+            }),
+            false => area,
+        };
+
+        if self.display.max_width != 0 && page_area.width > self.display.max_width {
+            [page_area] = Layout::horizontal([Constraint::Length(self.display.max_width)])
+                .flex(Flex::Center)
+                .areas(page_area);
+        }
+
+        // the banner is not part of the document, so it stays visible while scrolling
+        if let Some(ref revision) = self.revision {
+            let [banner_area, area] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(page_area);
+            page_area = area;
+
+            let date = revision.timestamp.split('T').next().unwrap_or_default();
+            f.render_widget(
+                self.theme
+                    .default_paragraph(format!(
+                        "Viewing the revision from {date} — press {} to return to the current version",
+                        self.config.bindings.page.pop_page
+                    ))
+                    .style(
+                        Style::default()
+                            .fg(self.theme.selected_fg)
+                            .bg(self.theme.selected_bg)
+                            .bold(),
+                    ),
+                banner_area,
+            );
+        }
+
+        // a page without columns or lines can't be rendered
+        self.scrollbar_area = None;
+        if page_area.is_empty() {
+            f.render_widget(
+                self.theme
+                    .default_paragraph("The terminal is too small for the page")
+                    .style(Style::default().fg(self.theme.inactive_fg)),
+                frame_area,
+            );
+            return;
+        }
+
+        self.viewport.width = page_area.width;
+        self.viewport.height = page_area.height;
+
+        // the viewport size is only known when rendering for the first time
+        if self.pending_start_at_lead {
+            self.pending_start_at_lead = false;
+            self.scroll_to_lead();
+        }
+        if let Some(anchor) = self.pending_header.take() {
+            self.select_header(anchor);
+        }
+        if let Some(y) = self.pending_y.take() {
+            self.finish_rendering();
+            self.scroll_to_y(y);
+        }
+        // renders for both widths are cached, so toggling the zen mode again renders nothing new
+        if let Some(position) = self.pending_word.take() {
+            self.finish_rendering();
+            if let Some(y) = rendered_page!(self, self.viewport.width).word_line(position) {
+                self.scroll_to_y(y as u16);
+            }
+        }
+
+        // the selected lines are lost when the page is rendered with a different width
+        if self
+            .visual
+            .is_some_and(|visual| visual.width != page_area.width)
+        {
+            self.visual = None;
+        }
+
+        // the matches have to be updated when the page is rendered with a different width
+        if !self.search.query.is_empty() && self.search.width != page_area.width {
+            self.update_search_matches();
+        }
+
+        // the title is part of the chrome hidden in zen mode. It takes the place of the last lines
+        // of the viewport
+        let header = match self.viewport.y == 0 && !self.is_zen_mode {
+            true => self.header_lines(page_area.width, is_plain),
+            false => Vec::new(),
+        };
+
+        let visual_lines = self.visual_lines();
+        // the buffer keeps its capacity, so the columns don't allocate for every frame
+        let mut word_columns = std::mem::take(&mut self.word_columns);
+        word_columns.clear();
+        let top = self.viewport.top() as usize;
+        let scroll_x = self.scroll_x();
+        let header_len = header.len();
+        let rendered_page = rendered_page!(self, page_area.width);
+        let mut lines: Vec<Line> = rendered_page
+            .lines
+            .iter()
+            .enumerate()
+            .skip(top)
+            .take((self.viewport.height as usize).saturating_sub(header_len))
+            .map(|(y, line)| {
+                let line_matches: Vec<(Range<usize>, Style)> = self
+                    .search
+                    .matches
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, m)| m.line == y)
+                    .map(|(idx, m)| {
+                        let bg = if self.search.current == Some(idx) {
+                            self.theme.search_current_bg
+                        } else {
+                            self.theme.search_match_bg
+                        };
+                        (
+                            m.columns.clone(),
+                            Style::new().fg(self.theme.search_match_fg).bg(bg),
+                        )
+                    })
+                    .collect();
+
+                let is_visual = visual_lines
+                    .as_ref()
+                    .is_some_and(|lines| lines.contains(&y));
+                // the words and their whitespace are borrowed, so drawing a frame doesn't
+                // allocate for every word
+                let mut spans: Vec<Span> = Vec::with_capacity(line.len() * 2);
+                // the matches are found by characters, the pointer by columns on the screen
+                let mut column = 0;
+                let mut screen_x: u16 = 0;
+                let row = (y - top + header_len) as u16;
+                for word in line {
+                    let mut style = word.style;
+                    if let Some(node) = word.node(&self.page.content) {
+                        let index = node.index();
+                        if self.config.page.link_selection
+                            && self.selected.0 <= index
+                            && index <= self.selected.1
+                        {
+                            style = style.add_modifier(Modifier::UNDERLINED);
+                            if self.theme.selected_link_fg != Color::Reset {
+                                style = style.fg(self.theme.selected_link_fg);
+                            }
+                        }
+
+                        if self.is_highlighted(index) {
+                            style = style.bg(self.theme.highlight_bg);
+                        }
+
+                        if self
+                            .hovered
+                            .is_some_and(|(first, last)| first <= index && index <= last)
+                        {
+                            style = style.bg(self.theme.hovered_link_bg);
+                        }
+                        // the columns are on the screen, so the words scrolled out of it are left
+                        // out
+                        let start = screen_x;
+                        let end = start + word.content.width() as u16;
+                        if end > scroll_x {
+                            word_columns.push((
+                                row,
+                                start.saturating_sub(scroll_x)..end - scroll_x,
+                                index,
+                            ));
+                        }
+                    }
+
+                    if is_visual {
+                        style = style.add_modifier(Modifier::REVERSED);
+                    }
+
+                    for content in [Cow::Borrowed(word.content.as_str()), word.whitespace()] {
+                        if content.is_empty() {
+                            continue;
+                        }
+                        let (width, screen_width) = (content.chars().count(), content.width());
+                        patch_search_matches(
+                            &mut spans,
+                            Span::styled(content, style),
+                            column,
+                            &line_matches,
+                        );
+                        column += width;
+                        screen_x += screen_width as u16;
+                    }
+                }
+                Line {
+                    spans,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        let is_empty =
+            !self.is_viewing_source() && rendered_page.lines.iter().all(|line| line.is_empty());
+        lines.splice(0..0, header);
+
+        // pages like stubs and special pages can come back without any content
+        if is_empty {
+            let placeholder = Line::raw("This page has no content");
+            lines.push(match is_plain {
+                true => placeholder,
+                false => placeholder.patch_style(Style::default().fg(self.theme.inactive_fg)),
+            });
+        }
+
+        f.render_widget(
+            Paragraph::new(lines).scroll((0, self.scroll_x())),
+            page_area,
+        );
+        let max_scroll = rendered_page
+            .lines
+            .len()
+            .saturating_sub(self.viewport.height as usize);
+        self.page_area = page_area;
+        self.word_columns = word_columns;
+        // the page moves below the pointer when it's scrolled or rendered again
+        let hovered = self
+            .pointer
+            .and_then(|(column, row)| self.link_at(column, row));
+        self.is_hover_stale = hovered != self.hovered;
+        self.hovered = hovered;
+        self.render_images(f, page_area);
+
+        match self.shown_reference_preview().copied() {
+            Some(preview) => self.render_reference_preview(f, page_area, preview, is_plain),
+            None => self.reference_links.clear(),
+        }
+
+        if is_scrollbar {
+            self.render_scrollbar(f, area, max_scroll);
+            let [_, scrollbar_area] =
+                Layout::horizontal([Constraint::Min(0), Constraint::Length(2)]).areas(area);
+            self.scrollbar_area = Some(scrollbar_area);
+        }
+
+        if let Some(status_area) = status_area {
+            self.render_status_bar(f, status_area);
+        }
+    }
+}
+
+/// Returns the keymap of the page and the keymap of its table of contents, the one of the focused
+/// pane first. The keys are the ones of the config, so remapped keys are listed
+pub fn page_keymaps(config: &Config, is_contents: bool) -> Vec<Keymap> {
+    let bindings = config.bindings.keymap("page");
+    let page = Keymap::new(
+        "Page",
+        bindings
+            .iter()
+            .copied()
+            .filter(|(name, ..)| *name != "jump_to_header")
+            .collect(),
+    );
 
-                PageAction::SelectFirstLink => self.select_first(),
-                PageAction::SelectLastLink => self.select_last(),
+    let global = config.bindings.keymap("global");
+    let scrolling = CONTENTS_SCROLLING.iter().filter_map(|(name, description)| {
+        global
+            .iter()
+            .find(|(global_name, ..)| global_name == name)
+            .map(|(name, _, binding)| (*name, *description, *binding))
+    });
+    let contents = Keymap::new(
+        "Contents",
+        bindings
+            .into_iter()
+            .filter(|(name, ..)| CONTENTS_BINDINGS.contains(name))
+            .map(|(name, description, binding)| match name {
+                "toggle_toc" => (name, "Return to the page", binding),
+                "search_other_site" => {
+                    (name, "Search the selected section on another site", binding)
+                }
+                _ => (name, description, binding),
+            })
+            .chain(scrolling)
+            .collect(),
+    );
 
-                PageAction::SelectTopLink | PageAction::SelectBottomLink => todo!(),
+    match is_contents {
+        true => vec![contents, page],
+        false => vec![page, contents],
+    }
+}
 
-                PageAction::SelectPrevLink => self.select_prev(),
-                PageAction::SelectNextLink => self.select_next(),
+/// Leaves out the padding on the sides of a terminal too small for it, so the page keeps at least
+/// `MIN_PAGE_WIDTH` columns and `MIN_PAGE_HEIGHT` lines
+fn fit_padding(padding: Padding, area: Rect) -> Padding {
+    let (left, right) = match area.width >= padding.left + padding.right + MIN_PAGE_WIDTH {
+        true => (padding.left, padding.right),
+        false => (0, 0),
+    };
+    let (top, bottom) = match area.height >= padding.top + padding.bottom + MIN_PAGE_HEIGHT {
+        true => (padding.top, padding.bottom),
+        false => (0, 0),
+    };
+    Padding::new(left, right, top, bottom)
+}
 
-                PageAction::GoToHeader(anchor) => self.select_header(anchor),
-            },
-            Action::ScrollUp(amount) => self.scroll_up(amount),
-            Action::ScrollDown(amount) => self.scroll_down(amount),
+/// Returns the line at the top of the viewport for the row of the scrollbar with the height. The
+/// first row shows the top of the page and the last row its end, the rows in between the lines at
+/// the same proportion of the lines that can be scrolled
+fn scrollbar_line(row: u16, height: u16, n_lines: usize, viewport_height: u16) -> u16 {
+    let max_y = n_lines.saturating_sub(viewport_height as usize);
+    let last_row = height.saturating_sub(1) as usize;
+    if max_y == 0 || last_row == 0 {
+        return 0;
+    }
+    let row = (row as usize).min(last_row);
+    ((row * max_y + last_row / 2) / last_row) as u16
+}
 
-            Action::ScrollHalfUp => self.scroll_up(self.viewport.height / 2),
-            Action::ScrollHalfDown => self.scroll_down(self.viewport.height / 2),
+/// Returns where the link leads, as shown in the status bar
+fn link_target(link: &Link) -> String {
+    match link {
+        Link::External(link) => link.url.to_string(),
+        Link::Interwiki(link) => match link.kind {
+            InterwikiKind::Unknown => link.url.to_string(),
+            _ => format!("{}:{}", link.prefix, link.title),
+        },
+        link => link.title().unwrap_or_default().to_string(),
+    }
+}
 
-            Action::ScrollToTop => self.scroll_to_y(0),
-            Action::ScrollToBottom => self.scroll_to_bottom(),
+/// Keeps the table of contents and the page from getting narrower than their minimum width, as
+/// long as the area is wide enough for both
+fn clamp_toc_columns(columns: u16, width: u16) -> u16 {
+    let min = MIN_TOC_WIDTH.min(width);
+    columns.clamp(min, width.saturating_sub(MIN_PAGE_WIDTH).max(min))
+}
 
-            Action::Resize(width, heigth) => self.resize(width, heigth),
-            _ => return ActionResult::Ignored,
+/// Splits the span at the boundaries of the matches overlapping it, patches the style of the
+/// matched parts and pushes the parts to `spans`. `column` is the column of the first character of
+/// the span in its line and the matches have to be ordered
+fn patch_search_matches<'a>(
+    spans: &mut Vec<Span<'a>>,
+    span: Span<'a>,
+    column: usize,
+    matches: &[(Range<usize>, Style)],
+) {
+    let width = span.content.chars().count();
+    let overlaps = |columns: &Range<usize>| columns.start < column + width && column < columns.end;
+    if !matches.iter().any(|(columns, _)| overlaps(columns)) {
+        spans.push(span);
+        return;
+    }
+
+    let chars: Vec<char> = span.content.chars().collect();
+    let part = |start: usize, end: usize| chars[start..end].iter().collect::<String>();
+    let mut pos = 0;
+    for (columns, style) in matches.iter().filter(|(columns, _)| overlaps(columns)) {
+        let start = columns.start.saturating_sub(column).max(pos);
+        let end = (columns.end - column).min(width);
+        if start > pos {
+            spans.push(Span::styled(part(pos, start), span.style));
         }
-        ActionResult::consumed()
+        if end > start {
+            spans.push(Span::styled(part(start, end), span.style.patch(*style)));
+        }
+        pos = pos.max(end);
+    }
+    if pos < width {
+        spans.push(Span::styled(part(pos, width), span.style));
     }
+}
 
-    fn render(&mut self, f: &mut Frame, mut area: Rect) {
-        let zen_mode = self.config.page.zen_mode.clone();
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
 
-        // when in zen mode, use the constraints for the zen mode, otherwise the regular padding
-        area = if self.is_zen_mode() {
-            let [area] = Layout::horizontal([self.config.page.zen_horizontal])
-                .flex(Flex::Center)
-                .areas(area);
-            Layout::vertical([self.config.page.zen_vertical])
-                .flex(Flex::Center)
-                .split(area)[0]
-        } else {
-            Block::new().padding(self.config.page.padding).inner(area)
+    use crossterm::event::{
+        KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+    use ratatui::{
+        backend::TestBackend, buffer::Buffer, layout::Rect, style::Modifier, widgets::Padding,
+        Terminal,
+    };
+    use unicode_width::UnicodeWidthStr;
+    use wiki_api::{
+        document::{Document, HeaderKind},
+        languages::Language,
+        page::{Page, PageKind, Section, SectionKind},
+        parser::{Parser, WikipediaParser},
+        Endpoint,
+    };
+
+    use crate::{
+        action::{Action, ActionPacket, ActionResult, PageAction},
+        components::{Component, Keymap},
+        config::{Config, Theme},
+        renderer::{top_level_sections, RenderedDocument},
+    };
+
+    use super::{
+        fit_padding, page_keymaps, scrollbar_line, ContentsEntry, PageComponent, RenderJob,
+        SEQUENCE_TIMEOUT,
+    };
+
+    fn empty_page(sections: Option<Vec<Section>>) -> PageComponent {
+        let page = Page {
+            title: "Empty".to_string(),
+            pageid: 0,
+            kind: PageKind::Article,
+            content: Document { nodes: Vec::new() },
+            language: Language::English,
+            endpoint: Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+            language_links: None,
+            sections,
+            revision_id: None,
+            redirected_from: None,
+            description: None,
+        };
+        PageComponent::new(
+            page,
+            Arc::new(Config::default()),
+            Arc::new(Theme::default()),
+        )
+    }
+
+    fn page_from_html(html: &str) -> PageComponent {
+        let mut page = empty_page(None);
+        page.page.content = Document {
+            nodes: WikipediaParser::parse_document(
+                html,
+                Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+                Language::English,
+            )
+            .nodes(),
         };
+        page
+    }
+
+    /// Draws the page on a terminal of the size and returns the drawn cells
+    fn draw(page: &mut PageComponent, width: u16, height: u16) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| page.render(f, f.size())).unwrap();
+        terminal.backend().buffer().clone()
+    }
 
-        if !self.is_zen_mode || zen_mode.contains(ZenModeComponents::STATUS_BAR) {
-            area = self.render_status_bar(f, area);
+    fn screen(buffer: &Buffer) -> String {
+        buffer.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    fn row(buffer: &Buffer, y: u16) -> String {
+        (0..buffer.area.width)
+            .map(|x| buffer.get(x, y).symbol())
+            .collect()
+    }
+
+    fn render(page: &mut PageComponent) -> String {
+        screen(&draw(page, 160, 20))
+    }
+
+    #[test]
+    fn test_empty_page() {
+        let mut page = empty_page(None);
+        let screen = render(&mut page);
+        assert!(screen.contains("Empty"));
+        assert!(screen.contains("This page has no content"));
+        assert!(screen.contains("No Contents available"));
+
+        for action in [
+            PageAction::SelectFirstLink,
+            PageAction::SelectLastLink,
+            PageAction::SelectNextLink,
+            PageAction::SelectPrevLink,
+            PageAction::FoldAll,
+            PageAction::GoToHeader("History".to_string()),
+        ] {
+            page.notice = None;
+            page.update(Action::Page(action));
+            assert_eq!(
+                page.notice.as_ref().map(|(notice, _)| notice.as_str()),
+                Some("This page has no content")
+            );
         }
+        assert_eq!(page.selected, (0, 0));
+        render(&mut page);
+    }
 
-        if !self.is_zen_mode || zen_mode.contains(ZenModeComponents::TOC) {
-            area = self.render_toc(f, area);
+    #[test]
+    fn test_fold_keys() {
+        let mut page = empty_page(None);
+        let is_action = |result: ActionResult, action: PageAction| {
+            matches!(result, ActionResult::Consumed(packet)
+                if packet == ActionPacket::single(Action::Page(action)))
+        };
+
+        assert!(page
+            .handle_key_events(KeyEvent::from(KeyCode::Char('z')))
+            .is_consumed());
+        let result = page.handle_key_events(KeyEvent::new(KeyCode::Char('M'), KeyModifiers::SHIFT));
+        assert!(is_action(result, PageAction::FoldAll));
+
+        page.handle_key_events(KeyEvent::from(KeyCode::Char('z')));
+        let result = page.handle_key_events(KeyEvent::from(KeyCode::Char('a')));
+        assert!(is_action(result, PageAction::ToggleFold));
+        // the key of a sequence does nothing on its own
+        let result = page.handle_key_events(KeyEvent::from(KeyCode::Char('a')));
+        assert!(!is_action(result, PageAction::ToggleFold));
+    }
+
+    #[test]
+    fn test_page_without_sections() {
+        let mut page = empty_page(Some(Vec::new()));
+        page.update(Action::Page(PageAction::ToggleContents));
+        page.update(Action::ScrollUp(1));
+        page.update(Action::ScrollDown(1));
+        assert!(page.selected_contents_entry().is_none());
+
+        let screen = render(&mut page);
+        assert!(screen.contains("No Contents available"));
+    }
+
+    #[test]
+    fn test_render_in_chunks() {
+        let section = r#"<section data-mw-section-id="{n}"><h2 id="S{n}">Section {n}</h2>
+            <p>Rust is an iron oxide, a usually reddish-brown oxide.</p></section>"#;
+        let html: Vec<String> = (0..4)
+            .map(|n| section.replace("{n}", &n.to_string()))
+            .collect();
+        let mut page = page_from_html(&html.concat());
+        render(&mut page);
+        assert!(!page.is_rendering());
+        let width = page.viewport.width;
+        let whole = page.render_cache[&width].lines.len();
+
+        // a section is rendered per chunk, the part rendered so far is displayed
+        page.flush_render_cache();
+        page.render_cache.insert(width, RenderedDocument::default());
+        page.render_job = Some(RenderJob {
+            width,
+            sections: top_level_sections(&page.page.content)
+                .unwrap()
+                .into_iter()
+                .rev()
+                .collect(),
+        });
+        page.render_chunk(Duration::ZERO);
+        assert!(page.is_rendering());
+        assert!(page.status_context().notice.starts_with("rendering… "));
+        let screen = render(&mut page);
+        assert!(screen.contains("Section 0") && !screen.contains("Section 1"));
+
+        while page.is_rendering() {
+            page.update(Action::Page(PageAction::RenderChunk));
         }
+        assert_eq!(page.render_cache[&width].lines.len(), whole);
+        assert!(page.status_context().notice.is_empty());
 
-        let page_area = if !self.is_zen_mode || zen_mode.contains(ZenModeComponents::SCROLLBAR) {
-            area.inner(&Margin {
-                vertical: 0,
-                horizontal: 2, // for the scrollbar
+        // jumping to the bottom or to the last link renders the rest of the page first
+        let restart = |page: &mut PageComponent| {
+            page.flush_render_cache();
+            page.render_cache.insert(width, RenderedDocument::default());
+            page.render_job = Some(RenderJob {
+                width,
+                sections: top_level_sections(&page.page.content)
+                    .unwrap()
+                    .into_iter()
+                    .rev()
+                    .collect(),
+            });
+            page.render_chunk(Duration::ZERO);
+        };
+        restart(&mut page);
+        page.scroll_to_bottom();
+        assert!(!page.is_rendering());
+        assert_eq!(
+            page.viewport.y as usize,
+            whole.saturating_sub(page.viewport.height as usize)
+        );
+        restart(&mut page);
+        page.select_last();
+        assert!(!page.is_rendering());
+
+        // rendering another width cancels the incomplete render
+        page.flush_render_cache();
+        page.render_cache.insert(width, RenderedDocument::default());
+        page.render_job = Some(RenderJob {
+            width,
+            sections: vec![0],
+        });
+        page.render_page(width - 10);
+        assert!(!page.is_rendering());
+        assert!(!page.render_cache.contains_key(&width));
+    }
+
+    #[test]
+    fn test_go_to_section_kind() {
+        let titles = ["Geschichte", "Verwendung", "Literatur", "Weblinks"];
+        let html: Vec<String> = titles
+            .iter()
+            .enumerate()
+            .map(|(n, title)| {
+                format!(
+                    r#"<section data-mw-section-id="{}"><h2 id="{}">{}</h2>{}</section>"#,
+                    n,
+                    title,
+                    title,
+                    "<p>Rost ist ein Korrosionsprodukt.</p>".repeat(4)
+                )
             })
-        } else {
-            area
+            .collect();
+        let sections = titles
+            .iter()
+            .enumerate()
+            .map(|(n, title)| Section {
+                index: n,
+                header_kind: HeaderKind::Main,
+                text: title.to_string(),
+                number: n.to_string(),
+                anchor: title.to_string(),
+            })
+            .collect();
+        let mut page = empty_page(Some(sections));
+        page.page.language = Language::German;
+        page.page.content = Document {
+            nodes: WikipediaParser::parse_document(
+                &html.concat(),
+                Endpoint::parse("https://de.wikipedia.org/w/api.php").unwrap(),
+                Language::German,
+            )
+            .nodes(),
         };
+        render(&mut page);
 
-        self.viewport.width = page_area.width;
-        self.viewport.height = page_area.height;
+        page.handle_key_events(KeyEvent::from(KeyCode::Char('g')));
+        let result = page.handle_key_events(KeyEvent::from(KeyCode::Char('e')));
+        let action = Action::Page(PageAction::GoToSectionKind(SectionKind::ExternalLinks));
+        assert!(
+            matches!(result, ActionResult::Consumed(packet) if packet == ActionPacket::single(action))
+        );
+        page.update(Action::Page(PageAction::GoToSectionKind(
+            SectionKind::ExternalLinks,
+        )));
+        assert!(page.viewport.y > 0);
+        assert!(page.notice.is_none());
 
-        let rendered_page = rendered_page!(self, page_area.width);
-        let mut lines: Vec<Line> = rendered_page
-            .lines
+        page.update(Action::Page(PageAction::GoToSectionKind(
+            SectionKind::References,
+        )));
+        assert_eq!(
+            page.notice.as_ref().map(|(notice, _)| notice.as_str()),
+            Some("The page has no references section")
+        );
+
+        // `g` without a sequence scrolls to the top after the timeout
+        page.handle_key_events(KeyEvent::from(KeyCode::Char('g')));
+        page.sequence_prefix = Some(('g', Instant::now() - SEQUENCE_TIMEOUT));
+        render(&mut page);
+        let action = page.take_released_action();
+        assert_eq!(action, Some(Action::ScrollToTop));
+        page.update(action.unwrap());
+        assert_eq!(page.viewport.y, 0);
+
+        // a key not completing the sequence runs after the prefix, global keys included
+        page.handle_key_events(KeyEvent::from(KeyCode::Char('g')));
+        let result = page.handle_key_events(KeyEvent::from(KeyCode::Char('j')));
+        let packet = ActionPacket::single(Action::ScrollToTop).action(Action::ScrollDown(1));
+        assert!(matches!(result, ActionResult::Consumed(result) if result == packet));
+    }
+
+    #[test]
+    fn test_contents_introduction_entry() {
+        let section = |index: usize, text: &str| Section {
+            index,
+            header_kind: HeaderKind::Main,
+            text: text.to_string(),
+            number: index.to_string(),
+            anchor: text.to_string(),
+        };
+        let mut page = empty_page(Some(vec![section(1, "History"), section(2, "Usage")]));
+        page.update(Action::Page(PageAction::ToggleContents));
+        let screen = render(&mut page);
+        assert!(screen.find("Introduction").unwrap() < screen.find("1 History").unwrap());
+
+        // the first entry scrolls to the introduction instead of jumping to a header
+        assert_eq!(
+            page.selected_contents_entry(),
+            Some(ContentsEntry::Introduction)
+        );
+        let result = page.handle_key_events(KeyEvent::from(KeyCode::Enter));
+        let packet = ActionPacket::single(Action::Page(PageAction::GoToIntroduction))
+            .action(Action::Page(PageAction::ToggleContents));
+        assert!(matches!(result, ActionResult::Consumed(actions) if actions == packet));
+
+        // the sections follow it, offset by one
+        page.update(Action::ScrollDown(1));
+        assert_eq!(
+            page.selected_contents_entry(),
+            Some(ContentsEntry::Section(&section(1, "History")))
+        );
+        page.update(Action::ScrollDown(1));
+        let result = page.handle_key_events(KeyEvent::from(KeyCode::Enter));
+        let packet =
+            ActionPacket::single(Action::Page(PageAction::GoToHeader("Usage".to_string())))
+                .action(Action::Page(PageAction::ToggleContents));
+        assert!(matches!(result, ActionResult::Consumed(actions) if actions == packet));
+
+        // scrolling past the last section wraps around to the introduction
+        page.update(Action::ScrollDown(1));
+        assert_eq!(
+            page.selected_contents_entry(),
+            Some(ContentsEntry::Introduction)
+        );
+        page.update(Action::ScrollUp(1));
+        assert_eq!(
+            page.selected_contents_entry(),
+            Some(ContentsEntry::Section(&section(2, "Usage")))
+        );
+    }
+
+    #[test]
+    fn test_keep_position_on_rewrap() {
+        let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/renderer/snapshots/fixtures");
+        let html: String = ["nested_blocks.html", "lists.html", "disambiguation.html"]
             .iter()
-            .skip(self.viewport.top() as usize)
-            .take(self.viewport.bottom() as usize)
-            .map(|line| {
-                let mut spans: Vec<Span> = Vec::new();
-                line.iter()
-                    .map(|word| {
-                        let mut span = Span::styled(
-                            format!(
-                                "{}{}",
-                                word.content,
-                                " ".repeat(word.whitespace_width as usize)
-                            ),
-                            word.style,
-                        );
+            .cycle()
+            .take(6)
+            .map(|fixture| std::fs::read_to_string(fixtures.join(fixture)).unwrap())
+            .collect();
+        let mut page = page_from_html(&html);
+        // the line at the top of the viewport shows a word of the node
+        let shows_node = |page: &PageComponent, node: usize| {
+            page.render_cache[&page.viewport.width].lines[page.viewport.y as usize]
+                .iter()
+                .any(|word| word.index == node)
+        };
 
-                        if let Some(node) = word.node(&self.page.content) {
-                            let index = node.index();
-                            if self.selected.0 <= index && index <= self.selected.1 {
-                                span = span
-                                    .patch_style(Style::new().add_modifier(Modifier::UNDERLINED))
-                            }
-                        }
+        draw(&mut page, 80, 30);
+        let wide = page.viewport.width;
+        page.scroll_to_y(100);
+        assert_eq!(page.viewport.y, 100);
+        let (node, _) = page.render_cache[&wide]
+            .word_position(&page.page.content, 100)
+            .unwrap();
 
-                        spans.push(span);
-                    })
-                    .count();
-                Line {
-                    spans,
-                    ..Default::default()
-                }
+        page.update(Action::Resize(40, 30));
+        draw(&mut page, 40, 30);
+        assert!(page.viewport.width < wide);
+        assert_ne!(page.viewport.y, 100);
+        assert!(shows_node(&page, node));
+
+        page.update(Action::Page(PageAction::SwitchRenderer(1)));
+        draw(&mut page, 40, 30);
+        assert!(shows_node(&page, node));
+    }
+
+    #[test]
+    fn test_reference_preview_links() {
+        let html = r##"<p>Rust is fast<sup class="mw-ref reference" id="cite_ref-1"><a href="./Rust#cite_note-1"><span class="mw-reflink-text">[1]</span></a></sup></p>
+            <ol class="mw-references references">
+                <li id="cite_note-1"><span class="mw-cite-backlink"><a href="./Rust#cite_ref-1">↑</a></span> <span id="mw-reference-text-cite_note-1" class="mw-reference-text">As told in 日本の<a rel="mw:WikiLink" href="./Rustonomicon" title="Rustonomicon">Rustonomicon</a>.</span></li>
+            </ol>"##;
+        let mut page = page_from_html(html);
+        render(&mut page);
+        page.update(Action::Page(PageAction::SelectFirstLink));
+        let screen = render(&mut page);
+        assert!(screen.contains("Reference [1]"));
+        assert!(screen.contains("Rustonomicon."));
+
+        // the link is drawn at its columns on the screen, measured in terminal columns
+        let (row, columns, link) = page.reference_links[0].clone();
+        let start = page.page_area.x + 1 + "As told in 日本の".width() as u16;
+        assert_eq!(columns, start..start + "Rustonomicon".len() as u16);
+        let opens_link = |result: ActionResult| match result {
+            ActionResult::Consumed(packet) => {
+                format!("{packet:?}").contains("open the page 'Rustonomicon'")
+            }
+            ActionResult::Ignored => false,
+        };
+
+        // the links of the reference are selected in turn, then the reflink again
+        let select = Action::Page(PageAction::SelectReferenceLink);
+        assert!(matches!(
+            page.handle_key_events(KeyEvent::from(KeyCode::Char('f'))),
+            ActionResult::Consumed(packet) if packet == ActionPacket::single(select.clone())
+        ));
+        page.update(select.clone());
+        assert_eq!(page.shown_reference_preview().unwrap().selected, Some(link));
+        assert!(opens_link(
+            page.handle_key_events(KeyEvent::from(KeyCode::Enter))
+        ));
+        page.update(select);
+        assert_eq!(page.shown_reference_preview().unwrap().selected, None);
+        assert!(!opens_link(
+            page.handle_key_events(KeyEvent::from(KeyCode::Enter))
+        ));
+
+        page.update(Action::Page(PageAction::SelectFirstLink));
+        render(&mut page);
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: columns.start,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        assert!(opens_link(page.handle_mouse_events(click)));
+    }
+
+    #[test]
+    fn test_current_contents_entry() {
+        let paragraphs = "<p>Rost ist ein Korrosionsprodukt.</p>".repeat(30);
+        let html = format!(
+            r#"<section data-mw-section-id="0">{paragraphs}</section><section data-mw-section-id="1"><h2 id="Geschichte">Geschichte</h2>{paragraphs}</section>"#
+        );
+        let mut page = empty_page(Some(vec![Section {
+            index: 1,
+            header_kind: HeaderKind::Main,
+            text: "Geschichte".to_string(),
+            number: "1".to_string(),
+            anchor: "Geschichte".to_string(),
+        }]));
+        page.page.language = Language::German;
+        page.page.content = Document {
+            nodes: WikipediaParser::parse_document(
+                &html,
+                Endpoint::parse("https://de.wikipedia.org/w/api.php").unwrap(),
+                Language::German,
+            )
+            .nodes(),
+        };
+
+        // the introduction takes its label from the config and is marked while reading the lead
+        let mut config = Config::default();
+        config.page.toc.introduction = "Einleitung".to_string();
+        page.config = Arc::new(config);
+        let screen = render(&mut page);
+        assert!(screen.contains("Einleitung"));
+        assert_eq!(page.current_contents_entry(), Some(0));
+
+        page.update(Action::Page(PageAction::GoToHeader(
+            "Geschichte".to_string(),
+        )));
+        render(&mut page);
+        assert_eq!(page.current_contents_entry(), Some(1));
+
+        // the introduction starts after the empty line below the title
+        page.update(Action::Page(PageAction::GoToIntroduction));
+        assert_eq!(page.viewport.y, 1);
+        assert_eq!(page.current_contents_entry(), Some(0));
+        let buffer = draw(&mut page, 160, 20);
+        assert!(row(&buffer, 1)
+            .trim_start()
+            .starts_with("Rost ist ein Korrosionsprodukt."));
+    }
+
+    #[test]
+    fn test_contents_home_end() {
+        let sections = ["History", "Usage", "See also"]
+            .iter()
+            .enumerate()
+            .map(|(n, text)| Section {
+                index: n + 1,
+                header_kind: HeaderKind::Main,
+                text: text.to_string(),
+                number: (n + 1).to_string(),
+                anchor: text.replace(' ', "_"),
+            })
+            .collect();
+        let mut page = empty_page(Some(sections));
+        page.update(Action::Page(PageAction::ToggleContents));
+        render(&mut page);
+
+        // the keys are handled by the contents instead of being left to the global bindings
+        for (key, action) in [
+            (KeyCode::End, Action::ScrollToBottom),
+            (KeyCode::Char('G'), Action::ScrollToBottom),
+            (KeyCode::Home, Action::ScrollToTop),
+            (KeyCode::Char('g'), Action::ScrollToTop),
+        ] {
+            let event = match key {
+                KeyCode::Char('G') => KeyEvent::new(key, KeyModifiers::SHIFT),
+                _ => KeyEvent::from(key),
+            };
+            let result = page.handle_key_events(event);
+            let packet = ActionPacket::single(action);
+            assert!(matches!(result, ActionResult::Consumed(actions) if actions == packet));
+        }
+
+        page.update(Action::ScrollToBottom);
+        assert_eq!(page.contents_state.list_state.selected(), Some(3));
+        page.update(Action::ScrollToTop);
+        assert_eq!(
+            page.selected_contents_entry(),
+            Some(ContentsEntry::Introduction)
+        );
+
+        // the page itself isn't scrolled while the contents are focused
+        page.viewport.y = 1;
+        page.update(Action::ScrollToBottom);
+        assert_eq!(page.viewport.y, 1);
+        page.update(Action::Page(PageAction::GoToIntroduction));
+        assert_eq!(page.viewport.y, 0);
+    }
+
+    #[test]
+    fn test_many_sections() {
+        // long lists have more sections than fit in a byte
+        let sections = (1..=300)
+            .map(|n| Section {
+                index: n,
+                header_kind: HeaderKind::Main,
+                text: format!("Section {}", n),
+                number: n.to_string(),
+                anchor: format!("Section_{}", n),
             })
             .collect();
+        let mut page = empty_page(Some(sections));
+        page.update(Action::Page(PageAction::ToggleContents));
+        render(&mut page);
+
+        page.update(Action::ScrollToBottom);
+        assert_eq!(page.contents_state.list_state.selected(), Some(300));
+        page.update(Action::ScrollDown(1));
+        assert_eq!(
+            page.selected_contents_entry(),
+            Some(ContentsEntry::Introduction)
+        );
+        page.update(Action::ScrollUp(1));
+        assert_eq!(page.contents_state.list_state.selected(), Some(300));
+
+        page.contents_state.list_state.select(Some(255));
+        page.update(Action::ScrollDown(1));
+        assert_eq!(page.contents_state.list_state.selected(), Some(256));
+    }
+
+    #[test]
+    fn test_search_other_site() {
+        let sections = vec![Section {
+            index: 1,
+            header_kind: HeaderKind::Main,
+            text: "Etymology".to_string(),
+            number: "1".to_string(),
+            anchor: "Etymology".to_string(),
+        }];
+        let mut page = empty_page(Some(sections));
+        render(&mut page);
+
+        // without a selected link there's nothing to search for
+        let result = page.update(Action::Page(PageAction::SearchOtherSite));
+        assert!(
+            matches!(result, ActionResult::Consumed(packet) if packet == ActionPacket::default())
+        );
+        assert!(page.notice.is_some());
+
+        page.update(Action::Page(PageAction::ToggleContents));
+        page.update(Action::ScrollToBottom);
+        let result = page.handle_key_events(KeyEvent::new(KeyCode::Char('W'), KeyModifiers::SHIFT));
+        let packet = ActionPacket::single(Action::Page(PageAction::SearchOtherSite));
+        assert!(matches!(result, ActionResult::Consumed(actions) if actions == packet));
+        let result = page.update(Action::Page(PageAction::SearchOtherSite));
+        let packet = ActionPacket::single(Action::ShowSiteSearch("Etymology".to_string()));
+        assert!(matches!(result, ActionResult::Consumed(actions) if actions == packet));
+    }
+
+    #[test]
+    fn test_page_keymaps() {
+        let mut config = Config::default();
+        let keymaps = page_keymaps(&config, false);
+        let titles: Vec<&str> = keymaps.iter().map(|keymap| keymap.title).collect();
+        assert_eq!(titles, ["Page", "Contents"]);
+        let names = |keymap: &Keymap| -> Vec<&str> {
+            keymap.bindings.iter().map(|action| action.name).collect()
+        };
+        let page = names(&keymaps[0]);
+        assert!(page.contains(&"toggle_toc") && page.contains(&"open_link"));
+        assert!(!page.contains(&"jump_to_header"));
+        assert_eq!(
+            names(&keymaps[1]),
+            [
+                "jump_to_header",
+                "toggle_toc",
+                "search_other_site",
+                "scroll_down",
+                "scroll_up",
+                "scroll_to_top",
+                "scroll_to_bottom"
+            ]
+        );
+        // jumping to a header needs the focused table of contents, so the palette can't run it
+        assert_eq!(keymaps[1].bindings[0].action, None);
+        assert_eq!(
+            keymaps[1].bindings[1].action,
+            Some(Action::Page(PageAction::ToggleContents))
+        );
+
+        // the key sequences are listed with their keys and run from the palette
+        let sequences = [
+            ("toggle_fold", "za", PageAction::ToggleFold),
+            ("fold_all", "zM", PageAction::FoldAll),
+            ("unfold_all", "zR", PageAction::UnfoldAll),
+            ("copy_section", "ys", PageAction::CopySection),
+            (
+                "go_to_references",
+                "gr",
+                PageAction::GoToSectionKind(SectionKind::References),
+            ),
+            (
+                "go_to_external_links",
+                "ge",
+                PageAction::GoToSectionKind(SectionKind::ExternalLinks),
+            ),
+        ];
+        for (name, keys, action) in sequences {
+            let binding = keymaps[0]
+                .bindings
+                .iter()
+                .find(|binding| binding.name == name)
+                .unwrap();
+            assert_eq!(binding.keys, keys);
+            assert_eq!(binding.action, Some(Action::Page(action)));
+        }
+
+        // the focused pane comes first and remapped keys are listed
+        let export_keys = config.bindings.page.export_page.to_string();
+        std::mem::swap(
+            &mut config.bindings.page.toggle_toc,
+            &mut config.bindings.page.export_page,
+        );
+        let keymaps = page_keymaps(&config, true);
+        assert_eq!(keymaps[0].title, "Contents");
+        let toggle_toc = &keymaps[0].bindings[1];
+        assert_eq!(toggle_toc.description, "Return to the page");
+        assert_eq!(toggle_toc.keys, export_keys);
+    }
+
+    #[test]
+    fn test_hover_link() {
+        let mut page = page_from_html(
+            r#"<p>Rust is an <a rel="mw:WikiLink" href="./Iron_oxide" title="Iron oxide">iron oxide</a> formed by <a rel="mw:WikiLink" href="./Redox" title="Redox">redox</a>.</p>"#,
+        );
+        let buffer = draw(&mut page, 60, 20);
+        let position = |buffer: &Buffer, word: &str| {
+            (0..buffer.area.height)
+                .find_map(|y| row(buffer, y).find(word).map(|x| (x as u16, y)))
+                .unwrap()
+        };
+        let moved = |(column, row): (u16, u16)| MouseEvent {
+            kind: MouseEventKind::Moved,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        // the whole link is hovered, while the first link stays selected
+        page.select_first();
+        let redox = position(&buffer, "redox");
+        assert!(page.handle_mouse_events(moved(redox)).is_consumed());
+        assert_eq!(page.status_context().link_target, "Redox");
+        assert_eq!(page.status_context().notice, "→ Redox");
+        let buffer = draw(&mut page, 60, 20);
+        let cell = buffer.get(redox.0 + 4, redox.1);
+        assert_eq!(cell.bg, page.theme.hovered_link_bg);
+        assert!(!cell.modifier.contains(Modifier::UNDERLINED));
+        let (x, y) = position(&buffer, "iron");
+        assert!(buffer.get(x, y).modifier.contains(Modifier::UNDERLINED));
 
-        if self.viewport.y == 0 {
-            let title_line =
-                Line::raw(&self.page.title).patch_style(Style::default().fg(Color::Red).bold());
+        // moving within the link doesn't draw the page again
+        let result = page.handle_mouse_events(moved((redox.0 + 2, redox.1)));
+        assert!(!result.is_consumed());
 
-            lines.insert(0, title_line);
-            lines.pop();
+        // both words of the link belong to it, the text around it doesn't
+        let oxide = position(&buffer, "oxide");
+        page.handle_mouse_events(moved(oxide));
+        assert_eq!(page.status_context().link_target, "Iron oxide");
+        let (x, y) = position(&buffer, "formed");
+        assert!(page.handle_mouse_events(moved((x, y))).is_consumed());
+        assert_eq!(page.hovered, None);
+        assert_eq!(page.status_context().link_target, "Iron oxide");
+    }
+
+    #[test]
+    fn test_hover_link_after_wide_characters() {
+        let mut page = page_from_html(
+            r#"<p>日本語の記事 <a rel="mw:WikiLink" href="./Iron_oxide" title="Iron oxide">iron oxide</a> and <a rel="mw:WikiLink" href="./Redox" title="Redox">redox</a>.</p>"#,
+        );
+        let buffer = draw(&mut page, 60, 20);
+        // the cells after a wide character don't hold a symbol of their own
+        let (x, y) = (0..buffer.area.height)
+            .find_map(|y| {
+                (0..buffer.area.width)
+                    .find(|&x| {
+                        (0..5).all(|i| {
+                            x + i < buffer.area.width
+                                && buffer.get(x + i, y).symbol()
+                                    == &"redox"[i as usize..i as usize + 1]
+                        })
+                    })
+                    .map(|x| (x, y))
+            })
+            .unwrap();
+
+        let event = MouseEvent {
+            kind: MouseEventKind::Moved,
+            column: x + 4,
+            row: y,
+            modifiers: KeyModifiers::NONE,
+        };
+        assert!(page.handle_mouse_events(event).is_consumed());
+        assert_eq!(page.status_context().link_target, "Redox");
+    }
+
+    #[test]
+    fn test_scrollbar_line() {
+        // pages shorter than or as long as the viewport can't be scrolled
+        for row in [0, 5, 19] {
+            assert_eq!(scrollbar_line(row, 20, 12, 20), 0);
+            assert_eq!(scrollbar_line(row, 20, 20, 20), 0);
         }
 
-        f.render_widget(Paragraph::new(lines), page_area);
+        // the rows map to the lines that can be scrolled, 980 here
+        assert_eq!(scrollbar_line(0, 21, 1000, 20), 0);
+        assert_eq!(scrollbar_line(10, 21, 1000, 20), 490);
+        assert_eq!(scrollbar_line(20, 21, 1000, 20), 980);
+        assert_eq!(scrollbar_line(25, 21, 1000, 20), 980);
+        // a page a line longer than the viewport has its end in the lower half
+        assert_eq!(scrollbar_line(9, 20, 21, 20), 0);
+        assert_eq!(scrollbar_line(10, 20, 21, 20), 1);
+    }
+
+    #[test]
+    fn test_small_terminal() {
+        let mut page = empty_page(None);
 
-        if !self.is_zen_mode || zen_mode.contains(ZenModeComponents::SCROLLBAR) {
-            self.render_scrollbar(
-                f,
-                area,
-                rendered_page
-                    .lines
-                    .len()
-                    .saturating_sub(self.viewport.height as usize),
-            )
+        // the padding is left out before the page loses its last columns
+        assert_eq!(
+            fit_padding(Padding::uniform(1), Rect::new(0, 0, 80, 20)),
+            Padding::uniform(1)
+        );
+        assert_eq!(
+            fit_padding(Padding::uniform(4), Rect::new(0, 0, 24, 20)),
+            Padding::vertical(4)
+        );
+        assert!(screen(&draw(&mut page, 30, 6)).contains("Empty"));
+
+        // the status bar takes the only line
+        assert!(screen(&draw(&mut page, 60, 1)).contains("The terminal is too small"));
+        assert_eq!(page.scrollbar_area, None);
+    }
+
+    #[test]
+    fn test_scroll_sideways() {
+        let mut page = page_from_html("<p>Rust is an iron oxide.</p>");
+        render(&mut page);
+
+        // terminals send `<` and `>` with or without shift
+        let scroll = ActionPacket::single(Action::Page(PageAction::ScrollSideways(true)));
+        for modifiers in [KeyModifiers::NONE, KeyModifiers::SHIFT] {
+            let result = page.handle_key_events(KeyEvent::new(KeyCode::Char('>'), modifiers));
+            assert!(matches!(result, ActionResult::Consumed(packet) if packet == scroll));
+        }
+
+        // wrapped prose fits the width and doesn't scroll
+        page.update(Action::Page(PageAction::ScrollSideways(true)));
+        assert_eq!(page.viewport.x, 0);
+
+        let wide = format!("{}{}", "a".repeat(150), "b".repeat(150));
+        let mut page = page_from_html(&format!("<p>Rust is an iron oxide.</p><p>{wide}</p>"));
+        assert!(render(&mut page).contains("Rust is an iron oxide."));
+
+        for _ in 0..3 {
+            page.update(Action::Page(PageAction::ScrollSideways(true)));
+        }
+        assert_eq!(page.viewport.x, 12);
+        let screen = render(&mut page);
+        assert!(screen.contains("ron oxide.") && !screen.contains("Rust is"));
+        assert_eq!(
+            page.status_context().columns,
+            format!("cols 12–{}", 12 + page.viewport.width)
+        );
+
+        // the widest line ends at the right edge of the viewport
+        for _ in 0..100 {
+            page.update(Action::Page(PageAction::ScrollSideways(true)));
+        }
+        assert_eq!(page.viewport.x, 300 - page.viewport.width);
+        assert!(render(&mut page).contains(&"b".repeat(page.viewport.width as usize)));
+        page.update(Action::Page(PageAction::ScrollSideways(false)));
+        assert_eq!(page.viewport.x, 300 - page.viewport.width - 4);
+
+        // another width starts at the first column again
+        page.flush_render_cache();
+        assert_eq!(page.viewport.x, 0);
+        assert!(page.status_context().columns.is_empty());
+
+        // wide characters take two columns each, the line can't be broken before the punctuation
+        let wide = format!("日{}", "。".repeat(149));
+        let mut page = page_from_html(&format!("<p>Rust is an iron oxide.</p><p>{}</p>", wide));
+        render(&mut page);
+        for _ in 0..100 {
+            page.update(Action::Page(PageAction::ScrollSideways(true)));
         }
+        assert_eq!(page.viewport.x, 300 - page.viewport.width);
+        let screen = render(&mut page);
+        assert!(screen.contains(&"。 ".repeat(page.viewport.width as usize / 2)));
+        assert!(!screen.contains('日'));
+        assert_eq!(
+            page.status_context().columns,
+            format!("cols {}–300", 300 - page.viewport.width)
+        );
+    }
+
+    #[test]
+    fn test_scrollbar_drag() {
+        let mut page = page_from_html(&"<p>Rust is an iron oxide.</p>".repeat(100));
+        render(&mut page);
+        let area = page.scrollbar_area.unwrap();
+        let event = |kind, row| MouseEvent {
+            kind,
+            column: area.x + 1,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        let max_y =
+            rendered_page!(page, page.viewport.width).lines.len() as u16 - page.viewport.height;
+
+        // clicking the middle of the track jumps to the middle of the page
+        let down = event(
+            MouseEventKind::Down(MouseButton::Left),
+            area.y + area.height / 2,
+        );
+        assert!(page.handle_mouse_events(down).is_consumed());
+        assert!(page.viewport.y.abs_diff(max_y / 2) <= max_y / area.height);
+
+        // dragging scrolls until the button is released, even beyond the scrollbar
+        let drag = event(MouseEventKind::Drag(MouseButton::Left), area.bottom() + 3);
+        page.handle_mouse_events(drag);
+        assert_eq!(page.viewport.y, max_y);
+        page.handle_mouse_events(event(MouseEventKind::Up(MouseButton::Left), area.y));
+        assert_eq!(page.viewport.y, max_y);
+        let result =
+            page.handle_mouse_events(event(MouseEventKind::Drag(MouseButton::Left), area.y));
+        assert!(!result.is_consumed());
+        assert_eq!(page.viewport.y, max_y);
+
+        // clicks next to the scrollbar are left to the page
+        let mut click = event(MouseEventKind::Down(MouseButton::Left), area.y);
+        click.column = area.x - 1;
+        assert!(!page.handle_mouse_events(click).is_consumed());
+    }
+
+    #[test]
+    fn test_page_description() {
+        let mut page = page_from_html(&"<p>Rust is an iron oxide.</p>".repeat(30));
+        page.page.description =
+            Some("A usually reddish-brown oxide formed by the reaction of iron".to_string());
+
+        let buffer = draw(&mut page, 60, 20);
+        let rows: Vec<String> = (0..buffer.area.height)
+            .map(|y| row(&buffer, y).trim().to_string())
+            .collect();
+
+        // the description is wrapped below the title and followed by the page
+        let title = rows.iter().position(|row| row.contains("Empty")).unwrap();
+        let header = page.header_lines(page.viewport.width, false);
+        assert!(header.len() > 2, "the description wasn't wrapped");
+        assert!(rows[title + 1].starts_with("A usually"));
+        // the page starts with an empty line
+        assert!(rows[title + header.len() + 1].starts_with("Rust is an iron oxide."));
+        let cell = (0..buffer.area.width)
+            .map(|x| buffer.get(x, title as u16 + 1))
+            .find(|cell| cell.symbol() == "A")
+            .unwrap();
+        assert!(cell.modifier.contains(Modifier::DIM | Modifier::ITALIC));
+
+        // the header is only shown at the top of the page
+        page.viewport.y = 1;
+        assert!(!screen(&draw(&mut page, 60, 20)).contains("A usually"));
+
+        page.page.description = None;
+        assert_eq!(page.header_lines(page.viewport.width, false).len(), 1);
+    }
+
+    #[test]
+    fn test_header_hides_last_lines() {
+        let mut page = page_from_html(
+            &r#"<p><a rel="mw:WikiLink" href="./Iron" title="Iron">Iron</a></p>"#.repeat(30),
+        );
+        page.page.description = Some("A chemical element".to_string());
+        render(&mut page);
+        let visible = page.visible_lines();
+        assert_eq!(visible.end, page.viewport.height - 2);
+
+        // a link on a line the header pushed below the viewport isn't visible
+        let (y, idx) = rendered_page!(page, page.viewport.width)
+            .links
+            .iter()
+            .copied()
+            .find(|(y, _)| *y as u16 >= visible.end)
+            .unwrap();
+        page.select_node(idx);
+        page.check_and_update_scrolling();
+        assert!(page.viewport.y > 0);
+        assert!(page.visible_lines().contains(&(y as u16)));
     }
 }
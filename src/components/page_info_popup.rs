@@ -0,0 +1,285 @@
+use std::{sync::Arc, time::Instant};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem},
+};
+use wiki_api::{
+    languages::Language,
+    page::{ArticleUrl, PageInfo},
+    Endpoint,
+};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult},
+    config::{Config, Theme},
+    terminal::Frame,
+    ui::{centered_rect, spinner, StatefulList},
+};
+
+use super::Component;
+
+enum State {
+    /// The info is being fetched since the instant
+    Loading(Instant),
+    Loaded(PageInfo),
+    Failed(String),
+}
+
+/// A popup showing the information about a page, like its last edit and its categories. The
+/// categories can be opened
+pub struct PageInfoComponent {
+    endpoint: Endpoint,
+    language: Language,
+    title: String,
+
+    state: State,
+    categories: StatefulList<String>,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl PageInfoComponent {
+    /// Creates the popup, showing the info when it's already known or the spinner until it's
+    /// loaded
+    pub fn new(
+        endpoint: Endpoint,
+        language: Language,
+        title: String,
+        info: Option<PageInfo>,
+        config: Arc<Config>,
+        theme: Arc<Theme>,
+    ) -> Self {
+        let mut popup = Self {
+            endpoint,
+            language,
+            title,
+
+            state: State::Loading(Instant::now()),
+            categories: StatefulList::with_items(Vec::new()),
+
+            config,
+            theme,
+        };
+        if let Some(info) = info {
+            popup.set_info(info);
+        }
+        popup
+    }
+
+    fn set_info(&mut self, info: PageInfo) {
+        self.categories = StatefulList::with_items(info.categories.clone());
+        self.state = State::Loaded(info);
+    }
+
+    fn fetch_action(&self) -> Action {
+        Action::FetchPageInfo(self.endpoint.clone(), self.title.clone())
+    }
+
+    fn open_selected_category(&self) -> ActionResult {
+        let category = match self.categories.selected() {
+            Some(category) => category.clone(),
+            None => return ActionResult::Ignored,
+        };
+        ActionPacket::single(Action::PopPopup)
+            .action(Action::LoadArticleUrl(ArticleUrl {
+                title: category,
+                language: self.language,
+                endpoint: self.endpoint.clone(),
+                anchor: None,
+            }))
+            .into()
+    }
+
+    fn info_lines(&self, info: &PageInfo) -> Vec<Line<'static>> {
+        let field = |name: &str, value: String| {
+            Line::from(vec![
+                Span::raw(format!("{name}: ")).fg(self.theme.inactive_fg),
+                Span::raw(value).fg(self.theme.fg),
+            ])
+        };
+
+        let last_edit = match (info.last_edited.as_ref(), info.last_editor.as_ref()) {
+            (Some(timestamp), Some(editor)) => {
+                format!("{} by {editor}", format_timestamp(timestamp))
+            }
+            (Some(timestamp), None) => format_timestamp(timestamp),
+            _ => "Unknown".to_string(),
+        };
+        let protection = match info.protection.is_empty() {
+            true => "None".to_string(),
+            false => info
+                .protection
+                .iter()
+                .map(|protection| {
+                    let expiry = match protection.expiry.as_str() {
+                        "infinity" | "" => "indefinitely".to_string(),
+                        expiry => format!("until {}", format_timestamp(expiry)),
+                    };
+                    format!("{} ({}, {expiry})", protection.kind, protection.level)
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+        };
+        let watchers = match info.watchers {
+            Some(watchers) => watchers.to_string(),
+            None => "Not exposed".to_string(),
+        };
+
+        vec![
+            field("Title", info.title.clone()),
+            field("Last edit", last_edit),
+            field("Size", format!("{} bytes", info.length)),
+            field("Protection", protection),
+            field("Watchers", watchers),
+            field("Categories", info.categories.len().to_string()),
+        ]
+    }
+}
+
+/// Formats an ISO 8601 timestamp, like `2024-03-01T12:30:00Z`, as `2024-03-01 12:30 UTC`
+pub fn format_timestamp(timestamp: &str) -> String {
+    match timestamp.split_once('T') {
+        Some((date, time)) => format!("{date} {} UTC", time.get(..5).unwrap_or(time)),
+        None => timestamp.to_string(),
+    }
+}
+
+impl Component for PageInfoComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        matches!(self.state, State::Loading(_))
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.pop_popup.matches_event(key)
+            || self.config.bindings.page.show_page_info.matches_event(key)
+        {
+            return Action::PopPopup.into();
+        }
+
+        match self.state {
+            State::Loaded(_) if self.config.bindings.global.submit.matches_event(key) => {
+                self.open_selected_category()
+            }
+            State::Failed(_) if key.code == KeyCode::Char('r') => {
+                self.state = State::Loading(Instant::now());
+                self.fetch_action().into()
+            }
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::PageInfoLoaded(endpoint, title, result)
+                if endpoint == self.endpoint && title == self.title =>
+            {
+                match result {
+                    Ok(info) => self.set_info(info),
+                    Err(error) => self.state = State::Failed(error),
+                }
+            }
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.categories.previous()
+                }
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.categories.next()
+                }
+            }
+            Action::UnselectScroll => self.categories.unselect(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let hint = match self.state {
+            State::Loaded(_) => "<ENTER> Open category",
+            State::Failed(_) => "<r> Retry",
+            State::Loading(_) => "",
+        };
+        let block = self
+            .theme
+            .default_block()
+            .title("Page Information")
+            .title_bottom(Line::from(hint).right_aligned())
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 50, 60);
+        f.render_widget(Clear, area);
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+
+        let info = match self.state {
+            State::Loading(started) => {
+                f.render_widget(
+                    self.theme.default_paragraph(format!(
+                        "{} Loading the information about '{}'",
+                        spinner(started),
+                        self.title
+                    )),
+                    inner_area,
+                );
+                return;
+            }
+            State::Failed(ref error) => {
+                f.render_widget(
+                    self.theme
+                        .default_paragraph(format!("Unable to load the information: {error}"))
+                        .fg(self.theme.red_link_fg),
+                    inner_area,
+                );
+                return;
+            }
+            State::Loaded(ref info) => info,
+        };
+
+        let lines = self.info_lines(info);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(lines.len() as u16 + 1),
+                Constraint::Min(0),
+            ])
+            .split(inner_area);
+        f.render_widget(self.theme.default_paragraph(lines), chunks[0]);
+
+        let items = self
+            .categories
+            .get_items()
+            .iter()
+            .map(|category| ListItem::new(category.clone()).fg(self.theme.fg));
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_stateful_widget(list, chunks[1], self.categories.get_state_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_timestamp;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(
+            format_timestamp("2024-03-01T12:30:00Z"),
+            "2024-03-01 12:30 UTC"
+        );
+        assert_eq!(format_timestamp("infinity"), "infinity");
+    }
+}
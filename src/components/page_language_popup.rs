@@ -1,13 +1,14 @@
 use std::sync::Arc;
 
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyModifiers};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style, Stylize},
+    text::{Line, Span},
     widgets::{Clear, List, ListItem},
 };
 use tui_input::{backend::crossterm::EventHandler, Input};
-use wiki_api::page::LanguageLink;
+use wiki_api::{languages::Language, page::LanguageLink};
 
 use crate::{
     action::{Action, ActionPacket, ActionResult},
@@ -21,23 +22,39 @@ use super::Component;
 const FOCUS_INPUT: u8 = 0;
 const FOCUS_LIST: u8 = 1;
 
+/// A popup switching to another language of the page. The languages used before are listed first
 pub struct PageLanguageSelectionComponent {
     input: Input,
     focus: u8,
     list: StatefulList<LanguageLink>,
     language_links: Vec<LanguageLink>,
+    /// Whether the chosen language also becomes the language of the search
+    set_default: bool,
 
     config: Arc<Config>,
     theme: Arc<Theme>,
 }
 
 impl PageLanguageSelectionComponent {
-    pub fn new(language_links: Vec<LanguageLink>, config: Arc<Config>, theme: Arc<Theme>) -> Self {
+    pub fn new(
+        mut language_links: Vec<LanguageLink>,
+        recent_languages: &[Language],
+        config: Arc<Config>,
+        theme: Arc<Theme>,
+    ) -> Self {
+        // the sort is stable, so the other languages keep their order
+        language_links.sort_by_key(|link| {
+            recent_languages
+                .iter()
+                .position(|language| *language == link.language)
+                .unwrap_or(usize::MAX)
+        });
         Self {
             input: Input::default(),
             list: StatefulList::with_items(language_links.clone()),
             language_links,
             focus: 0,
+            set_default: false,
 
             config,
             theme,
@@ -45,22 +62,35 @@ impl PageLanguageSelectionComponent {
     }
 
     fn update_list(&mut self) {
-        let input_value = self.input.value();
+        let query = self.input.value().to_lowercase();
         let sorted_languages = self
             .language_links
             .iter()
             .filter(|lang_link| {
-                let lang = lang_link.language.name().to_lowercase();
-                let query = input_value.to_lowercase();
-                lang.contains(&query)
+                [
+                    lang_link.language.name(),
+                    lang_link.language.code(),
+                    &lang_link.autonym,
+                    &lang_link.title,
+                ]
+                .iter()
+                .any(|text| text.to_lowercase().contains(&query))
             })
             .map(|x| x.to_owned())
             .collect::<Vec<LanguageLink>>();
         self.list = StatefulList::with_items(sorted_languages);
+        // select the best match, so it can be opened right away
+        if !query.is_empty() {
+            self.list.next();
+        }
     }
 }
 
 impl Component for PageLanguageSelectionComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
     fn handle_key_events(&mut self, key: crossterm::event::KeyEvent) -> ActionResult {
         if self.config.bindings.global.submit.matches_event(key) {
             if let Some(link) = self.list.selected() {
@@ -74,6 +104,9 @@ impl Component for PageLanguageSelectionComponent {
                         ),
                     ));
                 }
+                if self.set_default {
+                    packet = packet.action(Action::SwitchLanguage(link.language));
+                }
                 return packet
                     .action(Action::LoadLangaugeLink(link.to_owned()))
                     .into();
@@ -93,6 +126,19 @@ impl Component for PageLanguageSelectionComponent {
         }
 
         match key.code {
+            KeyCode::Char('d') if key.modifiers == KeyModifiers::CONTROL => {
+                self.set_default = !self.set_default;
+                ActionResult::consumed()
+            }
+            // the list can be scrolled while typing the filter
+            KeyCode::Up if self.focus == FOCUS_INPUT => {
+                self.list.previous();
+                ActionResult::consumed()
+            }
+            KeyCode::Down if self.focus == FOCUS_INPUT => {
+                self.list.next();
+                ActionResult::consumed()
+            }
             KeyCode::Tab | KeyCode::BackTab => {
                 if self.focus == FOCUS_INPUT {
                     self.focus = FOCUS_LIST;
@@ -144,8 +190,15 @@ impl Component for PageLanguageSelectionComponent {
             .theme
             .default_block()
             .title("Switch Page Language")
+            .title_bottom(
+                Line::from(format!(
+                    "<ENTER> Switch | <CTRL+D> [{}] Search in this language",
+                    if self.set_default { "x" } else { " " }
+                ))
+                .right_aligned(),
+            )
             .style(Style::default().bg(self.theme.bg));
-        let area = centered_rect(area, 25, 60);
+        let area = centered_rect(area, 40, 60);
         f.render_widget(Clear, area);
         f.render_widget(popup_block, area);
 
@@ -179,19 +232,18 @@ impl Component for PageLanguageSelectionComponent {
             );
         }
 
-        let list_items = self
-            .list
-            .get_items()
-            .iter()
-            .map(|x| ListItem::new(x.language.name().to_owned()).fg(self.theme.fg));
-        let list_widget = List::new(list_items).highlight_style(if self.focus == FOCUS_LIST {
+        let list_items = self.list.get_items().iter().map(|x| {
+            ListItem::new(Line::from(vec![
+                Span::raw(x.language.name().to_owned()).fg(self.theme.fg),
+                Span::raw(format!("  {}", x.title)).fg(self.theme.inactive_fg),
+            ]))
+        });
+        let list_widget = List::new(list_items).highlight_style(
             Style::default()
                 .fg(self.theme.selected_fg)
                 .bg(self.theme.selected_bg)
-                .add_modifier(Modifier::ITALIC)
-        } else {
-            Style::default()
-        });
+                .add_modifier(Modifier::ITALIC),
+        );
         f.render_stateful_widget(list_widget, list_area, self.list.get_state_mut());
     }
 }
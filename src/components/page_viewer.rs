@@ -1,30 +1,111 @@
-use std::sync::Arc;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
 
 use ratatui::{
     prelude::{Alignment, Rect},
-    style::Style,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::Clear,
 };
 use tokio::sync::mpsc::UnboundedSender;
-
-use wiki_api::page::Page;
+use tracing::{debug, info, warn};
+use wiki_api::{
+    languages::Language,
+    page::{Page, Revision},
+    Endpoint,
+};
 
 use crate::{
-    action::{Action, ActionResult, PageViewerAction},
+    action::{Action, ActionResult, PageAction, PageViewerAction},
     config::{Config, Theme},
+    history::{VisitedPage, VisitedPages, VISITED_STORE},
+    reading_list::ReadingList,
+    store::Store,
     terminal::Frame,
-    ui::centered_rect,
+    ui::{centered_rect, spinner},
 };
 
-use super::{page::PageComponent, page_language_popup::PageLanguageSelectionComponent, Component};
+use super::{
+    disambiguation_popup::DisambiguationComponent,
+    highlights_popup::HighlightsComponent,
+    page::{page_keymaps, PageComponent, PagePosition, PREFETCH_DELAY},
+    page_language_popup::PageLanguageSelectionComponent,
+    visited_popup::VisitedPagesComponent,
+    Component, Keymap,
+};
+
+/// Number of pages before and after the current one that are kept in memory. The other pages in
+/// the history are fetched again when navigating to them
+const LOADED_PAGES: usize = 5;
+
+/// A page in the history of the page viewer
+enum HistoryEntry {
+    Loaded(Box<PageComponent>),
+    /// A page that was dropped to save memory
+    Dropped {
+        title: String,
+        endpoint: Endpoint,
+        language: Language,
+        /// The old revision the page was showing
+        revision: Option<Box<Revision>>,
+        position: PagePosition,
+    },
+}
+
+impl HistoryEntry {
+    fn drop_page(&mut self) {
+        if let HistoryEntry::Loaded(page) = self {
+            debug!("dropping the page '{}' from memory", page.page.title);
+            *self = HistoryEntry::Dropped {
+                title: page.page.title.clone(),
+                endpoint: page.page.endpoint.clone(),
+                language: page.page.language,
+                revision: page.revision().cloned().map(Box::new),
+                position: page.position(),
+            };
+        }
+    }
+}
+
+/// A page being loaded, shown by the loading indicator
+struct Loading {
+    /// What's loading, like "'Title'"
+    what: String,
+    started: Instant,
+    /// What happens while loading, like a retry of a failed request
+    status: Option<String>,
+}
 
 /// Can display multiple pages and supports selecting between them
 /// Responsible for fetching the pages and managing them (NOT rendering)
+///
+/// The pages form a history like in a browser. Opening a page after going back discards the pages
+/// that came after the current one
 #[derive(Default)]
 pub struct PageViewer {
-    page: Vec<PageComponent>,
+    history: Vec<HistoryEntry>,
+    /// Index of the current page in the history
     page_n: usize,
+    visited: VisitedPages,
+    /// Number of the latest save of the visited pages. Saves run in the background, older ones
+    /// that didn't start writing yet are skipped
+    visited_save: Arc<AtomicU64>,
+    /// Held while writing the visited pages, so the saves don't write at the same time
+    visited_save_lock: Arc<Mutex<()>>,
+    /// Number of pages on the reading list, shown in the status bar of the page
+    reading_list_len: usize,
+    /// Whether the offline mode is shown in the status bar of the page
+    is_offline: bool,
+    /// The width of the table of contents after moving its divider, kept for the whole session
+    toc_width: Option<u16>,
 
-    is_processing: bool,
+    /// `None` while no page is loading
+    loading: Option<Loading>,
     changing_page_language_popup: Option<PageLanguageSelectionComponent>,
 
     config: Arc<Config>,
@@ -35,32 +116,241 @@ pub struct PageViewer {
 
 impl PageViewer {
     fn current_page_mut(&mut self) -> Option<&mut PageComponent> {
-        self.page.get_mut(self.page_n)
+        match self.history.get_mut(self.page_n) {
+            Some(HistoryEntry::Loaded(page)) => Some(page),
+            _ => None,
+        }
     }
 
     pub fn current_page(&self) -> Option<&PageComponent> {
-        self.page.get(self.page_n)
+        match self.history.get(self.page_n) {
+            Some(HistoryEntry::Loaded(page)) => Some(page),
+            _ => None,
+        }
     }
 
-    fn display_page(&mut self, page: Page) {
-        self.page_n = self.page.len();
-        self.page.push(PageComponent::new(
-            page,
-            self.config.clone(),
-            self.theme.clone(),
-        ));
+    fn display_page(&mut self, page: Page) -> ActionResult {
+        // the meanings of a disambiguation page are listed in a popup on top of it
+        let is_disambiguation = page.is_disambiguation();
+        self.add_visited_page(&page);
+        self.history.truncate(self.page_n + 1);
+        self.history
+            .push(HistoryEntry::Loaded(Box::new(PageComponent::new(
+                page,
+                self.config.clone(),
+                self.theme.clone(),
+            ))));
+
+        let depth = self.config.page.history_depth;
+        if self.history.len() > depth + 1 {
+            self.history.drain(..self.history.len() - depth - 1);
+        }
+        self.page_n = self.history.len() - 1;
+        self.drop_distant_pages();
 
         if self.changing_page_language_popup.is_some() {
             self.changing_page_language_popup = None;
         }
 
-        // always disable the processing screen when displaying a page
-        self.is_processing = false;
+        // always hide the loading indicator when displaying a page
+        self.loading = None;
+
+        match is_disambiguation {
+            true => Action::ShowDisambiguation.into(),
+            false => ActionResult::consumed(),
+        }
+    }
+
+    /// Replaces the current page with the fetched page when it was dropped before
+    fn restore_page(&mut self, page: Page) {
+        let (position, revision) = match self.history.get(self.page_n) {
+            Some(HistoryEntry::Dropped {
+                title,
+                position,
+                revision,
+                ..
+            }) if *title == page.title => (position.clone(), revision.clone()),
+            _ => {
+                info!("the page '{}' is not the current page anymore", page.title);
+                return;
+            }
+        };
+
+        self.replace_current_page(page, position, revision);
     }
 
-    fn pop(&mut self) {
-        self.page.pop();
+    /// Replaces the current page with the page fetched again, at the same position
+    fn refresh_page(&mut self, page: Page) {
+        let (position, revision) = match self.current_page() {
+            Some(current) if current.page.title == page.title => (
+                current.position(),
+                current.revision().cloned().map(Box::new),
+            ),
+            _ => {
+                info!("the page '{}' is not the current page anymore", page.title);
+                self.loading = None;
+                return;
+            }
+        };
+        self.replace_current_page(page, position, revision);
+    }
+
+    fn replace_current_page(
+        &mut self,
+        page: Page,
+        position: PagePosition,
+        revision: Option<Box<Revision>>,
+    ) {
+        let mut component = PageComponent::new(page, self.config.clone(), self.theme.clone());
+        component.restore_position(position);
+        if let Some(revision) = revision {
+            component.mark_revision(*revision);
+        }
+        self.history[self.page_n] = HistoryEntry::Loaded(Box::new(component));
+        self.loading = None;
+    }
+
+    fn pop(&mut self) -> ActionResult {
+        self.history.truncate(self.page_n);
         self.page_n = self.page_n.saturating_sub(1);
+        self.load_current_page()
+    }
+
+    fn back(&mut self) -> ActionResult {
+        if self.page_n == 0 {
+            info!("no previous page in the history");
+            return ActionResult::consumed();
+        }
+        self.page_n -= 1;
+        self.load_current_page()
+    }
+
+    fn forward(&mut self) -> ActionResult {
+        if self.page_n + 1 >= self.history.len() {
+            info!("no next page in the history");
+            return ActionResult::consumed();
+        }
+        self.page_n += 1;
+        self.load_current_page()
+    }
+
+    /// Passes an event or action to the current page. Its links are prefetched when it was
+    /// scrolled or another link was selected
+    fn with_current_page(
+        &mut self,
+        f: impl FnOnce(&mut PageComponent) -> ActionResult,
+    ) -> ActionResult {
+        let page = match self.current_page_mut() {
+            Some(page) => page,
+            None => return ActionResult::Ignored,
+        };
+        let position = page.position();
+        let result = f(page);
+        if page.position() != position {
+            self.schedule_prefetch();
+        }
+        result
+    }
+
+    /// Prefetches the links of the current page after a short delay, the selected link only when
+    /// it stayed selected until then
+    fn schedule_prefetch(&self) {
+        let action_tx = match self.action_tx {
+            Some(ref action_tx) if self.config.api.prefetch => action_tx.clone(),
+            _ => return,
+        };
+        tokio::spawn(async move {
+            tokio::time::sleep(PREFETCH_DELAY).await;
+            let _ = action_tx.send(Action::Page(PageAction::Prefetch));
+        });
+    }
+
+    /// Fetches the current page again when it was dropped to save memory
+    fn load_current_page(&mut self) -> ActionResult {
+        self.drop_distant_pages();
+        match self.history.get(self.page_n) {
+            Some(HistoryEntry::Dropped {
+                title,
+                endpoint,
+                language,
+                revision,
+                ..
+            }) => Action::ReloadPage(
+                endpoint.clone(),
+                *language,
+                title.clone(),
+                revision.as_ref().map(|revision| revision.id),
+            )
+            .into(),
+            _ => ActionResult::consumed(),
+        }
+    }
+
+    fn add_visited_page(&mut self, page: &Page) {
+        self.visited.add(VisitedPage::now(
+            page.title.clone(),
+            page.language,
+            page.endpoint.clone(),
+        ));
+        self.save_visited_pages();
+    }
+
+    fn remove_visited_page(&mut self, page: VisitedPage) {
+        self.visited.remove(&page);
+        self.save_visited_pages();
+    }
+
+    fn save_visited_pages(&self) {
+        if !self.config.ui.persist_visited_pages {
+            return;
+        }
+
+        // the pages are saved whenever one is displayed, so the disk is kept off the render path
+        let visited = self.visited.clone();
+        let save = self.visited_save.fetch_add(1, Ordering::SeqCst) + 1;
+        let latest = self.visited_save.clone();
+        let lock = self.visited_save_lock.clone();
+        tokio::task::spawn_blocking(move || {
+            let _guard = lock.lock().unwrap_or_else(|error| error.into_inner());
+            if latest.load(Ordering::SeqCst) != save {
+                return;
+            }
+
+            let result = Store::create(VISITED_STORE, visited).and_then(|store| store.save());
+            if let Err(error) = result {
+                warn!("{:?}", error.context("failed saving the visited pages"));
+            }
+        });
+    }
+
+    /// Switches to the visited page when it's still loaded. Returns whether the page was loaded
+    pub fn show_visited_page(&mut self, visited: &VisitedPage) -> bool {
+        let idx = self.history.iter().position(|entry| match entry {
+            HistoryEntry::Loaded(page) => {
+                page.page.title == visited.title
+                    && page.page.language == visited.language
+                    && page.revision().is_none()
+            }
+            HistoryEntry::Dropped { .. } => false,
+        });
+
+        match idx {
+            Some(idx) => {
+                self.page_n = idx;
+                self.drop_distant_pages();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn drop_distant_pages(&mut self) {
+        let page_n = self.page_n;
+        for (idx, entry) in self.history.iter_mut().enumerate() {
+            if idx.abs_diff(page_n) > LOADED_PAGES {
+                entry.drop_page();
+            }
+        }
     }
 
     pub fn get_page_language_selection_popup(&self) -> PageLanguageSelectionComponent {
@@ -68,7 +358,139 @@ impl PageViewer {
             .current_page()
             .and_then(|x| x.page.language_links.to_owned())
             .unwrap_or_default();
-        PageLanguageSelectionComponent::new(language_links, self.config.clone(), self.theme.clone())
+        PageLanguageSelectionComponent::new(
+            language_links,
+            &self.visited.languages(),
+            self.config.clone(),
+            self.theme.clone(),
+        )
+    }
+
+    pub fn get_visited_pages_popup(&self) -> VisitedPagesComponent {
+        VisitedPagesComponent::new(
+            self.visited.pages(),
+            self.config.clone(),
+            self.theme.clone(),
+        )
+    }
+
+    pub fn get_highlights_popup(&self) -> HighlightsComponent {
+        let highlights = self
+            .current_page()
+            .map(|x| x.highlights())
+            .unwrap_or_default();
+        HighlightsComponent::new(highlights, self.config.clone(), self.theme.clone())
+    }
+
+    pub fn get_disambiguation_popup(&self) -> Option<DisambiguationComponent> {
+        let page = &self.current_page()?.page;
+        let entries = page.content.disambiguation_entries();
+        if entries.is_empty() {
+            return None;
+        }
+        // leave out the qualifier of the title, like "(disambiguation)" in English
+        let term = match page.title.rsplit_once(" (") {
+            Some((term, qualifier)) if qualifier.ends_with(')') => term,
+            _ => &page.title,
+        };
+        Some(DisambiguationComponent::new(
+            term.to_string(),
+            entries,
+            self.config.clone(),
+            self.theme.clone(),
+        ))
+    }
+
+    fn render_page(&mut self, f: &mut Frame<'_>, area: Rect) {
+        if self.current_page().is_none() {
+            if self.loading.is_none() {
+                f.render_widget(
+                    self.theme
+                        .default_paragraph("No page opened")
+                        .alignment(Alignment::Center),
+                    centered_rect(area, 100, 50),
+                );
+            }
+            return;
+        }
+
+        let (back, forward) = (self.page_n, self.history.len() - self.page_n - 1);
+        let (reading_list_len, is_offline) = (self.reading_list_len, self.is_offline);
+        let toc_width = self.toc_width;
+        if let Some(page) = self.current_page_mut() {
+            page.set_history(back, forward);
+            page.set_reading_list_len(reading_list_len);
+            page.set_offline(is_offline);
+            page.set_toc_width(toc_width);
+            page.render(f, area);
+
+            let images = page.images_to_load();
+            let messages = page.take_messages();
+            let released_action = page.take_released_action();
+            let is_rendering = page.is_rendering();
+            if let Some(ref action_tx) = self.action_tx {
+                // large pages are rendered a chunk after every frame, so the input isn't blocked
+                if is_rendering {
+                    action_tx
+                        .send(Action::Page(PageAction::RenderChunk))
+                        .unwrap();
+                }
+                for (level, text) in messages {
+                    action_tx.send(Action::ShowMessage(level, text)).unwrap();
+                }
+                // the binding of a key sequence prefix that no key followed
+                if let Some(action) = released_action {
+                    action_tx.send(action).unwrap();
+                }
+                for url in images {
+                    action_tx.send(Action::LoadImage(url)).unwrap();
+                }
+            }
+        }
+    }
+
+    /// Renders the loading indicator above the page, which stays usable while loading
+    fn render_loading(&self, f: &mut Frame<'_>, area: Rect) {
+        let loading = match self.loading {
+            Some(ref loading) => loading,
+            None => return,
+        };
+        let mut lines = vec![Line::from(format!(
+            "{} Loading {}…",
+            spinner(loading.started),
+            loading.what
+        ))];
+        if let Some(ref status) = loading.status {
+            lines.push(Line::from(status.as_str()).fg(self.theme.inactive_fg));
+        }
+        let width = lines.iter().map(Line::width).max().unwrap_or_default() as u16;
+        let width = (width + 4).max(16).min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        let block = self
+            .theme
+            .default_block()
+            .border_style(
+                Style::default()
+                    .fg(self.theme.border_highlight_fg)
+                    .bg(self.theme.border_highlight_bg),
+            )
+            .title_bottom(Line::from("<ESC> Cancel").right_aligned())
+            .style(Style::default().bg(self.theme.bg));
+        f.render_widget(Clear, area);
+        f.render_widget(
+            self.theme
+                .default_paragraph(lines)
+                .alignment(Alignment::Center)
+                .block(block),
+            area,
+        );
     }
 }
 
@@ -80,12 +502,57 @@ impl Component for PageViewer {
         theme: Arc<Theme>,
     ) -> anyhow::Result<()> {
         self.action_tx = Some(action_tx);
+        if config.ui.persist_visited_pages {
+            match VisitedPages::open() {
+                Ok(store) => self.visited = store.get().clone(),
+                Err(error) => warn!("{:?}", error.context("failed loading the visited pages")),
+            }
+        }
+        match ReadingList::open() {
+            Ok(store) => self.reading_list_len = store.get().entries().len(),
+            Err(error) => warn!("{:?}", error.context("failed loading the reading list")),
+        }
         self.config = config;
         self.theme = theme;
         Ok(())
     }
 
+    fn keymaps(&self) -> Vec<Keymap> {
+        match self.current_page() {
+            Some(page) => page.keymaps(),
+            None => page_keymaps(&self.config, false),
+        }
+    }
+
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        for entry in self.history.iter_mut() {
+            if let HistoryEntry::Loaded(page) = entry {
+                page.set_theme(theme.clone());
+            }
+        }
+        if let Some(ref mut popup) = self.changing_page_language_popup {
+            popup.set_theme(theme.clone());
+        }
+        self.theme = theme;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.loading.is_some() || self.current_page().is_some_and(|page| page.needs_redraw())
+    }
+
     fn handle_key_events(&mut self, key: crossterm::event::KeyEvent) -> ActionResult {
+        // the search prompt and the source view of the page receive all keys
+        if let Some(page) = self
+            .current_page_mut()
+            .filter(|page| page.is_searching() || page.is_viewing_source())
+        {
+            return page.handle_key_events(key);
+        }
+
+        if self.loading.is_some() && self.config.bindings.global.pop_popup.matches_event(key) {
+            return Action::CancelLoading.into();
+        }
+
         if self
             .config
             .bindings
@@ -96,67 +563,153 @@ impl Component for PageViewer {
             return Action::ShowPageLanguageSelection.into();
         }
 
+        if self
+            .config
+            .bindings
+            .page
+            .toggle_highlights
+            .matches_event(key)
+        {
+            return Action::ShowHighlights.into();
+        }
+
+        if self
+            .config
+            .bindings
+            .page
+            .show_disambiguation
+            .matches_event(key)
+        {
+            return Action::ShowDisambiguation.into();
+        }
+
+        if self
+            .config
+            .bindings
+            .page
+            .toggle_visited_pages
+            .matches_event(key)
+        {
+            return Action::ShowVisitedPages.into();
+        }
+
+        if self
+            .config
+            .bindings
+            .page
+            .toggle_bookmarks
+            .matches_event(key)
+        {
+            return Action::ShowBookmarks.into();
+        }
+
+        if self
+            .config
+            .bindings
+            .page
+            .toggle_reading_list
+            .matches_event(key)
+        {
+            return Action::ShowReadingList.into();
+        }
+
+        if self
+            .config
+            .bindings
+            .page
+            .toggle_watched_pages
+            .matches_event(key)
+        {
+            return Action::ShowWatchedPages.into();
+        }
+
         if self.config.bindings.page.pop_page.matches_event(key) {
+            // the zen mode is left before the page is closed
+            if self.current_page().is_some_and(|page| page.is_zen_mode()) {
+                return Action::Page(PageAction::ToggleZenMode).into();
+            }
             return Action::PageViewer(PageViewerAction::PopPage).into();
         }
 
-        if let Some(page) = self.current_page_mut() {
-            return page.handle_key_events(key);
+        if self.config.bindings.page.history_back.matches_event(key) {
+            return Action::PageViewer(PageViewerAction::Back).into();
+        }
+
+        if self.config.bindings.page.history_forward.matches_event(key) {
+            return Action::PageViewer(PageViewerAction::Forward).into();
         }
 
-        ActionResult::Ignored
+        self.with_current_page(|page| page.handle_key_events(key))
+    }
+
+    fn handle_mouse_events(&mut self, mouse: crossterm::event::MouseEvent) -> ActionResult {
+        match self.loading {
+            Some(_) => ActionResult::Ignored,
+            None => self.with_current_page(|page| page.handle_mouse_events(mouse)),
+        }
     }
 
     fn update(&mut self, action: Action) -> ActionResult {
         match action {
             Action::PageViewer(page_viewer_action) => match page_viewer_action {
-                PageViewerAction::DisplayPage(page) => self.display_page(page),
-                PageViewerAction::PopPage => self.pop(),
-                PageViewerAction::ExitLoading => self.is_processing = false,
-            },
-            Action::EnterProcessing => self.is_processing = true,
-            Action::EnterNormal => self.is_processing = false,
-            _ => {
-                if let Some(page) = self.current_page_mut() {
-                    return page.update(action);
+                PageViewerAction::DisplayPage(page) => {
+                    self.schedule_prefetch();
+                    return self.display_page(page);
                 }
-                return ActionResult::Ignored;
-            }
+                PageViewerAction::RestorePage(page) => {
+                    self.restore_page(page);
+                    self.schedule_prefetch();
+                }
+                PageViewerAction::PopPage => {
+                    self.schedule_prefetch();
+                    return self.pop();
+                }
+                PageViewerAction::Back => {
+                    self.schedule_prefetch();
+                    return self.back();
+                }
+                PageViewerAction::Forward => {
+                    self.schedule_prefetch();
+                    return self.forward();
+                }
+                PageViewerAction::RemoveVisitedPage(page) => self.remove_visited_page(page),
+                PageViewerAction::ReadingListChanged(len) => self.reading_list_len = len,
+                PageViewerAction::RefreshPage(page) => {
+                    self.refresh_page(page);
+                    self.schedule_prefetch();
+                }
+                PageViewerAction::OfflineChanged(is_offline) => self.is_offline = is_offline,
+                PageViewerAction::TocWidthChanged(toc_width) => self.toc_width = Some(toc_width),
+                PageViewerAction::ImageLoaded(url, image) => {
+                    // the page could have been left while the image was loading
+                    for entry in self.history.iter_mut() {
+                        if let HistoryEntry::Loaded(page) = entry {
+                            page.image_loaded(&url, &image);
+                        }
+                    }
+                }
+                PageViewerAction::StartLoading(what) => {
+                    self.loading = Some(Loading {
+                        what,
+                        started: Instant::now(),
+                        status: None,
+                    })
+                }
+                PageViewerAction::SetLoadingStatus(status) => {
+                    if let Some(ref mut loading) = self.loading {
+                        loading.status = Some(status);
+                    }
+                }
+                PageViewerAction::ExitLoading => self.loading = None,
+            },
+            Action::EnterNormal => self.loading = None,
+            _ => return self.with_current_page(|page| page.update(action)),
         }
         ActionResult::consumed()
     }
 
     fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
-        if self.is_processing {
-            f.render_widget(
-                self.theme.default_block().border_style(
-                    Style::default()
-                        .fg(self.theme.border_highlight_fg)
-                        .bg(self.theme.border_highlight_bg),
-                ),
-                area,
-            );
-            f.render_widget(
-                self.theme
-                    .default_paragraph("Processing")
-                    .alignment(Alignment::Center),
-                centered_rect(area, 100, 50),
-            );
-            return;
-        }
-
-        if self.current_page().is_none() {
-            f.render_widget(
-                self.theme
-                    .default_paragraph("No page opened")
-                    .alignment(Alignment::Center),
-                centered_rect(area, 100, 50),
-            );
-            return;
-        }
-
-        if let Some(page) = self.current_page_mut() {
-            page.render(f, area);
-        }
+        self.render_page(f, area);
+        self.render_loading(f, area);
     }
 }
@@ -0,0 +1,277 @@
+use std::sync::Arc;
+
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem},
+};
+use tracing::warn;
+use tui_input::{backend::crossterm::EventHandler, Input};
+use wiki_api::page::ArticleUrl;
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, PageViewerAction},
+    config::{Config, Theme},
+    reading_list::{ReadingList, ReadingListEntry},
+    terminal::Frame,
+    ui::{centered_rect, StatefulList},
+};
+
+use super::Component;
+
+/// A popup managing the reading list. Opening a page removes it from the list. Changes are saved
+/// right away
+pub struct ReadingListComponent {
+    entries: Vec<ReadingListEntry>,
+    /// The entries matching the filter
+    list: StatefulList<ReadingListEntry>,
+    filter: Input,
+    is_filtering: bool,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl ReadingListComponent {
+    pub fn new(entries: &[ReadingListEntry], config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        Self {
+            list: StatefulList::with_items(entries.to_vec()),
+            entries: entries.to_vec(),
+            filter: Input::default(),
+            is_filtering: false,
+
+            config,
+            theme,
+        }
+    }
+
+    fn update_list(&mut self) {
+        let selected = self.list.get_state_mut().selected();
+        let filter = self.filter.value().to_lowercase();
+        let entries = self
+            .entries
+            .iter()
+            .filter(|entry| entry.title.to_lowercase().contains(&filter))
+            .cloned()
+            .collect();
+        self.list = StatefulList::with_items(entries);
+        if let Some(idx) = selected {
+            let idx = idx.min(self.list.get_items().len().saturating_sub(1));
+            self.list.get_state_mut().select(Some(idx));
+        }
+    }
+
+    /// Applies the change to the stored reading list and the entries shown in the popup. The page
+    /// viewer is told the new number of entries
+    fn change(&mut self, change: impl Fn(&mut ReadingList)) -> Result<Action, ActionResult> {
+        let result = ReadingList::open().and_then(|mut store| {
+            change(store.get_mut());
+            store.save()?;
+            Ok(store.get().entries().to_vec())
+        });
+        match result {
+            Ok(entries) => {
+                self.entries = entries;
+                self.update_list();
+                Ok(Action::PageViewer(PageViewerAction::ReadingListChanged(
+                    self.entries.len(),
+                )))
+            }
+            Err(error) => {
+                let error = error.context("Unable to save the reading list");
+                warn!("{:?}", error);
+                Err(Action::PopupError(error.to_string()).into())
+            }
+        }
+    }
+
+    fn remove_selected(&mut self) -> ActionResult {
+        let entry = match self.list.selected() {
+            Some(entry) => entry.clone(),
+            None => return ActionResult::Ignored,
+        };
+        match self.change(|list| list.remove(&entry.endpoint, entry.language, &entry.title)) {
+            Ok(action) => action.into(),
+            Err(result) => result,
+        }
+    }
+
+    /// Opens the selected page and removes it from the reading list
+    fn open_selected(&mut self) -> ActionResult {
+        let entry = match self.list.selected() {
+            Some(entry) => entry.clone(),
+            None => return ActionResult::Ignored,
+        };
+        let changed =
+            match self.change(|list| list.remove(&entry.endpoint, entry.language, &entry.title)) {
+                Ok(action) => action,
+                Err(result) => return result,
+            };
+        ActionPacket::single(Action::PopPopup)
+            .action(changed)
+            .action(Action::LoadArticleUrl(ArticleUrl {
+                title: entry.title,
+                language: entry.language,
+                endpoint: entry.endpoint,
+                anchor: entry.anchor,
+            }))
+            .into()
+    }
+
+    /// Moves the selected entry past the entry above or below it in the shown list
+    fn move_selected(&mut self, up: bool) -> ActionResult {
+        let idx = match self.list.get_state_mut().selected() {
+            Some(idx) => idx,
+            None => return ActionResult::Ignored,
+        };
+        let other_idx = match up {
+            true if idx > 0 => idx - 1,
+            false if idx + 1 < self.list.get_items().len() => idx + 1,
+            _ => return ActionResult::consumed(),
+        };
+        let entry = self.list.get_items()[idx].clone();
+        let other = self.list.get_items()[other_idx].clone();
+
+        let result = match self.change(|list| list.swap(&entry, &other)) {
+            Ok(action) => action.into(),
+            Err(result) => return result,
+        };
+        self.list.get_state_mut().select(Some(other_idx));
+        result
+    }
+
+    fn handle_filter_input(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.pop_popup.matches_event(key) {
+            self.is_filtering = false;
+            self.filter.reset();
+            self.update_list();
+        } else if self.config.bindings.global.submit.matches_event(key) {
+            self.is_filtering = false;
+        } else if self.filter.handle_event(&Event::Key(key)).is_some() {
+            self.update_list();
+        }
+        ActionResult::consumed()
+    }
+}
+
+impl Component for ReadingListComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.is_filtering {
+            return self.handle_filter_input(key);
+        }
+
+        if self.config.bindings.global.pop_popup.matches_event(key)
+            || self
+                .config
+                .bindings
+                .page
+                .toggle_reading_list
+                .matches_event(key)
+        {
+            return Action::PopPopup.into();
+        }
+
+        if self.config.bindings.global.submit.matches_event(key) {
+            return self.open_selected();
+        }
+
+        match key.code {
+            KeyCode::Char('/') => {
+                self.is_filtering = true;
+                ActionResult::consumed()
+            }
+            KeyCode::Char('K') => self.move_selected(true),
+            KeyCode::Char('J') => self.move_selected(false),
+            KeyCode::Char('d') => self.remove_selected(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.list.previous()
+                }
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.list.next()
+                }
+            }
+            Action::UnselectScroll => self.list.unselect(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let block = self
+            .theme
+            .default_block()
+            .title(format!("Reading List ({})", self.entries.len()))
+            .title_bottom(
+                Line::from("<ENTER> Open | </> Filter | <J/K> Move | <d> Delete").right_aligned(),
+            )
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 50, 60);
+        f.render_widget(Clear, area);
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+
+        let list_area = if self.is_filtering || !self.filter.value().is_empty() {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(inner_area);
+            f.render_widget(
+                self.theme
+                    .default_paragraph(format!("/{}", self.filter.value()))
+                    .fg(self.theme.highlight_fg),
+                chunks[0],
+            );
+            if self.is_filtering {
+                f.set_cursor(
+                    chunks[0].x + 1 + self.filter.visual_cursor() as u16,
+                    chunks[0].y,
+                );
+            }
+            chunks[1]
+        } else {
+            inner_area
+        };
+
+        if self.list.get_items().is_empty() {
+            f.render_widget(
+                self.theme.default_paragraph("Nothing to read later"),
+                list_area,
+            );
+            return;
+        }
+
+        let items = self.list.get_items().iter().map(|entry| {
+            let mut location = entry.language.name().to_string();
+            if let Some(anchor) = entry.anchor.as_ref() {
+                location.push_str(&format!(" | #{anchor}"));
+            }
+            ListItem::new(Line::from(vec![
+                Span::raw(entry.title.clone()).fg(self.theme.fg),
+                Span::raw(format!("  {location}")).fg(self.theme.inactive_fg),
+            ]))
+        });
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_stateful_widget(list, list_area, self.list.get_state_mut());
+    }
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::Clear,
+};
+use wiki_api::rate_limit::request_stats;
+
+use crate::{
+    action::{Action, ActionResult},
+    config::{Config, Theme},
+    terminal::Frame,
+    ui::centered_rect,
+};
+
+use super::Component;
+
+/// A popup counting the requests sent in this session, to see how the rate limit affects them.
+/// The counters are updated while the popup is shown
+pub struct RequestStatsComponent {
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl RequestStatsComponent {
+    pub fn new(config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        Self { config, theme }
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let field = |name: &str, value: String| {
+            Line::from(vec![
+                Span::raw(format!("{name}: ")).fg(self.theme.inactive_fg),
+                Span::raw(value).fg(self.theme.fg),
+            ])
+        };
+
+        let api = &self.config.api;
+        let rate_limit = match api.rate_limit > 0.0 {
+            true => format!(
+                "{} requests per second, bursts of {}",
+                api.rate_limit,
+                api.rate_burst.max(1)
+            ),
+            false => "Disabled".to_string(),
+        };
+        let stats = request_stats();
+        vec![
+            field("Rate limit", rate_limit),
+            Line::default(),
+            field("Sent", stats.sent.to_string()),
+            field("Throttled", stats.throttled.to_string()),
+            field("Failed", stats.failed.to_string()),
+        ]
+    }
+}
+
+impl Component for RequestStatsComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.pop_popup.matches_event(key)
+            || self
+                .config
+                .bindings
+                .global
+                .show_request_stats
+                .matches_event(key)
+        {
+            return Action::PopPopup.into();
+        }
+        ActionResult::Ignored
+    }
+
+    fn needs_redraw(&self) -> bool {
+        true
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let block = self
+            .theme
+            .default_block()
+            .title("Requests")
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 40, 30);
+        f.render_widget(Clear, area);
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+        f.render_widget(self.theme.default_paragraph(self.lines()), inner_area);
+    }
+}
@@ -0,0 +1,286 @@
+use std::{sync::Arc, time::Instant};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem},
+};
+use wiki_api::{languages::Language, page::Revision, Endpoint};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult},
+    config::{Config, Theme},
+    terminal::Frame,
+    ui::{centered_rect, spinner, ScrollBehaviour, StatefulList},
+};
+
+use super::{page_info_popup::format_timestamp, Component};
+
+/// Older revisions are fetched when the selection gets this close to the end of the list
+const LOAD_MORE_MARGIN: usize = 5;
+
+/// Returns the change of the page size by the revision. The size of the previous revision is only
+/// known once it's loaded, except for the revision creating the page
+fn size_delta(revisions: &[Revision], idx: usize) -> Option<isize> {
+    let revision = &revisions[idx];
+    if revision.parent_id == 0 {
+        return Some(revision.size as isize);
+    }
+    revisions
+        .get(idx + 1)
+        .filter(|parent| parent.id == revision.parent_id)
+        .map(|parent| revision.size as isize - parent.size as isize)
+}
+
+/// A popup listing the revisions of a page, newest first. Older revisions are fetched while
+/// scrolling down and the selected revision can be opened
+pub struct RevisionsComponent {
+    endpoint: Endpoint,
+    language: Language,
+    title: String,
+
+    list: StatefulList<Revision>,
+    /// Continues the listing with older revisions, `None` after the oldest revision
+    continue_from: Option<String>,
+    /// When the batch being fetched started loading, `None` while nothing is loading
+    loading: Option<Instant>,
+    /// The error of the last fetch, fetching is only retried when pressing 'r'
+    error: Option<String>,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl RevisionsComponent {
+    /// Creates the popup, which is loading the first batch of revisions
+    pub fn new(
+        endpoint: Endpoint,
+        language: Language,
+        title: String,
+        config: Arc<Config>,
+        theme: Arc<Theme>,
+    ) -> Self {
+        Self {
+            endpoint,
+            language,
+            title,
+
+            list: StatefulList::with_items(Vec::new())
+                .scroll_behavior(ScrollBehaviour::StickToEnds),
+            continue_from: None,
+            loading: Some(Instant::now()),
+            error: None,
+
+            config,
+            theme,
+        }
+    }
+
+    fn fetch_action(&mut self) -> Action {
+        self.loading = Some(Instant::now());
+        self.error = None;
+        Action::FetchRevisions(
+            self.endpoint.clone(),
+            self.title.clone(),
+            self.continue_from.clone(),
+        )
+    }
+
+    /// Fetches the next older revisions when the selection is near the end of the list
+    fn fetch_more(&mut self) -> ActionResult {
+        let selected = self.list.get_state_mut().selected().unwrap_or_default();
+        let is_near_end = selected + LOAD_MORE_MARGIN >= self.list.get_items().len();
+        if !is_near_end
+            || self.loading.is_some()
+            || self.error.is_some()
+            || self.continue_from.is_none()
+        {
+            return ActionResult::consumed();
+        }
+        self.fetch_action().into()
+    }
+
+    fn open_selected_revision(&self) -> ActionResult {
+        let revision = match self.list.selected() {
+            Some(revision) => revision.clone(),
+            None => return ActionResult::Ignored,
+        };
+        ActionPacket::single(Action::PopPopup)
+            .action(Action::LoadRevision(
+                self.endpoint.clone(),
+                self.language,
+                self.title.clone(),
+                revision,
+            ))
+            .into()
+    }
+
+    fn revision_item(&self, idx: usize) -> ListItem<'static> {
+        let revision = &self.list.get_items()[idx];
+        let (delta, delta_fg) = match size_delta(self.list.get_items(), idx) {
+            Some(delta) if delta < 0 => (delta.to_string(), self.theme.red_link_fg),
+            Some(delta) => (format!("+{delta}"), self.theme.fg),
+            None => ("?".to_string(), self.theme.inactive_fg),
+        };
+        let user = match revision.user.is_empty() {
+            true => "(hidden)",
+            false => revision.user.as_str(),
+        };
+
+        ListItem::new(Line::from(vec![
+            Span::raw(format!("{} ", format_timestamp(&revision.timestamp)))
+                .fg(self.theme.inactive_fg),
+            Span::raw(format!("{user:<20.20} ")).fg(self.theme.fg),
+            Span::raw(format!("{delta:>7} ")).fg(delta_fg),
+            Span::raw(revision.comment.clone())
+                .fg(self.theme.fg)
+                .italic(),
+        ]))
+    }
+}
+
+impl Component for RevisionsComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.pop_popup.matches_event(key)
+            || self.config.bindings.page.show_revisions.matches_event(key)
+        {
+            return Action::PopPopup.into();
+        }
+
+        if self.config.bindings.global.submit.matches_event(key) {
+            return self.open_selected_revision();
+        }
+        if self.error.is_some() && key.code == KeyCode::Char('r') {
+            return self.fetch_action().into();
+        }
+        ActionResult::Ignored
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::RevisionsLoaded(endpoint, title, result)
+                if endpoint == self.endpoint && title == self.title =>
+            {
+                self.loading = None;
+                match result {
+                    Ok(revisions) => {
+                        self.list.get_items_mut().extend(revisions.revisions);
+                        self.continue_from = revisions.continue_from;
+                    }
+                    Err(error) => self.error = Some(error),
+                }
+            }
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.list.previous()
+                }
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.list.next()
+                }
+                return self.fetch_more();
+            }
+            Action::UnselectScroll => self.list.unselect(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let hint = match self.error {
+            Some(_) => "<ENTER> View revision | <r> Retry",
+            None => "<ENTER> View revision",
+        };
+        let block = self
+            .theme
+            .default_block()
+            .title(format!("Revisions of '{}'", self.title))
+            .title_bottom(Line::from(hint).right_aligned())
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 70, 70);
+        f.render_widget(Clear, area);
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+
+        let status = match (self.loading, self.error.as_ref()) {
+            (Some(started), _) => Some(
+                Line::from(format!("{} Loading the revisions", spinner(started)))
+                    .fg(self.theme.inactive_fg),
+            ),
+            (None, Some(error)) => Some(
+                Line::from(format!("Unable to load the revisions: {error}"))
+                    .fg(self.theme.red_link_fg),
+            ),
+            (None, None) if self.list.get_items().is_empty() => {
+                Some(Line::from("No revisions").fg(self.theme.inactive_fg))
+            }
+            (None, None) => None,
+        };
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(status.is_some() as u16),
+            ])
+            .split(inner_area);
+        if let Some(status) = status {
+            f.render_widget(self.theme.default_paragraph(status), chunks[1]);
+        }
+
+        let items: Vec<ListItem> = (0..self.list.get_items().len())
+            .map(|idx| self.revision_item(idx))
+            .collect();
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_stateful_widget(list, chunks[0], self.list.get_state_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::page::Revision;
+
+    use super::size_delta;
+
+    fn revision(id: usize, parent_id: usize, size: usize) -> Revision {
+        Revision {
+            id,
+            parent_id,
+            timestamp: String::new(),
+            user: String::new(),
+            size,
+            comment: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_size_delta() {
+        let revisions = [
+            revision(3, 2, 120),
+            revision(2, 1, 150),
+            revision(1, 0, 100),
+        ];
+        assert_eq!(size_delta(&revisions, 0), Some(-30));
+        assert_eq!(size_delta(&revisions, 1), Some(50));
+        assert_eq!(size_delta(&revisions, 2), Some(100));
+
+        // the previous revision is not loaded yet
+        assert_eq!(size_delta(&revisions[..2], 1), None);
+    }
+}
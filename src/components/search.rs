@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent};
@@ -8,22 +8,49 @@ use ratatui::{
     text::{Line, Span, Text},
     widgets::{HighlightSpacing, List, ListItem, Wrap},
 };
-use tokio::sync::mpsc;
-use tracing::{error, info, warn};
+use tokio::{sync::mpsc, task::JoinHandle};
+use tracing::{debug, error, info, warn};
 use wiki_api::{
     languages::Language,
-    search::{Search as ApiSearch, SearchContinue, SearchInfo, SearchRequest, SearchResult},
+    rate_limit::{with_priority, Priority},
+    search::{
+        suggestions, Namespace, Search as ApiSearch, SearchContinue, SearchInfo, SearchRequest,
+        SearchResult, SnippetFragment,
+    },
     Endpoint,
 };
 
 use crate::{
     action::{Action, ActionPacket, ActionResult, SearchAction},
+    client::proxy_url,
     config::{Config, Theme},
+    retry::{describe_error, RetryPolicy},
     terminal::Frame,
     ui::{centered_rect, ScrollBehaviour, StatefulList},
 };
 
-use super::Component;
+use super::{search_filter_popup::SearchFilterComponent, Component, Keymap};
+
+/// How long to wait after the last keystroke before fetching the suggestions
+const SUGGESTIONS_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Filters applied to all searches of the session
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct SearchFilters {
+    pub namespaces: Vec<Namespace>,
+    /// Language of the searches, overriding the search language when set
+    pub language: Option<Language>,
+}
+
+impl SearchFilters {
+    /// The filters configured for new sessions
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            namespaces: config.api.search_namespaces.clone(),
+            language: None,
+        }
+    }
+}
 
 #[derive(Default, Debug, PartialEq, Eq, Clone)]
 pub enum Mode {
@@ -38,12 +65,27 @@ pub enum Mode {
 
 pub struct SearchComponent {
     mode: Mode,
-    pub endpoint: Option<Endpoint>,
+    /// Index of the searched site in the configured sites
+    site: usize,
+    /// Index of the site the shown results are from, another site than the searched one after
+    /// searching a query on it once
+    results_site: usize,
     pub language: Option<Language>,
+    filters: SearchFilters,
 
     search_results: StatefulList<SearchResult>,
     search_info: Option<SearchInfo>,
     continue_search: Option<SearchContinue>,
+    /// The task loading the next results, `None` when no results are being loaded
+    continue_task: Option<JoinHandle<()>>,
+    /// Why loading the next results failed, cleared when retrying
+    continue_error: Option<String>,
+    /// Whether the first result of the running search is opened right away
+    is_lucky: bool,
+    /// What happens while searching, like a retry of a failed request
+    search_status: Option<String>,
+    /// The task fetching the suggestions for the search bar
+    suggestions_task: Option<JoinHandle<()>>,
 
     config: Arc<Config>,
     theme: Arc<Theme>,
@@ -55,13 +97,20 @@ impl Default for SearchComponent {
     fn default() -> SearchComponent {
         SearchComponent {
             mode: Mode::default(),
-            endpoint: None,
+            site: 0,
+            results_site: 0,
             language: None,
+            filters: SearchFilters::default(),
 
             search_results: StatefulList::with_items(Vec::new())
                 .scroll_behavior(ScrollBehaviour::StickToEnds),
             search_info: None,
             continue_search: None,
+            continue_task: None,
+            continue_error: None,
+            is_lucky: false,
+            search_status: None,
+            suggestions_task: None,
 
             config: Arc::new(Config::default()),
             theme: Arc::new(Theme::default()),
@@ -71,13 +120,77 @@ impl Default for SearchComponent {
     }
 }
 
+// HACK: we need a way to get the API endpoint from the language
+/// Appends the new results, skipping the ones already in the list. The api can return a result in
+/// more than one batch when the search index changes in between
+fn append_unique_results(results: &mut Vec<SearchResult>, new_results: &mut Vec<SearchResult>) {
+    let mut pageids: HashSet<usize> = results.iter().map(|result| result.pageid).collect();
+    new_results.retain(|result| pageids.insert(result.pageid));
+    results.append(new_results);
+}
+
+/// Wraps the snippet into lines of the width, styling the matches with the match style. Words
+/// wider than the width are put on a line of their own
+fn wrap_snippet(
+    fragments: &[SnippetFragment],
+    width: usize,
+    style: Style,
+    match_style: Style,
+) -> Vec<Line<'static>> {
+    // a word can consist of matched and unmatched parts, like a match with a suffix
+    let mut words: Vec<Vec<Span<'static>>> = Vec::new();
+    let mut in_word = false;
+    for fragment in fragments {
+        let fragment_style = if fragment.is_match {
+            match_style
+        } else {
+            style
+        };
+        for (idx, part) in fragment.text.split(char::is_whitespace).enumerate() {
+            if idx > 0 {
+                in_word = false;
+            }
+            if part.is_empty() {
+                continue;
+            }
+
+            let span = Span::styled(part.to_string(), fragment_style);
+            match words.last_mut() {
+                Some(word) if in_word => word.push(span),
+                _ => words.push(vec![span]),
+            }
+            in_word = true;
+        }
+    }
+
+    let mut lines: Vec<Line<'static>> = Vec::new();
+    let mut line_width = 0;
+    for word in words {
+        let word_width: usize = word.iter().map(Span::width).sum();
+        match lines.last_mut() {
+            Some(line) if line_width + 1 + word_width <= width => {
+                line.spans.push(Span::styled(" ", style));
+                line.spans.extend(word);
+                line_width += 1 + word_width;
+            }
+            _ => {
+                lines.push(Line::from(word));
+                line_width = word_width;
+            }
+        }
+    }
+    lines
+}
+
 impl SearchComponent {
-    fn build_search(&self, query: String) -> Result<SearchRequest> {
+    fn build_search(
+        &self,
+        query: String,
+        endpoint: Endpoint,
+        language: Language,
+    ) -> Result<SearchRequest> {
         let api_config = &self.config.api;
 
-        let endpoint = self.endpoint.clone().unwrap_or(api_config.endpoint.clone());
-        let language = self.language.unwrap_or(api_config.language);
-
         Ok(ApiSearch::builder()
             .query(query)
             .endpoint(endpoint)
@@ -87,32 +200,52 @@ impl SearchComponent {
             .search_type(api_config.search_type.clone())
             .info(api_config.search_info.clone())
             .rewrites(api_config.search_rewrites)
+            .namespaces(self.filters.namespaces.clone())
             .sort_order(api_config.search_sort_order.clone()))
     }
 
-    fn start_search(&mut self, query: String) -> ActionResult {
+    /// Searches the site with the index in the configured sites for the query
+    fn start_search(&mut self, site: usize, query: String, is_lucky: bool) -> ActionResult {
+        self.is_lucky = is_lucky;
+        self.results_site = site;
         let tx = self.action_tx.clone().unwrap();
-        let search_request = match self.build_search(query) {
+        let (endpoint, language) = self.searched_site(site);
+        let proxy = proxy_url(&self.config.api, &endpoint);
+        let search_request = match self.build_search(query, endpoint, language) {
             Ok(search_request) => search_request,
             Err(error) => {
                 error!("Unable to build the search request: {:?}", error);
                 return ActionResult::consumed();
             }
         };
+        let retry = RetryPolicy::new(&self.config.api);
         tokio::spawn(async move {
             tx.send(Action::Search(SearchAction::ChangeMode(Mode::Searching)))
                 .unwrap();
             tx.send(Action::Search(SearchAction::ClearSearchResults))
                 .unwrap();
-            match search_request.search().await {
+            let result = retry
+                .run(
+                    || search_request.clone().search(),
+                    |retry| {
+                        tx.send(Action::Search(SearchAction::SetStatus(retry.to_string())))
+                            .unwrap()
+                    },
+                )
+                .await;
+            match result {
                 Ok(search) => tx
                     .send(Action::Search(SearchAction::FinshSearch(search)))
                     .unwrap(),
                 Err(error) => {
+                    let message = describe_error(&error, proxy.as_deref());
                     let error = error.context("Unable to execute the search");
                     tx.send(Action::Search(SearchAction::ChangeMode(Mode::NoSearch)))
                         .unwrap();
-                    tx.send(Action::PopupError(error.to_string())).unwrap();
+                    tx.send(Action::PopupError(
+                        message.unwrap_or_else(|| error.to_string()),
+                    ))
+                    .unwrap();
                     error!("{:?}", error);
                 }
             };
@@ -121,13 +254,73 @@ impl SearchComponent {
         ActionResult::consumed()
     }
 
+    /// Returns the endpoint and the language searched, ignoring the search filters
+    pub fn site(&self) -> (Endpoint, Language) {
+        let language = self.language.unwrap_or(self.config.api.language);
+        (
+            self.config.api.sites[self.site].endpoint(language),
+            language,
+        )
+    }
+
+    /// Returns the index of the searched site in the configured sites
+    pub fn site_index(&self) -> usize {
+        self.site
+    }
+
+    /// Returns the endpoint and the language a search on the site with the index uses. Another
+    /// site than the searched one is searched in its own language if it has one
+    fn searched_site(&self, site: usize) -> (Endpoint, Language) {
+        let site_language = match site == self.site {
+            true => None,
+            false => self.config.api.sites[site].language,
+        };
+        let language = self
+            .filters
+            .language
+            .or(site_language)
+            .unwrap_or_else(|| self.site().1);
+        (self.config.api.sites[site].endpoint(language), language)
+    }
+
+    /// Fetches the suggestions for the query once no other query has been typed for
+    /// [`SUGGESTIONS_DEBOUNCE`]. A pending fetch for a previous query is cancelled
+    fn fetch_suggestions(&mut self, query: String) -> ActionResult {
+        if let Some(task) = self.suggestions_task.take() {
+            task.abort();
+        }
+
+        if query.trim().is_empty() {
+            return Action::ShowSearchSuggestions(query, Vec::new()).into();
+        }
+
+        let tx = self.action_tx.clone().unwrap();
+        let (endpoint, language) = self.site();
+        let limit = self.config.api.suggestion_limit;
+        self.suggestions_task = Some(tokio::spawn(async move {
+            tokio::time::sleep(SUGGESTIONS_DEBOUNCE).await;
+
+            // suggestions are optional, so errors only result in no suggestions
+            let suggestions = suggestions(&query, endpoint, language, limit);
+            let suggestions = with_priority(Priority::Prefetch, suggestions)
+                .await
+                .unwrap_or_else(|error| {
+                    debug!("{:?}", error.context("failed fetching the suggestions"));
+                    Vec::new()
+                });
+            tx.send(Action::ShowSearchSuggestions(query, suggestions))
+                .unwrap();
+        }));
+
+        ActionResult::consumed()
+    }
+
     fn finish_search(&mut self, mut search: ApiSearch) -> ActionResult {
         let has_results = search.info.total_hits.unwrap_or_default() != 0;
         let has_suggestion = search.info.suggestion.is_some();
 
-        self.search_results
-            .get_items_mut()
-            .append(&mut search.results);
+        self.continue_task = None;
+        append_unique_results(self.search_results.get_items_mut(), &mut search.results);
 
         self.continue_search = search.continue_data().take();
         self.search_info = Some(search.info);
@@ -135,6 +328,24 @@ impl SearchComponent {
         crate::trace_dbg!(has_results);
         crate::trace_dbg!(has_suggestion);
 
+        // a query the api had to correct or rewrite is ambiguous, so the results are shown
+        // instead of guessing which page was meant
+        let is_lucky = std::mem::take(&mut self.is_lucky);
+        let search_info = self.search_info.as_ref().unwrap();
+        if is_lucky && has_results && !has_suggestion && search_info.rewritten_query.is_none() {
+            if let Some(result) = self.search_results.get_items().first() {
+                info!("opening the first result for '{}'", search_info.query);
+                return ActionPacket::single(Action::Search(SearchAction::ChangeMode(
+                    Mode::FinishedSearch,
+                )))
+                .action(Action::LoadLuckySearchResult(
+                    result.clone(),
+                    search_info.query.clone(),
+                ))
+                .into();
+            }
+        }
+
         if !has_results && !has_suggestion {
             warn!("could not find any results and no suggestion was given");
             return ActionPacket::single(Action::PopupMessage(
@@ -153,43 +364,75 @@ impl SearchComponent {
         Action::Search(SearchAction::ChangeMode(Mode::FinishedSearch)).into()
     }
 
+    /// Loads the next results and appends them to the current ones. The current results stay
+    /// visible while loading
     fn continue_search(&mut self) -> ActionResult {
-        if self.continue_search.is_none() {
-            return ActionPacket::single(Action::PopupMessage(
-                "Warning".to_string(),
-                "Could not find any search results and no suggestion could be made".to_string(),
-            ))
-            .action(Action::Search(SearchAction::ChangeMode(Mode::NoSearch)))
-            .into();
+        if self.continue_task.is_some() {
+            return ActionResult::consumed();
         }
 
-        let code = self.continue_search.as_ref().unwrap();
+        let code = match self.continue_search {
+            Some(ref code) => code,
+            None => {
+                return Action::PopupMessage(
+                    "Information".to_string(),
+                    "All search results have already been loaded".to_string(),
+                )
+                .into()
+            }
+        };
+
         let tx = self.action_tx.clone().unwrap();
-        let search_request = ApiSearch::builder()
-            .query(code.query.clone())
-            .endpoint(code.endpoint.clone())
-            .language(code.language)
-            .offset(code.offset);
-        tokio::spawn(async move {
-            tx.send(Action::Search(SearchAction::ChangeMode(Mode::Searching)))
-                .unwrap();
+        let search_request =
+            match self.build_search(code.query.clone(), code.endpoint.clone(), code.language) {
+                Ok(search_request) => search_request.offset(code.offset),
+                Err(error) => {
+                    error!("Unable to build the search request: {:?}", error);
+                    return ActionResult::consumed();
+                }
+            };
+
+        let proxy = proxy_url(&self.config.api, &code.endpoint);
+        self.continue_error = None;
+        self.continue_task = Some(tokio::spawn(async move {
             match search_request.search().await {
                 Ok(search) => tx
                     .send(Action::Search(SearchAction::FinshSearch(search)))
                     .unwrap(),
                 Err(error) => {
+                    let message = describe_error(&error, proxy.as_deref())
+                        .unwrap_or_else(|| error.root_cause().to_string());
                     let error = error.context("Unable to continue the search");
-                    tx.send(Action::Search(SearchAction::ChangeMode(Mode::NoSearch)))
+                    tx.send(Action::Search(SearchAction::ContinueSearchFailed(message)))
                         .unwrap();
-                    tx.send(Action::PopupError(error.to_string())).unwrap();
                     error!("{:?}", error)
                 }
             };
-        });
+        }));
 
         ActionResult::consumed()
     }
 
+    fn continue_search_failed(&mut self, error: String) -> ActionResult {
+        self.continue_task = None;
+        self.continue_error = Some(error);
+        ActionResult::consumed()
+    }
+
+    /// Loads the next results once the last result is selected. After a failure, the results are
+    /// only loaded again when retrying explicitly
+    fn continue_search_at_end(&mut self) -> ActionResult {
+        let last_result = self.search_results.get_items().len().checked_sub(1);
+        if self.search_results.get_state_mut().selected() != last_result
+            || self.continue_search.is_none()
+            || self.continue_error.is_some()
+        {
+            return ActionResult::consumed();
+        }
+
+        self.continue_search()
+    }
+
     fn open_selected_result(&self) -> ActionResult {
         if let Some(selected_result) = self.search_results.selected() {
             return ActionPacket::default()
@@ -203,6 +446,10 @@ impl SearchComponent {
     fn clear_search_results(&mut self) -> ActionResult {
         self.search_results = StatefulList::with_items(Vec::new());
         self.continue_search = None;
+        self.continue_error = None;
+        if let Some(task) = self.continue_task.take() {
+            task.abort();
+        }
         self.search_info = None;
 
         ActionResult::consumed()
@@ -210,17 +457,43 @@ impl SearchComponent {
 
     fn change_mode(&mut self, mode: Mode) -> ActionResult {
         self.mode = mode;
+        self.search_status = None;
         ActionResult::consumed()
     }
 
     fn change_language(&mut self, lang: Language) -> ActionResult {
-        self.endpoint = Some(
-            // HACK: we need a way to get the API endpoint from the language
-            Endpoint::parse(&format!("https://{}.wikipedia.org/w/api.php", lang.code())).unwrap(),
-        );
         self.language = Some(lang);
         ActionResult::consumed()
     }
+
+    /// Searches the site from now on, in its language if it has one
+    fn change_site(&mut self, site: usize) -> ActionResult {
+        self.site = site;
+        if let Some(language) = self.config.api.sites[site].language {
+            self.language = Some(language);
+        }
+        ActionResult::consumed()
+    }
+
+    /// Replaces the filters and searches for the shown query again
+    fn set_filters(&mut self, filters: SearchFilters) -> ActionResult {
+        self.filters = filters;
+        match self.search_info {
+            Some(ref info) if self.mode != Mode::Searching => {
+                let query = info.query.clone();
+                self.start_search(self.results_site, query, false)
+            }
+            _ => ActionResult::consumed(),
+        }
+    }
+
+    pub fn get_filters_popup(
+        &self,
+        config: Arc<Config>,
+        theme: Arc<Theme>,
+    ) -> SearchFilterComponent {
+        SearchFilterComponent::new(self.filters.clone(), config, theme)
+    }
 }
 
 impl Component for SearchComponent {
@@ -231,12 +504,31 @@ impl Component for SearchComponent {
         theme: Arc<Theme>,
     ) -> anyhow::Result<()> {
         self.action_tx = Some(sender);
+        self.filters = SearchFilters::from_config(&config);
         self.config = config;
         self.theme = theme;
         Ok(())
     }
 
+    fn keymaps(&self) -> Vec<Keymap> {
+        vec![Keymap::new("Search", self.config.bindings.keymap("search"))]
+    }
+
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
     fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self
+            .config
+            .bindings
+            .search
+            .toggle_filters
+            .matches_event(key)
+        {
+            return Action::ShowSearchFilters.into();
+        }
+
         match self.mode {
             Mode::Searching => ActionResult::Ignored,
             Mode::Suggestion => {
@@ -251,7 +543,11 @@ impl Component for SearchComponent {
                             .as_ref()
                             .unwrap()
                             .as_str();
-                        Action::Search(SearchAction::StartSearch(suggestion.to_string())).into()
+                        Action::Search(SearchAction::SearchSite(
+                            self.results_site,
+                            suggestion.to_string(),
+                        ))
+                        .into()
                     }
                     KeyCode::Char('n') => {
                         Action::Search(SearchAction::ChangeMode(Mode::NoSearch)).into()
@@ -283,13 +579,23 @@ impl Component for SearchComponent {
     fn update(&mut self, action: Action) -> ActionResult {
         match action {
             Action::Search(search_action) => match search_action {
-                SearchAction::StartSearch(query) => self.start_search(query),
+                SearchAction::StartSearch(query) => self.start_search(self.site, query, false),
+                SearchAction::StartLuckySearch(query) => self.start_search(self.site, query, true),
+                SearchAction::SearchSite(site, query) => self.start_search(site, query, false),
                 SearchAction::FinshSearch(search) => self.finish_search(search),
                 SearchAction::ContinueSearch => self.continue_search(),
+                SearchAction::ContinueSearchFailed(error) => self.continue_search_failed(error),
                 SearchAction::ClearSearchResults => self.clear_search_results(),
                 SearchAction::OpenSearchResult => self.open_selected_result(),
                 SearchAction::ChangeMode(mode) => self.change_mode(mode),
+                SearchAction::SetStatus(status) => {
+                    self.search_status = Some(status);
+                    ActionResult::consumed()
+                }
                 SearchAction::ChangeLanguage(lang) => self.change_language(lang),
+                SearchAction::ChangeSite(site) => self.change_site(site),
+                SearchAction::FetchSuggestions(query) => self.fetch_suggestions(query),
+                SearchAction::SetFilters(filters) => self.set_filters(filters),
             },
 
             Action::ScrollUp(n) => {
@@ -302,7 +608,7 @@ impl Component for SearchComponent {
                 for _ in 0..n {
                     self.search_results.next()
                 }
-                ActionResult::consumed()
+                self.continue_search_at_end()
             }
             Action::UnselectScroll => {
                 self.search_results.unselect();
@@ -320,9 +626,13 @@ impl Component for SearchComponent {
                     .border_style(Style::default().fg(Color::Yellow)),
                 area,
             );
+            let status = match self.search_status {
+                Some(ref status) => format!("Searching, {status}"),
+                None => "Searching. Please wait...".to_string(),
+            };
             f.render_widget(
                 self.theme
-                    .default_paragraph("Searching. Please wait...")
+                    .default_paragraph(status)
                     .alignment(Alignment::Center),
                 centered_rect(area, 100, 50),
             );
@@ -388,19 +698,49 @@ impl Component for SearchComponent {
             [rects[1], rects[0]]
         };
 
-        if let Some(ref search_info) = self.search_info {
-            let info = self
-                .theme
-                .default_paragraph(format!(
-                    " wiki-tui | Results: '{}' | Language: '{}' | [c]ontinue",
-                    search_info.total_hits.unwrap_or_default(),
-                    search_info.language.name()
+        let continue_status = match self.continue_error {
+            Some(ref error) => Some(
+                Line::from(format!(
+                    " Failed loading more results: {error} | [{}] Retry",
+                    self.config.bindings.search.continue_search
                 ))
-                .style(
-                    Style::default()
-                        .fg(self.theme.status_bar_fg)
-                        .bg(self.theme.status_bar_bg),
-                );
+                .fg(self.theme.red_link_fg),
+            ),
+            None if self.continue_task.is_some() => {
+                Some(Line::from(" Loading more…").fg(self.theme.inactive_fg))
+            }
+            None => None,
+        };
+        let results_area = match continue_status {
+            Some(status) => {
+                let rects = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(1)])
+                    .split(results_area);
+                f.render_widget(self.theme.default_paragraph(status), rects[1]);
+                rects[0]
+            }
+            None => results_area,
+        };
+
+        if let Some(ref search_info) = self.search_info {
+            let mut status = format!(
+                " {} | Results: '{}' | Language: '{}'",
+                self.config.api.sites[self.results_site].name,
+                search_info.total_hits.unwrap_or_default(),
+                search_info.language.name()
+            );
+            let (_, language) = self.site();
+            // the language of the filters is shown in the title of the results
+            if self.filters.language.is_none() && language != search_info.language {
+                status.push_str(&format!(" | Next searches: '{}'", language.name()));
+            }
+            status.push_str(" | [c]ontinue");
+            let info = self.theme.default_paragraph(status).style(
+                Style::default()
+                    .fg(self.theme.status_bar_fg)
+                    .bg(self.theme.status_bar_bg),
+            );
 
             f.render_widget(info, info_area);
         }
@@ -413,23 +753,64 @@ impl Component for SearchComponent {
             .get_items()
             .iter()
             .map(|result| {
-                let snippet = result.cleaned_snippet();
                 let mut text =
                     Text::from(Span::raw(result.title.clone()).fg(self.theme.search_title_fg));
-                text.lines.append(
-                    &mut textwrap::wrap(&snippet, results_list_width as usize)
-                        .iter()
-                        .map(|s| {
-                            Line::from(s.to_string()).style(Style::default().fg(self.theme.fg))
-                        })
-                        .collect(),
-                );
+                if self.config.ui.compact_search_results {
+                    return ListItem::new(text);
+                }
+
+                text.lines.append(&mut wrap_snippet(
+                    &result.snippet_fragments(),
+                    results_list_width as usize,
+                    Style::default().fg(self.theme.fg),
+                    Style::default()
+                        .fg(self.theme.highlight_fg)
+                        .add_modifier(Modifier::BOLD),
+                ));
+
+                let mut metadata = Vec::new();
+                if let Some(wordcount) = result.wordcount {
+                    metadata.push(format!("{wordcount} words"));
+                }
+                if let Some(ref timestamp) = result.timestamp {
+                    // only the date of the ISO 8601 timestamp is of interest
+                    let date = timestamp.split('T').next().unwrap_or(timestamp);
+                    metadata.push(format!("edited {date}"));
+                }
+                if !metadata.is_empty() {
+                    text.lines
+                        .push(Line::from(metadata.join(" | ")).fg(self.theme.inactive_fg));
+                }
                 ListItem::new(text)
             })
             .collect();
 
+        let mut title = match self.search_info.as_ref().and_then(|info| info.total_hits) {
+            Some(total_hits) => format!(
+                "Results ({} of {})",
+                self.search_results.get_items().len(),
+                total_hits
+            ),
+            None => "Results".to_string(),
+        };
+        let namespaces: Vec<String> = self
+            .filters
+            .namespaces
+            .iter()
+            .map(|namespace| namespace.to_string())
+            .collect();
+        title.push_str(&format!(" | {}", namespaces.join(", ")));
+        if let Some(language) = self.filters.language {
+            title.push_str(&format!(" | {}", language.name()));
+        }
+        if let Some(ref info) = self.search_info {
+            if self.results_site != self.site {
+                let site = &self.config.api.sites[self.results_site];
+                title.push_str(&format!(" | Searched {}", site.wiki_name(info.language)));
+            }
+        }
         let items = List::new(items)
-            .block(self.theme.default_block().title("Results"))
+            .block(self.theme.default_block().title(title))
             .repeat_highlight_symbol(true)
             .highlight_symbol("| ")
             .highlight_spacing(HighlightSpacing::Always)
@@ -447,8 +828,73 @@ impl Component for SearchComponent {
             Some(crate::event::Event::Quit) => Action::Quit.into(),
             Some(crate::event::Event::RenderTick) => Action::RenderTick.into(),
             Some(crate::event::Event::Key(key_event)) => self.handle_key_events(key_event),
+            Some(crate::event::Event::Mouse(mouse_event)) => self.handle_mouse_events(mouse_event),
             Some(crate::event::Event::Resize(x, y)) => Action::Resize(x, y).into(),
             None => ActionResult::Ignored,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        style::{Style, Stylize},
+        text::Line,
+    };
+    use wiki_api::{
+        languages::Language,
+        search::{Namespace, SearchResult, SnippetFragment},
+        Endpoint,
+    };
+
+    use super::{append_unique_results, wrap_snippet};
+
+    fn result(pageid: usize) -> SearchResult {
+        SearchResult {
+            namespace: Namespace::Main,
+            title: format!("Page {pageid}"),
+            pageid,
+            language: Language::default(),
+            endpoint: Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+            size: None,
+            wordcount: None,
+            snippet: None,
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_append_unique_results() {
+        let mut results = vec![result(1), result(2)];
+        append_unique_results(&mut results, &mut vec![result(2), result(3), result(3)]);
+
+        let pageids: Vec<usize> = results.iter().map(|result| result.pageid).collect();
+        assert_eq!(pageids, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_wrap_snippet() {
+        let fragment = |text: &str, is_match| SnippetFragment {
+            text: text.to_string(),
+            is_match,
+        };
+        let style = Style::default();
+        let match_style = Style::default().bold();
+
+        let lines = wrap_snippet(
+            &[
+                fragment("the ", false),
+                fragment("rust", true),
+                fragment("acean family  of crabs", false),
+            ],
+            13,
+            style,
+            match_style,
+        );
+        let text: Vec<String> = lines.iter().map(Line::to_string).collect();
+        assert_eq!(text, ["the rustacean", "family of", "crabs"]);
+        // the match keeps its style while being part of a longer word
+        assert_eq!(lines[0].spans[2].style, match_style);
+        assert_eq!(lines[0].spans[3].style, style);
+    }
+}
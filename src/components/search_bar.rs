@@ -5,12 +5,18 @@ use ratatui::{
     prelude::Rect,
     style::{Color, Modifier, Style},
     text::Text,
+    widgets::{Clear, List, ListState},
 };
 use tui_input::{backend::crossterm::EventHandler, Input};
+use wiki_api::{
+    page::{link_data::InternalData, Link},
+    search::{Namespace, Suggestion},
+};
 
 use crate::{
     action::{Action, ActionResult, SearchAction},
     config::{Config, Theme},
+    history::{HistoryKind, PromptHistory},
     terminal::Frame,
     ui::centered_rect,
 };
@@ -25,6 +31,18 @@ pub const SEARCH_BAR_HEIGTH: u16 = 3;
 #[derive(Default)]
 pub struct SearchBarComponent {
     input: Input,
+    /// The history of the search queries, loaded when initializing the component
+    history: Option<PromptHistory>,
+
+    /// The suggested pages for the current input
+    suggestions: Vec<Suggestion>,
+    /// Index of the selected suggestion, `None` when the input is selected
+    selected_suggestion: Option<usize>,
+    /// Whether the suggestions are displayed below the input
+    show_suggestions: bool,
+    /// Where the input was rendered, the suggestions are rendered below it
+    input_area: Rect,
+
     config: Arc<Config>,
     theme: Arc<Theme>,
     pub is_focussed: bool,
@@ -33,10 +51,91 @@ pub struct SearchBarComponent {
 impl SearchBarComponent {
     pub fn clear(&mut self) {
         self.input = Input::default();
+        self.dismiss_suggestions();
+    }
+
+    /// Displays the suggestions, unless they were fetched for an outdated input
+    pub fn show_suggestions(&mut self, query: String, suggestions: Vec<Suggestion>) {
+        if query != self.input.value() {
+            return;
+        }
+
+        self.suggestions = suggestions;
+        self.selected_suggestion = None;
+        self.show_suggestions = true;
+    }
+
+    fn dismiss_suggestions(&mut self) {
+        self.selected_suggestion = None;
+        self.show_suggestions = false;
+    }
+
+    fn has_suggestions(&self) -> bool {
+        self.show_suggestions && !self.suggestions.is_empty()
+    }
+
+    /// Returns the link to the selected suggestion, if a suggestion is selected
+    pub fn submit_suggestion(&mut self) -> Option<Action> {
+        if !self.show_suggestions {
+            return None;
+        }
+        let suggestion = self.suggestions.get(self.selected_suggestion?)?.clone();
+        self.dismiss_suggestions();
+
+        Some(Action::LoadLink(Link::Internal(InternalData {
+            namespace: Namespace::Main,
+            page: suggestion.title.clone(),
+            title: suggestion.title,
+            endpoint: suggestion.endpoint,
+            language: suggestion.language,
+            anchor: None,
+        })))
+    }
+
+    /// Renders the suggestions below the input, on top of everything else
+    pub fn render_suggestions(&mut self, f: &mut Frame<'_>) {
+        if !self.is_focussed || !self.has_suggestions() {
+            return;
+        }
+
+        let area = Rect {
+            y: self.input_area.bottom(),
+            height: self.suggestions.len() as u16 + 2,
+            ..self.input_area
+        }
+        .intersection(f.size());
+        f.render_widget(Clear, area);
+
+        let list = List::new(
+            self.suggestions
+                .iter()
+                .map(|suggestion| suggestion.title.as_str()),
+        )
+        .block(self.theme.default_block().title("Suggestions"))
+        .style(Style::default().fg(self.theme.fg).bg(self.theme.bg))
+        .highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg),
+        );
+        let mut state = ListState::default().with_selected(self.selected_suggestion);
+        f.render_stateful_widget(list, area, &mut state);
     }
 
-    pub fn submit(&self) -> Action {
-        Action::Search(SearchAction::StartSearch(self.input.value().to_string()))
+    /// Submits the query. A lucky search, also started by prefixing the query with `!`, opens
+    /// the first result right away
+    pub fn submit(&mut self, is_lucky: bool) -> Action {
+        self.dismiss_suggestions();
+        let query = self.input.value().to_string();
+        if let Some(ref mut history) = self.history {
+            history.add(&query);
+        }
+
+        match query.strip_prefix('!') {
+            Some(query) => Action::Search(SearchAction::StartLuckySearch(query.trim().to_string())),
+            None if is_lucky => Action::Search(SearchAction::StartLuckySearch(query)),
+            None => Action::Search(SearchAction::StartSearch(query)),
+        }
     }
 }
 
@@ -47,12 +146,24 @@ impl Component for SearchBarComponent {
         config: Arc<Config>,
         theme: Arc<Theme>,
     ) -> anyhow::Result<()> {
+        self.history = Some(PromptHistory::load(
+            HistoryKind::Search,
+            config.ui.history_size,
+        ));
         self.config = config;
         self.theme = theme;
         Ok(())
     }
 
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
     fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.submit_lucky.matches_event(key) {
+            return Action::SubmitLuckySearchBar.into();
+        }
+
         if self.config.bindings.global.submit.matches_event(key) {
             return Action::SubmitSearchBar.into();
         }
@@ -64,10 +175,68 @@ impl Component for SearchBarComponent {
             .exit_search_bar
             .matches_event(key)
         {
+            // the first escape only dismisses the suggestions
+            if self.has_suggestions() {
+                self.dismiss_suggestions();
+                return ActionResult::consumed();
+            }
+
+            if let Some(ref mut history) = self.history {
+                history.reset();
+            }
             return Action::ExitSearchBar.into();
         }
 
-        self.input.handle_event(&crossterm::event::Event::Key(key));
+        let global_bindings = &self.config.bindings.global;
+        if self.has_suggestions() {
+            if global_bindings.history_next.matches_event(key) {
+                self.selected_suggestion = match self.selected_suggestion {
+                    Some(idx) => Some((idx + 1).min(self.suggestions.len() - 1)),
+                    None => Some(0),
+                };
+                return ActionResult::consumed();
+            }
+
+            // moving up from the first suggestion selects the input again, moving up from the
+            // input recalls the history
+            if global_bindings.history_prev.matches_event(key) && self.selected_suggestion.is_some()
+            {
+                self.selected_suggestion =
+                    self.selected_suggestion.and_then(|idx| idx.checked_sub(1));
+                return ActionResult::consumed();
+            }
+        }
+
+        if let Some(ref mut history) = self.history {
+            let recalled = if global_bindings.history_prev.matches_event(key) {
+                Some(history.recall_prev(self.input.value()))
+            } else if global_bindings.history_next.matches_event(key) {
+                Some(history.recall_next())
+            } else {
+                history.reset();
+                None
+            };
+
+            if let Some(query) = recalled {
+                if let Some(query) = query {
+                    self.input = Input::new(query);
+                    self.show_suggestions = false;
+                }
+                return ActionResult::consumed();
+            }
+        }
+
+        let changed = self
+            .input
+            .handle_event(&crossterm::event::Event::Key(key))
+            .is_some_and(|state| state.value);
+        if changed && self.config.api.suggestion_limit != 0 {
+            self.selected_suggestion = None;
+            return Action::Search(SearchAction::FetchSuggestions(
+                self.input.value().to_string(),
+            ))
+            .into();
+        }
         ActionResult::consumed()
     }
 
@@ -99,6 +268,7 @@ impl Component for SearchBarComponent {
         .block(block);
 
         let input_area = centered_rect(area, SEARCH_BAR_X, 100);
+        self.input_area = input_area;
         f.render_widget(input, input_area);
         if self.is_focussed {
             f.set_cursor(
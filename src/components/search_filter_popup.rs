@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style, Stylize},
+    text::Line,
+    widgets::{Clear, List, ListItem, ListState},
+};
+use wiki_api::search::Namespace;
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, SearchAction},
+    config::{Config, Theme},
+    terminal::Frame,
+    ui::centered_rect,
+};
+
+use super::{search::SearchFilters, Component};
+
+/// Index of the language in the list, the namespaces follow it
+const LANGUAGE_ITEM: usize = 0;
+
+/// A popup changing the namespaces and the language searched in this session
+///
+/// The filters are applied when the popup is closed
+pub struct SearchFilterComponent {
+    filters: SearchFilters,
+    /// The filters when opening the popup, to only search again when they were changed
+    initial_filters: SearchFilters,
+    state: ListState,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl SearchFilterComponent {
+    pub fn new(filters: SearchFilters, config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        Self {
+            initial_filters: filters.clone(),
+            filters,
+            state: ListState::default().with_selected(Some(LANGUAGE_ITEM)),
+
+            config,
+            theme,
+        }
+    }
+
+    fn selected(&self) -> usize {
+        self.state.selected().unwrap_or(LANGUAGE_ITEM)
+    }
+
+    fn select(&mut self, idx: usize) {
+        self.state.select(Some(idx.min(Namespace::ALL.len())));
+    }
+
+    /// Toggles the selected namespace, at least one namespace has to stay selected
+    fn toggle_namespace(&mut self, namespace: Namespace) {
+        let namespaces = &mut self.filters.namespaces;
+        match namespaces
+            .iter()
+            .position(|selected| *selected == namespace)
+        {
+            Some(_) if namespaces.len() == 1 => {}
+            Some(idx) => {
+                namespaces.remove(idx);
+            }
+            None => {
+                namespaces.push(namespace);
+                namespaces.sort_by_key(|namespace| *namespace as usize);
+            }
+        }
+    }
+
+    fn close(&self) -> ActionResult {
+        let packet = ActionPacket::single(Action::PopPopup);
+        if self.filters == self.initial_filters {
+            return packet.into();
+        }
+        packet
+            .action(Action::Search(SearchAction::SetFilters(
+                self.filters.clone(),
+            )))
+            .into()
+    }
+}
+
+impl Component for SearchFilterComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.pop_popup.matches_event(key)
+            || self
+                .config
+                .bindings
+                .search
+                .toggle_filters
+                .matches_event(key)
+        {
+            return self.close();
+        }
+
+        if self.config.bindings.global.submit.matches_event(key) || key.code == KeyCode::Char(' ') {
+            match self.selected() {
+                LANGUAGE_ITEM => return Action::ShowFilterLanguageSelection.into(),
+                idx => self.toggle_namespace(Namespace::ALL[idx - 1]),
+            }
+            return ActionResult::consumed();
+        }
+
+        match key.code {
+            KeyCode::Char('r') => {
+                self.filters = SearchFilters::from_config(&self.config);
+                ActionResult::consumed()
+            }
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => self.select(self.selected().saturating_sub(n as usize)),
+            Action::ScrollDown(n) => self.select(self.selected() + n as usize),
+            Action::ScrollToTop => self.select(LANGUAGE_ITEM),
+            Action::ScrollToBottom => self.select(Namespace::ALL.len()),
+            Action::SelectFilterLanguage(language) => self.filters.language = Some(language),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let block = self
+            .theme
+            .default_block()
+            .title("Search Filters")
+            .title_bottom(Line::from("<SPACE> Toggle | <r> Reset").right_aligned())
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 40, 60);
+        f.render_widget(Clear, area);
+
+        let language = match self.filters.language.as_ref() {
+            Some(language) => language.name(),
+            None => "(default)",
+        };
+        let mut items = vec![ListItem::new(format!("Language: {language}")).fg(self.theme.fg)];
+        items.extend(Namespace::ALL.iter().map(|namespace| {
+            let checkbox = match self.filters.namespaces.contains(namespace) {
+                true => "[x]",
+                false => "[ ]",
+            };
+            ListItem::new(format!("{checkbox} {namespace}")).fg(self.theme.fg)
+        }));
+
+        let list = List::new(items).block(block).highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_stateful_widget(list, area, &mut self.state);
+    }
+}
@@ -7,10 +7,10 @@ use ratatui::{
     widgets::{Clear, List, ListItem},
 };
 use tui_input::{backend::crossterm::EventHandler, Input};
-use wiki_api::languages::{Language, LANGUAGES};
+use wiki_api::languages::{suggest_language, Language, LANGUAGES};
 
 use crate::{
-    action::{Action, ActionPacket, ActionResult, SearchAction},
+    action::{Action, ActionPacket, ActionResult},
     config::{Config, Theme},
     terminal::Frame,
     ui::{centered_rect, StatefulList},
@@ -25,6 +25,10 @@ pub struct SearchLanguageSelectionComponent {
     input: Input,
     focus: u8,
     list: StatefulList<Language>,
+    /// The language the input was probably meant as, when no language matches it
+    suggestion: Option<Language>,
+    /// Whether the language is selected for the search filters instead of all searches
+    is_filter: bool,
 
     config: Arc<Config>,
     theme: Arc<Theme>,
@@ -35,32 +39,57 @@ impl SearchLanguageSelectionComponent {
         Self {
             input: Input::default(),
             list: StatefulList::with_items(Vec::new()),
+            suggestion: None,
             focus: 0,
+            is_filter: false,
 
             config,
             theme,
         }
     }
 
+    /// Creates the selection of the language for the search filters
+    pub fn for_filters(config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        Self {
+            is_filter: true,
+            ..Self::new(config, theme)
+        }
+    }
+
     fn update_list(&mut self) {
         let input_value = self.input.value();
         let sorted_languages = LANGUAGES
             .iter()
             .filter(|lang| {
-                let lang = lang.name().to_lowercase();
                 let query = input_value.to_lowercase();
-                lang.contains(&query)
+                lang.name().to_lowercase().contains(&query)
+                    || lang.local_name().to_lowercase().contains(&query)
+                    || lang.code() == query
             })
             .map(|x| x.to_owned())
             .collect::<Vec<Language>>();
+        self.suggestion = match sorted_languages.is_empty() {
+            true => suggest_language(input_value),
+            false => None,
+        };
         self.list = StatefulList::with_items(sorted_languages);
     }
 }
 
 impl Component for SearchLanguageSelectionComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
     fn handle_key_events(&mut self, key: crossterm::event::KeyEvent) -> ActionResult {
         if self.config.bindings.global.submit.matches_event(key) {
-            if let Some(lang) = self.list.selected() {
+            if let Some(lang) = self.list.selected().or(self.suggestion.as_ref()) {
+                if self.is_filter {
+                    return ActionPacket::single(Action::PopPopup)
+                        .action(Action::SelectFilterLanguage(lang.to_owned()))
+                        .into();
+                }
+
                 let mut packet =
                     ActionPacket::single(Action::SwitchContextSearch).action(Action::PopPopup);
 
@@ -71,11 +100,7 @@ impl Component for SearchLanguageSelectionComponent {
                     ));
                 }
 
-                return packet
-                    .action(Action::Search(SearchAction::ChangeLanguage(
-                        lang.to_owned(),
-                    )))
-                    .into();
+                return packet.action(Action::SwitchLanguage(*lang)).into();
             }
             return ActionResult::Ignored;
         }
@@ -174,6 +199,20 @@ impl Component for SearchLanguageSelectionComponent {
             );
         }
 
+        if let Some(suggestion) = self.suggestion {
+            f.render_widget(
+                self.theme
+                    .default_paragraph(format!(
+                        "Did you mean '{}' - {}? <ENTER>",
+                        suggestion.code(),
+                        suggestion.name()
+                    ))
+                    .fg(self.theme.highlight_fg),
+                list_area,
+            );
+            return;
+        }
+
         let list_items = self
             .list
             .get_items()
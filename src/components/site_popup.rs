@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem},
+};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, SearchAction},
+    config::{Config, Site, Theme},
+    terminal::Frame,
+    ui::{centered_rect, StatefulList},
+};
+
+use super::Component;
+
+/// A popup listing the configured sites. The selected site is searched from then on, or only for
+/// the query when there is one
+pub struct SiteSelectionComponent {
+    list: StatefulList<Site>,
+    /// Index of the site searched right now
+    current: usize,
+    /// The query searched once on the selected site, without switching to it
+    query: Option<String>,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl SiteSelectionComponent {
+    pub fn new(current: usize, config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        let mut list = StatefulList::with_items(config.api.sites.clone());
+        list.get_state_mut().select(Some(current));
+        Self {
+            list,
+            current,
+            query: None,
+
+            config,
+            theme,
+        }
+    }
+
+    /// Creates the selection of the site the query is searched on. The configured lookup site is
+    /// selected, or the first one other than the current site
+    pub fn for_query(
+        query: String,
+        current: usize,
+        config: Arc<Config>,
+        theme: Arc<Theme>,
+    ) -> Self {
+        let selected = config
+            .api
+            .lookup_site
+            .or_else(|| (0..config.api.sites.len()).find(|site| *site != current))
+            .unwrap_or(current);
+        let mut selection = Self::new(current, config, theme);
+        selection.list.get_state_mut().select(Some(selected));
+        selection.query = Some(query);
+        selection
+    }
+}
+
+impl Component for SiteSelectionComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.pop_popup.matches_event(key)
+            || self
+                .config
+                .bindings
+                .global
+                .toggle_site_selection
+                .matches_event(key)
+        {
+            return Action::PopPopup.into();
+        }
+
+        if self.config.bindings.global.submit.matches_event(key) {
+            let site = match self.list.get_state_mut().selected() {
+                Some(site) => site,
+                None => return ActionResult::Ignored,
+            };
+            let action = match self.query {
+                Some(ref query) => Action::Search(SearchAction::SearchSite(site, query.clone())),
+                None => Action::SwitchSite(site),
+            };
+            return ActionPacket::single(Action::PopPopup)
+                .action(Action::SwitchContextSearch)
+                .action(action)
+                .into();
+        }
+        ActionResult::Ignored
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.list.previous()
+                }
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.list.next()
+                }
+            }
+            Action::UnselectScroll => self.list.unselect(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let (title, submit) = match self.query {
+            Some(ref query) => (format!("Search '{query}' on"), "<ENTER> Search"),
+            None => ("Sites".to_string(), "<ENTER> Switch"),
+        };
+        let block = self
+            .theme
+            .default_block()
+            .title(title)
+            .title_bottom(Line::from(submit).right_aligned())
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 50, 40);
+        f.render_widget(Clear, area);
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+
+        let items = self.list.get_items().iter().enumerate().map(|(idx, site)| {
+            let marker = if idx == self.current { "* " } else { "  " };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{marker}{}", site.name)).fg(self.theme.fg),
+                Span::raw(format!("  {}", site.url)).fg(self.theme.inactive_fg),
+            ]))
+        });
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_stateful_widget(list, inner_area, self.list.get_state_mut());
+    }
+}
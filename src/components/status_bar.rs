@@ -0,0 +1,510 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use wiki_api::document::WordCount;
+
+/// The values that can be placed in the template of the status bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Placeholder {
+    Site,
+    Kind,
+    Title,
+    Language,
+    LanguagesAvailable,
+    Renderer,
+    ScrollPercent,
+    Columns,
+    ReadingTime,
+    Section,
+    LinkTarget,
+    History,
+    ReadingList,
+    Cached,
+    Offline,
+    Notice,
+    Search,
+}
+
+impl Placeholder {
+    fn parse(name: &str) -> Option<Placeholder> {
+        Some(match name {
+            "site" => Placeholder::Site,
+            "kind" => Placeholder::Kind,
+            "title" => Placeholder::Title,
+            "language" => Placeholder::Language,
+            "languages_available" => Placeholder::LanguagesAvailable,
+            "renderer" => Placeholder::Renderer,
+            "scroll_percent" => Placeholder::ScrollPercent,
+            "columns" => Placeholder::Columns,
+            "reading_time" => Placeholder::ReadingTime,
+            "section" => Placeholder::Section,
+            "link_target" => Placeholder::LinkTarget,
+            "history" => Placeholder::History,
+            "reading_list" => Placeholder::ReadingList,
+            "cached" => Placeholder::Cached,
+            "offline" => Placeholder::Offline,
+            "notice" => Placeholder::Notice,
+            "search" => Placeholder::Search,
+            _ => return None,
+        })
+    }
+
+    /// Segments with a lower priority are dropped first when the status bar is too narrow
+    fn priority(self) -> u8 {
+        match self {
+            Placeholder::Title => 10,
+            Placeholder::Notice | Placeholder::Search => 9,
+            Placeholder::Offline => 8,
+            Placeholder::Section => 7,
+            Placeholder::LinkTarget => 6,
+            Placeholder::Kind
+            | Placeholder::ScrollPercent
+            | Placeholder::Columns
+            | Placeholder::ReadingTime => 5,
+            Placeholder::Language => 4,
+            Placeholder::History | Placeholder::ReadingList => 3,
+            Placeholder::Site | Placeholder::Renderer | Placeholder::Cached => 2,
+            Placeholder::LanguagesAvailable => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+/// The values of the placeholders, filled by the page every time the status bar is rendered.
+/// Values that are empty, like the section in the lead of an article, hide their segment
+#[derive(Debug, Default, Clone)]
+pub struct StatusContext {
+    pub site: String,
+    /// Whether the page is a random article or a regular page
+    pub kind: String,
+    pub title: String,
+    pub language: String,
+    pub languages_available: usize,
+    pub renderer: String,
+    pub scroll_percent: u16,
+    /// The visible columns of the lines wider than the page, like "cols 40–120", while they're
+    /// scrolled
+    pub columns: String,
+    /// The estimated time for reading the whole page, like "~23 min read"
+    pub reading_time: String,
+    pub section: String,
+    /// The page or url the selected link points to
+    pub link_target: String,
+    pub history: String,
+    pub reading_list: String,
+    pub cached: String,
+    pub offline: String,
+    pub notice: String,
+    pub search: String,
+}
+
+impl StatusContext {
+    fn value(&self, placeholder: Placeholder) -> String {
+        match placeholder {
+            Placeholder::Site => self.site.clone(),
+            Placeholder::Kind => self.kind.clone(),
+            Placeholder::Title => self.title.clone(),
+            Placeholder::Language => self.language.clone(),
+            // pages without language links have nothing to show
+            Placeholder::LanguagesAvailable => match self.languages_available {
+                0 => String::new(),
+                languages => languages.to_string(),
+            },
+            Placeholder::Renderer => self.renderer.clone(),
+            Placeholder::ScrollPercent => self.scroll_percent.to_string(),
+            Placeholder::Columns => self.columns.clone(),
+            Placeholder::ReadingTime => self.reading_time.clone(),
+            Placeholder::Section => self.section.clone(),
+            Placeholder::LinkTarget => self.link_target.clone(),
+            Placeholder::History => self.history.clone(),
+            Placeholder::ReadingList => self.reading_list.clone(),
+            Placeholder::Cached => self.cached.clone(),
+            Placeholder::Offline => self.offline.clone(),
+            Placeholder::Notice => self.notice.clone(),
+            Placeholder::Search => self.search.clone(),
+        }
+    }
+}
+
+/// The parsed template of the status bar. The template is split into segments at every `|`,
+/// which are dropped as a whole when the status bar is too narrow or all of their placeholders
+/// are empty. `{{` and `}}` are a literal brace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusTemplate {
+    segments: Vec<Vec<Part>>,
+    /// Names of the placeholders that don't exist, they're displayed as they are
+    unknown: Vec<String>,
+}
+
+impl StatusTemplate {
+    pub fn parse(template: &str) -> StatusTemplate {
+        let mut segments = vec![Vec::new()];
+        let mut unknown = Vec::new();
+        let mut text = String::new();
+
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' | '}' if chars.peek() == Some(&c) => {
+                    chars.next();
+                    text.push(c);
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut is_closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            is_closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+
+                    match Placeholder::parse(&name).filter(|_| is_closed) {
+                        Some(placeholder) => {
+                            let segment = segments.last_mut().unwrap();
+                            segment.push(Part::Text(std::mem::take(&mut text)));
+                            segment.push(Part::Placeholder(placeholder));
+                        }
+                        None => {
+                            unknown.push(name.clone());
+                            text.push('{');
+                            text.push_str(&name);
+                            if is_closed {
+                                text.push('}');
+                            }
+                        }
+                    }
+                }
+                '|' => {
+                    segments
+                        .last_mut()
+                        .unwrap()
+                        .push(Part::Text(std::mem::take(&mut text)));
+                    segments.push(Vec::new());
+                }
+                _ => text.push(c),
+            }
+        }
+        segments.last_mut().unwrap().push(Part::Text(text));
+
+        StatusTemplate { segments, unknown }
+    }
+
+    pub fn unknown_placeholders(&self) -> &[String] {
+        &self.unknown
+    }
+
+    /// Whether the template has a place for the target of the selected link
+    pub fn shows_link_target(&self) -> bool {
+        self.segments
+            .iter()
+            .flatten()
+            .any(|part| *part == Part::Placeholder(Placeholder::LinkTarget))
+    }
+
+    /// Fills in the placeholders and drops the segments with the lowest priority until the text
+    /// fits into the width. When even the last segments are too wide, the title is shortened in
+    /// the middle and then the text is cut at a word boundary
+    pub fn render(&self, context: &StatusContext, width: usize) -> String {
+        // (priority, parts) of every segment with a value
+        let mut segments: Vec<(u8, &[Part])> = self
+            .segments
+            .iter()
+            .filter_map(|parts| {
+                let mut priority = None;
+                let mut has_value = false;
+                for part in parts {
+                    if let Part::Placeholder(placeholder) = part {
+                        has_value |= !context.value(*placeholder).is_empty();
+                        priority = priority.max(Some(placeholder.priority()));
+                    }
+                }
+
+                match priority {
+                    // segments without placeholders are decoration and dropped first
+                    None => Some((0, parts.as_slice())),
+                    Some(priority) => has_value.then_some((priority, parts.as_slice())),
+                }
+            })
+            .collect();
+
+        let fill = |segments: &[(u8, &[Part])], title: &str| {
+            segments
+                .iter()
+                .map(|(_, parts)| {
+                    parts
+                        .iter()
+                        .map(|part| match part {
+                            Part::Text(text) => text.clone(),
+                            Part::Placeholder(Placeholder::Title) => title.to_string(),
+                            Part::Placeholder(placeholder) => context.value(*placeholder),
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<String>>()
+                .join("|")
+        };
+
+        while segments.len() > 1 && fill(&segments, &context.title).width() > width {
+            // of the segments with the same priority, the last one is dropped
+            let lowest = segments
+                .iter()
+                .enumerate()
+                .rev()
+                .min_by_key(|(_, (priority, _))| *priority)
+                .map(|(idx, _)| idx)
+                .unwrap();
+            segments.remove(lowest);
+        }
+
+        let mut text = fill(&segments, &context.title);
+        let overflow = text.width().saturating_sub(width);
+        let titles = segments
+            .iter()
+            .flat_map(|(_, parts)| parts.iter())
+            .filter(|part| **part == Part::Placeholder(Placeholder::Title))
+            .count();
+        if overflow > 0 && titles > 0 {
+            let title_width = context
+                .title
+                .width()
+                .saturating_sub(overflow.div_ceil(titles));
+            text = fill(&segments, &elide_middle(&context.title, title_width.max(1)));
+        }
+        truncate_at_word(&text, width)
+    }
+}
+
+/// Replaces the middle of the text with an ellipsis, so the start and the end stay readable
+fn elide_middle(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    // takes the characters that fit into the columns, wide characters take two
+    let take = |chars: &mut dyn Iterator<Item = char>, columns: usize| {
+        let mut taken = 0;
+        chars
+            .take_while(|c| {
+                taken += c.width().unwrap_or(0);
+                taken <= columns
+            })
+            .collect::<String>()
+    };
+    let kept = width - 1;
+    let head = take(&mut text.chars(), kept.div_ceil(2));
+    let tail: String = take(&mut text.chars().rev(), kept - head.width())
+        .chars()
+        .rev()
+        .collect();
+    format!("{}…{}", head.trim_end(), tail.trim_start())
+}
+
+/// Estimates how long reading the text takes, like "~23 min read". Chinese and Japanese
+/// characters are read at their own speed. Pages without text have no reading time
+pub fn format_reading_time(
+    count: WordCount,
+    words_per_minute: u32,
+    cjk_characters_per_minute: u32,
+) -> String {
+    if count == WordCount::default() {
+        return String::new();
+    }
+
+    let minutes = count.words as f64 / words_per_minute.max(1) as f64
+        + count.cjk_characters as f64 / cjk_characters_per_minute.max(1) as f64;
+    format!("~{} min read", (minutes.round() as u64).max(1))
+}
+
+/// Cuts the text at the last whitespace that leaves room for an ellipsis, or in the middle of a
+/// word when there's no whitespace. The width is measured in terminal columns
+fn truncate_at_word(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let cut = take_width(text, width - 1);
+    let cut = match cut.trim_end().rfind(char::is_whitespace) {
+        Some(idx) if !cut[..idx].trim().is_empty() => &cut[..idx],
+        _ => cut,
+    };
+    format!("{}…", cut.trim_end())
+}
+
+/// Returns the longest start of the text that fits into the width in terminal columns
+fn take_width(text: &str, width: usize) -> &str {
+    let mut taken = 0;
+    for (idx, c) in text.char_indices() {
+        taken += c.width().unwrap_or_default();
+        if taken > width {
+            return &text[..idx];
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use unicode_width::UnicodeWidthStr;
+    use wiki_api::document::WordCount;
+
+    use crate::config::Config;
+
+    use super::{
+        elide_middle, format_reading_time, truncate_at_word, StatusContext, StatusTemplate,
+    };
+
+    fn context() -> StatusContext {
+        StatusContext {
+            site: "Wikipedia".to_string(),
+            kind: "Page".to_string(),
+            title: "Rust (programming language)".to_string(),
+            language: "English".to_string(),
+            languages_available: 42,
+            scroll_percent: 12,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let template = StatusTemplate::parse(
+            " {site} | {kind} '{title}' | {scroll_percent}% | {section} | {{{language}}}",
+        );
+        assert!(template.unknown_placeholders().is_empty());
+        assert_eq!(
+            template.render(&context(), 100),
+            " Wikipedia | Page 'Rust (programming language)' | 12% | {English}"
+        );
+
+        // the site has the lowest priority, then the language and the scroll position
+        assert_eq!(
+            template.render(&context(), 50),
+            " Page 'Rust (programming language)' | 12% "
+        );
+        assert_eq!(
+            template.render(&context(), 36),
+            " Page 'Rust (programming language)' "
+        );
+        // the title is shortened in the middle when nothing else can be dropped
+        assert_eq!(template.render(&context(), 20), " Page 'Rust…uage)' ");
+    }
+
+    #[test]
+    fn test_unknown_placeholders() {
+        let template = StatusTemplate::parse("{title} {tabs} {unclosed");
+        assert_eq!(template.unknown_placeholders(), ["tabs", "unclosed"]);
+        assert_eq!(
+            template.render(&context(), 100),
+            "Rust (programming language) {tabs} {unclosed"
+        );
+    }
+
+    #[test]
+    fn test_format_reading_time() {
+        let count = |words, cjk_characters| WordCount {
+            words,
+            cjk_characters,
+        };
+        assert_eq!(
+            format_reading_time(count(5290, 0), 230, 500),
+            "~23 min read"
+        );
+        assert_eq!(format_reading_time(count(20, 0), 230, 500), "~1 min read");
+        assert_eq!(
+            format_reading_time(count(0, 10000), 230, 500),
+            "~20 min read"
+        );
+        assert_eq!(
+            format_reading_time(count(2300, 5000), 230, 500),
+            "~20 min read"
+        );
+        assert_eq!(format_reading_time(count(0, 0), 230, 500), "");
+    }
+
+    #[test]
+    fn test_truncate_at_word() {
+        assert_eq!(truncate_at_word("Rust is fast", 12), "Rust is fast");
+        assert_eq!(truncate_at_word("Rust is fast", 11), "Rust is…");
+        assert_eq!(truncate_at_word("Rustacean", 5), "Rust…");
+        assert_eq!(truncate_at_word("Rust", 0), "");
+        // wide characters take two columns
+        assert_eq!(truncate_at_word("日本語の記事", 7), "日本語…");
+        assert_eq!(truncate_at_word("日本 語の記事", 8), "日本…");
+    }
+
+    #[test]
+    fn test_render_narrow() {
+        let template = StatusTemplate::parse(&Config::default().page.status_bar);
+        let context = StatusContext {
+            site: "Wikipedia".to_string(),
+            kind: "Page".to_string(),
+            title: "List of Rust (programming language) compilers and toolchains".to_string(),
+            language: "English".to_string(),
+            languages_available: 12,
+            renderer: "default".to_string(),
+            scroll_percent: 40,
+            reading_time: "~23 min read".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            template.render(&context, 40),
+            " Page 'List of Rust (p…and toolchains' "
+        );
+        assert_eq!(
+            template.render(&context, 60),
+            " Page 'List of Rust (programming…compilers and toolchains' "
+        );
+        // the number of other languages is dropped before the language
+        assert_eq!(
+            template.render(&context, 120),
+            " Page 'List of Rust (programming language) compilers and toolchains' | Language 'English' | 40% of ~23 min read "
+        );
+        assert!(template
+            .render(&context, 220)
+            .contains("'12' other languages available"));
+
+        // pages without language links don't show a count
+        let context = StatusContext {
+            languages_available: 0,
+            ..context
+        };
+        assert!(!template
+            .render(&context, 220)
+            .contains("other languages available"));
+    }
+
+    #[test]
+    fn test_render_wide_title() {
+        let template = StatusTemplate::parse(&Config::default().page.status_bar);
+        let context = StatusContext {
+            kind: "Page".to_string(),
+            title: "プログラミング言語の一覧".to_string(),
+            language: "日本語".to_string(),
+            ..Default::default()
+        };
+        let status = template.render(&context, 20);
+        assert_eq!(status, " Page 'プロ…の一覧' ");
+        assert_eq!(status.width(), 20);
+    }
+
+    #[test]
+    fn test_elide_middle() {
+        assert_eq!(elide_middle("Rust", 4), "Rust");
+        assert_eq!(elide_middle("Rustacean", 5), "Ru…an");
+        // wide characters take two columns and are never cut
+        assert_eq!(elide_middle("日本語の記事", 5), "日…事");
+        assert_eq!(elide_middle("日本語の記事", 4), "日…");
+        assert_eq!(elide_middle("Rust", 0), "");
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::Clear,
+};
+
+use crate::{
+    action::{ActionPacket, MessageLevel},
+    config::{Config, Theme, ToastPosition},
+    page_cache::now,
+    terminal::Frame,
+};
+
+use super::Component;
+
+/// Number of messages kept in the log
+const MESSAGE_LOG_SIZE: usize = 50;
+/// Number of toasts shown at the same time, older toasts are hidden
+const MAX_SHOWN: usize = 4;
+/// Maximum width of a toast including its border
+const TOAST_WIDTH: u16 = 44;
+
+impl MessageLevel {
+    pub fn color(&self, theme: &Theme) -> Color {
+        match self {
+            MessageLevel::Info => theme.fg,
+            MessageLevel::Warning => Color::Yellow,
+            MessageLevel::Error => Color::Red,
+        }
+    }
+}
+
+/// A message shown to the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub level: MessageLevel,
+    pub text: String,
+    /// When the message was shown, in seconds since the unix epoch
+    pub time: u64,
+}
+
+/// Shows the messages as small boxes in a corner of the screen. Errors stay until they're
+/// dismissed, other messages disappear after a few seconds. The recent messages are kept in a log
+pub struct ToastsComponent {
+    /// The shown messages and since when, the newest message last
+    shown: Vec<(Message, Instant)>,
+    /// The recent messages, the newest message first
+    log: VecDeque<Message>,
+    /// The shown error of a failed load and the action retrying it
+    retry: Option<(Message, ActionPacket)>,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl Default for ToastsComponent {
+    fn default() -> Self {
+        Self {
+            shown: Vec::new(),
+            log: VecDeque::new(),
+            retry: None,
+
+            config: Arc::new(Config::default()),
+            theme: Arc::new(Theme::default()),
+        }
+    }
+}
+
+impl ToastsComponent {
+    pub fn set_config(&mut self, config: Arc<Config>) {
+        self.config = config;
+    }
+
+    pub fn push(&mut self, level: MessageLevel, text: String) {
+        self.push_message(level, text);
+    }
+
+    /// Shows the error of a failed load, which is loaded again with the retry when pressing the
+    /// `retry_load` key. Only the newest error can be retried
+    pub fn push_retry(&mut self, text: String, retry: ActionPacket) {
+        let message = self.push_message(MessageLevel::Error, text);
+        self.retry = Some((message, retry));
+    }
+
+    /// Hides the error that can be retried and returns its retry
+    pub fn take_retry(&mut self) -> Option<ActionPacket> {
+        let (message, retry) = self.retry.take()?;
+        self.shown.retain(|(shown, _)| *shown != message);
+        Some(retry)
+    }
+
+    fn push_message(&mut self, level: MessageLevel, text: String) -> Message {
+        let message = Message {
+            level,
+            text,
+            time: now(),
+        };
+        self.log.push_front(message.clone());
+        self.log.truncate(MESSAGE_LOG_SIZE);
+        self.shown.push((message.clone(), Instant::now()));
+        message
+    }
+
+    /// The recent messages, the newest message first
+    pub fn log(&self) -> &VecDeque<Message> {
+        &self.log
+    }
+
+    /// Whether an error is shown, which stays until it's dismissed
+    pub fn has_errors(&self) -> bool {
+        self.shown
+            .iter()
+            .any(|(message, _)| message.level == MessageLevel::Error)
+    }
+
+    /// Hides all shown messages
+    pub fn dismiss(&mut self) {
+        self.shown.clear();
+        self.retry = None;
+    }
+
+    /// Hides the messages that were shown long enough, errors are kept
+    fn remove_expired(&mut self) {
+        let duration = Duration::from_secs(self.config.ui.toast_duration);
+        self.shown.retain(|(message, shown)| {
+            message.level == MessageLevel::Error || shown.elapsed() < duration
+        });
+    }
+}
+
+impl Component for ToastsComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        // errors stay until they're dismissed
+        self.shown
+            .iter()
+            .any(|(message, _)| message.level != MessageLevel::Error)
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        self.remove_expired();
+
+        let position = self.config.ui.toast_position;
+        let width = TOAST_WIDTH.min(area.width);
+        let mut offset = 0;
+        // the newest toast is closest to the corner
+        for (message, _) in self.shown.iter().rev().take(MAX_SHOWN) {
+            let lines = textwrap::wrap(&message.text, width.saturating_sub(2).max(1) as usize);
+            let height = lines.len() as u16 + 2;
+            if offset + height > area.height {
+                break;
+            }
+
+            let x = match position {
+                ToastPosition::TopLeft | ToastPosition::BottomLeft => area.x,
+                ToastPosition::TopRight | ToastPosition::BottomRight => area.x + area.width - width,
+            };
+            let y = match position {
+                ToastPosition::TopLeft | ToastPosition::TopRight => area.y + offset,
+                ToastPosition::BottomLeft | ToastPosition::BottomRight => {
+                    area.y + area.height - offset - height
+                }
+            };
+            let toast_area = Rect {
+                x,
+                y,
+                width,
+                height,
+            };
+            offset += height;
+
+            let color = message.level.color(&self.theme);
+            let mut block = self
+                .theme
+                .default_block()
+                .title(Span::raw(message.level.title()).fg(color).bold())
+                .border_style(Style::default().fg(color))
+                .style(Style::default().bg(self.theme.bg));
+            let is_retry = self
+                .retry
+                .as_ref()
+                .is_some_and(|(retried, _)| retried == message);
+            if is_retry {
+                let retry_key = &self.config.bindings.global.retry_load;
+                let keys = format!("<{retry_key}> Retry | <ESC> Dismiss");
+                block = block.title_bottom(Line::from(keys).right_aligned());
+            } else if message.level == MessageLevel::Error {
+                block = block.title_bottom(Line::from("<ESC> Dismiss").right_aligned());
+            }
+
+            let lines: Vec<Line> = lines
+                .into_iter()
+                .map(|line| Line::from(line.into_owned()))
+                .collect();
+            f.render_widget(Clear, toast_area);
+            f.render_widget(self.theme.default_paragraph(lines).block(block), toast_area);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::action::{Action, ActionPacket, MessageLevel};
+
+    use super::{ToastsComponent, MESSAGE_LOG_SIZE};
+
+    #[test]
+    fn test_toasts() {
+        let mut toasts = ToastsComponent::default();
+        toasts.push(MessageLevel::Info, "Saved".to_string());
+        assert!(!toasts.has_errors());
+
+        toasts.push(MessageLevel::Error, "Failed".to_string());
+        assert!(toasts.has_errors());
+        assert_eq!(toasts.log()[0].text, "Failed");
+
+        toasts.dismiss();
+        assert!(!toasts.has_errors());
+        // dismissed messages stay in the log
+        assert_eq!(toasts.log().len(), 2);
+
+        for idx in 0..MESSAGE_LOG_SIZE {
+            toasts.push(MessageLevel::Warning, idx.to_string());
+        }
+        assert_eq!(toasts.log().len(), MESSAGE_LOG_SIZE);
+        assert_eq!(toasts.log()[0].text, (MESSAGE_LOG_SIZE - 1).to_string());
+    }
+
+    #[test]
+    fn test_retry() {
+        let mut toasts = ToastsComponent::default();
+        assert_eq!(toasts.take_retry(), None);
+
+        let retry = ActionPacket::single(Action::LoadRandomPage);
+        toasts.push(MessageLevel::Error, "Unable to save".to_string());
+        toasts.push_retry("Unable to fetch".to_string(), retry.clone());
+        assert_eq!(toasts.take_retry(), Some(retry.clone()));
+        assert_eq!(toasts.take_retry(), None);
+        // only the retried error is hidden
+        assert!(toasts.has_errors());
+
+        toasts.push_retry("Unable to fetch".to_string(), retry);
+        toasts.dismiss();
+        assert_eq!(toasts.take_retry(), None);
+    }
+}
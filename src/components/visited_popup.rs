@@ -0,0 +1,240 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem},
+};
+use tui_input::{backend::crossterm::EventHandler, Input};
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult, PageViewerAction},
+    config::{Config, Theme},
+    history::VisitedPage,
+    terminal::Frame,
+    ui::{centered_rect, StatefulList},
+};
+
+use super::Component;
+
+/// Formats how long ago a page was visited, both times in seconds since the unix epoch
+pub fn format_elapsed(now: u64, visited: u64) -> String {
+    let elapsed = now.saturating_sub(visited);
+    match elapsed {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", elapsed / 60),
+        3600..=86399 => format!("{} h ago", elapsed / 3600),
+        _ => format!("{} days ago", elapsed / 86400),
+    }
+}
+
+/// A popup listing the visited pages, most recent first
+pub struct VisitedPagesComponent {
+    /// All visited pages, most recent first
+    pages: Vec<VisitedPage>,
+    /// The pages matching the filter
+    list: StatefulList<VisitedPage>,
+    filter: Input,
+    is_filtering: bool,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl VisitedPagesComponent {
+    pub fn new(pages: &[VisitedPage], config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        let pages: Vec<VisitedPage> = pages.iter().rev().cloned().collect();
+        Self {
+            list: StatefulList::with_items(pages.clone()),
+            pages,
+            filter: Input::default(),
+            is_filtering: false,
+
+            config,
+            theme,
+        }
+    }
+
+    fn update_list(&mut self) {
+        let filter = self.filter.value().to_lowercase();
+        let pages = self
+            .pages
+            .iter()
+            .filter(|page| page.title.to_lowercase().contains(&filter))
+            .cloned()
+            .collect();
+        self.list = StatefulList::with_items(pages);
+    }
+
+    fn handle_filter_input(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.pop_popup.matches_event(key) {
+            self.filter.reset();
+            self.update_list();
+            self.is_filtering = false;
+        } else if self.config.bindings.global.submit.matches_event(key) {
+            self.is_filtering = false;
+        } else if self.filter.handle_event(&Event::Key(key)).is_some() {
+            self.update_list();
+        }
+        ActionResult::consumed()
+    }
+
+    fn remove_selected(&mut self) -> ActionResult {
+        let page = match self.list.selected() {
+            Some(page) => page.clone(),
+            None => return ActionResult::Ignored,
+        };
+
+        self.pages.retain(|visited| *visited != page);
+        let selected = self.list.get_state_mut().selected();
+        self.update_list();
+        if let Some(idx) = selected {
+            let idx = idx.min(self.list.get_items().len().saturating_sub(1));
+            self.list.get_state_mut().select(Some(idx));
+        }
+
+        Action::PageViewer(PageViewerAction::RemoveVisitedPage(page)).into()
+    }
+}
+
+impl Component for VisitedPagesComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.is_filtering {
+            return self.handle_filter_input(key);
+        }
+
+        if self.config.bindings.global.pop_popup.matches_event(key)
+            || self
+                .config
+                .bindings
+                .page
+                .toggle_visited_pages
+                .matches_event(key)
+        {
+            return Action::PopPopup.into();
+        }
+
+        if self.config.bindings.global.submit.matches_event(key) {
+            return match self.list.selected() {
+                Some(page) => ActionPacket::single(Action::PopPopup)
+                    .action(Action::OpenVisitedPage(page.clone()))
+                    .into(),
+                None => ActionResult::Ignored,
+            };
+        }
+
+        match key.code {
+            KeyCode::Char('/') => {
+                self.is_filtering = true;
+                ActionResult::consumed()
+            }
+            KeyCode::Char('d') => self.remove_selected(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.list.previous()
+                }
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.list.next()
+                }
+            }
+            Action::UnselectScroll => self.list.unselect(),
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let block = self
+            .theme
+            .default_block()
+            .title("Visited Pages")
+            .title_bottom(Line::from("<ENTER> Open | </> Filter | <d> Delete").right_aligned())
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 50, 60);
+        f.render_widget(Clear, area);
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+
+        let list_area = if self.is_filtering || !self.filter.value().is_empty() {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(inner_area);
+            f.render_widget(
+                self.theme
+                    .default_paragraph(format!("/{}", self.filter.value()))
+                    .fg(self.theme.highlight_fg),
+                chunks[0],
+            );
+            if self.is_filtering {
+                f.set_cursor(
+                    chunks[0].x + 1 + self.filter.visual_cursor() as u16,
+                    chunks[0].y,
+                );
+            }
+            chunks[1]
+        } else {
+            inner_area
+        };
+
+        if self.list.get_items().is_empty() {
+            f.render_widget(self.theme.default_paragraph("No visited pages"), list_area);
+            return;
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let items = self.list.get_items().iter().map(|page| {
+            ListItem::new(Line::from(vec![
+                Span::raw(page.title.clone()).fg(self.theme.fg),
+                Span::raw(format!(
+                    "  {} | {}",
+                    page.language.name(),
+                    format_elapsed(now, page.visited)
+                ))
+                .fg(self.theme.inactive_fg),
+            ]))
+        });
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_stateful_widget(list, list_area, self.list.get_state_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_elapsed;
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(1000, 990), "just now");
+        assert_eq!(format_elapsed(1000, 1010), "just now");
+        assert_eq!(format_elapsed(4000, 1000), "50 min ago");
+        assert_eq!(format_elapsed(8000, 0), "2 h ago");
+        assert_eq!(format_elapsed(3 * 86400 + 5, 0), "3 days ago");
+    }
+}
@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem},
+};
+use tracing::warn;
+
+use crate::{
+    action::{Action, ActionPacket, ActionResult},
+    config::{Config, Theme},
+    terminal::Frame,
+    ui::{centered_rect, StatefulList},
+    watches::{WatchedPage, Watches, MAX_WATCHED_PAGES},
+};
+
+use super::Component;
+
+/// A popup listing the watched pages and marking the ones edited since they were last opened from
+/// here. Changes are saved right away
+pub struct WatchedPagesComponent {
+    list: StatefulList<WatchedPage>,
+
+    config: Arc<Config>,
+    theme: Arc<Theme>,
+}
+
+impl WatchedPagesComponent {
+    pub fn new(pages: &[WatchedPage], config: Arc<Config>, theme: Arc<Theme>) -> Self {
+        Self {
+            list: StatefulList::with_items(pages.to_vec()),
+
+            config,
+            theme,
+        }
+    }
+
+    /// Shows the pages, keeping the selection in place
+    fn set_pages(&mut self, pages: Vec<WatchedPage>) {
+        let selected = self.list.get_state_mut().selected();
+        self.list = StatefulList::with_items(pages);
+        if let Some(idx) = selected {
+            let idx = idx.min(self.list.get_items().len().saturating_sub(1));
+            self.list.get_state_mut().select(Some(idx));
+        }
+    }
+
+    /// Applies the change to the stored watched pages and the pages shown in the popup
+    fn change(&mut self, change: impl Fn(&mut Watches)) -> Result<(), ActionResult> {
+        let result = Watches::open().and_then(|mut store| {
+            change(store.get_mut());
+            store.save()?;
+            Ok(store.get().pages().to_vec())
+        });
+        match result {
+            Ok(pages) => {
+                self.set_pages(pages);
+                Ok(())
+            }
+            Err(error) => {
+                let error = error.context("Unable to save the watched pages");
+                warn!("{:?}", error);
+                Err(Action::PopupError(error.to_string()).into())
+            }
+        }
+    }
+
+    fn remove_selected(&mut self) -> ActionResult {
+        let page = match self.list.selected() {
+            Some(page) => page.clone(),
+            None => return ActionResult::Ignored,
+        };
+        match self.change(|watches| watches.remove(&page.endpoint, page.language, &page.title)) {
+            Ok(()) => ActionResult::consumed(),
+            Err(result) => result,
+        }
+    }
+
+    /// Opens the latest revision of the selected page and marks its update as seen
+    fn open_selected(&mut self) -> ActionResult {
+        let page = match self.list.selected() {
+            Some(page) => page.clone(),
+            None => return ActionResult::Ignored,
+        };
+        if let Err(result) =
+            self.change(|watches| watches.mark_seen(&page.endpoint, page.language, &page.title))
+        {
+            return result;
+        }
+        ActionPacket::single(Action::PopPopup)
+            .action(Action::LoadFreshPage(
+                page.endpoint,
+                page.language,
+                page.title,
+            ))
+            .into()
+    }
+}
+
+impl Component for WatchedPagesComponent {
+    fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = theme;
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> ActionResult {
+        if self.config.bindings.global.pop_popup.matches_event(key)
+            || self
+                .config
+                .bindings
+                .page
+                .toggle_watched_pages
+                .matches_event(key)
+        {
+            return Action::PopPopup.into();
+        }
+
+        if self.config.bindings.global.submit.matches_event(key) {
+            return self.open_selected();
+        }
+
+        match key.code {
+            KeyCode::Char('d') => self.remove_selected(),
+            _ => ActionResult::Ignored,
+        }
+    }
+
+    fn update(&mut self, action: Action) -> ActionResult {
+        match action {
+            Action::ScrollUp(n) => {
+                for _ in 0..n {
+                    self.list.previous()
+                }
+            }
+            Action::ScrollDown(n) => {
+                for _ in 0..n {
+                    self.list.next()
+                }
+            }
+            Action::UnselectScroll => self.list.unselect(),
+            // a check in the background found a new revision
+            Action::WatchedPageChecked(..) => match Watches::open() {
+                Ok(store) => self.set_pages(store.get().pages().to_vec()),
+                Err(error) => warn!("{:?}", error.context("failed loading the watched pages")),
+            },
+            _ => return ActionResult::Ignored,
+        }
+        ActionResult::consumed()
+    }
+
+    fn render(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let block = self
+            .theme
+            .default_block()
+            .title(format!(
+                "Watched Pages ({}/{MAX_WATCHED_PAGES})",
+                self.list.get_items().len()
+            ))
+            .title_bottom(Line::from("<ENTER> Open | <d> Stop watching").right_aligned())
+            .style(Style::default().bg(self.theme.bg));
+        let area = centered_rect(area, 50, 60);
+        f.render_widget(Clear, area);
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+
+        if self.list.get_items().is_empty() {
+            f.render_widget(self.theme.default_paragraph("No watched pages"), inner_area);
+            return;
+        }
+
+        let items = self.list.get_items().iter().map(|page| {
+            let mut spans = vec![
+                Span::raw(page.title.clone()).fg(self.theme.fg),
+                Span::raw(format!("  {}", page.language.name())).fg(self.theme.inactive_fg),
+            ];
+            if page.has_update {
+                spans.push(Span::raw("  updated").fg(self.theme.highlight_fg).bold());
+            }
+            ListItem::new(Line::from(spans))
+        });
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .fg(self.theme.selected_fg)
+                .bg(self.theme.selected_bg)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_stateful_widget(list, inner_area, self.list.get_state_mut());
+    }
+}
@@ -1,21 +1,42 @@
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use bitflags::bitflags;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use directories::ProjectDirs;
 use ratatui::{
     layout::Constraint,
-    style::{Color, Style},
-    widgets::{BorderType, Padding},
+    style::{Color, Modifier},
+    widgets::Padding,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    str::FromStr,
+};
+use tracing::{level_filters::LevelFilter, warn};
+use wiki_api::{
+    languages::Language,
+    page::{article_title, ArticleUrl, PageKind},
+    search, Endpoint,
+};
+
+use crate::{
+    renderer::{hyphenate::Hyphenator, RenderOptions, DESCRIPTION_INDENT},
+    store::Store,
+    ui::ColorMode,
+};
+
+pub use crate::{
+    renderer::BlockSpacing,
+    theme::{Theme, ThemeBorderType},
 };
-use serde::Deserialize;
-use std::{path::PathBuf, str::FromStr};
-use tracing::level_filters::LevelFilter;
-use wiki_api::{languages::Language, search, Endpoint};
 
 pub const CACHE_ENV: &str = "WIKI_TUI_CACHE";
 pub const CONFIG_ENV: &str = "WIKI_TUI_CONFIG";
+pub const DATA_ENV: &str = "WIKI_TUI_DATA";
 
 pub const THEME_FILE_NAME: &str = "theme.toml";
+pub const THEME_STORE: &str = "theme";
 pub const CONFIG_FILE_NAME: &str = "config.toml";
 
 pub fn project_dir() -> Option<ProjectDirs> {
@@ -34,6 +55,22 @@ pub fn cache_dir() -> Result<PathBuf> {
     Ok(directory)
 }
 
+pub fn data_dir() -> Result<PathBuf> {
+    let directory = if let Ok(dir) = std::env::var(DATA_ENV) {
+        PathBuf::from(dir)
+    } else if let Some(project_dir) = project_dir() {
+        project_dir.data_local_dir().to_path_buf()
+    } else {
+        bail!("Unable to find data directory for wiki-tui");
+    };
+
+    if !directory.exists() {
+        std::fs::create_dir_all(&directory).context("Unable to create the data folder")?;
+    }
+
+    Ok(directory)
+}
+
 pub fn config_dir() -> Result<PathBuf> {
     let directory = if let Ok(dir) = std::env::var(CONFIG_ENV) {
         PathBuf::from(dir)
@@ -74,6 +111,7 @@ pub fn load_logging_config() -> Result<LoggingConfig> {
 
     if let Some(inner) = user_config.logging {
         override_options!(default_config, inner::enabled);
+        override_options!(default_config, inner::log_viewer);
 
         // we need to manually parse the level
         if let Some(ref level) = inner.level {
@@ -87,6 +125,8 @@ pub fn load_logging_config() -> Result<LoggingConfig> {
 pub struct LoggingConfig {
     pub enabled: bool,
     pub level: LevelFilter,
+    /// Whether the records are kept for the log viewer
+    pub log_viewer: bool,
 }
 
 impl Default for LoggingConfig {
@@ -94,6 +134,7 @@ impl Default for LoggingConfig {
         LoggingConfig {
             enabled: true,
             level: LevelFilter::WARN,
+            log_viewer: cfg!(debug_assertions),
         }
     }
 }
@@ -108,18 +149,28 @@ struct UserLoggingConfigInner {
     enabled: Option<bool>,
     #[serde(rename = "log_level")]
     level: Option<String>,
+    log_viewer: Option<bool>,
 }
 
 pub fn load_config() -> Result<Config> {
     let mut default_config = Config::default();
-    let user_config = load_user_config().context("failed loading the user config")?;
+    let user_config_str = get_user_config()?;
+    let user_config =
+        toml::from_str::<UserConfig>(&user_config_str).context("failed parsing the user config")?;
 
     if let Some(user_page_config) = user_config.page {
         override_page_config(&mut default_config.page, user_page_config)
     }
 
     if let Some(user_bindings_config) = user_config.bindings {
-        override_bindings_config(&mut default_config.bindings, user_bindings_config)
+        let diagnostics = override_bindings_config(
+            &mut default_config.bindings,
+            user_bindings_config,
+            &user_config_str,
+        );
+        for diagnostic in diagnostics {
+            warn!("ignoring keybinding in the config, {diagnostic}");
+        }
     }
 
     if let Some(user_api_config) = user_config.api {
@@ -141,12 +192,62 @@ fn override_page_config(config: &mut PageConfig, user_config: UserPageConfig) {
             position,
             title,
             item_format,
+            introduction,
 
             enable_scrolling
         });
     }
 
-    override_options!(config, user_config::padding);
+    if let Some(user_media) = user_config.media {
+        override_options!(config.media, user_media::{
+            enabled,
+            max_size,
+            viewer
+        });
+    }
+
+    if let Some(user_images) = user_config.images {
+        override_options!(config.images, user_images::{
+            enabled,
+            protocol,
+            rows
+        });
+    }
+
+    override_options!(config, user_config::{
+        padding,
+        start_at_lead,
+        renderer,
+        history_depth,
+        status_bar,
+        link_selection,
+        words_per_minute,
+        cjk_characters_per_minute,
+        lookup,
+        closing_punctuation,
+        hyphenation,
+        right_to_left,
+        block_spacing
+    });
+
+    override_options!(config.display, user_config::{
+        max_width->max_width,
+        justify->justify,
+        hide->hidden,
+        description_indent->description_indent
+    });
+
+    if let Some(user_kinds) = user_config.kinds {
+        for (kind, user_overrides) in user_kinds {
+            let overrides = config.kinds.entry(kind).or_default();
+            override_options!(overrides, user_overrides::{
+                max_width->max_width,
+                justify->justify,
+                hide->hidden,
+                description_indent->description_indent
+            });
+        }
+    }
 
     if let Some(user_zen) = user_config.zen_mode {
         override_options!(config, user_zen::{
@@ -159,74 +260,155 @@ fn override_page_config(config: &mut PageConfig, user_config: UserPageConfig) {
     }
 }
 
-fn override_bindings_config(config: &mut Keybindings, user_config: UserKeybindingsConfig) {
-    if let Some(user_global_bindings) = user_config.global {
-        override_options!(config.global, user_global_bindings::{
-            scroll_down,
-            scroll_up,
-
-            scroll_to_top,
-            scroll_to_bottom,
-
-            pop_popup,
+/// Pairs of actions that are allowed to share a keybinding because they are never active at the
+/// same time
+const SHARED_BINDINGS: [(&str, &str, &str); 6] = [
+    ("global", "pop_popup", "exit_search_bar"),
+    ("global", "scroll_up", "history_prev"),
+    ("global", "scroll_down", "history_next"),
+    ("page", "jump_to_header", "open_link"),
+    ("page", "switch_renderer", "toggle_search_regex"),
+    ("page", "watch_page", "toggle_source_wrap"),
+];
+
+/// Overrides the keybindings with the ones from the user config. Invalid, unknown and
+/// conflicting bindings are returned as diagnostics, naming the line in the config file
+fn override_bindings_config(
+    config: &mut Keybindings,
+    user_config: UserKeybindingsConfig,
+    source: &str,
+) -> Vec<String> {
+    let line = |span: std::ops::Range<usize>| {
+        source
+            .get(..span.start)
+            .map(|before| before.matches('\n').count() + 1)
+            .unwrap_or_default()
+    };
 
-            half_down,
-            half_up,
-            unselect_scroll,
+    let mut diagnostics = Vec::new();
+    let sections = [
+        ("global", user_config.global),
+        ("search", user_config.search),
+        ("page", user_config.page),
+        ("logger", user_config.logger),
+    ];
+    for (section, user_bindings) in sections {
+        let Some(user_bindings) = user_bindings else {
+            continue;
+        };
 
-            submit,
-            quit,
-            enter_search_bar,
-            exit_search_bar,
+        let mut overridden: Vec<(String, usize)> = Vec::new();
+        for (name, value) in user_bindings {
+            let line = line(value.span());
+            let binding = match config.get_mut(section, &name) {
+                Some(binding) => binding,
+                None => {
+                    diagnostics.push((line, format!("unknown action 'bindings.{section}.{name}'")));
+                    continue;
+                }
+            };
 
-            switch_context_search,
-            switch_context_page,
+            match value.into_inner().try_into::<UserKeybinding>() {
+                Ok(user_binding) => {
+                    *binding = user_binding.into();
+                    overridden.push((name, line));
+                }
+                Err(error) => diagnostics.push((
+                    line,
+                    format!(
+                        "invalid keybinding for 'bindings.{section}.{name}': {}",
+                        error.message()
+                    ),
+                )),
+            }
+        }
 
-            toggle_search_language_selection,
-            toggle_logger
-        });
-    }
+        let keymap = config.keymap(section);
+        for (name, line) in overridden.iter() {
+            let binding = match keymap.iter().find(|(other, ..)| other == name) {
+                Some((.., binding)) => binding,
+                None => continue,
+            };
+            for (other, _, other_binding) in keymap.iter() {
+                let is_shared = SHARED_BINDINGS.iter().any(|(shared_section, a, b)| {
+                    *shared_section == section
+                        && ((a == name && b == other) || (b == name && a == other))
+                });
+                // conflicts between two overridden bindings are only reported for the later one
+                let is_reported = overridden
+                    .iter()
+                    .any(|(overridden, other_line)| overridden == other && other_line > line);
+                if other == name || is_shared || is_reported || !binding.conflicts(other_binding) {
+                    continue;
+                }
 
-    if let Some(user_search_bindings) = user_config.search {
-        override_options!(config.search, user_search_bindings::continue_search);
+                diagnostics.push((
+                    *line,
+                    format!(
+                        "'bindings.{section}.{name}' conflicts with 'bindings.{section}.{other}'"
+                    ),
+                ));
+            }
+        }
     }
 
-    if let Some(user_page_bindings) = user_config.page {
-        override_options!(config.page, user_page_bindings::{
-            pop_page,
-            jump_to_header,
-            select_first_link,
-            select_last_link,
-            select_next_link,
-            select_prev_link,
-            open_link,
-            toggle_page_language_selection,
-            toggle_zen_mode,
-            toggle_toc
-        });
-    }
+    diagnostics.sort_by_key(|(line, _)| *line);
+    diagnostics
+        .into_iter()
+        .map(|(line, diagnostic)| format!("line {line}: {diagnostic}"))
+        .collect()
 }
 
 fn override_api_config(config: &mut ApiConfig, user_config: UserApiConfig) -> Result<()> {
-    // we need to manually build the endpoint
+    // the url of wikipedia can be changed with the pre and post language
     {
         let pre_language = match user_config.pre_language {
             Some(ref language) => language.as_str(),
             None => "https://",
         };
-        let language = &user_config.language.unwrap_or(config.language);
         let post_language = match user_config.post_language {
             Some(ref language) => language.as_str(),
             None => ".wikipedia.org/w/api.php",
         };
 
-        config.endpoint = Endpoint::parse(&format!(
-            "{}{}{}",
-            pre_language,
-            language.code(),
-            post_language,
-        ))
-        .context("failed parsing the endpoint url")?;
+        config.sites[0] = Site::new(
+            "Wikipedia",
+            format!("{pre_language}{LANGUAGE_PLACEHOLDER}{post_language}"),
+            None,
+        )?;
+    }
+
+    for site in user_config.sites.unwrap_or_default() {
+        config
+            .sites
+            .push(Site::new(&site.name, site.url, site.language)?);
+    }
+
+    if let Some(ref name) = user_config.lookup_site {
+        config.lookup_site = Some(
+            config
+                .sites
+                .iter()
+                .position(|site| site.name == *name)
+                .ok_or_else(|| anyhow!("unknown site '{name}' of the lookup site"))?,
+        );
+    }
+
+    if let Some(ref namespaces) = user_config.search_namespaces {
+        config.search_namespaces = namespaces
+            .iter()
+            .map(|namespace| {
+                search::Namespace::from_string(namespace)
+                    .ok_or_else(|| anyhow!("unknown namespace '{namespace}'"))
+            })
+            .collect::<Result<_>>()?;
+    }
+
+    if let Some(headers) = user_config.headers {
+        config.headers = headers.into_iter().collect();
+    }
+    if let Some(params) = user_config.query_params {
+        config.query_params = params.into_iter().collect();
     }
 
     override_options!(config, user_config::{
@@ -238,8 +420,21 @@ fn override_api_config(config: &mut ApiConfig, user_config: UserApiConfig) -> Re
         search_qiprofile,
         search_rewrites,
         search_sort_order,
-
-        page_redirects
+        suggestion_limit,
+
+        page_redirects,
+        cache_size,
+        cache_max_age,
+        prefetch,
+        prefetch_visible,
+        retry_attempts,
+        retry_delay,
+        proxy,
+        ca_certificate,
+        rate_limit,
+        rate_burst,
+        user_agent,
+        watch_interval
     });
 
     Ok(())
@@ -248,7 +443,16 @@ fn override_api_config(config: &mut ApiConfig, user_config: UserApiConfig) -> Re
 fn override_ui_config(config: &mut UiConfig, user_config: UserUiConfig) {
     override_options!(config, user_config::{
         popup_search_language_changed,
-        popup_page_language_changed
+        popup_page_language_changed,
+        color_mode,
+        history_size,
+        compact_search_results,
+        persist_visited_pages,
+        open_in_browser,
+        restore_session,
+        homepage,
+        toast_duration,
+        toast_position
     });
 }
 
@@ -264,11 +468,6 @@ fn get_user_config() -> Result<String> {
     std::fs::read_to_string(&path).context("failed reading the config file")
 }
 
-fn load_user_config() -> Result<UserConfig> {
-    let user_config_str = get_user_config()?;
-    toml::from_str::<UserConfig>(&user_config_str).context("failed parsing the user config")
-}
-
 pub struct Config {
     pub page: PageConfig,
     pub bindings: Keybindings,
@@ -278,8 +477,42 @@ pub struct Config {
 
 pub struct PageConfig {
     pub toc: TocConfig,
+    pub media: MediaConfig,
+    pub images: ImagesConfig,
     pub padding: Padding,
 
+    pub display: DisplayOptions,
+    pub kinds: HashMap<PageKind, DisplayOverrides>,
+
+    /// Start at the first real paragraph of the article instead of the title
+    pub start_at_lead: bool,
+    /// Name of the renderer used when opening a page
+    pub renderer: String,
+    /// Number of pages kept in the history to go back to
+    pub history_depth: usize,
+    /// Template of the status bar, with placeholders like `{title}`
+    pub status_bar: String,
+    /// Whether links can be selected and opened. Otherwise the keys selecting them are free for
+    /// other actions
+    pub link_selection: bool,
+    /// Reading speed the reading time of a page is estimated with
+    pub words_per_minute: u32,
+    /// Reading speed for Chinese and Japanese text, which is counted in characters
+    pub cjk_characters_per_minute: u32,
+    /// Whether looking up the words of the selected link shows the search results or opens the
+    /// first one
+    pub lookup: LookupMode,
+    /// Characters that stick to the word before them, like the comma after a link, and never
+    /// start a line on their own
+    pub closing_punctuation: String,
+    /// Whether long words are split at their hyphenation points when wrapping
+    pub hyphenation: bool,
+    /// Whether pages in a right-to-left language are aligned to the right with their text
+    /// reordered for display
+    pub right_to_left: bool,
+    /// How many empty lines separate the blocks of a page
+    pub block_spacing: BlockSpacing,
+
     pub default_zen: bool,
     pub zen_mode: ZenModeComponents,
 
@@ -287,8 +520,72 @@ pub struct PageConfig {
     pub zen_vertical: Constraint,
 }
 
+impl PageConfig {
+    /// Returns the hyphenator for pages in the language, `None` when hyphenation is disabled or
+    /// there are no patterns for the language
+    pub fn hyphenator(&self, language: Language) -> Option<Hyphenator> {
+        match self.hyphenation {
+            true => Hyphenator::new(language),
+            false => None,
+        }
+    }
+
+    /// Returns the display options for a kind of page, with the overrides for that kind applied
+    pub fn display_options(&self, kind: PageKind) -> DisplayOptions {
+        let mut options = self.display.clone();
+        if let Some(overrides) = self.kinds.get(&kind) {
+            if let Some(max_width) = overrides.max_width {
+                options.max_width = max_width;
+            }
+            if let Some(justify) = overrides.justify {
+                options.justify = justify;
+            }
+            if let Some(ref hidden) = overrides.hidden {
+                options.hidden = hidden.clone();
+            }
+            if let Some(description_indent) = overrides.description_indent {
+                options.description_indent = description_indent;
+            }
+        }
+        options
+    }
+
+    /// Returns the options the renderers lay out a page of the kind in the language with
+    pub fn render_options(&self, kind: PageKind, language: Language) -> RenderOptions {
+        let display = self.display_options(kind);
+        RenderOptions {
+            justify: display.justify,
+            closing_punctuation: self.closing_punctuation.clone(),
+            hyphenator: self.hyphenator(language),
+            spacing: self.block_spacing,
+            description_indent: display.description_indent,
+        }
+    }
+}
+
+/// Options controlling how the content of a page is displayed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayOptions {
+    /// Maximum width of the content, `0` means no limit
+    pub max_width: u16,
+    pub justify: bool,
+    /// Components that are always hidden, regardless of the zen-mode
+    pub hidden: ZenModeComponents,
+    /// Columns each level of a description list is indented by, like the replies on talk pages
+    pub description_indent: u8,
+}
+
+/// Overrides of the display options for a specific kind of page
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct DisplayOverrides {
+    pub max_width: Option<u16>,
+    pub justify: Option<bool>,
+    pub hidden: Option<ZenModeComponents>,
+    pub description_indent: Option<u8>,
+}
+
 bitflags! {
-    #[derive(Deserialize, Debug, Clone)]
+    #[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
     pub struct ZenModeComponents: u8 {
         const STATUS_BAR = 0b00000001;
         const TOC        = 0b00000010;
@@ -297,12 +594,15 @@ bitflags! {
     }
 }
 
+/// The widest padding on each side of the page, more would only waste the space of the content
+const MAX_PADDING: u16 = 4;
+
 #[derive(Deserialize, Debug)]
 #[serde(untagged)]
 pub enum PaddingConfig {
     Uniform(u16),
     Horizontal { horizontal: u16 },
-    Vertical { veritical: u16 },
+    Vertical { vertical: u16 },
     Proportional { proportional: u16 },
     Symmetric { symmetric: (u16, u16) },
     Custom(u16, u16, u16, u16),
@@ -312,16 +612,22 @@ pub enum PaddingConfig {
                                  // ignore the warning
 impl Into<Padding> for PaddingConfig {
     fn into(self) -> Padding {
-        match self {
+        let padding = match self {
             PaddingConfig::Uniform(val) => Padding::uniform(val),
             PaddingConfig::Horizontal { horizontal } => Padding::horizontal(horizontal),
-            PaddingConfig::Vertical { veritical } => Padding::vertical(veritical),
+            PaddingConfig::Vertical { vertical } => Padding::vertical(vertical),
             PaddingConfig::Proportional { proportional } => Padding::proportional(proportional),
             PaddingConfig::Symmetric { symmetric } => Padding::symmetric(symmetric.0, symmetric.1),
             PaddingConfig::Custom(left, right, top, bottom) => {
                 Padding::new(left, right, top, bottom)
             }
-        }
+        };
+        Padding::new(
+            padding.left.min(MAX_PADDING),
+            padding.right.min(MAX_PADDING),
+            padding.top.min(MAX_PADDING),
+            padding.bottom.min(MAX_PADDING),
+        )
     }
 }
 
@@ -338,20 +644,94 @@ pub enum TocConfigTitle {
     Custom(String),
 }
 
+/// How media links are opened
+pub struct MediaConfig {
+    /// Whether media links can be opened
+    pub enabled: bool,
+    /// Images are scaled down to fit into this many pixels before they're downloaded
+    pub max_size: u32,
+    /// The command opening the downloaded images, the opener of the platform when `None`
+    pub viewer: Option<String>,
+}
+
+/// How the images of figures are drawn into the page
+pub struct ImagesConfig {
+    /// Whether images are drawn at all
+    pub enabled: bool,
+    /// The graphics protocol of the terminal, detected from the environment with `Auto`
+    pub protocol: ImageProtocol,
+    /// Number of rows reserved below every figure
+    pub rows: u16,
+}
+
+/// The graphics protocol images are drawn with
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageProtocol {
+    #[default]
+    Auto,
+    Sixel,
+    Kitty,
+    Iterm2,
+    /// Don't draw any images
+    None,
+}
+
 pub struct TocConfig {
     pub enabled: bool,
     pub width_percentage: u16,
     pub position: TocConfigPosition,
     pub title: TocConfigTitle,
     item_format: String,
+    /// The label of the first entry, which leads to the introduction of the article
+    pub introduction: String,
 
     pub enable_scrolling: bool,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, PartialEq, Eq)]
 struct Binding {
     code: KeyCode,
     modifiers: KeyModifiers,
+    /// The key pressed before, for a key sequence like `za`
+    prefix: Option<char>,
+}
+
+impl std::fmt::Display for Binding {
+    /// Formats the binding in a human-readable form, e.g. `Shift+→` or `za`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(prefix) = self.prefix {
+            write!(f, "{prefix}")?;
+        }
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl"),
+            (KeyModifiers::ALT, "Alt"),
+            (KeyModifiers::SHIFT, "Shift"),
+        ] {
+            // the shift modifier is already visible in upper case characters, symbols and
+            // the back tab key
+            let is_implied = modifier == KeyModifiers::SHIFT
+                && (matches!(self.code, KeyCode::Char(c) if !c.is_lowercase())
+                    || self.code == KeyCode::BackTab);
+            if self.modifiers.contains(modifier) && !is_implied {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            code => write!(f, "{code:?}"),
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -359,6 +739,21 @@ pub struct Keybinding {
     bindings: Vec<Binding>,
 }
 
+impl std::fmt::Display for Keybinding {
+    /// Formats all bindings in a human-readable form, separated by a `/`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut bindings: Vec<String> = Vec::new();
+        for binding in self.bindings.iter() {
+            let binding = binding.to_string();
+            // the same key can be bound with and without the (implied) shift modifier
+            if !bindings.contains(&binding) {
+                bindings.push(binding);
+            }
+        }
+        write!(f, "{}", bindings.join(" / "))
+    }
+}
+
 impl Keybinding {
     fn new() -> Self {
         Self {
@@ -367,7 +762,20 @@ impl Keybinding {
     }
 
     fn binding(mut self, code: KeyCode, modifiers: KeyModifiers) -> Self {
-        self.bindings.push(Binding { code, modifiers });
+        self.bindings.push(Binding {
+            code,
+            modifiers,
+            prefix: None,
+        });
+        self
+    }
+
+    fn sequence(mut self, prefix: char, code: KeyCode, modifiers: KeyModifiers) -> Self {
+        self.bindings.push(Binding {
+            code,
+            modifiers,
+            prefix: Some(prefix),
+        });
         self
     }
 
@@ -375,7 +783,24 @@ impl Keybinding {
         return self
             .bindings
             .iter()
-            .any(|x| x.code == event.code && x.modifiers == event.modifiers);
+            .any(|x| x.prefix.is_none() && x.code == event.code && x.modifiers == event.modifiers);
+    }
+
+    /// Whether the key completes a key sequence of the keybinding starting with the prefix
+    pub fn matches_sequence(&self, prefix: char, event: KeyEvent) -> bool {
+        return self.bindings.iter().any(|x| {
+            x.prefix == Some(prefix) && x.code == event.code && x.modifiers == event.modifiers
+        });
+    }
+
+    /// Returns the keys starting the key sequences of the keybinding
+    fn prefixes(&self) -> impl Iterator<Item = char> + '_ {
+        self.bindings.iter().filter_map(|x| x.prefix)
+    }
+
+    /// Returns whether both keybindings share at least one key
+    fn conflicts(&self, other: &Keybinding) -> bool {
+        self.bindings.iter().any(|x| other.bindings.contains(x))
     }
 }
 
@@ -393,6 +818,7 @@ pub struct GlobalKeybindings {
     pub unselect_scroll: Keybinding,
 
     pub submit: Keybinding,
+    pub submit_lucky: Keybinding,
     pub quit: Keybinding,
     pub enter_search_bar: Keybinding,
     pub exit_search_bar: Keybinding,
@@ -401,15 +827,31 @@ pub struct GlobalKeybindings {
     pub switch_context_page: Keybinding,
 
     pub toggle_search_language_selection: Keybinding,
+    pub toggle_site_selection: Keybinding,
+    pub toggle_offline: Keybinding,
+    pub suspend: Keybinding,
     pub toggle_logger: Keybinding,
+    pub toggle_messages: Keybinding,
+    pub toggle_color_mode: Keybinding,
+    pub cycle_theme: Keybinding,
+    pub toggle_help: Keybinding,
+    pub command_palette: Keybinding,
+    pub history_prev: Keybinding,
+    pub history_next: Keybinding,
+    pub load_random_page: Keybinding,
+    pub show_request_stats: Keybinding,
+    pub retry_load: Keybinding,
 }
 
 pub struct SearchKeybindings {
     pub continue_search: Keybinding,
+    pub toggle_filters: Keybinding,
 }
 
 pub struct PageKeybindings {
     pub pop_page: Keybinding,
+    pub history_back: Keybinding,
+    pub history_forward: Keybinding,
     pub jump_to_header: Keybinding,
 
     pub select_first_link: Keybinding,
@@ -419,21 +861,188 @@ pub struct PageKeybindings {
     pub select_next_link: Keybinding,
 
     pub open_link: Keybinding,
+    pub jump_back: Keybinding,
+    pub select_reference_link: Keybinding,
 
     pub toggle_page_language_selection: Keybinding,
     pub toggle_zen_mode: Keybinding,
     pub toggle_toc: Keybinding,
+
+    pub add_highlight: Keybinding,
+    pub toggle_highlights: Keybinding,
+    pub show_disambiguation: Keybinding,
+    pub toggle_visited_pages: Keybinding,
+    pub add_bookmark: Keybinding,
+    pub toggle_bookmarks: Keybinding,
+    pub read_link_later: Keybinding,
+    pub read_page_later: Keybinding,
+    pub toggle_reading_list: Keybinding,
+    pub watch_page: Keybinding,
+    pub toggle_watched_pages: Keybinding,
+    pub export_page: Keybinding,
+    pub dump_render: Keybinding,
+    pub copy_url: Keybinding,
+    pub copy_section_url: Keybinding,
+    pub open_in_browser: Keybinding,
+    pub show_page_info: Keybinding,
+    pub show_revisions: Keybinding,
+    pub show_backlinks: Keybinding,
+    pub switch_namespace: Keybinding,
+    pub refresh_page: Keybinding,
+    pub view_source: Keybinding,
+    pub toggle_source_wrap: Keybinding,
+    pub visual_mode: Keybinding,
+    pub copy_selection: Keybinding,
+    pub toggle_scrollbar: Keybinding,
+    pub move_divider_left: Keybinding,
+    pub move_divider_right: Keybinding,
+    pub scroll_left: Keybinding,
+    pub scroll_right: Keybinding,
+    pub lookup_word: Keybinding,
+    pub search_other_site: Keybinding,
+    pub toggle_fold: Keybinding,
+    pub fold_all: Keybinding,
+    pub unfold_all: Keybinding,
+    pub copy_section: Keybinding,
+    pub go_to_references: Keybinding,
+    pub go_to_external_links: Keybinding,
+
+    pub switch_renderer: Keybinding,
+
+    pub search: Keybinding,
+    pub next_match: Keybinding,
+    pub prev_match: Keybinding,
+    pub toggle_search_regex: Keybinding,
+    pub toggle_search_whole_word: Keybinding,
+}
+
+impl PageKeybindings {
+    /// Returns the keys starting the key sequences of the page, like `z` for `za`
+    pub fn sequence_prefixes(&self) -> Vec<char> {
+        let mut prefixes: Vec<char> = self
+            .keymap()
+            .into_iter()
+            .flat_map(|(.., binding)| binding.prefixes())
+            .collect();
+        prefixes.sort_unstable();
+        prefixes.dedup();
+        prefixes
+    }
+}
+
+pub struct LoggerKeybindings {
+    pub filter_log: Keybinding,
+    pub cycle_log_level: Keybinding,
+    pub copy_log: Keybinding,
 }
 
 pub struct Keybindings {
     pub global: GlobalKeybindings,
     pub search: SearchKeybindings,
     pub page: PageKeybindings,
+    pub logger: LoggerKeybindings,
+}
+
+impl Keybindings {
+    fn get_mut(&mut self, section: &str, name: &str) -> Option<&mut Keybinding> {
+        match section {
+            "global" => self.global.get_mut(name),
+            "search" => self.search.get_mut(name),
+            "page" => self.page.get_mut(name),
+            "logger" => self.logger.get_mut(name),
+            _ => None,
+        }
+    }
+
+    /// Returns the names and descriptions of all actions in a section (`global`, `search`,
+    /// `page` or `logger`) with their effective keybindings
+    pub fn keymap(&self, section: &str) -> Vec<(&'static str, &'static str, &Keybinding)> {
+        match section {
+            "global" => self.global.keymap(),
+            "search" => self.search.keymap(),
+            "page" => self.page.keymap(),
+            "logger" => self.logger.keymap(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Replaced by the language code in the url of a site
+const LANGUAGE_PLACEHOLDER: &str = "{language}";
+
+/// Replaced by the version of wiki-tui in the user agent
+pub const VERSION_PLACEHOLDER: &str = "{version}";
+
+/// The user agent identifying wiki-tui with a way to contact its authors, as asked by the
+/// Wikimedia user-agent policy
+const DEFAULT_USER_AGENT: &str = "wiki-tui/{version} (https://github.com/builditluc/wiki-tui)";
+
+/// A MediaWiki site that can be searched and read, like Wikipedia, Wiktionary or a Fandom wiki
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Site {
+    pub name: String,
+    /// The url of the API. Sites with a wiki per language have a `{language}` in the url, which is
+    /// replaced by the language code
+    pub url: String,
+    /// The language switched to when switching to the site, the current language is kept when
+    /// `None`
+    pub language: Option<Language>,
+}
+
+impl Site {
+    pub fn new(name: &str, url: String, language: Option<Language>) -> Result<Self> {
+        let site = Site {
+            name: name.to_string(),
+            url,
+            language,
+        };
+        Endpoint::parse(
+            &site
+                .url
+                .replace(LANGUAGE_PLACEHOLDER, Language::English.code()),
+        )
+        .with_context(|| format!("invalid url '{}' of the site '{name}'", site.url))?;
+        Ok(site)
+    }
+
+    /// Returns the endpoint of the wiki in the language, sites without a wiki per language
+    /// always have the same endpoint
+    pub fn endpoint(&self, language: Language) -> Endpoint {
+        Endpoint::parse(&self.url.replace(LANGUAGE_PLACEHOLDER, language.code()))
+            .expect("the url of the site is checked when it's created")
+    }
+
+    /// Names the wiki of the site in the language, like "German Wikipedia"
+    pub fn wiki_name(&self, language: Language) -> String {
+        match self.url.contains(LANGUAGE_PLACEHOLDER) {
+            true => format!("{} {}", language.name(), self.name),
+            false => self.name.clone(),
+        }
+    }
+
+    /// Returns the language of the url when it belongs to the site. Sites without a wiki per
+    /// language return their language or the fallback
+    pub fn url_language(&self, url: &Endpoint, fallback: Language) -> Option<Language> {
+        let host = url.host_str()?;
+        let site_host = self.url.split("://").nth(1)?.split('/').next()?;
+        match site_host.split_once(LANGUAGE_PLACEHOLDER) {
+            Some((prefix, suffix)) => {
+                let code = host.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                // the mobile site looks like [lang].m.wikipedia.org
+                Language::from_str(code.trim_end_matches(".m")).ok()
+            }
+            None => (host == site_host).then(|| self.language.unwrap_or(fallback)),
+        }
+    }
 }
 
 pub struct ApiConfig {
-    pub endpoint: Endpoint,
     pub language: Language,
+    /// The sites that can be switched to, the first one is Wikipedia
+    pub sites: Vec<Site>,
+    /// Index of the site preselected when searching the selected link or section on another
+    /// site, the first site other than the searched one when `None`
+    pub lookup_site: Option<usize>,
 
     pub search_limit: usize,
     pub search_qiprofile: search::QiProfile,
@@ -441,13 +1050,137 @@ pub struct ApiConfig {
     pub search_info: search::Info,
     pub search_rewrites: bool,
     pub search_sort_order: search::SortOrder,
+    /// Namespaces searched by default, the search filters can change them for a session
+    pub search_namespaces: Vec<search::Namespace>,
+    /// Number of pages suggested while typing a search query, `0` disables the suggestions
+    pub suggestion_limit: usize,
 
+    /// Whether to open the target of a redirect instead of the redirect page
     pub page_redirects: bool,
+    /// Maximum size of the page cache in MiB, `0` disables the cache
+    pub cache_size: u64,
+    /// Hours a cached page is read from the cache without checking for a newer revision
+    pub cache_max_age: u64,
+    /// Fetch the pages of links that are likely to be opened in the background
+    pub prefetch: bool,
+    /// Number of the first links in the viewport that are prefetched
+    pub prefetch_visible: usize,
+    /// Maximum number of attempts of a request failing transiently, `1` disables retrying
+    pub retry_attempts: u32,
+    /// Milliseconds before the first retry, doubled for every further retry
+    pub retry_delay: u64,
+    /// The HTTP or SOCKS5 proxy of all requests, the proxy environment variables are used when
+    /// `None`
+    pub proxy: Option<String>,
+    /// An additional CA certificate trusted for TLS connections, for proxies intercepting them
+    pub ca_certificate: Option<PathBuf>,
+    /// Requests per second sent to the sites at most, `0` disables the limit
+    pub rate_limit: f64,
+    /// Number of requests that can be sent at once after not sending requests for a while
+    pub rate_burst: u32,
+    /// The user agent of all requests, `{version}` is replaced with the version of wiki-tui
+    pub user_agent: String,
+    /// Headers sent with every request, as their name and value
+    pub headers: Vec<(String, String)>,
+    /// Query parameters added to every request to the API of a site, like `origin=*`
+    pub query_params: Vec<(String, String)>,
+    /// Minutes between the checks of the watched pages for new revisions, `0` disables the
+    /// checks
+    pub watch_interval: u64,
+}
+
+impl ApiConfig {
+    /// Returns the site the endpoint belongs to
+    pub fn site_of(&self, endpoint: &Endpoint) -> Option<&Site> {
+        self.sites
+            .iter()
+            .find(|site| site.url_language(endpoint, self.language).is_some())
+    }
+
+    /// Returns the article the url points to, when the url belongs to one of the sites. Used for
+    /// following links to other wikis
+    pub fn resolve_article_url(&self, url: &Endpoint) -> Option<(&Site, ArticleUrl)> {
+        self.sites.iter().find_map(|site| {
+            let language = site.url_language(url, self.language)?;
+            let (title, anchor) = article_title(url).ok()?;
+            Some((
+                site,
+                ArticleUrl {
+                    title,
+                    language,
+                    endpoint: site.endpoint(language),
+                    anchor,
+                },
+            ))
+        })
+    }
+}
+
+/// What looking up the words of the selected link does
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LookupMode {
+    /// Search the site and show the results
+    #[default]
+    Search,
+    /// Open the first result of the search right away
+    Lucky,
+}
+
+/// The corner of the screen the messages are shown in
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToastPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// The page opened on startup
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Homepage {
+    /// Start with the search instead of a page
+    #[default]
+    Search,
+    Random,
+    /// Today's featured article
+    Featured,
+    /// The article with the title, in the language of the search
+    Article(String),
+}
+
+impl From<String> for Homepage {
+    fn from(value: String) -> Self {
+        match value.trim() {
+            "" | "search" => Homepage::Search,
+            "random" => Homepage::Random,
+            "featured" => Homepage::Featured,
+            title => Homepage::Article(title.to_string()),
+        }
+    }
 }
 
 pub struct UiConfig {
     pub popup_search_language_changed: bool,
     pub popup_page_language_changed: bool,
+    pub color_mode: ColorMode,
+    /// Number of queries kept in the history of each prompt
+    pub history_size: usize,
+    /// Only show the titles of the search results, without their snippet and metadata
+    pub compact_search_results: bool,
+    /// Save the visited pages, so they're listed in later sessions
+    pub persist_visited_pages: bool,
+    /// Allow opening pages in the web browser
+    pub open_in_browser: bool,
+    /// Reopen the page of the last session at its position on startup
+    pub restore_session: bool,
+    /// The page opened on startup, unless the page of the last session is restored
+    pub homepage: Homepage,
+    /// Seconds a message is shown, errors are shown until they're dismissed
+    pub toast_duration: u64,
+    pub toast_position: ToastPosition,
 }
 
 impl Config {
@@ -463,6 +1196,15 @@ impl Config {
                 }
             };
         }
+        macro_rules! sequence {
+            ($prefix:literal, $ch:expr; $($md:ident)|*) => {
+                Keybinding::new().sequence(
+                    $prefix,
+                    $ch,
+                    KeyModifiers::NONE$(|KeyModifiers::$md)*
+                )
+            };
+        }
 
         Self {
             page: PageConfig {
@@ -472,10 +1214,67 @@ impl Config {
                     position: TocConfigPosition::Right,
                     title: TocConfigTitle::Default,
                     item_format: "{NUMBER} {TEXT}".to_string(),
+                    introduction: "Introduction".to_string(),
 
                     enable_scrolling: true,
                 },
-                padding: Padding::zero(),
+                media: MediaConfig {
+                    enabled: true,
+                    max_size: 1280,
+                    viewer: None,
+                },
+                images: ImagesConfig {
+                    enabled: false,
+                    protocol: ImageProtocol::Auto,
+                    rows: 12,
+                },
+                padding: Padding::uniform(1),
+
+                display: DisplayOptions {
+                    max_width: 0,
+                    justify: false,
+                    hidden: ZenModeComponents::empty(),
+                    description_indent: DESCRIPTION_INDENT,
+                },
+                kinds: HashMap::from([
+                    (
+                        PageKind::Disambiguation,
+                        DisplayOverrides {
+                            max_width: Some(0),
+                            justify: Some(false),
+                            ..DisplayOverrides::default()
+                        },
+                    ),
+                    (
+                        PageKind::Category,
+                        DisplayOverrides {
+                            max_width: Some(0),
+                            justify: Some(false),
+                            ..DisplayOverrides::default()
+                        },
+                    ),
+                    // the replies of discussions are easier to tell apart with a wider indentation
+                    (
+                        PageKind::Talk,
+                        DisplayOverrides {
+                            description_indent: Some(4),
+                            ..DisplayOverrides::default()
+                        },
+                    ),
+                ]),
+
+                start_at_lead: false,
+                renderer: "default".to_string(),
+                history_depth: 50,
+                status_bar: " {site} | {kind} '{title}' | Language '{language}' | '{languages_available}' other languages available | Renderer '{renderer}' | {cached} | {offline} | {reading_list} | {history} | {scroll_percent}% of {reading_time} | {columns} | {notice} | {search}".to_string(),
+                link_selection: true,
+                words_per_minute: 230,
+                cjk_characters_per_minute: 500,
+                lookup: LookupMode::Search,
+                closing_punctuation: ",.:;!?%)]}\"'@".to_string(),
+                hyphenation: false,
+                right_to_left: true,
+                block_spacing: BlockSpacing::Normal,
 
                 default_zen: false,
                 zen_mode: ZenModeComponents::empty(),
@@ -499,6 +1298,7 @@ impl Config {
                     unselect_scroll: keybinding!([KeyCode::Char('h');]),
 
                     submit: keybinding!([KeyCode::Enter;]),
+                    submit_lucky: keybinding!([KeyCode::Enter; ALT]),
                     quit: keybinding!([KeyCode::Char('q');, KeyCode::Char('c'); CONTROL]),
 
                     enter_search_bar: keybinding!([KeyCode::Char('i');]),
@@ -508,28 +1308,102 @@ impl Config {
                     switch_context_page: keybinding!([KeyCode::Char('p');]),
 
                     toggle_search_language_selection: keybinding!([KeyCode::F(2);]),
-                    toggle_logger: keybinding!([KeyCode::Char('l');]),
+                    toggle_site_selection: keybinding!([KeyCode::Char('e'); CONTROL]),
+                    toggle_offline: keybinding!([KeyCode::Char('O'); SHIFT]),
+                    suspend: keybinding!([KeyCode::Char('z'); CONTROL]),
+                    toggle_logger: keybinding!([KeyCode::F(12);]),
+                    toggle_messages: keybinding!([KeyCode::F(10);]),
+                    toggle_color_mode: keybinding!([KeyCode::F(6);]),
+                    cycle_theme: keybinding!([KeyCode::F(7);]),
+                    toggle_help: keybinding!([KeyCode::Char('?');, KeyCode::Char('?'); SHIFT]),
+                    command_palette: keybinding!([KeyCode::Char(':');, KeyCode::Char(':'); SHIFT]),
+                    history_prev: keybinding!([KeyCode::Up;]),
+                    history_next: keybinding!([KeyCode::Down;]),
+                    load_random_page: keybinding!([KeyCode::Char('n'); CONTROL]),
+                    show_request_stats: keybinding!([KeyCode::Char('t'); CONTROL]),
+                    retry_load: keybinding!([KeyCode::Char('r'); ALT]),
                 },
                 search: SearchKeybindings {
                     continue_search: keybinding!([KeyCode::Char('c');]),
+                    toggle_filters: keybinding!([KeyCode::Char('F'); SHIFT]),
                 },
                 page: PageKeybindings {
                     pop_page: keybinding!([KeyCode::Esc;]),
+                    history_back: keybinding!([KeyCode::Backspace;]),
+                    history_forward: keybinding!([KeyCode::Char('L'); SHIFT]),
                     jump_to_header: keybinding!([KeyCode::Enter;]),
                     select_first_link: keybinding!([KeyCode::Left; SHIFT]),
                     select_last_link: keybinding!([KeyCode::Right; SHIFT]),
                     select_prev_link: keybinding!([KeyCode::Left;]),
                     select_next_link: keybinding!([KeyCode::Right;]),
                     open_link: keybinding!([KeyCode::Enter;]),
+                    jump_back: keybinding!([KeyCode::Char('\'');]),
+                    select_reference_link: keybinding!([KeyCode::Char('f');]),
                     toggle_page_language_selection: keybinding!([KeyCode::F(3);]),
-                    toggle_zen_mode: keybinding!([KeyCode::F(4);]),
+                    toggle_zen_mode: keybinding!([KeyCode::Char('Z'); SHIFT, KeyCode::F(4);]),
                     toggle_toc: keybinding!([KeyCode::Tab;, KeyCode::BackTab;]),
+
+                    add_highlight: keybinding!([KeyCode::Char('H'); SHIFT]),
+                    toggle_highlights: keybinding!([KeyCode::F(5);]),
+                    show_disambiguation: keybinding!([KeyCode::Char('M'); SHIFT]),
+                    toggle_visited_pages: keybinding!([KeyCode::F(8);]),
+                    add_bookmark: keybinding!([KeyCode::Char('b');]),
+                    toggle_bookmarks: keybinding!([KeyCode::Char('B'); SHIFT]),
+                    read_link_later: keybinding!([KeyCode::Char('S'); SHIFT]),
+                    read_page_later: keybinding!([KeyCode::Char('A'); SHIFT]),
+                    toggle_reading_list: keybinding!([KeyCode::F(9);]),
+                    watch_page: keybinding!([KeyCode::Char('w');]),
+                    toggle_watched_pages: keybinding!([KeyCode::F(11);]),
+                    export_page: keybinding!([KeyCode::Char('s'); CONTROL]),
+                    dump_render: keybinding!([KeyCode::Char('D'); SHIFT]),
+                    copy_url: keybinding!([KeyCode::Char('c');]),
+                    copy_section_url: keybinding!([KeyCode::Char('C'); SHIFT]),
+                    open_in_browser: keybinding!([KeyCode::Char('o'); CONTROL]),
+                    show_page_info: keybinding!([KeyCode::Char('I'); SHIFT]),
+                    show_revisions: keybinding!([KeyCode::Char('R'); SHIFT]),
+                    show_backlinks: keybinding!([KeyCode::Char('l'); CONTROL]),
+                    switch_namespace: keybinding!([KeyCode::Char('T'); SHIFT]),
+                    refresh_page: keybinding!([KeyCode::Char('r');]),
+                    view_source: keybinding!([KeyCode::Char('V'); SHIFT]),
+                    toggle_source_wrap: keybinding!([KeyCode::Char('w');]),
+                    visual_mode: keybinding!([KeyCode::Char('v');]),
+                    copy_selection: keybinding!([KeyCode::Char('y');]),
+                    toggle_scrollbar: keybinding!([KeyCode::Char('b'); CONTROL]),
+                    move_divider_left: keybinding!([KeyCode::Left; CONTROL | SHIFT]),
+                    move_divider_right: keybinding!([KeyCode::Right; CONTROL | SHIFT]),
+                    scroll_left: keybinding!([KeyCode::Char('<');, KeyCode::Char('<'); SHIFT]),
+                    scroll_right: keybinding!([KeyCode::Char('>');, KeyCode::Char('>'); SHIFT]),
+                    lookup_word: keybinding!([KeyCode::Char('K'); SHIFT]),
+                    search_other_site: keybinding!([KeyCode::Char('W'); SHIFT]),
+                    toggle_fold: sequence!('z', KeyCode::Char('a');),
+                    fold_all: sequence!('z', KeyCode::Char('M'); SHIFT),
+                    unfold_all: sequence!('z', KeyCode::Char('R'); SHIFT),
+                    copy_section: sequence!('y', KeyCode::Char('s');),
+                    go_to_references: sequence!('g', KeyCode::Char('r');),
+                    go_to_external_links: sequence!('g', KeyCode::Char('e');),
+
+                    switch_renderer: keybinding!([KeyCode::Char('r'); CONTROL]),
+
+                    search: keybinding!([KeyCode::Char('/');]),
+                    next_match: keybinding!([KeyCode::Char('n');]),
+                    prev_match: keybinding!([KeyCode::Char('N'); SHIFT]),
+                    toggle_search_regex: keybinding!([KeyCode::Char('r'); CONTROL]),
+                    toggle_search_whole_word: keybinding!([KeyCode::Char('w'); CONTROL]),
+                },
+                logger: LoggerKeybindings {
+                    filter_log: keybinding!([KeyCode::Char('/');]),
+                    cycle_log_level: keybinding!([KeyCode::Char('v');]),
+                    copy_log: keybinding!([KeyCode::Char('y');]),
                 },
             },
             api: ApiConfig {
-                endpoint: Endpoint::parse("https://en.wikipedia.org/w/api.php")
-                    .expect("Hardcoded links should work"),
                 language: Language::English,
+                sites: vec![Site {
+                    name: "Wikipedia".to_string(),
+                    url: format!("https://{LANGUAGE_PLACEHOLDER}.wikipedia.org/w/api.php"),
+                    language: None,
+                }],
+                lookup_site: None,
 
                 search_limit: 10,
                 search_qiprofile: search::QiProfile::default(),
@@ -537,12 +1411,37 @@ impl Config {
                 search_info: search::Info::default(),
                 search_rewrites: false,
                 search_sort_order: search::SortOrder::Relevance,
-
-                page_redirects: false,
+                search_namespaces: vec![search::Namespace::Main],
+                suggestion_limit: 8,
+
+                page_redirects: true,
+                cache_size: 100,
+                cache_max_age: 0,
+                prefetch: false,
+                prefetch_visible: 3,
+                retry_attempts: 3,
+                retry_delay: 1000,
+                proxy: None,
+                ca_certificate: None,
+                rate_limit: 5.0,
+                rate_burst: 5,
+                user_agent: DEFAULT_USER_AGENT.to_string(),
+                headers: Vec::new(),
+                query_params: Vec::new(),
+                watch_interval: 15,
             },
             ui: UiConfig {
                 popup_search_language_changed: true,
                 popup_page_language_changed: true,
+                color_mode: ColorMode::Auto,
+                history_size: 100,
+                compact_search_results: false,
+                persist_visited_pages: true,
+                open_in_browser: true,
+                restore_session: false,
+                homepage: Homepage::Search,
+                toast_duration: 4,
+                toast_position: ToastPosition::BottomRight,
             },
         }
     }
@@ -576,11 +1475,40 @@ struct UserConfig {
 #[derive(Deserialize)]
 struct UserPageConfig {
     toc: Option<UserTocConfig>,
+    media: Option<UserMediaConfig>,
+    images: Option<UserImagesConfig>,
     padding: Option<PaddingConfig>,
 
+    max_width: Option<u16>,
+    justify: Option<bool>,
+    hide: Option<ZenModeComponents>,
+    description_indent: Option<u8>,
+    kinds: Option<HashMap<PageKind, UserDisplayOverrides>>,
+
+    start_at_lead: Option<bool>,
+    renderer: Option<String>,
+    history_depth: Option<usize>,
+    status_bar: Option<String>,
+    link_selection: Option<bool>,
+    words_per_minute: Option<u32>,
+    cjk_characters_per_minute: Option<u32>,
+    lookup: Option<LookupMode>,
+    closing_punctuation: Option<String>,
+    hyphenation: Option<bool>,
+    right_to_left: Option<bool>,
+    block_spacing: Option<BlockSpacing>,
+
     zen_mode: Option<UserZenModeConfig>,
 }
 
+#[derive(Deserialize)]
+struct UserDisplayOverrides {
+    max_width: Option<u16>,
+    justify: Option<bool>,
+    hide: Option<ZenModeComponents>,
+    description_indent: Option<u8>,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "lowercase")]
 enum UserConstraint {
@@ -604,6 +1532,20 @@ impl Into<Constraint> for UserConstraint {
     }
 }
 
+#[derive(Deserialize)]
+struct UserMediaConfig {
+    enabled: Option<bool>,
+    max_size: Option<u32>,
+    viewer: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UserImagesConfig {
+    enabled: Option<bool>,
+    protocol: Option<ImageProtocol>,
+    rows: Option<u16>,
+}
+
 #[derive(Deserialize)]
 struct UserZenModeConfig {
     default: Option<bool>,
@@ -620,6 +1562,7 @@ struct UserTocConfig {
     position: Option<TocConfigPosition>,
     title: Option<TocConfigTitle>,
     item_format: Option<String>,
+    introduction: Option<String>,
 
     enable_scrolling: Option<bool>,
 }
@@ -694,8 +1637,8 @@ impl Into<KeyCode> for UserKeyCode {
                 UserKeyCodeInner::F8 => KeyCode::F(8),
                 UserKeyCodeInner::F9 => KeyCode::F(9),
                 UserKeyCodeInner::F10 => KeyCode::F(10),
-                UserKeyCodeInner::F11 => KeyCode::F(12),
-                UserKeyCodeInner::F12 => KeyCode::F(13),
+                UserKeyCodeInner::F11 => KeyCode::F(11),
+                UserKeyCodeInner::F12 => KeyCode::F(12),
             },
         }
     }
@@ -708,6 +1651,7 @@ enum UserBinding {
     Binding {
         code: UserKeyCode,
         modifiers: Option<KeyModifiers>,
+        prefix: Option<char>,
     },
 }
 
@@ -718,11 +1662,17 @@ impl Into<Binding> for UserBinding {
             UserBinding::CodeOnly(code) => UserBinding::Binding {
                 code,
                 modifiers: None,
+                prefix: None,
             }
             .into(),
-            UserBinding::Binding { code, modifiers } => Binding {
+            UserBinding::Binding {
+                code,
+                modifiers,
+                prefix,
+            } => Binding {
                 code: code.into(),
                 modifiers: modifiers.unwrap_or(KeyModifiers::empty()),
+                prefix,
             },
         }
     }
@@ -749,56 +1699,139 @@ impl Into<Keybinding> for UserKeybinding {
     }
 }
 
-macro_rules! user_keybindings {
-    ($name:ident, $($binding:ident),+) => {
-        #[derive(Deserialize)]
-        struct $name {
-            $($binding: Option<UserKeybinding>,)+
+macro_rules! keybinding_names {
+    ($name:ident, $($binding:ident: $description:literal),+) => {
+        impl $name {
+            fn get_mut(&mut self, name: &str) -> Option<&mut Keybinding> {
+                match name {
+                    $(stringify!($binding) => Some(&mut self.$binding),)+
+                    _ => None,
+                }
+            }
+
+            /// Returns the names and descriptions of all actions with their effective keybindings
+            pub fn keymap(&self) -> Vec<(&'static str, &'static str, &Keybinding)> {
+                vec![$((stringify!($binding), $description, &self.$binding),)+]
+            }
         }
     };
 }
 
-user_keybindings!(
-    UserGlobalKeybindings,
-    scroll_down,
-    scroll_up,
-    scroll_to_top,
-    scroll_to_bottom,
-    pop_popup,
-    half_down,
-    half_up,
-    unselect_scroll,
-    submit,
-    quit,
-    enter_search_bar,
-    exit_search_bar,
-    switch_context_search,
-    switch_context_page,
-    toggle_search_language_selection,
-    toggle_logger
+keybinding_names!(
+    GlobalKeybindings,
+    scroll_down: "Scroll down",
+    scroll_up: "Scroll up",
+    scroll_to_top: "Scroll to the top",
+    scroll_to_bottom: "Scroll to the bottom",
+    pop_popup: "Close the popup",
+    half_down: "Scroll half a page down",
+    half_up: "Scroll half a page up",
+    unselect_scroll: "Unselect the current selection",
+    submit: "Submit or open the selection",
+    submit_lucky: "Search and open the first result (in the search bar)",
+    quit: "Quit",
+    enter_search_bar: "Focus the search bar",
+    exit_search_bar: "Leave the search bar",
+    switch_context_search: "Switch to the search results",
+    switch_context_page: "Switch to the page",
+    toggle_search_language_selection: "Change the search language",
+    toggle_site_selection: "Switch to another wiki",
+    toggle_offline: "Toggle the offline mode",
+    suspend: "Suspend to the shell, continue with 'fg'",
+    toggle_logger: "Show or focus the log viewer, hides the focussed log viewer",
+    toggle_messages: "Show the recent messages",
+    toggle_color_mode: "Cycle through the color modes",
+    cycle_theme: "Switch to the next theme",
+    toggle_help: "Toggle this help",
+    command_palette: "Run an action by its name",
+    history_prev: "Recall the previous query (in search prompts)",
+    history_next: "Recall the next query (in search prompts)",
+    load_random_page: "Open a random article",
+    show_request_stats: "Show the number of requests sent, throttled and failed",
+    retry_load: "Retry the failed load of the shown error"
+);
+
+keybinding_names!(
+    SearchKeybindings,
+    continue_search: "Load more search results or retry loading them",
+    toggle_filters: "Filter the namespaces and language of the searches"
+);
+
+keybinding_names!(
+    PageKeybindings,
+    pop_page: "Close the page",
+    history_back: "Go back to the previous page",
+    history_forward: "Go forward to the next page",
+    jump_to_header: "Jump to the selected header",
+    select_first_link: "Select the first link",
+    select_last_link: "Select the last link",
+    select_prev_link: "Select the previous link",
+    select_next_link: "Select the next link",
+    open_link: "Open the selected link",
+    jump_back: "Return to the position before jumping to a reference",
+    select_reference_link: "Select the next link of the reference",
+    toggle_page_language_selection: "Change the page language",
+    toggle_zen_mode: "Toggle the zen mode",
+    toggle_toc: "Focus the table of contents",
+    add_highlight: "Highlight the selection",
+    toggle_highlights: "Show the highlights",
+    show_disambiguation: "Show the meanings of the disambiguation page",
+    toggle_visited_pages: "Show the visited pages",
+    add_bookmark: "Bookmark the page",
+    toggle_bookmarks: "Show the bookmarks",
+    read_link_later: "Read the selected link later",
+    read_page_later: "Read the page later",
+    toggle_reading_list: "Show the reading list",
+    watch_page: "Watch the page for changes, or stop watching it",
+    toggle_watched_pages: "Show the watched pages",
+    export_page: "Export the page to a file",
+    dump_render: "Write the rendered page as json for bug reports",
+    copy_url: "Copy the url of the page",
+    copy_section_url: "Copy the url of the current section",
+    open_in_browser: "Open the page in the web browser",
+    show_page_info: "Show the information about the page",
+    show_revisions: "Show the revisions of the page",
+    show_backlinks: "Show the pages linking to the page",
+    switch_namespace: "Switch between the page and its talk page",
+    refresh_page: "Fetch the page again, ignoring the cached page",
+    view_source: "Toggle the wikitext source of the page",
+    toggle_source_wrap: "Toggle wrapping the lines of the source",
+    visual_mode: "Select lines for copying them",
+    copy_selection: "Copy the selected lines",
+    toggle_scrollbar: "Toggle the scrollbar of the page",
+    move_divider_left: "Move the divider of the table of contents to the left",
+    move_divider_right: "Move the divider of the table of contents to the right",
+    scroll_left: "Scroll the lines wider than the page to the left",
+    scroll_right: "Scroll the lines wider than the page to the right",
+    lookup_word: "Search for the words of the selected link",
+    search_other_site: "Search the selected link or section on another site",
+    toggle_fold: "Fold or unfold the current section",
+    fold_all: "Fold every section",
+    unfold_all: "Unfold every section",
+    copy_section: "Copy the text of the current section",
+    go_to_references: "Jump to the references section",
+    go_to_external_links: "Jump to the external links section",
+    switch_renderer: "Switch to the next renderer",
+    search: "Search in the page",
+    next_match: "Jump to the next match",
+    prev_match: "Jump to the previous match",
+    toggle_search_regex: "Toggle regex matching (in the search prompt)",
+    toggle_search_whole_word: "Toggle whole-word matching (in the search prompt)"
 );
 
-user_keybindings!(UserSearchKeybindings, continue_search);
-
-user_keybindings!(
-    UserPageKeybindings,
-    pop_page,
-    jump_to_header,
-    select_first_link,
-    select_last_link,
-    select_prev_link,
-    select_next_link,
-    open_link,
-    toggle_page_language_selection,
-    toggle_zen_mode,
-    toggle_toc
+keybinding_names!(
+    LoggerKeybindings,
+    filter_log: "Filter the records by their text",
+    cycle_log_level: "Cycle through the shown log levels",
+    copy_log: "Copy the shown records"
 );
 
 #[derive(Deserialize)]
 struct UserKeybindingsConfig {
-    global: Option<UserGlobalKeybindings>,
-    search: Option<UserSearchKeybindings>,
-    page: Option<UserPageKeybindings>,
+    global: Option<HashMap<String, toml::Spanned<toml::Value>>>,
+    search: Option<HashMap<String, toml::Spanned<toml::Value>>>,
+    page: Option<HashMap<String, toml::Spanned<toml::Value>>>,
+    logger: Option<HashMap<String, toml::Spanned<toml::Value>>>,
 }
 
 #[derive(Deserialize)]
@@ -806,6 +1839,8 @@ struct UserApiConfig {
     pre_language: Option<String>,
     language: Option<Language>,
     post_language: Option<String>,
+    sites: Option<Vec<UserSite>>,
+    lookup_site: Option<String>,
 
     search_limit: Option<usize>,
     search_qiprofile: Option<search::QiProfile>,
@@ -813,21 +1848,71 @@ struct UserApiConfig {
     search_info: Option<search::Info>,
     search_rewrites: Option<bool>,
     search_sort_order: Option<search::SortOrder>,
+    search_namespaces: Option<Vec<String>>,
+    suggestion_limit: Option<usize>,
 
     page_redirects: Option<bool>,
+    cache_size: Option<u64>,
+    cache_max_age: Option<u64>,
+    prefetch: Option<bool>,
+    prefetch_visible: Option<usize>,
+    retry_attempts: Option<u32>,
+    retry_delay: Option<u64>,
+    proxy: Option<String>,
+    ca_certificate: Option<PathBuf>,
+    rate_limit: Option<f64>,
+    rate_burst: Option<u32>,
+    user_agent: Option<String>,
+    headers: Option<BTreeMap<String, String>>,
+    query_params: Option<BTreeMap<String, String>>,
+    watch_interval: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct UserSite {
+    name: String,
+    url: String,
+    language: Option<Language>,
 }
 
 #[derive(Deserialize, Debug)]
 struct UserUiConfig {
     popup_search_language_changed: Option<bool>,
     popup_page_language_changed: Option<bool>,
+    color_mode: Option<ColorMode>,
+    history_size: Option<usize>,
+    compact_search_results: Option<bool>,
+    persist_visited_pages: Option<bool>,
+    open_in_browser: Option<bool>,
+    restore_session: Option<bool>,
+    homepage: Option<String>,
+    toast_duration: Option<u64>,
+    toast_position: Option<ToastPosition>,
 }
 
-pub fn load_theme() -> Result<Theme> {
+/// Loads all themes, the first theme is the default one
+///
+/// Named themes are defined in the `themes` table of the theme config and are based on the
+/// default theme
+pub fn load_themes() -> Result<Vec<Theme>> {
+    let user_themes = load_user_themes().context("failed loading the user themes")?;
+
     let mut default_theme = Theme::default();
-    let user_theme = load_user_theme().context("failed loading the user theme")?;
+    override_theme(&mut default_theme, user_themes.base);
+
+    let mut themes = vec![default_theme.clone()];
+    for (name, user_theme) in user_themes.named {
+        let mut theme = default_theme.clone();
+        theme.name = name;
+        override_theme(&mut theme, user_theme);
+        themes.push(theme);
+    }
+
+    Ok(themes)
+}
 
-    override_options!(default_theme, user_theme::{
+fn override_theme(theme: &mut Theme, user_theme: UserTheme) {
+    override_options!(theme, user_theme::{
         bg,
         fg,
 
@@ -848,17 +1933,49 @@ pub fn load_theme() -> Result<Theme> {
 
         scrollbar_track_fg,
         scrollbar_thumb_fg,
+        scrollbar_track_char,
+        scrollbar_thumb_char,
 
         search_title_fg,
 
         status_bar_fg,
-        status_bar_bg
+        status_bar_bg,
+
+        highlight_bg,
+
+        horizontal_rule_char,
+        horizontal_rule_fg,
+
+        page_title_fg,
+        page_description_fg,
+        header_fg,
+        italic_fg,
+        reflink_fg,
+
+        wiki_link_fg,
+        external_link_fg,
+        red_link_fg,
+        media_link_fg,
+        selected_link_fg,
+        hovered_link_bg,
+
+        unsupported_fg,
+
+        contents_fg,
+        contents_border_fg,
+        contents_border_focused_fg,
+        contents_highlight_fg,
+        contents_highlight_bg,
+        contents_highlight_modifier,
+        contents_highlight_symbol,
+
+        search_match_fg,
+        search_match_bg,
+        search_current_bg
     });
-
-    Ok(default_theme)
 }
 
-fn load_user_theme() -> Result<UserTheme> {
+fn load_user_themes() -> Result<UserThemes> {
     let path = config_dir()
         .context("failed retrieving the config dir")?
         .join(THEME_FILE_NAME);
@@ -870,116 +1987,94 @@ fn load_user_theme() -> Result<UserTheme> {
     let user_theme_str =
         std::fs::read_to_string(&path).context("failed reading the theme config file")?;
 
-    toml::from_str::<UserTheme>(&user_theme_str).context("failed parsing the user theme")
-}
-
-#[derive(Clone)]
-pub struct Theme {
-    pub bg: Color,
-    pub fg: Color,
-
-    pub title: Color,
-
-    pub selected_bg: Color,
-    pub selected_fg: Color,
-
-    pub inactive_fg: Color,
-    pub highlight_fg: Color,
-
-    pub border_fg: Color,
-    pub border_bg: Color,
-    pub border_type: ThemeBorderType,
-
-    pub border_highlight_fg: Color,
-    pub border_highlight_bg: Color,
-
-    pub scrollbar_track_fg: Color,
-    pub scrollbar_thumb_fg: Color,
-
-    pub search_title_fg: Color,
+    let (user_themes, invalid_keys) = parse_user_themes(&user_theme_str)?;
+    if !invalid_keys.is_empty() {
+        warn!(
+            "ignoring invalid values in the theme for: {}",
+            invalid_keys.join(", ")
+        );
+    }
 
-    pub status_bar_fg: Color,
-    pub status_bar_bg: Color,
+    Ok(user_themes)
 }
 
-impl Theme {
-    pub fn new() -> Self {
-        Theme {
-            bg: Color::Reset,
-            fg: Color::Reset,
-
-            title: Color::White,
-
-            selected_bg: Color::DarkGray,
-            selected_fg: Color::Reset,
-
-            inactive_fg: Color::Blue,
-            highlight_fg: Color::White,
-
-            border_fg: Color::White,
-            border_bg: Color::Reset,
-            border_type: ThemeBorderType::Rounded,
-
-            border_highlight_fg: Color::Yellow,
-            border_highlight_bg: Color::Reset,
-
-            scrollbar_track_fg: Color::Black,
-            scrollbar_thumb_fg: Color::Blue,
+/// Parses the user themes, skipping every key with an invalid value (for example an unknown
+/// color name) instead of failing. Returns the themes and the skipped keys
+fn parse_user_themes(user_theme_str: &str) -> Result<(UserThemes, Vec<String>)> {
+    let mut table =
+        toml::from_str::<toml::Table>(user_theme_str).context("failed parsing the user theme")?;
+    let mut invalid_keys = Vec::new();
+
+    let named_tables = match table.remove("themes") {
+        Some(toml::Value::Table(named_tables)) => named_tables,
+        Some(_) => {
+            invalid_keys.push("themes".to_string());
+            toml::Table::new()
+        }
+        None => toml::Table::new(),
+    };
 
-            search_title_fg: Color::Red,
+    let base = parse_user_theme(table, "", &mut invalid_keys)?;
 
-            status_bar_fg: Color::Reset,
-            status_bar_bg: Color::DarkGray,
+    let mut named = Vec::new();
+    for (name, value) in named_tables {
+        match value {
+            toml::Value::Table(table) => {
+                let prefix = format!("themes.{name}.");
+                named.push((name, parse_user_theme(table, &prefix, &mut invalid_keys)?));
+            }
+            _ => invalid_keys.push(format!("themes.{name}")),
         }
     }
 
-    /// Returns a Paragraph with the background and foreground colors set
-    pub fn default_paragraph<'a, T>(&self, text: T) -> ratatui::widgets::Paragraph<'a>
-    where
-        T: Into<ratatui::text::Text<'a>>,
-    {
-        ratatui::widgets::Paragraph::new(text).style(Style::default().bg(self.bg).fg(self.fg))
-    }
-
-    /// Returns a Block with Borders::ALL, BorderType::Rounded and bg and fg colors set
-    pub fn default_block(&self) -> ratatui::widgets::Block {
-        ratatui::widgets::Block::default()
-            .borders(ratatui::widgets::Borders::ALL)
-            .border_type(self.border_type.clone().into())
-            .border_style(Style::default().fg(self.border_fg).bg(self.border_bg))
-            .title_style(Style::default().fg(self.title))
-    }
+    invalid_keys.sort();
+    Ok((UserThemes { base, named }, invalid_keys))
 }
 
-impl Default for Theme {
-    fn default() -> Self {
-        Theme::new()
+/// Parses a single user theme, removing invalid keys and adding them (with the prefix) to
+/// `invalid_keys`
+fn parse_user_theme(
+    mut table: toml::Table,
+    prefix: &str,
+    invalid_keys: &mut Vec<String>,
+) -> Result<UserTheme> {
+    let invalid: Vec<String> = table
+        .iter()
+        .filter(|(key, value)| {
+            let mut single = toml::Table::new();
+            single.insert(key.to_string(), (*value).clone());
+            single.try_into::<UserTheme>().is_err()
+        })
+        .map(|(key, _)| key.to_string())
+        .collect();
+
+    for key in invalid {
+        table.remove(&key);
+        invalid_keys.push(format!("{prefix}{key}"));
     }
+
+    table
+        .try_into::<UserTheme>()
+        .context("failed parsing the user theme")
 }
 
-#[derive(Deserialize, Clone)]
-pub enum ThemeBorderType {
-    Plain,
-    Rounded,
-    Double,
-    Thick,
-    QuadrantInside,
-    QuadrantOutside,
+/// The theme that was selected the last time, persisted between sessions
+#[derive(Serialize, Deserialize, Default)]
+pub struct ThemeState {
+    pub active: Option<String>,
 }
 
-impl From<ThemeBorderType> for BorderType {
-    fn from(val: ThemeBorderType) -> Self {
-        match val {
-            ThemeBorderType::Plain => BorderType::Plain,
-            ThemeBorderType::Rounded => BorderType::Rounded,
-            ThemeBorderType::Double => BorderType::Double,
-            ThemeBorderType::Thick => BorderType::Thick,
-            ThemeBorderType::QuadrantInside => BorderType::QuadrantInside,
-            ThemeBorderType::QuadrantOutside => BorderType::QuadrantOutside,
-        }
+impl ThemeState {
+    pub fn open() -> Result<Store<ThemeState>> {
+        Store::open(THEME_STORE)
     }
 }
 
+struct UserThemes {
+    base: UserTheme,
+    named: Vec<(String, UserTheme)>,
+}
+
 #[derive(Deserialize)]
 struct UserTheme {
     bg: Option<Color>,
@@ -1002,9 +2097,380 @@ struct UserTheme {
 
     scrollbar_track_fg: Option<Color>,
     scrollbar_thumb_fg: Option<Color>,
+    scrollbar_track_char: Option<char>,
+    scrollbar_thumb_char: Option<char>,
 
     search_title_fg: Option<Color>,
 
     status_bar_fg: Option<Color>,
     status_bar_bg: Option<Color>,
+
+    highlight_bg: Option<Color>,
+
+    horizontal_rule_char: Option<char>,
+    horizontal_rule_fg: Option<Color>,
+
+    page_title_fg: Option<Color>,
+    page_description_fg: Option<Color>,
+    header_fg: Option<Color>,
+    italic_fg: Option<Color>,
+    reflink_fg: Option<Color>,
+
+    wiki_link_fg: Option<Color>,
+    external_link_fg: Option<Color>,
+    red_link_fg: Option<Color>,
+    media_link_fg: Option<Color>,
+    selected_link_fg: Option<Color>,
+    hovered_link_bg: Option<Color>,
+
+    unsupported_fg: Option<Color>,
+
+    contents_fg: Option<Color>,
+    contents_border_fg: Option<Color>,
+    contents_border_focused_fg: Option<Color>,
+    contents_highlight_fg: Option<Color>,
+    contents_highlight_bg: Option<Color>,
+    contents_highlight_modifier: Option<Modifier>,
+    contents_highlight_symbol: Option<String>,
+
+    search_match_fg: Option<Color>,
+    search_match_bg: Option<Color>,
+    search_current_bg: Option<Color>,
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        style::{Color, Modifier},
+        widgets::Padding,
+    };
+    use wiki_api::{languages::Language, page::PageKind, Endpoint};
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{
+        override_bindings_config, override_page_config, override_theme, parse_user_themes, Config,
+        DisplayOptions, Homepage, Keybinding, Site, Theme, UserConfig, UserPageConfig,
+        ZenModeComponents, MAX_PADDING,
+    };
+
+    #[test]
+    fn test_resolve_article_url() {
+        let mut config = Config::default();
+        config.api.sites.push(
+            Site::new(
+                "Minecraft Wiki",
+                "https://minecraft.fandom.com/api.php".to_string(),
+                Some(Language::German),
+            )
+            .unwrap(),
+        );
+        let url = |url: &str| Endpoint::parse(url).unwrap();
+
+        let (site, article) = config
+            .api
+            .resolve_article_url(&url("https://fr.m.wikipedia.org/wiki/Rouille#Histoire"))
+            .unwrap();
+        assert_eq!(site.name, "Wikipedia");
+        assert_eq!(article.title, "Rouille");
+        assert_eq!(article.language, Language::French);
+        assert_eq!(article.anchor.as_deref(), Some("Histoire"));
+        assert_eq!(article.endpoint, url("https://fr.wikipedia.org/w/api.php"));
+
+        let (site, article) = config
+            .api
+            .resolve_article_url(&url("https://minecraft.fandom.com/wiki/Creeper"))
+            .unwrap();
+        assert_eq!(site.name, "Minecraft Wiki");
+        assert_eq!(article.language, Language::German);
+        assert_eq!(
+            article.endpoint,
+            url("https://minecraft.fandom.com/api.php")
+        );
+
+        assert!(config
+            .api
+            .resolve_article_url(&url("https://example.org/wiki/Rust"))
+            .is_none());
+        assert!(config
+            .api
+            .site_of(&url("https://www.wikipedia.org/w/api.php"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_homepage_from_string() {
+        assert_eq!(Homepage::from("search".to_string()), Homepage::Search);
+        assert_eq!(Homepage::from("random".to_string()), Homepage::Random);
+        assert_eq!(Homepage::from("featured".to_string()), Homepage::Featured);
+        assert_eq!(
+            Homepage::from(" Coral reef ".to_string()),
+            Homepage::Article("Coral reef".to_string())
+        );
+    }
+
+    #[test]
+    fn test_default_display_options() {
+        let config = Config::default();
+        let article = config.page.display_options(PageKind::Article);
+        assert_eq!(article, config.page.display);
+
+        for kind in [PageKind::Disambiguation, PageKind::Category] {
+            let options = config.page.display_options(kind);
+            assert_eq!(options.max_width, 0);
+            assert!(!options.justify);
+        }
+
+        // talk pages indent the replies of discussions further
+        let talk = config.page.display_options(PageKind::Talk);
+        assert_eq!(talk.description_indent, 4);
+        assert_eq!(
+            config
+                .page
+                .render_options(PageKind::Talk, Language::English)
+                .description_indent,
+            4
+        );
+
+        assert_eq!(
+            config.page.display_options(PageKind::Special),
+            config.page.display
+        );
+    }
+
+    #[test]
+    fn test_user_display_options() {
+        let mut config = Config::default();
+        let user_config: UserPageConfig = toml::from_str(
+            r#"
+            max_width = 100
+            justify = true
+
+            [kinds.talk]
+            hide = "TOC"
+            description_indent = 6
+
+            [kinds.disambiguation]
+            justify = true
+            "#,
+        )
+        .unwrap();
+        override_page_config(&mut config.page, user_config);
+
+        assert_eq!(
+            config.page.display_options(PageKind::Article),
+            DisplayOptions {
+                max_width: 100,
+                justify: true,
+                hidden: ZenModeComponents::empty(),
+                description_indent: 2,
+            }
+        );
+        assert_eq!(
+            config.page.display_options(PageKind::Talk),
+            DisplayOptions {
+                max_width: 100,
+                justify: true,
+                hidden: ZenModeComponents::TOC,
+                description_indent: 6,
+            }
+        );
+        // the user override only replaces the justification, the default width override remains
+        assert_eq!(
+            config.page.display_options(PageKind::Disambiguation),
+            DisplayOptions {
+                max_width: 0,
+                justify: true,
+                hidden: ZenModeComponents::empty(),
+                description_indent: 2,
+            }
+        );
+        assert_eq!(
+            config.page.display_options(PageKind::Category),
+            DisplayOptions {
+                max_width: 0,
+                justify: false,
+                hidden: ZenModeComponents::empty(),
+                description_indent: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_user_padding() {
+        let mut config = Config::default();
+        let user_config: UserPageConfig = toml::from_str("padding = { vertical = 2 }").unwrap();
+        override_page_config(&mut config.page, user_config);
+        assert_eq!(config.page.padding, Padding::vertical(2));
+
+        // every side is limited to the widest padding
+        let user_config: UserPageConfig = toml::from_str("padding = [1, 9, 0, 5]").unwrap();
+        override_page_config(&mut config.page, user_config);
+        assert_eq!(
+            config.page.padding,
+            Padding::new(1, MAX_PADDING, 0, MAX_PADDING)
+        );
+    }
+
+    #[test]
+    fn test_invalid_theme_colors() {
+        let (user_themes, invalid_keys) = parse_user_themes(
+            r#"
+            header_fg = "green"
+            wiki_link_fg = "notacolor"
+            reflink_fg = 42
+
+            [themes.light]
+            header_fg = "invalid"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            invalid_keys,
+            ["reflink_fg", "themes.light.header_fg", "wiki_link_fg"]
+        );
+        assert_eq!(user_themes.base.header_fg, Some(Color::Green));
+        assert_eq!(user_themes.base.wiki_link_fg, None);
+        assert_eq!(user_themes.base.reflink_fg, None);
+        assert_eq!(user_themes.named[0].1.header_fg, None);
+    }
+
+    #[test]
+    fn test_named_themes() {
+        let (user_themes, invalid_keys) = parse_user_themes(
+            r#"
+            reflink_fg = "gray"
+
+            [themes.light]
+            reflink_fg = "black"
+            contents_highlight_bg = "white"
+            contents_highlight_modifier = "BOLD | UNDERLINED"
+
+            [themes.dark]
+            contents_highlight_modifier = ""
+            "#,
+        )
+        .unwrap();
+        assert!(invalid_keys.is_empty());
+
+        let names: Vec<&str> = user_themes
+            .named
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["dark", "light"]);
+
+        let mut named = user_themes.named.into_iter();
+        let mut dark = Theme::default();
+        override_theme(&mut dark, named.next().unwrap().1);
+        assert_eq!(dark.contents_highlight_modifier, Modifier::empty());
+        let mut light = Theme::default();
+        override_theme(&mut light, named.next().unwrap().1);
+        assert_eq!(light.reflink_fg, Color::Black);
+        assert_eq!(light.contents_highlight_bg, Color::White);
+        assert_eq!(
+            light.contents_highlight_modifier,
+            Modifier::BOLD | Modifier::UNDERLINED
+        );
+    }
+
+    #[test]
+    fn test_user_keybindings() {
+        let source = r#"
+[bindings.global]
+toggle_logger = "f8"
+
+[bindings.page]
+open_link = { code = "o" }
+select_next_link = "z"
+toggle_toc = "notakey"
+unknown_action = "x"
+toggle_zen_mode = "z"
+"#;
+        let user_config: UserConfig = toml::from_str(source).unwrap();
+        let mut config = Config::default();
+        let diagnostics =
+            override_bindings_config(&mut config.bindings, user_config.bindings.unwrap(), source);
+
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert!(config
+            .bindings
+            .global
+            .toggle_logger
+            .matches_event(key(KeyCode::F(8))));
+        assert!(config
+            .bindings
+            .page
+            .open_link
+            .matches_event(key(KeyCode::Char('o'))));
+        assert!(!config
+            .bindings
+            .page
+            .open_link
+            .matches_event(key(KeyCode::Enter)));
+        // the invalid binding keeps its default
+        assert!(config
+            .bindings
+            .page
+            .toggle_toc
+            .matches_event(key(KeyCode::Tab)));
+
+        assert_eq!(diagnostics.len(), 3);
+        assert!(
+            diagnostics[0].starts_with("line 8: invalid keybinding for 'bindings.page.toggle_toc'")
+        );
+        assert_eq!(
+            diagnostics[1],
+            "line 9: unknown action 'bindings.page.unknown_action'"
+        );
+        assert_eq!(
+            diagnostics[2],
+            "line 10: 'bindings.page.toggle_zen_mode' conflicts with 'bindings.page.select_next_link'"
+        );
+    }
+
+    #[test]
+    fn test_user_sequence_keybindings() {
+        let source = r#"
+[bindings.page]
+toggle_fold = [ { prefix = "z", code = "c" }, { prefix = "Z", code = "a" } ]
+fold_all = "M"
+unfold_all = { prefix = "y", code = "R", modifiers = "SHIFT" }
+"#;
+        let user_config: UserConfig = toml::from_str(source).unwrap();
+        let mut config = Config::default();
+        let diagnostics =
+            override_bindings_config(&mut config.bindings, user_config.bindings.unwrap(), source);
+        assert!(diagnostics.is_empty());
+
+        let page = &config.bindings.page;
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        assert!(page.toggle_fold.matches_sequence('z', key('c')));
+        assert!(page.toggle_fold.matches_sequence('Z', key('a')));
+        assert!(!page.toggle_fold.matches_sequence('z', key('a')));
+        // a key of a sequence doesn't match without its prefix
+        assert!(!page.toggle_fold.matches_event(key('c')));
+        assert!(page
+            .unfold_all
+            .matches_sequence('y', KeyEvent::new(KeyCode::Char('R'), KeyModifiers::SHIFT)));
+        assert_eq!(page.sequence_prefixes(), ['Z', 'g', 'y', 'z']);
+        assert_eq!(page.toggle_fold.to_string(), "zc / Za");
+    }
+
+    #[test]
+    fn test_keybinding_display() {
+        let bindings = Config::default().bindings;
+        assert_eq!(bindings.global.scroll_to_bottom.to_string(), "G / End");
+        assert_eq!(bindings.global.half_down.to_string(), "Ctrl+d / PageDown");
+        assert_eq!(bindings.global.toggle_help.to_string(), "?");
+        assert_eq!(bindings.page.fold_all.to_string(), "zM");
+        assert_eq!(
+            Keybinding::new()
+                .binding(KeyCode::BackTab, KeyModifiers::SHIFT)
+                .binding(KeyCode::Right, KeyModifiers::ALT)
+                .to_string(),
+            "Shift+Tab / Alt+→"
+        );
+    }
 }
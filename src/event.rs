@@ -1,15 +1,28 @@
-use crossterm::event::{Event as CrosstermEvent, KeyEvent, KeyEventKind};
+use std::time::Duration;
+
+use crossterm::event::{
+    Event as CrosstermEvent, KeyEvent, KeyEventKind, MouseEvent, MouseEventKind,
+};
 use futures::FutureExt;
-use tokio::{sync::mpsc, task::JoinHandle};
+use tokio::{
+    sync::mpsc,
+    task::JoinHandle,
+    time::{Instant, MissedTickBehavior},
+};
 use tokio_stream::StreamExt;
 use tokio_util::sync::CancellationToken;
 use tracing::warn;
 
+/// Resizes closer together are coalesced, so that only the final size is rendered while the
+/// window is dragged
+const RESIZE_DELAY: Duration = Duration::from_millis(75);
+
 #[derive(Clone, Copy, Debug)]
 pub enum Event {
     Quit,
     RenderTick,
     Key(KeyEvent),
+    Mouse(MouseEvent),
     Resize(u16, u16),
 }
 
@@ -32,6 +45,14 @@ impl EventHandler {
         let task = tokio::spawn(async move {
             let mut reader = crossterm::event::EventStream::new();
             let mut render_interval = tokio::time::interval(render_tick_rate);
+            render_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            // the newest size while resizing, sent when no resize followed within the delay
+            let mut pending_resize = None;
+            let resize_delay = tokio::time::sleep(RESIZE_DELAY);
+            tokio::pin!(resize_delay);
+            // the newest position of the pointer, sent before the next render tick so moving the
+            // pointer doesn't update the components for every cell it passes
+            let mut pending_motion = None;
 
             loop {
                 let render_delay = render_interval.tick();
@@ -43,8 +64,13 @@ impl EventHandler {
                             CrosstermEvent::Key(key) => if key.kind == KeyEventKind::Press {
                                 event_tx.send(Event::Key(key)).unwrap();
                             },
+                            CrosstermEvent::Mouse(mouse) => match mouse.kind {
+                                MouseEventKind::Moved => pending_motion = Some(mouse),
+                                _ => event_tx.send(Event::Mouse(mouse)).unwrap(),
+                            },
                             CrosstermEvent::Resize(x, y) => {
-                                event_tx.send(Event::Resize(x, y)).unwrap();
+                                pending_resize = Some((x, y));
+                                resize_delay.as_mut().reset(Instant::now() + RESIZE_DELAY);
                             },
                             _ => {}
                         }
@@ -53,7 +79,18 @@ impl EventHandler {
                         }
                         None => {}
                     },
-                    _ = render_delay => event_tx.send(Event::RenderTick).unwrap(),
+                    _ = &mut resize_delay, if pending_resize.is_some() => {
+                        if let Some((x, y)) = pending_resize.take() {
+                            event_tx.send(Event::Resize(x, y)).unwrap();
+                        }
+                    }
+                    // nothing is rendered while resizing, the intermediate sizes are skipped
+                    _ = render_delay, if pending_resize.is_none() => {
+                        if let Some(mouse) = pending_motion.take() {
+                            event_tx.send(Event::Mouse(mouse)).unwrap();
+                        }
+                        event_tx.send(Event::RenderTick).unwrap()
+                    }
                 }
             }
         });
@@ -0,0 +1,838 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use wiki_api::{
+    document::{Data, Document, Node},
+    page::{article_url, Link, Page},
+};
+
+use crate::{
+    config::{data_dir, Theme},
+    highlights::Highlight,
+    renderer::{dump::RenderDump, PageRenderer},
+};
+
+/// Directory in the data directory the render dumps are written to
+const DUMP_DIR: &str = "render-dumps";
+
+/// The stylesheet of an html export, following the light or dark mode of the browser
+const HTML_STYLE: &str = "\
+:root { color-scheme: light dark; }
+body { max-width: 48rem; margin: 2rem auto; padding: 0 1rem; font-family: sans-serif; line-height: 1.6; }
+a { color: #3366cc; }
+blockquote { margin-left: 0; padding-left: 1rem; border-left: 3px solid #a2a9b1; }
+.hatnote { font-style: italic; }
+footer { margin-top: 2rem; font-size: 0.9em; }
+@media (prefers-color-scheme: dark) {
+  a { color: #88a3e8; }
+}
+";
+
+/// The format a page is exported in, chosen by the extension of the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Text,
+    Html,
+    /// The lines as they are displayed, styled with ansi escape codes
+    Ansi,
+}
+
+impl ExportFormat {
+    /// Returns the format for the file. Files ending in `.txt` are exported as plain text, files
+    /// ending in `.html` as a web page, files ending in `.ans` as text with ansi escape codes and
+    /// all others as markdown
+    pub fn from_path(path: &Path) -> ExportFormat {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("txt") => ExportFormat::Text,
+            Some(extension)
+                if extension.eq_ignore_ascii_case("html")
+                    || extension.eq_ignore_ascii_case("htm") =>
+            {
+                ExportFormat::Html
+            }
+            Some(extension) if extension.eq_ignore_ascii_case("ans") => ExportFormat::Ansi,
+            _ => ExportFormat::Markdown,
+        }
+    }
+
+    /// Whether the lines of the format are as wide as the page is rendered
+    pub fn has_width(self) -> bool {
+        self == ExportFormat::Ansi
+    }
+}
+
+/// Writes the render dump to a file in the data directory named after the title and the width,
+/// replacing an older dump of the page. Returns the path of the file
+pub fn write_render_dump(dump: &RenderDump) -> Result<PathBuf> {
+    let directory = data_dir()?.join(DUMP_DIR);
+    std::fs::create_dir_all(&directory)
+        .with_context(|| format!("failed creating '{}'", directory.display()))?;
+
+    let path = directory.join(format!("{}.{}.json", title_slug(&dump.title), dump.width));
+    let json = serde_json::to_string_pretty(dump).context("failed serializing the dump")?;
+    std::fs::write(&path, json)
+        .with_context(|| format!("failed writing the file '{}'", path.display()))?;
+    Ok(path)
+}
+
+/// Returns the default file name of the exported page, the slugified title of the page
+pub fn export_file_name(title: &str) -> String {
+    format!("{}.md", title_slug(title))
+}
+
+/// Returns the title in lowercase with runs of other characters than letters and digits replaced
+/// by a dash, or `page` when nothing is left
+pub fn title_slug(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    match slug.is_empty() {
+        true => "page".to_string(),
+        false => slug.to_string(),
+    }
+}
+
+/// Writes the page to the file, in the format matching the extension of the file. Ansi text is
+/// rendered by the renderer at the width, in the colors of the theme. The highlights are only
+/// exported to markdown
+pub fn export_page(
+    page: &Page,
+    highlights: &[Highlight],
+    path: &Path,
+    renderer: &dyn PageRenderer,
+    theme: &Theme,
+    width: u16,
+) -> Result<()> {
+    let contents = match ExportFormat::from_path(path) {
+        ExportFormat::Markdown => page_to_markdown(page, highlights),
+        ExportFormat::Html => page_to_html(page),
+        ExportFormat::Text => page_to_text(page),
+        ExportFormat::Ansi => rendered_text(renderer, page, width, Some(theme)),
+    };
+    std::fs::write(path, contents)
+        .with_context(|| format!("failed writing the file '{}'", path.display()))
+}
+
+/// Renders the page at the width as text, styled with ansi escape codes in the colors of the
+/// theme when one is given
+pub fn rendered_text(
+    renderer: &dyn PageRenderer,
+    page: &Page,
+    width: u16,
+    theme: Option<&Theme>,
+) -> String {
+    let rendered = renderer.render(&page.content, width);
+    match theme {
+        Some(theme) => rendered.ansi_text(theme),
+        None => rendered.plain_text(),
+    }
+}
+
+/// Converts the page to markdown, starting with the title of the page. The highlights are quoted
+/// in a section after the content
+pub fn page_to_markdown(page: &Page, highlights: &[Highlight]) -> String {
+    let mut writer = MarkdownWriter::new(&page.content);
+    writer.out.push_str(&format!("# {}", page.title));
+    writer.end_block();
+    if let Some(root) = page.content.nth(0) {
+        writer.write_node(root);
+    }
+
+    if !highlights.is_empty() {
+        writer.end_block();
+        writer.out.push_str("## Highlights");
+        writer.end_block();
+        for highlight in highlights {
+            writer.out.push_str("> ");
+            writer.line_start = writer.out.len();
+            writer.write_text(&highlight.text);
+            writer.end_block();
+        }
+    }
+    writer.finish()
+}
+
+/// Converts the page to a standalone html document, starting with the title of the page and ending
+/// with a link to the article
+pub fn page_to_html(page: &Page) -> String {
+    let mut writer = HtmlWriter::default();
+    if let Some(root) = page.content.nth(0) {
+        writer.write_node(root);
+    }
+    let url = page.url(None).to_string();
+
+    format!(
+        "<!DOCTYPE html>\n\
+        <html lang=\"{language}\">\n\
+        <head>\n\
+        <meta charset=\"utf-8\">\n\
+        <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+        <title>{title}</title>\n\
+        <style>\n{HTML_STYLE}</style>\n\
+        </head>\n\
+        <body>\n\
+        <h1>{title}</h1>\n\
+        {content}\
+        <footer><a href=\"{url}\">{url}</a></footer>\n\
+        </body>\n\
+        </html>\n",
+        language = escape_html(page.language.code()),
+        title = escape_html(&page.title),
+        content = writer.out,
+        url = escape_html(&url),
+    )
+}
+
+/// Converts the page to plain text, starting with the title of the page
+pub fn page_to_text(page: &Page) -> String {
+    let text = nodes_to_text(page.content.nth(0));
+    format!("{}\n\n{text}\n", page.title)
+}
+
+/// Converts the nodes to plain text. Links are replaced by their text, reflinks are dropped and
+/// blocks like paragraphs are separated by empty lines
+pub fn nodes_to_text<'a>(nodes: impl IntoIterator<Item = Node<'a>>) -> String {
+    let mut writer = TextWriter::default();
+    for node in nodes {
+        writer.write_node(node);
+    }
+    writer.finish()
+}
+
+/// Returns the absolute url the link points to, or the anchor for links within the page. `None`
+/// for links without a target, like red links
+fn link_url(link: &Link) -> Option<String> {
+    match link {
+        Link::Internal(data) => Some(
+            article_url(
+                &data.endpoint,
+                &data.title,
+                data.anchor.as_ref().map(|anchor| anchor.anchor.as_str()),
+            )
+            .to_string(),
+        ),
+        Link::Anchor(data) => Some(format!("#{}", data.anchor)),
+        Link::External(data) => Some(data.url.to_string()),
+        Link::Interwiki(data) => Some(data.url.to_string()),
+        Link::MediaLink(data) => Some(data.url.to_string()),
+        Link::RedLink(_) | Link::ExternalToInternal(_) => None,
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escapes the markers that only have a meaning at the start of a line, like headers, quotes and
+/// the markers of list items
+fn escape_line_start(text: &str) -> String {
+    let trimmed = text.trim_start();
+    let indent = &text[..text.len() - trimmed.len()];
+    if trimmed.starts_with(['#', '>', '-', '+']) {
+        return format!("{indent}\\{trimmed}");
+    }
+
+    let digits = trimmed.len()
+        - trimmed
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    if digits > 0 && trimmed[digits..].starts_with(['.', ')']) {
+        return format!("{indent}{}\\{}", &trimmed[..digits], &trimmed[digits..]);
+    }
+    text.to_string()
+}
+
+/// Escapes the characters with a meaning in html, the text itself stays utf-8
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+struct MarkdownWriter<'a> {
+    document: &'a Document,
+    out: String,
+    /// The lists the writer is in, whether they are ordered and the number of their last item
+    lists: Vec<(bool, usize)>,
+    /// Where the text of the current line starts, after the markers of its block
+    line_start: usize,
+}
+
+impl<'a> MarkdownWriter<'a> {
+    fn new(document: &'a Document) -> Self {
+        Self {
+            document,
+            out: String::new(),
+            lists: Vec::new(),
+            line_start: 0,
+        }
+    }
+
+    /// Writes the text, escaping the markers that would start a block at the start of the line
+    fn write_text(&mut self, text: &str) {
+        let escaped = escape_markdown(text);
+        match self.out[self.line_start..].trim().is_empty() {
+            true => self.out.push_str(&escape_line_start(&escaped)),
+            false => self.out.push_str(&escaped),
+        }
+    }
+
+    /// Ends the current block with an empty line, or a line break inside of lists
+    fn end_block(&mut self) {
+        let trimmed_len = self.out.trim_end_matches([' ', '\n']).len();
+        self.out.truncate(trimmed_len);
+        if self.out.is_empty() {
+            return;
+        }
+        match self.lists.is_empty() {
+            true => self.out.push_str("\n\n"),
+            false => self.out.push('\n'),
+        }
+        self.line_start = self.out.len();
+    }
+
+    fn start_line(&mut self) {
+        let trimmed_len = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed_len);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+        self.line_start = self.out.len();
+    }
+
+    fn finish(mut self) -> String {
+        self.end_block();
+        let mut out = self.out.trim_end().to_string();
+        out.push('\n');
+        out
+    }
+
+    fn write_children(&mut self, node: Node<'a>) {
+        for child in node.children() {
+            self.write_node(child);
+        }
+    }
+
+    fn write_wrapped(&mut self, node: Node<'a>, marker: &str) {
+        self.out.push_str(marker);
+        self.write_children(node);
+        self.out.push_str(marker);
+    }
+
+    fn write_node(&mut self, node: Node<'a>) {
+        match node.data() {
+            Data::Header { kind, .. } => {
+                self.end_block();
+                self.out.push_str(&"#".repeat(kind.clone() as usize));
+                self.out.push(' ');
+                self.write_children(node);
+                self.end_block();
+            }
+            Data::Text { contents } => self.write_text(contents),
+
+            Data::Paragraph
+            | Data::Hatnote
+            | Data::RedirectMessage
+            | Data::Disambiguation
+            | Data::DescriptionList => {
+                self.end_block();
+                self.write_children(node);
+                self.end_block();
+            }
+            Data::Blockquote => {
+                self.end_block();
+                let mut quote = MarkdownWriter::new(self.document);
+                quote.write_children(node);
+                for line in quote.finish().lines() {
+                    self.out.push_str(format!("> {line}").trim_end());
+                    self.out.push('\n');
+                }
+                self.end_block();
+            }
+
+            Data::OrderedList | Data::UnorderedList => {
+                self.end_block();
+                self.lists
+                    .push((matches!(node.data(), Data::OrderedList), 0));
+                self.write_children(node);
+                self.lists.pop();
+                self.end_block();
+            }
+            Data::ListItem => {
+                self.start_line();
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut() {
+                    Some((true, number)) => {
+                        *number += 1;
+                        format!("{number}. ")
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.out.push_str(&"  ".repeat(depth));
+                self.out.push_str(&marker);
+                self.line_start = self.out.len();
+                self.write_children(node);
+                self.start_line();
+            }
+            Data::DescriptionListTerm => {
+                self.start_line();
+                self.write_wrapped(node, "**");
+                self.start_line();
+            }
+            Data::DerscriptionListDescription => {
+                self.start_line();
+                self.out.push_str(": ");
+                self.line_start = self.out.len();
+                self.write_children(node);
+                self.start_line();
+            }
+
+            Data::Bold => self.write_wrapped(node, "**"),
+            Data::Italic => self.write_wrapped(node, "*"),
+            Data::Linebreak => self.start_line(),
+            Data::HorizontalRule => {
+                self.end_block();
+                self.out.push_str("---");
+                self.end_block();
+            }
+
+            Data::Reflink => {
+                let text: String = node
+                    .descendants()
+                    .filter_map(|node| match node.data() {
+                        Data::Text { contents } => Some(contents.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                let marker = text.trim().trim_start_matches('[').trim_end_matches(']');
+                if !marker.is_empty() {
+                    self.out.push_str(&format!("[^{marker}]"));
+                }
+            }
+            // reflinks are exported as footnotes, without the link to the reference
+            Data::Link(link) if link.is_reference() => self.write_children(node),
+            Data::Link(link) => match link_url(link) {
+                Some(url) => {
+                    self.out.push('[');
+                    self.write_children(node);
+                    self.out.push_str(&format!("]({url})"));
+                }
+                None => self.write_children(node),
+            },
+
+            Data::Figure { .. } | Data::Unsupported(_) | Data::UnsupportedInline(_) => {}
+            Data::Section { .. }
+            | Data::Division
+            | Data::Span
+            | Data::Reference { .. }
+            | Data::Unknown => self.write_children(node),
+        }
+    }
+}
+
+#[derive(Default)]
+struct HtmlWriter {
+    out: String,
+}
+
+impl HtmlWriter {
+    fn write_children(&mut self, node: Node) {
+        for child in node.children() {
+            self.write_node(child);
+        }
+    }
+
+    /// Writes the node into the element, blocks start on their own line
+    fn write_element(&mut self, node: Node, tag: &str, attributes: &str, is_block: bool) {
+        if is_block && !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+        self.out.push_str(&format!("<{tag}{attributes}>"));
+        self.write_children(node);
+        self.out.push_str(&format!("</{tag}>"));
+        if is_block {
+            self.out.push('\n');
+        }
+    }
+
+    fn write_node(&mut self, node: Node) {
+        match node.data() {
+            Data::Header { id, kind } => {
+                let tag = format!("h{}", kind.clone() as usize);
+                self.write_element(node, &tag, &format!(" id=\"{}\"", escape_html(id)), true);
+            }
+            Data::Text { contents } => self.out.push_str(&escape_html(contents)),
+
+            Data::Paragraph => self.write_element(node, "p", "", true),
+            Data::Hatnote | Data::RedirectMessage | Data::Disambiguation => {
+                self.write_element(node, "div", " class=\"hatnote\"", true)
+            }
+            Data::Blockquote => self.write_element(node, "blockquote", "", true),
+            Data::OrderedList => self.write_element(node, "ol", "", true),
+            Data::UnorderedList => self.write_element(node, "ul", "", true),
+            Data::ListItem => self.write_element(node, "li", "", true),
+            Data::DescriptionList => self.write_element(node, "dl", "", true),
+            Data::DescriptionListTerm => self.write_element(node, "dt", "", true),
+            Data::DerscriptionListDescription => self.write_element(node, "dd", "", true),
+
+            Data::Bold => self.write_element(node, "b", "", false),
+            Data::Italic => self.write_element(node, "i", "", false),
+            Data::Linebreak => self.out.push_str("<br>"),
+            Data::HorizontalRule => self.out.push_str("<hr>\n"),
+
+            // reflinks point to their reference in the references list of the page
+            Data::Reflink => self.write_element(node, "sup", "", false),
+            Data::Reference { id } => {
+                self.write_element(node, "span", &format!(" id=\"{}\"", escape_html(id)), false)
+            }
+            Data::Link(link) => match link_url(link) {
+                Some(url) => {
+                    let href = format!(" href=\"{}\"", escape_html(&url));
+                    self.write_element(node, "a", &href, false)
+                }
+                None => self.write_children(node),
+            },
+
+            Data::Figure { .. } | Data::Unsupported(_) | Data::UnsupportedInline(_) => {}
+            Data::Section { .. } | Data::Division | Data::Span | Data::Unknown => {
+                self.write_children(node)
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct TextWriter {
+    out: String,
+    /// The lists the writer is in, whether they are ordered and the number of their last item
+    lists: Vec<(bool, usize)>,
+}
+
+impl TextWriter {
+    /// Ends the current block with an empty line, or a line break inside of lists
+    fn end_block(&mut self) {
+        let trimmed_len = self.out.trim_end_matches([' ', '\n']).len();
+        self.out.truncate(trimmed_len);
+        if self.out.is_empty() {
+            return;
+        }
+        match self.lists.is_empty() {
+            true => self.out.push_str("\n\n"),
+            false => self.out.push('\n'),
+        }
+    }
+
+    fn start_line(&mut self) {
+        let trimmed_len = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed_len);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    /// Writes the text with every run of whitespace collapsed into a single space
+    fn write_text(&mut self, text: &str) {
+        for c in text.chars() {
+            if !c.is_whitespace() {
+                self.out.push(c);
+            } else if !self.out.is_empty() && !self.out.ends_with([' ', '\n']) {
+                self.out.push(' ');
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.end_block();
+        self.out.trim_end().to_string()
+    }
+
+    fn write_children(&mut self, node: Node) {
+        for child in node.children() {
+            self.write_node(child);
+        }
+    }
+
+    fn write_node(&mut self, node: Node) {
+        match node.data() {
+            Data::Text { contents } => self.write_text(contents),
+
+            Data::Header { .. }
+            | Data::Paragraph
+            | Data::Hatnote
+            | Data::RedirectMessage
+            | Data::Disambiguation
+            | Data::DescriptionList
+            | Data::Blockquote
+            | Data::HorizontalRule => {
+                self.end_block();
+                self.write_children(node);
+                self.end_block();
+            }
+
+            Data::OrderedList | Data::UnorderedList => {
+                self.end_block();
+                self.lists
+                    .push((matches!(node.data(), Data::OrderedList), 0));
+                self.write_children(node);
+                self.lists.pop();
+                self.end_block();
+            }
+            Data::ListItem => {
+                self.start_line();
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut() {
+                    Some((true, number)) => {
+                        *number += 1;
+                        format!("{number}. ")
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.out.push_str(&"  ".repeat(depth));
+                self.out.push_str(&marker);
+                self.write_children(node);
+                self.start_line();
+            }
+            Data::DescriptionListTerm | Data::DerscriptionListDescription | Data::Linebreak => {
+                self.start_line();
+                self.write_children(node);
+                self.start_line();
+            }
+
+            Data::Reflink
+            | Data::Figure { .. }
+            | Data::Unsupported(_)
+            | Data::UnsupportedInline(_) => {}
+            Data::Link(link) if link.is_reference() => {}
+            Data::Section { .. }
+            | Data::Division
+            | Data::Span
+            | Data::Bold
+            | Data::Italic
+            | Data::Link(_)
+            | Data::Reference { .. }
+            | Data::Unknown => self.write_children(node),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, sync::Arc};
+
+    use wiki_api::{
+        document::Document,
+        languages::Language,
+        page::{Page, PageKind},
+        parser::{Parser, WikipediaParser},
+        Endpoint,
+    };
+
+    use crate::{
+        config::Theme,
+        highlights::Highlight,
+        renderer::{default_renderer::DefaultRenderer, RenderOptions},
+    };
+
+    use super::{
+        export_file_name, nodes_to_text, page_to_html, page_to_markdown, page_to_text,
+        rendered_text, ExportFormat,
+    };
+
+    fn page(html: &str) -> Page {
+        let endpoint = Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap();
+        let nodes =
+            WikipediaParser::parse_document(html, endpoint.clone(), Language::English).nodes();
+        Page {
+            title: "Rust (fungus)".to_string(),
+            pageid: 0,
+            kind: PageKind::Article,
+            content: Document { nodes },
+            language: Language::English,
+            endpoint,
+            language_links: None,
+            sections: None,
+            revision_id: None,
+            redirected_from: None,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_page_to_markdown() {
+        let page = page(
+            r##"<p>A <b>plant</b> <i>disease</i> of <a rel="mw:WikiLink" href="./Wheat" title="Wheat">wheat</a>.<sup class="mw-ref reference"><a href="#cite_note-1"><span class="mw-reflink-text">[1]</span></a></sup></p>
+            <h2 id="Types">Types</h2>
+            <ul><li>Stem rust</li><li>Leaf rust</li></ul>
+            <ol><li>First</li><li>Second</li></ol>"##,
+        );
+
+        assert_eq!(
+            page_to_markdown(&page, &[]),
+            "# Rust (fungus)\n\n\
+            A **plant** *disease* of [wheat](https://en.wikipedia.org/wiki/Wheat).[^1]\n\n\
+            ## Types\n\n\
+            - Stem rust\n\
+            - Leaf rust\n\n\
+            1. First\n\
+            2. Second\n"
+        );
+
+        // the highlights are quoted at the end
+        let highlights = [
+            Highlight {
+                anchor: String::new(),
+                text: "A plant disease".to_string(),
+            },
+            Highlight {
+                anchor: "Types".to_string(),
+                text: "Leaf rust".to_string(),
+            },
+        ];
+        assert!(page_to_markdown(&page, &highlights).ends_with(
+            "2. Second\n\n\
+            ## Highlights\n\n\
+            > A plant disease\n\n\
+            > Leaf rust\n"
+        ));
+    }
+
+    #[test]
+    fn test_markdown_line_start() {
+        let page = page(
+            "<p># 1 hit</p><p>&gt; quoted</p><ul><li>- dash</li><li>+ plus</li></ul>\
+            <p>1984. The year</p><p>Not # a header - or 2. list</p>",
+        );
+
+        assert_eq!(
+            page_to_markdown(&page, &[]),
+            "# Rust (fungus)\n\n\
+            \\# 1 hit\n\n\
+            \\> quoted\n\n\
+            - \\- dash\n\
+            - \\+ plus\n\n\
+            1984\\. The year\n\n\
+            Not # a header - or 2. list\n"
+        );
+    }
+
+    #[test]
+    fn test_page_to_html() {
+        let page = page(
+            r##"<p>A <b>plant</b> disease of <a rel="mw:WikiLink" href="./Wheat" title="Wheat">wheat</a> in Zürich &amp; <i>Bern</i>.<sup class="mw-ref reference"><a href="#cite_note-1"><span class="mw-reflink-text">[1]</span></a></sup></p>
+            <h2 id="Types">Types &lt;2&gt;</h2>
+            <ul><li>Stem rust</li></ul>
+            <blockquote><p>Rust never sleeps</p></blockquote>
+            <ol class="mw-references"><li id="cite_note-1"><span class="mw-reference-text" id="mw-reference-text-cite_note-1">Smith, "Rusts"</span></li></ol>"##,
+        );
+        let html = page_to_html(&page);
+
+        assert!(html.starts_with("<!DOCTYPE html>\n<html lang=\"en\">\n"));
+        assert!(html.contains("<title>Rust (fungus)</title>"));
+        assert!(html.contains("<meta charset=\"utf-8\">"));
+        assert!(html.contains(
+            "<p>A <b>plant</b> disease of <a href=\"https://en.wikipedia.org/wiki/Wheat\">wheat</a> \
+            in Zürich &amp; <i>Bern</i>.<a href=\"#cite_note-1\"><sup>[1]</sup></a></p>"
+        ));
+        assert!(html.contains("<h2 id=\"Types\">Types &lt;2&gt;</h2>"));
+        assert!(html.contains("<ul>\n<li>Stem rust</li>\n</ul>"));
+        assert!(html.contains("<blockquote>\n<p>Rust never sleeps</p>\n</blockquote>"));
+        // the reflink leads to its reference
+        assert!(html.contains("<span id=\"cite_note-1\">Smith, &quot;Rusts&quot;</span>"));
+        assert!(html.ends_with(
+            "<footer><a href=\"https://en.wikipedia.org/wiki/Rust_(fungus)\">\
+            https://en.wikipedia.org/wiki/Rust_(fungus)</a></footer>\n</body>\n</html>\n"
+        ));
+    }
+
+    #[test]
+    fn test_nodes_to_text() {
+        let page = page(
+            r##"<p>A <b>plant</b> disease
+            of <a rel="mw:WikiLink" href="./Wheat" title="Wheat">wheat</a>.<sup class="mw-ref reference"><a href="#cite_note-1"><span class="mw-reflink-text">[1]</span></a></sup></p>
+            <p>It spreads <i>quickly</i>.</p>
+            <ul><li>Stem rust</li><li>Leaf rust</li></ul>"##,
+        );
+
+        assert_eq!(
+            nodes_to_text(page.content.nth(0).unwrap().children()),
+            "A plant disease of wheat.\n\n\
+            It spreads quickly.\n\n\
+            - Stem rust\n\
+            - Leaf rust"
+        );
+        assert!(page_to_text(&page).starts_with("Rust (fungus)\n\nA plant disease of wheat."));
+    }
+
+    #[test]
+    fn test_rendered_text() {
+        let page = page(
+            r#"<p>A <b>plant</b> disease of <a rel="mw:WikiLink" href="./Wheat" title="Wheat">wheat</a>.</p>"#,
+        );
+        let theme = Theme::default();
+        let renderer = DefaultRenderer::new(Arc::new(theme.clone()), RenderOptions::default());
+
+        let plain = rendered_text(&renderer, &page, 12, None);
+        assert_eq!(plain, "\nA plant\ndisease of\nwheat.\n\n");
+
+        // the colored text has the same lines, none of them leaves a style set
+        let ansi = rendered_text(&renderer, &page, 12, Some(&theme));
+        assert!(ansi.contains("\x1b[1mplant\x1b[0m"));
+        assert!(ansi.lines().all(|line| line
+            .rfind('\x1b')
+            .map_or(true, |start| line[start..].starts_with("\x1b[0m"))));
+        let stripped = regex::Regex::new("\x1b\\[[0-9;]*m")
+            .unwrap()
+            .replace_all(&ansi, "");
+        assert_eq!(stripped, plain);
+    }
+
+    #[test]
+    fn test_export_file() {
+        assert_eq!(
+            export_file_name("Rust (programming language)"),
+            "rust-programming-language.md"
+        );
+        assert_eq!(export_file_name("C++"), "c.md");
+        assert_eq!(export_file_name("???"), "page.md");
+
+        assert_eq!(
+            ExportFormat::from_path(Path::new("rust.md")),
+            ExportFormat::Markdown
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("rust.TXT")),
+            ExportFormat::Text
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("rust.ans")),
+            ExportFormat::Ansi
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("rust.html")),
+            ExportFormat::Html
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use wiki_api::document::{Data, Document, Node};
+
+use crate::store::Store;
+
+pub const HIGHLIGHTS_STORE: &str = "highlights";
+
+/// A highlighted passage of an article
+///
+/// A highlight doesn't store node indices, as these change whenever the article is updated.
+/// Instead it stores the id of the header preceding the passage (the anchor) and the text of the
+/// passage, which are used to find the passage again
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    /// Id of the header the passage belongs to. Empty for passages in the lead section
+    pub anchor: String,
+    pub text: String,
+}
+
+impl Highlight {
+    /// Creates a highlight from the nodes between `first` and `last` (both inclusive). Returns
+    /// `None` when the selection contains no text
+    pub fn from_selection(document: &Document, first: usize, last: usize) -> Option<Highlight> {
+        let root = document.nth(0)?;
+
+        let mut anchor = String::new();
+        let mut words: Vec<&str> = Vec::new();
+        for node in root.descendants() {
+            if node.index() > last {
+                break;
+            }
+
+            match node.data() {
+                Data::Header { id, .. } if node.index() < first => anchor = id.to_string(),
+                Data::Text { contents } if node.index() >= first => {
+                    words.extend(contents.split_whitespace())
+                }
+                _ => {}
+            }
+        }
+
+        if words.is_empty() {
+            return None;
+        }
+
+        Some(Highlight {
+            anchor,
+            text: words.join(" "),
+        })
+    }
+
+    /// Finds the passage in the document and returns the range of node indices (both inclusive)
+    /// it spans. Returns `None` when the passage could not be found (the highlight is orphaned)
+    pub fn resolve(&self, document: &Document) -> Option<(usize, usize)> {
+        let root = document.nth(0)?;
+        let start = if self.anchor.is_empty() {
+            root
+        } else {
+            root.descendants()
+                .find(|node| matches!(node.data(), Data::Header { id, .. } if id == &self.anchor))?
+        };
+
+        // whitespace is ignored when searching for the passage, that way the text can span
+        // multiple nodes regardless of how the whitespace is distributed between them
+        let needle: String = self.text.split_whitespace().collect();
+        if needle.is_empty() {
+            return None;
+        }
+
+        let mut haystack = String::new();
+        let mut offsets: Vec<(usize, usize)> = Vec::new();
+        for node in std::iter::once(start).chain(following(start)) {
+            if let Data::Text { contents } = node.data() {
+                offsets.push((haystack.len(), node.index()));
+                haystack.extend(contents.split_whitespace());
+            }
+        }
+
+        let begin = haystack.find(&needle)?;
+        let end = begin + needle.len() - 1;
+        let node_at = |offset: usize| {
+            offsets
+                .iter()
+                .take_while(|(node_offset, _)| *node_offset <= offset)
+                .last()
+                .map(|(_, index)| *index)
+        };
+
+        Some((node_at(begin)?, node_at(end)?))
+    }
+}
+
+/// Returns every node following the given node in document order (excluding the node itself)
+fn following(node: Node<'_>) -> impl Iterator<Item = Node<'_>> {
+    node.descendants().chain(
+        std::iter::successors(Some(node), |node| node.parent())
+            .flat_map(|node| std::iter::successors(node.next(), |next| next.next()))
+            .flat_map(|sibling| std::iter::once(sibling).chain(sibling.descendants())),
+    )
+}
+
+/// The highlights of all articles, keyed by the article (see [`article_key`])
+///
+/// [`article_key`]: crate::store::article_key
+#[derive(Serialize, Deserialize, Default)]
+pub struct Highlights {
+    articles: HashMap<String, Vec<Highlight>>,
+}
+
+impl Highlights {
+    pub fn open() -> anyhow::Result<Store<Highlights>> {
+        Store::open(HIGHLIGHTS_STORE)
+    }
+
+    pub fn get(&self, key: &str) -> &[Highlight] {
+        self.articles
+            .get(key)
+            .map(|x| x.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn add(&mut self, key: &str, highlight: Highlight) {
+        self.articles
+            .entry(key.to_string())
+            .or_default()
+            .push(highlight);
+    }
+
+    pub fn remove(&mut self, key: &str, idx: usize) -> Option<Highlight> {
+        let highlights = self.articles.get_mut(key)?;
+        if idx >= highlights.len() {
+            return None;
+        }
+
+        let highlight = highlights.remove(idx);
+        if highlights.is_empty() {
+            self.articles.remove(key);
+        }
+        Some(highlight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::{
+        document::{Data, Document},
+        languages::Language,
+        parser::{Parser, WikipediaParser},
+        Endpoint,
+    };
+
+    use super::{Highlight, Highlights};
+
+    fn parse(html: &str) -> Document {
+        let nodes = WikipediaParser::parse_document(
+            html,
+            Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+            Language::default(),
+        )
+        .nodes();
+        Document { nodes }
+    }
+
+    fn text_node(document: &Document, text: &str) -> usize {
+        document
+            .nodes
+            .iter()
+            .find(|raw| matches!(&raw.data, Data::Text { contents } if contents == text))
+            .map(|raw| raw.index)
+            .unwrap()
+    }
+
+    const ARTICLE: &str = r#"<p>The lead section</p>
+        <h2 id="History">History</h2>
+        <p>Some <b>bold words</b> in the history section</p>"#;
+
+    #[test]
+    fn test_add_and_jump() {
+        let document = parse(ARTICLE);
+        let first = text_node(&document, "Some ");
+        let last = text_node(&document, "bold words");
+
+        let highlight = Highlight::from_selection(&document, first, last).unwrap();
+        assert_eq!(highlight.anchor, "History");
+        assert_eq!(highlight.text, "Some bold words");
+
+        assert_eq!(highlight.resolve(&document), Some((first, last)));
+    }
+
+    #[test]
+    fn test_jump_after_update() {
+        let document = parse(ARTICLE);
+        let first = text_node(&document, "Some ");
+        let last = text_node(&document, "bold words");
+        let highlight = Highlight::from_selection(&document, first, last).unwrap();
+
+        let updated = parse(&ARTICLE.replace("The lead section", "An <i>updated</i> lead"));
+        let first = text_node(&updated, "Some ");
+        let last = text_node(&updated, "bold words");
+        assert_eq!(highlight.resolve(&updated), Some((first, last)));
+    }
+
+    #[test]
+    fn test_orphaned() {
+        let document = parse(ARTICLE);
+        let first = text_node(&document, "Some ");
+        let highlight = Highlight::from_selection(&document, first, first).unwrap();
+
+        let reworded = parse(&ARTICLE.replace("Some", "A few"));
+        assert_eq!(highlight.resolve(&reworded), None);
+
+        let renamed = parse(&ARTICLE.replace("id=\"History\"", "id=\"Background\""));
+        assert_eq!(highlight.resolve(&renamed), None);
+    }
+
+    #[test]
+    fn test_add_and_delete() {
+        let mut highlights = Highlights::default();
+        let highlight = |text: &str| Highlight {
+            anchor: String::new(),
+            text: text.to_string(),
+        };
+
+        highlights.add("en:Article", highlight("first"));
+        highlights.add("en:Article", highlight("second"));
+        highlights.add("en:Other", highlight("other"));
+        assert_eq!(highlights.get("en:Article").len(), 2);
+
+        assert_eq!(highlights.remove("en:Article", 0), Some(highlight("first")));
+        assert_eq!(highlights.get("en:Article"), &[highlight("second")]);
+        assert_eq!(highlights.remove("en:Article", 1), None);
+
+        assert_eq!(
+            highlights.remove("en:Article", 0),
+            Some(highlight("second"))
+        );
+        assert!(highlights.get("en:Article").is_empty());
+        assert_eq!(highlights.get("en:Other").len(), 1);
+    }
+}
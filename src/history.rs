@@ -0,0 +1,336 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use wiki_api::{languages::Language, Endpoint};
+
+use crate::store::Store;
+
+pub const HISTORY_STORE: &str = "history";
+pub const VISITED_STORE: &str = "visited";
+
+/// Number of visited pages that are remembered
+const VISITED_LIMIT: usize = 500;
+
+/// The prompts that keep a history of their queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryKind {
+    /// The article search bar
+    Search,
+    /// The search prompt of a page
+    PageSearch,
+    /// The command palette
+    Command,
+}
+
+/// The queries of all prompts, ordered from the oldest to the most recent one
+#[derive(Serialize, Deserialize, Default)]
+pub struct History {
+    search: Vec<String>,
+    page_search: Vec<String>,
+    #[serde(default)]
+    command: Vec<String>,
+}
+
+impl History {
+    pub fn open() -> anyhow::Result<Store<History>> {
+        Store::open(HISTORY_STORE)
+    }
+
+    pub fn get(&self, kind: HistoryKind) -> &[String] {
+        match kind {
+            HistoryKind::Search => &self.search,
+            HistoryKind::PageSearch => &self.page_search,
+            HistoryKind::Command => &self.command,
+        }
+    }
+
+    /// Adds the query as the most recent one, removing an earlier occurrence of it. Only the
+    /// `limit` most recent queries are kept
+    pub fn add(&mut self, kind: HistoryKind, query: &str, limit: usize) {
+        let entries = match kind {
+            HistoryKind::Search => &mut self.search,
+            HistoryKind::PageSearch => &mut self.page_search,
+            HistoryKind::Command => &mut self.command,
+        };
+
+        entries.retain(|entry| entry != query);
+        entries.push(query.to_string());
+        if entries.len() > limit {
+            entries.drain(..entries.len() - limit);
+        }
+    }
+}
+
+/// The history of a prompt, which can be walked like the history of a shell
+///
+/// When walking the history, only the queries starting with the input typed before are recalled
+pub struct PromptHistory {
+    kind: HistoryKind,
+    limit: usize,
+    entries: Vec<String>,
+
+    /// The input before walking the history, `None` when not walking the history
+    prefix: Option<String>,
+    /// Index of the recalled query, the number of entries when no query has been recalled
+    position: usize,
+}
+
+impl PromptHistory {
+    /// Loads the history of the prompt. A history that cannot be loaded starts empty
+    pub fn load(kind: HistoryKind, limit: usize) -> Self {
+        let entries = match History::open() {
+            Ok(store) => store.get().get(kind).to_vec(),
+            Err(error) => {
+                warn!("{:?}", error.context("failed loading the history"));
+                Vec::new()
+            }
+        };
+        Self::with_entries(kind, limit, entries)
+    }
+
+    fn with_entries(kind: HistoryKind, limit: usize, entries: Vec<String>) -> Self {
+        Self {
+            kind,
+            limit,
+            position: entries.len(),
+            entries,
+
+            prefix: None,
+        }
+    }
+
+    /// Returns the previous query starting with the input, if there is one
+    pub fn recall_prev(&mut self, input: &str) -> Option<String> {
+        let prefix = self.prefix.get_or_insert_with(|| input.to_string());
+        let position = self.entries[..self.position]
+            .iter()
+            .rposition(|entry| entry.starts_with(prefix.as_str()))?;
+        self.position = position;
+        Some(self.entries[position].clone())
+    }
+
+    /// Returns the next query starting with the input typed before walking the history. Returns
+    /// that input when there are no more recent queries
+    pub fn recall_next(&mut self) -> Option<String> {
+        if self.position >= self.entries.len() {
+            return None;
+        }
+        let prefix = self.prefix.as_ref()?;
+
+        let position = self.entries[self.position + 1..]
+            .iter()
+            .position(|entry| entry.starts_with(prefix.as_str()));
+        match position {
+            Some(position) => {
+                self.position += position + 1;
+                Some(self.entries[self.position].clone())
+            }
+            None => {
+                self.position = self.entries.len();
+                self.prefix.take()
+            }
+        }
+    }
+
+    /// Returns the queries, ordered from the oldest to the most recent one
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Stops walking the history, the next recall starts at the most recent query again
+    pub fn reset(&mut self) {
+        self.prefix = None;
+        self.position = self.entries.len();
+    }
+
+    /// Adds the query to the history and saves it
+    pub fn add(&mut self, query: &str) {
+        self.reset();
+        if query.is_empty() || self.limit == 0 {
+            return;
+        }
+
+        self.entries.retain(|entry| entry != query);
+        self.entries.push(query.to_string());
+        if self.entries.len() > self.limit {
+            self.entries.drain(..self.entries.len() - self.limit);
+        }
+        self.position = self.entries.len();
+
+        // the history is shared by all prompts, so the stored one has to be updated instead of
+        // overwriting it with the entries of this prompt
+        let result = History::open().and_then(|mut store| {
+            store.get_mut().add(self.kind, query, self.limit);
+            store.save()
+        });
+        if let Err(error) = result {
+            warn!("{:?}", error.context("failed saving the history"));
+        }
+    }
+}
+
+/// A page that was visited in this or an earlier session
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VisitedPage {
+    pub title: String,
+    #[serde(with = "crate::store::language_code")]
+    pub language: Language,
+    pub endpoint: Endpoint,
+    /// When the page was visited, in seconds since the unix epoch
+    pub visited: u64,
+}
+
+impl VisitedPage {
+    /// Creates the visit of a page happening now
+    pub fn now(title: String, language: Language, endpoint: Endpoint) -> Self {
+        let visited = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        Self {
+            title,
+            language,
+            endpoint,
+            visited,
+        }
+    }
+
+    fn is_same_page(&self, other: &VisitedPage) -> bool {
+        self.title == other.title && self.language == other.language
+    }
+}
+
+/// The visited pages, ordered from the oldest to the most recent visit. Only the most recent
+/// visit of a page is kept
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct VisitedPages {
+    pages: Vec<VisitedPage>,
+}
+
+impl VisitedPages {
+    pub fn open() -> anyhow::Result<Store<VisitedPages>> {
+        Store::open(VISITED_STORE)
+    }
+
+    pub fn pages(&self) -> &[VisitedPage] {
+        &self.pages
+    }
+
+    pub fn add(&mut self, page: VisitedPage) {
+        self.pages.retain(|visited| !visited.is_same_page(&page));
+        self.pages.push(page);
+        if self.pages.len() > VISITED_LIMIT {
+            self.pages.drain(..self.pages.len() - VISITED_LIMIT);
+        }
+    }
+
+    pub fn remove(&mut self, page: &VisitedPage) {
+        self.pages.retain(|visited| visited != page);
+    }
+
+    /// Returns the languages of the visited pages, the most recently used first
+    pub fn languages(&self) -> Vec<Language> {
+        let mut languages: Vec<Language> = Vec::new();
+        for page in self.pages.iter().rev() {
+            if !languages.contains(&page.language) {
+                languages.push(page.language);
+            }
+        }
+        languages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::{languages::Language, Endpoint};
+
+    use super::{History, HistoryKind, PromptHistory, VisitedPage, VisitedPages};
+
+    fn entries(queries: &[&str]) -> Vec<String> {
+        queries.iter().map(|query| query.to_string()).collect()
+    }
+
+    #[test]
+    fn test_add_deduplicates_and_limits() {
+        let mut history = History::default();
+        for query in ["rust", "iron", "oxide", "rust", "carbon"] {
+            history.add(HistoryKind::Search, query, 3);
+        }
+
+        assert_eq!(
+            history.get(HistoryKind::Search),
+            ["oxide", "rust", "carbon"]
+        );
+        assert!(history.get(HistoryKind::PageSearch).is_empty());
+    }
+
+    #[test]
+    fn test_walk_history() {
+        let mut history = PromptHistory::with_entries(
+            HistoryKind::Search,
+            100,
+            entries(&["rust", "iron", "oxide"]),
+        );
+
+        assert_eq!(history.recall_next(), None);
+        assert_eq!(history.recall_prev("").as_deref(), Some("oxide"));
+        assert_eq!(history.recall_prev("oxide").as_deref(), Some("iron"));
+        assert_eq!(history.recall_prev("iron").as_deref(), Some("rust"));
+        assert_eq!(history.recall_prev("rust"), None);
+        assert_eq!(history.recall_next().as_deref(), Some("iron"));
+        assert_eq!(history.recall_next().as_deref(), Some("oxide"));
+        // walking past the most recent query restores the input
+        assert_eq!(history.recall_next().as_deref(), Some(""));
+        assert_eq!(history.recall_next(), None);
+    }
+
+    #[test]
+    fn test_prefix_recall() {
+        let mut history = PromptHistory::with_entries(
+            HistoryKind::PageSearch,
+            100,
+            entries(&["iron", "rust", "iron oxide", "rusty"]),
+        );
+
+        assert_eq!(history.recall_prev("ir").as_deref(), Some("iron oxide"));
+        assert_eq!(history.recall_prev("iron oxide").as_deref(), Some("iron"));
+        assert_eq!(history.recall_prev("iron"), None);
+        assert_eq!(history.recall_next().as_deref(), Some("iron oxide"));
+        assert_eq!(history.recall_next().as_deref(), Some("ir"));
+    }
+
+    #[test]
+    fn test_visited_pages() {
+        let endpoint = Endpoint::parse("https://de.wikipedia.org/w/api.php").unwrap();
+        let visit = |title: &str, visited| VisitedPage {
+            title: title.to_string(),
+            language: Language::German,
+            endpoint: endpoint.clone(),
+            visited,
+        };
+
+        let mut pages = VisitedPages::default();
+        pages.add(visit("Rost", 1));
+        pages.add(visit("Eisen", 2));
+        pages.add(visit("Rost", 3));
+        assert_eq!(pages.pages(), [visit("Eisen", 2), visit("Rost", 3)]);
+
+        // the language is stored by its code
+        let json = serde_json::to_string(&pages.pages()[0]).unwrap();
+        assert!(json.contains(r#""language":"de""#));
+        assert_eq!(
+            serde_json::from_str::<VisitedPage>(&json).unwrap(),
+            visit("Eisen", 2)
+        );
+
+        pages.remove(&visit("Eisen", 2));
+        assert_eq!(pages.pages(), [visit("Rost", 3)]);
+
+        pages.add(VisitedPage {
+            language: Language::English,
+            ..visit("Rust", 4)
+        });
+        assert_eq!(pages.languages(), [Language::English, Language::German]);
+    }
+}
@@ -0,0 +1,447 @@
+use std::{
+    collections::BTreeSet,
+    io::Write,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crossterm::{cursor::MoveTo, QueueableCommand};
+use image::{codecs::png::PngEncoder, imageops::FilterType, ColorType, ImageEncoder, RgbImage};
+use ratatui::{buffer::Buffer, layout::Rect};
+use tracing::{info, warn};
+
+use crate::config::ImageProtocol;
+
+/// Images are scaled down to this size after decoding, larger images would only use memory
+const MAX_IMAGE_SIZE: u32 = 1024;
+
+/// The size of a cell in pixels when the terminal doesn't report it
+const DEFAULT_CELL_SIZE: (u16, u16) = (10, 20);
+
+/// Kitty sends the image data in chunks of this size
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// The images of the current frame and the ones on the screen
+static FRAME_IMAGES: Mutex<FrameImages> = Mutex::new(FrameImages {
+    queued: Vec::new(),
+    drawn: Vec::new(),
+    area: Rect::ZERO,
+    kitty_ids: BTreeSet::new(),
+});
+
+/// The escape sequences drawing images aren't part of the buffer, the terminal would only get them
+/// when their cell changed. They're written to the terminal after the frame is drawn instead, into
+/// cells the buffer skips
+struct FrameImages {
+    /// The images of the frame that's being rendered
+    queued: Vec<QueuedImage>,
+    /// The images drawn after the last frame. The terminal keeps them until their cells are drawn
+    /// over, so they're only drawn again when they moved
+    drawn: Vec<QueuedImage>,
+    /// The size of the last frame, the screen is cleared when it changes
+    area: Rect,
+    /// The kitty images on the screen. Kitty keeps images until they're deleted
+    kitty_ids: BTreeSet<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct QueuedImage {
+    area: Rect,
+    sequence: Arc<str>,
+    /// The id of a kitty image
+    kitty_id: Option<u32>,
+}
+
+impl PartialEq for QueuedImage {
+    fn eq(&self, other: &Self) -> bool {
+        self.area == other.area
+            && Arc::ptr_eq(&self.sequence, &other.sequence)
+            && self.kitty_id == other.kitty_id
+    }
+}
+
+/// A decoded image, shown below the placeholder of its figure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageImage {
+    pub width: u32,
+    pub height: u32,
+    /// The pixels, three bytes per pixel row by row
+    pub rgb: Vec<u8>,
+}
+
+impl PageImage {
+    /// Decodes a png or jpeg image and scales it down when it's larger than needed
+    pub fn decode(bytes: &[u8]) -> Result<PageImage> {
+        let mut image = image::load_from_memory(bytes).context("failed decoding the image")?;
+        if image.width() > MAX_IMAGE_SIZE || image.height() > MAX_IMAGE_SIZE {
+            image = image.resize(MAX_IMAGE_SIZE, MAX_IMAGE_SIZE, FilterType::Triangle);
+        }
+        let image = image.to_rgb8();
+        Ok(PageImage {
+            width: image.width(),
+            height: image.height(),
+            rgb: image.into_raw(),
+        })
+    }
+
+    /// Returns the number of columns and rows the image takes up when it's at most `rows` high
+    /// and `max_columns` wide, keeping its aspect ratio
+    pub fn cells(&self, rows: u16, max_columns: u16, cell: (u16, u16)) -> (u16, u16) {
+        if self.width == 0 || self.height == 0 || max_columns == 0 {
+            return (0, 0);
+        }
+        let (cell_width, cell_height) = (cell.0.max(1) as u64, cell.1.max(1) as u64);
+        let (width, height) = (self.width as u64, self.height as u64);
+
+        let columns = (rows as u64 * cell_height * width).div_ceil(height * cell_width);
+        if columns <= max_columns as u64 {
+            return (columns.max(1) as u16, rows);
+        }
+        let rows = (max_columns as u64 * cell_width * height).div_ceil(width * cell_height);
+        (max_columns, rows.clamp(1, u16::MAX as u64) as u16)
+    }
+
+    /// Returns the escape sequence drawing the image into the cells, starting at the cursor.
+    /// Afterwards the cursor is back where it started. `id` identifies the image for kitty
+    pub fn encode(
+        &self,
+        protocol: ImageProtocol,
+        (columns, rows): (u16, u16),
+        cell: (u16, u16),
+        id: u32,
+    ) -> String {
+        let image = match RgbImage::from_raw(self.width, self.height, self.rgb.clone()) {
+            Some(image) => image,
+            None => return String::new(),
+        };
+        let image = image::imageops::resize(
+            &image,
+            (columns as u32 * cell.0 as u32).max(1),
+            (rows as u32 * cell.1 as u32).max(1),
+            FilterType::Triangle,
+        );
+
+        let sequence = match protocol {
+            ImageProtocol::Sixel => encode_sixel(&image),
+            ImageProtocol::Kitty => {
+                let data = STANDARD.encode(encode_png(&image));
+                let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+                // the old image with the same id is deleted, so it's not drawn twice
+                let mut sequence = format!("\x1b_Ga=d,d=I,i={id},q=2\x1b\\");
+                for (idx, chunk) in chunks.iter().enumerate() {
+                    let more = (idx + 1 < chunks.len()) as u8;
+                    let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+                    if idx == 0 {
+                        sequence.push_str(&format!(
+                            "\x1b_Ga=T,f=100,i={id},c={columns},r={rows},C=1,z=-1,q=2,m={more};{chunk}\x1b\\"
+                        ));
+                    } else {
+                        sequence.push_str(&format!("\x1b_Gm={more};{chunk}\x1b\\"));
+                    }
+                }
+                sequence
+            }
+            ImageProtocol::Iterm2 => {
+                let png = encode_png(&image);
+                format!(
+                    "\x1b]1337;File=inline=1;size={};width={columns};height={rows};preserveAspectRatio=0:{}\x07",
+                    png.len(),
+                    STANDARD.encode(&png)
+                )
+            }
+            ImageProtocol::Auto | ImageProtocol::None => return String::new(),
+        };
+        format!("\x1b7{sequence}\x1b8")
+    }
+}
+
+fn encode_png(image: &RgbImage) -> Vec<u8> {
+    let mut png = Vec::new();
+    if let Err(error) = PngEncoder::new(&mut png).write_image(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        ColorType::Rgb8,
+    ) {
+        warn!("failed encoding the image as png: {error}");
+    }
+    png
+}
+
+/// Encodes the image as sixels with a palette of 6 levels per color
+fn encode_sixel(image: &RgbImage) -> String {
+    const LEVELS: u32 = 6;
+    let register = |pixel: &image::Rgb<u8>| -> usize {
+        let level = |value: u8| (value as u32 * (LEVELS - 1) + 127) / 255;
+        (level(pixel[0]) * LEVELS * LEVELS + level(pixel[1]) * LEVELS + level(pixel[2])) as usize
+    };
+
+    let (width, height) = (image.width(), image.height());
+    let mut sixel = format!("\x1bPq\"1;1;{width};{height}");
+    for idx in 0..LEVELS.pow(3) {
+        let percent = |level: u32| level * 100 / (LEVELS - 1);
+        sixel.push_str(&format!(
+            "#{idx};2;{};{};{}",
+            percent(idx / (LEVELS * LEVELS)),
+            percent(idx / LEVELS % LEVELS),
+            percent(idx % LEVELS)
+        ));
+    }
+
+    let mut bits = vec![0u8; width as usize];
+    for band in (0..height).step_by(6) {
+        let band_height = (height - band).min(6);
+        let mut registers: Vec<usize> = (0..width)
+            .flat_map(|x| (0..band_height).map(move |dy| (x, band + dy)))
+            .map(|(x, y)| register(image.get_pixel(x, y)))
+            .collect();
+        registers.sort_unstable();
+        registers.dedup();
+
+        for (nth, idx) in registers.iter().enumerate() {
+            for (x, bit) in bits.iter_mut().enumerate() {
+                *bit = (0..band_height)
+                    .filter(|dy| register(image.get_pixel(x as u32, band + dy)) == *idx)
+                    .fold(0, |bit, dy| bit | 1 << dy);
+            }
+
+            if nth > 0 {
+                sixel.push('$');
+            }
+            sixel.push_str(&format!("#{idx}"));
+            let mut x = 0;
+            while x < bits.len() {
+                let len = bits[x..].iter().take_while(|bit| **bit == bits[x]).count();
+                let c = (63 + bits[x]) as char;
+                match len {
+                    1..=3 => sixel.extend(std::iter::repeat(c).take(len)),
+                    len => sixel.push_str(&format!("!{len}{c}")),
+                }
+                x += len;
+            }
+        }
+        sixel.push('-');
+    }
+    sixel.push_str("\x1b\\");
+    sixel
+}
+
+/// Returns the protocol images are drawn with, detecting it from the environment for `Auto`
+pub fn resolve_protocol(protocol: ImageProtocol) -> ImageProtocol {
+    static DETECTED: OnceLock<ImageProtocol> = OnceLock::new();
+    match protocol {
+        ImageProtocol::Auto => *DETECTED.get_or_init(|| {
+            let detected = detect_protocol(|name| std::env::var(name).ok());
+            info!("detected the image protocol {:?}", detected);
+            detected
+        }),
+        protocol => protocol,
+    }
+}
+
+/// Guesses the graphics protocol of the terminal from its environment variables. Terminal
+/// multiplexers don't pass the images through, so no images are drawn inside of them
+fn detect_protocol(var: impl Fn(&str) -> Option<String>) -> ImageProtocol {
+    let term = var("TERM").unwrap_or_default();
+    let program = var("TERM_PROGRAM").unwrap_or_default();
+
+    if var("TMUX").is_some() || term.starts_with("screen") || term.starts_with("tmux") {
+        ImageProtocol::None
+    } else if var("KITTY_WINDOW_ID").is_some() || term.contains("kitty") || program == "ghostty" {
+        ImageProtocol::Kitty
+    } else if program == "iTerm.app"
+        || program == "WezTerm"
+        || var("LC_TERMINAL").as_deref() == Some("iTerm2")
+    {
+        ImageProtocol::Iterm2
+    } else if term.contains("foot")
+        || term.contains("mlterm")
+        || term.contains("sixel")
+        || program == "mintty"
+        || program == "contour"
+    {
+        ImageProtocol::Sixel
+    } else {
+        ImageProtocol::None
+    }
+}
+
+/// Returns the size of a cell in pixels
+pub fn cell_size() -> (u16, u16) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => {
+            (size.width / size.columns, size.height / size.rows)
+        }
+        _ => DEFAULT_CELL_SIZE,
+    }
+}
+
+/// Draws the image into the cells of the area after the current frame is drawn. The cells have to
+/// be skipped in the buffer, so the text doesn't overwrite the image. `kitty_id` identifies kitty
+/// images
+pub fn queue_image(area: Rect, sequence: Arc<str>, kitty_id: Option<u32>) {
+    if let Ok(mut images) = FRAME_IMAGES.lock() {
+        images.queued.push(QueuedImage {
+            area,
+            sequence,
+            kitty_id,
+        });
+    }
+}
+
+/// Leaves out the queued images that something was rendered over, like a popup. Called after
+/// rendering every frame
+pub fn finish_frame(buffer: &Buffer) {
+    let mut images = match FRAME_IMAGES.lock() {
+        Ok(images) => images,
+        Err(_) => return,
+    };
+    images.queued.retain(|image| {
+        let area = image.area.intersection(buffer.area);
+        area == image.area
+            && (area.top()..area.bottom())
+                .all(|y| (area.left()..area.right()).all(|x| buffer.get(x, y).skip))
+    });
+    // the terminal is cleared when it's resized
+    if images.area != buffer.area {
+        images.area = buffer.area;
+        images.drawn.clear();
+    }
+}
+
+/// Draws the images that are new or moved since the last frame and deletes the kitty images that
+/// are no longer shown. Called after drawing every frame
+pub fn draw_images(writer: &mut impl Write) {
+    let mut images = match FRAME_IMAGES.lock() {
+        Ok(images) => images,
+        Err(_) => return,
+    };
+    let FrameImages {
+        queued,
+        drawn,
+        kitty_ids,
+        ..
+    } = &mut *images;
+
+    let shown_ids: BTreeSet<u32> = queued.iter().filter_map(|image| image.kitty_id).collect();
+    let mut result = Ok(());
+    for id in kitty_ids.difference(&shown_ids) {
+        result = result.and_then(|_| write!(writer, "\x1b_Ga=d,d=I,i={id},q=2\x1b\\"));
+    }
+    for image in queued.iter().filter(|image| !drawn.contains(image)) {
+        result = result
+            .and_then(|_| writer.queue(MoveTo(image.area.x, image.area.y)).map(|_| ()))
+            .and_then(|_| writer.write_all(image.sequence.as_bytes()));
+    }
+    if let Err(error) = result.and_then(|_| writer.flush()) {
+        warn!("failed drawing the images: {error}");
+    }
+
+    *drawn = std::mem::take(queued);
+    *kitty_ids = shown_ids;
+}
+
+/// Forgets the images on the screen, so they're drawn again after the next frame. Called when the
+/// screen is cleared
+pub fn forget_drawn_images() {
+    if let Ok(mut images) = FRAME_IMAGES.lock() {
+        images.drawn.clear();
+        images.kitty_ids.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use image::{Rgb, RgbImage};
+    use ratatui::{buffer::Buffer, layout::Rect};
+
+    use crate::config::ImageProtocol;
+
+    use super::{detect_protocol, draw_images, encode_sixel, finish_frame, queue_image, PageImage};
+
+    #[test]
+    fn test_detect_protocol() {
+        let detect = |vars: &[(&str, &str)]| {
+            detect_protocol(|name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            })
+        };
+        assert_eq!(
+            detect(&[("TERM", "xterm-kitty"), ("KITTY_WINDOW_ID", "1")]),
+            ImageProtocol::Kitty
+        );
+        assert_eq!(
+            detect(&[("TERM_PROGRAM", "iTerm.app")]),
+            ImageProtocol::Iterm2
+        );
+        assert_eq!(detect(&[("TERM", "foot")]), ImageProtocol::Sixel);
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), ImageProtocol::None);
+        assert_eq!(
+            detect(&[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux-1000/default")]),
+            ImageProtocol::None
+        );
+    }
+
+    #[test]
+    fn test_cells() {
+        let image = PageImage {
+            width: 400,
+            height: 300,
+            rgb: Vec::new(),
+        };
+        // 12 rows of 20 pixels are 240 pixels high, so the image is 320 pixels wide
+        assert_eq!(image.cells(12, 80, (10, 20)), (32, 12));
+        // narrow pages make the image smaller
+        assert_eq!(image.cells(12, 20, (10, 20)), (20, 8));
+        assert_eq!(image.cells(12, 0, (10, 20)), (0, 0));
+    }
+
+    #[test]
+    fn test_encode_sixel() {
+        let mut image = RgbImage::from_pixel(5, 7, Rgb([255, 255, 255]));
+        image.put_pixel(0, 6, Rgb([255, 0, 0]));
+
+        let sixel = encode_sixel(&image);
+        assert!(sixel.starts_with("\x1bPq\"1;1;5;7"));
+        assert!(sixel.ends_with("-\x1b\\"));
+        // the first band is white, the second one has a red pixel in front of the white ones
+        assert!(sixel.contains("#215!5~-"));
+        assert!(sixel.contains("#180@!4?$#215?!4@-"));
+    }
+
+    #[test]
+    fn test_draw_images() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 10, 5));
+        for x in 2..4 {
+            buffer.get_mut(x, 1).set_skip(true);
+        }
+        let shown = Rect::new(2, 1, 2, 1);
+        let covered = Rect::new(5, 1, 2, 1);
+        let (shown_sequence, covered_sequence): (Arc<str>, Arc<str>) =
+            ("shown".into(), "covered".into());
+        let frame = |buffer: &Buffer| {
+            queue_image(shown, shown_sequence.clone(), None);
+            queue_image(covered, covered_sequence.clone(), None);
+            finish_frame(buffer);
+            let mut written = Vec::new();
+            draw_images(&mut written);
+            String::from_utf8(written).unwrap()
+        };
+
+        // only the image whose cells are skipped is drawn, at its position
+        assert_eq!(frame(&buffer), "\x1b[2;3Hshown");
+        // images stay on the screen, so they aren't drawn again
+        assert_eq!(frame(&buffer), "");
+        // resizing clears the screen
+        buffer.resize(Rect::new(0, 0, 10, 6));
+        for x in 2..4 {
+            buffer.get_mut(x, 1).set_skip(true);
+        }
+        assert_eq!(frame(&buffer), "\x1b[2;3Hshown");
+    }
+}
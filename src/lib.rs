@@ -1,13 +1,30 @@
 pub mod action;
 pub mod app;
+pub mod bookmarks;
+pub mod browser;
 pub mod cli;
+pub mod client;
+pub mod clipboard;
 pub mod components;
 pub mod config;
 pub mod event;
+pub mod export;
+pub mod highlights;
+pub mod history;
+pub mod images;
 pub mod key_macros;
 pub mod logging;
+pub mod media;
+pub mod page_cache;
 pub mod page_loader;
 pub mod panic_handler;
+pub mod print;
+pub mod reading_list;
 pub mod renderer;
+pub mod retry;
+pub mod session;
+pub mod store;
 pub mod terminal;
+pub mod theme;
 pub mod ui;
+pub mod watches;
@@ -1,12 +1,23 @@
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Display, Write},
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
 use anyhow::{Context, Result};
-use tracing::level_filters::LevelFilter;
-use tracing_log::AsLog;
-use tracing_subscriber::{self, prelude::*, EnvFilter};
+use tracing::{field::Field, level_filters::LevelFilter, Event, Level, Subscriber};
+use tracing_subscriber::{self, field::Visit, layer, prelude::*, EnvFilter, Layer};
 
 use crate::config::{self, load_logging_config};
 
 const LOG_ENV: &str = "WIKI_TUI_LOG";
 
+/// Number of records kept for the log viewer, older records are dropped
+const LOG_BUFFER_SIZE: usize = 1000;
+
+static LOG_BUFFER: OnceLock<Arc<LogBuffer>> = OnceLock::new();
+
 pub fn initialize_logging(level: Option<LevelFilter>) -> Result<()> {
     let logging_config = match load_logging_config() {
         Ok(config) => config,
@@ -43,16 +54,131 @@ pub fn initialize_logging(level: Option<LevelFilter>) -> Result<()> {
         .with_ansi(false)
         .with_filter(env_filter);
 
+    let buffer_subscriber = logging_config.log_viewer.then(|| {
+        let buffer = LOG_BUFFER.get_or_init(|| Arc::new(LogBuffer::new(LOG_BUFFER_SIZE)));
+        LogBufferLayer(buffer.clone()).with_filter(level)
+    });
+
     tracing_subscriber::registry()
         .with(file_subscriber)
-        .with(tui_logger::tracing_subscriber_layer())
+        .with(buffer_subscriber)
         .init();
 
-    tui_logger::set_default_level(level.as_log());
-
     Ok(())
 }
 
+/// Returns the records shown in the log viewer, `None` when the log viewer is disabled
+pub fn log_buffer() -> Option<&'static LogBuffer> {
+    LOG_BUFFER.get().map(|buffer| buffer.as_ref())
+}
+
+/// A log record kept for the log viewer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    /// Increases with every record, identifies the record after older records were dropped
+    pub id: u64,
+    pub level: Level,
+    /// Time since the start of the program
+    pub elapsed: Duration,
+    pub target: String,
+    /// The message followed by the other fields of the event
+    pub message: String,
+}
+
+impl Display for LogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>9.3} {:<5} {}: {}",
+            self.elapsed.as_secs_f64(),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+/// The most recent log records, filled by the tracing layer
+pub struct LogBuffer {
+    records: Mutex<VecDeque<LogRecord>>,
+    capacity: usize,
+    start: Instant,
+}
+
+impl LogBuffer {
+    fn new(capacity: usize) -> Self {
+        Self {
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            start: Instant::now(),
+        }
+    }
+
+    fn push(&self, level: Level, target: &str, message: String) {
+        let mut records = self.records.lock().unwrap();
+        let id = records.back().map_or(0, |record| record.id + 1);
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(LogRecord {
+            id,
+            level,
+            elapsed: self.start.elapsed(),
+            target: target.to_string(),
+            message,
+        });
+    }
+
+    /// Returns the id of the newest record, `None` before the first record
+    pub fn newest_id(&self) -> Option<u64> {
+        self.records.lock().unwrap().back().map(|record| record.id)
+    }
+
+    /// Calls the function with the records, the newest record last. Nothing must be logged in
+    /// the function, as the records are locked
+    pub fn with_records<T>(&self, f: impl FnOnce(&VecDeque<LogRecord>) -> T) -> T {
+        f(&self.records.lock().unwrap())
+    }
+}
+
+/// Keeps the events in the log buffer
+struct LogBufferLayer(Arc<LogBuffer>);
+
+impl<S: Subscriber> Layer<S> for LogBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _: layer::Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        self.0
+            .push(*metadata.level(), metadata.target(), visitor.finish());
+    }
+}
+
+/// Formats the message and the other fields of an event
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(self) -> String {
+        match self.message.is_empty() {
+            true => self.fields.trim_start().to_string(),
+            false => self.message + &self.fields,
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        let _ = match field.name() {
+            "message" => write!(self.message, "{value:?}"),
+            name => write!(self.fields, " {name}={value:?}"),
+        };
+    }
+}
+
 /// Similar to the `std::dbg!` macro, but generates `tracing` events rather
 /// than printing to stdout.
 ///
@@ -78,3 +204,33 @@ macro_rules! trace_dbg {
         $crate::trace_dbg!(level: tracing::Level::DEBUG, $ex)
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tracing_subscriber::prelude::*;
+
+    use super::{LogBuffer, LogBufferLayer};
+
+    #[test]
+    fn test_log_buffer() {
+        let buffer = Arc::new(LogBuffer::new(3));
+        let subscriber = tracing_subscriber::registry().with(LogBufferLayer(buffer.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            for idx in 0..4 {
+                tracing::info!("record {idx}");
+            }
+            tracing::warn!(title = "Rust", "failed loading");
+        });
+
+        buffer.with_records(|records| {
+            // the oldest records were dropped
+            let ids: Vec<u64> = records.iter().map(|record| record.id).collect();
+            assert_eq!(ids, [2, 3, 4]);
+            assert_eq!(records[0].message, "record 2");
+            assert_eq!(records[2].level, tracing::Level::WARN);
+            assert_eq!(records[2].message, "failed loading title=\"Rust\"");
+        });
+    }
+}
@@ -1,17 +1,26 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
 use anyhow::{Context, Result};
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, oneshot, Mutex};
 use tracing::warn;
 use wiki_tui::{
-    action::{Action, ActionPacket, ActionResult},
+    action::{Action, ActionPacket, ActionResult, PageAction},
     app::AppComponent,
+    browser::Opener,
     cli::match_cli,
+    client::init_client,
     components::Component,
-    config::{load_config, load_theme, Config, Theme},
-    event::EventHandler,
+    config::{load_config, load_themes, Config, Homepage, Theme},
+    event::{Event, EventHandler},
+    images,
     logging::initialize_logging,
+    media::remove_media_dir,
     panic_handler::initialize_panic_handler,
+    print::print_page,
+    session::Session,
     terminal::Tui,
     trace_dbg,
 };
@@ -22,6 +31,10 @@ async fn main() -> Result<()> {
     let mut actions = results.actions;
 
     initialize_logging(results.log_level)?;
+
+    if let Some(options) = results.print {
+        std::process::exit(print_page(options).await);
+    }
     initialize_panic_handler()?;
 
     let (action_tx, mut action_rx) = mpsc::unbounded_channel();
@@ -29,7 +42,7 @@ async fn main() -> Result<()> {
     let app_component = Arc::new(Mutex::new(AppComponent::default()));
     let mut should_quit = false;
 
-    let config = load_config()
+    let mut config = load_config()
         .context("failed loading the config")
         .unwrap_or_else(|err| {
             warn!("{:?}", err);
@@ -41,7 +54,12 @@ async fn main() -> Result<()> {
             Config::default()
         });
 
-    let theme = load_theme()
+    if let Err(error) = init_client(&mut config.api, results.proxy) {
+        eprintln!("error: unable to set up the connection: {error:#}");
+        std::process::exit(libc::EXIT_FAILURE);
+    }
+
+    let themes = load_themes()
         .context("failed loading the theme")
         .unwrap_or_else(|err| {
             warn!("{:?}", err);
@@ -50,32 +68,83 @@ async fn main() -> Result<()> {
                 Some(ref mut action_packet) => action_packet.add_action(action),
                 None => actions = Some(ActionPacket::single(action))
             }
-            Theme::default()
+            vec![Theme::default()]
         });
 
-    app_component
-        .lock()
-        .await
-        .init(action_tx.clone(), Arc::new(config), Arc::new(theme))?;
+    if results.starts_empty {
+        let startup = startup_actions(
+            &config,
+            results.no_restore || results.homepage.is_some(),
+            results
+                .homepage
+                .unwrap_or_else(|| config.ui.homepage.clone()),
+        );
+        let action_packet = actions.get_or_insert_with(ActionPacket::default);
+        for action in startup {
+            action_packet.add_action(action);
+        }
+    }
+
+    {
+        let mut app_component = app_component.lock().await;
+        let theme = app_component.set_themes(themes);
+        app_component.init(action_tx.clone(), Arc::new(config), theme)?;
+    }
 
     let mut tui = Tui::new()?;
     tui.enter()?;
 
+    // whether the state changed since the last render, nothing is rendered otherwise
+    let is_dirty = Arc::new(AtomicBool::new(true));
+
     let _action_tx = action_tx.clone();
     let _root = app_component.clone();
+    let _is_dirty = is_dirty.clone();
+    // pauses reading the input while a program in the terminal takes it over. The event thread
+    // confirms the pause and waits for the signal to continue
+    let (pause_tx, mut pause_rx) =
+        mpsc::unbounded_channel::<(oneshot::Sender<()>, oneshot::Receiver<()>)>();
 
     // Event Thread
     tokio::spawn(async move {
         let render_tick = 20;
         let mut event_handler = EventHandler::new(render_tick);
         loop {
-            let event = event_handler.next().await;
-            if let ActionResult::Consumed(action) = _root.lock().await.handle_events(event) {
-                action.send(&_action_tx);
+            tokio::select! {
+                event = event_handler.next() => {
+                    if let ActionResult::Consumed(action) = _root.lock().await.handle_events(event) {
+                        // keys can change the state without sending an action, like typing
+                        if !matches!(event, Some(Event::RenderTick)) {
+                            _is_dirty.store(true, Ordering::Relaxed);
+                        }
+                        action.send(&_action_tx);
+                    }
+                }
+                Some((paused_tx, resume_rx)) = pause_rx.recv() => {
+                    event_handler.stop().await;
+                    let _ = paused_tx.send(());
+                    let _ = resume_rx.await;
+                    event_handler = EventHandler::new(render_tick);
+                }
             }
         }
     });
 
+    // stopping the process from outside suspends it like the suspend key
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut stop_signal = signal(SignalKind::from_raw(libc::SIGTSTP))?;
+        let _action_tx = action_tx.clone();
+        tokio::spawn(async move {
+            while stop_signal.recv().await.is_some() {
+                if _action_tx.send(Action::Suspend).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
     // Send actions to be run at startup
     if let Some(actions) = actions {
         let _action_tx = action_tx.clone();
@@ -84,6 +153,8 @@ async fn main() -> Result<()> {
         });
     }
 
+    let mut was_changing = false;
+
     // Main Loop
     loop {
         if let Some(action) = action_rx.recv().await {
@@ -94,13 +165,60 @@ async fn main() -> Result<()> {
             match action {
                 Action::RenderTick => {
                     let mut app_component = app_component.lock().await;
-                    tui.terminal
-                        .draw(|frame| app_component.render(frame, frame.size()))
-                        .unwrap();
+                    // a component that stopped changing is rendered once more in its final state
+                    let needs_redraw = app_component.needs_redraw();
+                    if is_dirty.swap(false, Ordering::Relaxed) || needs_redraw || was_changing {
+                        tui.terminal
+                            .draw(|frame| app_component.render(frame, frame.size()))?;
+                        images::draw_images(tui.terminal.backend_mut());
+                    }
+                    was_changing = needs_redraw;
                 }
                 Action::Quit => should_quit = true,
+                // processes cannot be stopped on windows
+                #[cfg(not(unix))]
+                Action::Suspend => {}
+                #[cfg(unix)]
+                Action::Suspend => {
+                    let resume_tx = pause_events(&pause_tx).await;
+                    tui.suspend()?;
+                    tui.resume()?;
+                    let _ = resume_tx.send(());
+
+                    // the terminal might have been resized while suspended
+                    is_dirty.store(true, Ordering::Relaxed);
+                    let size = tui.terminal.size()?;
+                    action_tx
+                        .send(Action::Resize(size.width, size.height))
+                        .unwrap();
+                }
+                Action::OpenInBrowser(url) => {
+                    let opener = Opener::new(&url);
+                    if opener.is_terminal {
+                        let resume_tx = pause_events(&pause_tx).await;
+                        tui.exit()?;
+                        let result = opener.run().await;
+                        tui.enter()?;
+                        is_dirty.store(true, Ordering::Relaxed);
+                        let _ = resume_tx.send(());
+                        if let Err(error) = result {
+                            browser_failed(error).send(&action_tx);
+                        }
+                    } else {
+                        let _action_tx = action_tx.clone();
+                        tokio::spawn(async move {
+                            if let Err(error) = opener.run().await {
+                                browser_failed(error).send(&_action_tx);
+                            }
+                        });
+                    }
+                }
+                // ignored actions didn't change anything that's rendered
                 action => match app_component.lock().await.update(action) {
-                    ActionResult::Consumed(action) => action.send(&action_tx),
+                    ActionResult::Consumed(action) => {
+                        is_dirty.store(true, Ordering::Relaxed);
+                        action.send(&action_tx);
+                    }
                     ActionResult::Ignored => {}
                 },
             }
@@ -111,6 +229,49 @@ async fn main() -> Result<()> {
         }
     }
 
+    app_component.lock().await.save_session();
+    remove_media_dir();
     tui.exit()?;
     Ok(())
 }
+
+/// Stops reading the input until the returned sender is used, so that the terminal can be handed
+/// to another program
+async fn pause_events(
+    pause_tx: &mpsc::UnboundedSender<(oneshot::Sender<()>, oneshot::Receiver<()>)>,
+) -> oneshot::Sender<()> {
+    let (paused_tx, paused_rx) = oneshot::channel();
+    let (resume_tx, resume_rx) = oneshot::channel();
+    if pause_tx.send((paused_tx, resume_rx)).is_ok() {
+        let _ = paused_rx.await;
+    }
+    resume_tx
+}
+
+/// Returns the actions opening the page of the last session, or the homepage when there is no
+/// session to restore. A homepage given in the arguments takes precedence over the session
+fn startup_actions(config: &Config, skip_session: bool, homepage: Homepage) -> Vec<Action> {
+    if config.ui.restore_session && !skip_session {
+        match Session::open() {
+            Ok(store) => {
+                if let Some(page) = store.get().page.clone() {
+                    return vec![Action::ExitSearchBar, Action::RestoreSession(page)];
+                }
+            }
+            // a broken session must not prevent starting
+            Err(error) => warn!("{:?}", error.context("failed loading the last session")),
+        }
+    }
+
+    match homepage {
+        Homepage::Search => Vec::new(),
+        homepage => vec![Action::ExitSearchBar, Action::LoadHomepage(homepage)],
+    }
+}
+
+/// Reports that the browser could not be opened in the status bar of the page
+fn browser_failed(error: anyhow::Error) -> ActionPacket {
+    let error = error.context("Unable to open the browser");
+    warn!("{:?}", error);
+    ActionPacket::single(Action::Page(PageAction::ShowNotice(format!("{error:#}"))))
+}
@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use tokio::io::AsyncWriteExt;
+use tracing::warn;
+use wiki_api::page::{request_file, MediaFile};
+
+/// Returns the directory the media files of this process are downloaded to. It's removed when
+/// the program exits
+pub fn media_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("wiki-tui-media-{}", std::process::id()))
+}
+
+/// Removes the downloaded media files
+pub fn remove_media_dir() {
+    let dir = media_dir();
+    if !dir.exists() {
+        return;
+    }
+    if let Err(error) = std::fs::remove_dir_all(&dir) {
+        warn!(
+            "failed removing the media files in '{}': {error}",
+            dir.display()
+        );
+    }
+}
+
+/// Downloads the file into the media directory and returns its path. The progress is reported
+/// with the downloaded and the total number of bytes, when the size is known
+pub async fn download(
+    file: &MediaFile,
+    mut progress: impl FnMut(u64, Option<u64>),
+) -> Result<PathBuf> {
+    let url = file.download_url();
+    let name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("media");
+
+    let dir = media_dir();
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .with_context(|| format!("failed creating the directory '{}'", dir.display()))?;
+    let path = dir.join(name);
+
+    let mut response = request_file(url.clone()).await?;
+    let total = response.content_length();
+    let mut output = tokio::fs::File::create(&path)
+        .await
+        .with_context(|| format!("failed creating '{}'", path.display()))?;
+
+    let mut downloaded = 0;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .context("failed receiving the file")?
+    {
+        output
+            .write_all(&chunk)
+            .await
+            .with_context(|| format!("failed writing '{}'", path.display()))?;
+        downloaded += chunk.len() as u64;
+        progress(downloaded, total);
+    }
+    output
+        .flush()
+        .await
+        .with_context(|| format!("failed writing '{}'", path.display()))?;
+
+    Ok(path)
+}
+
+/// Describes the progress of a download, in percent when the size of the file is known
+pub fn format_progress(downloaded: u64, total: Option<u64>) -> String {
+    match total.filter(|total| *total > 0) {
+        Some(total) => format!("{}%", (downloaded * 100 / total).min(100)),
+        None => format!("{} KB", downloaded / 1024),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_progress;
+
+    #[test]
+    fn test_format_progress() {
+        assert_eq!(format_progress(512, Some(2048)), "25%");
+        assert_eq!(format_progress(2048, Some(2048)), "100%");
+        assert_eq!(format_progress(4096, None), "4 KB");
+        assert_eq!(format_progress(4096, Some(0)), "4 KB");
+    }
+}
@@ -0,0 +1,385 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use wiki_api::{languages::Language, Endpoint};
+
+use crate::{config::data_dir, store::Store};
+
+pub const PAGE_CACHE_STORE: &str = "page_cache";
+/// Directory in the data directory holding the cached responses
+const PAGE_CACHE_DIR: &str = "page_cache";
+
+/// Returns the current time in seconds since the unix epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Identifies a cached page by the request it was fetched with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    pub endpoint: Endpoint,
+    pub language: Language,
+    pub title: String,
+    /// The old revision of the page, `None` for the current revision
+    pub oldid: Option<usize>,
+}
+
+impl CacheKey {
+    /// Identifies the page regardless of its revision
+    fn page_id(&self) -> String {
+        format!("{} {} {}", self.endpoint, self.language.code(), self.title)
+    }
+
+    /// Identifies a revision of the page
+    fn id(&self, revision_id: usize) -> String {
+        format!(
+            "{} {} {} {}",
+            self.endpoint,
+            self.language.code(),
+            revision_id,
+            self.title
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct CacheEntry {
+    /// Name of the file in the cache directory holding the response
+    file: String,
+    /// Size of the file in bytes
+    size: u64,
+    /// When the response was fetched, in seconds since the unix epoch
+    fetched: u64,
+    /// When the page was last read from the cache or fetched, in seconds since the unix epoch
+    used: u64,
+    /// The revision of the page the response holds
+    #[serde(default)]
+    revision_id: usize,
+}
+
+/// The cached pages by the id of their revision
+#[derive(Serialize, Deserialize, Default)]
+pub struct CacheIndex {
+    entries: HashMap<String, CacheEntry>,
+    /// The id of the latest cached revision of every page, by the id of the page
+    #[serde(default)]
+    latest: HashMap<String, String>,
+}
+
+impl CacheIndex {
+    /// Returns the id of the cached revision of the page, the latest one for the current revision
+    fn entry_id(&self, key: &CacheKey) -> Option<String> {
+        match key.oldid {
+            Some(oldid) => Some(key.id(oldid)),
+            None => self.latest.get(&key.page_id()).cloned(),
+        }
+    }
+
+    fn total_size(&self) -> u64 {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+
+    /// Removes the least recently used entries until the cached pages fit into the size. Returns
+    /// the files of the removed entries
+    fn evict(&mut self, max_size: u64) -> Vec<String> {
+        let mut by_use: Vec<(String, u64)> = self
+            .entries
+            .iter()
+            .map(|(id, entry)| (id.clone(), entry.used))
+            .collect();
+        by_use.sort_by_key(|(_, used)| *used);
+
+        let mut total_size = self.total_size();
+        let mut files = Vec::new();
+        for (id, _) in by_use {
+            if total_size <= max_size {
+                break;
+            }
+            if let Some(entry) = self.entries.remove(&id) {
+                total_size -= entry.size;
+                files.push(entry.file);
+            }
+        }
+        let entries = &self.entries;
+        self.latest.retain(|_, id| entries.contains_key(id));
+        files
+    }
+}
+
+/// A response fetched before
+pub struct CachedResponse {
+    pub response: serde_json::Value,
+    /// When the response was fetched, in seconds since the unix epoch
+    pub fetched: u64,
+    /// The revision of the page the response holds
+    pub revision_id: usize,
+}
+
+impl CachedResponse {
+    /// Returns whether the response was fetched less than `max_age` seconds before `now`
+    pub fn is_fresh(&self, max_age: u64, now: u64) -> bool {
+        now.saturating_sub(self.fetched) < max_age
+    }
+}
+
+/// The responses of fetched pages, stored in the data directory so pages can be read without a
+/// connection. The responses are cached per revision, the current revision of a page is its latest
+/// cached one. The least recently used pages are removed when the cache exceeds its size
+///
+/// Reading a page only marks it as used in memory, the index is written when a page is cached
+pub struct PageCache {
+    index: Store<CacheIndex>,
+    dir: PathBuf,
+    /// Maximum total size of the cached responses in bytes
+    max_size: u64,
+}
+
+impl PageCache {
+    pub fn open(max_size: u64) -> Result<Self> {
+        let data_dir = data_dir().context("failed retrieving the data dir")?;
+        Self::open_in(&data_dir, max_size)
+    }
+
+    /// Opens the cache with its index and responses in the directory
+    fn open_in(data_dir: &Path, max_size: u64) -> Result<Self> {
+        let dir = data_dir.join(PAGE_CACHE_DIR);
+        std::fs::create_dir_all(&dir).context("failed creating the cache directory")?;
+        Ok(Self {
+            index: Store::open_path(data_dir.join(format!("{PAGE_CACHE_STORE}.json")))?,
+            dir,
+            max_size,
+        })
+    }
+
+    /// Returns the cached response of the page and marks it as used
+    pub fn get(&mut self, key: &CacheKey) -> Result<Option<CachedResponse>> {
+        let Some(id) = self.index.get().entry_id(key) else {
+            return Ok(None);
+        };
+        let entry = match self.index.get_mut().entries.get_mut(&id) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        entry.used = now();
+        let fetched = entry.fetched;
+        let revision_id = entry.revision_id;
+        let path = self.dir.join(&entry.file);
+
+        let response = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed reading the cached page '{}'", path.display()))
+            .and_then(|response| {
+                serde_json::from_str(&response).context("failed parsing the cached page")
+            });
+        let response = match response {
+            Ok(response) => response,
+            Err(error) => {
+                // a broken entry is removed, so the page is fetched again
+                let index = self.index.get_mut();
+                index.entries.remove(&id);
+                index.latest.retain(|_, latest| *latest != id);
+                let _ = std::fs::remove_file(&path);
+                self.index.save()?;
+                return Err(error);
+            }
+        };
+        Ok(Some(CachedResponse {
+            response,
+            fetched,
+            revision_id,
+        }))
+    }
+
+    /// Marks the cached current revision of the page as fetched now, after the site confirmed
+    /// that it's still the latest revision
+    pub fn confirm(&mut self, key: &CacheKey) {
+        let index = self.index.get_mut();
+        let Some(id) = index.entry_id(key) else {
+            return;
+        };
+        if let Some(entry) = index.entries.get_mut(&id) {
+            entry.fetched = now();
+        }
+    }
+
+    /// Caches the response holding the revision of the page. For the current revision, it
+    /// replaces the cached current revision
+    pub fn insert(
+        &mut self,
+        key: &CacheKey,
+        revision_id: usize,
+        response: &serde_json::Value,
+    ) -> Result<()> {
+        let id = key.id(revision_id);
+        let mut hasher = DefaultHasher::new();
+        id.hash(&mut hasher);
+        let file = format!("{:016x}.json", hasher.finish());
+
+        let response = serde_json::to_string(response).context("failed serializing the page")?;
+        std::fs::write(self.dir.join(&file), &response)
+            .context("failed writing the cached page")?;
+
+        let now = now();
+        let index = self.index.get_mut();
+        index.entries.insert(
+            id.clone(),
+            CacheEntry {
+                file,
+                size: response.len() as u64,
+                fetched: now,
+                used: now,
+                revision_id,
+            },
+        );
+        // the previous current revision stays cached as an old revision until it's evicted
+        if key.oldid.is_none() {
+            index.latest.insert(key.page_id(), id);
+        }
+        for file in index.evict(self.max_size) {
+            debug!("evicting '{file}' from the page cache");
+            let _ = std::fs::remove_file(self.dir.join(file));
+        }
+        self.index.save()
+    }
+}
+
+impl Drop for PageCache {
+    fn drop(&mut self) {
+        // keeps the times the pages were read for evicting the least recently used ones
+        let _ = self.index.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiki_api::{languages::Language, Endpoint};
+
+    use super::{CacheEntry, CacheIndex, CacheKey, CachedResponse, PageCache};
+
+    fn key(title: &str, oldid: Option<usize>) -> CacheKey {
+        CacheKey {
+            endpoint: Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+            language: Language::English,
+            title: title.to_string(),
+            oldid,
+        }
+    }
+
+    /// Opens an empty cache in a directory of its own
+    fn cache(name: &str, max_size: u64) -> (PageCache, std::path::PathBuf) {
+        let dir =
+            std::env::temp_dir().join(format!("wiki-tui-page-cache-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        (PageCache::open_in(&dir, max_size).unwrap(), dir)
+    }
+
+    fn revision(cache: &mut PageCache, key: &CacheKey) -> Option<usize> {
+        let cached = cache.get(key).unwrap()?;
+        assert_eq!(cached.response["revision"], json!(cached.revision_id));
+        Some(cached.revision_id)
+    }
+
+    #[test]
+    fn test_revision_keying() {
+        let (mut cache, dir) = cache("revisions", u64::MAX);
+        let rust = key("Rust", None);
+        assert_eq!(revision(&mut cache, &rust), None);
+
+        cache.insert(&rust, 1, &json!({ "revision": 1 })).unwrap();
+        assert_eq!(revision(&mut cache, &rust), Some(1));
+
+        // a new revision replaces the current one, the previous one is still cached as an old
+        // revision
+        cache.insert(&rust, 2, &json!({ "revision": 2 })).unwrap();
+        assert_eq!(revision(&mut cache, &rust), Some(2));
+        assert_eq!(revision(&mut cache, &key("Rust", Some(1))), Some(1));
+        assert_eq!(revision(&mut cache, &key("Rust", Some(3))), None);
+
+        // caching an old revision doesn't change the current one
+        cache
+            .insert(&key("Rust", Some(0)), 0, &json!({ "revision": 0 }))
+            .unwrap();
+        assert_eq!(revision(&mut cache, &rust), Some(2));
+        assert_eq!(revision(&mut cache, &key("Iron", None)), None);
+
+        // the index is kept when the cache is opened again
+        drop(cache);
+        let mut cache = PageCache::open_in(&dir, u64::MAX).unwrap();
+        assert_eq!(revision(&mut cache, &rust), Some(2));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_evict_cached_pages() {
+        let response = json!({ "revision": 1, "text": "x".repeat(100) });
+        let size = serde_json::to_string(&response).unwrap().len() as u64;
+        let (mut cache, dir) = cache("evict", size * 2);
+
+        cache.insert(&key("Rust", None), 1, &response).unwrap();
+        cache.insert(&key("Iron", None), 1, &response).unwrap();
+        // reading a page keeps it in the cache
+        for entry in cache.index.get_mut().entries.values_mut() {
+            entry.used = 0;
+        }
+        assert!(cache.get(&key("Rust", None)).unwrap().is_some());
+
+        cache.insert(&key("Oxygen", None), 1, &response).unwrap();
+        assert!(cache.get(&key("Iron", None)).unwrap().is_none());
+        assert!(cache.get(&key("Rust", None)).unwrap().is_some());
+        assert!(cache.get(&key("Oxygen", None)).unwrap().is_some());
+        assert_eq!(
+            std::fs::read_dir(dir.join("page_cache")).unwrap().count(),
+            2
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_cached_response_expiry() {
+        let cached = CachedResponse {
+            response: json!({}),
+            fetched: 1000,
+            revision_id: 1,
+        };
+        assert!(cached.is_fresh(60, 1000));
+        assert!(cached.is_fresh(60, 1059));
+        assert!(!cached.is_fresh(60, 1060));
+        // without a maximum age, the page is always checked
+        assert!(!cached.is_fresh(0, 1000));
+        // a clock set back doesn't expire the page
+        assert!(cached.is_fresh(60, 900));
+    }
+
+    #[test]
+    fn test_evict_least_recently_used() {
+        let mut index = CacheIndex::default();
+        for (id, size, used) in [("a", 40, 3), ("b", 30, 1), ("c", 20, 2), ("d", 10, 4)] {
+            index.entries.insert(
+                id.to_string(),
+                CacheEntry {
+                    file: format!("{id}.json"),
+                    size,
+                    fetched: 0,
+                    used,
+                    revision_id: 0,
+                },
+            );
+        }
+
+        assert!(index.evict(100).is_empty());
+        assert_eq!(index.evict(60), ["b.json", "c.json"]);
+        assert_eq!(index.total_size(), 50);
+        assert_eq!(index.evict(0), ["a.json", "d.json"]);
+    }
+}
@@ -1,32 +1,749 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
-use tokio::sync::mpsc::UnboundedSender;
-use tracing::error;
+use anyhow::{Context, Result};
+use tokio::{
+    sync::{mpsc::UnboundedSender, Semaphore},
+    task::AbortHandle,
+};
+use tracing::{debug, error, info, warn};
 use wiki_api::{
     languages::Language,
-    page::{LanguageLink, Link, Page, Property},
+    page::{
+        associated_title, featured_title, page_source, random_title, revisions, site_namespaces,
+        ArticleUrl, ConnectionFailed, LanguageLink, Link, NoPageID, Page, PageBuilder,
+        PageNotFound, Property, Revision, SiteNamespace, WithEndpoint, WithLanguage, WithPage,
+    },
+    rate_limit::{with_priority, Priority},
     search::SearchResult,
     Endpoint,
 };
 
 use crate::{
-    action::{Action, PageViewerAction},
-    config::Config,
+    action::{Action, MessageLevel, PageAction, PageViewerAction, SearchAction},
+    client::proxy_url,
+    config::{Config, Homepage},
+    page_cache::{now, CacheKey, CachedResponse, PageCache},
+    retry::{describe_error, RetryPolicy},
+    session::SessionPage,
 };
 
+/// What to do with a fetched page
+enum LoadKind {
+    /// Display the page as the newest page of the history
+    Display,
+    /// Display the first result of a lucky search and show a notice naming the page opened for
+    /// the query
+    Lucky(String),
+    /// Display the page, or search for the title when there is no page with it
+    OrSearch(String),
+    /// Restore a page of the history that was dropped to save memory
+    Restore,
+    /// Display the page and jump to the header with the anchor
+    Section(Option<String>),
+    /// Display an old revision of the page
+    Revision(Revision),
+    /// Display the page of the last session at its line and selected link
+    Session(u16, (usize, usize)),
+    /// Replace the current page with the page fetched again
+    Refresh,
+    /// Display the page fetched again, ignoring the cached page
+    Fresh,
+}
+
+impl LoadKind {
+    /// Returns the action loading the page again the same way
+    fn retry_action(
+        &self,
+        endpoint: Endpoint,
+        language: Language,
+        title: String,
+        oldid: Option<usize>,
+    ) -> Action {
+        match self {
+            LoadKind::Display | LoadKind::Lucky(_) | LoadKind::OrSearch(_) => {
+                Action::LoadArticleUrl(ArticleUrl {
+                    title,
+                    language,
+                    endpoint,
+                    anchor: None,
+                })
+            }
+            LoadKind::Section(anchor) => Action::LoadArticleUrl(ArticleUrl {
+                title,
+                language,
+                endpoint,
+                anchor: anchor.clone(),
+            }),
+            LoadKind::Revision(revision) => {
+                Action::LoadRevision(endpoint, language, title, revision.clone())
+            }
+            LoadKind::Session(y, selected) => Action::RestoreSession(SessionPage {
+                title,
+                language,
+                endpoint,
+                y: *y,
+                selected: *selected,
+            }),
+            LoadKind::Restore => Action::ReloadPage(endpoint, language, title, oldid),
+            LoadKind::Refresh => Action::RefreshPage(endpoint, language, title, oldid),
+            LoadKind::Fresh => Action::LoadFreshPage(endpoint, language, title),
+        }
+    }
+}
+
+type PageRequest = PageBuilder<NoPageID, WithPage, WithEndpoint, WithLanguage>;
+
+/// Number of redirect pages followed to reach a page, longer chains are most likely a loop
+const MAX_REDIRECT_HOPS: usize = 5;
+/// Number of prefetched pages kept in memory
+const PREFETCHED_PAGES: usize = 5;
+/// Number of pages prefetched at the same time
+const PREFETCH_CONCURRENCY: usize = 2;
+/// Time between the checks whether the site can be reached again while offline
+const OFFLINE_PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The prefetched pages, the least recently used page is dropped first
+#[derive(Default)]
+struct PrefetchedPages {
+    /// The most recently used page first
+    pages: VecDeque<(CacheKey, FetchedPage)>,
+}
+
+impl PrefetchedPages {
+    fn contains(&self, key: &CacheKey) -> bool {
+        self.pages.iter().any(|(page_key, _)| page_key == key)
+    }
+
+    fn insert(&mut self, key: CacheKey, page: FetchedPage) {
+        self.pages.retain(|(page_key, _)| *page_key != key);
+        self.pages.push_front((key, page));
+        self.pages.truncate(PREFETCHED_PAGES);
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<FetchedPage> {
+        let idx = self
+            .pages
+            .iter()
+            .position(|(page_key, _)| page_key == key)?;
+        let entry = self.pages.remove(idx)?;
+        let page = entry.1.clone();
+        self.pages.push_front(entry);
+        Some(page)
+    }
+}
+
+/// Fetches the pages of links in the background, so they're displayed right away when the links
+/// are opened. Prefetching is cancelled when a page is loaded
+struct Prefetcher {
+    pages: Arc<Mutex<PrefetchedPages>>,
+    /// The pages being prefetched
+    running: Arc<Mutex<Vec<(CacheKey, AbortHandle)>>>,
+    /// The pages that couldn't be prefetched, they aren't prefetched again. Cancelled pages are
+    /// prefetched again when their links are still shown
+    failed: Arc<Mutex<Vec<CacheKey>>>,
+    permits: Arc<Semaphore>,
+}
+
+impl Prefetcher {
+    fn new() -> Self {
+        Self {
+            pages: Arc::default(),
+            running: Arc::default(),
+            failed: Arc::default(),
+            permits: Arc::new(Semaphore::new(PREFETCH_CONCURRENCY)),
+        }
+    }
+
+    fn prefetch(&self, fetcher: PageFetcher, key: CacheKey) {
+        let mut running = self.running.lock().unwrap();
+        if running.iter().any(|(running_key, _)| *running_key == key)
+            || self.pages.lock().unwrap().contains(&key)
+            || self.failed.lock().unwrap().contains(&key)
+        {
+            return;
+        }
+
+        let pages = self.pages.clone();
+        let running_tasks = self.running.clone();
+        let failed = self.failed.clone();
+        let permits = self.permits.clone();
+        let task_key = key.clone();
+        let task = tokio::spawn(async move {
+            let key = task_key;
+            if let Ok(_permit) = permits.acquire().await {
+                debug!("prefetching '{}'", key.title);
+                let fetch = fetcher.fetch(
+                    key.endpoint.clone(),
+                    key.language,
+                    key.title.clone(),
+                    None,
+                    false,
+                );
+                let result = with_priority(Priority::Prefetch, fetch).await;
+                match result {
+                    Ok(fetched) => pages.lock().unwrap().insert(key.clone(), fetched),
+                    Err(error) => {
+                        debug!("unable to prefetch '{}': {:?}", key.title, error);
+                        failed.lock().unwrap().push(key.clone());
+                    }
+                }
+            }
+            running_tasks
+                .lock()
+                .unwrap()
+                .retain(|(running_key, _)| *running_key != key);
+        });
+        running.push((key, task.abort_handle()));
+    }
+
+    /// Cancels prefetching, so the pages requested by the user are fetched first
+    fn cancel(&self) {
+        for (key, task) in self.running.lock().unwrap().drain(..) {
+            debug!("cancelled prefetching '{}'", key.title);
+            task.abort();
+        }
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<FetchedPage> {
+        self.pages.lock().unwrap().get(key)
+    }
+}
+
+/// The page isn't cached while being offline
+///
+/// Fetching a page fails with this error, it can be detected by downcasting the error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotCached(pub String);
+
+impl Display for NotCached {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "'{}' is not cached, it can only be read offline after reading it online",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for NotCached {}
+
+/// A page fetched from the site or read from the cache
+#[derive(Clone)]
+struct FetchedPage {
+    page: Page,
+    /// When the page was fetched, in seconds since the unix epoch. `None` when it wasn't read
+    /// from the cache
+    cached: Option<u64>,
+}
+
+impl FetchedPage {
+    /// Sends the page to the page viewer, marked as cached when it was read from the cache
+    fn send(self, tx: &UnboundedSender<Action>, action: impl FnOnce(Page) -> PageViewerAction) {
+        tx.send(Action::PageViewer(action(self.page))).unwrap();
+        if let Some(fetched) = self.cached {
+            tx.send(Action::Page(PageAction::MarkCached(fetched)))
+                .unwrap();
+        }
+    }
+}
+
+/// Fetches the pages through the page cache. Recently cached pages are read from the cache and
+/// while offline, only cached pages can be read
+#[derive(Clone)]
+struct PageFetcher {
+    /// `None` when the cache is disabled or couldn't be opened
+    cache: Option<Arc<Mutex<PageCache>>>,
+    /// The namespaces of the sites, fetched once per site
+    namespaces: Arc<Mutex<HashMap<Endpoint, Vec<SiteNamespace>>>>,
+    is_offline: Arc<AtomicBool>,
+    /// Whether the connection is checked while offline
+    is_probing: Arc<AtomicBool>,
+    /// Seconds a cached page is read without checking for a newer revision
+    max_age: u64,
+    redirects: bool,
+    retry: RetryPolicy,
+    action_tx: UnboundedSender<Action>,
+
+    config: Arc<Config>,
+}
+
+impl PageFetcher {
+    fn new(config: Arc<Config>, action_tx: UnboundedSender<Action>) -> Self {
+        let cache = match config.api.cache_size {
+            0 => None,
+            size => match PageCache::open(size * 1024 * 1024) {
+                Ok(cache) => Some(Arc::new(Mutex::new(cache))),
+                Err(error) => {
+                    warn!("{:?}", error.context("Unable to open the page cache"));
+                    None
+                }
+            },
+        };
+        Self {
+            cache,
+            namespaces: Arc::default(),
+            is_offline: Arc::new(AtomicBool::new(false)),
+            is_probing: Arc::new(AtomicBool::new(false)),
+            max_age: config.api.cache_max_age * 60 * 60,
+            redirects: config.api.page_redirects,
+            retry: RetryPolicy::new(&config.api),
+            action_tx,
+
+            config,
+        }
+    }
+
+    /// Sends the request with the retry policy, the loading indicator shows when it's retried
+    async fn retry<T, F, Fut>(&self, request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let tx = self.action_tx.clone();
+        self.retry
+            .run(request, |retry| {
+                tx.send(Action::PageViewer(PageViewerAction::SetLoadingStatus(
+                    retry.to_string(),
+                )))
+                .unwrap()
+            })
+            .await
+    }
+
+    /// Names the wiki the page belongs to, like "German Wikipedia"
+    fn wiki_name(&self, key: &CacheKey) -> String {
+        match self.config.api.site_of(&key.endpoint) {
+            Some(site) => site.wiki_name(key.language),
+            None => key.endpoint.host_str().unwrap_or_default().to_string(),
+        }
+    }
+
+    /// Returns the namespaces of the site with their localized names and aliases. They are only
+    /// fetched the first time, afterwards the fetched namespaces are returned
+    async fn site_namespaces(&self, endpoint: &Endpoint) -> Result<Vec<SiteNamespace>> {
+        if let Some(namespaces) = self.namespaces.lock().unwrap().get(endpoint) {
+            return Ok(namespaces.clone());
+        }
+
+        let namespaces = self.retry(|| site_namespaces(endpoint.clone())).await?;
+        self.namespaces
+            .lock()
+            .unwrap()
+            .insert(endpoint.clone(), namespaces.clone());
+        Ok(namespaces)
+    }
+
+    /// Returns the namespaces the page is classified with. Without them, only the canonical
+    /// namespace names are understood, so a failure is only logged. While offline, only the
+    /// namespaces fetched before are known
+    async fn classifying_namespaces(&self, endpoint: &Endpoint) -> Vec<SiteNamespace> {
+        if self.is_offline.load(Ordering::SeqCst) {
+            let namespaces = self.namespaces.lock().unwrap();
+            return namespaces.get(endpoint).cloned().unwrap_or_default();
+        }
+
+        self.site_namespaces(endpoint)
+            .await
+            .unwrap_or_else(|error| {
+                debug!("unable to fetch the namespaces of the site: {:?}", error);
+                Vec::new()
+            })
+    }
+
+    fn request(&self, key: &CacheKey) -> PageRequest {
+        let request = Page::builder()
+            .page(key.title.clone())
+            .properties(vec![
+                Property::Text,
+                Property::Sections,
+                Property::LangLinks,
+                Property::Properties,
+            ])
+            .endpoint(key.endpoint.clone())
+            .language(key.language)
+            .redirects(self.redirects);
+        match key.oldid {
+            Some(oldid) => request.oldid(oldid),
+            None => request,
+        }
+    }
+
+    /// Reads the cached page. The cache is read on a blocking thread, it reads from the disk
+    async fn cached(&self, key: &CacheKey) -> Option<CachedResponse> {
+        let cache = self.cache.clone()?;
+        let key = key.clone();
+        let result = tokio::task::spawn_blocking(move || cache.lock().unwrap().get(&key))
+            .await
+            .context("the page cache task failed")
+            .and_then(|result| result);
+        match result {
+            Ok(cached) => cached,
+            Err(error) => {
+                warn!("{:?}", error.context("Unable to read the cached page"));
+                None
+            }
+        }
+    }
+
+    /// Caches the response of the revision in the background
+    fn store(&self, key: &CacheKey, revision_id: usize, response: serde_json::Value) {
+        if let Some(cache) = self.cache.clone() {
+            let key = key.clone();
+            tokio::task::spawn_blocking(move || {
+                if let Err(error) = cache.lock().unwrap().insert(&key, revision_id, &response) {
+                    warn!("{:?}", error.context("Unable to cache the page"));
+                }
+            });
+        }
+    }
+
+    /// Returns whether the cached revision is still the current revision of the page. The cached
+    /// page counts as fetched now when it is
+    async fn is_latest_revision(&self, key: &CacheKey, revision_id: usize) -> bool {
+        let latest = match revisions(key.endpoint.clone(), &key.title, 1, None).await {
+            Ok(revisions) => revisions.revisions.first().map(|revision| revision.id),
+            Err(error) => {
+                debug!(
+                    "unable to check the revision of the cached page: {:?}",
+                    error
+                );
+                return false;
+            }
+        };
+        if latest != Some(revision_id) {
+            return false;
+        }
+
+        if let Some(cache) = self.cache.clone() {
+            let key = key.clone();
+            tokio::task::spawn_blocking(move || cache.lock().unwrap().confirm(&key));
+        }
+        true
+    }
+
+    /// Checks whether the site can be reached again every [`OFFLINE_PROBE_INTERVAL`] while
+    /// offline and leaves the offline mode when it can. Only one check runs at a time
+    fn probe_connection(&self, endpoint: Endpoint) {
+        if self.is_probing.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let is_probing = ResetOnDrop(self.is_probing.clone());
+        let is_offline = self.is_offline.clone();
+        let tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let _is_probing = is_probing;
+            loop {
+                tokio::time::sleep(OFFLINE_PROBE_INTERVAL).await;
+                // the offline mode was left in the meantime
+                if !is_offline.load(Ordering::SeqCst) {
+                    return;
+                }
+                match with_priority(Priority::Background, random_title(endpoint.clone())).await {
+                    Err(error) if error.chain().any(|cause| cause.is::<ConnectionFailed>()) => {
+                        debug!("the site still cannot be reached");
+                    }
+                    _ => {
+                        info!("the site can be reached again, leaving the offline mode");
+                        let _ = tx.send(Action::SetOffline(false));
+                        return;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Fetches the page and follows the redirect pages the site didn't resolve, up to
+    /// [`MAX_REDIRECT_HOPS`] redirects. The page remembers the title of the first redirect
+    async fn fetch(
+        &self,
+        endpoint: Endpoint,
+        language: Language,
+        title: String,
+        oldid: Option<usize>,
+        refresh: bool,
+    ) -> Result<FetchedPage> {
+        let mut fetched = self
+            .fetch_page(endpoint, language, title, oldid, refresh)
+            .await?;
+        // old revisions of a redirect are shown as they are
+        if !self.redirects || oldid.is_some() {
+            return Ok(fetched);
+        }
+
+        let redirected_from = fetched
+            .page
+            .redirected_from
+            .clone()
+            .unwrap_or_else(|| fetched.page.title.clone());
+        let mut visited = vec![fetched.page.title.clone()];
+        while let Some(Link::Internal(target)) = fetched.page.content.redirect_target().cloned() {
+            if visited.len() > MAX_REDIRECT_HOPS || visited.contains(&target.title) {
+                warn!(
+                    "not following the redirect from '{}' to '{}', the redirects form a loop or are too long",
+                    fetched.page.title, target.title
+                );
+                break;
+            }
+
+            debug!(
+                "following the redirect from '{}' to '{}'",
+                fetched.page.title, target.title
+            );
+            visited.push(target.title.clone());
+            fetched = self
+                .fetch_page(target.endpoint, target.language, target.page, None, refresh)
+                .await?;
+        }
+
+        if fetched.page.title != redirected_from {
+            fetched.page.redirected_from = Some(redirected_from);
+        }
+        Ok(fetched)
+    }
+
+    /// Fetches a single page. Refreshing ignores the cached page and fetches it even while
+    /// offline. When the site cannot be reached, the cached page is read and the offline mode is
+    /// entered
+    async fn fetch_page(
+        &self,
+        endpoint: Endpoint,
+        language: Language,
+        title: String,
+        oldid: Option<usize>,
+        refresh: bool,
+    ) -> Result<FetchedPage> {
+        let key = CacheKey {
+            endpoint,
+            language,
+            title,
+            oldid,
+        };
+        let is_offline = self.is_offline.load(Ordering::SeqCst) && !refresh;
+        let cached = match refresh {
+            true => None,
+            false => self.cached(&key).await,
+        };
+
+        if let Some(ref cached) = cached {
+            // old revisions never change, the current revision is checked once the cached page
+            // is older than the maximum age
+            if key.oldid.is_some()
+                || is_offline
+                || cached.is_fresh(self.max_age, now())
+                || self.is_latest_revision(&key, cached.revision_id).await
+            {
+                let namespaces = self.classifying_namespaces(&key.endpoint).await;
+                let page = self
+                    .request(&key)
+                    .namespaces(namespaces)
+                    .from_response(cached.response.clone())?;
+                return Ok(FetchedPage {
+                    page,
+                    cached: Some(cached.fetched),
+                });
+            }
+        }
+        if is_offline {
+            return Err(NotCached(key.title).into());
+        }
+
+        // the namespaces are only fetched for the first page of a site, along with the page
+        let request = self.request(&key);
+        let (namespaces, response) = tokio::join!(
+            self.classifying_namespaces(&key.endpoint),
+            self.retry(|| request.fetch_response())
+        );
+        let request = request.namespaces(namespaces);
+        match response {
+            Ok(response) => {
+                let page = request.from_response(response.clone())?;
+                if let Some(revision_id) = key.oldid.or(page.revision_id) {
+                    self.store(&key, revision_id, response);
+                }
+                Ok(FetchedPage { page, cached: None })
+            }
+            Err(error) if error.chain().any(|cause| cause.is::<ConnectionFailed>()) => {
+                info!("the site cannot be reached, entering the offline mode");
+                self.action_tx.send(Action::SetOffline(true)).unwrap();
+                self.probe_connection(key.endpoint.clone());
+                match cached {
+                    Some(cached) => Ok(FetchedPage {
+                        page: request.from_response(cached.response)?,
+                        cached: Some(cached.fetched),
+                    }),
+                    None => Err(error),
+                }
+            }
+            Err(error) if error.chain().any(|cause| cause.is::<PageNotFound>()) => {
+                let message = format!(
+                    "Article '{}' does not exist in {}",
+                    key.title,
+                    self.wiki_name(&key)
+                );
+                Err(error.context(message))
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns the message shown for a failed page load, a readable explanation when there is one
+    /// and the causes of the error otherwise
+    fn load_error_message(
+        &self,
+        error: &anyhow::Error,
+        endpoint: &Endpoint,
+        context: &str,
+    ) -> String {
+        if error.chain().any(|cause| cause.is::<PageNotFound>()) {
+            return error.to_string();
+        }
+        describe_error(error, proxy_url(&self.config.api, endpoint).as_deref())
+            .unwrap_or_else(|| format!("{context}: {error:#}"))
+    }
+
+    /// Sends the error of a failed page load, the load can be tried again in a popup with the
+    /// retry action. Pages that don't exist are shown as an error message and pages that aren't
+    /// cached while offline only as an information
+    fn send_load_error(
+        &self,
+        error: anyhow::Error,
+        endpoint: &Endpoint,
+        context: &'static str,
+        retry: Action,
+    ) {
+        let tx = &self.action_tx;
+        tx.send(Action::PageViewer(PageViewerAction::ExitLoading))
+            .unwrap();
+        if let Some(not_cached) = error.downcast_ref::<NotCached>() {
+            info!("{}", not_cached);
+            tx.send(Action::ShowMessage(
+                MessageLevel::Info,
+                not_cached.to_string(),
+            ))
+            .unwrap();
+            return;
+        }
+        let message = self.load_error_message(&error, endpoint, context);
+        let action = match error.chain().any(|cause| cause.is::<PageNotFound>()) {
+            true => Action::ShowMessage(MessageLevel::Error, message),
+            false => Action::ShowRetryMessage(message, Box::new(retry.into())),
+        };
+        tx.send(action).unwrap();
+        error!("{:?}", error.context(context));
+    }
+}
+
+/// Resets the flag when dropped, also when the task holding it is cancelled
+struct ResetOnDrop(Arc<AtomicBool>);
+
+impl Drop for ResetOnDrop {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
 /// Responsible for loading a page
 pub struct PageLoader {
-    config: Arc<Config>,
     action_tx: UnboundedSender<Action>,
+    fetcher: PageFetcher,
+    /// `None` when prefetching is disabled
+    prefetcher: Option<Prefetcher>,
+    /// Whether a random page is being loaded, further random pages are ignored until it's loaded
+    is_loading_random: Arc<AtomicBool>,
+    /// The task loading the page that's displayed next, loading another page cancels it
+    loading: Mutex<Option<AbortHandle>>,
 }
 
 impl PageLoader {
     pub fn new(config: Arc<Config>, action_tx: UnboundedSender<Action>) -> Self {
-        Self { config, action_tx }
+        Self {
+            fetcher: PageFetcher::new(config.clone(), action_tx.clone()),
+            prefetcher: config.api.prefetch.then(Prefetcher::new),
+            action_tx,
+            is_loading_random: Arc::new(AtomicBool::new(false)),
+            loading: Mutex::new(None),
+        }
+    }
+
+    /// Runs the task loading a page, cancelling the load it supersedes so an older page cannot
+    /// replace the newer page
+    fn spawn_load(&self, task: impl Future<Output = ()> + Send + 'static) {
+        let mut loading = self.loading.lock().unwrap();
+        if let Some(previous) = loading.take() {
+            previous.abort();
+        }
+        *loading = Some(tokio::spawn(task).abort_handle());
+    }
+
+    /// Cancels loading the page, returns whether a page was being loaded
+    pub fn cancel_loading(&self) -> bool {
+        match self.loading.lock().unwrap().take() {
+            Some(task) if !task.is_finished() => {
+                task.abort();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.fetcher.is_offline.load(Ordering::SeqCst)
+    }
+
+    /// In the offline mode, only cached pages can be read
+    pub fn set_offline(&self, is_offline: bool) {
+        self.fetcher.is_offline.store(is_offline, Ordering::SeqCst);
+    }
+
+    /// Fetches the page in the background, unless prefetching is disabled or while offline. A
+    /// failed prefetch isn't retried, the page is fetched again when it's opened. A cancelled
+    /// prefetch is started again
+    pub fn prefetch_page(&self, endpoint: Endpoint, language: Language, title: String) {
+        if let Some(prefetcher) = self.prefetcher.as_ref().filter(|_| !self.is_offline()) {
+            let key = CacheKey {
+                endpoint,
+                language,
+                title,
+                oldid: None,
+            };
+            let fetcher = PageFetcher {
+                retry: RetryPolicy::disabled(),
+                ..self.fetcher.clone()
+            };
+            prefetcher.prefetch(fetcher, key);
+        }
     }
 
     pub fn load_search_result(&self, result: SearchResult) {
-        self.load_page_custom(result.endpoint, result.language, result.title);
+        self.load_page_custom(
+            result.endpoint,
+            result.language,
+            result.title,
+            None,
+            LoadKind::Display,
+        );
+    }
+
+    /// Loads the first result of a search and shows a notice naming the opened page, so a
+    /// redirect to an unexpected page doesn't go unnoticed
+    pub fn load_lucky_search_result(&self, result: SearchResult, query: String) {
+        self.load_page_custom(
+            result.endpoint,
+            result.language,
+            result.title,
+            None,
+            LoadKind::Lucky(query),
+        );
     }
 
     pub fn load_link(&self, link: Link) {
@@ -35,44 +752,450 @@ impl PageLoader {
             _ => return,
         };
 
-        self.load_page_custom(link_data.endpoint, link_data.language, link_data.page);
+        self.load_page_custom(
+            link_data.endpoint,
+            link_data.language,
+            link_data.page,
+            None,
+            LoadKind::Display,
+        );
     }
 
     pub fn load_language_link(&self, link: LanguageLink) {
-        self.load_page_custom(link.endpoint, link.language, link.title);
+        self.load_page_custom(
+            link.endpoint,
+            link.language,
+            link.title,
+            None,
+            LoadKind::Display,
+        );
     }
 
-    fn load_page_custom(&self, endpoint: Endpoint, language: Language, title: String) {
-        let page_request = Page::builder()
-            .page(title)
-            .properties(vec![
-                Property::Text,
-                Property::Sections,
-                Property::LangLinks,
-            ])
-            .endpoint(endpoint)
-            .language(language)
-            .redirects(self.config.api.page_redirects);
+    pub fn load_page(&self, endpoint: Endpoint, language: Language, title: String) {
+        self.load_page_custom(endpoint, language, title, None, LoadKind::Display);
+    }
 
+    /// Loads the page with the title, searching for the title when there is no such page
+    pub fn load_page_or_search(&self, endpoint: Endpoint, language: Language, title: String) {
+        let kind = LoadKind::OrSearch(title.clone());
+        self.load_page_custom(endpoint, language, title, None, kind);
+    }
+
+    /// Loads the page and jumps to the header with the anchor, if there is one
+    pub fn load_page_at(
+        &self,
+        endpoint: Endpoint,
+        language: Language,
+        title: String,
+        anchor: Option<String>,
+    ) {
+        self.load_page_custom(endpoint, language, title, None, LoadKind::Section(anchor));
+    }
+
+    /// Loads a random article. Ignored while another random article is being loaded
+    pub fn load_random_page(&self, endpoint: Endpoint, language: Language) {
+        if self.is_offline() {
+            self.action_tx
+                .send(Action::PopupMessage(
+                    "Offline".to_string(),
+                    "Random pages cannot be loaded while offline".to_string(),
+                ))
+                .unwrap();
+            return;
+        }
+        if self.is_loading_random.swap(true, Ordering::SeqCst) {
+            info!("already loading a random page");
+            return;
+        }
+
+        let fetcher = self.fetcher.clone();
+        let is_loading_random = ResetOnDrop(self.is_loading_random.clone());
         let tx = self.action_tx.clone();
-        tokio::spawn(async move {
+        self.spawn_load(async move {
+            tx.send(Action::SwitchContextPage).unwrap();
+            tx.send(Action::PageViewer(PageViewerAction::StartLoading(
+                "a random page".to_string(),
+            )))
+            .unwrap();
+
+            let result = match fetcher.retry(|| random_title(endpoint.clone())).await {
+                Ok(title) => {
+                    fetcher
+                        .fetch(endpoint.clone(), language, title, None, false)
+                        .await
+                }
+                Err(error) => Err(error),
+            };
+            match result {
+                Ok(fetched) => {
+                    fetched.send(&tx, PageViewerAction::DisplayPage);
+                    tx.send(Action::Page(PageAction::MarkRandom)).unwrap();
+                }
+                Err(error) => fetcher.send_load_error(
+                    error,
+                    &endpoint,
+                    "Unable to fetch a random page",
+                    Action::LoadRandomPage,
+                ),
+            };
+
+            drop(is_loading_random);
+            tx.send(Action::EnterNormal).unwrap();
+        });
+    }
+
+    /// Loads the talk page of the page, or the subject page when the page is a talk page. The
+    /// namespaces are looked up on the site, so their localized names and aliases are understood
+    pub fn load_associated_page(&self, endpoint: Endpoint, language: Language, title: String) {
+        let retry = Action::LoadAssociatedPage(endpoint.clone(), language, title.clone());
+        let fetcher = self.fetcher.clone();
+        let tx = self.action_tx.clone();
+        self.spawn_load(async move {
+            tx.send(Action::SwitchContextPage).unwrap();
+            tx.send(Action::PageViewer(PageViewerAction::StartLoading(
+                "the talk page".to_string(),
+            )))
+            .unwrap();
+
+            let associated = match fetcher.site_namespaces(&endpoint).await {
+                Ok(namespaces) => associated_title(&namespaces, &title),
+                Err(error) => {
+                    fetcher.send_load_error(
+                        error,
+                        &endpoint,
+                        "Unable to fetch the namespaces of the site",
+                        retry,
+                    );
+                    tx.send(Action::EnterNormal).unwrap();
+                    return;
+                }
+            };
+            let (associated, is_talk) = match associated {
+                Some(associated) => associated,
+                None => {
+                    tx.send(Action::PageViewer(PageViewerAction::ExitLoading))
+                        .unwrap();
+                    tx.send(Action::PopupMessage(
+                        "Information".to_string(),
+                        format!("'{title}' has no talk page"),
+                    ))
+                    .unwrap();
+                    tx.send(Action::EnterNormal).unwrap();
+                    return;
+                }
+            };
+
+            match fetcher
+                .fetch(endpoint.clone(), language, associated.clone(), None, false)
+                .await
+            {
+                Ok(fetched) => fetched.send(&tx, PageViewerAction::DisplayPage),
+                Err(error) if error.chain().any(|cause| cause.is::<PageNotFound>()) => {
+                    info!("the associated page '{associated}' doesn't exist");
+                    let message = match is_talk {
+                        true => format!(
+                            "There is no discussion about '{title}' yet, its talk page '{associated}' doesn't exist"
+                        ),
+                        false => format!("The page '{associated}' doesn't exist"),
+                    };
+                    tx.send(Action::PageViewer(PageViewerAction::ExitLoading))
+                        .unwrap();
+                    tx.send(Action::PopupMessage("Information".to_string(), message))
+                        .unwrap();
+                }
+                Err(error) => {
+                    fetcher.send_load_error(error, &endpoint, "Unable to fetch the page", retry)
+                }
+            };
+
+            tx.send(Action::EnterNormal).unwrap();
+        });
+    }
+
+    /// Loads the homepage, showing the search with the error when it cannot be loaded
+    pub fn load_homepage(&self, endpoint: Endpoint, language: Language, homepage: Homepage) {
+        if homepage == Homepage::Search {
+            return;
+        }
+
+        let fetcher = self.fetcher.clone();
+        let tx = self.action_tx.clone();
+        self.spawn_load(async move {
             tx.send(Action::SwitchContextPage).unwrap();
-            tx.send(Action::EnterProcessing).unwrap();
+            tx.send(Action::PageViewer(PageViewerAction::StartLoading(
+                "the homepage".to_string(),
+            )))
+            .unwrap();
 
-            match page_request.fetch().await {
-                Ok(page) => tx
-                    .send(Action::PageViewer(PageViewerAction::DisplayPage(page)))
-                    .unwrap(),
+            let is_random = homepage == Homepage::Random;
+            let title = match homepage {
+                Homepage::Search => unreachable!("the search is not a page"),
+                Homepage::Random => fetcher.retry(|| random_title(endpoint.clone())).await,
+                Homepage::Featured => fetcher.retry(|| featured_title(endpoint.clone())).await,
+                Homepage::Article(title) => Ok(title),
+            };
+            let result = match title {
+                Ok(title) => {
+                    fetcher
+                        .fetch(endpoint.clone(), language, title, None, false)
+                        .await
+                }
+                Err(error) => Err(error),
+            };
+            match result {
+                Ok(fetched) => {
+                    fetched.send(&tx, PageViewerAction::DisplayPage);
+                    if is_random {
+                        tx.send(Action::Page(PageAction::MarkRandom)).unwrap();
+                    }
+                }
                 Err(error) => {
-                    let error = error.context("Unable to fetch the page");
+                    let context = "Unable to open the homepage";
                     tx.send(Action::PageViewer(PageViewerAction::ExitLoading))
                         .unwrap();
-                    tx.send(Action::PopupError(error.to_string())).unwrap();
-                    error!("{:?}", error);
+                    tx.send(Action::SwitchContextSearch).unwrap();
+                    tx.send(Action::ShowMessage(
+                        MessageLevel::Error,
+                        fetcher.load_error_message(&error, &endpoint, context),
+                    ))
+                    .unwrap();
+                    error!("{:?}", error.context(context));
                 }
             };
 
             tx.send(Action::EnterNormal).unwrap();
         });
     }
+
+    /// Loads an old revision of the page
+    pub fn load_revision(
+        &self,
+        endpoint: Endpoint,
+        language: Language,
+        title: String,
+        revision: Revision,
+    ) {
+        let oldid = Some(revision.id);
+        self.load_page_custom(
+            endpoint,
+            language,
+            title,
+            oldid,
+            LoadKind::Revision(revision),
+        );
+    }
+
+    /// Fetches a page of the history again, which was dropped to save memory
+    pub fn reload_page(
+        &self,
+        endpoint: Endpoint,
+        language: Language,
+        title: String,
+        oldid: Option<usize>,
+    ) {
+        self.load_page_custom(endpoint, language, title, oldid, LoadKind::Restore);
+    }
+
+    /// Loads the page of the last session and restores its position
+    pub fn restore_session(&self, page: SessionPage) {
+        self.load_page_custom(
+            page.endpoint,
+            page.language,
+            page.title,
+            None,
+            LoadKind::Session(page.y, page.selected),
+        );
+    }
+
+    /// Fetches the current page again, ignoring the cached page
+    pub fn refresh_page(
+        &self,
+        endpoint: Endpoint,
+        language: Language,
+        title: String,
+        oldid: Option<usize>,
+    ) {
+        self.load_page_custom(endpoint, language, title, oldid, LoadKind::Refresh);
+    }
+
+    /// Fetches the page ignoring the cached page and displays it as the newest page of the
+    /// history, like a watched page that was edited
+    pub fn load_fresh_page(&self, endpoint: Endpoint, language: Language, title: String) {
+        self.load_page_custom(endpoint, language, title, None, LoadKind::Fresh);
+    }
+
+    /// Fetches the wikitext of the page, the page is displayed while it's loading
+    pub fn load_page_source(&self, endpoint: Endpoint, title: String) {
+        let tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let result = page_source(endpoint, &title).await.map_err(|error| {
+                let error = error.context("Unable to fetch the source of the page");
+                error!("{:?}", error);
+                error.to_string()
+            });
+            tx.send(Action::Page(PageAction::SourceLoaded(title, result)))
+                .unwrap();
+        });
+    }
+
+    fn load_page_custom(
+        &self,
+        endpoint: Endpoint,
+        language: Language,
+        title: String,
+        oldid: Option<usize>,
+        kind: LoadKind,
+    ) {
+        let refresh = matches!(kind, LoadKind::Refresh | LoadKind::Fresh);
+        let prefetched = match self.prefetcher {
+            Some(ref prefetcher) => {
+                prefetcher.cancel();
+                let key = CacheKey {
+                    endpoint: endpoint.clone(),
+                    language,
+                    title: title.clone(),
+                    oldid,
+                };
+                prefetcher.get(&key).filter(|_| !refresh)
+            }
+            None => None,
+        };
+
+        let retry = kind.retry_action(endpoint.clone(), language, title.clone(), oldid);
+        let fetcher = self.fetcher.clone();
+        let tx = self.action_tx.clone();
+        self.spawn_load(async move {
+            tx.send(Action::SwitchContextPage).unwrap();
+            tx.send(Action::PageViewer(PageViewerAction::StartLoading(format!(
+                "'{title}'"
+            ))))
+            .unwrap();
+
+            let result = match prefetched {
+                Some(fetched) => {
+                    debug!("using the prefetched page '{}'", fetched.page.title);
+                    Ok(fetched)
+                }
+                None => {
+                    fetcher
+                        .fetch(endpoint.clone(), language, title, oldid, refresh)
+                        .await
+                }
+            };
+            match result {
+                Ok(fetched) => match kind {
+                    LoadKind::Display | LoadKind::Fresh | LoadKind::OrSearch(_) => {
+                        fetched.send(&tx, PageViewerAction::DisplayPage)
+                    }
+                    LoadKind::Lucky(query) => {
+                        let notice = format!("Opened '{}' for '{}'", fetched.page.title, query);
+                        fetched.send(&tx, PageViewerAction::DisplayPage);
+                        tx.send(Action::Page(PageAction::ShowNotice(notice)))
+                            .unwrap();
+                    }
+                    LoadKind::Section(anchor) => {
+                        fetched.send(&tx, PageViewerAction::DisplayPage);
+                        if let Some(anchor) = anchor {
+                            tx.send(Action::Page(PageAction::GoToHeader(anchor)))
+                                .unwrap();
+                        }
+                    }
+                    LoadKind::Revision(revision) => {
+                        fetched.send(&tx, PageViewerAction::DisplayPage);
+                        tx.send(Action::Page(PageAction::MarkRevision(revision)))
+                            .unwrap();
+                    }
+                    LoadKind::Session(y, selected) => {
+                        fetched.send(&tx, PageViewerAction::DisplayPage);
+                        tx.send(Action::Page(PageAction::RestorePosition(y, selected)))
+                            .unwrap();
+                    }
+                    LoadKind::Restore => fetched.send(&tx, PageViewerAction::RestorePage),
+                    LoadKind::Refresh => {
+                        fetched.send(&tx, PageViewerAction::RefreshPage);
+                        tx.send(Action::Page(PageAction::ShowNotice(
+                            "Refreshed the page".to_string(),
+                        )))
+                        .unwrap();
+                    }
+                },
+                Err(error) if error.chain().any(|cause| cause.is::<PageNotFound>()) => match kind {
+                    LoadKind::OrSearch(query) => {
+                        info!("there is no page '{query}', searching for it instead");
+                        tx.send(Action::PageViewer(PageViewerAction::ExitLoading))
+                            .unwrap();
+                        tx.send(Action::SwitchContextSearch).unwrap();
+                        tx.send(Action::Search(SearchAction::StartSearch(query)))
+                            .unwrap();
+                    }
+                    _ => {
+                        fetcher.send_load_error(error, &endpoint, "Unable to fetch the page", retry)
+                    }
+                },
+                Err(error) => {
+                    fetcher.send_load_error(error, &endpoint, "Unable to fetch the page", retry)
+                }
+            };
+
+            tx.send(Action::EnterNormal).unwrap();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::{
+        document::Document,
+        languages::Language,
+        page::{Page, PageKind},
+        Endpoint,
+    };
+
+    use super::{FetchedPage, PrefetchedPages, PREFETCHED_PAGES};
+    use crate::page_cache::CacheKey;
+
+    fn key(title: &str) -> CacheKey {
+        CacheKey {
+            endpoint: Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+            language: Language::English,
+            title: title.to_string(),
+            oldid: None,
+        }
+    }
+
+    fn page(title: &str) -> FetchedPage {
+        FetchedPage {
+            page: Page {
+                title: title.to_string(),
+                pageid: 0,
+                kind: PageKind::Article,
+                content: Document { nodes: Vec::new() },
+                language: Language::English,
+                endpoint: Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+                language_links: None,
+                sections: None,
+                revision_id: None,
+                redirected_from: None,
+                description: None,
+            },
+            cached: None,
+        }
+    }
+
+    #[test]
+    fn test_prefetched_pages_drop_least_recently_used() {
+        let mut pages = PrefetchedPages::default();
+        for idx in 0..PREFETCHED_PAGES {
+            pages.insert(key(&idx.to_string()), page(&idx.to_string()));
+        }
+
+        // reading the oldest page keeps it
+        assert_eq!(pages.get(&key("0")).unwrap().page.title, "0");
+        pages.insert(key("new"), page("new"));
+
+        assert!(pages.contains(&key("0")));
+        assert!(pages.contains(&key("new")));
+        assert!(!pages.contains(&key("1")));
+        assert!(pages.get(&key("1")).is_none());
+    }
 }
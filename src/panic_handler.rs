@@ -1,53 +1,83 @@
-use crate::terminal::Tui;
-use anyhow::Result;
-use tracing::error;
+use std::{
+    backtrace::Backtrace,
+    fmt::Write,
+    panic::PanicInfo,
+    path::{Path, PathBuf},
+};
 
-pub fn initialize_panic_handler() -> Result<()> {
-    #[allow(unused_variables)]
-    let (panic_hook, eyre_hook) = color_eyre::config::HookBuilder::default()
-        .display_location_section(true)
-        .display_env_section(true)
-        .into_hooks();
-
-    eyre_hook.install()?;
-
-    std::panic::set_hook(Box::new(move |panic_info| {
-        match Tui::new() {
-            Ok(mut tui) => {
-                if let Err(error) = tui.exit() {
-                    error!("unable to exit terminal: {error:?}");
-                }
-            }
-            Err(error) => error!("unable to exit terminal {error:?}"),
-        }
+use anyhow::{Context, Result};
+use tracing::error;
 
-        #[cfg(not(debug_assertions))]
-        {
-            eprintln!("{}", panic_hook.panic_report(panic_info));
-            use human_panic::{handle_dump, print_msg, Metadata};
-            let meta = Metadata {
-                version: env!("CARGO_PKG_VERSION").into(),
-                name: env!("CARGO_PKG_NAME").into(),
-                authors: env!("CARGO_PKG_AUTHORS").replace(':', ", ").into(),
-                homepage: env!("CARGO_PKG_HOMEPAGE").into(),
-            };
-
-            let file_path = handle_dump(&meta, panic_info);
-            print_msg(file_path, &meta)
-                .expect("human-panic: printing error message to console failed");
-        }
+use crate::{config::data_dir, page_cache::now, terminal};
 
-        #[cfg(debug_assertions)]
-        {
-            better_panic::Settings::auto()
-                .most_recent_first(false)
-                .lineno_suffix(true)
-                .verbosity(better_panic::Verbosity::Full)
-                .create_panic_handler()(panic_info);
-        }
+/// Number of backtrace lines printed to stderr, the crash file has the full backtrace
+const BACKTRACE_LINES: usize = 20;
 
+/// Restores the terminal and reports the panic before exiting
+pub fn initialize_panic_handler() -> Result<()> {
+    std::panic::set_hook(Box::new(|panic_info| {
+        let crash_dir = data_dir().ok();
+        report_panic(panic_info, terminal::restore, crash_dir.as_deref());
         std::process::exit(libc::EXIT_FAILURE);
     }));
 
     Ok(())
 }
+
+/// Restores the terminal and prints the panic with the first lines of the backtrace to stderr.
+/// The full report is written to a crash file in the directory, its path is returned
+pub fn report_panic(
+    panic_info: &PanicInfo<'_>,
+    restore: impl FnOnce() -> Result<()>,
+    crash_dir: Option<&Path>,
+) -> Option<PathBuf> {
+    // the terminal is restored first, otherwise the report is unreadable
+    if let Err(error) = restore() {
+        eprintln!("unable to restore the terminal: {error:#}");
+    }
+
+    let backtrace = Backtrace::force_capture().to_string();
+    error!("{panic_info}\n{backtrace}");
+
+    eprintln!("wiki-tui crashed, {panic_info}\n");
+    let mut lines = backtrace.lines();
+    for line in lines.by_ref().take(BACKTRACE_LINES) {
+        eprintln!("{line}");
+    }
+    if lines.next().is_some() {
+        eprintln!("      ...");
+    }
+
+    let path = match crash_dir.map(|dir| write_crash_file(dir, panic_info, &backtrace)) {
+        Some(Ok(path)) => path,
+        Some(Err(error)) => {
+            eprintln!("\nunable to write the crash report: {error:#}");
+            return None;
+        }
+        None => return None,
+    };
+    eprintln!(
+        "\nThe full report was written to '{}', please attach it when reporting the crash at {}/issues",
+        path.display(),
+        env!("CARGO_PKG_REPOSITORY")
+    );
+    Some(path)
+}
+
+fn write_crash_file(dir: &Path, panic_info: &PanicInfo<'_>, backtrace: &str) -> Result<PathBuf> {
+    let mut report = String::new();
+    let _ = writeln!(report, "wiki-tui {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        report,
+        "{} {}\n",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(report, "{panic_info}\n\n{backtrace}");
+
+    std::fs::create_dir_all(dir).context("failed creating the data directory")?;
+    let path = dir.join(format!("crash-{}.txt", now()));
+    std::fs::write(&path, report)
+        .with_context(|| format!("failed writing '{}'", path.display()))?;
+    Ok(path)
+}
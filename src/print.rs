@@ -0,0 +1,180 @@
+use std::io::{IsTerminal, Write};
+
+use anyhow::Context;
+use wiki_api::{
+    languages::Language,
+    page::{site_namespaces, ArticleUrl, Page, PageNotFound, Property},
+    Endpoint,
+};
+
+use crate::{
+    cli::is_url,
+    client::init_client,
+    config::{load_config, load_themes, Config},
+    export::{rendered_text, write_render_dump},
+    renderer::{dump::RenderDump, renderers},
+};
+
+/// Exit code when the page could be printed
+pub const EXIT_SUCCESS: i32 = 0;
+/// Exit code when the url of the article is invalid
+pub const EXIT_INVALID_URL: i32 = 1;
+/// Exit code when the page doesn't exist. `2` is left to the invalid arguments reported by clap
+pub const EXIT_NOT_FOUND: i32 = 5;
+/// Exit code when the page could not be fetched, for example because the network is down
+pub const EXIT_FETCH_FAILED: i32 = 3;
+/// Exit code when the page could not be written to stdout
+pub const EXIT_WRITE_FAILED: i32 = 4;
+
+/// Width of the page when stdout is not a terminal and no width was given
+const DEFAULT_WIDTH: u16 = 80;
+
+/// What to print and how, set with the `--print` or the `--dump-render` argument
+pub struct PrintOptions {
+    /// Title or url of the article
+    pub article: String,
+    pub language: Option<Language>,
+    pub width: Option<u16>,
+    /// Whether the page is printed with ansi escape codes
+    pub color: bool,
+    /// Whether the rendered lines are written as json to the data directory instead of printing
+    /// the page
+    pub dump: bool,
+    /// The proxy overriding the configured one
+    pub proxy: Option<String>,
+}
+
+/// Fetches the article, renders it and writes it to stdout without starting the tui. A dump of the
+/// rendered lines is written to the data directory instead, with its path on stdout. Returns the
+/// exit code of the program
+pub async fn print_page(options: PrintOptions) -> i32 {
+    let mut config = load_config().unwrap_or_else(|error| {
+        eprintln!(
+            "{:?}",
+            error.context("failed loading the config, using the default")
+        );
+        Config::default()
+    });
+    if let Err(error) = init_client(&mut config.api, options.proxy) {
+        eprintln!("error: unable to set up the connection: {error:#}");
+        return EXIT_FETCH_FAILED;
+    }
+    let theme = load_themes()
+        .ok()
+        .and_then(|themes| themes.into_iter().next())
+        .unwrap_or_default();
+
+    let (endpoint, language, title) = if is_url(&options.article) {
+        match ArticleUrl::parse(&options.article) {
+            Ok(url) => (url.endpoint, url.language, url.title),
+            Err(error) => {
+                eprintln!(
+                    "error: unable to open the url '{}': {error:#}",
+                    options.article
+                );
+                return EXIT_INVALID_URL;
+            }
+        }
+    } else {
+        let (endpoint, language) = match options.language {
+            Some(language) => (config.api.sites[0].endpoint(language), language),
+            None => (
+                config.api.sites[0].endpoint(config.api.language),
+                config.api.language,
+            ),
+        };
+        (endpoint, language, options.article)
+    };
+
+    let page = match fetch_page(&config, endpoint, language, &title).await {
+        Ok(page) => page,
+        Err(error) => {
+            eprintln!("error: {error:#}");
+            return match error.chain().any(|cause| cause.is::<PageNotFound>()) {
+                true => EXIT_NOT_FOUND,
+                false => EXIT_FETCH_FAILED,
+            };
+        }
+    };
+
+    let width = options.width.unwrap_or_else(|| {
+        if std::io::stdout().is_terminal() {
+            crossterm::terminal::size()
+                .map(|(width, _)| width)
+                .unwrap_or(DEFAULT_WIDTH)
+        } else {
+            DEFAULT_WIDTH
+        }
+    });
+    let mut renderers = renderers(
+        std::sync::Arc::new(theme.clone()),
+        config.page.render_options(page.kind, page.language),
+    );
+    let renderer = match renderers
+        .iter()
+        .position(|renderer| renderer.name() == config.page.renderer)
+    {
+        Some(idx) => renderers.swap_remove(idx),
+        None => {
+            eprintln!(
+                "warning: unknown renderer '{}', using the default",
+                config.page.renderer
+            );
+            renderers.swap_remove(0)
+        }
+    };
+    if options.dump {
+        let rendered = renderer.render(&page.content, width);
+        let dump = RenderDump::new(&page.title, renderer.name(), width, &rendered);
+        return match write_render_dump(&dump) {
+            Ok(path) => {
+                println!("{}", path.display());
+                EXIT_SUCCESS
+            }
+            Err(error) => {
+                eprintln!("error: unable to dump the rendered page: {error:#}");
+                EXIT_WRITE_FAILED
+            }
+        };
+    }
+
+    let text = rendered_text(
+        renderer.as_ref(),
+        &page,
+        width,
+        options.color.then_some(&theme),
+    );
+    match std::io::stdout().lock().write_all(text.as_bytes()) {
+        Ok(_) => EXIT_SUCCESS,
+        // the reader of a pipe might have stopped reading, like `head`
+        Err(error) if error.kind() == std::io::ErrorKind::BrokenPipe => EXIT_SUCCESS,
+        Err(error) => {
+            eprintln!("error: failed writing the page: {error}");
+            EXIT_WRITE_FAILED
+        }
+    }
+}
+
+async fn fetch_page(
+    config: &Config,
+    endpoint: Endpoint,
+    language: Language,
+    title: &str,
+) -> anyhow::Result<Page> {
+    // the namespaces only classify the page, which can be done by the canonical names without
+    let namespaces = site_namespaces(endpoint.clone()).await.unwrap_or_default();
+    Page::builder()
+        .page(title)
+        .properties(vec![
+            Property::Text,
+            Property::Sections,
+            Property::Properties,
+        ])
+        .endpoint(endpoint)
+        .language(language)
+        .redirects(config.api.page_redirects)
+        .namespaces(namespaces)
+        .fetch()
+        .await
+        .with_context(|| format!("unable to fetch the page '{title}'"))
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use wiki_api::{languages::Language, Endpoint};
+
+use crate::store::Store;
+
+pub const READING_LIST_STORE: &str = "reading_list";
+
+/// A page queued to be read later, optionally pointing to one of its sections
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReadingListEntry {
+    pub title: String,
+    #[serde(with = "crate::store::language_code")]
+    pub language: Language,
+    pub endpoint: Endpoint,
+    /// Id of the header the page is opened at, `None` for the top of the page
+    pub anchor: Option<String>,
+}
+
+impl ReadingListEntry {
+    pub fn new(
+        title: String,
+        language: Language,
+        endpoint: Endpoint,
+        anchor: Option<String>,
+    ) -> Self {
+        Self {
+            title,
+            language,
+            endpoint,
+            anchor,
+        }
+    }
+
+    fn is_same_page(&self, endpoint: &Endpoint, language: Language, title: &str) -> bool {
+        self.endpoint == *endpoint && self.language == language && self.title == title
+    }
+}
+
+/// The pages to read later, the most recently added page first. There is at most one entry per
+/// page
+#[derive(Serialize, Deserialize, Default)]
+pub struct ReadingList {
+    entries: Vec<ReadingListEntry>,
+}
+
+impl ReadingList {
+    pub fn open() -> anyhow::Result<Store<ReadingList>> {
+        Store::open(READING_LIST_STORE)
+    }
+
+    pub fn entries(&self) -> &[ReadingListEntry] {
+        &self.entries
+    }
+
+    /// Adds the entry at the top of the list. An entry of the same page is moved to the top
+    /// instead. Returns whether the page was already in the list
+    pub fn add(&mut self, entry: ReadingListEntry) -> bool {
+        let len = self.entries.len();
+        self.remove(&entry.endpoint, entry.language, &entry.title);
+        let is_moved = self.entries.len() != len;
+        self.entries.insert(0, entry);
+        is_moved
+    }
+
+    pub fn remove(&mut self, endpoint: &Endpoint, language: Language, title: &str) {
+        self.entries
+            .retain(|entry| !entry.is_same_page(endpoint, language, title));
+    }
+
+    /// Swaps the positions of the entries of two pages
+    pub fn swap(&mut self, a: &ReadingListEntry, b: &ReadingListEntry) {
+        let position = |entry: &ReadingListEntry| {
+            self.entries.iter().position(|existing| {
+                existing.is_same_page(&entry.endpoint, entry.language, &entry.title)
+            })
+        };
+        if let (Some(a), Some(b)) = (position(a), position(b)) {
+            self.entries.swap(a, b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::{languages::Language, Endpoint};
+
+    use super::{ReadingList, ReadingListEntry};
+
+    #[test]
+    fn test_reading_list() {
+        let endpoint = Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap();
+        let entry = |title: &str, anchor: Option<&str>| {
+            ReadingListEntry::new(
+                title.to_string(),
+                Language::English,
+                endpoint.clone(),
+                anchor.map(|anchor| anchor.to_string()),
+            )
+        };
+        let titles = |list: &ReadingList| -> Vec<String> {
+            list.entries()
+                .iter()
+                .map(|entry| entry.title.clone())
+                .collect()
+        };
+
+        let mut list = ReadingList::default();
+        assert!(!list.add(entry("Rust", None)));
+        assert!(!list.add(entry("Iron", None)));
+        assert!(!list.add(entry("Oxygen", None)));
+        assert_eq!(titles(&list), ["Oxygen", "Iron", "Rust"]);
+
+        // adding a page again moves it to the top
+        assert!(list.add(entry("Rust", Some("History"))));
+        assert_eq!(titles(&list), ["Rust", "Oxygen", "Iron"]);
+        assert_eq!(list.entries()[0].anchor.as_deref(), Some("History"));
+
+        list.swap(&entry("Iron", None), &entry("Oxygen", None));
+        assert_eq!(titles(&list), ["Rust", "Iron", "Oxygen"]);
+
+        list.remove(&endpoint, Language::English, "Iron");
+        assert_eq!(titles(&list), ["Rust", "Oxygen"]);
+
+        // the same title on another site is another page
+        let wiktionary = Endpoint::parse("https://en.wiktionary.org/w/api.php").unwrap();
+        assert!(!list.add(ReadingListEntry::new(
+            "Rust".to_string(),
+            Language::English,
+            wiktionary.clone(),
+            None,
+        )));
+        assert_eq!(titles(&list), ["Rust", "Rust", "Oxygen"]);
+        list.remove(&wiktionary, Language::English, "Rust");
+        assert_eq!(list.entries()[0], entry("Rust", Some("History")));
+    }
+}
@@ -0,0 +1,82 @@
+//! Escape codes of the styles of words, for printing rendered pages outside of the terminal user
+//! interface without a terminal library
+
+use ratatui::style::{Color, Modifier, Style};
+
+/// Resets the colors and modifiers set by an escape sequence
+pub const RESET: &str = "\x1b[0m";
+
+const MODIFIERS: [(Modifier, u8); 6] = [
+    (Modifier::BOLD, 1),
+    (Modifier::DIM, 2),
+    (Modifier::ITALIC, 3),
+    (Modifier::UNDERLINED, 4),
+    (Modifier::REVERSED, 7),
+    (Modifier::CROSSED_OUT, 9),
+];
+
+/// Returns the escape sequence setting the colors and modifiers of the style, `None` when it sets
+/// nothing
+pub fn escape_sequence(style: Style) -> Option<String> {
+    let mut codes: Vec<String> = MODIFIERS
+        .iter()
+        .filter(|(modifier, _)| style.add_modifier.contains(*modifier))
+        .map(|(_, code)| code.to_string())
+        .collect();
+    codes.extend(style.fg.and_then(|color| color_code(color, 30)));
+    codes.extend(style.bg.and_then(|color| color_code(color, 40)));
+
+    (!codes.is_empty()).then(|| format!("\x1b[{}m", codes.join(";")))
+}
+
+/// Returns the code of the color, with `base` being `30` for the foreground and `40` for the
+/// background. `None` for the color of the terminal
+fn color_code(color: Color, base: u8) -> Option<String> {
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::Gray => base + 7,
+        Color::DarkGray => base + 60,
+        Color::LightRed => base + 61,
+        Color::LightGreen => base + 62,
+        Color::LightYellow => base + 63,
+        Color::LightBlue => base + 64,
+        Color::LightMagenta => base + 65,
+        Color::LightCyan => base + 66,
+        Color::White => base + 67,
+        Color::Indexed(idx) => return Some(format!("{};5;{idx}", base + 8)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{r};{g};{b}", base + 8)),
+    };
+    Some(code.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::{Color, Style, Stylize};
+
+    use super::escape_sequence;
+
+    #[test]
+    fn test_escape_sequence() {
+        assert_eq!(escape_sequence(Style::new()), None);
+        assert_eq!(escape_sequence(Style::new().fg(Color::Reset)), None);
+        assert_eq!(
+            escape_sequence(Style::new().bold().italic().fg(Color::Blue)),
+            Some("\x1b[1;3;34m".to_string())
+        );
+        assert_eq!(
+            escape_sequence(Style::new().fg(Color::LightRed).bg(Color::Indexed(237))),
+            Some("\x1b[91;48;5;237m".to_string())
+        );
+        assert_eq!(
+            escape_sequence(Style::new().underlined().bg(Color::Rgb(1, 2, 3))),
+            Some("\x1b[4;48;2;1;2;3m".to_string())
+        );
+    }
+}
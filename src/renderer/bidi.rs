@@ -0,0 +1,279 @@
+use ratatui::style::Style;
+use unicode_bidi::{bidi_class, BidiClass, BidiInfo, Level};
+use unicode_width::UnicodeWidthChar;
+use wiki_api::document::{Data, Document};
+
+use super::Word;
+
+/// Number of characters with a strong direction that are looked at to guess the direction of a
+/// page
+const DETECTION_SAMPLE: usize = 500;
+
+/// Returns whether most of the characters with a strong direction in the text nodes of the
+/// document are written from right to left. Only the beginning of the document is looked at
+pub fn is_right_to_left(document: &Document) -> bool {
+    let (mut rtl, mut ltr) = (0, 0);
+    let texts = document.nodes.iter().filter_map(|node| match &node.data {
+        Data::Text { contents } => Some(contents),
+        _ => None,
+    });
+    for c in texts.flat_map(|contents| contents.chars()) {
+        match bidi_class(c) {
+            BidiClass::R | BidiClass::AL => rtl += 1,
+            BidiClass::L => ltr += 1,
+            _ => continue,
+        }
+        if rtl + ltr >= DETECTION_SAMPLE {
+            break;
+        }
+    }
+    rtl > ltr
+}
+
+/// A character of a line in logical order and the word it belongs to
+struct LineChar {
+    byte: usize,
+    c: char,
+    word: usize,
+    is_whitespace: bool,
+}
+
+/// Returns the characters of the line in logical order, including the whitespace after the words,
+/// and the order they're displayed in on a right-to-left page as their indices with whether they
+/// belong to a right-to-left run. The whitespace at the end of the line is left out
+fn visual_order(line: &[Word]) -> (Vec<LineChar>, Vec<(usize, bool)>) {
+    let mut text = String::new();
+    let mut chars = Vec::new();
+    for (idx, word) in line.iter().enumerate() {
+        let whitespace = std::iter::repeat(' ').take(word.whitespace_width as usize);
+        for (c, is_whitespace) in word
+            .content
+            .chars()
+            .map(|c| (c, false))
+            .chain(whitespace.map(|c| (c, true)))
+        {
+            chars.push(LineChar {
+                byte: text.len(),
+                c,
+                word: idx,
+                is_whitespace,
+            });
+            text.push(c);
+        }
+    }
+    // the whitespace at the end of the line would end up in front of it
+    while chars.last().is_some_and(|c| c.is_whitespace) {
+        let last = chars.pop().unwrap();
+        text.truncate(last.byte);
+    }
+    if text.is_empty() {
+        return (chars, Vec::new());
+    }
+
+    let info = BidiInfo::new(&text, Some(Level::rtl()));
+    let (levels, runs) = match info.paragraphs.first() {
+        Some(paragraph) => info.visual_runs(paragraph, 0..text.len()),
+        None => return (chars, Vec::new()),
+    };
+    let mut order = Vec::with_capacity(chars.len());
+    for run in runs {
+        let is_rtl = levels[run.start].is_rtl();
+        let start = chars.partition_point(|c| c.byte < run.start);
+        let end = chars.partition_point(|c| c.byte < run.end);
+        match is_rtl {
+            true => order.extend((start..end).rev().map(|idx| (idx, true))),
+            false => order.extend((start..end).map(|idx| (idx, false))),
+        }
+    }
+    (chars, order)
+}
+
+/// Returns the number of columns the line is moved to the right by, so it ends at the width
+fn padding(chars: &[LineChar], width: u16) -> usize {
+    let line_width: usize = chars
+        .iter()
+        .map(|c| match c.is_whitespace {
+            true => 1,
+            false => c.c.width().unwrap_or(0),
+        })
+        .sum();
+    (width as usize).saturating_sub(line_width)
+}
+
+/// Reorders the words of a line from their logical order into the order they're displayed in on a
+/// right-to-left page and aligns the line to the right of the width. Words in a right-to-left
+/// script are reversed, so the terminal shows them correctly when drawing from left to right.
+/// Words made of runs in both directions are split into one word per run, every part keeps the
+/// node and style of its word
+pub fn reorder_line(line: Vec<Word>, width: u16) -> Vec<Word> {
+    let (chars, order) = visual_order(&line);
+    if order.is_empty() {
+        return line;
+    }
+
+    let mut visual: Vec<Word> = Vec::new();
+    let mut last_word = None;
+    for (idx, is_rtl) in order {
+        let c = &chars[idx];
+        let is_new_word = match visual.last() {
+            Some(prev) => {
+                last_word != Some(c.word) || (!c.is_whitespace && prev.whitespace_width > 0.0)
+            }
+            None => true,
+        };
+        if is_new_word {
+            let word = &line[c.word];
+            visual.push(Word {
+                index: word.index,
+                content: String::new(),
+                style: word.style,
+                width: 0.0,
+                whitespace_width: 0.0,
+                penalty_width: 0.0,
+                is_hyphenated: false,
+            });
+            last_word = Some(c.word);
+        }
+
+        let word = visual.last_mut().unwrap();
+        match c.is_whitespace {
+            true => word.whitespace_width += 1.0,
+            false => {
+                word.content.push(if is_rtl { mirror(c.c) } else { c.c });
+                word.width += c.c.width().unwrap_or(0) as f64;
+            }
+        }
+    }
+
+    let padding = padding(&chars, width);
+    if padding > 0 {
+        visual.insert(
+            0,
+            Word {
+                index: usize::MAX,
+                content: String::new(),
+                style: Style::default(),
+                width: 0.0,
+                whitespace_width: padding as f64,
+                penalty_width: 0.0,
+                is_hyphenated: false,
+            },
+        );
+    }
+    visual
+}
+
+/// Returns the position every character of the line in logical order, including the whitespace
+/// after the words, is displayed at after [`reorder_line`] as the number of characters in front of
+/// it. The whitespace at the end of the line, which isn't displayed, is at the start
+pub fn visual_positions(line: &[Word], width: u16) -> Vec<usize> {
+    let (chars, order) = visual_order(line);
+    let len: usize = line
+        .iter()
+        .map(|word| word.content.chars().count() + word.whitespace_width as usize)
+        .sum();
+    let mut positions = vec![0; len];
+    let padding = padding(&chars, width);
+    for (position, (idx, _)) in order.into_iter().enumerate() {
+        positions[idx] = padding + position;
+    }
+    positions
+}
+
+/// Returns the mirrored form of brackets, which are displayed the other way around in
+/// right-to-left text
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::{
+        document::Document,
+        languages::Language,
+        parser::{Parser, WikipediaParser},
+        Endpoint,
+    };
+
+    use crate::{
+        renderer::{default_renderer::render_document, line_text, search_pattern},
+        theme::Theme,
+    };
+
+    use super::is_right_to_left;
+
+    fn parse(html: &str) -> Document {
+        let nodes = WikipediaParser::parse_document(
+            html,
+            Endpoint::parse("https://he.wikipedia.org/w/api.php").unwrap(),
+            Language::Hebrew,
+        )
+        .nodes();
+        Document { nodes }
+    }
+
+    #[test]
+    fn test_mixed_hebrew_and_english() {
+        let document = parse(
+            r#"<p>שפת התכנות <a rel="mw:WikiLink" href="./Rust" title="Rust">Rust</a> נוצרה (בשנת 2010) על ידי Mozilla.</p>"#,
+        );
+        assert!(is_right_to_left(&document));
+
+        let rendered = render_document(&document, &Theme::default(), 30, false).right_to_left(30);
+        let lines: Vec<String> = rendered
+            .lines
+            .iter()
+            .map(|line| line_text(line))
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        // the Hebrew words are reversed and start on the right, the English ones stay readable and
+        // the brackets are mirrored
+        assert_eq!(
+            lines,
+            [
+                "   תנשב) הרצונ Rust תונכתה תפש",
+                "         .Mozilla ידי לע (2010",
+            ]
+        );
+        assert!(lines.iter().all(|line| line.chars().count() == 30));
+
+        // copying and searching see the text in the order it's written in, the columns of a match
+        // are the ones it's displayed in
+        let y = rendered
+            .lines
+            .iter()
+            .position(|line| !line_text(line).trim().is_empty())
+            .unwrap();
+        assert_eq!(
+            rendered.text(y..=y + 1),
+            "שפת התכנות Rust נוצרה (בשנת\n2010) על ידי Mozilla.\n"
+        );
+        let matches = rendered.search(&search_pattern("התכנות Rust", false, false).unwrap());
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].line, matches[0].columns.clone()), (y, 15..26));
+
+        // the words of the link keep their node, so the link is still highlighted when selected
+        let (y, index) = rendered.links[0];
+        let link = rendered.lines[y]
+            .iter()
+            .find(|word| word.content == "Rust")
+            .and_then(|word| word.node(&document))
+            .unwrap();
+        assert!(link.ancestors().any(|node| node.index() == index));
+
+        let document = parse("<p>Rust is a language. שפת תכנות</p>");
+        assert!(!is_right_to_left(&document));
+    }
+}
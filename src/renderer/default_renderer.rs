@@ -1,14 +1,20 @@
+use std::{borrow::Cow, sync::Arc};
+
 use ratatui::style::{Color, Modifier, Style};
-use textwrap::wrap_algorithms::{wrap_optimal_fit, Penalties};
+use textwrap::wrap_algorithms::{wrap_first_fit, wrap_optimal_fit, Penalties};
 use tracing::warn;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 use wiki_api::{
     document::{Data, Document, HeaderKind, Node, UnsupportedElement},
-    page::Link,
+    page::{link_data::InterwikiKind, Link},
 };
 
-use crate::renderer::Word;
+use crate::{
+    renderer::{hyphenate::Hyphenator, BlockSpacing, Word, CLOSING_PUNCTUATION},
+    theme::Theme,
+};
 
-use super::RenderedDocument;
+use super::{PageRenderer, RenderOptions, RenderedDocument};
 
 const DISAMBIGUATION_PADDING: u8 = 1;
 const DISAMBIGUATION_PREFIX: char = '|';
@@ -18,221 +24,190 @@ const BLOCKQUOTE_PADDING: u8 = 4;
 const LIST_PADDING: u8 = 1;
 const LIST_PREFIX: char = '-';
 
-struct Renderer {
-    rendered_lines: Vec<Vec<Word>>,
-    links: Vec<(usize, usize)>,
+/// Only words with more characters are hyphenated
+const HYPHENATION_MIN_WIDTH: usize = 7;
+
+/// Punctuation of Chinese and Japanese text that never starts a line
+const WIDE_CLOSING_PUNCTUATION: &str = "、。，．：；！？）］｝」』】〉》〕";
+/// Punctuation of Chinese and Japanese text that never ends a line
+const WIDE_OPENING_PUNCTUATION: &str = "（［｛「『【〈《〔";
+
+const PLAIN_HEADER_PREFIX: char = '#';
+const PLAIN_HORIZONTAL_RULE: &str = "---";
+
+/// A step of laying out a document. The steps are recorded by walking the document once and
+/// replayed for every width, only the line breaks depend on the width
+#[derive(Debug, Clone)]
+enum Item {
+    /// Words wrapped onto the current line and the following ones
+    Words(Vec<Word>),
+    /// A whitespace after the last word of the current line
+    Whitespace,
+    /// Finishes the current line
+    ClearLine,
+    /// Finishes the current line and adds an empty one
+    EmptyLine,
+    /// Adds an empty line, unless the last line is empty already
+    EnsureEmptyLine,
+    /// Indents the following lines by more columns
+    Indent(u8),
+    /// Removes columns of the indentation
+    Dedent(u8),
+    /// Indents a description of a description list, unless half of the width is used up
+    IndentDescription,
+    /// Removes the indentation of the last description
+    DedentDescription,
+    /// Sets the prefix drawn in front of the following lines
+    Prefix(Option<char>),
+    /// The bullet of a list item at the current indentation
+    Bullet,
+    /// A line filling the rest of the current line, like the one below a header
+    HorizontalLine(Style),
+    /// A horizontal rule spanning the whole width, drawn with the character
+    HorizontalRule {
+        index: usize,
+        symbol: char,
+        style: Style,
+    },
+    /// The start of the link with the node index
+    Link(usize),
+}
 
-    current_line: Vec<Word>,
-    width: u16,
+/// The words of a document or one of its sections with the breaks and the indentation between
+/// them. Walking the document is done once when preparing it, wrapping it at a width is cheap
+#[derive(Debug, Clone)]
+pub struct PreparedDocument {
+    items: Vec<Item>,
+    justify: bool,
+    closing_punctuation: String,
+    description_indent: u8,
+}
 
-    text_style: Style,
+impl PreparedDocument {
+    /// Wraps the words at the width
+    pub fn wrap(&self, document: &Document, width: u16) -> RenderedDocument {
+        let mut layout = Layout::new(document, width, self.justify, &self.closing_punctuation);
+        layout.description_indent = self.description_indent;
+        layout.wrap(&self.items)
+    }
 
-    left_padding: u8,
-    prefix: Option<char>,
+    /// Returns all words, to change their style
+    pub fn words_mut(&mut self) -> impl Iterator<Item = &mut Word> {
+        self.items
+            .iter_mut()
+            .filter_map(|item| match item {
+                Item::Words(words) => Some(words),
+                _ => None,
+            })
+            .flatten()
+    }
 }
 
-impl<'a> Renderer {
-    fn render_document(document: &'a Document, width: u16) -> RenderedDocument {
-        if document.nodes.is_empty() {
-            warn!("document contains no nodes, aborting the render");
-            return RenderedDocument {
-                lines: Vec::new(),
-                links: Vec::new(),
-            };
-        }
-
-        let mut renderer = Renderer {
-            rendered_lines: Vec::new(),
-            links: Vec::new(),
+/// Walks the document and turns its nodes into styled words and the breaks between them
+struct Renderer<'a> {
+    theme: &'a Theme,
+    /// Splits long words when wrapping, `None` when hyphenation is disabled
+    hyphenator: Option<&'a Hyphenator>,
+    /// Renders without any styling, with explicit header and link markers
+    plain: bool,
+    spacing: BlockSpacing,
 
-            current_line: Vec::new(),
-            width,
+    items: Vec<Item>,
+    text_style: Style,
+    /// Whether nothing was rendered since the last header, compact spacing adds no empty lines
+    /// below headers
+    after_header: bool,
+}
 
+impl<'a> Renderer<'a> {
+    fn new(
+        theme: &'a Theme,
+        plain: bool,
+        hyphenator: Option<&'a Hyphenator>,
+        spacing: BlockSpacing,
+    ) -> Self {
+        Renderer {
+            theme,
+            hyphenator,
+            plain,
+            spacing,
+
+            items: Vec::new(),
             text_style: Style::default(),
-
-            left_padding: 0,
-            prefix: None,
-        };
-
-        renderer.render_node(document.nth(0).unwrap());
-
-        RenderedDocument {
-            lines: renderer.rendered_lines,
-            links: renderer.links,
+            after_header: false,
         }
     }
 
-    /// Returns whether the last word of the current line is a whitespace
-    fn is_last_whitespace(&self) -> bool {
-        self.current_line
-            .last()
-            .map(|last| last.index == usize::MAX)
-            .unwrap_or(false)
-    }
-
-    /// Returns whether the last rendered line is an empty one
-    ///
-    /// When the current line is not empty, this will return false
-    fn is_last_empty(&self) -> bool {
-        if !self.current_line.is_empty() {
-            false
-        } else {
-            self.rendered_lines
-                .last()
-                .map(|last| last.is_empty())
-                .unwrap_or(false)
-        }
+    /// Walks the node, the whole document or one of its top-level sections, and returns the
+    /// steps of laying it out
+    fn prepare(mut self, node: Node<'a>) -> Vec<Item> {
+        self.render_node(node);
+        self.items
     }
 
     /// Adds a whitespace to the end of the current line
     ///
     /// The whitespace word has an index of `usize::MAX` and a width of `0` to not interfere with text wrapping. Note: If there already is a whitespace at the end of the current line, no whitespace will be added!
     fn add_whitespace(&mut self) {
-        if self
-            .current_line
-            .last()
-            .map(|word| word.index == usize::MAX)
-            .unwrap_or(false)
-        {
-            return;
-        }
-
-        self.current_line.push(self.n_whitespace(1));
-    }
-
-    /// Returns a Word containing n amount of whitespace
-    fn n_whitespace(&self, n: u8) -> Word {
-        Word {
-            index: usize::MAX,
-            content: String::new(),
-            style: Style::default(),
-            width: 0.0,
-            whitespace_width: n as f64,
-            penalty_width: 0.0,
-        }
+        self.items.push(Item::Whitespace);
     }
 
     /// Clears the current line
     ///
     /// When the current line is not empty already, it adds it to the rendered lines
     fn clear_line(&mut self) {
-        if self.current_line.is_empty() {
-            return;
-        }
-
-        self.rendered_lines
-            .push(std::mem::take(&mut self.current_line));
+        self.items.push(Item::ClearLine);
     }
 
     /// Adds an empty line to the finished lines
     ///
-    /// Clears the current line before adding the empty one
+    /// Clears the current line before adding the empty one. With compact spacing, the empty line
+    /// is only added when the last line isn't empty already
     fn add_empty_line(&mut self) {
-        self.clear_line();
-        self.rendered_lines.push(Vec::new());
-    }
-
-    fn current_width(&self) -> usize {
-        let mut current_width: f64 = 0.0;
-        for word in self.current_line.iter() {
-            current_width = current_width + word.width + word.whitespace_width;
+        match self.spacing {
+            BlockSpacing::Compact => self.ensure_empty_line(),
+            BlockSpacing::Normal | BlockSpacing::Spacious => self.items.push(Item::EmptyLine),
         }
-        current_width as usize
     }
 
-    /// Wraps and appends words
+    /// Adds an empty line only if the last line is not empty
     ///
-    /// This fills up the current line with words and wraps the remaining words into lines, appending them to the finished words. Note: This leaves the current line empty, except when there are not enough words to fill it up completely
-    fn wrap_append(&mut self, words: Vec<Word>) {
-        if words.is_empty() {
-            return;
-        }
-
-        let current_width = self.current_width() as f64;
-        let mut remaining_width = (self.width as f64) - current_width;
-
-        // if the first word doesn't fit onto the current line, the line wrapping algorithm gets confuesed.
-        // that means we have to clear it in this case
-        if words.first().map(|word| word.width).unwrap_or_default() > remaining_width {
-            remaining_width = self.width as f64;
-            self.clear_line();
-        }
-
-        if self.current_line.is_empty() {
-            remaining_width -= self.left_padding as f64;
-            self.current_line.push(self.n_whitespace(self.left_padding));
-            if let Some(prefix) = self.prefix {
-                self.current_line.push(Word {
-                    index: usize::MAX,
-                    content: prefix.to_string(),
-                    style: Style::default(),
-                    width: 1.0,
-                    whitespace_width: 1.0,
-                    penalty_width: 0.0,
-                });
-
-                remaining_width -= 2.0; // subtract 2: 1 char & 1 whitespace
-            }
-        }
-
-        let line_widths: [f64; 2] = [remaining_width, self.width as f64];
-        let mut wrapped_lines: Vec<Vec<Word>> =
-            wrap_optimal_fit(&words, &line_widths, &Penalties::default())
-                .unwrap()
-                .into_iter()
-                .map(|word| word.to_vec())
-                .collect();
-
-        self.current_line.append(&mut wrapped_lines.remove(0));
-
-        // add prefixes
-        if let Some(prefix) = self.prefix {
-            for line in wrapped_lines.iter_mut() {
-                line.insert(
-                    0,
-                    Word {
-                        index: usize::MAX,
-                        content: prefix.to_string(),
-                        style: Style::default(),
-                        width: 1.0,
-                        whitespace_width: 1.0,
-                        penalty_width: 0.0,
-                    },
-                );
-            }
-        }
-
-        // indent the current line
-        for line in wrapped_lines.iter_mut() {
-            line.insert(0, self.n_whitespace(self.left_padding));
-        }
-
-        if let Some(last_line) = wrapped_lines.pop() {
-            self.clear_line();
-            self.current_line = last_line;
-            self.rendered_lines.append(&mut wrapped_lines)
+    /// With compact spacing, directly below a header the current line is only cleared
+    fn ensure_empty_line(&mut self) {
+        match self.spacing == BlockSpacing::Compact && self.after_header {
+            true => self.clear_line(),
+            false => self.items.push(Item::EnsureEmptyLine),
         }
     }
 
-    /// Adds an empty line only if the last line is not empty
-    fn ensure_empty_line(&mut self) {
-        if !self.is_last_empty() {
-            self.add_empty_line();
-        }
+    /// Adds the item of a block with content, like words or a rule, which ends the part below a
+    /// header
+    fn push_content(&mut self, item: Item) {
+        self.after_header = false;
+        self.items.push(item);
     }
 
     /// Adds a modifier to the current text style
     fn add_modifier(&mut self, modifier: Modifier) {
+        if self.plain {
+            return;
+        }
         self.text_style = self.text_style.add_modifier(modifier);
     }
 
     /// Removes a modifier from the current text style
     fn remove_modifier(&mut self, modifier: Modifier) {
+        if self.plain {
+            return;
+        }
         self.text_style = self.text_style.remove_modifier(modifier);
     }
 
     /// Changes the foreground color of the text style
     fn set_text_fg(&mut self, color: Color) {
+        if self.plain {
+            return;
+        }
         self.text_style = self.text_style.fg(color);
     }
 
@@ -243,36 +218,26 @@ impl<'a> Renderer {
 
     /// Adds n spaces to the left padding
     fn add_n_padding(&mut self, n: u8) {
-        self.left_padding = self.left_padding.saturating_add(n);
+        self.items.push(Item::Indent(n));
     }
 
     /// Removes n spaces from the left padding
     fn remove_n_padding(&mut self, n: u8) {
-        self.left_padding = self.left_padding.saturating_sub(n);
+        self.items.push(Item::Dedent(n));
     }
 
     /// Sets the prefix to a given value
     fn set_prefix(&mut self, prefix: char) {
-        self.prefix = Some(prefix);
+        self.items.push(Item::Prefix(Some(prefix)));
     }
 
     /// Resets the prefix
     fn reset_prefix(&mut self) {
-        self.prefix = None;
+        self.items.push(Item::Prefix(None));
     }
 
     fn add_horizontal_line(&mut self) {
-        let remaining_width = (self.width as usize) - self.current_width();
-        let line = Word {
-            index: usize::MAX,
-            content: "─".repeat(remaining_width),
-            style: self.text_style,
-            width: remaining_width as f64,
-            whitespace_width: 0.0,
-            penalty_width: 0.0,
-        };
-        self.current_line.push(line);
-        self.clear_line();
+        self.items.push(Item::HorizontalLine(self.text_style));
     }
 
     fn render_children(&mut self, node: Node<'a>) {
@@ -300,12 +265,24 @@ impl<'a> Renderer {
             return;
         };
 
-        self.ensure_empty_line();
+        // spacious spacing separates every header from the block before it, also from a header
+        // right above it
+        match self.spacing {
+            BlockSpacing::Spacious => self.items.push(Item::EnsureEmptyLine),
+            _ => self.ensure_empty_line(),
+        }
+
+        if self.plain {
+            let prefix = PLAIN_HEADER_PREFIX
+                .to_string()
+                .repeat(kind.clone() as usize);
+            self.render_string(&format!("{prefix} "), node.index());
+        }
 
         if !matches!(kind, &HeaderKind::Main | &HeaderKind::Sub) {
             self.add_modifier(Modifier::BOLD);
         }
-        self.set_text_fg(Color::Red);
+        self.set_text_fg(self.theme.header_fg);
 
         self.render_children(node);
 
@@ -314,11 +291,12 @@ impl<'a> Renderer {
         }
         self.reset_text_fg();
 
-        if matches!(kind, &HeaderKind::Main | &HeaderKind::Sub) {
+        if matches!(kind, &HeaderKind::Main | &HeaderKind::Sub) && !self.plain {
             self.clear_line();
             self.add_horizontal_line();
         }
 
+        self.after_header = true;
         self.ensure_empty_line();
     }
 
@@ -331,16 +309,19 @@ impl<'a> Renderer {
             }
         };
 
+        // the whitespace after inline elements, like links, is left out when the text continues
+        // right after them, like the `dish` of `<a>red</a>dish`
+        if !contents.is_empty() && !contents.starts_with(char::is_whitespace) {
+            while matches!(self.items.last(), Some(Item::Whitespace)) {
+                self.items.pop();
+            }
+        }
+
         self.render_string(contents, node.index());
         self.render_children(node);
     }
 
     fn render_string(&mut self, content: &str, index: usize) {
-        const TEXT_SPECIAL_CHARACTERS: [char; 9] = [',', '.', ':', ';', '\"', '\'', '!', '@', '%'];
-        if content.starts_with(TEXT_SPECIAL_CHARACTERS) && self.is_last_whitespace() {
-            self.current_line.pop();
-        }
-
         let has_trailing_whitespace = content.ends_with(' ');
         let mut words: Vec<Word> = content
             .split_whitespace()
@@ -348,9 +329,10 @@ impl<'a> Renderer {
                 index,
                 content: word.to_string(),
                 style: self.text_style,
-                width: word.chars().count() as f64,
+                width: word.width() as f64,
                 whitespace_width: 1.0,
                 penalty_width: 0.0,
+                is_hyphenated: false,
             })
             .collect();
 
@@ -360,7 +342,22 @@ impl<'a> Renderer {
             }
         }
 
-        self.wrap_append(words);
+        if let Some(hyphenator) = self.hyphenator {
+            words = words
+                .into_iter()
+                .flat_map(|word| hyphenate(hyphenator, word))
+                .collect();
+        }
+        if words
+            .iter()
+            .any(|word| word.content.chars().any(|c| c.width() == Some(2)))
+        {
+            words = words.into_iter().flat_map(split_wide).collect();
+        }
+
+        if !words.is_empty() {
+            self.push_content(Item::Words(words));
+        }
     }
 
     fn render_block_element(&mut self, node: Node<'a>) {
@@ -376,7 +373,7 @@ impl<'a> Renderer {
 
     fn render_reflink(&mut self, node: Node<'a>) {
         self.add_modifier(Modifier::ITALIC);
-        self.set_text_fg(Color::Gray);
+        self.set_text_fg(self.theme.reflink_fg);
 
         self.render_children(node);
 
@@ -424,20 +421,14 @@ impl<'a> Renderer {
 
     fn render_list_item(&mut self, node: Node<'a>) {
         self.clear_line();
-        self.current_line.push(Word {
-            index: usize::MAX,
-            content: format!("{}{LIST_PREFIX}", " ".repeat(self.left_padding as usize)),
-            style: Style::default(),
-            width: 1.0,
-            whitespace_width: 1.0,
-            penalty_width: 0.0,
-        });
+        self.push_content(Item::Bullet);
         self.add_n_padding(2);
 
         self.render_children(node);
 
         self.remove_n_padding(2);
         self.clear_line();
+        self.separate_item();
     }
 
     fn render_description_list_term(&mut self, node: Node<'a>) {
@@ -448,8 +439,18 @@ impl<'a> Renderer {
 
     fn render_description_list_description(&mut self, node: Node<'a>) {
         self.clear_line();
+        self.items.push(Item::IndentDescription);
         self.render_children(node);
+        self.items.push(Item::DedentDescription);
         self.clear_line();
+        self.separate_item();
+    }
+
+    /// Separates the items of lists and description lists by an empty line with spacious spacing
+    fn separate_item(&mut self) {
+        if self.spacing == BlockSpacing::Spacious {
+            self.ensure_empty_line();
+        }
     }
 
     fn render_bold(&mut self, node: Node<'a>) {
@@ -463,7 +464,7 @@ impl<'a> Renderer {
 
     fn render_italic(&mut self, node: Node<'a>) {
         self.add_modifier(Modifier::ITALIC);
-        self.set_text_fg(Color::Blue);
+        self.set_text_fg(self.theme.italic_fg);
 
         self.render_children(node);
 
@@ -477,8 +478,32 @@ impl<'a> Renderer {
         self.render_children(node);
     }
 
+    /// Renders a horizontal rule as its own block
+    ///
+    /// The rule is a single word spanning the whole width, so it never merges with the
+    /// surrounding text
+    fn render_horizontal_rule(&mut self, node: Node<'a>) {
+        self.ensure_empty_line();
+
+        if self.plain {
+            self.render_string(PLAIN_HORIZONTAL_RULE, node.index());
+            self.add_empty_line();
+            return;
+        }
+
+        self.push_content(Item::HorizontalRule {
+            index: node.index(),
+            symbol: self.theme.horizontal_rule_char,
+            style: Style::default()
+                .fg(self.theme.horizontal_rule_fg)
+                .add_modifier(Modifier::DIM),
+        });
+
+        self.add_empty_line();
+    }
+
     fn render_link(&mut self, node: Node<'a>, link: Link) {
-        self.links.push((self.rendered_lines.len(), node.index()));
+        self.items.push(Item::Link(node.index()));
 
         match link {
             Link::Internal(_) => self.render_wiki_link(node),
@@ -487,11 +512,38 @@ impl<'a> Renderer {
             Link::MediaLink(_) => self.render_media_link(node),
             Link::External(_) => self.render_external_link(node),
             Link::ExternalToInternal(_) => self.render_external_link(node),
+            Link::Interwiki(_) => self.render_external_link(node),
         }
+
+        // the number of a reflink already names the reference
+        if self.plain && !link.is_reference() {
+            self.render_link_marker(node, &link);
+        }
+    }
+
+    /// Renders the target of a link inline, e.g. `[link: Title]`
+    ///
+    /// The marker uses the index of the link node, so it's selected together with the link
+    fn render_link_marker(&mut self, node: Node<'a>, link: &Link) {
+        let marker = match link {
+            Link::Internal(data) => format!("[link: {}]", data.title),
+            Link::Anchor(data) => format!("[link: {}]", data.title),
+            Link::RedLink(data) => format!("[missing page: {}]", data.title),
+            Link::MediaLink(data) => format!("[media: {}]", data.title),
+            Link::External(data) => format!("[external link: {}]", data.url),
+            Link::ExternalToInternal(_) => "[external link]".to_string(),
+            Link::Interwiki(data) => match data.kind {
+                InterwikiKind::Unknown => format!("[external link: {}]", data.url),
+                _ => format!("[link: {}:{}]", data.prefix, data.title),
+            },
+        };
+
+        self.render_string(&marker, node.index());
+        self.add_whitespace();
     }
 
     fn render_wiki_link(&mut self, node: Node<'a>) {
-        self.set_text_fg(Color::Blue);
+        self.set_text_fg(self.theme.wiki_link_fg);
         self.render_children(node);
         self.reset_text_fg();
 
@@ -500,7 +552,7 @@ impl<'a> Renderer {
 
     fn render_red_link(&mut self, node: Node<'a>) {
         self.add_modifier(Modifier::ITALIC);
-        self.set_text_fg(Color::Red);
+        self.set_text_fg(self.theme.red_link_fg);
 
         self.render_children(node);
 
@@ -511,7 +563,7 @@ impl<'a> Renderer {
 
     fn render_media_link(&mut self, node: Node<'a>) {
         self.add_modifier(Modifier::ITALIC);
-        self.set_text_fg(Color::Blue);
+        self.set_text_fg(self.theme.media_link_fg);
 
         self.render_children(node);
 
@@ -522,9 +574,11 @@ impl<'a> Renderer {
 
     fn render_external_link(&mut self, node: Node<'a>) {
         self.add_modifier(Modifier::ITALIC);
+        self.set_text_fg(self.theme.external_link_fg);
 
         self.render_children(node);
 
+        self.reset_text_fg();
         self.remove_modifier(Modifier::ITALIC);
         self.add_whitespace();
     }
@@ -540,7 +594,7 @@ impl<'a> Renderer {
 
             self.add_whitespace();
 
-            self.set_text_fg(Color::DarkGray);
+            self.set_text_fg(self.theme.unsupported_fg);
             self.render_string("[x]", index);
             self.reset_text_fg();
 
@@ -575,7 +629,7 @@ impl<'a> Renderer {
             Data::Text { contents: _ } => self.render_text(node),
             Data::Division => self.render_block_element(node),
             Data::Paragraph => self.render_block_element(node),
-            Data::Span => self.render_span(node),
+            Data::Span | Data::Reference { .. } => self.render_span(node),
             Data::Reflink => self.render_reflink(node),
             Data::Hatnote => self.render_block_element(node),
             Data::RedirectMessage => self.render_block_element(node),
@@ -590,8 +644,13 @@ impl<'a> Renderer {
             Data::Bold => self.render_bold(node),
             Data::Italic => self.render_italic(node),
             Data::Linebreak => self.render_linebreak(node),
+            Data::HorizontalRule => self.render_horizontal_rule(node),
             Data::Link(link) => self.render_link(node, link.clone()),
             Data::Unknown => self.render_children(node),
+            // the image is drawn below the placeholder by the page, when the terminal can show it
+            Data::Figure { .. } => {
+                self.render_unsupported_element(false, &UnsupportedElement::Figure, node.index())
+            }
             Data::Unsupported(element) => {
                 self.render_unsupported_element(false, element, node.index())
             }
@@ -602,6 +661,1116 @@ impl<'a> Renderer {
     }
 }
 
-pub fn render_document(document: &Document, width: u16) -> RenderedDocument {
-    Renderer::render_document(document, width)
+/// Wraps the prepared words of a document into lines of a width
+struct Layout<'a> {
+    document: &'a Document,
+    /// Characters attached to the word before them
+    closing_punctuation: &'a str,
+
+    rendered_lines: Vec<Vec<Word>>,
+    links: Vec<(usize, usize)>,
+
+    current_line: Vec<Word>,
+    width: u16,
+    justify: bool,
+
+    left_padding: u8,
+    prefix: Option<char>,
+    /// Columns each level of a description list is indented by
+    description_indent: u8,
+    /// The padding of every description the lines are in, which depends on the width
+    description_paddings: Vec<u8>,
+}
+
+impl<'a> Layout<'a> {
+    fn new(
+        document: &'a Document,
+        width: u16,
+        justify: bool,
+        closing_punctuation: &'a str,
+    ) -> Self {
+        Layout {
+            document,
+            closing_punctuation,
+
+            rendered_lines: Vec::new(),
+            links: Vec::new(),
+
+            current_line: Vec::new(),
+            width,
+            justify,
+
+            left_padding: 0,
+            prefix: None,
+            description_indent: super::DESCRIPTION_INDENT,
+            description_paddings: Vec::new(),
+        }
+    }
+
+    fn wrap(mut self, items: &[Item]) -> RenderedDocument {
+        for item in items {
+            match item {
+                Item::Words(words) => self.append_words(words),
+                Item::Whitespace => self.add_whitespace(),
+                Item::ClearLine => self.clear_line(),
+                Item::EmptyLine => self.add_empty_line(),
+                Item::EnsureEmptyLine => self.ensure_empty_line(),
+                Item::Indent(n) => self.left_padding = self.left_padding.saturating_add(*n),
+                Item::Dedent(n) => self.left_padding = self.left_padding.saturating_sub(*n),
+                Item::IndentDescription => {
+                    // deeply nested replies stop indenting once half of the width is used up
+                    let indent = self.description_indent;
+                    let padding = match (self.left_padding + indent) as u16 * 2 <= self.width {
+                        true => indent,
+                        false => 0,
+                    };
+                    self.description_paddings.push(padding);
+                    self.left_padding = self.left_padding.saturating_add(padding);
+                }
+                Item::DedentDescription => {
+                    let padding = self.description_paddings.pop().unwrap_or_default();
+                    self.left_padding = self.left_padding.saturating_sub(padding);
+                }
+                Item::Prefix(prefix) => self.prefix = *prefix,
+                Item::Bullet => self.current_line.push(Word {
+                    index: usize::MAX,
+                    content: format!("{}{LIST_PREFIX}", " ".repeat(self.left_padding as usize)),
+                    style: Style::default(),
+                    width: 1.0,
+                    whitespace_width: 1.0,
+                    penalty_width: 0.0,
+                    is_hyphenated: false,
+                }),
+                Item::HorizontalLine(style) => self.add_horizontal_line(*style),
+                Item::HorizontalRule {
+                    index,
+                    symbol,
+                    style,
+                } => {
+                    // inside indented blocks the rule starts at the indentation
+                    let width = self.width.saturating_sub(self.left_padding as u16);
+                    self.rendered_lines.push(vec![
+                        self.n_whitespace(self.left_padding),
+                        Word {
+                            index: *index,
+                            content: symbol.to_string().repeat(width as usize),
+                            style: *style,
+                            width: width as f64,
+                            whitespace_width: 0.0,
+                            penalty_width: 0.0,
+                            is_hyphenated: false,
+                        },
+                    ])
+                }
+                Item::Link(index) => self.links.push((self.rendered_lines.len(), *index)),
+            }
+        }
+
+        for line in self.rendered_lines.iter_mut() {
+            trim_end(line);
+        }
+
+        RenderedDocument {
+            lines: self.rendered_lines,
+            links: self.links,
+            images: Vec::new(),
+            logical_lines: None,
+        }
+    }
+
+    /// Returns whether the last word of the current line is a whitespace
+    fn is_last_whitespace(&self) -> bool {
+        self.current_line
+            .last()
+            .map(|last| last.index == usize::MAX)
+            .unwrap_or(false)
+    }
+
+    /// Returns whether the last rendered line is an empty one
+    ///
+    /// When the current line is not empty, this will return false
+    fn is_last_empty(&self) -> bool {
+        if !self.current_line.is_empty() {
+            false
+        } else {
+            self.rendered_lines
+                .last()
+                .map(|last| last.is_empty())
+                .unwrap_or(false)
+        }
+    }
+
+    /// Adds a whitespace to the end of the current line
+    ///
+    /// The whitespace word has an index of `usize::MAX` and a width of `0` to not interfere with text wrapping. Note: If there already is a whitespace at the end of the current line, no whitespace will be added!
+    /// An empty line gets no whitespace either, it would keep the padding from being added
+    fn add_whitespace(&mut self) {
+        if self
+            .current_line
+            .last()
+            .map(|word| word.index == usize::MAX)
+            .unwrap_or(true)
+        {
+            return;
+        }
+
+        self.current_line.push(self.n_whitespace(1));
+    }
+
+    /// Returns a Word containing n amount of whitespace
+    fn n_whitespace(&self, n: u8) -> Word {
+        Word {
+            index: usize::MAX,
+            content: String::new(),
+            style: Style::default(),
+            width: 0.0,
+            whitespace_width: n as f64,
+            penalty_width: 0.0,
+            is_hyphenated: false,
+        }
+    }
+
+    /// Clears the current line
+    ///
+    /// When the current line is not empty already, it adds it to the rendered lines
+    fn clear_line(&mut self) {
+        if self.current_line.is_empty() {
+            return;
+        }
+
+        self.rendered_lines
+            .push(std::mem::take(&mut self.current_line));
+    }
+
+    /// Adds an empty line to the finished lines
+    ///
+    /// Clears the current line before adding the empty one
+    fn add_empty_line(&mut self) {
+        self.clear_line();
+        self.rendered_lines.push(Vec::new());
+    }
+
+    /// Adds an empty line only if the last line is not empty
+    fn ensure_empty_line(&mut self) {
+        if !self.is_last_empty() {
+            self.add_empty_line();
+        }
+    }
+
+    fn current_width(&self) -> usize {
+        let mut current_width: f64 = 0.0;
+        for word in self.current_line.iter() {
+            current_width = current_width + word.width + word.whitespace_width;
+        }
+        current_width as usize
+    }
+
+    /// Appends the words of a text. Closing punctuation sticks to the word before it
+    fn append_words(&mut self, words: &[Word]) {
+        let starts_with_punctuation = words.first().is_some_and(|word| {
+            word.content
+                .starts_with(|c| self.closing_punctuation.contains(c))
+        });
+        if starts_with_punctuation && self.is_last_whitespace() {
+            self.current_line.pop();
+        }
+
+        self.wrap_append(words);
+    }
+
+    /// Wraps and appends words
+    ///
+    /// This fills up the current line with words and wraps the remaining words into lines, appending them to the finished words. Note: This leaves the current line empty, except when there are not enough words to fill it up completely
+    fn wrap_append(&mut self, words: &[Word]) {
+        if words.is_empty() {
+            return;
+        }
+
+        let current_width = self.current_width() as f64;
+        let mut remaining_width = (self.width as f64) - current_width;
+
+        // if the first word doesn't fit onto the current line, the line wrapping algorithm gets confuesed.
+        // that means we have to clear it in this case
+        let mut words = Cow::Borrowed(words);
+        if words.first().map(|word| word.width).unwrap_or_default() > remaining_width {
+            // closing punctuation never starts a line and words continuing the last word, like
+            // the `dish` of `<a>red</a>dish`, aren't split from it. The word they're attached to
+            // moves along
+            let attached = match words.first() {
+                Some(first)
+                    if self.is_closing_punctuation(&first.content)
+                        || !self.can_break_before(&first.content) =>
+                {
+                    self.take_attached_word()
+                }
+                _ => None,
+            };
+
+            remaining_width = self.width as f64;
+            self.justify_line();
+            let y = self.rendered_lines.len();
+            self.clear_line();
+
+            if let Some(word) = attached {
+                self.move_link_start(y, word.index);
+                words.to_mut().insert(0, word);
+            }
+        }
+
+        if self.current_line.is_empty() {
+            remaining_width -= self.left_padding as f64;
+            self.current_line.push(self.n_whitespace(self.left_padding));
+            if let Some(prefix) = self.prefix {
+                self.current_line.push(Word {
+                    index: usize::MAX,
+                    content: prefix.to_string(),
+                    style: Style::default(),
+                    width: 1.0,
+                    whitespace_width: 1.0,
+                    penalty_width: 0.0,
+                    is_hyphenated: false,
+                });
+
+                remaining_width -= 2.0; // subtract 2: 1 char & 1 whitespace
+            }
+        }
+
+        // the following lines get the padding and the prefix too
+        let indent = self.left_padding as f64 + self.prefix.map_or(0.0, |_| 2.0);
+        let line_widths: [f64; 2] = [remaining_width, self.width as f64 - indent];
+        let mut wrapped_lines: Vec<Vec<Word>> =
+            wrap_optimal_fit(&words, &line_widths, &penalties(self.width, words.len()))
+                .unwrap_or_else(|error| {
+                    warn!("{error}, wrapping the words line by line");
+                    wrap_first_fit(&words, &line_widths)
+                })
+                .into_iter()
+                .map(|word| word.to_vec())
+                .collect();
+        let last = wrapped_lines.len() - 1;
+        for (idx, line) in wrapped_lines.iter_mut().enumerate() {
+            join_hyphenated(line, idx == last);
+        }
+
+        self.current_line.append(&mut wrapped_lines.remove(0));
+
+        // add prefixes
+        if let Some(prefix) = self.prefix {
+            for line in wrapped_lines.iter_mut() {
+                line.insert(
+                    0,
+                    Word {
+                        index: usize::MAX,
+                        content: prefix.to_string(),
+                        style: Style::default(),
+                        width: 1.0,
+                        whitespace_width: 1.0,
+                        penalty_width: 0.0,
+                        is_hyphenated: false,
+                    },
+                );
+            }
+        }
+
+        // indent the current line
+        for line in wrapped_lines.iter_mut() {
+            line.insert(0, self.n_whitespace(self.left_padding));
+        }
+
+        if let Some(last_line) = wrapped_lines.pop() {
+            self.justify_line();
+            self.clear_line();
+            self.current_line = last_line;
+
+            if self.justify {
+                for line in wrapped_lines.iter_mut() {
+                    justify(line, self.width);
+                }
+            }
+            self.rendered_lines.append(&mut wrapped_lines)
+        }
+    }
+
+    /// Returns whether the word consists of closing punctuation only
+    fn is_closing_punctuation(&self, word: &str) -> bool {
+        !word.is_empty() && word.chars().all(|c| self.closing_punctuation.contains(c))
+    }
+
+    /// Returns whether the line can be broken between the last word of the current line and the
+    /// word, when no whitespace separates them
+    fn can_break_before(&self, word: &str) -> bool {
+        let before = self
+            .current_line
+            .last()
+            .and_then(|last| last.content.chars().last());
+        match (before, word.chars().next()) {
+            (Some(before), Some(after)) => can_break_between(before, after),
+            _ => true,
+        }
+    }
+
+    /// Removes the last word of the current line when nothing separates it from the next word,
+    /// unless it's the only word on the line
+    fn take_attached_word(&mut self) -> Option<Word> {
+        let last = self.current_line.last()?;
+        if last.index == usize::MAX || last.whitespace_width != 0.0 {
+            return None;
+        }
+        let words = self
+            .current_line
+            .iter()
+            .filter(|word| word.index != usize::MAX)
+            .count();
+        if words < 2 {
+            return None;
+        }
+        self.current_line.pop()
+    }
+
+    /// Moves the start of the last link from the line `y` to the current line, when its first
+    /// word was moved there
+    fn move_link_start(&mut self, y: usize, index: usize) {
+        let (link_y, link) = match self.links.last() {
+            Some(link) if link.0 == y => *link,
+            _ => return,
+        };
+        let is_in_link = |index: usize| {
+            Node::new(self.document, index)
+                .is_some_and(|node| node.ancestors().any(|node| node.index() == link))
+        };
+        let stays = self
+            .rendered_lines
+            .get(link_y)
+            .is_some_and(|line| line.iter().any(|word| is_in_link(word.index)));
+        if is_in_link(index) && !stays {
+            let new_y = self.rendered_lines.len();
+            if let Some(link) = self.links.last_mut() {
+                link.0 = new_y;
+            }
+        }
+    }
+
+    /// Justifies the current line when justification is enabled
+    ///
+    /// This should only be called for lines that were wrapped, the last line of a block is never
+    /// justified
+    fn justify_line(&mut self) {
+        if self.justify {
+            justify(&mut self.current_line, self.width);
+        }
+    }
+
+    fn add_horizontal_line(&mut self, style: Style) {
+        let remaining_width = (self.width as usize) - self.current_width();
+        let line = Word {
+            index: usize::MAX,
+            content: "─".repeat(remaining_width),
+            style,
+            width: remaining_width as f64,
+            whitespace_width: 0.0,
+            penalty_width: 0.0,
+            is_hyphenated: false,
+        };
+        self.current_line.push(line);
+        self.clear_line();
+    }
+}
+
+/// Returns the penalties for wrapping a number of words at the width. A line only overflows when
+/// a single word is too long for it, moving words to the next line is always cheaper than letting
+/// them stick out, however ragged the lines get
+fn penalties(width: u16, words: usize) -> Penalties {
+    let penalties = Penalties::default();
+    let line_cost = penalties.nline_penalty + (width as usize).pow(2);
+    Penalties {
+        overflow_penalty: line_cost.saturating_mul(words + 1),
+        ..penalties
+    }
+}
+
+/// Stretches the whitespace between the words of a line so the line fills the given width
+///
+/// Leading words without an index (padding and prefixes) are left untouched
+fn justify(line: &mut [Word], width: u16) {
+    let (first, last) = match (
+        line.iter().position(|word| word.index != usize::MAX),
+        line.iter().rposition(|word| word.index != usize::MAX),
+    ) {
+        (Some(first), Some(last)) if first < last => (first, last),
+        _ => return,
+    };
+
+    let line_width: f64 = line[..=last]
+        .iter()
+        .map(|word| word.width + word.whitespace_width)
+        .sum::<f64>()
+        - line[last].whitespace_width;
+    let extra = (width as f64 - line_width).floor();
+    if extra <= 0.0 {
+        return;
+    }
+
+    let gaps: Vec<usize> = (first..last)
+        .filter(|idx| line[*idx].whitespace_width > 0.0)
+        .collect();
+    if gaps.is_empty() {
+        return;
+    }
+
+    let extra = extra as usize;
+    for (n, idx) in gaps.iter().enumerate() {
+        let mut additional = extra / gaps.len();
+        if n < extra % gaps.len() {
+            additional += 1;
+        }
+        line[*idx].whitespace_width += additional as f64;
+    }
+}
+
+/// Removes the whitespace after the last word of a finished line, including the whitespace words
+/// and the space after a prefix ending the line. The line breaks are already decided, so only the
+/// drawn and copied text changes
+fn trim_end(line: &mut [Word]) {
+    for word in line.iter_mut().rev() {
+        word.whitespace_width = 0.0;
+        if !word.content.is_empty() {
+            break;
+        }
+    }
+}
+
+/// Splits a long word at its hyphenation points. The pieces share the node of the word and are
+/// joined again after wrapping, the hyphen only takes up space when a line ends with a piece
+fn hyphenate(hyphenator: &Hyphenator, word: Word) -> Vec<Word> {
+    if word.width as usize <= HYPHENATION_MIN_WIDTH {
+        return vec![word];
+    }
+    let breaks = hyphenator.breaks(&word.content);
+    if breaks.is_empty() {
+        return vec![word];
+    }
+
+    let mut pieces = Vec::with_capacity(breaks.len() + 1);
+    let mut start = 0;
+    for end in breaks
+        .into_iter()
+        .chain(std::iter::once(word.content.len()))
+    {
+        let content = &word.content[start..end];
+        pieces.push(Word {
+            content: content.to_string(),
+            width: content.width() as f64,
+            whitespace_width: 0.0,
+            penalty_width: 1.0,
+            is_hyphenated: true,
+            ..word.clone()
+        });
+        start = end;
+    }
+    if let Some(last) = pieces.last_mut() {
+        last.whitespace_width = word.whitespace_width;
+        last.penalty_width = word.penalty_width;
+        last.is_hyphenated = word.is_hyphenated;
+    }
+    pieces
+}
+
+/// Returns whether a line can be broken between two characters that aren't separated by
+/// whitespace, which is the case next to the wide characters of Chinese or Japanese text
+fn can_break_between(before: char, after: char) -> bool {
+    let is_wide = |c: char| c.width() == Some(2);
+    (is_wide(before) || is_wide(after))
+        && !WIDE_OPENING_PUNCTUATION.contains(before)
+        && !WIDE_CLOSING_PUNCTUATION.contains(after)
+        && !CLOSING_PUNCTUATION.contains(after)
+}
+
+/// Splits a word with wide characters into the pieces lines can be broken between, Chinese and
+/// Japanese text has no spaces between its words. The pieces share the node of the word
+fn split_wide(word: Word) -> Vec<Word> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut chars = word.content.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        let end = match chars.peek() {
+            Some((idx, next)) if can_break_between(c, *next) => *idx,
+            Some(_) => continue,
+            None => word.content.len(),
+        };
+        let content = &word.content[start..end];
+        pieces.push(Word {
+            content: content.to_string(),
+            width: content.width() as f64,
+            whitespace_width: 0.0,
+            penalty_width: 0.0,
+            is_hyphenated: false,
+            ..word.clone()
+        });
+        start = end;
+    }
+    if let Some(last) = pieces.last_mut() {
+        last.whitespace_width = word.whitespace_width;
+        last.penalty_width = word.penalty_width;
+        last.is_hyphenated = word.is_hyphenated;
+    }
+    pieces
+}
+
+/// Joins the pieces of hyphenated words that stayed on the same line and adds the hyphen to a
+/// piece ending the line
+fn join_hyphenated(line: &mut Vec<Word>, is_last: bool) {
+    let mut joined: Vec<Word> = Vec::with_capacity(line.len());
+    for word in line.drain(..) {
+        match joined.last_mut() {
+            Some(piece) if piece.is_hyphenated && piece.index == word.index => {
+                piece.content.push_str(&word.content);
+                piece.width += word.width;
+                piece.whitespace_width = word.whitespace_width;
+                piece.penalty_width = word.penalty_width;
+                piece.is_hyphenated = word.is_hyphenated;
+            }
+            _ => joined.push(word),
+        }
+    }
+
+    if let Some(piece) = joined.last_mut().filter(|piece| piece.is_hyphenated) {
+        if !is_last {
+            piece.content.push('-');
+            piece.width += 1.0;
+        }
+        piece.penalty_width = 0.0;
+        piece.is_hyphenated = false;
+    }
+    *line = joined;
+}
+
+/// Walks the node and prepares its words
+fn prepare(
+    document: &Document,
+    node: usize,
+    theme: &Theme,
+    plain: bool,
+    hyphenator: Option<&Hyphenator>,
+    spacing: BlockSpacing,
+) -> Option<Vec<Item>> {
+    let node = document.nth(node)?;
+    Some(Renderer::new(theme, plain, hyphenator, spacing).prepare(node))
+}
+
+/// Renders the whole document by preparing and wrapping it at once
+fn render_whole(renderer: &impl PageRenderer, document: &Document, width: u16) -> RenderedDocument {
+    match renderer.prepare(document, 0) {
+        Some(prepared) => prepared.wrap(document, width),
+        None => {
+            warn!("document contains no nodes, aborting the render");
+            RenderedDocument::default()
+        }
+    }
+}
+
+pub fn render_document(
+    document: &Document,
+    theme: &Theme,
+    width: u16,
+    justify: bool,
+) -> RenderedDocument {
+    let options = RenderOptions {
+        justify,
+        ..RenderOptions::default()
+    };
+    let renderer = DefaultRenderer::new(Arc::new(theme.clone()), options);
+    render_whole(&renderer, document, width)
+}
+
+/// Renders the document without any styling, prefixing headers with `#` and adding the targets
+/// of links inline
+pub fn render_plain_document(document: &Document, theme: &Theme, width: u16) -> RenderedDocument {
+    let renderer = PlainRenderer::new(Arc::new(theme.clone()), RenderOptions::default());
+    render_whole(&renderer, document, width)
+}
+
+/// The renderer used per default, rendering the document as styled and wrapped text
+pub struct DefaultRenderer {
+    theme: Arc<Theme>,
+    options: RenderOptions,
+}
+
+impl DefaultRenderer {
+    pub fn new(theme: Arc<Theme>, options: RenderOptions) -> Self {
+        Self { theme, options }
+    }
+}
+
+impl PageRenderer for DefaultRenderer {
+    fn name(&self) -> &str {
+        "default"
+    }
+
+    fn render(&self, document: &Document, width: u16) -> RenderedDocument {
+        render_whole(self, document, width)
+    }
+
+    fn prepare(&self, document: &Document, node: usize) -> Option<PreparedDocument> {
+        Some(PreparedDocument {
+            items: prepare(
+                document,
+                node,
+                &self.theme,
+                false,
+                self.options.hyphenator.as_ref(),
+                self.options.spacing,
+            )?,
+            justify: self.options.justify,
+            closing_punctuation: self.options.closing_punctuation.clone(),
+            description_indent: self.options.description_indent,
+        })
+    }
+}
+
+/// A renderer without any styling, meant for screen readers and piping the output. It neither
+/// justifies nor hyphenates the lines
+pub struct PlainRenderer {
+    theme: Arc<Theme>,
+    options: RenderOptions,
+}
+
+impl PlainRenderer {
+    pub fn new(theme: Arc<Theme>, options: RenderOptions) -> Self {
+        Self { theme, options }
+    }
+}
+
+impl PageRenderer for PlainRenderer {
+    fn name(&self) -> &str {
+        "plain"
+    }
+
+    fn is_plain(&self) -> bool {
+        true
+    }
+
+    fn render(&self, document: &Document, width: u16) -> RenderedDocument {
+        render_whole(self, document, width)
+    }
+
+    fn prepare(&self, document: &Document, node: usize) -> Option<PreparedDocument> {
+        Some(PreparedDocument {
+            items: prepare(
+                document,
+                node,
+                &self.theme,
+                true,
+                None,
+                self.options.spacing,
+            )?,
+            justify: false,
+            closing_punctuation: self.options.closing_punctuation.clone(),
+            description_indent: self.options.description_indent,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Style;
+    use wiki_api::{
+        document::{Data, Document},
+        languages::Language,
+        parser::{Parser, WikipediaParser},
+        Endpoint,
+    };
+
+    use proptest::prelude::*;
+
+    use super::{render_document, render_plain_document, Layout, PlainRenderer, Renderer};
+    use crate::{
+        renderer::{
+            line_text, BlockSpacing, PageRenderer, RenderOptions, RenderedDocument, Word,
+            CLOSING_PUNCTUATION,
+        },
+        theme::Theme,
+    };
+
+    fn render_text(document: &Document, width: u16) -> Vec<String> {
+        lines_text(&render_plain_document(document, &Theme::default(), width))
+    }
+
+    fn lines_text(rendered: &RenderedDocument) -> Vec<String> {
+        rendered
+            .lines
+            .iter()
+            .map(|line| {
+                let mut text = String::new();
+                for word in line {
+                    text.push_str(&word.content);
+                    text.push_str(&" ".repeat(word.whitespace_width as usize));
+                }
+                text.trim_end().to_string()
+            })
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    fn parse(html: &str) -> Document {
+        let nodes = WikipediaParser::parse_document(
+            html,
+            Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+            Language::default(),
+        )
+        .nodes();
+        Document { nodes }
+    }
+
+    #[test]
+    fn test_plain_document() {
+        let document = parse(
+            r#"<h2 id="History">History</h2>
+            <p>See <a rel="mw:WikiLink" href="./Iron" title="Iron">iron</a> for <i>more</i>.</p>"#,
+        );
+        let rendered = render_plain_document(&document, &Theme::default(), 80);
+
+        let text = render_text(&document, 80);
+        assert_eq!(text, ["## History", "See iron [link: Iron] for more."]);
+
+        assert!(rendered
+            .lines
+            .iter()
+            .flatten()
+            .all(|word| word.style == Style::default()));
+
+        // the link marker belongs to the link node, so it's selected together with the link
+        let link = document
+            .nodes
+            .iter()
+            .find(|raw| matches!(raw.data, Data::Link(_)))
+            .unwrap()
+            .index;
+        assert!(rendered
+            .lines
+            .iter()
+            .flatten()
+            .any(|word| word.content == "[link:" && word.index == link));
+    }
+
+    #[test]
+    fn test_description_list_indentation() {
+        let document = parse(
+            "<dl><dd>First reply<dl><dd>Second reply<dl><dd>Third reply</dd></dl></dd></dl></dd></dl>",
+        );
+        assert_eq!(
+            render_text(&document, 80),
+            ["  First reply", "    Second reply", "      Third reply"]
+        );
+
+        // narrow pages stop indenting before the text gets squeezed
+        assert_eq!(
+            render_text(&document, 8),
+            [
+                "  First",
+                "  reply",
+                "    Second",
+                "    reply",
+                "    Third",
+                "    reply"
+            ]
+        );
+
+        // talk pages indent the replies further
+        let renderer = PlainRenderer::new(
+            std::sync::Arc::new(Theme::default()),
+            RenderOptions {
+                description_indent: 4,
+                ..RenderOptions::default()
+            },
+        );
+        assert_eq!(
+            lines_text(&renderer.render(&document, 80)),
+            [
+                "    First reply",
+                "        Second reply",
+                "            Third reply"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_indented_horizontal_rule() {
+        let document = parse("<dl><dd><p>Reply</p><hr></dd></dl>");
+        let rendered = render_document(&document, &Theme::default(), 10, false);
+        let rule = rendered
+            .lines
+            .iter()
+            .find(|line| line.iter().any(|word| word.content.contains('─')))
+            .unwrap();
+        assert_eq!(rule[0].whitespace_width, 2.0);
+        assert_eq!(
+            rule.iter()
+                .map(|word| word.width + word.whitespace_width)
+                .sum::<f64>(),
+            10.0
+        );
+    }
+
+    #[test]
+    fn test_closing_punctuation() {
+        let link = r#"<a rel="mw:WikiLink" href="./Rust" title="Rust">Rust</a>"#;
+        for punctuation in [",", ".", ";", ":", ")", "?", "!", "%", "\"", "'"] {
+            let document = parse(&format!(
+                "<p>The {link}{punctuation} language and {link}{punctuation}</p>"
+            ));
+            let rendered = render_document(&document, &Theme::default(), 80, false);
+            assert_eq!(
+                lines_text(&rendered),
+                [format!(
+                    "The Rust{punctuation} language and Rust{punctuation}"
+                )]
+            );
+        }
+    }
+
+    #[test]
+    fn test_closing_punctuation_wrapping() {
+        let link = r#"<a rel="mw:WikiLink" href="./Rust" title="Rust">Rust</a>"#;
+
+        // the parenthesis doesn't fit behind the link, so the link moves to the next line with it
+        let document = parse(&format!("<p>Written in {link}) today</p>"));
+        let rendered = render_document(&document, &Theme::default(), 15, false);
+        assert_eq!(lines_text(&rendered), ["Written in", "Rust) today"]);
+        let y = rendered
+            .lines
+            .iter()
+            .position(|line| line.iter().any(|word| word.content == "Rust"));
+        assert_eq!(rendered.links.first().map(|(y, _)| *y), y);
+
+        let document = parse(&format!("<p>Is it {link}?! Yes, {link}; and {link}.</p>"));
+        let rendered = render_document(&document, &Theme::default(), 10, false);
+        assert_eq!(
+            lines_text(&rendered),
+            ["Is it", "Rust?!", "Yes, Rust;", "and Rust."]
+        );
+
+        // a word alone on its line keeps its punctuation, even when it's too wide
+        let document = parse(&format!("<p>{link}%</p>"));
+        let rendered = render_document(&document, &Theme::default(), 4, false);
+        assert_eq!(lines_text(&rendered), ["Rust", "%"]);
+    }
+
+    #[test]
+    fn test_no_trailing_whitespace() {
+        let document = parse(
+            r#"<div class="hatnote"><p>Lepidocrocite is an iron oxide-hydroxide mineral.</p></div><p>Rust is an "<a rel="mw:WikiLink" href="./Iron_oxide" title="Iron oxide">iron oxide</a>", a usually <b>reddish-brown</b> <i>oxide</i>.</p><ul><li>Hydrated iron oxide <a rel="mw:WikiLink" href="./Goethite" title="Goethite">goethite</a></li></ul><blockquote>Rust never sleeps</blockquote><dl><dt>Oxidation</dt><dd>The loss of electrons</dd></dl>"#,
+        );
+        for width in [6, 10, 30, 80] {
+            let rendered = [
+                render_document(&document, &Theme::default(), width, false),
+                render_document(&document, &Theme::default(), width, true),
+                render_plain_document(&document, &Theme::default(), width),
+            ];
+            for line in rendered.iter().flat_map(|rendered| rendered.lines.iter()) {
+                let text = line_text(line);
+                assert!(
+                    !text.ends_with(' '),
+                    "line '{}' at width {} ends with whitespace",
+                    text,
+                    width
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_wrap_prepared() {
+        use super::DefaultRenderer;
+        use crate::renderer::{PageRenderer, RenderOptions};
+
+        let document = parse(
+            r#"<p>Rust is a <b>general-purpose</b> programming language.</p><ul><li>Fast (<a rel="mw:WikiLink" href="./Speed" title="Speed">speed</a>)</li></ul><hr><dl><dt>Safe</dt><dd>Memory safety without a garbage collector</dd></dl>"#,
+        );
+        let renderer = DefaultRenderer::new(
+            std::sync::Arc::new(Theme::default()),
+            RenderOptions::default(),
+        );
+        let prepared = renderer.prepare(&document, 0).unwrap();
+
+        // the words prepared once are wrapped to every width like rendering the whole document
+        for width in [8, 20, 80] {
+            let wrapped = prepared.wrap(&document, width);
+            let rendered = render_document(&document, &Theme::default(), width, false);
+            assert_eq!(lines_text(&wrapped), lines_text(&rendered));
+            assert_eq!(wrapped.links, rendered.links);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "hyphenation")]
+    fn test_hyphenation() {
+        use std::sync::Arc;
+
+        use wiki_api::languages::Language;
+
+        use super::DefaultRenderer;
+        use crate::renderer::{hyphenate::Hyphenator, PageRenderer, RenderOptions};
+
+        let document = parse(
+            r#"<p>Rust is a general-purpose programming language emphasizing performance, type safety, and concurrency. It enforces memory safety without a conventional <a rel="mw:WikiLink" href="./Garbage_collection" title="Garbage collection">garbage collector</a>.</p>"#,
+        );
+        let renderer = |hyphenator| {
+            DefaultRenderer::new(
+                Arc::new(Theme::default()),
+                RenderOptions {
+                    hyphenator,
+                    ..RenderOptions::default()
+                },
+            )
+        };
+
+        let plain = renderer(None).render(&document, 30);
+        assert_eq!(
+            lines_text(&plain),
+            [
+                "Rust is a general-purpose",
+                "programming language",
+                "emphasizing performance, type",
+                "safety, and concurrency. It",
+                "enforces memory safety without",
+                "a conventional garbage",
+                "collector.",
+            ]
+        );
+
+        let hyphenated = renderer(Hyphenator::new(Language::English)).render(&document, 30);
+        assert_eq!(
+            lines_text(&hyphenated),
+            [
+                "Rust is a general-purpose",
+                "programming language emphasiz-",
+                "ing performance, type safety,",
+                "and concurrency. It enforces",
+                "memory safety without a con-",
+                "ventional garbage collector.",
+            ]
+        );
+
+        // both halves belong to the text node, so they're selected and highlighted together
+        let halves: Vec<usize> = hyphenated
+            .lines
+            .iter()
+            .flatten()
+            .filter(|word| word.content == "emphasiz-" || word.content == "ing")
+            .map(|word| word.index)
+            .collect();
+        assert_eq!(halves.len(), 2);
+        assert_eq!(halves[0], halves[1]);
+    }
+
+    /// A text node of the generated paragraph: its words, whether it ends with whitespace and
+    /// whether a whitespace word separates it from the next node
+    fn arb_text() -> impl Strategy<Value = (Vec<String>, bool, bool)> {
+        let word = prop_oneof![
+            6 => "[a-z]{1,12}",
+            1 => "[a-z]{13,40}",
+            1 => "[,.;)]{1,2}",
+        ];
+        (
+            prop::collection::vec(word, 0..8),
+            any::<bool>(),
+            any::<bool>(),
+        )
+    }
+
+    /// Returns the width of the line up to its last visible word, a line without any only has the
+    /// width of its padding
+    fn line_width(line: &[Word]) -> f64 {
+        match line.iter().rposition(|word| word.width > 0.0) {
+            Some(last) => {
+                line[..=last]
+                    .iter()
+                    .map(|word| word.width + word.whitespace_width)
+                    .sum::<f64>()
+                    - line[last].whitespace_width
+            }
+            None => line.first().map_or(0.0, |word| word.whitespace_width),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_wrap_append_invariants(
+            texts in prop::collection::vec(arb_text(), 1..8),
+            width in 1u16..60,
+            left_padding in 0u8..8,
+            prefix in prop::option::of(Just('|')),
+            justify in any::<bool>(),
+        ) {
+            let theme = Theme::default();
+            let mut renderer = Renderer::new(&theme, false, None, BlockSpacing::Normal);
+
+            let mut expected: Vec<(usize, String)> = Vec::new();
+            for (index, (words, has_trailing_whitespace, has_whitespace_word)) in
+                texts.into_iter().enumerate()
+            {
+                let mut content = words.join(" ");
+                if has_trailing_whitespace {
+                    content.push(' ');
+                }
+                renderer.render_string(&content, index);
+                if has_whitespace_word {
+                    renderer.add_whitespace();
+                }
+                expected.extend(words.into_iter().map(|word| (index, word)));
+            }
+            renderer.clear_line();
+
+            let document = Document { nodes: Vec::new() };
+            let mut layout = Layout::new(&document, width, justify, CLOSING_PUNCTUATION);
+            layout.left_padding = left_padding;
+            layout.prefix = prefix;
+            let rendered = layout.wrap(&renderer.items);
+
+            // the words are kept in their order, without losing or duplicating any
+            let words: Vec<(usize, String)> = rendered
+                .lines
+                .iter()
+                .flatten()
+                .filter(|word| word.index != usize::MAX)
+                .map(|word| (word.index, word.content.clone()))
+                .collect();
+            prop_assert_eq!(words, expected);
+
+            let indent = left_padding as f64 + if prefix.is_some() { 2.0 } else { 0.0 };
+            for line in rendered.lines.iter() {
+                // every line starts with the padding and the prefix, the only other decorations
+                // are the whitespace words between the nodes
+                prop_assert_eq!(line[0].index, usize::MAX);
+                prop_assert_eq!(line[0].whitespace_width, left_padding as f64);
+                let decorations = match prefix {
+                    Some(prefix) => {
+                        prop_assert_eq!(line[1].index, usize::MAX);
+                        prop_assert_eq!(&line[1].content, &prefix.to_string());
+                        2
+                    }
+                    None => 1,
+                };
+                prop_assert!(line[decorations..]
+                    .iter()
+                    .all(|word| word.index != usize::MAX || word.width == 0.0));
+                prop_assert!(!line_text(line).ends_with(' '), "line {:?} ends with whitespace", line);
+
+                // only a single word that is too long on its own overflows the line. On very
+                // narrow pages, the padding alone can be too wide
+                if line_width(line) > width as f64 {
+                    let words: Vec<&Word> = line[decorations..]
+                        .iter()
+                        .filter(|word| word.width > 0.0)
+                        .collect();
+                    let is_oversized = match words[..] {
+                        [] => indent > width as f64,
+                        [word] => word.width > width as f64 - indent,
+                        _ => false,
+                    };
+                    prop_assert!(is_oversized, "line {:?} is wider than {}", line, width);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,179 @@
+use ratatui::style::{Color, Style};
+use serde::{Deserialize, Serialize};
+
+use super::RenderedDocument;
+
+/// Version of the format of the dumps, increased when a field changes its meaning or is removed
+pub const DUMP_VERSION: u32 = 1;
+
+/// The lines and links of a rendered page, written as json to attach it to bug reports about
+/// pages rendering wrong
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RenderDump {
+    pub version: u32,
+    pub title: String,
+    /// Name of the renderer, like `default` or `plain`
+    pub renderer: String,
+    pub width: u16,
+    pub lines: Vec<Vec<DumpWord>>,
+    pub links: Vec<DumpLink>,
+}
+
+/// A rendered word with the whitespace after it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DumpWord {
+    pub content: String,
+    /// Index of the node the word belongs to, `None` for padding, prefixes and bullets
+    pub node: Option<usize>,
+    /// The style in the notation of [`style_notation`]
+    pub style: String,
+    /// Number of columns of the content
+    pub width: f64,
+    /// Number of spaces after the word
+    pub whitespace: f64,
+    /// Number of columns added when the line is broken after the word
+    #[serde(default)]
+    pub penalty: f64,
+}
+
+/// The start of a link
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DumpLink {
+    pub line: usize,
+    pub node: usize,
+}
+
+impl RenderDump {
+    pub fn new(title: &str, renderer: &str, width: u16, rendered: &RenderedDocument) -> Self {
+        let lines = rendered
+            .lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|word| DumpWord {
+                        content: word.content.clone(),
+                        node: (word.index != usize::MAX).then_some(word.index),
+                        style: style_notation(word.style),
+                        width: word.width,
+                        whitespace: word.whitespace_width,
+                        penalty: word.penalty_width,
+                    })
+                    .collect()
+            })
+            .collect();
+        let links = rendered
+            .links
+            .iter()
+            .map(|(line, node)| DumpLink {
+                line: *line,
+                node: *node,
+            })
+            .collect();
+
+        RenderDump {
+            version: DUMP_VERSION,
+            title: title.to_string(),
+            renderer: renderer.to_string(),
+            width,
+            lines,
+            links,
+        }
+    }
+}
+
+/// Describes the style like `fg=Blue bg=Reset +BOLD+ITALIC`, leaving out the parts that aren't
+/// set
+pub fn style_notation(style: Style) -> String {
+    let color = |color: Option<Color>| color.map(|color| format!("{color:?}"));
+    let mut parts = Vec::new();
+    if let Some(fg) = color(style.fg) {
+        parts.push(format!("fg={fg}"));
+    }
+    if let Some(bg) = color(style.bg) {
+        parts.push(format!("bg={bg}"));
+    }
+    if !style.add_modifier.is_empty() {
+        let names: Vec<&str> = style
+            .add_modifier
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect();
+        parts.push(format!("+{}", names.join("+")));
+    }
+    if !style.sub_modifier.is_empty() {
+        let names: Vec<&str> = style
+            .sub_modifier
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect();
+        parts.push(format!("-{}", names.join("-")));
+    }
+    match parts.is_empty() {
+        true => "default".to_string(),
+        false => parts.join(" "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::{
+        document::Document,
+        languages::Language,
+        parser::{Parser, WikipediaParser},
+        Endpoint,
+    };
+
+    use crate::{renderer::default_renderer::render_document, theme::Theme};
+
+    use super::{DumpLink, RenderDump, DUMP_VERSION};
+
+    #[test]
+    fn test_render_dump() {
+        let nodes = WikipediaParser::parse_document(
+            r#"<p>Rust is an <b>iron</b> <a rel="mw:WikiLink" href="./Oxide" title="Oxide">oxide</a>.</p><ul><li>Goethite</li></ul>"#,
+            Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+            Language::default(),
+        )
+        .nodes();
+        let document = Document { nodes };
+        let rendered = render_document(&document, &Theme::default(), 40, false);
+        let dump = RenderDump::new("Rust (iron oxide)", "default", 40, &rendered);
+
+        assert_eq!(dump.version, DUMP_VERSION);
+        assert_eq!(dump.lines.len(), rendered.lines.len());
+        let iron = dump
+            .lines
+            .iter()
+            .flatten()
+            .find(|word| word.content == "iron")
+            .unwrap();
+        assert_eq!(iron.style, "+BOLD");
+        assert_eq!(iron.width, 4.0);
+        // the bullet of the list doesn't belong to a node
+        let bullet = dump
+            .lines
+            .iter()
+            .flatten()
+            .find(|word| word.content.ends_with('-'))
+            .unwrap();
+        assert_eq!(bullet.node, None);
+        let (line, node) = rendered.links[0];
+        assert_eq!(dump.links, [DumpLink { line, node }]);
+
+        // the fields keep their names, so older dumps can still be read
+        let json = serde_json::to_value(&dump).unwrap();
+        for field in ["version", "title", "renderer", "width", "lines", "links"] {
+            assert!(json.get(field).is_some(), "missing field '{}'", field);
+        }
+        assert_eq!(
+            json["lines"][1][0]
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            ["content", "node", "penalty", "style", "whitespace", "width"]
+        );
+        let parsed: RenderDump = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, dump);
+    }
+}
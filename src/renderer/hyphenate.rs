@@ -0,0 +1,115 @@
+#[cfg(feature = "hyphenation")]
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+#[cfg(feature = "hyphenation")]
+use hyphenation::{Hyphenator as _, Load, Standard};
+#[cfg(feature = "hyphenation")]
+use tracing::warn;
+use wiki_api::languages::Language;
+
+/// Splits long words at the hyphenation points of a language. Without the `hyphenation` feature,
+/// no patterns are bundled and words are never split
+#[derive(Clone)]
+pub struct Hyphenator {
+    #[cfg(feature = "hyphenation")]
+    dictionary: Arc<Standard>,
+}
+
+impl Hyphenator {
+    /// Returns the hyphenator for the language, `None` when no patterns are bundled for it. The
+    /// patterns are loaded once per language
+    #[cfg(feature = "hyphenation")]
+    pub fn new(language: Language) -> Option<Hyphenator> {
+        static DICTIONARIES: OnceLock<Mutex<HashMap<String, Option<Arc<Standard>>>>> =
+            OnceLock::new();
+
+        let patterns = hyphenation::Language::try_from_code(pattern_code(language.code()))?;
+        let mut dictionaries = DICTIONARIES.get_or_init(Default::default).lock().ok()?;
+        let dictionary = dictionaries
+            .entry(patterns.code().to_string())
+            .or_insert_with(|| match Standard::from_embedded(patterns) {
+                Ok(dictionary) => Some(Arc::new(dictionary)),
+                Err(error) => {
+                    warn!("failed loading the hyphenation patterns for '{patterns}': {error}");
+                    None
+                }
+            })
+            .clone()?;
+        Some(Hyphenator { dictionary })
+    }
+
+    #[cfg(not(feature = "hyphenation"))]
+    pub fn new(_language: Language) -> Option<Hyphenator> {
+        None
+    }
+
+    /// Returns the byte offsets the word can be split at. Punctuation around the word stays
+    /// attached to its first and last piece
+    #[cfg(feature = "hyphenation")]
+    pub fn breaks(&self, word: &str) -> Vec<usize> {
+        let start = match word.find(char::is_alphabetic) {
+            Some(start) => start,
+            None => return Vec::new(),
+        };
+        let end = word
+            .rfind(char::is_alphabetic)
+            .map(|end| end + word[end..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(word.len());
+
+        let core = &word[start..end];
+        // words that are already split, like compounds with a hyphen, are left as they are
+        if !core.chars().all(char::is_alphabetic) {
+            return Vec::new();
+        }
+        self.dictionary
+            .hyphenate(core)
+            .breaks
+            .into_iter()
+            .map(|idx| start + idx)
+            .collect()
+    }
+
+    #[cfg(not(feature = "hyphenation"))]
+    pub fn breaks(&self, _word: &str) -> Vec<usize> {
+        Vec::new()
+    }
+}
+
+/// Returns the code of the patterns for the language of a wiki, which is more specific for some
+/// languages
+#[cfg(feature = "hyphenation")]
+fn pattern_code(code: &str) -> &str {
+    match code {
+        "en" | "simple" => "en-us",
+        "de" => "de-1996",
+        "el" => "el-monoton",
+        "mn" => "mn-cyrl",
+        "sr" => "sr-cyrl",
+        "sh" => "sh-latn",
+        "no" => "nb",
+        code => code,
+    }
+}
+
+#[cfg(all(test, feature = "hyphenation"))]
+mod tests {
+    use wiki_api::languages::Language;
+
+    use super::Hyphenator;
+
+    #[test]
+    fn test_breaks() {
+        let hyphenator = Hyphenator::new(Language::English).unwrap();
+        assert_eq!(hyphenator.breaks("programming"), [3, 7]);
+        // the punctuation isn't part of the word
+        assert_eq!(hyphenator.breaks("(programming),"), [4, 8]);
+        assert!(hyphenator.breaks("state-of-the-art").is_empty());
+
+        assert!(Hyphenator::new(Language::German).is_some());
+        // there are no patterns for Chinese
+        assert!(Hyphenator::new(Language::Chinese).is_none());
+    }
+}
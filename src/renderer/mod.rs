@@ -1,28 +1,300 @@
+//! Renders the documents of pages into lines of styled words.
+//!
+//! The renderers only need the document and a [`Theme`] for the styles, no terminal, so they can
+//! be used on their own, like for rendering articles ahead of time. [`RenderedDocument::plain_text`]
+//! and [`RenderedDocument::ansi_text`] serialize the lines for printing them.
+//!
+//! ```
+//! use wiki_api::document::{Data, Document, Raw};
+//! use wiki_tui::{renderer::default_renderer::render_document, theme::Theme};
+//!
+//! // a paragraph with a single text
+//! let node = |index, parent, child, data| Raw {
+//!     index,
+//!     parent,
+//!     prev: None,
+//!     next: None,
+//!     first_child: child,
+//!     last_child: child,
+//!     data,
+//! };
+//! let document = Document {
+//!     nodes: vec![
+//!         node(0, None, Some(1), Data::Paragraph),
+//!         node(1, Some(0), None, Data::Text {
+//!             contents: "Rust is an iron oxide, usually reddish-brown".to_string(),
+//!         }),
+//!     ],
+//! };
+//!
+//! // the paragraph is wrapped at 20 columns, with an empty line around it
+//! let rendered = render_document(&document, &Theme::default(), 20, false);
+//! assert_eq!(
+//!     rendered.plain_text(),
+//!     "\nRust is an iron\noxide, usually\nreddish-brown\n\n"
+//! );
+//! // the words point back to the node they were rendered from
+//! let rust = rendered.lines[1].iter().find(|word| word.content == "Rust");
+//! assert_eq!(rust.map(|word| word.index), Some(1));
+//! ```
+//!
+//! Lines built outside of a renderer are serialized the same way:
+//!
+//! ```
+//! use ratatui::style::{Style, Stylize};
+//! use wiki_tui::{
+//!     renderer::{RenderedDocument, Word},
+//!     theme::Theme,
+//! };
+//!
+//! let rendered = RenderedDocument::new(vec![vec![
+//!     Word::new(None, "Rust", Style::new().bold(), 1),
+//!     Word::new(None, "oxide", Style::new(), 0),
+//! ]]);
+//! assert_eq!(rendered.plain_text(), "Rust oxide\n");
+//! assert_eq!(
+//!     rendered.ansi_text(&Theme::default()),
+//!     "\x1b[1mRust \x1b[0moxide\n"
+//! );
+//! ```
+
+pub mod ansi;
+pub mod bidi;
 pub mod default_renderer;
+pub mod dump;
+pub mod hyphenate;
+#[cfg(test)]
+mod snapshot_tests;
 #[cfg(debug_assertions)]
 pub mod test_renderer;
 
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    ops::{Range, RangeInclusive},
+    sync::Arc,
+};
+
 use ratatui::style::Style;
+use regex::Regex;
+use reqwest::Url;
+use serde::Deserialize;
 use textwrap::core::Fragment;
-use wiki_api::document::{Document, Node};
+use unicode_width::UnicodeWidthStr;
+use wiki_api::document::{Data, Document, Node};
+
+use crate::theme::Theme;
+
+use self::{default_renderer::PreparedDocument, hyphenate::Hyphenator};
+
+/// Characters that stick to the word before them when they start a text, like the comma after a
+/// link. A word made of them only never starts a line
+pub const CLOSING_PUNCTUATION: &str = ",.:;!?%)]}\"'@";
+
+/// Spaces the whitespace of the words is borrowed from when drawing them, wider whitespace is
+/// allocated
+const PADDING: &str = match std::str::from_utf8(&[b' '; 256]) {
+    Ok(padding) => padding,
+    Err(_) => panic!("spaces are valid utf-8"),
+};
+
+/// Returns the header of a section node, `None` for other nodes and sections without a header
+/// like the lead of an article
+pub fn section_header(node: Node) -> Option<Node> {
+    match node.data() {
+        Data::Section { .. } => node
+            .children()
+            .find(|child| matches!(child.data(), Data::Header { .. })),
+        _ => None,
+    }
+}
+
+/// Returns whether folding the section hides the node, which is the case for every node of the
+/// section except its header
+pub fn fold_hides(document: &Document, section: usize, index: usize) -> bool {
+    let section = match document.nth(section) {
+        Some(section) => section,
+        None => return false,
+    };
+    match section_header(section) {
+        Some(header) => {
+            let last = |node: Node| node.descendants().last().unwrap_or(node).index();
+            (section.index()..=last(section)).contains(&index)
+                && !(header.index()..=last(header)).contains(&index)
+        }
+        None => false,
+    }
+}
+
+/// Returns the outermost sections of the document in their order, which render the same on their
+/// own as in the whole document. `None` when there are fewer than two or anything outside of
+/// them would be rendered, then the document can only be rendered as a whole
+pub fn top_level_sections(document: &Document) -> Option<Vec<usize>> {
+    let mut sections = Vec::new();
+    let mut nodes = vec![document.nth(0)?];
+    while let Some(node) = nodes.pop() {
+        match node.data() {
+            Data::Section { .. } => {
+                sections.push(node.index());
+                continue;
+            }
+            // the elements around the sections, like the body, render nothing themselves
+            Data::Unknown => {}
+            Data::Text { contents } if contents.trim().is_empty() => {}
+            _ => return None,
+        }
+        let children: Vec<Node> = node.children().collect();
+        nodes.extend(children.into_iter().rev());
+    }
+    (sections.len() > 1).then_some(sections)
+}
+
+/// A renderer turning a document into lines of words that can be displayed by the page
+pub trait PageRenderer {
+    /// Name of the renderer, displayed in the status bar
+    fn name(&self) -> &str;
+
+    /// Whether the page should be displayed without any styling or decorations
+    fn is_plain(&self) -> bool {
+        false
+    }
+
+    fn render(&self, document: &Document, width: u16) -> RenderedDocument;
 
+    /// Walks the node, the whole document or one of its `top_level_sections`, and prepares its
+    /// words once, so they can be wrapped at every width without walking the document again.
+    /// Large pages are prepared section by section. `None` when the renderer can only render
+    /// whole documents
+    fn prepare(&self, _document: &Document, _node: usize) -> Option<PreparedDocument> {
+        None
+    }
+
+    /// Describes the selected nodes for the status bar, for renderers made for debugging the
+    /// document. `None` shows only the name of the renderer
+    fn node_label(&self, _document: &Document, _selected: (usize, usize)) -> Option<String> {
+        None
+    }
+}
+
+/// Columns each level of a description list is indented by per default
+pub const DESCRIPTION_INDENT: u8 = 2;
+
+/// How many empty lines separate the blocks of a page, like paragraphs, lists and headers
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockSpacing {
+    /// At most one empty line between blocks and none below headers
+    Compact,
+    /// One empty line around every block
+    #[default]
+    Normal,
+    /// Like normal, with an empty line before every header and between the items of lists and
+    /// description lists
+    Spacious,
+}
+
+/// How the renderers lay out the words of a page
+#[derive(Clone)]
+pub struct RenderOptions {
+    /// Whether wrapped lines are justified. The plain renderer never justifies them
+    pub justify: bool,
+    /// Characters that stick to the word before them, see [`CLOSING_PUNCTUATION`]
+    pub closing_punctuation: String,
+    /// Splits long words when wrapping, `None` when hyphenation is disabled
+    pub hyphenator: Option<Hyphenator>,
+    pub spacing: BlockSpacing,
+    /// Columns each level of a description list is indented by, which talk pages use for the
+    /// replies of a discussion
+    pub description_indent: u8,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            justify: false,
+            closing_punctuation: CLOSING_PUNCTUATION.to_string(),
+            hyphenator: None,
+            spacing: BlockSpacing::default(),
+            description_indent: DESCRIPTION_INDENT,
+        }
+    }
+}
+
+/// Returns all available renderers. The first one is the default renderer
+pub fn renderers(theme: Arc<Theme>, options: RenderOptions) -> Vec<Box<dyn PageRenderer + Send>> {
+    #[allow(unused_mut)]
+    let mut renderers: Vec<Box<dyn PageRenderer + Send>> = vec![
+        Box::new(default_renderer::DefaultRenderer::new(
+            theme.clone(),
+            options.clone(),
+        )),
+        Box::new(default_renderer::PlainRenderer::new(
+            theme.clone(),
+            options.clone(),
+        )),
+    ];
+
+    #[cfg(debug_assertions)]
+    renderers.extend([
+        Box::new(test_renderer::NodeOverlayRenderer::new(
+            default_renderer::DefaultRenderer::new(theme, options),
+        )) as Box<dyn PageRenderer + Send>,
+        Box::new(test_renderer::TreeDataRenderer),
+        Box::new(test_renderer::TreeRawRenderer),
+        Box::new(test_renderer::NodesRawRenderer),
+    ]);
+
+    renderers
+}
+
+/// A word of a rendered line, with the whitespace drawn after it
 #[derive(Debug, Clone)]
 pub struct Word {
+    /// Index of the node the word was rendered from, `usize::MAX` for words that don't belong to
+    /// any node, like the padding and the bullets of lists
     pub index: usize,
     pub content: String,
     pub style: Style,
+    /// Number of columns of the content
     // TODO: Change width type to u64
     pub width: f64,
+    /// Number of spaces after the word
     // TODO: Change whitespace_width type to u8
     pub whitespace_width: f64,
+    /// Number of columns added when the line is broken after the word, like for a hyphen
     // TODO: Change penalty_width type to u8
     pub penalty_width: f64,
+    /// Whether the word is a piece of a hyphenated word that continues in the next word
+    pub is_hyphenated: bool,
 }
 
 impl<'a> Word {
+    /// Creates a word of the node with the index followed by `whitespace` spaces. `None` for
+    /// words that don't belong to any node
+    pub fn new(index: Option<usize>, content: &str, style: Style, whitespace: u16) -> Word {
+        Word {
+            index: index.unwrap_or(usize::MAX),
+            content: content.to_string(),
+            style,
+            width: content.width() as f64,
+            whitespace_width: whitespace as f64,
+            penalty_width: 0.0,
+            is_hyphenated: false,
+        }
+    }
+
     pub fn node(&self, document: &'a Document) -> Option<Node<'a>> {
         return document.nth(self.index);
     }
+
+    /// Returns the whitespace drawn after the word, borrowed unless it's wider than `PADDING`
+    pub fn whitespace(&self) -> Cow<'static, str> {
+        let width = self.whitespace_width as usize;
+        match PADDING.get(..width) {
+            Some(padding) => Cow::Borrowed(padding),
+            None => Cow::Owned(" ".repeat(width)),
+        }
+    }
 }
 
 impl Fragment for Word {
@@ -42,9 +314,930 @@ impl Fragment for Word {
     }
 }
 
-#[derive(Debug)]
+/// A match of an in-page search, spanning the characters `columns` of the line `line`. The
+/// columns count the characters of the words in the line, including their whitespace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub line: usize,
+    pub columns: Range<usize>,
+}
+
+/// Builds the case-insensitive pattern for an in-page search. Without `regex`, the query is
+/// matched literally. With `whole_word`, matches have to start and end at a word boundary
+pub fn search_pattern(query: &str, regex: bool, whole_word: bool) -> Result<Regex, regex::Error> {
+    let mut pattern = if regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    if whole_word {
+        pattern = format!(r"\b(?:{pattern})\b");
+    }
+    Regex::new(&format!("(?i){pattern}"))
+}
+
+/// Returns the text of a line as it's displayed, including the whitespace after every word
+fn line_text(line: &[Word]) -> String {
+    let mut text = String::new();
+    for word in line {
+        text.push_str(&word.content);
+        text.extend(std::iter::repeat(' ').take(word.whitespace_width as usize));
+    }
+    text
+}
+
+/// The empty lines reserved for drawing the image of a figure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageArea {
+    /// The first reserved line
+    pub y: usize,
+    pub rows: u16,
+    /// The index of the figure node
+    pub index: usize,
+}
+
+/// The lines of a rendered document
+#[derive(Debug, Default)]
 pub struct RenderedDocument {
     pub lines: Vec<Vec<Word>>,
+    /// The line each link starts at with the index of its node, in the order of the lines.
     /// Vec<(y-Coord, idx)>
     pub links: Vec<(usize, usize)>,
+    /// The lines left empty for the images of the figures
+    pub images: Vec<ImageArea>,
+    /// The lines of a right-to-left page in their logical order and the width they're aligned
+    /// to. Copying and searching use them, so they see the text in the order it's written in.
+    /// `None` when the lines aren't reordered
+    pub logical_lines: Option<(Vec<Vec<Word>>, u16)>,
+}
+
+impl RenderedDocument {
+    /// Creates a document of the lines, without any links or images
+    pub fn new(lines: Vec<Vec<Word>>) -> RenderedDocument {
+        RenderedDocument {
+            lines,
+            links: Vec::new(),
+            images: Vec::new(),
+            logical_lines: None,
+        }
+    }
+
+    /// Appends the rendered chunk of a page to the part rendered before. Every chunk starts with
+    /// the empty line in front of its section, which the part before already ends with
+    pub fn append(&mut self, chunk: RenderedDocument) {
+        let mut lines = chunk.lines.into_iter().peekable();
+        let mut skipped = 0;
+        if self.lines.last().is_some_and(Vec::is_empty)
+            && lines.next_if(|line| line.is_empty()).is_some()
+        {
+            skipped = 1;
+        }
+
+        let offset = self.lines.len();
+        let y = |y: usize| (y + offset).saturating_sub(skipped);
+        self.lines.extend(lines);
+        if let Some((chunk_lines, width)) = chunk.logical_lines {
+            let chunk_lines = chunk_lines.into_iter().skip(skipped);
+            match self.logical_lines {
+                Some((ref mut logical_lines, _)) => logical_lines.extend(chunk_lines),
+                None if offset == 0 => self.logical_lines = Some((chunk_lines.collect(), width)),
+                None => {}
+            }
+        }
+        self.links.extend(
+            chunk
+                .links
+                .into_iter()
+                .map(|(line, index)| (y(line), index)),
+        );
+        self.images
+            .extend(chunk.images.into_iter().map(|image| ImageArea {
+                y: y(image.y),
+                ..image
+            }));
+    }
+
+    /// Displays the text as it is, with one word per line that doesn't belong to any node. The
+    /// lines are wrapped at the width when one is given
+    pub fn from_text(text: &str, style: Style, width: Option<u16>) -> RenderedDocument {
+        let word = |content: &str| Word::new(None, content, style, 0);
+
+        let mut lines = Vec::new();
+        for line in text.lines() {
+            let line = line.replace('\t', "    ");
+            match width {
+                Some(width) => lines.extend(
+                    textwrap::wrap(&line, width.max(1) as usize)
+                        .iter()
+                        .map(|part| vec![word(part)]),
+                ),
+                None => lines.push(vec![word(&line)]),
+            }
+        }
+
+        RenderedDocument::new(lines)
+    }
+
+    /// Returns the width of the widest line in columns on the screen
+    pub fn max_width(&self) -> usize {
+        self.lines
+            .iter()
+            .map(|line| line_text(line).width())
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns the first line belonging to a paragraph with at least `min_words` words, skipping
+    /// hatnotes, image captions and other short blocks before the actual lead of the article.
+    /// Returns `None` when the lead is already visible on the first screen of the given height
+    pub fn lead_line(&self, document: &Document, min_words: usize, height: u16) -> Option<usize> {
+        let y = self.lines.iter().position(|line| {
+            line.iter()
+                .find_map(|word| word.node(document))
+                .and_then(|node| {
+                    std::iter::successors(Some(node), |node| node.parent())
+                        .find(|node| node.data() == &Data::Paragraph)
+                })
+                .is_some_and(|paragraph| {
+                    let words: usize = paragraph
+                        .descendants()
+                        .map(|node| match node.data() {
+                            Data::Text { contents } => contents.split_whitespace().count(),
+                            _ => 0,
+                        })
+                        .sum();
+                    words >= min_words
+                })
+        })?;
+
+        (y >= height as usize).then_some(y)
+    }
+
+    /// Returns the position of the first word at or after the line `y` that belongs to a node, as
+    /// the index of the node and the number of its words before it. Unlike the line, the position
+    /// stays the same when the document is rendered with another width
+    pub fn word_position(&self, document: &Document, y: usize) -> Option<(usize, usize)> {
+        let word = self
+            .lines
+            .iter()
+            .skip(y)
+            .flatten()
+            .find(|word| word.node(document).is_some())?;
+        let nth = self
+            .lines
+            .iter()
+            .take(y)
+            .flatten()
+            .filter(|other| other.index == word.index)
+            .count();
+        Some((word.index, nth))
+    }
+
+    /// Returns the line of the word at the position returned by `word_position`. When the node has
+    /// fewer words in this render, the line of its last word is returned
+    pub fn word_line(&self, (index, nth): (usize, usize)) -> Option<usize> {
+        self.lines
+            .iter()
+            .enumerate()
+            .flat_map(|(y, line)| line.iter().map(move |word| (y, word)))
+            .filter(|(_, word)| word.index == index)
+            .take(nth + 1)
+            .last()
+            .map(|(y, _)| y)
+    }
+
+    /// Returns the innermost section with a header that contains the first node at or after the
+    /// line `y`, as the index of the section node
+    pub fn section_at(&self, document: &Document, y: usize) -> Option<usize> {
+        let (index, _) = self.word_position(document, y)?;
+        let node = document.nth(index)?;
+        std::iter::once(node)
+            .chain(node.ancestors())
+            .find(|node| section_header(*node).is_some())
+            .map(|section| section.index())
+    }
+
+    /// Hides the content of the folded sections, leaving their header followed by a marker with
+    /// the number of hidden lines. `folded` are the indices of the section nodes. Lines without a
+    /// node, like empty lines, are hidden when the lines around them are hidden by the same section
+    pub fn fold(
+        self,
+        document: &Document,
+        folded: &HashSet<usize>,
+        marker_style: Style,
+    ) -> RenderedDocument {
+        // the nodes of the header and of the whole section for every folded section
+        let ranges: Vec<(RangeInclusive<usize>, RangeInclusive<usize>)> = folded
+            .iter()
+            .filter_map(|index| {
+                let section = document.nth(*index)?;
+                let header = section_header(section)?;
+                let last = |node: Node| node.descendants().last().unwrap_or(node).index();
+                Some((
+                    header.index()..=last(header),
+                    section.index()..=last(section),
+                ))
+            })
+            .collect();
+        if ranges.is_empty() {
+            return self;
+        }
+
+        // the outermost folded section hiding a node
+        let hiding = |index: usize| {
+            ranges
+                .iter()
+                .enumerate()
+                .filter(|(_, (header, section))| {
+                    section.contains(&index) && !header.contains(&index)
+                })
+                .max_by_key(|(_, (_, section))| section.end() - section.start())
+                .map(|(fold, _)| fold)
+        };
+        let line_nodes: Vec<Option<usize>> = self
+            .lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .find(|word| word.node(document).is_some())
+                    .map(|word| word.index)
+            })
+            .collect();
+        let hidden_by_node: Vec<Option<Option<usize>>> =
+            line_nodes.iter().map(|node| node.map(hiding)).collect();
+
+        // the fold of the next line with a node after every line
+        let mut next_fold = vec![None; self.lines.len()];
+        let mut next = None;
+        for y in (0..self.lines.len()).rev() {
+            next_fold[y] = next;
+            if let Some(fold) = hidden_by_node[y] {
+                next = fold;
+            }
+        }
+        let mut hidden = vec![None; self.lines.len()];
+        let mut counts = vec![0; ranges.len()];
+        let mut prev = None;
+        for y in 0..self.lines.len() {
+            hidden[y] = match hidden_by_node[y] {
+                Some(fold) => {
+                    prev = fold;
+                    fold
+                }
+                None => match (prev, next_fold[y]) {
+                    (Some(prev), Some(next)) if prev == next => Some(prev),
+                    _ => None,
+                },
+            };
+            if let Some(fold) = hidden[y] {
+                counts[fold] += 1;
+            }
+        }
+
+        let mut lines = Vec::new();
+        let mut new_y = vec![None; self.lines.len()];
+        let mut has_marker = vec![false; ranges.len()];
+        for (y, line) in self.lines.into_iter().enumerate() {
+            match hidden[y] {
+                Some(fold) if !has_marker[fold] => {
+                    has_marker[fold] = true;
+                    let content = format!("… ({} lines)", counts[fold]);
+                    lines.push(vec![Word {
+                        index: usize::MAX,
+                        width: content.chars().count() as f64,
+                        content,
+                        style: marker_style,
+                        whitespace_width: 0.0,
+                        penalty_width: 0.0,
+                        is_hyphenated: false,
+                    }]);
+                }
+                Some(_) => {}
+                None => {
+                    new_y[y] = Some(lines.len());
+                    lines.push(line);
+                }
+            }
+        }
+
+        let links = self
+            .links
+            .into_iter()
+            .filter_map(|(y, index)| Some((new_y.get(y).copied().flatten()?, index)))
+            .collect();
+        let images = self
+            .images
+            .into_iter()
+            .filter_map(|image| {
+                Some(ImageArea {
+                    y: new_y.get(image.y).copied().flatten()?,
+                    ..image
+                })
+            })
+            .collect();
+        RenderedDocument {
+            lines,
+            links,
+            images,
+            logical_lines: None,
+        }
+    }
+
+    /// Reserves `rows` empty lines below the placeholder of every figure with an image, so the
+    /// page can draw the image there while scrolling stays line-based. Figures whose image is
+    /// rejected by `has_image` only keep their placeholder
+    pub fn reserve_images(
+        self,
+        document: &Document,
+        rows: u16,
+        has_image: impl Fn(&Url) -> bool,
+    ) -> RenderedDocument {
+        let mut lines = Vec::new();
+        let mut new_y = Vec::with_capacity(self.lines.len());
+        let mut images = Vec::new();
+        for line in self.lines {
+            new_y.push(lines.len());
+            let figure = line
+                .iter()
+                .find_map(|word| word.node(document))
+                .filter(
+                    |node| matches!(node.data(), Data::Figure { image, .. } if has_image(image)),
+                )
+                .map(|node| node.index());
+            lines.push(line);
+
+            if let Some(index) =
+                figure.filter(|index| !images.iter().any(|image: &ImageArea| image.index == *index))
+            {
+                images.push(ImageArea {
+                    y: lines.len(),
+                    rows,
+                    index,
+                });
+                lines.extend(std::iter::repeat_with(Vec::new).take(rows as usize));
+            }
+        }
+
+        let links = self
+            .links
+            .into_iter()
+            .map(|(y, index)| (new_y[y], index))
+            .collect();
+        RenderedDocument {
+            lines,
+            links,
+            images,
+            logical_lines: None,
+        }
+    }
+
+    /// Displays the lines from right to left, aligned to the right of the width. The words are
+    /// reordered into the order they're displayed in, the lines in their logical order are kept
+    /// for searching and copying
+    pub fn right_to_left(self, width: u16) -> RenderedDocument {
+        RenderedDocument {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| bidi::reorder_line(line.clone(), width))
+                .collect(),
+            logical_lines: Some((self.lines, width)),
+            ..self
+        }
+    }
+
+    /// Returns the lines in their logical order
+    fn logical_lines(&self) -> &[Vec<Word>] {
+        match self.logical_lines {
+            Some((ref lines, _)) => lines,
+            None => &self.lines,
+        }
+    }
+
+    /// Returns every match of the pattern, ordered by their position. The words of a line are
+    /// concatenated (including their whitespace) so a match can span multiple words. Empty
+    /// matches are ignored
+    pub fn search(&self, pattern: &Regex) -> Vec<SearchMatch> {
+        let mut matches = Vec::new();
+        for (y, line) in self.logical_lines().iter().enumerate() {
+            let text = line_text(line);
+
+            let column = |byte: usize| text[..byte].chars().count();
+            let line_matches =
+                pattern
+                    .find_iter(&text)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| SearchMatch {
+                        line: y,
+                        columns: column(m.start())..column(m.end()),
+                    });
+            match self.logical_lines {
+                // the columns of a match in a reordered line span the characters it's displayed in
+                Some((_, width)) => {
+                    let mut positions = None;
+                    matches.extend(line_matches.map(|m| {
+                        let positions =
+                            positions.get_or_insert_with(|| bidi::visual_positions(line, width));
+                        let columns = &positions[m.columns];
+                        SearchMatch {
+                            line: y,
+                            columns: columns.iter().min().copied().unwrap_or_default()
+                                ..columns.iter().max().map_or(0, |column| column + 1),
+                        }
+                    }));
+                }
+                None => matches.extend(line_matches),
+            }
+        }
+        matches
+    }
+
+    /// Returns the text of the lines without the padding and prefixes in front of them. The
+    /// whitespace stretched by justifying a line is collapsed into a single space
+    pub fn text(&self, lines: RangeInclusive<usize>) -> String {
+        let mut text = String::new();
+        for line in self.logical_lines().get(lines).unwrap_or_default() {
+            let mut line_text = String::new();
+            for word in line.iter().skip_while(|word| word.index == usize::MAX) {
+                line_text.push_str(&word.content);
+                if word.whitespace_width > 0.0 && !line_text.ends_with(' ') {
+                    line_text.push(' ');
+                }
+            }
+            text.push_str(line_text.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Returns the rendered words of the nodes in the range, separated by single spaces and without
+    /// the punctuation around them, like the quotes and the comma of `"iron oxide",`
+    pub fn nodes_text(&self, nodes: RangeInclusive<usize>) -> String {
+        let mut text = String::new();
+        for line in self.logical_lines() {
+            // the end of a line separates the words, unless the word was hyphenated
+            if !text.is_empty() && !text.ends_with([' ', '-']) {
+                text.push(' ');
+            }
+            for word in line.iter().filter(|word| nodes.contains(&word.index)) {
+                text.push_str(&word.content);
+                if word.whitespace_width > 0.0 {
+                    text.push(' ');
+                }
+            }
+        }
+        text.trim_matches(|c: char| !c.is_alphanumeric())
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    /// Returns the lines as plain text, without trailing whitespace
+    pub fn plain_text(&self) -> String {
+        let mut text = String::new();
+        for line in self.lines.iter() {
+            text.push_str(line_text(line).trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Returns the lines as text styled with ansi escape codes, without trailing whitespace. The
+    /// whitespace after a word has its style, like on the page. The style only changes between
+    /// differently styled words and every line ends with a reset, so the styles don't bleed into
+    /// the next line in pagers. The background of the theme is left out, so the text fits the
+    /// background of the terminal
+    pub fn ansi_text(&self, theme: &Theme) -> String {
+        let mut text = String::new();
+        for line in self.lines.iter() {
+            let mut parts: Vec<(Style, Cow<str>)> = Vec::with_capacity(line.len() * 2);
+            for word in line {
+                let mut style = word.style;
+                if style.bg == Some(theme.bg) {
+                    style.bg = None;
+                }
+                parts.push((style, Cow::Borrowed(word.content.as_str())));
+                parts.push((style, word.whitespace()));
+            }
+            // the whitespace at the end isn't written, so it isn't styled either
+            while let Some((_, content)) = parts.last_mut() {
+                let trimmed = content.trim_end().len();
+                if trimmed > 0 {
+                    *content = match content {
+                        Cow::Borrowed(content) => Cow::Borrowed(&content[..trimmed]),
+                        Cow::Owned(content) => Cow::Owned(content[..trimmed].to_string()),
+                    };
+                    break;
+                }
+                parts.pop();
+            }
+
+            // the style of the last escape sequence, until it's reset
+            let mut open = None;
+            for (style, content) in parts.iter().filter(|(_, content)| !content.is_empty()) {
+                if open != Some(*style) {
+                    if open.take().is_some() {
+                        text.push_str(ansi::RESET);
+                    }
+                    if let Some(sequence) = ansi::escape_sequence(*style) {
+                        text.push_str(&sequence);
+                        open = Some(*style);
+                    }
+                }
+                text.push_str(content);
+            }
+            if open.is_some() {
+                text.push_str(ansi::RESET);
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc};
+
+    use ratatui::style::{Color, Modifier, Style};
+    use wiki_api::{
+        document::{Data, Document},
+        languages::Language,
+        parser::{Parser, WikipediaParser},
+        Endpoint,
+    };
+
+    use crate::{
+        renderer::{
+            default_renderer::{render_document, DefaultRenderer, PlainRenderer},
+            fold_hides, line_text, search_pattern, top_level_sections, BlockSpacing, PageRenderer,
+            RenderOptions, RenderedDocument, SearchMatch, Word,
+        },
+        theme::Theme,
+    };
+
+    const MIN_WORDS: usize = 8;
+
+    fn parse(html: &str) -> Document {
+        let nodes = WikipediaParser::parse_document(
+            html,
+            Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+            Language::default(),
+        )
+        .nodes();
+        Document { nodes }
+    }
+
+    #[test]
+    fn test_lead_line_after_hatnotes_and_infobox() {
+        let document = parse(
+            r#"<div class="hatnote">For other uses, see Rust (disambiguation).</div>
+            <div class="hatnote">Not to be confused with Rust (video game).</div>
+            <div class="hatnote">"Oxidation" redirects here.</div>
+            <table class="infobox"><tr><td>Iron oxide</td></tr></table>
+            <p><span>Rust</span></p>
+            <p>Rust is an iron oxide, a usually reddish-brown oxide formed by the reaction of iron and oxygen.</p>"#,
+        );
+        let rendered = render_document(&document, &Theme::default(), 40, false);
+
+        let y = rendered.lead_line(&document, MIN_WORDS, 4).unwrap();
+        let words: Vec<&str> = rendered.lines[y]
+            .iter()
+            .filter(|word| word.node(&document).is_some())
+            .map(|word| word.content.as_str())
+            .take(2)
+            .collect();
+        assert_eq!(words, ["Rust", "is"]);
+
+        // the lead is visible on the first screen
+        assert_eq!(rendered.lead_line(&document, MIN_WORDS, 40), None);
+    }
+
+    #[test]
+    fn test_search() {
+        let document = parse("<p>Rust is an iron oxide. Iron rusts, <b>trust</b> me.</p>");
+        let rendered = render_document(&document, &Theme::default(), 12, false);
+        let text = |m: &SearchMatch| -> String {
+            line_text(&rendered.lines[m.line])
+                .chars()
+                .skip(m.columns.start)
+                .take(m.columns.len())
+                .collect()
+        };
+        let search = |query: &str, regex: bool, whole_word: bool| -> Vec<String> {
+            let pattern = search_pattern(query, regex, whole_word).unwrap();
+            rendered.search(&pattern).iter().map(text).collect()
+        };
+
+        assert_eq!(search("RUST", false, false), ["Rust", "rust", "rust"]);
+        assert_eq!(search("rust", false, true), ["Rust"]);
+        // matches can span multiple words
+        assert_eq!(search("iron oxide", false, false), ["iron oxide"]);
+        assert_eq!(search("t.", false, false), Vec::<String>::new());
+
+        assert_eq!(search(r"i\w+n", true, false), ["iron", "Iron"]);
+        assert_eq!(search(r"r\w*t", true, true), ["Rust"]);
+        assert_eq!(search("z*", true, false), Vec::<String>::new());
+
+        assert!(search_pattern("(iron", true, false).is_err());
+        assert!(search_pattern("(iron", false, false).is_ok());
+    }
+
+    #[test]
+    fn test_reserve_images() {
+        let document = parse(
+            r#"<p>Rust</p><figure><img src="//upload.wikimedia.org/Rust.jpg"/></figure><p><a rel="mw:WikiLink" href="./Iron" title="Iron">Iron</a></p>"#,
+        );
+        let rendered = render_document(&document, &Theme::default(), 40, false);
+        let link_y = rendered.links[0].0;
+        let figure_y = rendered
+            .lines
+            .iter()
+            .position(|line| {
+                line.iter().any(|word| {
+                    word.node(&document)
+                        .is_some_and(|node| matches!(node.data(), Data::Figure { .. }))
+                })
+            })
+            .unwrap();
+
+        // figures without an image only keep their placeholder
+        let reserved = render_document(&document, &Theme::default(), 40, false).reserve_images(
+            &document,
+            3,
+            |_| false,
+        );
+        assert!(reserved.images.is_empty());
+        assert_eq!(reserved.lines.len(), rendered.lines.len());
+
+        let reserved = rendered.reserve_images(&document, 3, |_| true);
+        assert_eq!(reserved.images.len(), 1);
+        assert_eq!(reserved.images[0].y, figure_y + 1);
+        assert_eq!(reserved.images[0].rows, 3);
+        assert!(reserved.lines[figure_y + 1..=figure_y + 3]
+            .iter()
+            .all(|line| line.is_empty()));
+        // the lines below the image are moved down
+        assert_eq!(reserved.links[0].0, link_y + 3);
+    }
+
+    #[test]
+    fn test_lead_line_stub() {
+        let document = parse("<p>Foo is a stub article about a thing that exists.</p>");
+        let rendered = render_document(&document, &Theme::default(), 40, false);
+
+        assert_eq!(rendered.lead_line(&document, MIN_WORDS, 4), None);
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let word = |content: &str, whitespace_width: f64| Word {
+            index: 0,
+            content: content.to_string(),
+            style: Style::default(),
+            width: content.chars().count() as f64,
+            whitespace_width,
+            penalty_width: 0.0,
+            is_hyphenated: false,
+        };
+        let rendered = RenderedDocument {
+            lines: vec![
+                vec![word("Rust", 1.0), word("is", 1.0), word("fast", 1.0)],
+                vec![],
+                vec![word("Hello", 0.0), word(",", 1.0), word("world", 3.0)],
+            ],
+            links: Vec::new(),
+            images: Vec::new(),
+            logical_lines: None,
+        };
+        assert_eq!(rendered.plain_text(), "Rust is fast\n\nHello, world\n");
+
+        // words built outside of a renderer are measured in terminal columns
+        assert_eq!(Word::new(None, "日本語", Style::default(), 0).width, 6.0);
+    }
+
+    #[test]
+    fn test_ansi_text() {
+        let theme = Theme::default();
+        let bold = Style::new().add_modifier(Modifier::BOLD);
+        let blue = Style::new().fg(Color::Blue).bg(theme.bg);
+        let rendered = RenderedDocument::new(vec![
+            vec![
+                Word::new(Some(0), "Rust", bold, 1),
+                Word::new(Some(0), "is", bold, 1),
+                Word::new(Some(1), "an", Style::new(), 1),
+                Word::new(Some(2), "oxide", blue, 3),
+            ],
+            vec![],
+            vec![Word::new(Some(3), "Iron", Style::new(), 2)],
+        ]);
+
+        // the words of the same style share their escape sequence, the background of the theme is
+        // left out and the line ends with a reset
+        assert_eq!(
+            rendered.ansi_text(&theme),
+            "\x1b[1mRust is \x1b[0man \x1b[34moxide\x1b[0m\n\nIron\n"
+        );
+    }
+
+    #[test]
+    fn test_word_position() {
+        let document = parse(
+            "<p>Rust is an iron oxide, a usually reddish-brown oxide formed by the reaction of iron \
+            and oxygen.</p><p>Rust consists of hydrous iron(III) oxides.</p>",
+        );
+        let narrow = render_document(&document, &Theme::default(), 20, false);
+        let wide = render_document(&document, &Theme::default(), 60, false);
+
+        // the first word of every line in the narrow render is found in the wide render
+        for (y, line) in narrow.lines.iter().enumerate() {
+            let word = match line.iter().find(|word| word.node(&document).is_some()) {
+                Some(word) => word,
+                None => continue,
+            };
+            let position = narrow.word_position(&document, y).unwrap();
+            let wide_y = wide.word_line(position).unwrap();
+            assert!(wide.lines[wide_y]
+                .iter()
+                .any(|other| other.index == word.index && other.content == word.content));
+        }
+
+        // no word belongs to a node past the end of the document
+        assert_eq!(wide.word_line((document.nodes.len(), 0)), None);
+    }
+
+    #[test]
+    fn test_from_text() {
+        let source = "'''Rust''' is an [[iron oxide]].\n\n== History ==";
+
+        let rendered = RenderedDocument::from_text(source, Style::default(), None);
+        assert_eq!(rendered.plain_text(), format!("{source}\n"));
+        assert_eq!(rendered.max_width(), 32);
+
+        let rendered = RenderedDocument::from_text(source, Style::default(), Some(16));
+        assert_eq!(
+            rendered.plain_text(),
+            "'''Rust''' is an\n[[iron oxide]].\n\n== History ==\n"
+        );
+        assert!(rendered
+            .lines
+            .iter()
+            .flatten()
+            .all(|word| word.index == usize::MAX));
+    }
+
+    #[test]
+    fn test_fold() {
+        let document = parse(
+            r#"<section data-mw-section-id="0"><p>Rust is an iron oxide.</p></section>
+            <section data-mw-section-id="1"><h2 id="Chemistry">Chemistry</h2>
+            <p>Rust consists of <a rel="mw:WikiLink" href="./Iron">iron</a> oxides.</p>
+            <p>It forms in moist air.</p></section>
+            <section data-mw-section-id="2"><h2 id="Prevention">Prevention</h2>
+            <p>Rust is prevented by coatings.</p></section>"#,
+        );
+        let rendered = render_document(&document, &Theme::default(), 80, false);
+        let text = |rendered: &RenderedDocument| -> Vec<String> {
+            rendered
+                .lines
+                .iter()
+                .map(|line| line_text(line).trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect()
+        };
+        let line_of = |text: &str| {
+            rendered
+                .lines
+                .iter()
+                .position(|line| line_text(line).contains(text))
+                .unwrap()
+        };
+
+        // the lead has no header and can't be folded
+        assert_eq!(rendered.section_at(&document, 0), None);
+        let chemistry = rendered
+            .section_at(&document, line_of("Chemistry"))
+            .unwrap();
+        assert_eq!(
+            rendered.section_at(&document, line_of("moist air")),
+            Some(chemistry)
+        );
+
+        let link = rendered.links[0].1;
+        assert!(fold_hides(&document, chemistry, link));
+        assert!(!fold_hides(&document, chemistry, chemistry + 1));
+
+        let folded = rendered.fold(&document, &HashSet::from([chemistry]), Style::default());
+        // the underline belongs to the header
+        let underline = "─".repeat(80);
+        assert_eq!(
+            text(&folded),
+            [
+                "Rust is an iron oxide.",
+                "Chemistry",
+                &underline,
+                "… (3 lines)",
+                "Prevention",
+                &underline,
+                "Rust is prevented by coatings."
+            ]
+        );
+        // the link is hidden with its section
+        assert!(folded.links.is_empty());
+    }
+
+    #[test]
+    fn test_nodes_text() {
+        let document = parse(
+            r#"<p>Rust is an "<a rel="mw:WikiLink" href="./Iron_oxide" title="Iron oxide">iron oxide</a>", a usually reddish-brown oxide.</p>"#,
+        );
+        // the link is wrapped into two lines
+        let rendered = render_document(&document, &Theme::default(), 16, false);
+        let (_, link) = rendered.links[0];
+        let last = document.nth(link).unwrap().descendants().last().unwrap();
+
+        assert_eq!(rendered.nodes_text(link..=last.index()), "iron oxide");
+        assert_eq!(rendered.nodes_text(usize::MAX..=usize::MAX), "");
+    }
+
+    #[test]
+    fn test_text() {
+        let document = parse(
+            "<p>Rust is an iron oxide, a usually reddish-brown oxide.</p>\
+            <ul><li>Iron oxide</li></ul>",
+        );
+        let rendered = render_document(&document, &Theme::default(), 20, true);
+        assert_eq!(line_text(&rendered.lines[1]), "Rust   is   an  iron");
+
+        // justified lines are copied with single spaces and the list without its bullet
+        assert_eq!(
+            rendered.text(1..=5),
+            "Rust is an iron\noxide, a usually\nreddish-brown oxide.\n\nIron oxide\n"
+        );
+        assert_eq!(rendered.text(100..=200), "");
+    }
+
+    #[test]
+    fn test_render_sections() {
+        let document = parse(
+            r#"<section data-mw-section-id="0"><p>Rust is an iron oxide, a usually reddish-brown oxide.</p></section>
+            <section data-mw-section-id="1"><h2 id="Chemistry">Chemistry</h2>
+            <p>Rust consists of <a rel="mw:WikiLink" href="./Iron">iron</a> oxides.</p>
+            <section data-mw-section-id="2"><h3 id="Forms">Forms</h3>
+            <ul><li>Hydrated iron oxide</li><li>Iron oxide-hydroxide</li></ul></section></section>
+            <section data-mw-section-id="3"><h2 id="Prevention">Prevention</h2>
+            <p>Rust is prevented by <i>coatings</i>.</p><hr></section>"#,
+        );
+        let sections = top_level_sections(&document).unwrap();
+        assert_eq!(sections.len(), 3);
+
+        let theme = Arc::new(Theme::default());
+        let renderers: [Box<dyn PageRenderer>; 3] = [
+            Box::new(DefaultRenderer::new(
+                theme.clone(),
+                RenderOptions {
+                    justify: true,
+                    ..RenderOptions::default()
+                },
+            )),
+            Box::new(DefaultRenderer::new(
+                theme.clone(),
+                RenderOptions {
+                    spacing: BlockSpacing::Compact,
+                    ..RenderOptions::default()
+                },
+            )),
+            Box::new(PlainRenderer::new(
+                theme,
+                RenderOptions {
+                    spacing: BlockSpacing::Spacious,
+                    ..RenderOptions::default()
+                },
+            )),
+        ];
+        for renderer in renderers {
+            for width in [20, 80] {
+                let whole = renderer.render(&document, width);
+                let mut chunked = RenderedDocument::default();
+                for section in sections.iter() {
+                    let prepared = renderer.prepare(&document, *section).unwrap();
+                    chunked.append(prepared.wrap(&document, width));
+                }
+
+                // the chunks render the same as the whole document
+                let lines = |rendered: &RenderedDocument| -> Vec<String> {
+                    rendered.lines.iter().map(|line| line_text(line)).collect()
+                };
+                assert_eq!(lines(&chunked), lines(&whole));
+                assert_eq!(chunked.links, whole.links);
+            }
+        }
+
+        // the paragraph outside of the sections can only be rendered with the whole document
+        let document = parse(
+            r#"<p>Rust</p><section data-mw-section-id="0"><p>Iron</p></section>
+            <section data-mw-section-id="1"><p>Oxide</p></section>"#,
+        );
+        assert_eq!(top_level_sections(&document), None);
+    }
 }
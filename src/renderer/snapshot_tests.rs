@@ -0,0 +1,247 @@
+//! Golden-file tests pinning the output of the default renderer.
+//!
+//! Every fixture in `snapshots/fixtures` is a page in the html format of the api. It's rendered at
+//! each width in `WIDTHS` and compared with `snapshots/<fixture>.<width>.json`. The snapshots are
+//! render dumps, the same json `--dump-render` writes, with every word on a line of its own to
+//! keep the diffs readable.
+//!
+//! After an intended change of the output, the snapshots are regenerated with
+//! `UPDATE_SNAPSHOTS=1 cargo test snapshot`. New fixtures get their snapshots the same way.
+//! Review the changes of the snapshots before committing them.
+//!
+//! The fixture with nested blocks is also rendered with every block spacing, into
+//! `snapshots/nested_blocks.<spacing>.json`.
+
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Serialize;
+use wiki_api::{
+    document::{Data, Document},
+    languages::Language,
+    parser::{Parser, WikipediaParser},
+    Endpoint,
+};
+
+use crate::{
+    renderer::{
+        default_renderer::{render_document, DefaultRenderer},
+        dump::{DumpWord, RenderDump},
+        BlockSpacing,
+    },
+    theme::Theme,
+};
+
+use super::{PageRenderer, RenderOptions};
+
+const WIDTHS: [u16; 3] = [40, 80, 120];
+
+const SPACINGS: [(&str, BlockSpacing); 3] = [
+    ("compact", BlockSpacing::Compact),
+    ("normal", BlockSpacing::Normal),
+    ("spacious", BlockSpacing::Spacious),
+];
+
+fn snapshots_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("src/renderer/snapshots")
+}
+
+fn parse(html: &str) -> Document {
+    let nodes = WikipediaParser::parse_document(
+        html,
+        Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+        Language::default(),
+    )
+    .nodes();
+    Document { nodes }
+}
+
+fn json(value: &impl Serialize) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+/// Serializes the dump as json, like `serde_json::to_string_pretty` but with every word and link
+/// on a single line
+fn snapshot(dump: &RenderDump) -> String {
+    let mut snapshot = String::from("{\n");
+    let _ = writeln!(snapshot, "  \"version\": {},", dump.version);
+    let _ = writeln!(snapshot, "  \"title\": {},", json(&dump.title));
+    let _ = writeln!(snapshot, "  \"renderer\": {},", json(&dump.renderer));
+    let _ = writeln!(snapshot, "  \"width\": {},", dump.width);
+
+    snapshot.push_str("  \"lines\": [");
+    for (y, line) in dump.lines.iter().enumerate() {
+        snapshot.push_str(if y == 0 { "\n" } else { ",\n" });
+        if line.is_empty() {
+            snapshot.push_str("    []");
+            continue;
+        }
+        snapshot.push_str("    [\n");
+        let words: Vec<String> = line
+            .iter()
+            .map(|word| format!("      {}", json(word)))
+            .collect();
+        snapshot.push_str(&words.join(",\n"));
+        snapshot.push_str("\n    ]");
+    }
+    snapshot.push_str("\n  ],\n");
+
+    snapshot.push_str("  \"links\": [");
+    let links: Vec<String> = dump
+        .links
+        .iter()
+        .map(|link| format!("\n    {}", json(link)))
+        .collect();
+    snapshot.push_str(&links.join(","));
+    snapshot.push_str("\n  ]\n}\n");
+    snapshot
+}
+
+/// The text of a line of a dump, used to point at the line in a mismatch
+fn line_text(line: &[DumpWord]) -> String {
+    let mut text = String::new();
+    for word in line {
+        text.push_str(&word.content);
+        text.extend(std::iter::repeat(' ').take(word.whitespace as usize));
+    }
+    text.trim_end().to_string()
+}
+
+/// Compares the dump with its golden file, or overwrites the golden file when `UPDATE_SNAPSHOTS`
+/// is set. Returns a description of the mismatch
+fn check_snapshot(name: &str, actual: &RenderDump) -> Option<String> {
+    let path = snapshots_dir().join(format!("{name}.json"));
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, snapshot(actual)).unwrap();
+        return None;
+    }
+
+    let expected: RenderDump = match std::fs::read_to_string(&path)
+        .map_err(|error| error.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|error| error.to_string()))
+    {
+        Ok(expected) => expected,
+        Err(error) => return Some(format!("{name}: can't read '{}': {error}", path.display())),
+    };
+    if expected == *actual {
+        return None;
+    }
+
+    let empty = Vec::new();
+    let line = (0..expected.lines.len().max(actual.lines.len())).find(|y| {
+        expected.lines.get(*y).unwrap_or(&empty) != actual.lines.get(*y).unwrap_or(&empty)
+    });
+    Some(match line {
+        Some(y) => format!(
+            "{name}: differs at line {y}\n  expected: {}\n  actual:   {}",
+            line_text(expected.lines.get(y).unwrap_or(&empty)),
+            line_text(actual.lines.get(y).unwrap_or(&empty)),
+        ),
+        None => format!(
+            "{name}: the links differ\n  expected: {:?}\n  actual:   {:?}",
+            expected.links, actual.links
+        ),
+    })
+}
+
+#[test]
+fn test_snapshots() {
+    let mut fixtures: Vec<PathBuf> = std::fs::read_dir(snapshots_dir().join("fixtures"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "html")
+        })
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty());
+
+    let mut mismatches = Vec::new();
+    for fixture in fixtures {
+        let name = fixture.file_stem().unwrap().to_string_lossy().to_string();
+        let document = parse(&std::fs::read_to_string(&fixture).unwrap());
+        for width in WIDTHS {
+            let rendered = render_document(&document, &Theme::default(), width, false);
+            mismatches.extend(check_snapshot(
+                &format!("{name}.{width}"),
+                &RenderDump::new(&name, "default", width, &rendered),
+            ));
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "the rendered pages differ from their snapshots, regenerate them with \
+        `UPDATE_SNAPSHOTS=1 cargo test snapshot` if the change is intended\n\n{}",
+        mismatches.join("\n\n")
+    );
+}
+
+#[test]
+fn test_block_spacing_snapshots() {
+    let fixture = snapshots_dir().join("fixtures/nested_blocks.html");
+    let document = parse(&std::fs::read_to_string(fixture).unwrap());
+
+    let mut mismatches = Vec::new();
+    for (name, spacing) in SPACINGS {
+        let renderer = DefaultRenderer::new(
+            Arc::new(Theme::default()),
+            RenderOptions {
+                spacing,
+                ..RenderOptions::default()
+            },
+        );
+        let rendered = renderer.render(&document, 80);
+        mismatches.extend(check_snapshot(
+            &format!("nested_blocks.{name}"),
+            &RenderDump::new("nested_blocks", "default", 80, &rendered),
+        ));
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "the block spacings differ from their snapshots, regenerate them with \
+        `UPDATE_SNAPSHOTS=1 cargo test snapshot` if the change is intended\n\n{}",
+        mismatches.join("\n\n")
+    );
+}
+
+#[test]
+fn test_spacious_headers() {
+    let renderer = DefaultRenderer::new(
+        Arc::new(Theme::default()),
+        RenderOptions {
+            spacing: BlockSpacing::Spacious,
+            ..RenderOptions::default()
+        },
+    );
+    // headers right below another header and inside of lists and quotes
+    let html = r#"<h2 id="A">A</h2><h3 id="B">B</h3><ul><li>Item<h3 id="C">C</h3>text</li></ul>
+        <blockquote><p>Quote</p><h3 id="D">D</h3></blockquote>"#;
+    let fixture = snapshots_dir().join("fixtures/nested_blocks.html");
+    for html in [html.to_string(), std::fs::read_to_string(fixture).unwrap()] {
+        let document = parse(&html);
+        let rendered = renderer.render(&document, 40);
+        for (y, line) in rendered.lines.iter().enumerate() {
+            let is_header = line
+                .iter()
+                .find_map(|word| word.node(&document))
+                .is_some_and(|node| {
+                    std::iter::once(node)
+                        .chain(node.ancestors())
+                        .any(|node| matches!(node.data(), Data::Header { .. }))
+                });
+            if is_header {
+                assert!(
+                    y > 0 && rendered.lines[y - 1].is_empty(),
+                    "no empty line before the header at line {}",
+                    y
+                );
+            }
+        }
+    }
+}
@@ -1,7 +1,40 @@
-use ratatui::style::Style;
+use ratatui::style::{Color, Style};
 use wiki_api::document::{Document, Node};
 
-use super::{RenderedDocument, Word};
+use super::{
+    default_renderer::{DefaultRenderer, PreparedDocument},
+    PageRenderer, RenderedDocument, Word,
+};
+
+/// The colors the words of the node overlay are drawn in
+const NODE_COLORS: [Color; 12] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+];
+
+/// Returns the color of a node in the node overlay. The index is hashed, so neighbouring nodes
+/// are unlikely to get the same color
+pub fn node_color(index: usize) -> Color {
+    let hash = (index as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 32;
+    NODE_COLORS[hash as usize % NODE_COLORS.len()]
+}
+
+/// Returns the name of the variant of the node's data, like `Data::Paragraph`
+fn data_variant(node: Node) -> String {
+    let data = format!("{:?}", node.data());
+    let end = data.find([' ', '{', '(']).unwrap_or(data.len());
+    format!("Data::{}", &data[..end])
+}
 
 #[derive(Clone, Debug)]
 struct Descendants<'a> {
@@ -75,12 +108,15 @@ pub fn render_tree_data(document: &Document) -> RenderedDocument {
             width: 0.0,
             whitespace_width: 0.0,
             penalty_width: 0.0,
+            is_hyphenated: false,
         }])
     }
 
     RenderedDocument {
         lines,
         links: Vec::new(),
+        images: Vec::new(),
+        logical_lines: None,
     }
 }
 
@@ -102,12 +138,15 @@ pub fn render_tree_raw(document: &Document) -> RenderedDocument {
             width: 0.0,
             whitespace_width: 0.0,
             penalty_width: 0.0,
+            is_hyphenated: false,
         }])
     }
 
     RenderedDocument {
         lines,
         links: Vec::new(),
+        images: Vec::new(),
+        logical_lines: None,
     }
 }
 
@@ -123,11 +162,154 @@ pub fn render_nodes_raw(document: &Document) -> RenderedDocument {
             width: 0.0,
             whitespace_width: 0.0,
             penalty_width: 0.0,
+            is_hyphenated: false,
         }])
     }
 
     RenderedDocument {
         lines,
         links: Vec::new(),
+        images: Vec::new(),
+        logical_lines: None,
+    }
+}
+
+/// Renders the data of every node, indented by its depth in the tree
+pub struct TreeDataRenderer;
+
+impl PageRenderer for TreeDataRenderer {
+    fn name(&self) -> &str {
+        "test: tree data"
+    }
+
+    fn render(&self, document: &Document, _width: u16) -> RenderedDocument {
+        render_tree_data(document)
+    }
+}
+
+/// Renders every raw node, indented by its depth in the tree
+pub struct TreeRawRenderer;
+
+impl PageRenderer for TreeRawRenderer {
+    fn name(&self) -> &str {
+        "test: tree raw"
+    }
+
+    fn render(&self, document: &Document, _width: u16) -> RenderedDocument {
+        render_tree_raw(document)
+    }
+}
+
+/// Renders every raw node in the order they are stored
+pub struct NodesRawRenderer;
+
+impl PageRenderer for NodesRawRenderer {
+    fn name(&self) -> &str {
+        "test: nodes raw"
+    }
+
+    fn render(&self, document: &Document, _width: u16) -> RenderedDocument {
+        render_nodes_raw(document)
+    }
+}
+
+/// Colors every word that belongs to a node by the node
+fn color_by_node(mut rendered: RenderedDocument) -> RenderedDocument {
+    for word in rendered.lines.iter_mut().flatten() {
+        if word.index != usize::MAX {
+            word.style = word.style.fg(node_color(word.index));
+        }
+    }
+    rendered
+}
+
+/// Renders the page like the default renderer, but every word is colored by the node it belongs
+/// to, so the boundaries of the nodes are visible
+pub struct NodeOverlayRenderer {
+    renderer: DefaultRenderer,
+}
+
+impl NodeOverlayRenderer {
+    pub fn new(renderer: DefaultRenderer) -> Self {
+        Self { renderer }
+    }
+}
+
+impl PageRenderer for NodeOverlayRenderer {
+    fn name(&self) -> &str {
+        "test: node overlay"
+    }
+
+    fn render(&self, document: &Document, width: u16) -> RenderedDocument {
+        color_by_node(self.renderer.render(document, width))
+    }
+
+    fn prepare(&self, document: &Document, node: usize) -> Option<PreparedDocument> {
+        let mut prepared = self.renderer.prepare(document, node)?;
+        for word in prepared.words_mut().filter(|word| word.index != usize::MAX) {
+            word.style = word.style.fg(node_color(word.index));
+        }
+        Some(prepared)
+    }
+
+    fn node_label(&self, document: &Document, (start, end): (usize, usize)) -> Option<String> {
+        let node = document.nth(start)?;
+        Some(match start == end {
+            true => format!("[#{start} {}]", data_variant(node)),
+            false => format!("[#{start}..#{end} {}]", data_variant(node)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use wiki_api::{
+        document::Document,
+        languages::Language,
+        parser::{Parser, WikipediaParser},
+        Endpoint,
+    };
+
+    use crate::{
+        renderer::{default_renderer::DefaultRenderer, PageRenderer, RenderOptions},
+        theme::Theme,
+    };
+
+    use super::{node_color, NodeOverlayRenderer};
+
+    #[test]
+    fn test_node_overlay() {
+        let nodes = WikipediaParser::parse_document(
+            r#"<p>Rust is an <a rel="mw:WikiLink" href="./Iron_oxide" title="Iron oxide">iron oxide</a>.</p>"#,
+            Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+            Language::default(),
+        )
+        .nodes();
+        let document = Document { nodes };
+        let renderer = NodeOverlayRenderer::new(DefaultRenderer::new(
+            Arc::new(Theme::default()),
+            RenderOptions::default(),
+        ));
+
+        let rendered = renderer.render(&document, 80);
+        let words: Vec<_> = rendered
+            .lines
+            .iter()
+            .flatten()
+            .filter(|word| word.index != usize::MAX)
+            .collect();
+        assert!(!words.is_empty());
+        assert!(words
+            .iter()
+            .all(|word| word.style.fg == Some(node_color(word.index))));
+
+        let (_, link) = rendered.links[0];
+        assert_eq!(
+            renderer.node_label(&document, (link, link)).as_deref(),
+            Some(format!("[#{link} Data::Link]").as_str())
+        );
+        assert_ne!(node_color(1), node_color(2));
     }
 }
@@ -0,0 +1,254 @@
+use std::{
+    fmt::Display,
+    future::Future,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use tracing::info;
+use wiki_api::page::{ConnectionFailed, ServerError, TimedOut};
+
+use crate::config::ApiConfig;
+
+/// Longest delay between two attempts, unless the server asks to wait longer
+const MAX_DELAY: Duration = Duration::from_secs(30);
+/// The request is given up when the server asks to wait longer than this
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// How failed requests are sent again. Only transient failures are retried, that is timeouts and
+/// servers that are overloaded or limiting the requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts including the first one, `1` disables retrying
+    pub max_attempts: u32,
+    /// Delay before the first retry, it's doubled for every further retry
+    pub base_delay: Duration,
+}
+
+/// A failed request that's sent again after the delay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry {
+    /// The attempt that's made next, starting with `2` for the first retry
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub delay: Duration,
+}
+
+impl Display for Retry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "retrying in {}s (attempt {}/{})…",
+            self.delay.as_secs_f64().ceil(),
+            self.attempt,
+            self.max_attempts
+        )
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(config: &ApiConfig) -> Self {
+        Self {
+            max_attempts: config.retry_attempts.max(1),
+            base_delay: Duration::from_millis(config.retry_delay),
+        }
+    }
+
+    /// Sends every request only once
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: 1,
+            base_delay: Duration::ZERO,
+        }
+    }
+
+    /// Returns the delay before retrying the failed attempt. The delay the server asked for is
+    /// honored, otherwise the delay grows exponentially with up to a quarter of jitter, so that
+    /// clients failing at the same time don't retry at the same time
+    fn delay(&self, failed_attempt: u32, retry_after: Option<Duration>, jitter: f64) -> Duration {
+        if let Some(retry_after) = retry_after {
+            return retry_after;
+        }
+        let factor = 2u32.saturating_pow(failed_attempt.saturating_sub(1));
+        self.base_delay
+            .saturating_mul(factor)
+            .min(MAX_DELAY)
+            .mul_f64(1.0 + jitter.clamp(0.0, 1.0) / 4.0)
+    }
+
+    /// Sends the request until it succeeds, fails permanently or the attempts are used up.
+    /// `on_retry` is called before waiting for the next attempt
+    pub async fn run<T, F, Fut>(&self, mut request: F, mut on_retry: impl FnMut(Retry)) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let error = match request().await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            let retry_after = match transient_failure(&error) {
+                Some(retry_after) if attempt < self.max_attempts => retry_after,
+                _ => return Err(error),
+            };
+            let delay = self.delay(attempt, retry_after, jitter());
+            if delay > MAX_RETRY_AFTER {
+                info!("the server asked to wait {delay:?}, giving up");
+                return Err(error);
+            }
+
+            attempt += 1;
+            let retry = Retry {
+                attempt,
+                max_attempts: self.max_attempts,
+                delay,
+            };
+            info!("the request failed: {error:#}, {retry}");
+            on_retry(retry);
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Returns the delay the server asked for when the request failed transiently
+fn transient_failure(error: &anyhow::Error) -> Option<Option<Duration>> {
+    for cause in error.chain() {
+        if cause.is::<TimedOut>() {
+            return Some(None);
+        }
+        if let Some(error) = cause.downcast_ref::<ServerError>() {
+            return error.is_transient().then_some(error.retry_after);
+        }
+    }
+    None
+}
+
+/// A number between 0 and 1 that's good enough to spread the retries
+fn jitter() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or_default();
+    (nanos % 1000) as f64 / 1000.0
+}
+
+/// Returns a readable explanation of a failed request, when the connection or the server failed.
+/// Failed connections name the proxy the request was sent through
+pub fn describe_error(error: &anyhow::Error, proxy: Option<&str>) -> Option<String> {
+    for cause in error.chain() {
+        if cause.is::<ConnectionFailed>() {
+            return Some(match proxy {
+                Some(proxy) => format!(
+                    "Unable to connect through the proxy '{proxy}', check the proxy settings"
+                ),
+                None => "No network connection, the site cannot be reached".to_string(),
+            });
+        }
+        if cause.is::<TimedOut>() {
+            return Some("The site took too long to answer".to_string());
+        }
+        if let Some(error) = cause.downcast_ref::<ServerError>() {
+            return Some(match error.status {
+                429 => "The site received too many requests, try again in a moment".to_string(),
+                500..=599 => format!("The site is unavailable right now ({})", error.status),
+                status => format!("The site refused the request ({status})"),
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use anyhow::anyhow;
+    use wiki_api::page::{ConnectionFailed, ServerError};
+
+    use super::RetryPolicy;
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(1),
+        };
+        assert_eq!(policy.delay(1, None, 0.0), Duration::from_secs(1));
+        assert_eq!(policy.delay(2, None, 0.0), Duration::from_secs(2));
+        assert_eq!(policy.delay(3, None, 1.0), Duration::from_millis(5000));
+        assert_eq!(policy.delay(10, None, 0.0), Duration::from_secs(30));
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(7)), 0.5),
+            Duration::from_secs(7)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient_failures() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::ZERO,
+        };
+        let unavailable = || {
+            anyhow::Error::from(ServerError {
+                status: 503,
+                retry_after: None,
+            })
+        };
+
+        let mut attempts = 0;
+        let mut retries = Vec::new();
+        let result = policy
+            .run(
+                || {
+                    attempts += 1;
+                    let result = match attempts {
+                        3 => Ok(attempts),
+                        _ => Err(unavailable()),
+                    };
+                    async move { result }
+                },
+                |retry| retries.push(retry.attempt),
+            )
+            .await;
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(retries, [2, 3]);
+
+        // the attempts are used up
+        let mut attempts = 0;
+        let result: anyhow::Result<()> = policy
+            .run(
+                || {
+                    attempts += 1;
+                    async { Err(unavailable()) }
+                },
+                |_| {},
+            )
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+
+        // permanent failures and disabled retries are not retried
+        for (policy, error) in [
+            (policy, anyhow!(ConnectionFailed("dns error".to_string()))),
+            (RetryPolicy::disabled(), unavailable()),
+        ] {
+            let mut error = Some(error);
+            let mut attempts = 0;
+            let result: anyhow::Result<()> = policy
+                .run(
+                    || {
+                        attempts += 1;
+                        let error = error.take().unwrap_or_else(unavailable);
+                        async move { Err(error) }
+                    },
+                    |_| {},
+                )
+                .await;
+            assert!(result.is_err());
+            assert_eq!(attempts, 1);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use wiki_api::{languages::Language, Endpoint};
+
+use crate::store::Store;
+
+pub const SESSION_STORE: &str = "session";
+
+/// The page that was open when quitting, restored on the next startup
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SessionPage {
+    pub title: String,
+    #[serde(with = "crate::store::language_code")]
+    pub language: Language,
+    pub endpoint: Endpoint,
+    /// The first line shown in the viewport. It's clamped when the page is rendered with another
+    /// width and has less lines
+    pub y: u16,
+    /// Node indices of the first and last node of the selected link
+    pub selected: (usize, usize),
+}
+
+/// The state of the last session, saved when quitting
+#[derive(Serialize, Deserialize, Default)]
+pub struct Session {
+    /// The current page, `None` when no page was open
+    pub page: Option<SessionPage>,
+}
+
+impl Session {
+    pub fn open() -> anyhow::Result<Store<Session>> {
+        Store::open(SESSION_STORE)
+    }
+
+    /// Replaces the saved session, even when the stored one cannot be read
+    pub fn save(self) -> anyhow::Result<()> {
+        Store::create(SESSION_STORE, self)?.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::{languages::Language, Endpoint};
+
+    use super::{Session, SessionPage};
+    use crate::store::Store;
+
+    #[test]
+    fn test_session_store() {
+        let path =
+            std::env::temp_dir().join(format!("wiki-tui-session-{}.json", std::process::id()));
+        let page = SessionPage {
+            title: "Rust".to_string(),
+            language: Language::German,
+            endpoint: Endpoint::parse("https://de.wikipedia.org/w/api.php").unwrap(),
+            y: 42,
+            selected: (120, 122),
+        };
+
+        let mut store = Store::<Session>::open_path(path.clone()).unwrap();
+        assert_eq!(store.get().page, None);
+        store.get_mut().page = Some(page.clone());
+        store.save().unwrap();
+        let store = Store::<Session>::open_path(path.clone()).unwrap();
+        assert_eq!(store.get().page, Some(page));
+
+        // a broken session is reported instead of being silently replaced
+        std::fs::write(&path, "{\"page\": {\"title\": ").unwrap();
+        assert!(Store::<Session>::open_path(path.clone()).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
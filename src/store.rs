@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use wiki_api::{languages::Language, page::Page};
+
+use crate::config::data_dir;
+
+/// A piece of user data that is persisted between sessions
+///
+/// The data is stored as json in the data directory (see [`data_dir`]) and loaded when the store
+/// is opened. Changes are only written back to the disk when calling [`Store::save`]
+///
+/// [`data_dir`]: crate::config::data_dir
+/// [`Store::save`]: Store::save
+pub struct Store<T> {
+    path: PathBuf,
+    data: T,
+}
+
+impl<T> Store<T>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    /// Opens the store with the given name, loading the stored data if it exists
+    pub fn open(name: &str) -> Result<Self> {
+        let path = data_dir()
+            .context("failed retrieving the data dir")?
+            .join(format!("{name}.json"));
+        Self::open_path(path)
+    }
+
+    /// Creates the store with the given name holding the data, which replaces the stored data
+    /// when saving
+    pub fn create(name: &str, data: T) -> Result<Self> {
+        let path = data_dir()
+            .context("failed retrieving the data dir")?
+            .join(format!("{name}.json"));
+        Ok(Self { path, data })
+    }
+
+    /// Opens the store at a specific path, loading the stored data if it exists
+    pub fn open_path(path: PathBuf) -> Result<Self> {
+        let data = if path.exists() {
+            let data_str = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed reading the store '{}'", path.display()))?;
+            serde_json::from_str(&data_str)
+                .with_context(|| format!("failed parsing the store '{}'", path.display()))?
+        } else {
+            T::default()
+        };
+
+        Ok(Self { path, data })
+    }
+
+    pub fn get(&self) -> &T {
+        &self.data
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+
+    /// Writes the current data back to the disk
+    ///
+    /// The data is written to a temporary file first, which then replaces the store. That way a
+    /// crash while writing cannot leave a truncated store behind
+    pub fn save(&self) -> Result<()> {
+        let data_str = serde_json::to_string(&self.data).context("failed serializing the store")?;
+        let tmp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, data_str)
+            .with_context(|| format!("failed writing the store '{}'", tmp_path.display()))?;
+        std::fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("failed replacing the store '{}'", self.path.display()))
+    }
+}
+
+/// Returns the key identifying an article in a store
+pub fn article_key(language: Language, title: &str) -> String {
+    format!("{}:{}", language.code(), title)
+}
+
+/// Returns the key identifying the article of the page in a store
+pub fn page_key(page: &Page) -> String {
+    article_key(page.language, &page.title)
+}
+
+/// Stores a language by its code, the serialized name of the variant cannot always be parsed again
+pub mod language_code {
+    use std::str::FromStr;
+
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use wiki_api::languages::Language;
+
+    pub fn serialize<S: Serializer>(language: &Language, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(language.code())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Language, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Language::from_str(&code).map_err(de::Error::custom)
+    }
+}
@@ -8,15 +8,33 @@ use ratatui::prelude::CrosstermBackend as Backend;
 
 pub type Frame<'a> = ratatui::Frame<'a>;
 
+/// Leaves the raw mode and the alternate screen, also when the terminal was entered by a
+/// [`Tui`] that's no longer available, like after a panic
+pub fn restore() -> Result<()> {
+    crossterm::execute!(
+        std::io::stderr(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        cursor::Show
+    )?;
+    crossterm::terminal::disable_raw_mode()?;
+    Ok(())
+}
+
 pub struct Tui {
     pub terminal: ratatui::Terminal<Backend<std::io::Stderr>>,
+    /// Whether the terminal is in raw mode and shows the alternate screen
+    is_entered: bool,
 }
 
 impl Tui {
     pub fn new() -> Result<Self> {
         let terminal = ratatui::Terminal::new(Backend::new(std::io::stderr()))
             .context("unable to create terminal")?;
-        Ok(Self { terminal })
+        Ok(Self {
+            terminal,
+            is_entered: false,
+        })
     }
 
     pub fn enter(&mut self) -> Result<()> {
@@ -27,27 +45,26 @@ impl Tui {
             EnableMouseCapture,
             cursor::Hide
         )?;
+        self.is_entered = true;
         self.terminal.clear()?;
+        // the images were cleared with the screen
+        crate::images::forget_drawn_images();
         Ok(())
     }
 
     pub fn exit(&mut self) -> Result<()> {
-        self.terminal.clear()?;
-        crossterm::execute!(
-            std::io::stderr(),
-            LeaveAlternateScreen,
-            DisableMouseCapture,
-            cursor::Show
-        )?;
-        crossterm::terminal::disable_raw_mode()?;
-        Ok(())
+        self.is_entered = false;
+        // the terminal is restored even when clearing it fails
+        let cleared = self.terminal.clear();
+        restore()?;
+        Ok(cleared?)
     }
 
+    /// Restores the terminal and stops the process like the default behaviour of `SIGTSTP`.
+    /// Returns once the process is continued, for example with `fg`
+    #[cfg(unix)]
     pub fn suspend(&mut self) -> Result<()> {
         self.exit()?;
-        #[cfg(windows)]
-        signal_hook::low_level::raise(signal_hook::consts::signal::SIGABRT)?;
-        #[cfg(not(windows))]
         signal_hook::low_level::raise(signal_hook::consts::signal::SIGSTOP)?;
         Ok(())
     }
@@ -57,3 +74,12 @@ impl Tui {
         Ok(())
     }
 }
+
+/// Restores the terminal when returning early with an error
+impl Drop for Tui {
+    fn drop(&mut self) {
+        if self.is_entered {
+            let _ = self.exit();
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! The colors and characters the page and the other components are drawn with. The themes are
+//! loaded from the config by [`crate::config::load_themes`], the renderers only need the styles
+//! and don't depend on the rest of the config or the terminal
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    widgets::BorderType,
+};
+use serde::Deserialize;
+
+#[derive(Clone)]
+pub struct Theme {
+    pub name: String,
+
+    pub bg: Color,
+    pub fg: Color,
+
+    pub title: Color,
+
+    pub selected_bg: Color,
+    pub selected_fg: Color,
+
+    pub inactive_fg: Color,
+    pub highlight_fg: Color,
+
+    pub border_fg: Color,
+    pub border_bg: Color,
+    pub border_type: ThemeBorderType,
+
+    pub border_highlight_fg: Color,
+    pub border_highlight_bg: Color,
+
+    pub scrollbar_track_fg: Color,
+    pub scrollbar_thumb_fg: Color,
+    pub scrollbar_track_char: char,
+    pub scrollbar_thumb_char: char,
+
+    pub search_title_fg: Color,
+
+    pub status_bar_fg: Color,
+    pub status_bar_bg: Color,
+
+    pub highlight_bg: Color,
+
+    pub horizontal_rule_char: char,
+    pub horizontal_rule_fg: Color,
+
+    pub page_title_fg: Color,
+    /// Foreground color of the short description below the title, `Reset` keeps the color of the
+    /// text
+    pub page_description_fg: Color,
+    pub header_fg: Color,
+    pub italic_fg: Color,
+    pub reflink_fg: Color,
+
+    pub wiki_link_fg: Color,
+    pub external_link_fg: Color,
+    pub red_link_fg: Color,
+    pub media_link_fg: Color,
+    /// Foreground color of the selected link, `Reset` keeps the color of the link
+    pub selected_link_fg: Color,
+    /// Background color of the link under the mouse pointer
+    pub hovered_link_bg: Color,
+
+    pub unsupported_fg: Color,
+
+    /// Foreground color of the items in the table of contents, `Reset` uses the foreground color
+    pub contents_fg: Color,
+    /// Border color of the table of contents, `Reset` uses the border color
+    pub contents_border_fg: Color,
+    /// Border color of the focused table of contents, `Reset` uses the highlighted border color
+    pub contents_border_focused_fg: Color,
+    pub contents_highlight_fg: Color,
+    pub contents_highlight_bg: Color,
+    /// Modifiers of the selected item in the table of contents, like italic
+    pub contents_highlight_modifier: Modifier,
+    /// Drawn in front of the selected item in the table of contents
+    pub contents_highlight_symbol: String,
+
+    pub search_match_fg: Color,
+    pub search_match_bg: Color,
+    /// Background color of the match the page was scrolled to
+    pub search_current_bg: Color,
+}
+
+impl Theme {
+    pub fn new() -> Self {
+        Theme {
+            name: "default".to_string(),
+
+            bg: Color::Reset,
+            fg: Color::Reset,
+
+            title: Color::White,
+
+            selected_bg: Color::DarkGray,
+            selected_fg: Color::Reset,
+
+            inactive_fg: Color::Blue,
+            highlight_fg: Color::White,
+
+            border_fg: Color::White,
+            border_bg: Color::Reset,
+            border_type: ThemeBorderType::Rounded,
+
+            border_highlight_fg: Color::Yellow,
+            border_highlight_bg: Color::Reset,
+
+            scrollbar_track_fg: Color::Black,
+            scrollbar_thumb_fg: Color::Blue,
+            scrollbar_track_char: ' ',
+            scrollbar_thumb_char: '█',
+
+            search_title_fg: Color::Red,
+
+            status_bar_fg: Color::Reset,
+            status_bar_bg: Color::DarkGray,
+
+            highlight_bg: Color::Indexed(58),
+
+            horizontal_rule_char: '─',
+            horizontal_rule_fg: Color::DarkGray,
+
+            page_title_fg: Color::Red,
+            page_description_fg: Color::Reset,
+            header_fg: Color::Red,
+            italic_fg: Color::Blue,
+            reflink_fg: Color::Gray,
+
+            wiki_link_fg: Color::Blue,
+            external_link_fg: Color::Reset,
+            red_link_fg: Color::LightRed,
+            media_link_fg: Color::Blue,
+            selected_link_fg: Color::Reset,
+            hovered_link_bg: Color::Indexed(237),
+
+            unsupported_fg: Color::DarkGray,
+
+            contents_fg: Color::Reset,
+            contents_border_fg: Color::Reset,
+            contents_border_focused_fg: Color::Reset,
+            contents_highlight_fg: Color::Reset,
+            contents_highlight_bg: Color::DarkGray,
+            contents_highlight_modifier: Modifier::ITALIC,
+            contents_highlight_symbol: "▶ ".to_string(),
+
+            search_match_fg: Color::Black,
+            search_match_bg: Color::Yellow,
+            search_current_bg: Color::LightRed,
+        }
+    }
+
+    /// Returns a Paragraph with the background and foreground colors set
+    pub fn default_paragraph<'a, T>(&self, text: T) -> ratatui::widgets::Paragraph<'a>
+    where
+        T: Into<ratatui::text::Text<'a>>,
+    {
+        ratatui::widgets::Paragraph::new(text).style(Style::default().bg(self.bg).fg(self.fg))
+    }
+
+    /// Returns a Block with Borders::ALL, BorderType::Rounded and bg and fg colors set
+    pub fn default_block(&self) -> ratatui::widgets::Block {
+        ratatui::widgets::Block::default()
+            .borders(ratatui::widgets::Borders::ALL)
+            .border_type(self.border_type.clone().into())
+            .border_style(Style::default().fg(self.border_fg).bg(self.border_bg))
+            .title_style(Style::default().fg(self.title))
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::new()
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub enum ThemeBorderType {
+    Plain,
+    Rounded,
+    Double,
+    Thick,
+    QuadrantInside,
+    QuadrantOutside,
+}
+
+impl From<ThemeBorderType> for BorderType {
+    fn from(val: ThemeBorderType) -> Self {
+        match val {
+            ThemeBorderType::Plain => BorderType::Plain,
+            ThemeBorderType::Rounded => BorderType::Rounded,
+            ThemeBorderType::Double => BorderType::Double,
+            ThemeBorderType::Thick => BorderType::Thick,
+            ThemeBorderType::QuadrantInside => BorderType::QuadrantInside,
+            ThemeBorderType::QuadrantOutside => BorderType::QuadrantOutside,
+        }
+    }
+}
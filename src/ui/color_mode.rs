@@ -0,0 +1,210 @@
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use serde::Deserialize;
+
+/// The colors the terminal is able to display
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Detect the color mode from the environment (`NO_COLOR`, `COLORTERM` and `TERM`)
+    #[default]
+    Auto,
+    /// Display all colors as they are
+    Full,
+    /// Downgrade all colors to the nearest of the 8 ANSI colors
+    Ansi,
+    /// Don't display any colors, only bold, italic, underline and reverse
+    Monochrome,
+}
+
+impl ColorMode {
+    /// Resolves `Auto` to the color mode detected from the environment
+    pub fn resolve(self) -> ColorMode {
+        match self {
+            ColorMode::Auto => ColorMode::detect(
+                std::env::var("NO_COLOR").ok().as_deref(),
+                std::env::var("COLORTERM").ok().as_deref(),
+                std::env::var("TERM").ok().as_deref(),
+            ),
+            mode => mode,
+        }
+    }
+
+    fn detect(no_color: Option<&str>, colorterm: Option<&str>, term: Option<&str>) -> ColorMode {
+        // https://no-color.org: any non-empty value disables colors
+        if no_color.is_some_and(|value| !value.is_empty()) {
+            return ColorMode::Monochrome;
+        }
+
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return ColorMode::Full;
+        }
+
+        match term {
+            Some("dumb") => ColorMode::Monochrome,
+            Some(term) if term.contains("256color") || term.contains("direct") => ColorMode::Full,
+            Some(_) => ColorMode::Ansi,
+            None => ColorMode::Full,
+        }
+    }
+
+    /// Returns the next concrete color mode, used for toggling the mode at runtime
+    pub fn next(self) -> ColorMode {
+        match self {
+            ColorMode::Auto | ColorMode::Full => ColorMode::Ansi,
+            ColorMode::Ansi => ColorMode::Monochrome,
+            ColorMode::Monochrome => ColorMode::Full,
+        }
+    }
+
+    /// Filters the colors of every cell in the buffer according to the color mode
+    ///
+    /// In monochrome mode, cells with a background color other than `bg` (selections,
+    /// highlights, the status bar) are reversed so they remain distinguishable
+    pub fn apply(self, buffer: &mut Buffer, bg: Color) {
+        match self {
+            ColorMode::Auto | ColorMode::Full => {}
+            ColorMode::Ansi => {
+                for cell in buffer.content.iter_mut() {
+                    cell.fg = nearest_ansi(cell.fg);
+                    cell.bg = nearest_ansi(cell.bg);
+                }
+            }
+            ColorMode::Monochrome => {
+                for cell in buffer.content.iter_mut() {
+                    if cell.bg != Color::Reset && cell.bg != bg {
+                        cell.modifier.insert(Modifier::REVERSED);
+                    }
+                    cell.fg = Color::Reset;
+                    cell.bg = Color::Reset;
+                }
+            }
+        }
+    }
+}
+
+/// The 8 ANSI colors and their (approximate) rgb values
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 8] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+];
+
+/// Returns the nearest of the 8 ANSI colors
+fn nearest_ansi(color: Color) -> Color {
+    let (r, g, b) = match color {
+        Color::DarkGray => return Color::Black,
+        Color::LightRed => return Color::Red,
+        Color::LightGreen => return Color::Green,
+        Color::LightYellow => return Color::Yellow,
+        Color::LightBlue => return Color::Blue,
+        Color::LightMagenta => return Color::Magenta,
+        Color::LightCyan => return Color::Cyan,
+        Color::White => return Color::Gray,
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(n) => match indexed_rgb(n) {
+            Ok(rgb) => rgb,
+            Err(color) => return color,
+        },
+        color => return color,
+    };
+
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        let dr = r as i32 - cr as i32;
+        let dg = g as i32 - cg as i32;
+        let db = b as i32 - cb as i32;
+        dr * dr + dg * dg + db * db
+    };
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+/// Returns the rgb value of an indexed color, or the ANSI color itself for the first 16 colors
+fn indexed_rgb(n: u8) -> Result<(u8, u8, u8), Color> {
+    match n {
+        0..=15 => Err(ANSI_COLORS[(n % 8) as usize].0),
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let n = n - 16;
+            Ok((level(n / 36), level((n / 6) % 6), level(n % 6)))
+        }
+        232..=255 => {
+            let gray = 8 + (n - 232) * 10;
+            Ok((gray, gray, gray))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        buffer::Buffer,
+        layout::Rect,
+        style::{Color, Modifier, Style},
+    };
+
+    use super::{nearest_ansi, ColorMode};
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            ColorMode::detect(Some("1"), Some("truecolor"), Some("xterm-256color")),
+            ColorMode::Monochrome
+        );
+        assert_eq!(
+            ColorMode::detect(Some(""), None, Some("xterm-256color")),
+            ColorMode::Full
+        );
+        assert_eq!(
+            ColorMode::detect(None, Some("truecolor"), Some("xterm")),
+            ColorMode::Full
+        );
+        assert_eq!(
+            ColorMode::detect(None, None, Some("xterm")),
+            ColorMode::Ansi
+        );
+        assert_eq!(
+            ColorMode::detect(None, None, Some("dumb")),
+            ColorMode::Monochrome
+        );
+    }
+
+    #[test]
+    fn test_nearest_ansi() {
+        assert_eq!(nearest_ansi(Color::Reset), Color::Reset);
+        assert_eq!(nearest_ansi(Color::Red), Color::Red);
+        assert_eq!(nearest_ansi(Color::LightBlue), Color::Blue);
+        assert_eq!(nearest_ansi(Color::Rgb(250, 10, 20)), Color::Red);
+        assert_eq!(nearest_ansi(Color::Indexed(9)), Color::Red);
+        assert_eq!(nearest_ansi(Color::Indexed(58)), Color::Black);
+        assert_eq!(nearest_ansi(Color::Indexed(255)), Color::Gray);
+    }
+
+    #[test]
+    fn test_monochrome() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buffer.set_string(0, 0, "a", Style::default().fg(Color::Red));
+        buffer.set_string(1, 0, "b", Style::default().bg(Color::DarkGray));
+        buffer.set_string(2, 0, "c", Style::default().bg(Color::Black));
+
+        ColorMode::Monochrome.apply(&mut buffer, Color::Black);
+
+        let cells = &buffer.content;
+        assert!(cells
+            .iter()
+            .all(|cell| cell.fg == Color::Reset && cell.bg == Color::Reset));
+        assert!(!cells[0].modifier.contains(Modifier::REVERSED));
+        assert!(cells[1].modifier.contains(Modifier::REVERSED));
+        assert!(!cells[2].modifier.contains(Modifier::REVERSED));
+    }
+}
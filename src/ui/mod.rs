@@ -1,8 +1,12 @@
 mod centered_rect;
+mod color_mode;
 mod padded_rect;
+mod spinner;
 mod stateful_list;
 
 pub use centered_rect::centered_rect;
+pub use color_mode::ColorMode;
 pub use padded_rect::padded_rect;
+pub use spinner::spinner;
 
 pub use stateful_list::{ScrollBehaviour, StatefulList};
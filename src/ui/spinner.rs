@@ -0,0 +1,11 @@
+use std::time::Instant;
+
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+/// How long each frame of the spinner is shown, in milliseconds
+const SPINNER_FRAME_MS: u128 = 80;
+
+/// Returns the frame of a loading spinner that was started at the instant
+pub fn spinner(started: Instant) -> &'static str {
+    let frame = (started.elapsed().as_millis() / SPINNER_FRAME_MS) as usize % SPINNER_FRAMES.len();
+    SPINNER_FRAMES[frame]
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use wiki_api::{languages::Language, Endpoint};
+
+use crate::store::{article_key, Store};
+
+pub const WATCHES_STORE: &str = "watched_pages";
+
+/// Number of pages that can be watched, every watched page is requested once per interval
+pub const MAX_WATCHED_PAGES: usize = 10;
+
+/// Maximum number of rounds of polling a page is skipped after its checks failed
+const MAX_SKIPPED_ROUNDS: u32 = 8;
+
+/// A page that's checked for new revisions in the background
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WatchedPage {
+    pub title: String,
+    #[serde(with = "crate::store::language_code")]
+    pub language: Language,
+    pub endpoint: Endpoint,
+    /// Id of the latest revision that was seen, `None` until it's known
+    pub revision_id: Option<usize>,
+    /// Whether the page was edited since it was last opened from the watched pages
+    pub has_update: bool,
+}
+
+impl WatchedPage {
+    pub fn new(
+        title: String,
+        language: Language,
+        endpoint: Endpoint,
+        revision_id: Option<usize>,
+    ) -> Self {
+        Self {
+            title,
+            language,
+            endpoint,
+            revision_id,
+            has_update: false,
+        }
+    }
+
+    pub fn is_same_page(&self, endpoint: &Endpoint, language: Language, title: &str) -> bool {
+        self.endpoint == *endpoint && self.language == language && self.title == title
+    }
+}
+
+/// The watched pages, the most recently watched page first. At most [`MAX_WATCHED_PAGES`] pages
+/// are watched
+#[derive(Serialize, Deserialize, Default)]
+pub struct Watches {
+    pages: Vec<WatchedPage>,
+}
+
+impl Watches {
+    pub fn open() -> anyhow::Result<Store<Watches>> {
+        Store::open(WATCHES_STORE)
+    }
+
+    pub fn pages(&self) -> &[WatchedPage] {
+        &self.pages
+    }
+
+    pub fn is_watched(&self, endpoint: &Endpoint, language: Language, title: &str) -> bool {
+        self.pages
+            .iter()
+            .any(|page| page.is_same_page(endpoint, language, title))
+    }
+
+    /// Watches the page, unless the maximum number of pages is already watched. Returns whether
+    /// the page was added
+    pub fn add(&mut self, page: WatchedPage) -> bool {
+        if self.is_watched(&page.endpoint, page.language, &page.title) {
+            return true;
+        }
+        if self.pages.len() >= MAX_WATCHED_PAGES {
+            return false;
+        }
+        self.pages.insert(0, page);
+        true
+    }
+
+    pub fn remove(&mut self, endpoint: &Endpoint, language: Language, title: &str) {
+        self.pages
+            .retain(|page| !page.is_same_page(endpoint, language, title));
+    }
+
+    /// Records the latest revision of the page. Returns whether the page was edited since the
+    /// previous check, the first known revision is no update
+    pub fn update_revision(&mut self, checked: &WatchedPage, revision_id: usize) -> bool {
+        let Some(page) = self
+            .pages
+            .iter_mut()
+            .find(|page| page.is_same_page(&checked.endpoint, checked.language, &checked.title))
+        else {
+            return false;
+        };
+        let is_update = page.revision_id.is_some_and(|id| id != revision_id);
+        page.revision_id = Some(revision_id);
+        page.has_update |= is_update;
+        is_update
+    }
+
+    /// Marks the update of the page as seen
+    pub fn mark_seen(&mut self, endpoint: &Endpoint, language: Language, title: &str) {
+        for page in self.pages.iter_mut() {
+            if page.is_same_page(endpoint, language, title) {
+                page.has_update = false;
+            }
+        }
+    }
+}
+
+/// The state of checking a watched page
+#[derive(Default)]
+struct PollState {
+    is_checking: bool,
+    /// Number of checks that failed in a row
+    failures: u32,
+    /// Number of rounds left until the page is checked again
+    skipped: u32,
+}
+
+/// Decides which watched pages are checked in a round of polling. A page isn't checked while its
+/// previous check is still running, and a page whose checks fail is skipped for 1, 2, 4 and at
+/// most [`MAX_SKIPPED_ROUNDS`] rounds
+#[derive(Default)]
+pub struct WatchPoller {
+    pages: HashMap<(Endpoint, String), PollState>,
+}
+
+impl WatchPoller {
+    /// Returns the pages to check in this round and marks them as being checked
+    pub fn start_round(&mut self, pages: &[WatchedPage]) -> Vec<WatchedPage> {
+        self.pages
+            .retain(|key, _| pages.iter().any(|page| poll_key(page) == *key));
+
+        let mut due = Vec::new();
+        for page in pages {
+            let state = self.pages.entry(poll_key(page)).or_default();
+            if state.is_checking {
+                continue;
+            }
+            if state.skipped > 0 {
+                state.skipped -= 1;
+                continue;
+            }
+            state.is_checking = true;
+            due.push(page.clone());
+        }
+        due
+    }
+
+    /// Records the end of a check of the page
+    pub fn finish(&mut self, page: &WatchedPage, is_success: bool) {
+        let Some(state) = self.pages.get_mut(&poll_key(page)) else {
+            return;
+        };
+        state.is_checking = false;
+        match is_success {
+            true => state.failures = 0,
+            false => {
+                state.skipped = 2u32.saturating_pow(state.failures).min(MAX_SKIPPED_ROUNDS);
+                state.failures = state.failures.saturating_add(1);
+            }
+        }
+    }
+}
+
+/// Returns the key of the page in the poller, the same title can be watched on several sites
+fn poll_key(page: &WatchedPage) -> (Endpoint, String) {
+    (
+        page.endpoint.clone(),
+        article_key(page.language, &page.title),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use wiki_api::{languages::Language, Endpoint};
+
+    use super::{WatchPoller, WatchedPage, Watches, MAX_WATCHED_PAGES};
+
+    fn endpoint() -> Endpoint {
+        Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap()
+    }
+
+    fn page(title: &str, revision_id: Option<usize>) -> WatchedPage {
+        WatchedPage::new(
+            title.to_string(),
+            Language::English,
+            endpoint(),
+            revision_id,
+        )
+    }
+
+    #[test]
+    fn test_watches() {
+        let mut watches = Watches::default();
+        assert!(watches.add(page("Rust", Some(1))));
+        assert!(watches.add(page("Iron", None)));
+        // watching a page again keeps its revision
+        assert!(watches.add(page("Rust", None)));
+        assert_eq!(watches.pages().len(), 2);
+        assert_eq!(watches.pages()[1].revision_id, Some(1));
+
+        // the first known revision isn't an update
+        assert!(!watches.update_revision(&page("Iron", None), 5));
+        assert!(!watches.update_revision(&page("Rust", None), 1));
+        assert!(watches.update_revision(&page("Rust", None), 2));
+        assert!(watches.pages()[1].has_update);
+        assert!(!watches.update_revision(&page("Oxygen", None), 3));
+
+        watches.mark_seen(&endpoint(), Language::English, "Rust");
+        assert!(!watches.pages()[1].has_update);
+        assert_eq!(watches.pages()[1].revision_id, Some(2));
+
+        for idx in watches.pages().len()..MAX_WATCHED_PAGES {
+            assert!(watches.add(page(&format!("Page {idx}"), None)));
+        }
+        assert!(!watches.add(page("Oxygen", None)));
+        assert!(!watches.is_watched(&endpoint(), Language::English, "Oxygen"));
+
+        watches.remove(&endpoint(), Language::English, "Iron");
+        assert!(!watches.is_watched(&endpoint(), Language::English, "Iron"));
+        assert!(watches.add(page("Oxygen", None)));
+
+        // the same title on another site is another page
+        let wiktionary = Endpoint::parse("https://en.wiktionary.org/w/api.php").unwrap();
+        assert!(!watches.is_watched(&wiktionary, Language::English, "Rust"));
+        watches.remove(&wiktionary, Language::English, "Rust");
+        assert!(watches.is_watched(&endpoint(), Language::English, "Rust"));
+    }
+
+    #[test]
+    fn test_watch_poller() {
+        let pages = [page("Rust", None), page("Iron", None)];
+        let titles = |pages: Vec<WatchedPage>| -> Vec<String> {
+            pages.into_iter().map(|page| page.title).collect()
+        };
+        let mut poller = WatchPoller::default();
+
+        assert_eq!(titles(poller.start_round(&pages)), ["Rust", "Iron"]);
+        // the check of a page doesn't overlap with its previous one
+        poller.finish(&page("Iron", None), true);
+        assert_eq!(titles(poller.start_round(&pages)), ["Iron"]);
+        poller.finish(&page("Iron", None), true);
+
+        // the failing page is skipped for 1, 2, 4, 8 and then 8 rounds again
+        let mut skipped = Vec::new();
+        poller.finish(&page("Rust", None), false);
+        for _ in 0..5 {
+            let mut rounds = 0;
+            loop {
+                let due = titles(poller.start_round(&pages));
+                poller.finish(&page("Iron", None), true);
+                if due.contains(&"Rust".to_string()) {
+                    break;
+                }
+                rounds += 1;
+            }
+            skipped.push(rounds);
+            poller.finish(&page("Rust", None), false);
+        }
+        assert_eq!(skipped, [1, 2, 4, 8, 8]);
+
+        // a successful check resets the backoff
+        poller.finish(&page("Rust", None), true);
+        for _ in 0..8 {
+            poller.start_round(&pages);
+            poller.finish(&page("Iron", None), true);
+        }
+        assert_eq!(titles(poller.start_round(&pages)), ["Rust", "Iron"]);
+        poller.finish(&page("Rust", None), false);
+        assert!(poller.start_round(&pages).is_empty());
+        poller.finish(&page("Iron", None), true);
+        assert_eq!(titles(poller.start_round(&pages)), ["Rust", "Iron"]);
+    }
+}
@@ -0,0 +1,124 @@
+//! Counts the allocations of drawing a page that's already rendered, which happens on every key
+//! press and tick. The words are borrowed into the spans of the frame, so the number of
+//! allocations must not grow with the number of visible words.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::Arc,
+};
+
+use ratatui::{backend::TestBackend, Terminal};
+use wiki_api::{
+    document::Document,
+    languages::Language,
+    page::{Page, PageKind},
+    parser::{Parser, WikipediaParser},
+    Endpoint,
+};
+use wiki_tui::{
+    components::{page::PageComponent, Component},
+    config::{Config, Theme},
+};
+
+/// Counts the allocations of the current thread, so the other tests running at the same time
+/// don't change the count
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+fn page(words: usize) -> PageComponent {
+    let text: Vec<&str> = ["iron", "oxide", "usually", "reddish", "brown"]
+        .iter()
+        .cycle()
+        .take(words)
+        .copied()
+        .collect();
+    let paragraphs = format!("<p>{}</p>", text.join(" ")).repeat(20);
+    let nodes = WikipediaParser::parse_document(
+        &paragraphs,
+        Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+        Language::English,
+    )
+    .nodes();
+
+    let page = Page {
+        title: "Rust".to_string(),
+        pageid: 0,
+        kind: PageKind::Article,
+        content: Document { nodes },
+        language: Language::English,
+        endpoint: Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+        language_links: None,
+        sections: None,
+        revision_id: None,
+        redirected_from: None,
+        description: None,
+    };
+    PageComponent::new(
+        page,
+        Arc::new(Config::default()),
+        Arc::new(Theme::default()),
+    )
+}
+
+/// Returns the allocations of drawing a frame of a page with paragraphs of `words` words, after
+/// the page was rendered by the first frame
+fn frame_allocations(words: usize) -> usize {
+    let mut page = page(words);
+    let mut terminal = Terminal::new(TestBackend::new(160, 50)).unwrap();
+    terminal.draw(|f| page.render(f, f.size())).unwrap();
+
+    let mut count = 0;
+    terminal
+        .draw(|f| {
+            let before = allocations();
+            page.render(f, f.size());
+            count = allocations() - before;
+        })
+        .unwrap();
+    count
+}
+
+#[test]
+fn test_cached_frame_allocations() {
+    // both pages fill the viewport, but the lines of the second one have far more words
+    let few_words = frame_allocations(2);
+    let many_words = frame_allocations(200);
+    println!(
+        "allocations per frame: {} with few words, {} with many words",
+        few_words, many_words
+    );
+    assert!(
+        many_words <= few_words + 50,
+        "drawing a frame allocates for every word: {} allocations with few words, {} with many \
+        words",
+        few_words,
+        many_words
+    );
+}
@@ -0,0 +1,53 @@
+use std::{
+    panic::AssertUnwindSafe,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use ratatui::{backend::TestBackend, layout::Rect, Terminal};
+use wiki_tui::{components::Component, panic_handler::report_panic, terminal::Frame};
+
+/// A component failing while rendering
+struct PanickingComponent;
+
+impl Component for PanickingComponent {
+    fn render(&mut self, _: &mut Frame<'_>, _: Rect) {
+        panic!("deliberate panic while rendering");
+    }
+}
+
+#[test]
+fn test_panic_restores_terminal() {
+    let crash_dir = std::env::temp_dir().join(format!("wiki-tui-crash-{}", std::process::id()));
+    let restored = Arc::new(AtomicUsize::new(0));
+    let crash_file = Arc::new(Mutex::new(None));
+
+    let (hook_restored, hook_crash_file, hook_crash_dir) =
+        (restored.clone(), crash_file.clone(), crash_dir.clone());
+    std::panic::set_hook(Box::new(move |panic_info| {
+        let restore = || {
+            hook_restored.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        };
+        *hook_crash_file.lock().unwrap() = report_panic(panic_info, restore, Some(&hook_crash_dir));
+    }));
+
+    let mut terminal = Terminal::new(TestBackend::new(20, 5)).unwrap();
+    let mut component = PanickingComponent;
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        let _ = terminal.draw(|frame| component.render(frame, frame.size()));
+    }));
+    let _ = std::panic::take_hook();
+
+    assert!(result.is_err());
+    assert_eq!(restored.load(Ordering::SeqCst), 1);
+
+    let crash_file = crash_file.lock().unwrap().take().expect("no crash file");
+    let report = std::fs::read_to_string(crash_file).unwrap();
+    assert!(report.contains("deliberate panic while rendering"));
+    assert!(report.contains(env!("CARGO_PKG_VERSION")));
+
+    let _ = std::fs::remove_dir_all(crash_dir);
+}
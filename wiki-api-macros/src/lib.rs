@@ -57,6 +57,7 @@ pub fn parse_languages(input: TokenStream) -> TokenStream {
     let mut language_data_arms = quote!();
     let mut from_str_arms = quote!();
     let mut array_def = quote!();
+    let mut rtl_arms = quote!();
     for (_key, value) in languages {
         let ident = value.identifier.clone().unwrap();
         let en_name = value.localname.clone();
@@ -82,6 +83,12 @@ pub fn parse_languages(input: TokenStream) -> TokenStream {
         array_def = quote! {
             #array_def
             Language::#ident,
+        };
+        if value.dir == "rtl" {
+            rtl_arms = quote! {
+                #rtl_arms
+                Language::#ident => true,
+            };
         }
     }
 
@@ -120,6 +127,14 @@ pub fn parse_languages(input: TokenStream) -> TokenStream {
             pub fn code(&self) -> &str {
                 self.language_data().2
             }
+
+            /// Returns whether the language is written from right to left
+            pub fn is_right_to_left(&self) -> bool {
+                match self {
+                    #rtl_arms
+                    _ => false,
+                }
+            }
         }
 
         impl FromStr for Language {
@@ -144,9 +159,16 @@ pub fn parse_languages(input: TokenStream) -> TokenStream {
 
         impl std::fmt::Display for ParseLanguageError {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                f.pad(
-                    &format!("error parsing langugage: '{}' is an unknown language", self.0)
-                )
+                let mut message =
+                    format!("error parsing langugage: '{}' is an unknown language", self.0);
+                if let Some(language) = suggest_language(&self.0) {
+                    message.push_str(&format!(
+                        ", did you mean '{}' ({})?",
+                        language.code(),
+                        language.name()
+                    ));
+                }
+                f.pad(&message)
             }
         }
 
@@ -1,4 +1,5 @@
 use serde_repr::Deserialize_repr;
+use url::Url;
 
 use crate::page::Link;
 
@@ -17,6 +18,157 @@ impl Document {
     pub fn nth(&self, n: usize) -> Option<Node> {
         Node::new(self, n)
     }
+
+    /// Returns the text of the reference the reflink with the anchor points to. References
+    /// used several times have one entry, which all of their reflinks point to
+    pub fn reference(&self, anchor: &str) -> Option<Node> {
+        self.nth(0)?
+            .descendants()
+            .find(|node| matches!(node.data(), Data::Reference { id } if id == anchor))
+    }
+
+    /// Returns the link of a redirect page to its target, `None` when the page isn't a redirect
+    pub fn redirect_target(&self) -> Option<&Link> {
+        self.nth(0)?
+            .descendants()
+            .filter(|node| node.data() == &Data::RedirectMessage)
+            .flat_map(|node| node.descendants())
+            .find_map(|node| match node.data() {
+                Data::Link(link @ Link::Internal(_)) => Some(link),
+                _ => None,
+            })
+    }
+
+    /// Collects the meanings listed on a disambiguation page. Every list item with a link to
+    /// another page is an entry, the link is paired with the rest of the text in the item.
+    /// Nested lists are entries of their own and the items in hatnotes are skipped
+    pub fn disambiguation_entries(&self) -> Vec<DisambiguationEntry> {
+        let root = match self.nth(0) {
+            Some(root) => root,
+            None => return Vec::new(),
+        };
+
+        root.descendants()
+            .filter(|node| node.data() == &Data::ListItem)
+            .filter(|node| {
+                !node
+                    .ancestors()
+                    .any(|ancestor| ancestor.data() == &Data::Disambiguation)
+            })
+            .filter_map(|item| {
+                let mut link = None;
+                let mut description = String::new();
+                collect_entry(item, &mut link, &mut description);
+
+                let (link, text) = link?;
+                let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+                let description = description
+                    .trim_start_matches([',', ':', '-', '–', '—'])
+                    .trim()
+                    .to_string();
+                Some(DisambiguationEntry {
+                    link,
+                    text,
+                    description,
+                })
+            })
+            .collect()
+    }
+
+    /// Counts the words in the text of the document, leaving out reflinks, references and
+    /// unsupported elements like tables and navboxes
+    pub fn word_count(&self) -> WordCount {
+        let mut count = WordCount::default();
+        if let Some(root) = self.nth(0) {
+            count_words(root, &mut count, &mut false);
+        }
+        count
+    }
+}
+
+/// The length of the text of a document
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WordCount {
+    /// Words separated by whitespace
+    pub words: usize,
+    /// Chinese and Japanese characters, which are written without spaces between the words
+    pub cjk_characters: usize,
+}
+
+/// Returns whether the character belongs to a script written without spaces between the words.
+/// Korean uses spaces and is counted in words
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'     // Hiragana and Katakana
+        | '\u{3400}'..='\u{4DBF}'   // CJK Unified Ideographs Extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK Unified Ideographs
+        | '\u{F900}'..='\u{FAFF}'   // CJK Compatibility Ideographs
+        | '\u{20000}'..='\u{2FA1F}' // CJK Unified Ideographs Extension B to F and supplements
+    )
+}
+
+/// Counts the words of the text below the node. `in_word` is carried between the text nodes, so a
+/// word split by formatting like `<b>wh</b>eat` is counted once
+fn count_words(node: Node, count: &mut WordCount, in_word: &mut bool) {
+    for child in node.children() {
+        match child.data() {
+            Data::Reflink
+            | Data::Reference { .. }
+            | Data::Figure { .. }
+            | Data::Unsupported(_)
+            | Data::UnsupportedInline(_) => *in_word = false,
+            Data::Text { contents } => {
+                for c in contents.chars() {
+                    if is_cjk(c) {
+                        count.cjk_characters += 1;
+                        *in_word = false;
+                    } else if c.is_whitespace() {
+                        *in_word = false;
+                    } else if c.is_alphanumeric() && !*in_word {
+                        count.words += 1;
+                        *in_word = true;
+                    }
+                }
+            }
+            _ => {
+                // blocks end the last word, even without whitespace between them
+                if !matches!(
+                    child.data(),
+                    Data::Bold | Data::Italic | Data::Span | Data::Link(_)
+                ) {
+                    *in_word = false;
+                }
+                count_words(child, count, in_word);
+            }
+        }
+    }
+}
+
+/// A meaning listed on a disambiguation page
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisambiguationEntry {
+    pub link: Link,
+    /// The text of the link
+    pub text: String,
+    /// The text following the link, like "a chemical element"
+    pub description: String,
+}
+
+/// Walks the children of a list item, the first link to another page is taken as the link of the
+/// entry and all other text is added to the description
+fn collect_entry(node: Node, link: &mut Option<(Link, String)>, description: &mut String) {
+    for child in node.children() {
+        match child.data() {
+            Data::OrderedList | Data::UnorderedList => continue,
+            Data::Link(target @ Link::Internal(_)) if link.is_none() => {
+                *link = Some((target.clone(), child.text().trim().to_string()));
+                continue;
+            }
+            Data::Text { contents } => description.push_str(contents),
+            Data::Linebreak => description.push(' '),
+            _ => collect_entry(child, link, description),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize_repr)]
@@ -46,6 +198,10 @@ pub enum Data {
     Paragraph,
     Span,
     Reflink,
+    /// The text of a reference in the references list. `id` is the anchor its reflinks point to
+    Reference {
+        id: String,
+    },
     Hatnote,
     RedirectMessage,
     Disambiguation,
@@ -63,6 +219,14 @@ pub enum Data {
     Italic,
 
     Linebreak,
+    HorizontalRule,
+    /// A figure with an image, `width` and `height` are the size the image is displayed at on the
+    /// site, `0` when it's unknown
+    Figure {
+        image: Url,
+        width: u32,
+        height: u32,
+    },
 
     Link(Link),
     #[default]
@@ -162,6 +326,21 @@ impl<'a> Node<'a> {
             next: self.first_child(),
         }
     }
+
+    /// Iterates over the parent of the node, its parent and so on up to the root
+    pub fn ancestors(&self) -> impl Iterator<Item = Node<'a>> {
+        std::iter::successors(self.parent(), |node| node.parent())
+    }
+
+    /// Concatenates the text of all descendants
+    pub fn text(&self) -> String {
+        self.descendants()
+            .filter_map(|node| match node.data() {
+                Data::Text { contents } => Some(contents.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -229,3 +408,270 @@ impl<'a> Iterator for Children<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        languages::Language,
+        page::{
+            link_data::{InterwikiData, InterwikiKind},
+            Link,
+        },
+        parser::{Parser, WikipediaParser},
+        Endpoint,
+    };
+
+    use super::{Data, Document, WordCount};
+
+    fn parse(html: &str) -> Document {
+        let nodes = WikipediaParser::parse_document(
+            html,
+            Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap(),
+            Language::default(),
+        )
+        .nodes();
+        Document { nodes }
+    }
+
+    #[test]
+    fn test_word_count() {
+        let document = parse(
+            r##"<p>Rust is an <b>iron</b> oxide, a well-known <a rel="mw:WikiLink" href="./Red" title="Red">red</a>dish-brown oxide.<sup class="mw-ref reference"><a href="#cite_note-1"><span class="mw-reflink-text">[1]</span></a></sup></p>
+            <table class="navbox"><tr><td>Iron compounds</td></tr></table>
+            <p>铁锈是氧化铁。</p>"##,
+        );
+        assert_eq!(
+            document.word_count(),
+            WordCount {
+                words: 9,
+                cjk_characters: 6
+            }
+        );
+    }
+
+    #[test]
+    fn test_redirect_target() {
+        let document = parse(
+            r#"<div class="redirectMsg"><p>Redirect to:</p><ul class="redirectText"><li><a rel="mw:WikiLink" href="./New_York_City" title="New York City">New York City</a></li></ul></div>"#,
+        );
+        match document.redirect_target() {
+            Some(Link::Internal(data)) => assert_eq!(data.title, "New York City"),
+            target => panic!("unexpected redirect target {:?}", target),
+        }
+
+        let document = parse(
+            r#"<p><a rel="mw:WikiLink" href="./New_York_City" title="New York City">NYC</a></p>"#,
+        );
+        assert_eq!(document.redirect_target(), None);
+    }
+
+    #[test]
+    fn test_red_link() {
+        let document = parse(
+            r#"<p><a rel="mw:WikiLink" href="./Iron_rust?action=edit&amp;redlink=1" title="Iron rust" class="new" typeof="mw:LocalizedAttrs">iron rust</a> <a rel="mw:WikiLink" href="./Rust" title="Rust">rust</a></p>"#,
+        );
+        let links: Vec<Link> = document
+            .nth(0)
+            .unwrap()
+            .descendants()
+            .filter_map(|node| match node.data() {
+                Data::Link(link) => Some(link.to_owned()),
+                _ => None,
+            })
+            .collect();
+        match &links[..] {
+            [Link::RedLink(red_link), Link::Internal(link)] => {
+                assert_eq!(red_link.title, "Iron rust");
+                assert_eq!(link.title, "Rust");
+            }
+            links => panic!("unexpected links {:?}", links),
+        }
+    }
+
+    #[test]
+    fn test_interwiki_links() {
+        let document = parse(
+            r#"<p><a rel="mw:WikiLink/Interwiki" href="https://fr.wikipedia.org/wiki/Paris" title="fr:Paris" class="extiw">Paris</a>
+            <a rel="mw:WikiLink/Interwiki" href="https://en.wiktionary.org/wiki/serendipity" title="wikt:serendipity" class="extiw">serendipity</a>
+            <a rel="mw:WikiLink/Interwiki" href="https://commons.wikimedia.org/wiki/File:Rust.jpg" title="commons:File:Rust.jpg" class="extiw">photo</a>
+            <a rel="mw:WikiLink/Interwiki" href="https://example.org/wiki/Rust" title="foo:Rust" class="extiw">foo</a>
+            <a rel="mw:WikiLink" href="https://de.wikipedia.org/wiki/Rost" title="Rost">Rost</a>
+            <a rel="mw:WikiLink" href="./Mission:_Impossible" title="Mission: Impossible">film</a></p>"#,
+        );
+        let links: Vec<Link> = document
+            .nth(0)
+            .unwrap()
+            .descendants()
+            .filter_map(|node| match node.data() {
+                Data::Link(link) => Some(link.to_owned()),
+                _ => None,
+            })
+            .collect();
+        let interwiki = |link: &Link| match link {
+            Link::Interwiki(data) => (data.prefix.clone(), data.title.clone(), data.kind),
+            link => panic!("unexpected link {:?}", link),
+        };
+
+        assert_eq!(
+            interwiki(&links[0]),
+            (
+                "fr".to_string(),
+                "Paris".to_string(),
+                InterwikiKind::Language(Language::French)
+            )
+        );
+        assert_eq!(
+            interwiki(&links[1]),
+            (
+                "wikt".to_string(),
+                "serendipity".to_string(),
+                InterwikiKind::SisterProject("Wiktionary")
+            )
+        );
+        assert_eq!(
+            interwiki(&links[2]),
+            (
+                "commons".to_string(),
+                "File:Rust.jpg".to_string(),
+                InterwikiKind::SisterProject("Wikimedia Commons")
+            )
+        );
+        assert_eq!(interwiki(&links[3]).2, InterwikiKind::Unknown);
+        // links without a prefix to another language of the site are named after its subdomain
+        assert_eq!(
+            interwiki(&links[4]),
+            (
+                "de".to_string(),
+                "Rost".to_string(),
+                InterwikiKind::Language(Language::German)
+            )
+        );
+        // a colon without a namespace is part of the title
+        match &links[5] {
+            Link::Internal(data) => assert_eq!(data.page, "Mission:_Impossible"),
+            link => panic!("unexpected link {:?}", link),
+        }
+
+        let endpoint = Endpoint::parse("https://en.wikipedia.org/w/api.php").unwrap();
+        let article = match &links[0] {
+            Link::Interwiki(data) => data.language_article(&endpoint).unwrap(),
+            _ => unreachable!(),
+        };
+        assert_eq!(article.title, "Paris");
+        assert_eq!(article.language, Language::French);
+        assert_eq!(
+            article.endpoint.as_str(),
+            "https://fr.wikipedia.org/w/api.php"
+        );
+        let wiktionary = InterwikiData::new(
+            "wikt:rust",
+            "https://en.wiktionary.org/wiki/rust".parse().unwrap(),
+            &endpoint,
+        );
+        assert_eq!(wiktionary.language_article(&endpoint), None);
+    }
+
+    #[test]
+    fn test_figure() {
+        let document = parse(
+            r#"<figure typeof="mw:File/Thumb"><a href="./File:Rust.jpg" class="mw-file-description"><img src="//upload.wikimedia.org/wikipedia/commons/thumb/a/ab/Rust.jpg/250px-Rust.jpg" width="250" height="188"/></a><figcaption>Rust on a chain</figcaption></figure>
+            <figure><figcaption>No image</figcaption></figure>"#,
+        );
+        let figures: Vec<Data> = document
+            .nth(0)
+            .unwrap()
+            .descendants()
+            .map(|node| node.data().to_owned())
+            .filter(|data| matches!(data, Data::Figure { .. } | Data::Unsupported(_)))
+            .collect();
+        match &figures[..] {
+            [Data::Figure {
+                image,
+                width,
+                height,
+            }, Data::Unsupported(_)] => {
+                assert_eq!(
+                    image.as_str(),
+                    "https://upload.wikimedia.org/wikipedia/commons/thumb/a/ab/Rust.jpg/250px-Rust.jpg"
+                );
+                assert_eq!((*width, *height), (250, 188));
+            }
+            figures => panic!("unexpected figures {:?}", figures),
+        }
+    }
+
+    #[test]
+    fn test_reference() {
+        let document = parse(
+            r##"<p>Rust<sup class="mw-ref reference" id="cite_ref-speed_1-0"><a href="./Rust#cite_note-speed-1"><span class="mw-reflink-text">[1]</span></a></sup>
+            is fast<sup class="mw-ref reference" id="cite_ref-2"><a href="./Rust#cite_note-2"><span class="mw-reflink-text">[2]</span></a></sup>
+            and safe<sup class="mw-ref reference" id="cite_ref-speed_1-1"><a href="./Rust#cite_note-speed-1"><span class="mw-reflink-text">[1]</span></a></sup></p>
+            <ol class="mw-references references">
+                <li id="cite_note-speed-1"><span class="mw-cite-backlink"><a href="./Rust#cite_ref-speed_1-0">a</a> <a href="./Rust#cite_ref-speed_1-1">b</a></span> <span id="mw-reference-text-cite_note-speed-1" class="mw-reference-text">A benchmark</span></li>
+                <li id="cite_note-2"><span class="mw-cite-backlink"><a href="./Rust#cite_ref-2">↑</a></span> <span id="mw-reference-text-cite_note-2" class="mw-reference-text">The <a rel="mw:WikiLink" href="./Rustonomicon" title="Rustonomicon">Rustonomicon</a></span></li>
+            </ol>"##,
+        );
+
+        // every reflink resolves to its reference, also when the reference is used twice
+        let reflinks: Vec<(String, String)> = document
+            .nth(0)
+            .unwrap()
+            .descendants()
+            .filter_map(|node| match node.data() {
+                Data::Link(link @ Link::Anchor(data)) if link.is_reference() => {
+                    let reference = document.reference(&data.anchor)?;
+                    Some((node.text(), reference.text()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            reflinks,
+            [
+                ("[1]".to_string(), "A benchmark".to_string()),
+                ("[2]".to_string(), "The Rustonomicon".to_string()),
+                ("[1]".to_string(), "A benchmark".to_string()),
+            ]
+        );
+        assert!(document.reference("cite_note-3").is_none());
+    }
+
+    #[test]
+    fn test_disambiguation_entries() {
+        let document = parse(
+            r#"<div class="hatnote">See also <ul><li><a rel="mw:WikiLink" href="./Mercurial" title="Mercurial">Mercurial</a></li></ul></div>
+            <p><b>Mercury</b> may refer to:</p>
+            <ul>
+                <li><a rel="mw:WikiLink" href="./Mercury_(element)" title="Mercury (element)">Mercury (element)</a>, a <i>chemical</i> element</li>
+                <li>The <a rel="mw:WikiLink" href="./Mercury_(planet)" title="Mercury (planet)">Mercury</a> planet
+                    <ul><li><a rel="mw:WikiLink" href="./Mercury_(mythology)" title="Mercury (mythology)">Mercury</a> – a Roman god</li></ul>
+                </li>
+                <li>A line without a link</li>
+            </ul>"#,
+        );
+        let entries = document.disambiguation_entries();
+
+        let entries: Vec<(Option<&str>, &str, &str)> = entries
+            .iter()
+            .map(|entry| {
+                let page = match entry.link {
+                    Link::Internal(ref data) => Some(data.page.as_str()),
+                    _ => None,
+                };
+                (page, entry.text.as_str(), entry.description.as_str())
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (
+                    Some("Mercury_(element)"),
+                    "Mercury (element)",
+                    "a chemical element"
+                ),
+                (Some("Mercury_(planet)"), "Mercury", "The planet"),
+                (Some("Mercury_(mythology)"), "Mercury", "a Roman god"),
+            ]
+        );
+    }
+}
@@ -8099,3 +8099,86 @@ parse_languages!(
 }
 "#
 );
+
+/// Returns the language closest to the misspelled code or name, compared to the codes, English
+/// names and local names of all languages. `None` when no language is close enough
+pub fn suggest_language(input: &str) -> Option<Language> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return None;
+    }
+    // allow about one typo per three characters
+    let max_distance = (input.chars().count() / 3).max(1);
+
+    LANGUAGES
+        .iter()
+        .filter_map(|language| {
+            [language.code(), language.name(), language.local_name()]
+                .iter()
+                .map(|name| edit_distance(&input, &name.to_lowercase()))
+                .min()
+                .map(|distance| (distance, *language))
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, language)| language)
+}
+
+/// The number of insertions, deletions, substitutions and transpositions of adjacent characters
+/// needed to turn one string into the other
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // distances[i][j] is the distance between the first i characters of a and j characters of b
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=b.len() {
+        distances[0][j] = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{edit_distance, suggest_language, Language};
+
+    #[test]
+    fn test_suggest_language() {
+        assert_eq!(edit_distance("germna", "german"), 1);
+        assert_eq!(edit_distance("", "de"), 2);
+
+        assert_eq!(suggest_language("germna"), Some(Language::German));
+        assert_eq!(suggest_language("Englsh"), Some(Language::English));
+        assert_eq!(suggest_language("deutsh"), Some(Language::German));
+        assert_eq!(suggest_language("qwertyuiop"), None);
+
+        let error = Language::from_str("germna").unwrap_err();
+        assert!(error.to_string().contains("did you mean 'de' (German)?"));
+    }
+
+    #[test]
+    fn test_is_right_to_left() {
+        assert!(Language::Hebrew.is_right_to_left());
+        assert!(Language::Arabic.is_right_to_left());
+        assert!(!Language::English.is_right_to_left());
+        assert!(!Language::Unknown.is_right_to_left());
+    }
+}
@@ -1,10 +1,88 @@
+use std::sync::OnceLock;
+
+use rate_limit::{count_sent, current_priority, RateLimiter};
+use reqwest::{Client, RequestBuilder, Response};
 use url::Url;
 
 pub mod document;
 pub mod languages;
 pub mod page;
 pub mod parser;
+pub mod rate_limit;
 pub mod search;
 
 // TODO: Make Endpoint a real struct
 pub type Endpoint = Url;
+
+/// The user agent of the default client, identifying the requests as the Wikimedia user-agent
+/// policy asks for
+pub const USER_AGENT: &str = concat!(
+    "wiki-api/",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/builditluc/wiki-tui)"
+);
+
+static CLIENT: OnceLock<Client> = OnceLock::new();
+static QUERY_PARAMS: OnceLock<Vec<(String, String)>> = OnceLock::new();
+static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+
+/// Sets the client sending all requests, for example a client using a proxy. The client can only
+/// be set before the first request, returns whether it was set
+pub fn set_client(client: Client) -> bool {
+    CLIENT.set(client).is_ok()
+}
+
+/// Sets the query parameters added to every API request, like `origin=*` for wikis that need it.
+/// The parameters can only be set before the first request, returns whether they were set
+pub fn set_query_params(params: Vec<(String, String)>) -> bool {
+    QUERY_PARAMS.set(params).is_ok()
+}
+
+/// Limits all requests to `rate` requests per second with bursts of up to `burst` requests. The
+/// limit can only be set before the first request, returns whether it was set. Without a limit,
+/// the requests are sent right away
+pub fn set_rate_limit(rate: f64, burst: u32) -> bool {
+    LIMITER.set(RateLimiter::new(rate, burst)).is_ok()
+}
+
+/// Returns the client sending the requests, a default client unless another one was set
+pub(crate) fn client() -> Client {
+    CLIENT
+        .get_or_init(|| {
+            Client::builder()
+                .user_agent(USER_AGENT)
+                .build()
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+/// Starts a request to the API of the endpoint with the query parameters that were set
+pub(crate) fn api_request(endpoint: Endpoint) -> RequestBuilder {
+    let request = client().get(endpoint);
+    match QUERY_PARAMS.get() {
+        Some(params) => request.query(params),
+        None => request,
+    }
+}
+
+/// Sends a request through the rate limiter, at the priority of the current task, and counts it
+/// for the [`rate_limit::request_stats`]
+pub(crate) trait SendLimited {
+    async fn send_limited(self) -> reqwest::Result<Response>;
+}
+
+impl SendLimited for RequestBuilder {
+    async fn send_limited(self) -> reqwest::Result<Response> {
+        let is_throttled = match LIMITER.get() {
+            Some(limiter) => limiter.acquire(current_priority()).await,
+            None => false,
+        };
+        let result = self.send().await;
+        let is_failed = !result
+            .as_ref()
+            .is_ok_and(|response| response.status().is_success());
+        count_sent(is_throttled, is_failed);
+        result
+    }
+}
@@ -1,23 +1,31 @@
 use crate::{
+    api_request, client,
     document::{Document, HeaderKind},
     parser::{Parser, WikipediaParser},
-    Endpoint,
+    rate_limit::{with_priority, Priority},
+    search::Namespace,
+    Endpoint, SendLimited,
 };
 use anyhow::{anyhow, Context, Result};
-use reqwest::{Client, Response};
+use reqwest::{header::RETRY_AFTER, Response};
 use scraper::Html;
 use serde::{de, Deserialize, Deserializer};
 use std::fmt::Display;
 use std::str::FromStr;
+use std::time::Duration;
 use tracing::{debug, warn};
 use url::Url;
 
 use super::languages::Language;
 
 pub mod link_data {
+    use std::str::FromStr;
+
     use crate::{languages::Language, search::Namespace, Endpoint};
     use url::Url;
 
+    use super::{article_title, ArticleUrl};
+
     #[derive(Debug, Clone, PartialEq, Eq)]
     pub struct InternalData {
         pub namespace: Namespace,
@@ -46,6 +54,28 @@ pub mod link_data {
         pub title: String,
     }
 
+    impl MediaData {
+        /// Returns the title of the file page, like `File:Rust.ogg`. Media links name the file
+        /// without a namespace or with the `Media:` namespace
+        pub fn file_title(&self) -> String {
+            let title = match self.title.is_empty() {
+                true => self
+                    .url
+                    .path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    .and_then(|name| urlencoding::decode(name).ok())
+                    .map(|name| name.replace('_', " "))
+                    .unwrap_or_default(),
+                false => self.title.clone(),
+            };
+            let name = title
+                .strip_prefix("Media:")
+                .or_else(|| title.strip_prefix("File:"))
+                .unwrap_or(&title);
+            format!("File:{}", name)
+        }
+    }
+
     #[derive(Debug, Clone, PartialEq, Eq)]
     pub struct ExternalData {
         pub url: Url,
@@ -53,6 +83,127 @@ pub mod link_data {
 
     #[derive(Debug, Clone, PartialEq, Eq)]
     pub struct ExternalToInteralData {}
+
+    /// The interwiki prefixes of the Wikimedia sister projects and their names
+    const SISTER_PROJECTS: [(&str, &str); 20] = [
+        ("wikt", "Wiktionary"),
+        ("wiktionary", "Wiktionary"),
+        ("commons", "Wikimedia Commons"),
+        ("c", "Wikimedia Commons"),
+        ("q", "Wikiquote"),
+        ("wikiquote", "Wikiquote"),
+        ("s", "Wikisource"),
+        ("wikisource", "Wikisource"),
+        ("b", "Wikibooks"),
+        ("wikibooks", "Wikibooks"),
+        ("n", "Wikinews"),
+        ("wikinews", "Wikinews"),
+        ("v", "Wikiversity"),
+        ("wikiversity", "Wikiversity"),
+        ("voy", "Wikivoyage"),
+        ("wikivoyage", "Wikivoyage"),
+        ("d", "Wikidata"),
+        ("wikidata", "Wikidata"),
+        ("species", "Wikispecies"),
+        ("m", "Meta-Wiki"),
+    ];
+
+    /// What kind of wiki the prefix of an interwiki link names
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum InterwikiKind {
+        /// The same project in another language, like `fr:` on Wikipedia
+        Language(Language),
+        /// One of the Wikimedia sister projects, like `wikt:` for Wiktionary, with its name
+        SisterProject(&'static str),
+        /// A prefix that isn't known, the link is opened as an external link
+        Unknown,
+    }
+
+    impl InterwikiKind {
+        /// Classifies the prefix of an interwiki link, ignoring its case
+        pub fn classify(prefix: &str) -> InterwikiKind {
+            let prefix = prefix.trim().to_lowercase();
+            if let Some((_, name)) = SISTER_PROJECTS.iter().find(|(other, _)| *other == prefix) {
+                return InterwikiKind::SisterProject(name);
+            }
+            match Language::from_str(&prefix) {
+                Ok(language) => InterwikiKind::Language(language),
+                Err(_) => InterwikiKind::Unknown,
+            }
+        }
+    }
+
+    /// Link to a page on another wiki, written with an interwiki prefix like `fr:Paris` or
+    /// `wikt:serendipity`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct InterwikiData {
+        /// The prefix naming the other wiki, like `fr` or `wikt`. Empty when the link has none
+        pub prefix: String,
+        /// The title of the page on the other wiki, without the prefix
+        pub title: String,
+        /// The resolved url of the page on the other wiki
+        pub url: Url,
+        pub kind: InterwikiKind,
+    }
+
+    impl InterwikiData {
+        /// Splits the prefix off the target of the link, like `:fr:Paris`, and classifies it.
+        /// Links without a known prefix to another language of the site of the endpoint, like
+        /// `fr.wikipedia.org` from `en.wikipedia.org`, are interlanguage links as well
+        pub fn new(target: &str, url: Url, endpoint: &Endpoint) -> InterwikiData {
+            let target = target.trim_start_matches(':');
+            let (prefix, title) = target.split_once(':').unwrap_or(("", target));
+            let kind = InterwikiKind::classify(prefix);
+            if kind != InterwikiKind::Unknown {
+                return InterwikiData {
+                    prefix: prefix.to_string(),
+                    title: title.to_string(),
+                    url,
+                    kind,
+                };
+            }
+
+            let split = |url: &Url| {
+                url.host_str()
+                    .and_then(|host| host.split_once('.'))
+                    .map(|(subdomain, site)| (subdomain.to_string(), site.to_string()))
+            };
+            match (split(&url), split(endpoint)) {
+                (Some((subdomain, site)), Some((_, endpoint_site))) if site == endpoint_site => {
+                    InterwikiData {
+                        kind: InterwikiKind::classify(&subdomain),
+                        prefix: subdomain,
+                        title: target.to_string(),
+                        url,
+                    }
+                }
+                _ => InterwikiData {
+                    prefix: prefix.to_string(),
+                    title: title.to_string(),
+                    url,
+                    kind,
+                },
+            }
+        }
+
+        /// Returns the article of an interlanguage link. The other languages of the site of the
+        /// endpoint use the same api path, only the host differs
+        pub fn language_article(&self, endpoint: &Endpoint) -> Option<ArticleUrl> {
+            let language = match self.kind {
+                InterwikiKind::Language(language) => language,
+                _ => return None,
+            };
+            let (title, anchor) = article_title(&self.url).ok()?;
+            let mut endpoint = endpoint.clone();
+            endpoint.set_host(self.url.host_str()).ok()?;
+            Some(ArticleUrl {
+                title,
+                language,
+                endpoint,
+                anchor,
+            })
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -71,9 +222,19 @@ pub enum Link {
     External(link_data::ExternalData),
     /// External link to an interal page in the same wiki
     ExternalToInternal(link_data::ExternalToInteralData),
+    /// Link to a page on another wiki, like another language or a sister project
+    Interwiki(link_data::InterwikiData),
 }
 
+/// The anchors of the references on a page start with this prefix, like `cite_note-3`
+pub const REFERENCE_ANCHOR_PREFIX: &str = "cite_note";
+
 impl Link {
+    /// Whether the link is a reflink pointing to a reference on the same page
+    pub fn is_reference(&self) -> bool {
+        matches!(self, Link::Anchor(data) if data.anchor.starts_with(REFERENCE_ANCHOR_PREFIX))
+    }
+
     pub fn title(&self) -> Option<&str> {
         match self {
             Link::Anchor(link_data) => Some(&link_data.title),
@@ -82,6 +243,7 @@ impl Link {
             &Link::ExternalToInternal(_) => None,
             Link::MediaLink(link_data) => Some(&link_data.title),
             Link::Internal(link_data) => Some(&link_data.title),
+            Link::Interwiki(link_data) => Some(&link_data.title),
         }
     }
 }
@@ -132,15 +294,161 @@ pub struct Section {
     pub anchor: String,
 }
 
+/// A section most articles end with, which can be found by its localized title
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionKind {
+    /// The footnotes and cited sources of the article
+    References,
+    /// The links to other websites
+    ExternalLinks,
+}
+
+impl SectionKind {
+    /// The usual titles of the section on the Wikipedia of the language, in the order they're
+    /// preferred. Empty for languages without known titles
+    pub fn localized_titles(&self, language: &Language) -> &'static [&'static str] {
+        use SectionKind::*;
+        match (self, language.code()) {
+            (References, "en") => &["References", "Notes", "Citations", "Footnotes", "Sources"],
+            (ExternalLinks, "en") => &["External links"],
+            (References, "de") => &["Einzelnachweise", "Anmerkungen", "Belege", "Quellen"],
+            (ExternalLinks, "de") => &["Weblinks"],
+            (References, "fr") => &["Notes et références", "Références", "Notes"],
+            (ExternalLinks, "fr") => &["Liens externes"],
+            (References, "es") => &["Referencias", "Notas"],
+            (ExternalLinks, "es") => &["Enlaces externos"],
+            (References, "it") => &["Note", "Riferimenti"],
+            (ExternalLinks, "it") => &["Collegamenti esterni"],
+            (References, "pt") => &["Referências", "Notas"],
+            (ExternalLinks, "pt") => &["Ligações externas", "Links externos"],
+            (References, "nl") => &["Referenties", "Bronnen", "Noten"],
+            (ExternalLinks, "nl") => &["Externe links", "Externe link"],
+            (References, "pl") => &["Przypisy", "Bibliografia"],
+            (ExternalLinks, "pl") => &["Linki zewnętrzne"],
+            (References, "sv") => &["Referenser", "Noter", "Källor"],
+            (ExternalLinks, "sv") => &["Externa länkar"],
+            (References, "ru") => &["Примечания", "Литература"],
+            (ExternalLinks, "ru") => &["Ссылки"],
+            (References, "uk") => &["Примітки", "Джерела"],
+            (ExternalLinks, "uk") => &["Посилання"],
+            (References, "ja") => &["脚注", "出典", "参考文献"],
+            (ExternalLinks, "ja") => &["外部リンク"],
+            (References, "zh") => &[
+                "参考文献",
+                "參考文獻",
+                "参考资料",
+                "參考資料",
+                "注释",
+                "註釋",
+            ],
+            (ExternalLinks, "zh") => &["外部链接", "外部連結"],
+            _ => &[],
+        }
+    }
+
+    /// The titles to look for on a page of the language. The English titles are tried last, since
+    /// smaller wikis often keep the titles of translated articles
+    pub fn titles(&self, language: &Language) -> Vec<&'static str> {
+        let mut titles = self.localized_titles(language).to_vec();
+        for title in self.localized_titles(&Language::English) {
+            if !titles.contains(title) {
+                titles.push(title);
+            }
+        }
+        titles
+    }
+}
+
+impl Display for SectionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SectionKind::References => write!(f, "references"),
+            SectionKind::ExternalLinks => write!(f, "external links"),
+        }
+    }
+}
+
+/// Finds the first section with one of the titles, trying the titles in order. Titles are
+/// compared ignoring case and surrounding whitespace
+pub fn find_section<'a>(sections: &'a [Section], titles: &[&str]) -> Option<&'a Section> {
+    titles.iter().find_map(|title| {
+        let title = title.trim().to_lowercase();
+        sections
+            .iter()
+            .find(|section| section.text.trim().to_lowercase() == title)
+    })
+}
+
+/// What kind of page a page is. Different kinds of pages can be displayed differently
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageKind {
+    /// A regular article
+    #[default]
+    Article,
+    /// A page listing the different meanings of a term
+    Disambiguation,
+    /// A page in the category namespace
+    Category,
+    /// A talk page of any namespace
+    Talk,
+    /// A special page generated by the software
+    Special,
+}
+
+impl PageKind {
+    /// Classifies a page by the namespace in its title, using the localized names and aliases of
+    /// the namespaces of its site. Without the namespaces of the site, only the canonical english
+    /// names are understood. Whether the page is a disambiguation page can't be determined by its
+    /// title and needs to be passed separately
+    pub fn classify(
+        namespaces: &[SiteNamespace],
+        title: &str,
+        is_disambiguation: bool,
+    ) -> PageKind {
+        const SPECIAL_NAMESPACE: i64 = -1;
+        const CATEGORY_NAMESPACE: i64 = 14;
+
+        let namespace = title.split_once(':').and_then(|(prefix, _)| {
+            if namespaces.is_empty() {
+                let prefix = prefix.trim().replace(' ', "_");
+                return match prefix.eq_ignore_ascii_case("special") {
+                    true => Some(SPECIAL_NAMESPACE),
+                    false => Namespace::from_string(&prefix).map(|namespace| namespace as i64),
+                };
+            }
+            namespaces
+                .iter()
+                .find(|namespace| namespace.has_prefix(prefix))
+                .map(|namespace| namespace.id)
+        });
+
+        match namespace {
+            Some(SPECIAL_NAMESPACE) => PageKind::Special,
+            Some(CATEGORY_NAMESPACE) => PageKind::Category,
+            Some(id) if id % 2 == 1 => PageKind::Talk,
+            _ if is_disambiguation => PageKind::Disambiguation,
+            _ => PageKind::Article,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq)]
 pub struct Page {
     pub title: String,
     pub pageid: usize,
+    pub kind: PageKind,
     pub content: Document,
     pub language: Language,
+    /// API endpoint of the MediaWiki site the page was fetched from
+    pub endpoint: Endpoint,
     pub language_links: Option<Vec<LanguageLink>>,
     pub sections: Option<Vec<Section>>,
     pub revision_id: Option<usize>,
+    /// The title of the redirect that led to the page
+    pub redirected_from: Option<String>,
+    /// The short description of the page, like `City in and capital of France`
+    pub description: Option<String>,
 }
 
 impl Page {
@@ -151,21 +459,23 @@ impl Page {
         }
 
         let content = std::fs::read_to_string(path).ok()?;
-        let nodes = WikipediaParser::parse_document(
-            &content,
-            url::Url::parse("https://en.wikipedia.org/w/api.php").ok()?,
-            Language::default(),
-        )
-        .nodes();
+        let endpoint = url::Url::parse("https://en.wikipedia.org/w/api.php").ok()?;
+        let nodes =
+            WikipediaParser::parse_document(&content, endpoint.clone(), Language::default())
+                .nodes();
 
         Some(Page {
             title: "DEBUG: FILE".to_string(),
             pageid: 0,
+            kind: PageKind::Article,
             content: Document { nodes },
             language: Language::default(),
+            endpoint,
             language_links: None,
             sections: None,
             revision_id: None,
+            redirected_from: None,
+            description: None,
         })
     }
 
@@ -186,6 +496,23 @@ impl Page {
         }
         None
     }
+
+    /// Returns the section of the kind, looked up by the titles of the section in the language
+    /// of the page
+    pub fn section_of_kind(&self, kind: SectionKind) -> Option<&Section> {
+        find_section(self.sections()?, &kind.titles(&self.language))
+    }
+
+    /// Whether the page lists the different meanings of a term, as marked by its
+    /// `disambiguation` page property
+    pub fn is_disambiguation(&self) -> bool {
+        self.kind == PageKind::Disambiguation
+    }
+
+    /// Returns the url of the page on its Wikipedia, optionally pointing to a section
+    pub fn url(&self, anchor: Option<&str>) -> Url {
+        article_url(&self.endpoint, &self.title, anchor)
+    }
 }
 
 impl std::fmt::Debug for Page {
@@ -193,11 +520,14 @@ impl std::fmt::Debug for Page {
         f.debug_struct("Page")
             .field("title", &self.title)
             .field("pageid", &self.pageid)
+            .field("kind", &self.kind)
             .field("content", &self.content)
             .field("language", &self.language)
             .field("language_links", &self.language_links.is_some())
             .field("sections", &self.sections.is_some())
             .field("revision_id", &self.revision_id)
+            .field("redirected_from", &self.redirected_from)
+            .field("description", &self.description)
             .finish()
     }
 }
@@ -300,8 +630,10 @@ pub struct PageBuilder<I, P, E, L> {
     endpoint: E,
     language: L,
     revision: Option<usize>,
+    oldid: Option<usize>,
     redirects: Option<bool>,
     properties: Option<Vec<Property>>,
+    namespaces: Vec<SiteNamespace>,
 }
 
 pub type PageRequest = PageBuilder<NoPageID, WithPage, WithEndpoint, WithLanguage>;
@@ -315,9 +647,11 @@ impl<E, L> PageBuilder<NoPageID, NoPage, E, L> {
             page: self.page,
             endpoint: self.endpoint,
             revision: self.revision,
+            oldid: self.oldid,
             redirects: self.redirects,
             properties: self.properties,
             language: self.language,
+            namespaces: self.namespaces,
         }
     }
 
@@ -328,9 +662,11 @@ impl<E, L> PageBuilder<NoPageID, NoPage, E, L> {
             page: WithPage(page.into()),
             endpoint: self.endpoint,
             revision: self.revision,
+            oldid: self.oldid,
             redirects: self.redirects,
             properties: self.properties,
             language: self.language,
+            namespaces: self.namespaces,
         }
     }
 }
@@ -342,9 +678,11 @@ impl<I, P, L> PageBuilder<I, P, NoEndpoint, L> {
             page: self.page,
             endpoint: WithEndpoint(url.into()),
             revision: self.revision,
+            oldid: self.oldid,
             redirects: self.redirects,
             properties: self.properties,
             language: self.language,
+            namespaces: self.namespaces,
         }
     }
 
@@ -354,9 +692,11 @@ impl<I, P, L> PageBuilder<I, P, NoEndpoint, L> {
             page: self.page,
             endpoint: WithEndpoint(endpoint),
             revision: self.revision,
+            oldid: self.oldid,
             redirects: self.redirects,
             properties: self.properties,
             language: self.language,
+            namespaces: self.namespaces,
         }
     }
 }
@@ -369,8 +709,10 @@ impl<I, P, E> PageBuilder<I, P, E, NoLanguage> {
             endpoint: self.endpoint,
             language: WithLanguage(language),
             revision: self.revision,
+            oldid: self.oldid,
             redirects: self.redirects,
             properties: self.properties,
+            namespaces: self.namespaces,
         }
     }
 }
@@ -382,6 +724,12 @@ impl<I, P, U, L> PageBuilder<I, P, U, L> {
         self
     }
 
+    /// Parse the old revision with this ID instead of the current revision of the page
+    pub fn oldid(mut self, oldid: usize) -> Self {
+        self.oldid = Some(oldid);
+        self
+    }
+
     /// If page or pageid is set to a redirect, resolve it
     pub fn redirects(mut self, redirects: bool) -> Self {
         self.redirects = Some(redirects);
@@ -393,13 +741,27 @@ impl<I, P, U, L> PageBuilder<I, P, U, L> {
         self.properties = Some(properties);
         self
     }
+
+    /// The namespaces of the site, to classify the page by the localized namespace of its title
+    pub fn namespaces(mut self, namespaces: Vec<SiteNamespace>) -> Self {
+        self.namespaces = namespaces;
+        self
+    }
 }
 
 impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
-    async fn fetch_with_params(self, mut params: Vec<(&str, String)>) -> Result<Page> {
+    async fn fetch_with_params(self, params: Vec<(&str, String)>) -> Result<Page> {
+        let res_json = self.fetch_response_with_params(params).await?;
+        self.serialize_result(res_json)
+            .context("failed serializing the returned response")
+    }
+
+    async fn fetch_response_with_params(
+        &self,
+        mut params: Vec<(&str, String)>,
+    ) -> Result<serde_json::Value> {
         async fn action_parse(params: Vec<(&str, String)>, endpoint: Url) -> Result<Response> {
-            Client::new()
-                .get(endpoint)
+            api_request(endpoint)
                 .query(&[
                     ("action", "parse"),
                     ("format", "json"),
@@ -407,20 +769,21 @@ impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
                     ("parsoid", "true"),
                 ])
                 .query(&params)
-                .send()
+                .send_limited()
                 .await
                 .map(|response| {
                     debug!("response url: '{}'", response.url().as_str());
                     response
                 })
-                .context("failed sending the request")
+                .map_err(request_failed)
         }
 
         if let Some(revision) = self.revision {
             params.push(("revid", revision.to_string()));
         }
 
-        if let Some(redirects) = self.redirects {
+        // an old revision is never a redirect that could be resolved
+        if let Some(redirects) = self.redirects.filter(|_| self.oldid.is_none()) {
             params.push(("redirects", redirects.to_string()));
         }
 
@@ -434,9 +797,8 @@ impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
         }
 
         let response = action_parse(params, self.endpoint.0.clone())
-            .await?
-            .error_for_status()
-            .context("the server returned an error")?;
+            .await
+            .and_then(check_status)?;
 
         let res_json: serde_json::Value = serde_json::from_str(
             &response
@@ -446,8 +808,21 @@ impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
         )
         .context("failed interpreting the response as json")?;
 
-        self.serialize_result(res_json)
-            .context("failed serializing the returned response")
+        if let Some(error) = res_json.get("error") {
+            let code = error
+                .get("code")
+                .and_then(|x| x.as_str())
+                .unwrap_or_default();
+            let info = error
+                .get("info")
+                .and_then(|x| x.as_str())
+                .unwrap_or_default();
+            if matches!(code, "missingtitle" | "invalidtitle" | "nosuchpageid") {
+                return Err(PageNotFound(info.to_string()).into());
+            }
+            return Err(anyhow!("the api returned the error '{code}': {info}"));
+        }
+        Ok(res_json)
     }
 
     fn serialize_result(self, res_json: serde_json::Value) -> Result<Page> {
@@ -465,6 +840,24 @@ impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
             .map(|x| x as usize)
             .ok_or_else(|| anyhow!("missing the pageid"))?;
 
+        // disambiguation pages have the 'disambiguation' page property set, this is only available
+        // when the properties were requested
+        let is_disambiguation = res_json
+            .get("parse")
+            .and_then(|x| x.get("properties"))
+            .and_then(|x| x.get("disambiguation"))
+            .is_some();
+        let kind = PageKind::classify(&self.namespaces, &title, is_disambiguation);
+
+        // the short description is a page property as well, set by a template or Wikidata
+        let description = res_json
+            .get("parse")
+            .and_then(|x| x.get("properties"))
+            .and_then(|x| x.get("wikibase-shortdesc"))
+            .and_then(|x| x.as_str())
+            .map(|x| x.trim().to_string())
+            .filter(|x| !x.is_empty());
+
         let endpoint = self.endpoint.0;
         let language = self.language.0;
         let content = res_json
@@ -527,19 +920,6 @@ impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
                         })
                     })
                     .collect::<Vec<Section>>()
-            })
-            .map(|mut x| {
-                x.insert(
-                    0,
-                    Section {
-                        index: 0,
-                        header_kind: HeaderKind::Main,
-                        text: "(Top)".to_string(),
-                        number: "".to_string(),
-                        anchor: "Content_Top".to_string(),
-                    },
-                );
-                x
             });
 
         let revision_id = res_json
@@ -548,14 +928,29 @@ impl<I, P> PageBuilder<I, P, WithEndpoint, WithLanguage> {
             .and_then(|x| x.as_u64())
             .map(|x| x as usize);
 
+        // resolved redirects are listed as `{"from": "NYC", "to": "New York City"}`, the first
+        // one is the title that was requested
+        let redirected_from = res_json
+            .get("parse")
+            .and_then(|x| x.get("redirects"))
+            .and_then(|x| x.as_array())
+            .and_then(|x| x.first())
+            .and_then(|x| x.get("from"))
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string());
+
         Ok(Page {
             title,
             pageid,
+            kind,
             content,
             language,
+            endpoint,
             language_links,
             sections,
             revision_id,
+            redirected_from,
+            description,
         })
     }
 }
@@ -569,7 +964,1365 @@ impl PageBuilder<WithPageID, NoPage, WithEndpoint, WithLanguage> {
 
 impl PageBuilder<NoPageID, WithPage, WithEndpoint, WithLanguage> {
     pub async fn fetch(self) -> Result<Page> {
-        let param = vec![("page", self.page.0.to_string())];
-        self.fetch_with_params(param).await
+        let response = self.fetch_response().await?;
+        self.from_response(response)
+    }
+
+    /// Fetches the response of the api without parsing the page, so it can be stored and parsed
+    /// later with [`PageBuilder::from_response`]
+    pub async fn fetch_response(&self) -> Result<serde_json::Value> {
+        // the revision already identifies the page, the api refuses getting both
+        let param = match self.oldid {
+            Some(oldid) => vec![("oldid", oldid.to_string())],
+            None => vec![("page", self.page.0.to_string())],
+        };
+        self.fetch_response_with_params(param).await
+    }
+
+    /// Parses the page from a response fetched before with [`PageBuilder::fetch_response`]
+    pub fn from_response(self, response: serde_json::Value) -> Result<Page> {
+        self.serialize_result(response)
+            .context("failed serializing the returned response")
+    }
+}
+
+/// The requested page doesn't exist, contains the explanation of the api
+///
+/// Fetching a page fails with this error, it can be detected by downcasting the error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageNotFound(pub String);
+
+impl Display for PageNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the page doesn't exist: {}", self.0)
+    }
+}
+
+impl std::error::Error for PageNotFound {}
+
+/// The request for the page couldn't be sent or wasn't answered, for example without a connection
+///
+/// Fetching a page fails with this error, it can be detected by downcasting the error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionFailed(pub String);
+
+impl Display for ConnectionFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed sending the request: {}", self.0)
+    }
+}
+
+impl std::error::Error for ConnectionFailed {}
+
+/// The request for the page wasn't answered in time
+///
+/// Fetching a page fails with this error, it can be detected by downcasting the error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimedOut(pub String);
+
+impl Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the request timed out: {}", self.0)
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// The server answered with an error status, like `429 Too Many Requests` or
+/// `503 Service Unavailable`. Contains how long the server asked to wait before retrying, when it
+/// sent a `Retry-After` header
+///
+/// Requests fail with this error, it can be detected by downcasting the error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerError {
+    pub status: u16,
+    pub retry_after: Option<Duration>,
+}
+
+impl ServerError {
+    /// Whether the request can succeed when it's sent again later, because the server was only
+    /// overloaded or limiting the requests
+    pub fn is_transient(&self) -> bool {
+        matches!(self.status, 408 | 429 | 500 | 502 | 503 | 504)
+    }
+}
+
+impl Display for ServerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the server returned the status {}", self.status)
+    }
+}
+
+impl std::error::Error for ServerError {}
+
+/// Converts the error of sending a request into a [`ConnectionFailed`] or [`TimedOut`] error
+pub(crate) fn request_failed(error: reqwest::Error) -> anyhow::Error {
+    match error.is_timeout() {
+        true => TimedOut(error.to_string()).into(),
+        false => ConnectionFailed(error.to_string()).into(),
+    }
+}
+
+/// Fails with a [`ServerError`] when the server answered with an error status
+pub(crate) fn check_status(response: Response) -> Result<Response> {
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return Ok(response);
+    }
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after);
+    Err(ServerError {
+        status: status.as_u16(),
+        retry_after,
+    }
+    .into())
+}
+
+/// Parses the `Retry-After` header. Only the delay in seconds is understood, not a date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+/// An article addressed by the url of a Wikipedia page, like
+/// `https://de.wikipedia.org/wiki/Berlin#Geschichte`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArticleUrl {
+    pub title: String,
+    pub language: Language,
+    /// API endpoint of the Wikipedia the article belongs to
+    pub endpoint: Endpoint,
+    /// Id of the header the url points to
+    pub anchor: Option<String>,
+}
+
+impl ArticleUrl {
+    /// Parses the url of a Wikipedia article. Both the `/wiki/<title>` and the
+    /// `/w/index.php?title=<title>` form are supported, as well as the mobile site
+    pub fn parse(url: &str) -> Result<ArticleUrl> {
+        let url = Url::parse(url).context("invalid url")?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(anyhow!("unsupported scheme '{}'", url.scheme()));
+        }
+
+        // the host looks like [lang].wikipedia.org or [lang].m.wikipedia.org for the mobile site
+        let host = url.host_str().ok_or_else(|| anyhow!("missing the host"))?;
+        let code = host
+            .strip_suffix(".wikipedia.org")
+            .map(|subdomain| subdomain.trim_end_matches(".m"))
+            .ok_or_else(|| anyhow!("'{host}' is not a Wikipedia site"))?;
+        let language = Language::from_str(code)
+            .map_err(|_| anyhow!("unknown language '{code}' of the host '{host}'"))?;
+
+        let (title, anchor) = article_title(&url)?;
+        let endpoint = Url::parse(&format!("https://{code}.wikipedia.org/w/api.php"))
+            .context("invalid endpoint")?;
+
+        Ok(ArticleUrl {
+            title,
+            language,
+            endpoint,
+            anchor,
+        })
+    }
+}
+
+/// Returns the title of the article and the id of the header the url of an article of a MediaWiki
+/// site points to. Both the `/wiki/<title>` and the `/w/index.php?title=<title>` form are
+/// supported
+pub fn article_title(url: &Url) -> Result<(String, Option<String>)> {
+    let title = match url.path().strip_prefix("/wiki/") {
+        Some(title) => urlencoding::decode(title)
+            .context("invalid title")?
+            .into_owned(),
+        None => url
+            .query_pairs()
+            .find(|(name, _)| name == "title")
+            .map(|(_, title)| title.into_owned())
+            .ok_or_else(|| anyhow!("the url doesn't name an article"))?,
+    };
+    let title = title.replace('_', " ");
+    if title.trim().is_empty() {
+        return Err(anyhow!("the url doesn't name an article"));
+    }
+
+    let anchor = match url.fragment() {
+        Some(fragment) if !fragment.is_empty() => Some(
+            urlencoding::decode(fragment)
+                .context("invalid section")?
+                .into_owned(),
+        ),
+        _ => None,
+    };
+    Ok((title, anchor))
+}
+
+/// Returns the url of an article of the Wikipedia the endpoint belongs to, like
+/// `https://en.wikipedia.org/wiki/Rust_(fungus)#Types`
+pub fn article_url(endpoint: &Endpoint, title: &str, anchor: Option<&str>) -> Url {
+    let mut url = endpoint.clone();
+    url.set_query(None);
+    url.set_path(&format!("/wiki/{}", encode_title(title)));
+    url.set_fragment(anchor.map(encode_title).as_deref());
+    url
+}
+
+/// Encodes a title for urls the way MediaWiki does. Spaces become underscores and everything
+/// except for some punctuation, that is left readable, is percent-encoded
+pub fn encode_title(title: &str) -> String {
+    const READABLE: [(&str, &str); 11] = [
+        ("%2F", "/"),
+        ("%3A", ":"),
+        ("%3B", ";"),
+        ("%40", "@"),
+        ("%24", "$"),
+        ("%21", "!"),
+        ("%2A", "*"),
+        ("%28", "("),
+        ("%29", ")"),
+        ("%2C", ","),
+        ("%7E", "~"),
+    ];
+
+    let mut encoded = urlencoding::encode(&title.replace(' ', "_")).into_owned();
+    for (escaped, c) in READABLE {
+        encoded = encoded.replace(escaped, c);
+    }
+    encoded
+}
+
+/// The protection of a page against an action, like editing or moving it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Protection {
+    /// The protected action, `edit` or `move` for example
+    pub kind: String,
+    /// The group of users allowed to perform the action, like `autoconfirmed` or `sysop`
+    pub level: String,
+    /// When the protection ends, `infinity` for unlimited protections
+    pub expiry: String,
+}
+
+/// Information about a page that's not part of its content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageInfo {
+    pub title: String,
+    /// Size of the wikitext in bytes
+    pub length: usize,
+    /// Timestamp of the last edit in ISO 8601
+    pub last_edited: Option<String>,
+    /// Name of the user that last edited the page
+    pub last_editor: Option<String>,
+    pub protection: Vec<Protection>,
+    /// Number of users watching the page, only exposed for pages with enough watchers
+    pub watchers: Option<usize>,
+    /// Titles of the categories of the page, without the hidden ones
+    pub categories: Vec<String>,
+}
+
+/// Fetches the information about a page, using the info, revisions and categories props of the
+/// query API
+pub async fn page_info(endpoint: Endpoint, title: &str) -> Result<PageInfo> {
+    let response = api_request(endpoint)
+        .query(&[
+            ("action", "query"),
+            ("format", "json"),
+            ("formatversion", "2"),
+            ("redirects", "1"),
+            ("titles", title),
+            ("prop", "info|revisions|categories"),
+            ("inprop", "protection|watchers"),
+            ("rvprop", "timestamp|user"),
+            ("cllimit", "max"),
+            ("clshow", "!hidden"),
+        ])
+        .send_limited()
+        .await
+        .map_err(request_failed)
+        .and_then(check_status)?;
+
+    parse_page_info(
+        &response
+            .text()
+            .await
+            .context("failed reading the response")?,
+    )
+}
+
+/// Parses the info of the first page in a query response with `formatversion=2`
+fn parse_page_info(response: &str) -> Result<PageInfo> {
+    let res_json: serde_json::Value =
+        serde_json::from_str(response).context("failed interpreting the response as json")?;
+
+    let page = res_json
+        .get("query")
+        .and_then(|x| x.get("pages"))
+        .and_then(|x| x.get(0))
+        .ok_or_else(|| anyhow!("missing the page in the response"))?;
+    let title = page
+        .get("title")
+        .and_then(|x| x.as_str())
+        .unwrap_or_default()
+        .to_string();
+    if page.get("missing").is_some() || page.get("invalid").is_some() {
+        return Err(PageNotFound(title).into());
+    }
+
+    let str_field = |value: &serde_json::Value, name: &str| {
+        value
+            .get(name)
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string())
+    };
+    let revision = page.get("revisions").and_then(|x| x.get(0));
+
+    let protection = page
+        .get("protection")
+        .and_then(|x| x.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|protection| {
+            Some(Protection {
+                kind: str_field(protection, "type")?,
+                level: str_field(protection, "level")?,
+                expiry: str_field(protection, "expiry").unwrap_or_default(),
+            })
+        })
+        .collect();
+    let categories = page
+        .get("categories")
+        .and_then(|x| x.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|category| str_field(category, "title"))
+        .collect();
+
+    Ok(PageInfo {
+        title,
+        length: page
+            .get("length")
+            .and_then(|x| x.as_u64())
+            .unwrap_or_default() as usize,
+        last_edited: revision.and_then(|x| str_field(x, "timestamp")),
+        last_editor: revision.and_then(|x| str_field(x, "user")),
+        protection,
+        watchers: page
+            .get("watchers")
+            .and_then(|x| x.as_u64())
+            .map(|x| x as usize),
+        categories,
+    })
+}
+
+/// A revision of a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    pub id: usize,
+    /// ID of the previous revision, `0` for the revision creating the page
+    pub parent_id: usize,
+    /// Timestamp of the edit in ISO 8601
+    pub timestamp: String,
+    /// Name of the user that made the edit, empty when it was hidden
+    pub user: String,
+    /// Size of the page after the edit in bytes
+    pub size: usize,
+    /// The edit summary, empty when it was hidden
+    pub comment: String,
+}
+
+/// A batch of the revisions of a page, the newest first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revisions {
+    pub revisions: Vec<Revision>,
+    /// Continues the listing with the next older revisions, `None` after the oldest revision
+    pub continue_from: Option<String>,
+}
+
+/// Fetches a batch of revisions of a page, newest first, using the revisions prop of the query
+/// API. The listing is continued with the `continue_from` of the previous batch
+pub async fn revisions(
+    endpoint: Endpoint,
+    title: &str,
+    limit: usize,
+    continue_from: Option<&str>,
+) -> Result<Revisions> {
+    let mut request = api_request(endpoint).query(&[
+        ("action", "query"),
+        ("format", "json"),
+        ("formatversion", "2"),
+        ("redirects", "1"),
+        ("titles", title),
+        ("prop", "revisions"),
+        ("rvprop", "ids|timestamp|user|size|comment"),
+        ("rvlimit", &limit.to_string()),
+    ]);
+    if let Some(continue_from) = continue_from {
+        request = request.query(&[("rvcontinue", continue_from)]);
+    }
+
+    let response = request
+        .send_limited()
+        .await
+        .map_err(request_failed)
+        .and_then(check_status)?;
+
+    parse_revisions(
+        &response
+            .text()
+            .await
+            .context("failed reading the response")?,
+    )
+}
+
+/// Parses the revisions of the first page in a query response with `formatversion=2`
+fn parse_revisions(response: &str) -> Result<Revisions> {
+    let res_json: serde_json::Value =
+        serde_json::from_str(response).context("failed interpreting the response as json")?;
+
+    let page = res_json
+        .get("query")
+        .and_then(|x| x.get("pages"))
+        .and_then(|x| x.get(0))
+        .ok_or_else(|| anyhow!("missing the page in the response"))?;
+    if page.get("missing").is_some() || page.get("invalid").is_some() {
+        let title = page
+            .get("title")
+            .and_then(|x| x.as_str())
+            .unwrap_or_default();
+        return Err(PageNotFound(title.to_string()).into());
+    }
+
+    let str_field = |value: &serde_json::Value, name: &str| {
+        value
+            .get(name)
+            .and_then(|x| x.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let usize_field = |value: &serde_json::Value, name: &str| {
+        value.get(name).and_then(|x| x.as_u64()).map(|x| x as usize)
+    };
+    let revisions = page
+        .get("revisions")
+        .and_then(|x| x.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|revision| {
+            Some(Revision {
+                id: usize_field(revision, "revid")?,
+                parent_id: usize_field(revision, "parentid").unwrap_or_default(),
+                timestamp: str_field(revision, "timestamp"),
+                user: str_field(revision, "user"),
+                size: usize_field(revision, "size").unwrap_or_default(),
+                comment: str_field(revision, "comment"),
+            })
+        })
+        .collect();
+
+    Ok(Revisions {
+        revisions,
+        continue_from: res_json
+            .get("continue")
+            .and_then(|x| x.get("rvcontinue"))
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string()),
+    })
+}
+
+/// A page linking to another page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backlink {
+    pub title: String,
+    /// Whether the page is a redirect to the linked page
+    pub is_redirect: bool,
+}
+
+/// A batch of the pages linking to a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backlinks {
+    pub links: Vec<Backlink>,
+    /// Continues the listing with the next pages, `None` after the last page
+    pub continue_from: Option<String>,
+}
+
+/// Fetches a batch of the pages linking to a page in the namespaces, all namespaces when none are
+/// given, using the backlinks list of the query API. The listing is continued with the
+/// `continue_from` of the previous batch
+pub async fn backlinks(
+    endpoint: Endpoint,
+    title: &str,
+    namespaces: &[Namespace],
+    limit: usize,
+    continue_from: Option<&str>,
+) -> Result<Backlinks> {
+    let mut request = api_request(endpoint).query(&[
+        ("action", "query"),
+        ("format", "json"),
+        ("formatversion", "2"),
+        ("list", "backlinks"),
+        ("bltitle", title),
+        ("bllimit", &limit.to_string()),
+    ]);
+    if !namespaces.is_empty() {
+        let namespaces: Vec<String> = namespaces
+            .iter()
+            .map(|namespace| (*namespace as usize).to_string())
+            .collect();
+        request = request.query(&[("blnamespace", namespaces.join("|"))]);
+    }
+    if let Some(continue_from) = continue_from {
+        request = request.query(&[("blcontinue", continue_from)]);
+    }
+
+    let response = request
+        .send_limited()
+        .await
+        .map_err(request_failed)
+        .and_then(check_status)?;
+
+    parse_backlinks(
+        &response
+            .text()
+            .await
+            .context("failed reading the response")?,
+    )
+}
+
+/// Parses the pages from a backlinks list response, which has the form
+/// `{"continue": {"blcontinue": ...}, "query": {"backlinks": [{"title": ..., "redirect": true}]}}`
+fn parse_backlinks(response: &str) -> Result<Backlinks> {
+    let res_json: serde_json::Value =
+        serde_json::from_str(response).context("failed interpreting the response as json")?;
+
+    let links = res_json
+        .get("query")
+        .and_then(|x| x.get("backlinks"))
+        .and_then(|x| x.as_array())
+        .ok_or_else(|| anyhow!("missing the backlinks in the response"))?
+        .iter()
+        .filter_map(|link| {
+            Some(Backlink {
+                title: link.get("title")?.as_str()?.to_string(),
+                is_redirect: link
+                    .get("redirect")
+                    .and_then(|x| x.as_bool())
+                    .unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    Ok(Backlinks {
+        links,
+        continue_from: res_json
+            .get("continue")
+            .and_then(|x| x.get("blcontinue"))
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string()),
+    })
+}
+
+/// A namespace of a site, with its localized name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiteNamespace {
+    /// The number of the namespace. Talk namespaces are odd and follow their subject namespace
+    pub id: i64,
+    /// The localized name, the prefix of the titles in the namespace. Empty for articles
+    pub name: String,
+    /// The english name of the namespace, which is understood by every site
+    pub canonical: Option<String>,
+    pub aliases: Vec<String>,
+}
+
+impl SiteNamespace {
+    /// Returns whether the title prefix, like `Talk` or `talk`, is a name of the namespace
+    fn has_prefix(&self, prefix: &str) -> bool {
+        let normalize = |name: &str| name.replace('_', " ").trim().to_lowercase();
+        let prefix = normalize(prefix);
+        std::iter::once(&self.name)
+            .chain(self.canonical.iter())
+            .chain(self.aliases.iter())
+            .any(|name| !name.is_empty() && normalize(name) == prefix)
+    }
+}
+
+/// Fetches the namespaces of the site, with their localized names and aliases, using the siteinfo
+/// meta of the query API
+pub async fn site_namespaces(endpoint: Endpoint) -> Result<Vec<SiteNamespace>> {
+    let response = api_request(endpoint)
+        .query(&[
+            ("action", "query"),
+            ("format", "json"),
+            ("formatversion", "2"),
+            ("meta", "siteinfo"),
+            ("siprop", "namespaces|namespacealiases"),
+        ])
+        .send_limited()
+        .await
+        .map_err(request_failed)
+        .and_then(check_status)?;
+
+    parse_site_namespaces(
+        &response
+            .text()
+            .await
+            .context("failed reading the response")?,
+    )
+}
+
+/// Parses the namespaces from a siteinfo response, which has the form
+/// `{"query": {"namespaces": {"1": {"id": 1, "name": ...}}, "namespacealiases": [...]}}`
+fn parse_site_namespaces(response: &str) -> Result<Vec<SiteNamespace>> {
+    let res_json: serde_json::Value =
+        serde_json::from_str(response).context("failed interpreting the response as json")?;
+
+    let query = res_json
+        .get("query")
+        .ok_or_else(|| anyhow!("missing the query in the response"))?;
+    let aliases: Vec<(i64, &str)> = query
+        .get("namespacealiases")
+        .and_then(|x| x.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|alias| Some((alias.get("id")?.as_i64()?, alias.get("alias")?.as_str()?)))
+        .collect();
+
+    let mut namespaces: Vec<SiteNamespace> = query
+        .get("namespaces")
+        .and_then(|x| x.as_object())
+        .ok_or_else(|| anyhow!("missing the namespaces in the response"))?
+        .values()
+        .filter_map(|namespace| {
+            let id = namespace.get("id")?.as_i64()?;
+            Some(SiteNamespace {
+                id,
+                name: namespace.get("name")?.as_str()?.to_string(),
+                canonical: namespace
+                    .get("canonical")
+                    .and_then(|x| x.as_str())
+                    .map(|x| x.to_string()),
+                aliases: aliases
+                    .iter()
+                    .filter(|(alias_id, _)| *alias_id == id)
+                    .map(|(_, alias)| alias.to_string())
+                    .collect(),
+            })
+        })
+        .collect();
+    namespaces.sort_by_key(|namespace| namespace.id);
+    Ok(namespaces)
+}
+
+/// Returns the title of the page associated with the page: the talk page of an article or another
+/// subject page, and the subject page of a talk page. Also returns whether the associated page is a
+/// talk page. Special pages don't have associated pages
+pub fn associated_title(namespaces: &[SiteNamespace], title: &str) -> Option<(String, bool)> {
+    let (namespace, name) = title
+        .split_once(':')
+        .and_then(|(prefix, name)| {
+            let namespace = namespaces
+                .iter()
+                .find(|namespace| namespace.has_prefix(prefix))?;
+            Some((namespace.id, name.trim()))
+        })
+        // titles of articles can contain colons as well
+        .unwrap_or((0, title));
+    if namespace < 0 {
+        return None;
+    }
+
+    let associated_id = namespace ^ 1;
+    let associated = namespaces
+        .iter()
+        .find(|namespace| namespace.id == associated_id)?;
+    let title = match associated.name.is_empty() {
+        true => name.to_string(),
+        false => format!("{}:{name}", associated.name),
+    };
+    Some((title, associated_id % 2 == 1))
+}
+
+/// Fetches the wikitext of the latest revision of a page, using the revisions prop of the query
+/// API
+pub async fn page_source(endpoint: Endpoint, title: &str) -> Result<String> {
+    let response = api_request(endpoint)
+        .query(&[
+            ("action", "query"),
+            ("format", "json"),
+            ("formatversion", "2"),
+            ("redirects", "1"),
+            ("titles", title),
+            ("prop", "revisions"),
+            ("rvprop", "content"),
+            ("rvslots", "main"),
+        ])
+        .send_limited()
+        .await
+        .map_err(request_failed)
+        .and_then(check_status)?;
+
+    parse_page_source(
+        &response
+            .text()
+            .await
+            .context("failed reading the response")?,
+    )
+}
+
+/// Parses the wikitext from a revisions response, which has the form
+/// `{"query": {"pages": [{"revisions": [{"slots": {"main": {"content": ...}}}]}]}}`
+fn parse_page_source(response: &str) -> Result<String> {
+    let res_json: serde_json::Value =
+        serde_json::from_str(response).context("failed interpreting the response as json")?;
+
+    let page = res_json
+        .get("query")
+        .and_then(|x| x.get("pages"))
+        .and_then(|x| x.get(0))
+        .ok_or_else(|| anyhow!("missing the page in the response"))?;
+    if page.get("missing").is_some() || page.get("invalid").is_some() {
+        let title = page
+            .get("title")
+            .and_then(|x| x.as_str())
+            .unwrap_or_default();
+        return Err(PageNotFound(title.to_string()).into());
+    }
+
+    page.get("revisions")
+        .and_then(|x| x.get(0))
+        .and_then(|x| x.get("slots"))
+        .and_then(|x| x.get("main"))
+        .and_then(|x| x.get("content"))
+        .and_then(|x| x.as_str())
+        .map(|x| x.to_string())
+        .ok_or_else(|| anyhow!("missing the source of the page"))
+}
+
+/// Fetches the title of a random article, using the random list of the query API
+pub async fn random_title(endpoint: Endpoint) -> Result<String> {
+    let response = api_request(endpoint)
+        .query(&[
+            ("action", "query"),
+            ("format", "json"),
+            ("list", "random"),
+            ("rnnamespace", "0"),
+            ("rnlimit", "1"),
+        ])
+        .send_limited()
+        .await
+        .map_err(request_failed)
+        .and_then(check_status)?;
+
+    parse_random_title(
+        &response
+            .text()
+            .await
+            .context("failed reading the response")?,
+    )
+}
+
+/// Fetches the title of today's featured article from the feed of the REST API. Only some
+/// languages feature an article every day
+pub async fn featured_title(endpoint: Endpoint) -> Result<String> {
+    let days = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .context("the system time is before the unix epoch")?
+        .as_secs()
+        / 86400;
+    let url = endpoint
+        .join(&format!("/api/rest_v1/feed/featured/{}", feed_date(days)))
+        .context("failed building the url of the feed")?;
+
+    let response = api_request(url)
+        .send_limited()
+        .await
+        .map_err(request_failed)
+        .and_then(check_status)?;
+
+    parse_featured_title(
+        &response
+            .text()
+            .await
+            .context("failed reading the response")?,
+    )
+}
+
+/// Formats the date of the days since the unix epoch as `YYYY/MM/DD`, the path of the feed
+fn feed_date(days: u64) -> String {
+    // the civil from days algorithm by Howard Hinnant, shifted to eras starting in March
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as u64;
+    format!("{year:04}/{month:02}/{day:02}")
+}
+
+/// Parses the title of the featured article from a feed response, which has the form
+/// `{"tfa": {"titles": {"normalized": ...}, "normalizedtitle": ...}}`
+fn parse_featured_title(response: &str) -> Result<String> {
+    let res_json: serde_json::Value =
+        serde_json::from_str(response).context("failed interpreting the response as json")?;
+
+    let article = res_json
+        .get("tfa")
+        .ok_or_else(|| anyhow!("there is no featured article today"))?;
+    article
+        .get("titles")
+        .and_then(|x| x.get("normalized"))
+        .or_else(|| article.get("normalizedtitle"))
+        .and_then(|x| x.as_str())
+        .map(|x| x.to_string())
+        .ok_or_else(|| anyhow!("missing the title of the featured article"))
+}
+
+/// Parses the title from a random list response, which has the form
+/// `{"query": {"random": [{"title": ...}]}}`
+fn parse_random_title(response: &str) -> Result<String> {
+    let res_json: serde_json::Value =
+        serde_json::from_str(response).context("failed interpreting the response as json")?;
+
+    res_json
+        .get("query")
+        .and_then(|x| x.get("random"))
+        .and_then(|x| x.get(0))
+        .and_then(|x| x.get("title"))
+        .and_then(|x| x.as_str())
+        .map(|x| x.to_string())
+        .ok_or_else(|| anyhow!("missing the title of the random page"))
+}
+
+/// A file uploaded to the site, like an image or a recording
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaFile {
+    pub title: String,
+    /// Url of the original file
+    pub url: Url,
+    /// Url of the image scaled down to the requested size, only for images
+    pub thumb_url: Option<Url>,
+    /// Mime type of the file, like `image/jpeg`
+    pub mime: String,
+}
+
+impl MediaFile {
+    pub fn is_image(&self) -> bool {
+        self.mime.starts_with("image/")
+    }
+
+    /// Returns the url of the scaled down image, or of the original file when there's none
+    pub fn download_url(&self) -> &Url {
+        self.thumb_url.as_ref().unwrap_or(&self.url)
+    }
+}
+
+/// Fetches the url of a file, using the imageinfo prop of the query API. Images are scaled down
+/// to fit into `max_size` pixels, files from a shared repository like Wikimedia Commons are found
+/// as well
+pub async fn media_file(endpoint: Endpoint, title: &str, max_size: u32) -> Result<MediaFile> {
+    let max_size = max_size.to_string();
+    let response = api_request(endpoint)
+        .query(&[
+            ("action", "query"),
+            ("format", "json"),
+            ("formatversion", "2"),
+            ("redirects", "1"),
+            ("titles", title),
+            ("prop", "imageinfo"),
+            ("iiprop", "url|mime"),
+            ("iiurlwidth", &max_size),
+            ("iiurlheight", &max_size),
+        ])
+        .send_limited()
+        .await
+        .map_err(request_failed)
+        .and_then(check_status)?;
+
+    parse_media_file(
+        &response
+            .text()
+            .await
+            .context("failed reading the response")?,
+    )
+}
+
+/// Parses the file of the first page in an imageinfo response with `formatversion=2`. Pages of
+/// files from a shared repository are `missing` but still have an imageinfo
+fn parse_media_file(response: &str) -> Result<MediaFile> {
+    let res_json: serde_json::Value =
+        serde_json::from_str(response).context("failed interpreting the response as json")?;
+
+    let page = res_json
+        .get("query")
+        .and_then(|x| x.get("pages"))
+        .and_then(|x| x.get(0))
+        .ok_or_else(|| anyhow!("missing the page in the response"))?;
+    let title = page
+        .get("title")
+        .and_then(|x| x.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    let info = match page.get("imageinfo").and_then(|x| x.get(0)) {
+        Some(info) => info,
+        None => return Err(PageNotFound(title).into()),
+    };
+    let url_field = |name: &str| {
+        info.get(name)
+            .and_then(|x| x.as_str())
+            .and_then(|x| Url::parse(x).ok())
+    };
+
+    Ok(MediaFile {
+        url: url_field("url").ok_or_else(|| anyhow!("missing the url of the file"))?,
+        thumb_url: url_field("thumburl"),
+        mime: info
+            .get("mime")
+            .and_then(|x| x.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        title,
+    })
+}
+
+/// Requests a file, like the url of a [`MediaFile`]. The body is left to the caller, so that it
+/// can be read in chunks. Files like images are shown after the page, so they're requested at the
+/// prefetch priority and let the pages the user is waiting for go first
+pub async fn request_file(url: Url) -> Result<Response> {
+    with_priority(Priority::Prefetch, client().get(url).send_limited())
+        .await
+        .map_err(request_failed)
+        .and_then(check_status)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use url::Url;
+
+    use crate::{document::HeaderKind, languages::Language};
+
+    use super::{
+        article_url, associated_title, feed_date, find_section, link_data::MediaData,
+        parse_backlinks, parse_featured_title, parse_media_file, parse_page_info,
+        parse_page_source, parse_random_title, parse_retry_after, parse_revisions,
+        parse_site_namespaces, ArticleUrl, Backlink, PageKind, PageNotFound, Protection, Revision,
+        Section, SectionKind, ServerError,
+    };
+
+    #[test]
+    fn test_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 5 "), Some(Duration::from_secs(5)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+
+        let error = |status| ServerError {
+            status,
+            retry_after: None,
+        };
+        assert!(error(429).is_transient());
+        assert!(error(503).is_transient());
+        assert!(!error(404).is_transient());
+        assert!(!error(403).is_transient());
+    }
+
+    #[test]
+    fn test_associated_title() {
+        let namespaces = parse_site_namespaces(
+            r#"{"batchcomplete": true, "query": {
+                "namespaces": {
+                    "-1": {"id": -1, "case": "first-letter", "name": "Spezial", "canonical": "Special"},
+                    "0": {"id": 0, "case": "first-letter", "name": "", "content": true},
+                    "1": {"id": 1, "case": "first-letter", "name": "Diskussion", "canonical": "Talk"},
+                    "4": {"id": 4, "case": "first-letter", "name": "Wikipedia", "canonical": "Project"},
+                    "5": {"id": 5, "case": "first-letter", "name": "Wikipedia Diskussion",
+                        "canonical": "Project talk"}
+                },
+                "namespacealiases": [{"id": 4, "alias": "WP"}]
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(namespaces.len(), 5);
+        assert_eq!(namespaces[3].aliases, ["WP"]);
+
+        let associated = |title: &str| associated_title(&namespaces, title);
+        assert_eq!(
+            associated("Rost"),
+            Some(("Diskussion:Rost".to_string(), true))
+        );
+        assert_eq!(
+            associated("Diskussion:Rost"),
+            Some(("Rost".to_string(), false))
+        );
+        // the canonical names and aliases are understood as well
+        assert_eq!(associated("Talk:Rost"), Some(("Rost".to_string(), false)));
+        assert_eq!(
+            associated("WP:Relevanzkriterien"),
+            Some(("Wikipedia Diskussion:Relevanzkriterien".to_string(), true))
+        );
+        assert_eq!(
+            associated("C++: Die Sprache"),
+            Some(("Diskussion:C++: Die Sprache".to_string(), true))
+        );
+        assert_eq!(associated("Spezial:Zufällige Seite"), None);
+    }
+
+    #[test]
+    fn test_parse_backlinks() {
+        let backlinks = parse_backlinks(
+            r#"{"continue": {"blcontinue": "0|1234", "continue": "-||"},
+                "query": {"backlinks": [
+                {"pageid": 1, "ns": 0, "title": "Iron oxide"},
+                {"pageid": 2, "ns": 0, "title": "Rusting", "redirect": true}
+                ]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            backlinks.links,
+            [
+                Backlink {
+                    title: "Iron oxide".to_string(),
+                    is_redirect: false,
+                },
+                Backlink {
+                    title: "Rusting".to_string(),
+                    is_redirect: true,
+                }
+            ]
+        );
+        assert_eq!(backlinks.continue_from.as_deref(), Some("0|1234"));
+
+        let backlinks = parse_backlinks(r#"{"query": {"backlinks": []}}"#).unwrap();
+        assert!(backlinks.links.is_empty());
+        assert_eq!(backlinks.continue_from, None);
+        assert!(parse_backlinks(r#"{"batchcomplete": true}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_revisions() {
+        let revisions = parse_revisions(
+            r#"{"continue": {"rvcontinue": "20210304120000|1000", "continue": "||"},
+                "query": {"pages": [{"pageid": 1, "ns": 0, "title": "Rust", "revisions": [
+                {"revid": 1200, "parentid": 1100, "user": "Editor", "timestamp": "2021-03-05T08:00:00Z",
+                    "size": 5120, "comment": "copyedit"},
+                {"revid": 1100, "parentid": 1000, "userhidden": true,
+                    "timestamp": "2021-03-04T12:30:00Z", "size": 5000, "commenthidden": true}
+                ]}]}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            revisions.revisions,
+            [
+                Revision {
+                    id: 1200,
+                    parent_id: 1100,
+                    timestamp: "2021-03-05T08:00:00Z".to_string(),
+                    user: "Editor".to_string(),
+                    size: 5120,
+                    comment: "copyedit".to_string(),
+                },
+                Revision {
+                    id: 1100,
+                    parent_id: 1000,
+                    timestamp: "2021-03-04T12:30:00Z".to_string(),
+                    user: String::new(),
+                    size: 5000,
+                    comment: String::new(),
+                }
+            ]
+        );
+        assert_eq!(
+            revisions.continue_from.as_deref(),
+            Some("20210304120000|1000")
+        );
+
+        let revisions = parse_revisions(
+            r#"{"batchcomplete": true, "query": {"pages": [{"title": "Rust", "revisions": []}]}}"#,
+        )
+        .unwrap();
+        assert!(revisions.revisions.is_empty());
+        assert_eq!(revisions.continue_from, None);
+    }
+
+    #[test]
+    fn test_parse_page_source() {
+        let source = parse_page_source(
+            r#"{"query": {"pages": [{"pageid": 1, "ns": 0, "title": "Rust",
+                "revisions": [{"slots": {"main": {"contentmodel": "wikitext",
+                "content": "'''Rust''' is an [[iron oxide]]."}}}]}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(source, "'''Rust''' is an [[iron oxide]].");
+
+        assert!(
+            parse_page_source(r#"{"query": {"pages": [{"title": "Rust", "missing": true}]}}"#)
+                .unwrap_err()
+                .is::<PageNotFound>()
+        );
+    }
+
+    #[test]
+    fn test_parse_page_info() {
+        let info = parse_page_info(
+            r#"{"batchcomplete": true, "query": {"pages": [{
+                "pageid": 26301, "ns": 0, "title": "Rust (fungus)", "length": 21034,
+                "protection": [{"type": "edit", "level": "autoconfirmed", "expiry": "infinity"}],
+                "watchers": 112,
+                "revisions": [{"user": "Editor", "timestamp": "2024-03-01T12:30:00Z"}],
+                "categories": [{"ns": 14, "title": "Category:Fungal plant pathogens"}]
+            }]}}"#,
+        )
+        .unwrap();
+        assert_eq!(info.title, "Rust (fungus)");
+        assert_eq!(info.length, 21034);
+        assert_eq!(info.last_edited.as_deref(), Some("2024-03-01T12:30:00Z"));
+        assert_eq!(info.last_editor.as_deref(), Some("Editor"));
+        assert_eq!(
+            info.protection,
+            [Protection {
+                kind: "edit".to_string(),
+                level: "autoconfirmed".to_string(),
+                expiry: "infinity".to_string()
+            }]
+        );
+        assert_eq!(info.watchers, Some(112));
+        assert_eq!(info.categories, ["Category:Fungal plant pathogens"]);
+
+        let error = parse_page_info(
+            r#"{"query": {"pages": [{"ns": 0, "title": "Missing", "missing": true}]}}"#,
+        )
+        .unwrap_err();
+        assert!(error.is::<PageNotFound>());
+    }
+
+    #[test]
+    fn test_parse_media_file() {
+        let file = parse_media_file(
+            r#"{"query": {"pages": [{"ns": 6, "title": "File:Rust.jpg", "missing": true,
+                "known": true, "imagerepository": "shared", "imageinfo": [{
+                "thumburl": "https://upload.wikimedia.org/thumb/a/ab/Rust.jpg/1280px-Rust.jpg",
+                "url": "https://upload.wikimedia.org/a/ab/Rust.jpg", "mime": "image/jpeg"
+            }]}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(file.title, "File:Rust.jpg");
+        assert!(file.is_image());
+        assert_eq!(
+            file.download_url().as_str(),
+            "https://upload.wikimedia.org/thumb/a/ab/Rust.jpg/1280px-Rust.jpg"
+        );
+
+        let file = parse_media_file(
+            r#"{"query": {"pages": [{"ns": 6, "title": "File:Rust.ogg", "imageinfo": [{
+                "url": "https://upload.wikimedia.org/c/cd/Rust.ogg", "mime": "audio/ogg"
+            }]}]}}"#,
+        )
+        .unwrap();
+        assert!(!file.is_image());
+        assert_eq!(
+            file.download_url().as_str(),
+            "https://upload.wikimedia.org/c/cd/Rust.ogg"
+        );
+
+        assert!(parse_media_file(
+            r#"{"query": {"pages": [{"ns": 6, "title": "File:Missing.jpg", "missing": true}]}}"#,
+        )
+        .unwrap_err()
+        .is::<PageNotFound>());
+    }
+
+    #[test]
+    fn test_media_file_title() {
+        let media = |url: &str, title: &str| MediaData {
+            url: Url::parse(url).unwrap(),
+            title: title.to_string(),
+        };
+        let url = "https://upload.wikimedia.org/wikipedia/commons/c/cd/Rust_%28sound%29.ogg";
+        assert_eq!(media(url, "Rust.ogg").file_title(), "File:Rust.ogg");
+        assert_eq!(media(url, "Media:Rust.ogg").file_title(), "File:Rust.ogg");
+        assert_eq!(media(url, "File:Rust.ogg").file_title(), "File:Rust.ogg");
+        assert_eq!(media(url, "").file_title(), "File:Rust (sound).ogg");
+    }
+
+    #[test]
+    fn test_article_url() {
+        let endpoint = Url::parse("https://en.wikipedia.org/w/api.php").unwrap();
+        assert_eq!(
+            article_url(&endpoint, "Rust (fungus)", None).as_str(),
+            "https://en.wikipedia.org/wiki/Rust_(fungus)"
+        );
+        assert_eq!(
+            article_url(&endpoint, "AC/DC", Some("Early_years")).as_str(),
+            "https://en.wikipedia.org/wiki/AC/DC#Early_years"
+        );
+        assert_eq!(
+            article_url(&endpoint, "Café au lait", Some("Café")).as_str(),
+            "https://en.wikipedia.org/wiki/Caf%C3%A9_au_lait#Caf%C3%A9"
+        );
+        assert_eq!(
+            article_url(&endpoint, "C++ & Rust?", None).as_str(),
+            "https://en.wikipedia.org/wiki/C%2B%2B_%26_Rust%3F"
+        );
+
+        // the url can be opened again
+        let url = ArticleUrl::parse(article_url(&endpoint, "Café au lait", None).as_str()).unwrap();
+        assert_eq!(url.title, "Café au lait");
+    }
+
+    #[test]
+    fn test_parse_article_url() {
+        let url = ArticleUrl::parse("https://de.wikipedia.org/wiki/Berlin#Geschichte").unwrap();
+        assert_eq!(url.title, "Berlin");
+        assert_eq!(url.language, Language::German);
+        assert_eq!(url.endpoint.as_str(), "https://de.wikipedia.org/w/api.php");
+        assert_eq!(url.anchor.as_deref(), Some("Geschichte"));
+
+        let url = ArticleUrl::parse("https://en.m.wikipedia.org/wiki/Caf%C3%A9_au_lait").unwrap();
+        assert_eq!(url.title, "Café au lait");
+        assert_eq!(url.language, Language::English);
+        assert_eq!(url.anchor, None);
+
+        let url =
+            ArticleUrl::parse("https://en.wikipedia.org/w/index.php?title=Rust_(fungus)").unwrap();
+        assert_eq!(url.title, "Rust (fungus)");
+
+        assert!(ArticleUrl::parse("https://example.org/wiki/Berlin").is_err());
+        assert!(ArticleUrl::parse("https://xx-unknown.wikipedia.org/wiki/Berlin").is_err());
+        assert!(ArticleUrl::parse("https://en.wikipedia.org/").is_err());
+        assert!(ArticleUrl::parse("Berlin").is_err());
+    }
+
+    #[test]
+    fn test_parse_random_title() {
+        let response = r#"{"batchcomplete":"","continue":{"rncontinue":"0.5|0.5|1|0","continue":"-||"},"query":{"random":[{"id":7261,"ns":0,"title":"Coral reef"}]}}"#;
+        assert_eq!(parse_random_title(response).unwrap(), "Coral reef");
+        assert!(parse_random_title(r#"{"query":{"random":[]}}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_featured_title() {
+        let response = r#"{"tfa":{"type":"standard","title":"Coral_reef","displaytitle":"<span>Coral reef</span>","titles":{"canonical":"Coral_reef","normalized":"Coral reef","display":"<span>Coral reef</span>"},"normalizedtitle":"Coral reef"},"mostread":{}}"#;
+        assert_eq!(parse_featured_title(response).unwrap(), "Coral reef");
+        assert!(parse_featured_title(r#"{"mostread":{}}"#).is_err());
+    }
+
+    #[test]
+    fn test_feed_date() {
+        assert_eq!(feed_date(0), "1970/01/01");
+        assert_eq!(feed_date(19_782), "2024/02/29");
+        assert_eq!(feed_date(20_454), "2026/01/01");
+    }
+
+    #[test]
+    fn test_page_kind_classify() {
+        let namespaces = parse_site_namespaces(
+            r#"{"batchcomplete": true, "query": {
+                "namespaces": {
+                    "-1": {"id": -1, "case": "first-letter", "name": "Spezial", "canonical": "Special"},
+                    "0": {"id": 0, "case": "first-letter", "name": "", "content": true},
+                    "1": {"id": 1, "case": "first-letter", "name": "Diskussion", "canonical": "Talk"},
+                    "2": {"id": 2, "case": "first-letter", "name": "Benutzer", "canonical": "User"},
+                    "3": {"id": 3, "case": "first-letter", "name": "Benutzer Diskussion",
+                        "canonical": "User talk"},
+                    "14": {"id": 14, "case": "first-letter", "name": "Kategorie",
+                        "canonical": "Category"}
+                },
+                "namespacealiases": [{"id": 3, "alias": "BD"}]
+            }}"#,
+        )
+        .unwrap();
+        let classify = |title: &str, is_disambiguation| {
+            PageKind::classify(&namespaces, title, is_disambiguation)
+        };
+
+        assert_eq!(classify("Bedeutung", false), PageKind::Article);
+        assert_eq!(classify("Merkur", true), PageKind::Disambiguation);
+        assert_eq!(classify("Kategorie:Physik", false), PageKind::Category);
+        assert_eq!(classify("Diskussion:Bedeutung", false), PageKind::Talk);
+        assert_eq!(classify("BD:Beispiel", false), PageKind::Talk);
+        assert_eq!(classify("Benutzer:Beispiel", false), PageKind::Article);
+        assert_eq!(
+            classify("Spezial:Zufällige Seite", false),
+            PageKind::Special
+        );
+        // the canonical names are understood as well
+        assert_eq!(classify("Category:Physik", false), PageKind::Category);
+        assert_eq!(classify("User talk:Beispiel", false), PageKind::Talk);
+
+        // titles containing a colon that isn't a namespace delimiter
+        assert_eq!(classify("Star Wars: Andor", false), PageKind::Article);
+
+        // without the namespaces of the site, only the canonical names are understood
+        assert_eq!(
+            PageKind::classify(&[], "Talk:Meaning", false),
+            PageKind::Talk
+        );
+        assert_eq!(
+            PageKind::classify(&[], "Category talk:Physics", false),
+            PageKind::Talk
+        );
+        assert_eq!(
+            PageKind::classify(&[], "Special:Random", false),
+            PageKind::Special
+        );
+        assert_eq!(
+            PageKind::classify(&[], "Diskussion:Bedeutung", false),
+            PageKind::Article
+        );
+    }
+
+    #[test]
+    fn test_find_section() {
+        let section = |index: usize, text: &str| Section {
+            index,
+            header_kind: HeaderKind::Main,
+            text: text.to_string(),
+            number: index.to_string(),
+            anchor: text.replace(' ', "_"),
+        };
+        let german = [
+            section(1, "Geschichte"),
+            section(2, "Weblinks"),
+            section(3, "Einzelnachweise"),
+        ];
+        let english = [
+            section(1, "History"),
+            section(2, "Notes"),
+            section(3, "References"),
+            section(4, "External Links "),
+        ];
+
+        let references = SectionKind::References.titles(&Language::German);
+        assert_eq!(references.first(), Some(&"Einzelnachweise"));
+        assert_eq!(find_section(&german, &references).map(|s| s.index), Some(3));
+        // the preferred title wins over the order of the sections
+        let references = SectionKind::References.titles(&Language::English);
+        assert_eq!(
+            find_section(&english, &references).map(|s| s.index),
+            Some(3)
+        );
+        // titles are compared ignoring case and surrounding whitespace
+        let links = SectionKind::ExternalLinks.titles(&Language::English);
+        assert_eq!(find_section(&english, &links).map(|s| s.index), Some(4));
+        // the english titles are tried for every language
+        assert_eq!(
+            find_section(
+                &english,
+                &SectionKind::ExternalLinks.titles(&Language::German)
+            )
+            .map(|s| s.index),
+            Some(4)
+        );
+        assert_eq!(find_section(&german, &["Literatur"]), None);
     }
 }
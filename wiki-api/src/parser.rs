@@ -8,14 +8,19 @@ use crate::{
     document::{Data, HeaderKind, Raw, UnsupportedElement},
     languages::Language,
     page::{
-        link_data::{AnchorData, ExternalData, ExternalToInteralData, InternalData, MediaData},
-        Link,
+        link_data::{
+            AnchorData, ExternalData, InternalData, InterwikiData, MediaData, RedLinkData,
+        },
+        Link, REFERENCE_ANCHOR_PREFIX,
     },
     search::Namespace,
     Endpoint,
 };
 
 // TODO: remove Parser and replace it with normal functions and helper functions
+/// The id of the text of a reference is its anchor with this prefix
+const REFERENCE_TEXT_PREFIX: &str = "mw-reference-text-";
+
 pub trait Parser {
     fn parse_document(document: &str, endpoint: Endpoint, language: Language) -> Self;
     fn nodes(self) -> Vec<Raw>;
@@ -75,7 +80,8 @@ impl WikipediaParser {
                     }
                     "figure" => {
                         ignore_children = true;
-                        Data::Unsupported(UnsupportedElement::Figure)
+                        self.parse_figure(node)
+                            .unwrap_or(Data::Unsupported(UnsupportedElement::Figure))
                     }
                     "pre" => {
                         ignore_children = true;
@@ -162,6 +168,19 @@ impl WikipediaParser {
                         Data::Reflink
                     }
 
+                    "span"
+                        if attrs.iter().any(|(name, value)| {
+                            name.as_str() == "class" && value.contains("mw-reference-text")
+                        }) =>
+                    {
+                        attrs
+                            .iter()
+                            .find(|(name, _)| name.as_str() == "id")
+                            .and_then(|(_, id)| id.strip_prefix(REFERENCE_TEXT_PREFIX))
+                            .map(|id| Data::Reference { id: id.to_string() })
+                            .unwrap_or(Data::Span)
+                    }
+
                     "section" => self.parse_section(attrs.iter()).unwrap_or_default(),
                     "h1" => self
                         .parse_header(attrs.iter(), HeaderKind::Main)
@@ -194,6 +213,14 @@ impl WikipediaParser {
                     "dd" => Data::DerscriptionListDescription,
 
                     "br" => Data::Linebreak,
+                    "hr" => Data::HorizontalRule,
+                    _ if attrs.iter().any(|(name, value)| {
+                        name.as_str() == "role" && value.as_str() == "separator"
+                    }) =>
+                    {
+                        ignore_children = true;
+                        Data::HorizontalRule
+                    }
 
                     "b" => Data::Bold,
                     "i" => Data::Italic,
@@ -303,6 +330,44 @@ impl WikipediaParser {
         })
     }
 
+    /// Returns the first image of a figure, with the size it's displayed at on the site
+    fn parse_figure(&self, node: &Handle) -> Option<Data> {
+        fn find_image(node: &Handle) -> Option<Vec<(String, String)>> {
+            if let NodeData::Element {
+                ref name,
+                ref attrs,
+                ..
+            } = node.data
+            {
+                if name.local.as_ref() == "img" {
+                    return Some(
+                        attrs
+                            .borrow()
+                            .iter()
+                            .map(|attr| (attr.name.local.to_string(), attr.value.to_string()))
+                            .collect(),
+                    );
+                }
+            }
+            node.children.borrow().iter().find_map(find_image)
+        }
+
+        let attrs = find_image(node)?;
+        let attr = |name: &str| {
+            attrs
+                .iter()
+                .find(|(attr, _)| attr.as_str() == name)
+                .map(|(_, value)| value.as_str())
+        };
+        let size = |name: &str| attr(name).and_then(|value| value.parse().ok()).unwrap_or(0);
+
+        Some(Data::Figure {
+            image: self.endpoint.join(attr("src")?).ok()?,
+            width: size("width"),
+            height: size("height"),
+        })
+    }
+
     fn parse_link(endpoint: &Url, language: Language, attrs: &[(String, String)]) -> Option<Data> {
         let href = attrs
             .iter()
@@ -316,6 +381,18 @@ impl WikipediaParser {
             .unwrap_or_default();
 
         let link_url = endpoint.join(&href).ok()?;
+
+        // reflinks point to their reference on the same page and have no `rel`
+        if let Some(anchor) = link_url
+            .fragment()
+            .filter(|fragment| fragment.starts_with(REFERENCE_ANCHOR_PREFIX))
+        {
+            return Some(Data::Link(Link::Anchor(AnchorData {
+                anchor: anchor.to_string(),
+                title,
+            })));
+        }
+
         let link_type: &str = match attrs
             .iter()
             .find(|(name, _)| name.as_str() == "rel")
@@ -324,6 +401,8 @@ impl WikipediaParser {
         {
             "mw:WikiLink" => "wiki",
             "mw:MediaLink" => "media",
+            // interwiki links like `fr:` or `wikt:` point to other wikis
+            "mw:WikiLink/Interwiki" => "interwiki",
             "mw:ExtLink" => "external",
             _ => "",
         };
@@ -336,28 +415,36 @@ impl WikipediaParser {
         if link_type == "wiki" {
             let namespace = Namespace::Main;
 
+            // links to other wikis are opened like interwiki links
             let is_same_wiki = link_url.domain() == endpoint.domain();
             if !is_same_wiki {
-                return Some(Data::Link(Link::ExternalToInternal(
-                    ExternalToInteralData {},
-                )));
+                return Some(Data::Link(Link::Interwiki(InterwikiData::new(
+                    &title, link_url, endpoint,
+                ))));
+            }
+
+            // links to pages that don't exist have the class `new`
+            let is_red_link = attrs.iter().any(|(name, value)| {
+                name.as_str() == "class" && value.split_whitespace().any(|class| class == "new")
+            }) || link_url.query_pairs().any(|(key, _)| key == "redlink");
+            if is_red_link {
+                return Some(Data::Link(Link::RedLink(RedLinkData {
+                    url: link_url,
+                    title,
+                })));
             }
 
             let page = link_url.path_segments()?.last()?;
 
             const NAMESPACE_DELIMITER: char = ':';
-            let (namespace, page) =
-                if let Some((ns_str, page_str)) = page.split_once(NAMESPACE_DELIMITER) {
-                    (
-                        Namespace::from_string(ns_str).unwrap_or_else(|| {
-                            warn!("invalid namespace '{}', using default", ns_str);
-                            namespace
-                        }),
-                        page_str,
-                    )
-                } else {
-                    (namespace, page)
-                };
+            // titles like `Mission:_Impossible` contain the delimiter without a namespace
+            let (namespace, page) = match page
+                .split_once(NAMESPACE_DELIMITER)
+                .and_then(|(ns_str, page_str)| Some((Namespace::from_string(ns_str)?, page_str)))
+            {
+                Some((namespace, page)) => (namespace, page),
+                None => (namespace, page),
+            };
 
             // we get the language from the host
             // for wikipedia, the host looks like this
@@ -394,6 +481,12 @@ impl WikipediaParser {
             })));
         }
 
+        if link_type == "interwiki" {
+            return Some(Data::Link(Link::Interwiki(InterwikiData::new(
+                &title, link_url, endpoint,
+            ))));
+        }
+
         if link_type == "external" {
             return Some(Data::Link(Link::External(ExternalData { url: link_url })));
         }
@@ -0,0 +1,287 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// Time a request waits for the requests of a higher priority to take the available token
+const YIELD_DELAY: Duration = Duration::from_millis(10);
+
+static SENT: AtomicUsize = AtomicUsize::new(0);
+static THROTTLED: AtomicUsize = AtomicUsize::new(0);
+static FAILED: AtomicUsize = AtomicUsize::new(0);
+
+/// How urgent a request is. A request waiting for the rate limiter lets the waiting requests of a
+/// higher priority go first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Requests the user is waiting for, like loading a page
+    Interactive,
+    /// Requests made ahead of time that may never be needed, like prefetched pages and search
+    /// suggestions
+    Prefetch,
+    /// Requests nobody is waiting for, like checking pages for changes
+    Background,
+}
+
+tokio::task_local! {
+    static PRIORITY: Priority;
+}
+
+/// Runs the future with its requests sent at the priority. Requests made outside of it are
+/// interactive
+pub async fn with_priority<F: Future>(priority: Priority, future: F) -> F::Output {
+    PRIORITY.scope(priority, future).await
+}
+
+pub(crate) fn current_priority() -> Priority {
+    PRIORITY
+        .try_with(|priority| *priority)
+        .unwrap_or(Priority::Interactive)
+}
+
+/// Counters of the requests sent since the start of the process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestStats {
+    pub sent: usize,
+    /// Requests that waited for the rate limiter
+    pub throttled: usize,
+    /// Requests that couldn't be sent or got an error status
+    pub failed: usize,
+}
+
+pub fn request_stats() -> RequestStats {
+    RequestStats {
+        sent: SENT.load(Ordering::Relaxed),
+        throttled: THROTTLED.load(Ordering::Relaxed),
+        failed: FAILED.load(Ordering::Relaxed),
+    }
+}
+
+pub(crate) fn count_sent(is_throttled: bool, is_failed: bool) {
+    SENT.fetch_add(1, Ordering::Relaxed);
+    if is_throttled {
+        THROTTLED.fetch_add(1, Ordering::Relaxed);
+    }
+    if is_failed {
+        FAILED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Returns the time of the clock of tokio, which can be paused in tests
+fn now() -> Instant {
+    tokio::time::Instant::now().into_std()
+}
+
+/// A bucket holding up to `burst` tokens that's refilled with `rate` tokens per second. Every
+/// request takes a token, so bursts of requests are allowed after a quiet moment. The time is
+/// passed in, so the bucket doesn't depend on the clock
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Returns a full bucket
+    pub fn new(rate: f64, burst: u32, now: Instant) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            rate,
+            burst,
+            tokens: burst,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.updated = self.updated.max(now);
+    }
+
+    /// Returns how long it takes until a token is available, zero when there is one
+    pub fn wait_time(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            return Duration::ZERO;
+        }
+        Duration::try_from_secs_f64((1.0 - self.tokens) / self.rate).unwrap_or(Duration::MAX)
+    }
+
+    /// Takes a token, or returns how long it takes until one is available
+    pub fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        let wait_time = self.wait_time(now);
+        if !wait_time.is_zero() {
+            return Err(wait_time);
+        }
+        self.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+struct LimiterState {
+    bucket: TokenBucket,
+    /// Number of requests waiting for a token, by their priority
+    waiting: [usize; 3],
+}
+
+/// Limits the rate of the requests with a [`TokenBucket`], letting the requests of a higher
+/// priority go first
+pub struct RateLimiter {
+    state: Mutex<LimiterState>,
+}
+
+/// Counts a request as waiting until it got its token or was cancelled
+struct Waiting<'a> {
+    limiter: &'a RateLimiter,
+    priority: Priority,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.limiter.state.lock().unwrap().waiting[self.priority as usize] -= 1;
+    }
+}
+
+impl RateLimiter {
+    pub fn new(rate: f64, burst: u32) -> Self {
+        Self {
+            state: Mutex::new(LimiterState {
+                bucket: TokenBucket::new(rate, burst, now()),
+                waiting: [0; 3],
+            }),
+        }
+    }
+
+    /// Waits until a request of the priority may be sent. Returns whether it had to wait
+    pub async fn acquire(&self, priority: Priority) -> bool {
+        let mut waiting: Option<Waiting> = None;
+        loop {
+            let delay = {
+                let mut state = self.state.lock().unwrap();
+                let is_preceded = state.waiting[..priority as usize]
+                    .iter()
+                    .any(|waiting| *waiting > 0);
+                let now = now();
+                match is_preceded {
+                    true => state.bucket.wait_time(now).max(YIELD_DELAY),
+                    false => match state.bucket.try_take(now) {
+                        Ok(()) => {
+                            drop(state);
+                            return waiting.is_some();
+                        }
+                        Err(wait_time) => wait_time,
+                    },
+                }
+            };
+            if waiting.is_none() {
+                self.state.lock().unwrap().waiting[priority as usize] += 1;
+                waiting = Some(Waiting {
+                    limiter: self,
+                    priority,
+                });
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Mutex,
+        time::{Duration, Instant},
+    };
+
+    use super::{Priority, RateLimiter, TokenBucket};
+
+    #[test]
+    fn test_token_bucket() {
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+        let mut bucket = TokenBucket::new(5.0, 3, start);
+
+        // the full bucket allows a burst
+        for _ in 0..3 {
+            assert_eq!(bucket.try_take(start), Ok(()));
+        }
+        assert_eq!(bucket.try_take(start), Err(Duration::from_millis(200)));
+
+        // a token is refilled every 200ms
+        assert_eq!(bucket.try_take(at(150)), Err(Duration::from_millis(50)));
+        assert_eq!(bucket.try_take(at(200)), Ok(()));
+        assert_eq!(bucket.wait_time(at(300)), Duration::from_millis(100));
+
+        // the bucket doesn't fill beyond the burst
+        for _ in 0..3 {
+            assert_eq!(bucket.try_take(at(10_000)), Ok(()));
+        }
+        assert!(bucket.try_take(at(10_000)).is_err());
+
+        // going back in time doesn't add or remove tokens
+        assert_eq!(bucket.wait_time(at(5_000)), Duration::from_millis(200));
+
+        let mut slow = TokenBucket::new(0.5, 0, start);
+        assert_eq!(slow.try_take(start), Ok(()));
+        assert_eq!(slow.try_take(at(1_000)), Err(Duration::from_secs(1)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_priorities() {
+        let limiter = RateLimiter::new(1.0, 1);
+        assert!(!limiter.acquire(Priority::Interactive).await);
+
+        // the interactive request starts waiting after the prefetch, but gets the next token
+        let order = Mutex::new(Vec::new());
+        let acquire = |priority: Priority, delay: u64| {
+            let (limiter, order) = (&limiter, &order);
+            async move {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                assert!(limiter.acquire(priority).await);
+                order.lock().unwrap().push(priority);
+            }
+        };
+        tokio::join!(
+            acquire(Priority::Prefetch, 0),
+            acquire(Priority::Interactive, 100)
+        );
+        assert_eq!(
+            order.into_inner().unwrap(),
+            [Priority::Interactive, Priority::Prefetch]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancelled_request() {
+        let limiter = RateLimiter::new(1.0, 1);
+        assert!(!limiter.acquire(Priority::Interactive).await);
+        let waiting = || limiter.state.lock().unwrap().waiting;
+
+        // the request counts as waiting until it's dropped
+        let (result, during) = tokio::join!(
+            tokio::time::timeout(
+                Duration::from_millis(500),
+                limiter.acquire(Priority::Prefetch)
+            ),
+            async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                waiting()
+            }
+        );
+        assert!(result.is_err());
+        assert_eq!(during, [0, 1, 0]);
+        assert_eq!(waiting(), [0; 3]);
+
+        // so it doesn't hold back the requests of a lower priority
+        let background = limiter.acquire(Priority::Background);
+        assert!(tokio::time::timeout(Duration::from_secs(5), background)
+            .await
+            .unwrap());
+    }
+}
@@ -2,15 +2,16 @@ use anyhow::{anyhow, Context, Result};
 
 use bitflags::bitflags;
 use core::fmt;
-use reqwest::{Client, Response};
-use scraper::Html;
+use reqwest::Response;
+use scraper::{ElementRef, Html};
 use serde::Deserialize;
 use serde_repr::Deserialize_repr;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Write;
 
-use crate::Endpoint;
+use crate::page::{check_status, request_failed};
+use crate::{api_request, Endpoint, SendLimited};
 
 use crate::languages::Language;
 
@@ -93,15 +94,21 @@ pub struct SearchInfo {
 ///
 /// # Example
 ///
-/// ```
+/// ```no_run
+/// # use wiki_api::search::Search;
+/// # async fn continue_search(search: Search) -> anyhow::Result<()> {
 /// // This will continue the already completed search
-/// let continue_data = search.continue_data()?;
-/// let continued_search = Search::builder()
-///     .query(continue_data.query)
-///     .endpoint(continue_data.endpoint)
-///     .langauge(continue_data.language)
-///     .offset(continue_data.offset)
-///     .search()?;
+/// if let Some(continue_data) = search.continue_data() {
+///     let continued_search = Search::builder()
+///         .query(continue_data.query)
+///         .endpoint(continue_data.endpoint)
+///         .language(continue_data.language)
+///         .offset(continue_data.offset)
+///         .search()
+///         .await?;
+/// }
+/// # Ok(())
+/// # }
 /// ```
 ///
 /// [`Search::continue_data`]: Search::continue_data
@@ -155,6 +162,53 @@ impl SearchResult {
             })
             .unwrap_or_default()
     }
+
+    /// Returns the snippet split into the parts matching the query and the ones in between, with
+    /// the html entities decoded. Empty when the result has no snippet
+    pub fn snippet_fragments(&self) -> Vec<SnippetFragment> {
+        self.snippet
+            .as_deref()
+            .map(parse_snippet)
+            .unwrap_or_default()
+    }
+}
+
+/// A part of the snippet of a search result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetFragment {
+    pub text: String,
+    /// Whether the text matches the query
+    pub is_match: bool,
+}
+
+/// Parses the snippet html, where the api wraps the matches in `<span class="searchmatch">`
+fn parse_snippet(snippet: &str) -> Vec<SnippetFragment> {
+    let html = Html::parse_fragment(snippet);
+    let mut fragments: Vec<SnippetFragment> = Vec::new();
+    for node in html.root_element().descendants() {
+        let text = match node.value().as_text() {
+            Some(text) => text,
+            None => continue,
+        };
+        let is_match = node
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|element| {
+                element
+                    .value()
+                    .classes()
+                    .any(|class| class == "searchmatch")
+            });
+
+        match fragments.last_mut() {
+            Some(last) if last.is_match == is_match => last.text.push_str(text),
+            _ => fragments.push(SnippetFragment {
+                text: text.to_string(),
+                is_match,
+            }),
+        }
+    }
+    fragments
 }
 
 /// The 16 built-in namespaces (excluding two "virtual" namespaces) of MediaWiki
@@ -168,7 +222,7 @@ impl SearchResult {
 /// They each have a unique number (0 to 15) and are grouped in subject/talk pairs
 ///
 /// Read more in the [MediaWiki API docs](https://www.mediawiki.org/wiki/Manual:Namespace)
-#[derive(Deserialize_repr, Debug, Clone, PartialEq, Eq)]
+#[derive(Deserialize_repr, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(usize)]
 pub enum Namespace {
     Main = 0,
@@ -213,10 +267,30 @@ impl Display for Namespace {
 }
 
 impl Namespace {
+    /// All namespaces, ordered by their number
+    pub const ALL: [Namespace; 16] = [
+        Namespace::Main,
+        Namespace::MainTalk,
+        Namespace::User,
+        Namespace::UserTalk,
+        Namespace::Project,
+        Namespace::ProjectTalk,
+        Namespace::File,
+        Namespace::FileTalk,
+        Namespace::MediaWiki,
+        Namespace::MediaWikiTalk,
+        Namespace::Template,
+        Namespace::TemplateTalk,
+        Namespace::Help,
+        Namespace::HelpTalk,
+        Namespace::Category,
+        Namespace::CategoryTalk,
+    ];
+
     pub fn from_string(namespace: &str) -> Option<Namespace> {
         match namespace.to_lowercase().as_str() {
             "main" => Some(Namespace::Main),
-            "main_talk" => Some(Namespace::MainTalk),
+            "main_talk" | "talk" => Some(Namespace::MainTalk),
             "user" => Some(Namespace::User),
             "user_talk" => Some(Namespace::UserTalk),
             "project" => Some(Namespace::Project),
@@ -234,11 +308,50 @@ impl Namespace {
             _ => None,
         }
     }
+
+    /// Whether the namespace is the talk namespace of a subject namespace
+    pub fn is_talk(&self) -> bool {
+        (*self as usize) % 2 == 1
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Namespace;
+    use super::{parse_snippet, parse_suggestions, Namespace, SnippetFragment};
+
+    #[test]
+    fn test_parse_snippet() {
+        let fragment = |text: &str, is_match| SnippetFragment {
+            text: text.to_string(),
+            is_match,
+        };
+
+        assert_eq!(
+            parse_snippet(
+                r#"<span class="searchmatch">Rust</span> is a &quot;safe&quot; language &amp; <span class="searchmatch">ラスト</span>"#
+            ),
+            [
+                fragment("Rust", true),
+                fragment(r#" is a "safe" language & "#, false),
+                fragment("ラスト", true),
+            ]
+        );
+        assert!(parse_snippet("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_suggestions() {
+        let response = r#"["rus",["Russia","Rust (programming language)"],["",""],
+            ["https://en.wikipedia.org/wiki/Russia","https://en.wikipedia.org/wiki/Rust_(programming_language)"]]"#;
+        assert_eq!(
+            parse_suggestions(response).unwrap(),
+            ["Russia", "Rust (programming language)"]
+        );
+
+        assert!(parse_suggestions(r#"["rus",[],[],[]]"#).unwrap().is_empty());
+        assert!(parse_suggestions(r#"{"error":{"code":"badvalue"}}"#).is_err());
+    }
+
     #[test]
     fn test_namespace_display_and_str() {
         macro_rules! test_namespace {
@@ -386,6 +499,7 @@ impl fmt::Display for Info {
 }
 
 /// A Page property
+#[derive(Clone)]
 pub enum Property {
     /// The size of the page in bytes
     Size,
@@ -476,36 +590,39 @@ impl Display for SortOrder {
 }
 
 #[doc(hidden)]
+#[derive(Clone)]
 pub struct WithQuery(String);
 
 #[doc(hidden)]
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct NoQuery;
 
 #[doc(hidden)]
+#[derive(Clone)]
 pub struct WithEndpoint(Endpoint);
 
 #[doc(hidden)]
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct NoEndpoint;
 
 #[doc(hidden)]
+#[derive(Clone)]
 pub struct WithLanguage(Language);
 
 #[doc(hidden)]
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct NoLanguage;
 
 /// A fully configured `SearchBuilder` that can be used to execute the search. This is a convenience type
 pub type SearchRequest = SearchBuilder<WithQuery, WithEndpoint, WithLanguage>;
 
 /// A `SearchBuilder` can be used to configure and perform a search
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct SearchBuilder<Q, E, L> {
     query: Q,
     endpoint: E,
     language: L,
-    namespace: Option<Namespace>,
+    namespaces: Option<Vec<Namespace>>,
     limit: Option<usize>,
     offset: Option<usize>,
     qiprofile: Option<QiProfile>,
@@ -524,7 +641,7 @@ impl<E, L> SearchBuilder<NoQuery, E, L> {
             query: WithQuery(query.into()),
             endpoint: self.endpoint,
             language: self.language,
-            namespace: self.namespace,
+            namespaces: self.namespaces,
             limit: self.limit,
             offset: self.offset,
             qiprofile: self.qiprofile,
@@ -545,7 +662,7 @@ impl<Q, L> SearchBuilder<Q, NoEndpoint, L> {
             query: self.query,
             endpoint: WithEndpoint(endpoint),
             language: self.language,
-            namespace: self.namespace,
+            namespaces: self.namespaces,
             limit: self.limit,
             offset: self.offset,
             qiprofile: self.qiprofile,
@@ -566,7 +683,7 @@ impl<Q, E> SearchBuilder<Q, E, NoLanguage> {
             query: self.query,
             endpoint: self.endpoint,
             language: WithLanguage(language),
-            namespace: self.namespace,
+            namespaces: self.namespaces,
             limit: self.limit,
             offset: self.offset,
             qiprofile: self.qiprofile,
@@ -583,7 +700,17 @@ impl<Q, E> SearchBuilder<Q, E, NoLanguage> {
 impl<Q, E, L> SearchBuilder<Q, E, L> {
     /// Search only in this specific namespace
     pub fn namespace(mut self, namespace: Namespace) -> Self {
-        self.namespace = Some(namespace);
+        self.namespaces = Some(vec![namespace]);
+        self
+    }
+
+    /// Search only in these namespaces
+    ///
+    /// Default: [`Namespace::Main`]
+    ///
+    /// [`Namespace::Main`]: Namespace::Main
+    pub fn namespaces(mut self, namespaces: Vec<Namespace>) -> Self {
+        self.namespaces = Some(namespaces);
         self
     }
 
@@ -682,13 +809,18 @@ impl SearchBuilder<WithQuery, WithEndpoint, WithLanguage> {
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
+    /// # use wiki_api::{languages::Language, search::Search, Endpoint};
+    /// # async fn search() -> anyhow::Result<()> {
     /// // This searches for the pages containing 'meaning' in the english wikipedia
     /// let search = Search::builder()
     ///     .query("meaning")
-    ///     .endpoint(Url::from("https://en.wikipedia.org/w/api.php")?)
+    ///     .endpoint(Endpoint::parse("https://en.wikipedia.org/w/api.php")?)
     ///     .language(Language::English)
-    ///     .search()?;
+    ///     .search()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
     /// ```
     ///
     /// # Error
@@ -699,17 +831,16 @@ impl SearchBuilder<WithQuery, WithEndpoint, WithLanguage> {
     /// - The returned result could not interpreted as a `Search`
     pub async fn search(self) -> Result<Search> {
         async fn action_query(params: Vec<(&str, String)>, endpoint: Endpoint) -> Result<Response> {
-            Client::new()
-                .get(endpoint)
+            api_request(endpoint)
                 .query(&[
                     ("action", "query"),
                     ("format", "json"),
                     ("formatversion", "2"),
                 ])
                 .query(&params)
-                .send()
+                .send_limited()
                 .await
-                .context("failed sending the request")
+                .map_err(request_failed)
         }
 
         let mut params = vec![
@@ -717,8 +848,13 @@ impl SearchBuilder<WithQuery, WithEndpoint, WithLanguage> {
             ("srsearch", self.query.0.clone()),
         ];
 
-        if let Some(namespace) = self.namespace {
-            params.push(("srnamespace", namespace.to_string()));
+        if let Some(namespaces) = self.namespaces {
+            let namespaces = namespaces
+                .into_iter()
+                .map(|namespace| (namespace as usize).to_string())
+                .collect::<Vec<String>>()
+                .join("|");
+            params.push(("srnamespace", namespaces));
         }
 
         if let Some(limit) = self.limit {
@@ -763,9 +899,8 @@ impl SearchBuilder<WithQuery, WithEndpoint, WithLanguage> {
         }
 
         let response = action_query(params, self.endpoint.0.clone())
-            .await?
-            .error_for_status()
-            .context("the server returned an error")?;
+            .await
+            .and_then(check_status)?;
 
         let res_json: serde_json::Value = serde_json::from_str(
             &response
@@ -849,3 +984,68 @@ impl SearchBuilder<WithQuery, WithEndpoint, WithLanguage> {
         })
     }
 }
+
+/// A page suggested for an incomplete search query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// Title of the page
+    pub title: String,
+    /// API endpoint of the MediaWiki site this page belongs to
+    pub endpoint: Endpoint,
+    /// Language, the page is written in
+    pub language: Language,
+}
+
+/// Fetches up to `limit` pages whose title starts with the prefix, using the opensearch API
+pub async fn suggestions(
+    prefix: &str,
+    endpoint: Endpoint,
+    language: Language,
+    limit: usize,
+) -> Result<Vec<Suggestion>> {
+    let response = api_request(endpoint.clone())
+        .query(&[
+            ("action", "opensearch"),
+            ("format", "json"),
+            ("namespace", "0"),
+            ("redirects", "resolve"),
+        ])
+        .query(&[("search", prefix), ("limit", &limit.to_string())])
+        .send_limited()
+        .await
+        .map_err(request_failed)
+        .and_then(check_status)?;
+
+    let titles = parse_suggestions(
+        &response
+            .text()
+            .await
+            .context("failed reading the response")?,
+    )?;
+
+    Ok(titles
+        .into_iter()
+        .map(|title| Suggestion {
+            title,
+            endpoint: endpoint.clone(),
+            language,
+        })
+        .collect())
+}
+
+/// Parses the titles from an opensearch response, which has the form
+/// `[query, [titles], [descriptions], [urls]]`
+fn parse_suggestions(response: &str) -> Result<Vec<String>> {
+    let res_json: serde_json::Value =
+        serde_json::from_str(response).context("failed interpreting the response as json")?;
+
+    let titles = res_json
+        .get(1)
+        .and_then(|x| x.as_array())
+        .ok_or_else(|| anyhow!("missing the suggested titles"))?;
+
+    Ok(titles
+        .iter()
+        .filter_map(|x| x.as_str().map(|x| x.to_string()))
+        .collect())
+}